@@ -14,7 +14,7 @@ export function connectRustServer() {
 
     function connect() {
 
-        socket = new WebSocket("ws://localhost:9001");
+        socket = new WebSocket("ws://localhost:9001/?protocol=1");
 
         socket.onopen = () => {
             console.log("Connected to Rust physics server");
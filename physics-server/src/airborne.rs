@@ -0,0 +1,146 @@
+// ==============================================================================
+// airborne.rs — AIRBORNE DETECTION, MID-AIR CONTROL + LANDING SETTLE
+// ------------------------------------------------------------------------------
+// A car with no wheel on the ground gets no tire forces, which is right, but
+// it also had no way to adjust its attitude, and the ground-tuned angular
+// damping kept eating its rotation. Per vehicle (Vehicle::air):
+//
+//   airborne  : no wheel compressed / embedded for more than AIRBORNE_AFTER_SECS
+//               (one-tick skips over crests don't count)
+//   in the air: body angular damping drops to AIR_ANGULAR_DAMPING_SCALE of
+//               config.angular_damping, the low-speed brake lock stays off, and
+//               steer / pitch drive an air-control torque (body yaw / pitch axes)
+//               limited to config.air_control_torque N·m in total
+//   landing   : the first wheel down ends the flight and starts a settle window
+//               of LANDING_SETTLE_SECS: LANDING_DAMPING_SCALE x damper rate, and
+//               the damper may cancel up to LANDING_DAMPER_LIMIT of the spring
+//               (normally 0.6). That catches the rebound that otherwise throws
+//               the car back off the ground after a hard landing
+//
+// Snapshots carry "airborne" for client audio / FX.
+// ==============================================================================
+
+use rapier3d::prelude::*;
+
+const AIRBORNE_AFTER_SECS: f32 = 0.05;       // ~3 ticks at 60 Hz
+pub const AIR_ANGULAR_DAMPING_SCALE: f32 = 0.1;
+const LANDING_SETTLE_SECS: f32 = 0.25;
+const LANDING_DAMPING_SCALE: f32 = 1.5;
+const LANDING_DAMPER_LIMIT: f32 = 0.95;      // damper force cap while settling, fraction of spring force
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AirTransition {
+    TookOff,
+    Landed { air_time: f32 },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AirState {
+    pub airborne: bool,
+    pub air_time: f32,   // seconds since the last wheel left the ground
+    settle_left: f32,    // seconds of landing settle remaining
+}
+
+impl AirState {
+    /// Feed one step: `wheels_down` = wheels compressed or embedded this step.
+    pub fn update(&mut self, wheels_down: usize, dt: f32) -> Option<AirTransition> {
+        self.settle_left = (self.settle_left - dt).max(0.0);
+
+        if wheels_down > 0 {
+            let landed = self.airborne.then_some(AirTransition::Landed { air_time: self.air_time });
+            if landed.is_some() {
+                self.settle_left = LANDING_SETTLE_SECS;
+            }
+            self.airborne = false;
+            self.air_time = 0.0;
+            return landed;
+        }
+
+        self.air_time += dt;
+        if !self.airborne && self.air_time > AIRBORNE_AFTER_SECS {
+            self.airborne = true;
+            return Some(AirTransition::TookOff);
+        }
+        None
+    }
+
+    /// Damper rate multiplier (above 1 while settling after a landing).
+    pub fn damping_scale(&self) -> f32 {
+        if self.settle_left > 0.0 { LANDING_DAMPING_SCALE } else { 1.0 }
+    }
+
+    /// Damper force cap as a fraction of spring force (`base` unless settling).
+    pub fn damper_limit(&self, base: f32) -> f32 {
+        if self.settle_left > 0.0 { base.max(LANDING_DAMPER_LIMIT) } else { base }
+    }
+}
+
+/// World-space air-control torque (N·m): steer yaws right, pitch > 0 lifts the
+/// nose; the total is capped at `max_torque`.
+pub fn air_control_torque(rotation: &Rotation<Real>, steer: f32, pitch: f32, max_torque: f32) -> Vector<Real> {
+    // Body frame: +X right, +Y up, +Z forward (nose up = negative rotation about +X)
+    let local = vector![-pitch.clamp(-1.0, 1.0), steer.clamp(-1.0, 1.0), 0.0] * max_torque;
+    let local = if local.norm() > max_torque { local.normalize() * max_torque } else { local };
+    rotation * local
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn a_crest_skip_is_not_a_flight() {
+        let mut air = AirState::default();
+        assert_eq!(air.update(0, DT), None);
+        assert_eq!(air.update(0, DT), None);
+        assert_eq!(air.update(2, DT), None, "two ticks up: never airborne, no landing");
+        assert_eq!(air.damping_scale(), 1.0);
+
+        let events: Vec<_> = (0..4).filter_map(|_| air.update(0, DT)).collect();
+        assert_eq!(events, [AirTransition::TookOff]);
+        assert!(air.airborne);
+    }
+
+    #[test]
+    fn landing_settles_for_a_window_then_lets_go() {
+        let mut air = AirState::default();
+        for _ in 0..30 {
+            air.update(0, DT);
+        }
+        let Some(AirTransition::Landed { air_time }) = air.update(4, DT) else { panic!("no landing") };
+        assert!((air_time - 0.5).abs() < 1e-4);
+        assert_eq!(air.damping_scale(), LANDING_DAMPING_SCALE);
+        assert_eq!(air.damper_limit(0.6), LANDING_DAMPER_LIMIT);
+
+        let settle_ticks = (LANDING_SETTLE_SECS / DT).round() as usize;
+        for _ in 0..settle_ticks - 2 {
+            air.update(4, DT);
+        }
+        assert_eq!(air.damping_scale(), LANDING_DAMPING_SCALE, "still settling");
+        for _ in 0..3 {
+            air.update(4, DT);
+        }
+        assert_eq!(air.damping_scale(), 1.0);
+        assert_eq!(air.damper_limit(0.6), 0.6);
+    }
+
+    #[test]
+    fn air_control_stays_within_its_budget() {
+        let level = Rotation::identity();
+        let yaw = air_control_torque(&level, 1.0, 0.0, 1500.0);
+        assert_eq!(yaw, vector![0.0, 1500.0, 0.0], "steer right yaws about +Y");
+        let nose_up = air_control_torque(&level, 0.0, 1.0, 1500.0);
+        assert_eq!(nose_up, vector![-1500.0, 0.0, 0.0]);
+
+        // Both axes at once and out-of-range input share the one cap
+        let both = air_control_torque(&level, 4.0, -4.0, 1500.0);
+        assert!((both.norm() - 1500.0).abs() < 1e-2);
+
+        // Torque follows the body: upside down, the same steer yaws about -Y
+        let flipped = Rotation::from_axis_angle(&Vector::z_axis(), std::f32::consts::PI);
+        assert!((air_control_torque(&flipped, 1.0, 0.0, 1500.0).y + 1500.0).abs() < 1e-2);
+        assert_eq!(air_control_torque(&level, 1.0, 1.0, 0.0), Vector::zeros());
+    }
+}
@@ -5,10 +5,13 @@
 // left/right wheels on the same axle based on suspension compression difference.
 //
 // apply_arb_load_transfer(left, right, ...):
-// - Reads compression(left/right)
+// - Reads compression(left/right); both wheels must be loaded (compression > 0):
+//   a hanging wheel has nothing to push against, so the bar does nothing
 // - Computes delta = cl - cr
 // - Computes a transfer amount proportional to delta (arb_stiffness * delta)
-// - Clamps transfer to avoid negative loads / exceeding reference load
+//   toward the MORE compressed wheel (the bar resists roll)
+// - Clamps transfer to the reference-load saturation and to what the lighter
+//   wheel carries, so the axle total is conserved (no load created or lost)
 // - Updates axle_normal_force map for left/right
 //
 // Output of this module is consumed by physics.rs Phase 3:
@@ -21,6 +24,7 @@
 use rapier3d::prelude::*;
 use std::collections::HashMap;
 use crate::aven_tire::WheelId;
+use crate::aven_tire::types::MAX_AXLES;
 
 /// One anti-roll bar axle pair (FL/FR or RL/RR)
 #[derive(Clone)]
@@ -37,6 +41,29 @@ pub struct AntiRollPair {
     pub stiffness: f32, // N/m
 }
 
+/// Left / right ARB pair of every axle from (axle, body-local x offset, id)
+/// per wheel: the outermost wheel on each side of the centerline (x < 0 is
+/// left, as FL / RL). Axles without a wheel on both sides get no bar.
+pub fn axle_arb_pairs(
+    wheels: impl IntoIterator<Item = (usize, f32, WheelId)>,
+) -> [Option<(WheelId, WheelId)>; MAX_AXLES] {
+    let mut left: [Option<(f32, WheelId)>; MAX_AXLES] = [None; MAX_AXLES];
+    let mut right: [Option<(f32, WheelId)>; MAX_AXLES] = [None; MAX_AXLES];
+    for (axle, x, id) in wheels {
+        if axle >= MAX_AXLES || x == 0.0 {
+            continue;
+        }
+        let (side, outer) = if x < 0.0 { (&mut left[axle], -x) } else { (&mut right[axle], x) };
+        if side.is_none_or(|(best, _)| outer > best) {
+            *side = Some((outer, id));
+        }
+    }
+    std::array::from_fn(|axle| match (left[axle], right[axle]) {
+        (Some((_, l)), Some((_, r))) => Some((l, r)),
+        _ => None,
+    })
+}
+
 pub fn apply_arb_load_transfer(
     left: WheelId,
     right: WheelId,
@@ -50,6 +77,11 @@ pub fn apply_arb_load_transfer(
         axle_compression.get(&right),
     ) else { return };
 
+    // One wheel off the ground (drooped ray hit): no bar load to transfer
+    if *cl <= 0.0 || *cr <= 0.0 {
+        return;
+    }
+
     let delta = cl - cr;
 
     if delta.abs() < 1e-4 {
@@ -74,8 +106,77 @@ pub fn apply_arb_load_transfer(
     // let max_transfer = 0.6 * (nl + nr);
     let max_transfer = 0.4 * fz_ref;
     let transfer = transfer.clamp(-max_transfer, max_transfer);
+    // ...and never more than the unloading wheel carries (axle total conserved)
+    let transfer = transfer.clamp(-nl, nr);
+
+    // redistribute: the compressed (outside) wheel gains load
+    axle_normal_force.insert(left,  nl + transfer);
+    axle_normal_force.insert(right, nr - transfer);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FZ_REF: f32 = 20_000.0;
 
-    // redistribute
-    axle_normal_force.insert(left,  (nl - transfer).max(0.0));
-    axle_normal_force.insert(right, (nr + transfer).max(0.0));
+    #[test]
+    fn every_axle_gets_its_own_pair_from_the_outermost_wheels() {
+        // Six-wheel truck with a dually rear axle, inner duals at ±0.6 (ids are
+        // the four corners; only the pairing by offset is checked here)
+        let truck = [
+            (0, -1.0, WheelId::FL), (0, 1.0, WheelId::FR),
+            (1, -1.0, WheelId::RL), (1, 1.0, WheelId::RR),
+            (2, -0.6, WheelId::FL), (2, -1.1, WheelId::RL), (2, 1.1, WheelId::RR), (2, 0.6, WheelId::FR),
+        ];
+        let pairs = axle_arb_pairs(truck);
+        assert_eq!(pairs[0], Some((WheelId::FL, WheelId::FR)));
+        assert_eq!(pairs[1], Some((WheelId::RL, WheelId::RR)));
+        assert_eq!(pairs[2], Some((WheelId::RL, WheelId::RR)), "outermost of the duals");
+        assert_eq!(pairs[3], None);
+
+        // One-sided and centered wheels pair with nothing
+        let pairs = axle_arb_pairs([(0, 0.0, WheelId::FL), (1, -1.0, WheelId::RL)]);
+        assert_eq!(pairs, [None; MAX_AXLES]);
+    }
+
+    #[test]
+    fn each_axle_transfers_with_its_own_rate_and_keeps_its_total() {
+        let compression = HashMap::from([(WheelId::FL, 0.12), (WheelId::FR, 0.08), (WheelId::RL, 0.12), (WheelId::RR, 0.08)]);
+        let mut normal = HashMap::from([(WheelId::FL, FZ_REF), (WheelId::FR, FZ_REF), (WheelId::RL, FZ_REF), (WheelId::RR, FZ_REF)]);
+        let rates = [18_000.0, 0.0];
+        for ((left, right), rate) in [(WheelId::FL, WheelId::FR), (WheelId::RL, WheelId::RR)].into_iter().zip(rates) {
+            apply_arb_load_transfer(left, right, &mut normal, &compression, rate, FZ_REF);
+        }
+
+        // Front bar: 18 kN/m x 0.04 m toward the compressed left wheel
+        assert!((normal[&WheelId::FL] - (FZ_REF + 720.0)).abs() < 1e-2, "{:?}", normal);
+        assert!((normal[&WheelId::FL] + normal[&WheelId::FR] - 2.0 * FZ_REF).abs() < 1e-2);
+        // No rear bar: the rear axle is left alone
+        assert_eq!((normal[&WheelId::RL], normal[&WheelId::RR]), (FZ_REF, FZ_REF));
+    }
+
+    #[test]
+    fn a_hanging_wheel_gets_no_phantom_load() {
+        let compression = HashMap::from([(WheelId::FL, 0.15), (WheelId::FR, 0.0)]);
+        let mut normal = HashMap::from([(WheelId::FL, 2.0 * FZ_REF), (WheelId::FR, 0.0)]);
+        apply_arb_load_transfer(WheelId::FL, WheelId::FR, &mut normal, &compression, 18_000.0, FZ_REF);
+        assert_eq!((normal[&WheelId::FL], normal[&WheelId::FR]), (2.0 * FZ_REF, 0.0));
+    }
+
+    #[test]
+    fn transfer_saturates_and_never_unloads_below_zero() {
+        // A huge bar is held to 0.4 x the reference load
+        let compression = HashMap::from([(WheelId::FL, 0.05), (WheelId::FR, 0.25)]);
+        let mut normal = HashMap::from([(WheelId::FL, FZ_REF), (WheelId::FR, FZ_REF)]);
+        apply_arb_load_transfer(WheelId::FL, WheelId::FR, &mut normal, &compression, 1.0e7, FZ_REF);
+        assert!((normal[&WheelId::FR] - 1.4 * FZ_REF).abs() < 1e-2, "{:?}", normal);
+        assert!((normal[&WheelId::FL] - 0.6 * FZ_REF).abs() < 1e-2, "{:?}", normal);
+
+        // ...and to what the unloading wheel carries
+        let mut light = HashMap::from([(WheelId::FL, 0.1 * FZ_REF), (WheelId::FR, 1.5 * FZ_REF)]);
+        apply_arb_load_transfer(WheelId::FL, WheelId::FR, &mut light, &compression, 1.0e7, FZ_REF);
+        assert_eq!(light[&WheelId::FL], 0.0);
+        assert!((light[&WheelId::FR] - 1.6 * FZ_REF).abs() < 1e-2, "{:?}", light);
+    }
 }
@@ -94,7 +94,7 @@
         let compression_ratio = patch.compression_ratio.clamp(0.0, 1.0);
         let suspension_factor = 1.0 - compression_ratio * cfg.suspension_falloff;
         let speed = (patch.v_long * patch.v_long + v_lat * v_lat).sqrt();
-        let mass = (ctx.mass * 0.25).max(1.0);
+        let mass = (ctx.mass * 0.25 * ctx.wheels_per_contact).max(1.0);
 
         // 5) Same desired impulse  
         let mut lateral_impulse =
@@ -0,0 +1,94 @@
+// ==============================================================================
+// combined_slip.rs — KAMM CIRCLE / FRICTION ELLIPSE (IMPULSE DOMAIN)
+// ------------------------------------------------------------------------------
+// Combines longitudinal and lateral tire impulses under a shared friction
+// budget. Longitudinal and lateral capacities get separate stiffness
+// coefficients, turning the Kamm circle into an ellipse:
+//
+//     nx = |J_long · forward_xz| / (cx * J_long_max)
+//     ny = |J_lat|  / (cy * J_lat_max)
+//     k  = sqrt(nx² + ny²)
+//     if k > 1: scale both impulses by 1/k
+//
+// The longitudinal demand is measured along the wheel's planar forward
+// (forward_xz): on a slope J_long tilts with the contact, and its vertical part
+// is not tire grip. cx = cy = 1.0 reproduces the plain circular Coulomb limit.
+// ==============================================================================
+
+use crate::aven_tire::types::{Vec3, v_dot, v_mag, v_scale};
+
+/// Friction ellipse stiffness (dimensionless capacity multipliers)
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KammCircle {
+    pub cx: f32, // longitudinal
+    pub cy: f32, // lateral
+}
+
+impl Default for KammCircle {
+    fn default() -> Self {
+        Self { cx: 1.0, cy: 1.0 }
+    }
+}
+
+/// Scale (long, lat) impulses so their combined demand stays inside the ellipse.
+pub fn apply_kamm_scaling(
+    long: Vec3,
+    lat: Vec3,
+    forward_xz: Vec3,
+    max_long: f32,
+    max_lat: f32,
+    kamm: &KammCircle,
+) -> (Vec3, Vec3) {
+    let cap_x = (kamm.cx * max_long).max(1e-6);
+    let cap_y = (kamm.cy * max_lat).max(1e-6);
+
+    let nx = v_dot(long, forward_xz).abs() / cap_x;
+    let ny = v_mag(lat) / cap_y;
+
+    let k = (nx * nx + ny * ny).sqrt();
+    let scale = if k > 1.0 { 1.0 / k } else { 1.0 };
+
+    (v_scale(long, scale), v_scale(lat, scale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CIRCLE: KammCircle = KammCircle { cx: 1.0, cy: 1.0 };
+    const FLAT_FORWARD: Vec3 = [0.0, 0.0, 1.0];
+
+    fn close(a: Vec3, b: Vec3) -> bool {
+        (0..3).all(|i| (a[i] - b[i]).abs() < 1e-4)
+    }
+
+    #[test]
+    fn demand_inside_the_circle_is_untouched_and_outside_is_scaled_back() {
+        let (long, lat) = apply_kamm_scaling([0.0, 0.0, 3.0], [4.0, 0.0, 0.0], FLAT_FORWARD, 5.0, 5.0, &CIRCLE);
+        assert!(close(long, [0.0, 0.0, 3.0]) && close(lat, [4.0, 0.0, 0.0]));
+
+        // k = sqrt(1.2² + 1.6²) = 2
+        let (long, lat) = apply_kamm_scaling([0.0, 0.0, 6.0], [8.0, 0.0, 0.0], FLAT_FORWARD, 5.0, 5.0, &CIRCLE);
+        assert!(close(long, [0.0, 0.0, 3.0]) && close(lat, [4.0, 0.0, 0.0]));
+
+        // Separate stiffness: cx = 0.5 halves the longitudinal capacity
+        let ellipse = KammCircle { cx: 0.5, cy: 1.0 };
+        let (long, _) = apply_kamm_scaling([0.0, 0.0, 3.0], [0.0, 0.0, 0.0], FLAT_FORWARD, 5.0, 5.0, &ellipse);
+        assert!(close(long, [0.0, 0.0, 2.5]));
+    }
+
+    #[test]
+    fn on_a_slope_only_the_planar_part_of_the_longitudinal_impulse_counts() {
+        // Contact pitched 30°: the impulse tilts with the wheel's forward
+        let (sin, cos) = 30f32.to_radians().sin_cos();
+        let long = [0.0, 6.0 * sin, 6.0 * cos];
+        let lat = [8.0, 0.0, 0.0];
+        let (long_i, lat_i) = apply_kamm_scaling(long, lat, FLAT_FORWARD, 5.0, 5.0, &CIRCLE);
+
+        let nx = 6.0 * cos / 5.0; // 1.039, not |long| / 5 = 1.2
+        let scale = 1.0 / (nx * nx + 1.6 * 1.6_f32).sqrt();
+        assert!(close(long_i, v_scale(long, scale)), "{:?}", long_i);
+        assert!(close(lat_i, v_scale(lat, scale)), "{:?}", lat_i);
+    }
+}
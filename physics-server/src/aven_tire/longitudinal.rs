@@ -7,9 +7,10 @@
 // - Lateral forces are handled independently (brush model)
 // ------------------------------------------------------------------------------
 // Computes the longitudinal impulse demand per wheel using:
-// 1) Engine force (drive wheels only)
+// 1) Engine force (drive wheels only), scaled by the powertrain efficiency curve
 // 2) Brake force (all wheels, brake-biased)
 // 3) ABS / TCS limiting (relative demand vs capacity)
+// 4) Engine braking (drive wheels, throttle lifted)
 //
 // Important properties:
 // - No wheel angular velocity state is tracked.
@@ -17,8 +18,17 @@
 // - Stability depends on the combined-slip ellipse in solve_step().
 // - Capacity is proportional to normal force (Fz) and dt.
 //
+// Brake demand is split by brake_share (this wheel's share of the whole car's
+// demand, from the front/rear bias), then limited by friction and ABS.
+//
+// Engine braking: below ENGINE_BRAKE_CREEP throttle, a driven wheel is dragged
+// by engine_brake_coefficient * v_long (fading in as the throttle closes). It
+// is part of the engine impulse, so it uses the same friction budget and
+// shows up in the combined-slip ellipse: lifting mid-corner takes lateral
+// grip from the driven axle (lift-off oversteer on RWD).
+//
 // Output:
-// - LongitudinalResult { impulse, nx }
+// - LongitudinalResult { impulse, abs_intensity, brake_impulse }
 // where nx is used in solve.rs for the combined-slip ellipse.
 // ================================================================================
 // - cardinal rules
@@ -41,14 +51,20 @@ use crate::aven_tire::types::{
     ContactPatch,
     v_scale,
     v_add,
+    v_mag,
+    interpolate_curve,
 };
 
+const ENGINE_BRAKE_CREEP: f32 = 0.05; // |throttle| below this counts as lifted (full drag at 0)
+
 // ====================================================================
 // Result of longitudinal solve
 // ====================================================================
 
 pub struct LongitudinalResult {
-    pub impulse: Vec3
+    pub impulse: Vec3,
+    pub abs_intensity: f32, // 0 = ABS idle, 1 = maximum brake release
+    pub brake_impulse: f32, // N*s actually applied by the brake (after ABS / tire state)
 }
 // ====================================================================
 // Longitudinal tire model step
@@ -59,10 +75,10 @@ pub fn solve_longitudinal(
     ctx: &SolveContext,
     ctrl: &ControlInput,
     patch: &ContactPatch,
-    _brake_share: f32,
+    brake_share: f32,
 ) -> LongitudinalResult {
 
-    if !patch.grounded { return LongitudinalResult { impulse: [0.0,0.0,0.0]};}
+    if !patch.grounded { return LongitudinalResult { impulse: [0.0,0.0,0.0], abs_intensity: 0.0, brake_impulse: 0.0 };}
     
     let dt = ctx.dt.max(1e-6);
 
@@ -88,10 +104,15 @@ pub fn solve_longitudinal(
         (patch.normal_force / (ctx.mass * 9.81 / ctx.driven_wheels.max(1.0)))
             .clamp(0.5, 1.6);
     
+    // torque-speed approximation (less force near redline)
+    let efficiency = interpolate_curve(&ctx.powertrain_curve, patch.v_long.abs()).clamp(0.0, 1.0);
+
     let engine_force = if patch.drive {
         (ctx.engine_force / ctx.driven_wheels.max(1.0))
         * ctrl.throttle
         * load_frac
+        * efficiency
+        * patch.drive_scale
 
     } else {
         0.0
@@ -119,13 +140,14 @@ pub fn solve_longitudinal(
 
             // Desired impulse to cancel longitudinal slip
             // NOTE: no mass guess — use velocity cancellation directly
-            let j_desired = -v_long_eff * ctx.mass * 0.25;
+            let j_desired = -v_long_eff * ctx.mass * brake_share * ctx.wheels_per_contact;
 
             // Scale by brake input (driver intent)
             let j_cmd = j_desired * brake_input;
 
-            // Clamp by friction capacity
-            let j = j_cmd.clamp(-j_cap, j_cap);
+            // Clamp by friction capacity, then by the actuator (brake_force * bias share)
+            let j_act = ctx.brake_force * dt * brake_share * ctx.wheels_per_contact * brake_input;
+            let j = j_cmd.clamp(-j_cap, j_cap).clamp(-j_act, j_act);
 
             brake_impulse = v_scale(patch.forward, j);
         }
@@ -144,14 +166,28 @@ pub fn solve_longitudinal(
     if ctx.tcs_enabled && ctrl.throttle > 0.01 {
         let nx = engine_jx / j_cap;
         if nx > ctx.tcs_limit {
-            let s = (ctx.tcs_limit / nx).clamp(0.0, 1.0);
-            engine_impulse = v_scale(engine_impulse, s);
+            // TCS intervention overrides torque vectoring on this wheel
+            let undo_tv = 1.0 / patch.drive_scale.max(1e-3);
+            let s = (ctx.tcs_limit / (nx * undo_tv)).clamp(0.0, 1.0);
+            engine_impulse = v_scale(engine_impulse, undo_tv * s);
         }
     }
     
+    // =========================================================
+    // ENGINE BRAKING (drive wheels, throttle lifted)
+    // After TCS: it never limits the drag, only drive torque.
+    // =========================================================
+    let lift = (1.0 - ctrl.throttle.abs() / ENGINE_BRAKE_CREEP).max(0.0);
+    if patch.drive && lift > 0.0 {
+        let drag_force = -ctx.engine_brake_coefficient / ctx.driven_wheels.max(1.0) * patch.v_long * lift;
+        let drag_j = (drag_force * dt).clamp(-j_cap, j_cap);
+        engine_impulse = v_add(engine_impulse, v_scale(patch.forward, drag_j));
+    }
+
     // =========================================================
     // ABS (based on longitudinal usage)
     // =========================================================
+    let mut abs_intensity = 0.0;
     if ctx.abs_enabled
         && ctrl.brake > 0.01
         && patch.speed_planar > 1.0
@@ -159,26 +195,28 @@ pub fn solve_longitudinal(
         let nx = brake_jx / j_cap;
         let s = (ctx.abs_limit / nx).clamp(0.2, 1.0);
         brake_impulse = v_scale(brake_impulse, s);
+        abs_intensity = (1.0 - s) / 0.8;
     }
 
-    let mut impulse = v_add(engine_impulse, brake_impulse);
-
-
-    match patch.tire_state {
-        TireState::Grip => { 
-            /* unchanged */ 
+    let state_scale = match patch.tire_state {
+        TireState::Grip => {
+            /* unchanged */
+            1.0
         }
 
         TireState::Slide => {
             // soften longitudinal authority
-            impulse = v_scale(impulse, 0.85);
+            0.85
         }
 
         TireState::Lock => {
             // braking lock: NO engine, NO corrective braking
-            impulse = v_scale(impulse, 0.5);
+            0.5
         }
-    }
+    };
+
+    let impulse = v_scale(v_add(engine_impulse, brake_impulse), state_scale);
+    let brake_impulse = v_mag(brake_impulse) * state_scale;
 
-    LongitudinalResult { impulse }
+    LongitudinalResult { impulse, abs_intensity, brake_impulse }
 }
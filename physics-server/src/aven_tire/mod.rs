@@ -8,6 +8,7 @@ pub mod steering;
 pub mod kinematics;
 pub mod anti_roll;
 pub mod state;
+pub mod combined_slip;
 
 pub use types::*;
 pub use solve::solve_step;
@@ -5,7 +5,7 @@
 // This module combines:
 // - Longitudinal impulses (engine + brake) from longitudinal.rs
 // - Lateral impulses from brush_lite.rs
-// - A combined-slip friction ellipse in impulse space
+// - A combined-slip friction ellipse in impulse space (combined_slip.rs)
 // - A split of lateral impulse into:
 //     (a) at-contact component -> yaw moment
 //     (b) at-COM component     -> lateral translation
@@ -16,17 +16,19 @@
 //     Mz = -Fy * trail
 //     torque impulse = Mz * dt around up axis
 //
-// Outputs a list of Impulse actions consumed by physics.rs, which applies:
+// Appends Impulse actions to a caller-owned buffer (reused across vehicles and
+// ticks, so the solve allocates nothing once warm); physics.rs applies:
 // - apply_impulse() at COM
 // - apply_impulse_at_point() at contact point
 // - apply_torque_impulse() for aligning torque
 // ==============================================================================
 
 
-use crate::aven_tire::types::{ ContactPatch, ControlInput, Impulse, SolveContext, v_mag, v_scale,};
+use crate::aven_tire::types::{ ContactPatch, ControlInput, Impulse, ImpulseSource, SolveContext, v_dot, v_mag,};
 use crate::aven_tire::longitudinal::solve_longitudinal;
 use crate::aven_tire::brush_lite::{solve_brush_lite, BrushLiteConfig};
 use crate::aven_tire::state::update_tire_state;
+use crate::aven_tire::combined_slip::apply_kamm_scaling;
 
 #[derive(Clone, Copy, Debug)]
 pub struct AligningTorqueConfig {
@@ -49,36 +51,157 @@ impl Default for AligningTorqueConfig {
 
 
 pub struct TireForces {
-    pub impulses: Vec<Impulse>,
+    pub drive_split: [f32; 2],   // [left, right] share of drive torque (0.5/0.5 = even)
+    pub yaw_impulse: f32,        // N*m*s about world up (stability assist)
+    pub abs_intensity: f32,      // strongest ABS intervention this tick (0..1, haptics)
+    pub brake_force_axle: [f32; 2], // [front, rear] applied brake force (N, diagnostics)
     // pub rack_torque: f32, // N·m (about steering axis)
 }
 
+// ==============================================================================
+// Stability assist (arcade)
+// ------------------------------------------------------------------------------
+// Without steering input, yaw is unintended: remove a fraction of it each tick
+//     J_stab = -stability_assist * yaw_rate * I_yaw
+// Returned as an angular impulse about world up (a linear impulse at the COM
+// cannot change yaw). Only active on the ground and outside the dead band so
+// intentional cornering and small wobbles are left alone.
+// ==============================================================================
+const STABILITY_STEER_DEADBAND: f32 = 0.1;
+const STABILITY_MIN_YAW_RATE: f32 = 0.3; // rad/s
+
+fn stability_assist_impulse(ctx: &SolveContext, ctrl: &ControlInput, contacts: &[ContactPatch]) -> f32 {
+    if ctx.stability_assist <= 0.0 || ctrl.steer.abs() >= STABILITY_STEER_DEADBAND {
+        return 0.0;
+    }
+    let Some(patch) = contacts.iter().find(|p| p.grounded) else { return 0.0 };
+
+    let yaw_rate = patch.yaw_rate;
+    if yaw_rate.abs() <= STABILITY_MIN_YAW_RATE {
+        return 0.0;
+    }
+    -ctx.stability_assist * yaw_rate * ctx.yaw_inertia
+}
+
+// ==============================================================================
+// Torque vectoring assist
+// ------------------------------------------------------------------------------
+// Target yaw rate from the bicycle model, using the steered front wheel angle
+// measured against the rear (chassis) forward so it is independent of the
+// steer sign convention:
+//     r_target = v * δ / L,  |r_target| <= μ g / v
+// Bias toward the outside wheels grows with steer input and understeer
+// (actual yaw below target), clamped to tv_max_bias. The outside wheels are
+// picked by where they sit relative to the COM, not by their FL/FR names
+// (the "left" wheels are at -X, which is the right-hand side of a car facing
+// +Z). Returns [left, right] drive share (by name) for diagnostics.
+// ==============================================================================
+fn apply_torque_vectoring(
+    ctx: &SolveContext,
+    ctrl: &ControlInput,
+    contacts: &mut [ContactPatch],
+) -> [f32; 2] {
+    for patch in contacts.iter_mut() {
+        patch.drive_scale = 1.0;
+    }
+
+    if !ctx.tv_enabled || ctrl.throttle <= 0.01 {
+        return [0.5, 0.5];
+    }
+
+    let front = contacts.iter().find(|p| p.wheel.is_front() && p.grounded);
+    let rear = contacts.iter().find(|p| p.wheel.is_rear() && p.grounded);
+    let (Some(front), Some(rear)) = (front, rear) else { return [0.5, 0.5] };
+
+    let speed = rear.v_long;
+    if speed < ctx.tv_min_speed {
+        return [0.5, 0.5];
+    }
+
+    // signed steer angle, + = left (yaw about +Y)
+    let cross_y = rear.forward[2] * front.forward[0] - rear.forward[0] * front.forward[2];
+    let delta = cross_y.atan2(v_dot(rear.forward, front.forward));
+
+    let r_max = ctx.mu_base * 9.81 / speed.max(1.0);
+    let r_target = (speed * delta / ctx.wheelbase.max(0.1)).clamp(-r_max, r_max);
+    if r_target.abs() < 1e-3 {
+        return [0.5, 0.5];
+    }
+
+    let turn_sign = r_target.signum();
+    let yaw_err = (r_target - rear.yaw_rate) * turn_sign / r_target.abs().max(0.1);
+
+    let bias = (ctx.tv_gain * (ctrl.steer.abs() + yaw_err))
+        .clamp(0.0, ctx.tv_max_bias.clamp(0.0, 0.95));
+
+    // (forward x relative_com).y > 0: the wheel is on the side a yaw > 0 turns toward
+    let chassis_forward = rear.forward;
+    let outside = |p: &ContactPatch| {
+        let side = chassis_forward[2] * p.relative_com[0] - chassis_forward[0] * p.relative_com[2];
+        side * turn_sign < 0.0
+    };
+    let left_outside = contacts.iter().find(|p| p.wheel.is_left()).is_some_and(outside);
+    for patch in contacts.iter_mut().filter(|p| p.drive) {
+        patch.drive_scale = if outside(patch) { 1.0 + bias } else { 1.0 - bias };
+    }
+
+    let left = 0.5 * if left_outside { 1.0 + bias } else { 1.0 - bias };
+    [left, 1.0 - left]
+}
+
+// ==============================================================================
+// Brake bias across axles
+// ------------------------------------------------------------------------------
+// The front axle takes base_front_bias of the brake demand; the remaining axles
+// share the rest equally (a single-axle vehicle takes it all). Each axle's share
+// is split equally across the wheels on it (ctx.axle_wheels).
+// ==============================================================================
+pub fn wheel_brake_share(ctx: &SolveContext, axle: usize) -> f32 {
+    let wheels = ctx.axle_wheels.get(axle).copied().unwrap_or(0);
+    if wheels == 0 {
+        return 0.0;
+    }
+    let axles = ctx.axle_wheels.iter().filter(|&&n| n > 0).count();
+    let front_bias = ctx.base_front_bias.clamp(0.0, 1.0);
+    let axle_share = match (axles, axle) {
+        (1, _) => 1.0,
+        (_, 0) => front_bias,
+        _ => (1.0 - front_bias) / (axles - 1) as f32,
+    };
+    axle_share / wheels as f32
+}
+
+/// Solve every contact; impulses are appended to `impulses` (not cleared).
 pub fn solve_step(
     ctx: &SolveContext,
     ctrl: &ControlInput,
-    contacts: &mut[ContactPatch],
+    contacts: &mut [ContactPatch],
+    impulses: &mut Vec<Impulse>,
 ) -> TireForces {
 
-    let mut impulses = Vec::new();
+    let mut abs_intensity: f32 = 0.0;
+    let mut brake_force_axle = [0.0_f32; 2];
     // let mut rack_torque_sum: f32 = 0.0;
 
     let brush_cfg = BrushLiteConfig::default();
 
+    // --------------------------------------------------
+    // Torque vectoring (sets patch.drive_scale)
+    // --------------------------------------------------
+    let drive_split = apply_torque_vectoring(ctx, ctrl, contacts);
+
     // --------------------------------------------------
     // Per-wheel tire solve
     // --------------------------------------------------
     for patch in contacts.iter_mut() {
         if !patch.grounded || patch.normal_force < 50.0 { continue; }
         
-        // let brake_share = if patch.wheel.is_front() { front_per_wheel } else { rear_per_wheel };
-        let brake_share = if patch.wheel.is_front() {
-            0.6 * 0.5 // 60% front axle, split across two wheels
-        } else {
-            0.4 * 0.5 // 40% rear axle, split across two wheels
-        };
+        // Share of the car's brake demand: axle bias, split across that axle's wheels
+        let brake_share = wheel_brake_share(ctx, patch.axle);
 
         // Longitudinal impulse (engine + brake)
         let long = solve_longitudinal(ctx, ctrl, patch, brake_share);
+        abs_intensity = abs_intensity.max(long.abs_intensity);
 
         // Lateral impulse (brush model)
         let lat  = solve_brush_lite(&brush_cfg, ctx, ctrl, patch);
@@ -103,14 +226,12 @@ pub fn solve_step(
         // lateral is fine as magnitude (since lat is aligned with side already)
         let ny = v_mag(lat) / jy_cap;
 
-        // ellipse constraint
-        let k = (nx*nx + ny*ny).sqrt();
+        // ellipse constraint (Kamm circle with separate Cx / Cy)
+        let (long_i, lat_i) = apply_kamm_scaling(long.impulse, lat, fwd_xz, jx_cap, jy_cap, &ctx.kamm);
 
-        let scale = if k > 1.0 {
-            1.0 / k
-        } else {
-            1.0
-        };
+        // Brake force that survived the ellipse (diagnostics)
+        let long_kept = v_mag(long_i) / v_mag(long.impulse).max(1e-6);
+        brake_force_axle[patch.axle.min(1)] += long.brake_impulse * long_kept.min(1.0) / ctx.dt.max(1e-6);
 
 
         let new_state = update_tire_state(
@@ -126,27 +247,97 @@ pub fn solve_step(
         // --------------------------------------------------
         // LONGITUDINAL → ENGINE
         // --------------------------------------------------
-        let long_i = v_scale(long.impulse, scale);
         impulses.push(Impulse {
             impulse: long_i,
             at_point: None,
+            wheel: patch.wheel,
+            source: ImpulseSource::TireLongitudinal,
         });
         
         // --------------------------------------------------
         // LATERAL → CONTACT (yaw comes from tire geometry)
         // Apply roll coupling reduction
         // --------------------------------------------------
-        let lat_i = v_scale(lat, scale);
         impulses.push(Impulse {
             impulse: lat_i,
             at_point: Some(patch.apply_point),
+            wheel: patch.wheel,
+            source: ImpulseSource::TireLateral,
         });
         
     } // Contacts iter end
 
 
     TireForces {
-        impulses,
+        drive_split,
+        yaw_impulse: stability_assist_impulse(ctx, ctrl, contacts),
+        abs_intensity,
+        brake_force_axle,
         // rack_torque: rack_torque_sum,
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aven_tire::combined_slip::KammCircle;
+
+    /// A context where only the axle layout and the brake bias matter.
+    fn ctx(axle_wheels: [u8; 4], base_front_bias: f32) -> SolveContext {
+        SolveContext {
+            dt: 1.0 / 60.0,
+            mass: 8000.0,
+            engine_force: 20_000.0,
+            brake_force: 60_000.0,
+            abs_enabled: false,
+            tcs_enabled: false,
+            abs_limit: 1.0,
+            tcs_limit: 1.0,
+            driven_wheels: 4.0,
+            wheels_per_contact: 1.0,
+            axle_wheels,
+            base_front_bias,
+            wheelbase: 4.5,
+            mu_base: 0.9,
+            powertrain_curve: [(0.0, 1.0), (10.0, 1.0), (20.0, 1.0), (30.0, 1.0)],
+            engine_brake_coefficient: 0.0,
+            kamm: KammCircle { cx: 1.0, cy: 1.0 },
+            tv_enabled: false,
+            tv_gain: 0.0,
+            tv_max_bias: 0.0,
+            tv_min_speed: 0.0,
+            stability_assist: 0.0,
+            yaw_inertia: 20_000.0,
+        }
+    }
+
+    /// Brake share of every wheel, axle by axle.
+    fn shares(ctx: &SolveContext) -> Vec<Vec<f32>> {
+        (0..4).map(|axle| vec![wheel_brake_share(ctx, axle); ctx.axle_wheels[axle] as usize]).collect()
+    }
+
+    #[test]
+    fn a_three_axle_truck_splits_the_rear_share_across_both_rear_axles() {
+        for bias in [0.4, 0.6, 0.75] {
+            let truck = shares(&ctx([2, 2, 2, 0], bias));
+            assert!((truck.iter().flatten().sum::<f32>() - 1.0).abs() < 1e-6, "the whole demand is handed out");
+            assert!((truck[0].iter().sum::<f32>() - bias).abs() < 1e-6);
+            for axle in [1, 2] {
+                assert!((truck[axle].iter().sum::<f32>() - (1.0 - bias) / 2.0).abs() < 1e-6, "bias {}: axle {}", bias, axle);
+            }
+            assert!(truck[3].is_empty());
+        }
+    }
+
+    #[test]
+    fn a_two_axle_car_keeps_the_old_per_wheel_split() {
+        let car = ctx([2, 2, 0, 0], 0.6);
+        assert!((wheel_brake_share(&car, 0) - 0.6 * 0.5).abs() < 1e-6);
+        assert!((wheel_brake_share(&car, 1) - 0.4 * 0.5).abs() < 1e-6);
+        assert_eq!(wheel_brake_share(&car, 2), 0.0);
+
+        // Uneven axles: a single front wheel takes the whole front share
+        let trike = ctx([1, 2, 0, 0], 0.6);
+        assert!((wheel_brake_share(&trike, 0) - 0.6).abs() < 1e-6);
+        assert!((wheel_brake_share(&trike, 1) - 0.2).abs() < 1e-6);
+    }
+}
@@ -41,4 +41,44 @@ pub fn update_tire_state(
         }
         TireState::Grip => TireState::Grip,
     }
-}
\ No newline at end of file
+}
+
+// ============================================
+// Visual wheel spin (client rendering only)
+// ============================================
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct WheelState {
+    pub spin_rpm: f32,       // |rotation speed| in RPM
+    pub spin_direction: f32, // +1 forward, -1 reverse, 0 stopped
+}
+
+const SPIN_STOPPED_SPEED: f32 = 0.05; // m/s below which the wheel counts as stopped
+const SPIN_THROTTLE_MIN: f32 = 0.01;  // throttle needed to spin a driven wheel at rest
+
+impl WheelState {
+    /// Update from the contact-patch longitudinal velocity.
+    /// - Rolling: direction follows v_long.
+    /// - Driven wheel spinning up before the car moves: direction follows throttle.
+    /// - Locked wheel: direction still follows vehicle motion, RPM drops to 0.
+    pub fn update(
+        &mut self,
+        v_long: f32,
+        throttle: f32,
+        drive: bool,
+        tire_state: TireState,
+        radius: f32,
+    ) {
+        let motion_dir = if v_long.abs() > SPIN_STOPPED_SPEED { v_long.signum() } else { 0.0 };
+
+        let (dir, surface_speed) = match tire_state {
+            TireState::Lock => (motion_dir, 0.0),
+            _ if motion_dir == 0.0 && drive && throttle.abs() > SPIN_THROTTLE_MIN => {
+                (throttle.signum(), v_long.abs().max(SPIN_STOPPED_SPEED))
+            }
+            _ => (motion_dir, v_long.abs()),
+        };
+
+        self.spin_direction = dir;
+        self.spin_rpm = surface_speed / radius.max(1e-3) * 60.0 / std::f32::consts::TAU;
+    }
+}
@@ -32,7 +32,6 @@ use rapier3d::prelude::Vector;
 use rapier3d::na::UnitQuaternion;
 use crate::aven_tire::types::{Vec3};
 use crate::vehicle::Vehicle;
-use std::collections::hash_map::{ ValuesMut};
 
 
 /// Steering configuration (per vehicle)
@@ -188,9 +187,9 @@ pub fn solve_steering(
     let up = Vector::new(0.0, 1.0, 0.0);
 
     // your chassis basis (MUST match wheel_basis_world rear)
-    // +X forward, -Z right
-    let chassis_fwd   = chassis_rot * Vector::new(1.0, 0.0, 0.0);
-    let chassis_right = chassis_rot * Vector::new(0.0, 0.0, -1.0);
+    // +Z forward, -X right
+    let chassis_fwd   = chassis_rot * Vector::new(0.0, 0.0, 1.0);
+    let chassis_right = chassis_rot * Vector::new(-1.0, 0.0, 0.0);
 
     // ------------------------------------------------------------
     // Rotate forward direction by steering angles (PLANAR)
@@ -228,11 +227,55 @@ pub fn solve_steering(
 // - Apply vehicle controls (throttle + steering) to each vehicle.
 // =========================================================================
 pub fn apply_vehicle_controls<'a>(
-    vehicles: ValuesMut<'a, String, Vehicle>,
+    vehicles: impl Iterator<Item = &'a mut Vehicle>,
     _dt: Real,
 ) {
     for v in vehicles {
         v.throttle = v.throttle.clamp(-1.0, 1.0);
         v.brake    = v.brake.clamp(0.0, 1.0);
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aven_tire::kinematics::wheel_basis_world;
+
+    const CONFIG: SteeringConfig = SteeringConfig {
+        wheelbase: 2.57,
+        track_width: 1.52,
+        max_steer_angle: 0.6,
+        ackermann: 0.0,
+    };
+
+    fn close(a: Vector<f32>, b: Vector<f32>) -> bool {
+        (a - b).norm() < 1e-4
+    }
+
+    #[test]
+    fn straight_front_wheels_roll_along_the_same_axis_as_the_rear() {
+        // A yawed, pitched and rolled chassis: the front basis must follow
+        // the chassis +Z the same way wheel_basis_world does for the rear.
+        let rot = UnitQuaternion::from_euler_angles(0.2, 1.1, -0.15);
+        let (fl, fr) = solve_steering(&CONFIG, &rot, 0.0);
+        let (rear_fwd, rear_side) = wheel_basis_world("RL", &rot, &fl, &fr);
+
+        for wheel in ["FL", "FR"] {
+            let (fwd, side) = wheel_basis_world(wheel, &rot, &fl, &fr);
+            assert!(close(fwd, rear_fwd), "{wheel} forward {fwd:?} vs rear {rear_fwd:?}");
+            assert!(side.dot(&rear_side).abs() > 0.99, "{wheel} side not along the rear axle");
+        }
+    }
+
+    #[test]
+    fn steering_turns_the_front_wheels_toward_chassis_right() {
+        let rot = UnitQuaternion::from_euler_angles(0.0, 0.7, 0.0);
+        let angle = 0.3;
+        let (fl, _) = solve_steering(&CONFIG, &rot, angle);
+        let fwd = Vector::new(fl.forward[0], fl.forward[1], fl.forward[2]);
+
+        let chassis_fwd = rot * Vector::new(0.0, 0.0, 1.0);
+        let chassis_right = rot * Vector::new(-1.0, 0.0, 0.0);
+        assert!((fwd.dot(&chassis_fwd) - angle.cos()).abs() < 1e-4);
+        assert!((fwd.dot(&chassis_right) - angle.sin()).abs() < 1e-4);
+    }
+}
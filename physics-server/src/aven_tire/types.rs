@@ -4,6 +4,7 @@ use std::fmt;
 pub type Vec3 = [f32; 3];
 use rapier3d::prelude::Real;
 use crate::aven_tire::state::{TireState};
+use crate::aven_tire::combined_slip::KammCircle;
 
 
 // ----- tiny vec helpers (avoid pulling a math crate into the tire solver) -----
@@ -28,6 +29,24 @@ pub fn v_cross(a: Vec3, b: Vec3) -> Vec3 {
     ]
 }
 
+/// Piecewise-linear lookup through (x, y) control points sorted by x.
+/// Clamps to the first/last y outside the curve's x range.
+#[inline]
+pub fn interpolate_curve<const N: usize>(curve: &[(f32, f32); N], x: f32) -> f32 {
+    if N == 0 { return 1.0; }
+    if x <= curve[0].0 { return curve[0].1; }
+
+    for w in curve.windows(2) {
+        let (x0, y0) = w[0];
+        let (x1, y1) = w[1];
+        if x <= x1 {
+            let span = (x1 - x0).max(1e-6);
+            return y0 + (y1 - y0) * ((x - x0) / span);
+        }
+    }
+    curve[N - 1].1
+}
+
 #[inline]
 fn norm(v: [f32;3]) -> f32 { (v[0]*v[0] + v[1]*v[1] + v[2]*v[2]).sqrt() }
 
@@ -63,12 +82,18 @@ impl WheelId {
         }
     }
 
-    pub fn is_left(&self) -> bool {
-        matches!(self, WheelId::FL | WheelId::RL)
+    /// Position in FL/FR/RL/RR-ordered per-corner arrays.
+    pub fn index(&self) -> usize {
+        match self {
+            WheelId::FL => 0,
+            WheelId::FR => 1,
+            WheelId::RL => 2,
+            WheelId::RR => 3,
+        }
     }
 
-    pub fn is_right(&self) -> bool {
-        matches!(self, WheelId::FR | WheelId::RR)
+    pub fn is_left(&self) -> bool {
+        matches!(self, WheelId::FL | WheelId::RL)
     }
 
     pub fn is_front(&self) -> bool {
@@ -95,6 +120,9 @@ impl fmt::Display for WheelId {
 // ============================================
 // ----- configs / inputs ---------------------
 // ============================================
+/// Axles per vehicle (wheel axle index 0 = front-most).
+pub const MAX_AXLES: usize = 4;
+
 #[derive(Debug, Clone, Copy)]
 pub struct SolveContext {
     pub dt: f32,                // s  
@@ -109,13 +137,31 @@ pub struct SolveContext {
     pub tcs_limit: f32,         // 0.85–1.0
 
     pub driven_wheels: f32,     // RL+RR => 2.0 for typical RWD
+    pub wheels_per_contact: f32, // 1.0 per-wheel, 2.0 when patches are axle-averaged
+    pub axle_wheels: [u8; MAX_AXLES], // wheels on each axle, front to rear (0 = no such axle)
 
-    /// brake bias params (matches your old block)
-    pub base_front_bias: f32,   // 0.0–1.0
-    pub bias_gain: f32,         // per total brake force
+    /// brake bias (see solve::wheel_brake_share)
+    pub base_front_bias: f32,   // 0.0–1.0, front axle share of brake demand (driver-adjustable)
 
     pub wheelbase: f32,
     pub mu_base: f32,
+
+    /// (speed m/s, efficiency 0..1) — engine force falloff toward top speed
+    pub powertrain_curve: [(f32, f32); 4],
+    pub engine_brake_coefficient: f32, // N per m/s, whole car (throttle lifted)
+
+    /// friction ellipse stiffness (combined slip)
+    pub kamm: KammCircle,
+
+    /// torque vectoring assist (outside-wheel drive bias)
+    pub tv_enabled: bool,
+    pub tv_gain: f32,           // bias per (steer + normalized yaw error)
+    pub tv_max_bias: f32,       // 0..1, max fraction moved to the outside wheel
+    pub tv_min_speed: f32,      // m/s
+
+    /// arcade yaw stabilization (0 = off, 1 = strong)
+    pub stability_assist: f32,
+    pub yaw_inertia: f32,       // kg*m^2 about world up
     // pub load_sensitivity: f32,
 
     // pub track_width: f32,
@@ -143,6 +189,7 @@ pub struct ControlInput {
 #[derive(Debug, Clone, Copy)]
 pub struct ContactPatch {
     pub wheel: WheelId,
+    pub axle: usize,     // axle index, 0 = front-most (Wheel::axle)
     pub grounded: bool,
 
     pub hit_point: Vec3,
@@ -173,6 +220,19 @@ pub struct ContactPatch {
     pub relative_com: [f32; 3],  // apply_point - COM (world-space vector)
     
     pub tire_state: TireState,
+
+    pub drive_scale: f32,        // per-wheel drive torque multiplier (torque vectoring), 1.0 = even
+}
+
+/// What produced an impulse (impulse audit log / debugging).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ImpulseSource {
+    Suspension,
+    TireLongitudinal,
+    TireLateral,
+    StabilityAssist,
+    Depenetration, // pushes an embedded wheel back out of the ground
+    AirControl,    // mid-air attitude torque (airborne.rs)
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -182,5 +242,9 @@ pub struct Impulse {
 
     /// Optional application point (world). If None => apply at COM.
     pub at_point: Option<Vec3>,
+
+    /// Wheel whose contact produced it + which model.
+    pub wheel: WheelId,
+    pub source: ImpulseSource,
 }
 
@@ -0,0 +1,414 @@
+// ==============================================================================
+// client_message.rs — TYPED CLIENT → SERVER MESSAGES
+// ------------------------------------------------------------------------------
+// Every JSON text frame a client sends (besides the bare "ping") is one
+// ClientMessage, picked by its "type" field. net.rs matches on the variant.
+//
+//   {"type":"input","throttle":0.8,"steer":-0.2}        absent axis = keep last
+//   {"type":"admin","cmd":"set_payload","token":..,..}  AdminMessage, cmd inside
+//
+// Parsing is strict where the old field-by-field reads were lenient:
+//
+//   numbers : a string or null where a number belongs rejects the message
+//             (it used to read as "absent" / zero)
+//   types   : fields of the wrong JSON type reject the message
+//   unknown : unknown fields are ignored (newer clients, fuzz "extra")
+//
+// A rejected frame gets a structured error back instead of a server log line:
+//
+//   {"type":"error","reason":"invalid_json"}
+//   {"type":"error","reason":"missing_type"}
+//   {"type":"error","reason":"unknown_message_type","message_type":"warp"}
+//   {"type":"error","reason":"malformed_message","message_type":"input",
+//    "detail":"invalid type: string \"1\", expected f32"}
+// ==============================================================================
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value, json};
+
+use crate::protocol;
+
+/// Every "type" ClientMessage accepts (for telling unknown types from
+/// malformed ones)
+pub const MESSAGE_TYPES: &[&str] = &[
+    "input", "turret", "adjust", "ability", "hello", "rename", "chat", "respawn", "ghost", "stats",
+    "debug_subscribe", "switch_room", "admin", "ping", "ack", "time_sync", "resume", "join", "fire",
+];
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Driving axes; None = absent (keep last value, see input_hold.rs)
+    Input {
+        #[serde(default, deserialize_with = "non_null")]
+        #[schemars(with = "f32")]
+        throttle: Option<f32>,
+        #[serde(default, deserialize_with = "non_null")]
+        #[schemars(with = "f32")]
+        steer: Option<f32>,
+        #[serde(default, deserialize_with = "non_null")]
+        #[schemars(with = "f32")]
+        brake: Option<f32>,
+        #[serde(default, deserialize_with = "non_null")]
+        #[schemars(with = "f32")]
+        ascend: Option<f32>,
+        #[serde(default, deserialize_with = "non_null")]
+        #[schemars(with = "f32")]
+        pitch: Option<f32>,
+        #[serde(default, deserialize_with = "non_null")]
+        #[schemars(with = "f32")]
+        yaw: Option<f32>,
+        #[serde(default, deserialize_with = "non_null")]
+        #[schemars(with = "f32")]
+        roll: Option<f32>,
+        /// Client prediction: increasing per input, echoed as last_processed_seq
+        #[serde(default, deserialize_with = "non_null")]
+        #[schemars(with = "u64")]
+        seq: Option<u64>,
+        /// Server tick the input is meant for (client estimate): buffered
+        /// until then (input_buffer.rs); absent = next tick
+        #[serde(default, deserialize_with = "non_null")]
+        #[schemars(with = "u64")]
+        tick: Option<u64>,
+    },
+    /// Turret yaw / pitch deltas (rad)
+    Turret {
+        #[serde(default)]
+        yaw: f32,
+        #[serde(default)]
+        pitch: f32,
+    },
+    /// Driver setup tweak; every other field is a tune parameter the room
+    /// must whitelist (input_sanity.rs)
+    Adjust {
+        #[serde(default, deserialize_with = "non_null")]
+        #[schemars(with = "f32")]
+        brake_bias_delta: Option<f32>,
+        #[serde(flatten)]
+        other: Map<String, Value>,
+    },
+    Ability {
+        ability: Option<String>,
+        target: Option<String>,
+    },
+    Hello {
+        caps: Option<Caps>,
+        name: Option<String>,
+        want_velocities: Option<bool>,
+        input_hold: Option<Value>, // per-axis overrides, parsed by InputHold::with_overrides
+        encoding: Option<String>,  // snapshot / debug wire encoding, "json" | "msgpack" (snapshot.rs)
+    },
+    Rename {
+        #[serde(default)]
+        name: String,
+    },
+    Chat {
+        #[serde(default)]
+        text: String,
+    },
+    Respawn,
+    Ghost {
+        enabled: Option<bool>,
+    },
+    Stats,
+    DebugSubscribe {
+        #[serde(default, deserialize_with = "non_null")]
+        #[schemars(with = "u32")]
+        categories: Option<u32>,
+        #[serde(default)]
+        auto_lod: bool,
+        target: Option<String>,
+        token: Option<String>,
+    },
+    SwitchRoom {
+        #[serde(default, deserialize_with = "non_null")]
+        #[schemars(with = "usize")]
+        room_id: Option<usize>,
+    },
+    Admin(AdminMessage),
+    Ping,
+    /// Last snapshot tick received: later snapshots are deltas against it (delta.rs)
+    Ack {
+        tick: u64,
+    },
+    /// Clock offset handshake: client_time is echoed back (time_sync.rs)
+    TimeSync {
+        client_time: f64,
+    },
+    /// Take a held car back; only as the first message (session.rs)
+    Resume {
+        #[serde(default)]
+        token: String,
+    },
+    /// How to join, only as the first message: "mode":"spectator" watches
+    /// room_id (default 0) without a vehicle; "player" (default) drives.
+    /// "token" = AVENLAB_ADMIN_TOKEN makes the connection an admin one
+    Join {
+        mode: Option<String>,
+        #[serde(default, deserialize_with = "non_null")]
+        #[schemars(with = "usize")]
+        room_id: Option<usize>,
+        token: Option<String>,
+        #[serde(default, deserialize_with = "non_null")]
+        protocol_version: Option<u32>, // protocol.rs; required in a join
+    },
+    /// Hitscan shot; other cars are rewound to snapshot `tick`, the one the
+    /// shooter was looking at (lag compensation); absent = now
+    Fire {
+        #[serde(default, deserialize_with = "non_null")]
+        #[schemars(with = "u64")]
+        tick: Option<u64>,
+    },
+}
+
+/// Capability flags from "hello"
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct Caps {
+    pub compression: Option<String>,
+}
+
+/// {"type":"admin", "token":.., ...}: "cmd" picks the command, except bullet
+/// time, which is {"set_timescale":0.25} without one
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct AdminMessage {
+    pub token: Option<String>,
+    pub cmd: Option<String>,
+    #[serde(rename = "player_id")]
+    pub target_id: Option<String>,
+    #[serde(default, deserialize_with = "non_null")]
+    #[schemars(with = "usize")]
+    pub room_id: Option<usize>,
+    #[serde(default, deserialize_with = "non_null")]
+    #[schemars(with = "f32")]
+    pub set_timescale: Option<f32>,
+    #[serde(rename = "mass", default, deserialize_with = "non_null")]
+    #[schemars(with = "f32")]
+    pub payload_mass: Option<f32>,
+    #[serde(rename = "offset", default, deserialize_with = "non_null")]
+    #[schemars(with = "[f32; 3]")]
+    pub payload_offset: Option<[f32; 3]>,
+    #[serde(rename = "path")]
+    pub trace_path: Option<String>,
+    #[serde(rename = "entity")]
+    pub trace_entity: Option<String>,
+}
+
+/// Why a frame was rejected; `to_json` is the reply to the client.
+#[derive(Debug)]
+pub enum ParseError {
+    InvalidJson,
+    MissingType,
+    UnknownType(String),
+    Malformed { msg_type: String, detail: String },
+}
+
+impl ParseError {
+    pub fn to_json(&self) -> String {
+        let fields = match self {
+            ParseError::InvalidJson => json!({ "reason": "invalid_json" }),
+            ParseError::MissingType => json!({ "reason": "missing_type" }),
+            ParseError::UnknownType(t) => json!({ "reason": "unknown_message_type", "message_type": t }),
+            ParseError::Malformed { msg_type, detail } => json!({
+                "reason": "malformed_message",
+                "message_type": msg_type,
+                "detail": detail,
+            }),
+        };
+        protocol::event("error", fields)
+    }
+}
+
+impl ClientMessage {
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let value: Value = serde_json::from_str(text).map_err(|_| ParseError::InvalidJson)?;
+        let msg_type = value.get("type").and_then(Value::as_str).ok_or(ParseError::MissingType)?.to_string();
+        if !MESSAGE_TYPES.contains(&msg_type.as_str()) {
+            return Err(ParseError::UnknownType(msg_type));
+        }
+        serde_json::from_value(value).map_err(|e| ParseError::Malformed { msg_type, detail: e.to_string() })
+    }
+
+    /// Messages that act on the sender's own vehicle (rejected for spectators)
+    pub fn needs_vehicle(&self) -> bool {
+        matches!(
+            self,
+            ClientMessage::Input { .. }
+                | ClientMessage::Turret { .. }
+                | ClientMessage::Fire { .. }
+                | ClientMessage::Adjust { .. }
+                | ClientMessage::Ability { .. }
+                | ClientMessage::Rename { .. }
+                | ClientMessage::Chat { .. }
+                | ClientMessage::Respawn
+                | ClientMessage::Ghost { .. }
+                | ClientMessage::SwitchRoom { .. }
+        )
+    }
+}
+
+/// Present fields must hold a real value: null (or a string where a number
+/// belongs) is an error, not "absent". Pair with #[serde(default)].
+fn non_null<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(value: Value) -> Result<ClientMessage, ParseError> {
+        ClientMessage::parse(&value.to_string())
+    }
+
+    #[test]
+    fn every_message_type_parses_into_its_variant() {
+        let samples = [
+            json!({ "type": "input", "throttle": 0.8, "steer": -0.2, "seq": 4, "tick": 120 }),
+            json!({ "type": "turret", "yaw": 0.1 }),
+            json!({ "type": "adjust", "brake_bias_delta": 0.02, "arb_front": 1.5 }),
+            json!({ "type": "ability", "ability": "magnet", "target": "b" }),
+            json!({ "type": "hello", "caps": { "compression": "deflate" }, "encoding": "msgpack" }),
+            json!({ "type": "rename", "name": "Ayrton" }),
+            json!({ "type": "chat", "text": "gg" }),
+            json!({ "type": "respawn" }),
+            json!({ "type": "ghost", "enabled": true }),
+            json!({ "type": "stats" }),
+            json!({ "type": "debug_subscribe", "categories": 3, "auto_lod": true }),
+            json!({ "type": "switch_room", "room_id": 2 }),
+            json!({ "type": "admin", "token": "t", "cmd": "set_payload", "player_id": "p", "mass": 80.0, "offset": [0.0, 0.2, -1.0] }),
+            json!({ "type": "ping" }),
+            json!({ "type": "ack", "tick": 77 }),
+            json!({ "type": "time_sync", "client_time": 1234.5 }),
+            json!({ "type": "resume", "token": "abc" }),
+            json!({ "type": "join", "mode": "spectator", "room_id": 1, "protocol_version": 1 }),
+            json!({ "type": "fire", "tick": 118 }),
+        ];
+        let types: Vec<&str> = samples.iter().map(|s| s["type"].as_str().unwrap()).collect();
+        assert_eq!(types, MESSAGE_TYPES, "one sample per message type");
+
+        let parsed: Vec<ClientMessage> = samples.into_iter().map(|s| parse(s).unwrap()).collect();
+        assert!(matches!(
+            parsed[0],
+            ClientMessage::Input { throttle: Some(0.8), steer: Some(-0.2), brake: None, ascend: None, pitch: None, yaw: None, roll: None, seq: Some(4), tick: Some(120) }
+        ));
+        assert!(matches!(parsed[1], ClientMessage::Turret { yaw: 0.1, pitch: 0.0 }));
+        assert!(matches!(&parsed[2], ClientMessage::Adjust { brake_bias_delta: Some(0.02), other } if other["arb_front"] == 1.5));
+        assert!(matches!(&parsed[3], ClientMessage::Ability { ability: Some(a), target: Some(t) } if a == "magnet" && t == "b"));
+        assert!(matches!(
+            &parsed[4],
+            ClientMessage::Hello { caps: Some(Caps { compression: Some(c) }), name: None, encoding: Some(e), .. } if c == "deflate" && e == "msgpack"
+        ));
+        assert!(matches!(&parsed[5], ClientMessage::Rename { name } if name == "Ayrton"));
+        assert!(matches!(&parsed[6], ClientMessage::Chat { text } if text == "gg"));
+        assert!(matches!(parsed[7], ClientMessage::Respawn));
+        assert!(matches!(parsed[8], ClientMessage::Ghost { enabled: Some(true) }));
+        assert!(matches!(parsed[9], ClientMessage::Stats));
+        assert!(matches!(parsed[10], ClientMessage::DebugSubscribe { categories: Some(3), auto_lod: true, target: None, token: None }));
+        assert!(matches!(parsed[11], ClientMessage::SwitchRoom { room_id: Some(2) }));
+        assert!(matches!(
+            &parsed[12],
+            ClientMessage::Admin(AdminMessage { cmd: Some(cmd), target_id: Some(p), payload_mass: Some(80.0), payload_offset: Some([0.0, 0.2, -1.0]), .. })
+                if cmd == "set_payload" && p == "p"
+        ));
+        assert!(matches!(parsed[13], ClientMessage::Ping));
+        assert!(matches!(parsed[14], ClientMessage::Ack { tick: 77 }));
+        assert!(matches!(parsed[15], ClientMessage::TimeSync { client_time: 1234.5 }));
+        assert!(matches!(&parsed[16], ClientMessage::Resume { token } if token == "abc"));
+        assert!(matches!(
+            &parsed[17],
+            ClientMessage::Join { mode: Some(m), room_id: Some(1), token: None, protocol_version: Some(1) } if m == "spectator"
+        ));
+        assert!(matches!(parsed[18], ClientMessage::Fire { tick: Some(118) }));
+
+        // Bullet time is an admin message without a cmd
+        assert!(matches!(
+            parse(json!({ "type": "admin", "token": "t", "set_timescale": 0.25 })),
+            Ok(ClientMessage::Admin(AdminMessage { cmd: None, set_timescale: Some(0.25), .. }))
+        ));
+        // Only the sender's own car needs a vehicle
+        assert!(parsed[0].needs_vehicle() && parsed[7].needs_vehicle() && parsed[18].needs_vehicle());
+        assert!(!parsed[13].needs_vehicle() && !parsed[17].needs_vehicle());
+    }
+
+    #[test]
+    fn malformed_payloads_are_rejected_not_zeroed() {
+        let malformed = |value: Value| match parse(value) {
+            Err(ParseError::Malformed { msg_type, detail }) => (msg_type, detail),
+            other => panic!("expected malformed_message, got {:?}", other),
+        };
+        // A string or a null where a number belongs
+        let (msg_type, detail) = malformed(json!({ "type": "input", "throttle": "1" }));
+        assert_eq!(msg_type, "input");
+        assert!(detail.contains("string"), "{}", detail);
+        malformed(json!({ "type": "input", "steer": null }));
+        malformed(json!({ "type": "switch_room", "room_id": -1 }));
+        malformed(json!({ "type": "admin", "mass": "heavy" }));
+        // Required fields and wrong JSON types
+        malformed(json!({ "type": "ack" }));
+        malformed(json!({ "type": "turret", "yaw": [1.0] }));
+        malformed(json!({ "type": "join", "protocol_version": 1.5 }));
+
+        // Unknown fields are ignored, absent axes stay absent
+        assert!(matches!(
+            parse(json!({ "type": "input", "extra": "fuzz" })),
+            Ok(ClientMessage::Input { throttle: None, steer: None, seq: None, .. })
+        ));
+
+        assert!(matches!(ClientMessage::parse("{not json"), Err(ParseError::InvalidJson)));
+        assert!(matches!(parse(json!({ "throttle": 1.0 })), Err(ParseError::MissingType)));
+        assert!(matches!(parse(json!({ "type": 3 })), Err(ParseError::MissingType)));
+        let unknown = parse(json!({ "type": "warp" })).unwrap_err();
+        assert!(matches!(&unknown, ParseError::UnknownType(t) if t == "warp"));
+
+        // The replies the client gets
+        let reply: Value = serde_json::from_str(&unknown.to_json()).unwrap();
+        assert_eq!(reply, json!({ "type": "error", "v": protocol::PROTOCOL_VERSION, "reason": "unknown_message_type", "message_type": "warp" }));
+        let reply: Value = serde_json::from_str(&parse(json!({ "type": "ack", "tick": "7" })).unwrap_err().to_json()).unwrap();
+        assert_eq!(reply["reason"], "malformed_message");
+        assert_eq!(reply["message_type"], "ack");
+        assert!(reply["detail"].is_string());
+    }
+
+    #[test]
+    fn non_finite_input_for_100_ticks_keeps_every_body_finite() {
+        use crate::input_sanity::InputSanity;
+        use crate::physics::{DEFAULT_VEHICLE, PhysicsWorld};
+        use std::time::{Duration, Instant};
+
+        let mut phys = PhysicsWorld::new();
+        phys.spawn_vehicle_for_player("wire".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        phys.spawn_vehicle_for_player("direct".into(), [6.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        let mut sanity = InputSanity::new(240);
+        let start = Instant::now();
+
+        for tick in 0..100 {
+            // Over the wire: 1e300 overflows f32 to infinity
+            let text = format!(r#"{{"type":"input","throttle":1e300,"steer":-1e300,"brake":0.0,"seq":{}}}"#, tick);
+            let Ok(ClientMessage::Input { throttle, steer, brake, ascend, pitch, yaw, roll, .. }) = ClientMessage::parse(&text) else {
+                panic!("input parses: {}", text);
+            };
+            assert!(throttle.is_some_and(f32::is_infinite));
+            let axes = [throttle, steer, brake, ascend, pitch, yaw, roll];
+            let checked = sanity.check_input(axes, start + Duration::from_millis(tick * 17)).expect("under the rate limit");
+            phys.apply_partial_input("wire", checked).unwrap();
+
+            // A future caller that skips the net checks entirely
+            phys.apply_player_input("direct", f32::NAN, f32::INFINITY, f32::NAN, f32::NAN, f32::NEG_INFINITY, f32::NAN, f32::NAN);
+            phys.step(1.0 / 60.0);
+        }
+
+        assert_eq!(sanity.counters.non_finite, 200);
+        for (id, vehicle) in &phys.vehicles {
+            let body = &phys.bodies[vehicle.body];
+            let state = [body.translation().as_slice(), body.linvel().as_slice(), body.angvel().as_slice()].concat();
+            assert!(state.iter().all(|x| x.is_finite()), "{}: {:?}", id, state);
+            assert!([vehicle.throttle, vehicle.steer, vehicle.brake].iter().all(|x| x.is_finite()), "{}", id);
+        }
+        assert!(phys.debug_validate().is_empty());
+    }
+}
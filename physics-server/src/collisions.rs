@@ -0,0 +1,150 @@
+// ==============================================================================
+// collisions.rs — CHASSIS IMPACT RECORDS FOR CLIENT SOUND / CAMERA SHAKE
+// ------------------------------------------------------------------------------
+// Chassis colliders raise Rapier contact-force events (ChannelEventCollector in
+// PhysicsWorld::step). Each event is one collider pair on one substep; the
+// tracker folds them into at most one record per pair per tick:
+//
+//   a, b    : player ids, or "world" for the ground, map objects and any body
+//             that isn't a vehicle (a sorts before b; "world" is always b)
+//   point   : contact point (world), from the substep with the largest impulse
+//   impulse : N*s, summed over the tick's substeps
+//
+// Thresholding, so resting contact (a car on its roof, two cars pushing)
+// doesn't repeat every tick:
+//
+//   size : impulse / lighter vehicle's mass >= AVENLAB_COLLISION_MIN_DV
+//          (m/s, default 0.5; resting weight is g * dt, ~0.16 at 60 Hz)
+//   edge : a pair is reported when it crosses the threshold, then again only
+//          after a tick below it
+//
+// main.rs drains PhysicsWorld::collision_events into
+// SharedGameState::broadcast_events → {"type":"collisions", ...} per room.
+// ==============================================================================
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+
+pub const WORLD: &str = "world";
+const DEFAULT_MIN_DV: f32 = 0.5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CollisionRecord {
+    pub a: String,
+    pub b: String,
+    pub point: [f32; 3],
+    pub impulse: f32, // N*s
+}
+
+struct PairHit {
+    record: CollisionRecord,
+    peak: f32,      // largest single-substep impulse (its point is kept)
+    mass: f32,      // lighter vehicle's mass (kg)
+}
+
+pub struct CollisionTracker {
+    min_dv: f32,
+    pending: BTreeMap<(String, String), PairHit>, // this tick, sorted for a stable order
+    in_contact: HashSet<(String, String)>,        // pairs above the threshold last tick
+}
+
+impl CollisionTracker {
+    pub fn from_env() -> Self {
+        let min_dv = std::env::var("AVENLAB_COLLISION_MIN_DV")
+            .ok()
+            .and_then(|v| v.parse::<f32>().ok())
+            .filter(|v| v.is_finite() && *v >= 0.0)
+            .unwrap_or(DEFAULT_MIN_DV);
+        Self { min_dv, pending: BTreeMap::new(), in_contact: HashSet::new() }
+    }
+
+    /// One substep's contact between `a` and `b` (player id or WORLD);
+    /// `mass` = the lighter vehicle's mass.
+    pub fn add(&mut self, a: &str, b: &str, point: [f32; 3], impulse: f32, mass: f32) {
+        let (a, b) = if b == WORLD || (a != WORLD && a <= b) { (a, b) } else { (b, a) };
+        let hit = self.pending.entry((a.to_string(), b.to_string())).or_insert_with(|| PairHit {
+            record: CollisionRecord { a: a.to_string(), b: b.to_string(), point, impulse: 0.0 },
+            peak: 0.0,
+            mass,
+        });
+        hit.record.impulse += impulse;
+        hit.mass = hit.mass.min(mass);
+        if impulse > hit.peak {
+            hit.peak = impulse;
+            hit.record.point = point;
+        }
+    }
+
+    /// End of tick: the pairs that just crossed the threshold.
+    pub fn finish(&mut self) -> Vec<CollisionRecord> {
+        let mut above = HashSet::new();
+        let mut out = Vec::new();
+        for (key, hit) in std::mem::take(&mut self.pending) {
+            if hit.record.impulse / hit.mass.max(1.0) < self.min_dv {
+                continue;
+            }
+            if !self.in_contact.contains(&key) {
+                out.push(hit.record);
+            }
+            above.insert(key);
+        }
+        self.in_contact = above;
+        out
+    }
+
+    /// Drop a despawned player's pairs so a new car with the same id starts clean.
+    pub fn forget(&mut self, player_id: &str) {
+        self.in_contact.retain(|(a, b)| a != player_id && b != player_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MASS: f32 = 1200.0;
+
+    #[test]
+    fn substeps_fold_into_one_record_per_pair() {
+        let mut tracker = CollisionTracker::from_env();
+        tracker.add("b", "a", [1.0, 0.0, 0.0], 800.0, MASS);
+        tracker.add("a", "b", [2.0, 0.0, 0.0], 1200.0, MASS);
+        tracker.add(WORLD, "c", [0.0, 0.0, 5.0], 900.0, MASS);
+
+        let records = tracker.finish();
+        assert_eq!(records.len(), 2);
+        assert_eq!((records[0].a.as_str(), records[0].b.as_str()), ("a", "b"));
+        assert_eq!(records[0].impulse, 2000.0);
+        assert_eq!(records[0].point, [2.0, 0.0, 0.0], "point of the strongest substep");
+        assert_eq!((records[1].a.as_str(), records[1].b.as_str()), ("c", WORLD), "world is always b");
+    }
+
+    #[test]
+    fn resting_contact_stays_quiet_and_a_new_hit_reports_again() {
+        let mut tracker = CollisionTracker::from_env();
+        // A resting car's weight over one 60 Hz tick is well under the threshold
+        let resting = MASS * 9.81 / 60.0;
+        for _ in 0..10 {
+            tracker.add("a", WORLD, [0.0; 3], resting, MASS);
+            assert!(tracker.finish().is_empty());
+        }
+
+        // Pushing on: reported once, not every tick
+        let hit = MASS * 2.0;
+        tracker.add("a", "b", [0.0; 3], hit, MASS);
+        assert_eq!(tracker.finish().len(), 1);
+        tracker.add("a", "b", [0.0; 3], hit, MASS);
+        assert!(tracker.finish().is_empty());
+
+        // Apart for a tick, then a second hit
+        assert!(tracker.finish().is_empty());
+        tracker.add("a", "b", [0.0; 3], hit, MASS);
+        assert_eq!(tracker.finish().len(), 1);
+
+        // A respawned car with the same id starts clean
+        tracker.forget("a");
+        tracker.add("a", "b", [0.0; 3], hit, MASS);
+        assert_eq!(tracker.finish().len(), 1);
+    }
+}
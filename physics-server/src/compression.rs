@@ -0,0 +1,151 @@
+// ==============================================================================
+// compression.rs — OPTIONAL PAYLOAD COMPRESSION FOR WEBSOCKET FRAMES
+// ------------------------------------------------------------------------------
+// tungstenite 0.21 does not implement permessage-deflate, so compression is
+// done at the application level and negotiated in the client's hello:
+//
+//   {"type":"hello","caps":{"compression":"zstd"}}
+//
+// Once enabled, text payloads above COMPRESS_THRESHOLD bytes are sent as
+// binary frames:
+//
+//   [ 'A' 'Z' ][ version u8 ][ raw_len u32 LE ][ zstd frame ... ]
+//
+// Small messages (pong, events) stay plain text. The zstd context lives in
+// the per-client writer task and is reused for every message.
+// ==============================================================================
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+pub const COMPRESS_THRESHOLD: usize = 1024;
+pub const FRAME_MAGIC: [u8; 2] = *b"AZ";
+pub const FRAME_VERSION: u8 = 1;
+const HEADER_LEN: usize = 7;
+const ZSTD_LEVEL: i32 = 3;
+
+pub struct PayloadCompressor {
+    ctx: zstd::bulk::Compressor<'static>,
+}
+
+impl PayloadCompressor {
+    pub fn new() -> std::io::Result<Self> {
+        Ok(Self { ctx: zstd::bulk::Compressor::new(ZSTD_LEVEL)? })
+    }
+
+    /// Compressed binary frame, or None if the payload should stay plain text
+    /// (below threshold, compression failed, or it did not shrink).
+    pub fn encode(&mut self, text: &str) -> Option<Vec<u8>> {
+        if text.len() < COMPRESS_THRESHOLD {
+            return None;
+        }
+
+        let body = self.ctx.compress(text.as_bytes()).ok()?;
+        if body.len() + HEADER_LEN >= text.len() {
+            return None;
+        }
+
+        let mut frame = Vec::with_capacity(HEADER_LEN + body.len());
+        frame.extend_from_slice(&FRAME_MAGIC);
+        frame.push(FRAME_VERSION);
+        frame.extend_from_slice(&(text.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&body);
+        Some(frame)
+    }
+}
+
+// ==========================================================
+// Per-client transport stats
+// ==========================================================
+#[derive(Default)]
+pub struct ClientStats {
+    pub compression: AtomicBool,      // negotiated via hello
+    pub messages: AtomicU64,
+    pub compressed_messages: AtomicU64,
+    pub bytes_raw: AtomicU64,         // payload size before compression
+    pub bytes_sent: AtomicU64,        // bytes actually written
+}
+
+impl ClientStats {
+    pub fn record(&self, raw: usize, sent: usize, compressed: bool) {
+        self.messages.fetch_add(1, Ordering::Relaxed);
+        if compressed {
+            self.compressed_messages.fetch_add(1, Ordering::Relaxed);
+        }
+        self.bytes_raw.fetch_add(raw as u64, Ordering::Relaxed);
+        self.bytes_sent.fetch_add(sent as u64, Ordering::Relaxed);
+    }
+
+    /// bytes_raw / bytes_sent (1.0 = no savings)
+    pub fn compression_ratio(&self) -> f32 {
+        let sent = self.bytes_sent.load(Ordering::Relaxed);
+        if sent == 0 {
+            return 1.0;
+        }
+        self.bytes_raw.load(Ordering::Relaxed) as f32 / sent as f32
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "compression": self.compression.load(Ordering::Relaxed),
+            "messages": self.messages.load(Ordering::Relaxed),
+            "compressed_messages": self.compressed_messages.load(Ordering::Relaxed),
+            "bytes_raw": self.bytes_raw.load(Ordering::Relaxed),
+            "bytes_sent": self.bytes_sent.load(Ordering::Relaxed),
+            "compression_ratio": self.compression_ratio(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot_like(entities: usize) -> String {
+        let rows: Vec<String> = (0..entities)
+            .map(|i| format!(r#"{{"id":"player_{i}","p":[{i}.5,0.42,-{i}.25],"r":[0,0,0,1],"v":[0,0,0]}}"#))
+            .collect();
+        format!(r#"{{"type":"snapshot","tick":1234,"entities":[{}]}}"#, rows.join(","))
+    }
+
+    #[test]
+    fn frame_round_trips_through_zstd() {
+        let text = snapshot_like(40);
+        assert!(text.len() > COMPRESS_THRESHOLD);
+
+        let frame = PayloadCompressor::new().unwrap().encode(&text).expect("large snapshot compresses");
+        assert_eq!(frame[..2], FRAME_MAGIC);
+        assert_eq!(frame[2], FRAME_VERSION);
+        let raw_len = u32::from_le_bytes(frame[3..HEADER_LEN].try_into().unwrap()) as usize;
+        assert_eq!(raw_len, text.len());
+        assert!(frame.len() < text.len());
+
+        let body = zstd::bulk::decompress(&frame[HEADER_LEN..], raw_len).unwrap();
+        assert_eq!(body, text.as_bytes());
+    }
+
+    #[test]
+    fn small_payloads_stay_plain_text() {
+        let mut compressor = PayloadCompressor::new().unwrap();
+        assert!(compressor.encode(r#"{"type":"pong"}"#).is_none());
+        assert!(compressor.encode(&snapshot_like(40)).is_some(), "context is reusable after a skip");
+    }
+
+    #[test]
+    fn record_counts_raw_and_sent_bytes() {
+        let stats = ClientStats::default();
+        assert_eq!(stats.compression_ratio(), 1.0);
+
+        stats.record(2000, 500, true);
+        stats.record(100, 100, false);
+
+        assert_eq!(stats.messages.load(Ordering::Relaxed), 2);
+        assert_eq!(stats.compressed_messages.load(Ordering::Relaxed), 1);
+        assert_eq!(stats.bytes_raw.load(Ordering::Relaxed), 2100);
+        assert_eq!(stats.bytes_sent.load(Ordering::Relaxed), 600);
+        assert_eq!(stats.compression_ratio(), 3.5);
+
+        let json = stats.to_json();
+        assert_eq!(json["bytes_sent"], 600);
+        assert_eq!(json["compressed_messages"], 1);
+    }
+}
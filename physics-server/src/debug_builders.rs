@@ -9,6 +9,8 @@
 // Helpers:
 // - build_wheel_ray(): standardizes wheel ray origin/max distance computation
 // - push_wheel_debug(): pushes DebugWheel snapshots into DebugOverlay
+// - build_chassis_box_wireframe(): 12 edge rays of the chassis box (uses the
+//   live overlay types from physics.rs, not the copies below)
 //
 // This file is purely visualization scaffolding and should not contain physics
 // side effects.
@@ -144,4 +146,47 @@ pub fn build_wheel_ray(
         ground_n,
         wheel_center_air,
     }
-}
\ No newline at end of file
+}
+
+/// Chassis collision box as 12 edge rays (corner -> adjacent corner) in world
+/// space, so clients don't rebuild the box from half-extents + quaternion.
+pub fn build_chassis_box_wireframe(
+    chassis: &crate::physics::DebugChassis,
+    color: [f32; 3],
+) -> Vec<crate::physics::DebugRay> {
+    let [px, py, pz] = chassis.position;
+    let [qx, qy, qz, qw] = chassis.rotation;
+    let [hx, hy, hz] = chassis.half_extents;
+
+    let iso = Isometry::from_parts(
+        Translation::new(px, py, pz),
+        Rotation::from_quaternion(rapier3d::na::Quaternion::new(qw, qx, qy, qz)),
+    );
+
+    // Corner i has sign bits (x, y, z) = (i & 1, i & 2, i & 4)
+    let corner = |i: usize| -> Point<Real> {
+        let s = |bit: usize, h: f32| if i & bit != 0 { h } else { -h };
+        iso * point![s(1, hx), s(2, hy), s(4, hz)]
+    };
+
+    let mut edges = Vec::with_capacity(12);
+    for a in 0..8 {
+        for bit in [1, 2, 4] {
+            if a & bit != 0 {
+                continue; // each edge once, from its "negative" end
+            }
+            let (from, to) = (corner(a), corner(a | bit));
+            let d = to - from;
+            let length = d.norm();
+            let direction = if length > 1e-6 { d / length } else { vector![0.0, 0.0, 0.0] };
+            edges.push(crate::physics::DebugRay {
+                origin: from.into(),
+                direction: direction.into(),
+                length,
+                hit: Some(to.into()),
+                color,
+            });
+        }
+    }
+    edges
+}
@@ -0,0 +1,263 @@
+// ==============================================================================
+// delta.rs — SNAPSHOT BASELINES + CLIENT ACKS FOR DELTA SNAPSHOTS
+// ------------------------------------------------------------------------------
+// Full snapshots carry every room entity every tick. A client that acks what
+// it received gets deltas instead:
+//
+//   {"type":"ack","tick":1234}     last snapshot tick the client received
+//
+//   snapshot "baseline":1234       diffed against the client's acked tick
+//            "players"             only entities added or moved since then
+//            "added" / "removed"   ids that entered / left the room since then
+//
+// The client applies a delta on top of the latest state it holds. Entities it
+// doesn't find in "players" keep their last pose; "removed" lists every id
+// that was in the room at any snapshot after the baseline and is gone now, so
+// an entity that came and went between two acks is dropped too.
+//
+// SnapshotHistory keeps the last HISTORY_TICKS snapshots' entity poses. Poses
+// are recorded as published poses: an entity's published pose only moves
+// when the body has moved more than the interest.rs epsilons from it, so a
+// slow creep still gets sent once it adds up, and what a client holds never
+// ends up more than two epsilons from the body.
+//
+// Full snapshot (no "baseline") when: the client never acked (older clients,
+// late joiners until their first ack), the acked tick has left the history
+// (ack loss for longer than HISTORY_TICKS snapshots), or the client's car was
+// in another room at the baseline. Only pose decides what a delta carries;
+// the other per-entity fields ride along whenever the entity is sent.
+// Interest-managed snapshots (AVENLAB_INTEREST_RADIUS) keep their own
+// per-client diff and ignore acks; spectators, who always get the whole
+// room, still use deltas.
+// ==============================================================================
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::interest::{MOVE_EPSILON, ROT_EPSILON};
+
+/// Snapshots kept to diff against (~1 s at 60 Hz)
+pub const HISTORY_TICKS: usize = 64;
+
+/// An entity's published pose at one snapshot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PublishedPose {
+    room_id: usize,
+    pos: [f32; 3],
+    rot: [f32; 4],
+}
+
+impl PublishedPose {
+    fn moved(&self, pos: [f32; 3], rot: [f32; 4]) -> bool {
+        (0..3).any(|i| (pos[i] - self.pos[i]).abs() > MOVE_EPSILON)
+            || (0..4).any(|i| (rot[i] - self.rot[i]).abs() > ROT_EPSILON)
+    }
+}
+
+/// What a delta snapshot carries for one room against one baseline.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Delta {
+    pub send: HashSet<String>, // added or moved: full entries in "players"
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct SnapshotHistory {
+    ticks: VecDeque<(u64, HashMap<String, PublishedPose>)>,
+    acks: HashMap<String, u64>, // player_id → last acked snapshot tick
+}
+
+impl SnapshotHistory {
+    /// Record this tick's snapshot: every entity sent, (id, room, pos, rot).
+    pub fn record<'a>(&mut self, tick: u64, entities: impl Iterator<Item = (&'a str, usize, [f32; 3], [f32; 4])>) {
+        let previous = self.ticks.back().map(|(_, poses)| poses);
+        let poses: HashMap<String, PublishedPose> = entities
+            .map(|(id, room_id, pos, rot)| {
+                let pose = match previous.and_then(|p| p.get(id)) {
+                    Some(last) if last.room_id == room_id && !last.moved(pos, rot) => *last,
+                    _ => PublishedPose { room_id, pos, rot },
+                };
+                (id.to_string(), pose)
+            })
+            .collect();
+        if self.ticks.back().is_some_and(|(last, _)| *last >= tick) {
+            self.ticks.clear(); // tick went backwards (reset): old baselines are meaningless
+        }
+        self.ticks.push_back((tick, poses));
+        while self.ticks.len() > HISTORY_TICKS {
+            self.ticks.pop_front();
+        }
+    }
+
+    /// A client received snapshot `tick`. Acks arriving out of order are
+    /// ignored; acks for ticks not sent yet are rejected.
+    pub fn ack(&mut self, player_id: &str, tick: u64, current_tick: u64) -> Result<(), &'static str> {
+        if tick > current_tick {
+            return Err("ack_from_future");
+        }
+        let acked = self.acks.entry(player_id.to_string()).or_insert(tick);
+        *acked = (*acked).max(tick);
+        Ok(())
+    }
+
+    pub fn forget(&mut self, player_id: &str) {
+        self.acks.remove(player_id);
+    }
+
+    pub fn acked_clients(&self) -> impl Iterator<Item = &String> {
+        self.acks.keys()
+    }
+
+    /// Baseline to diff `player_id`'s snapshot against, if it can have one:
+    /// acked, still in the history, and its car was in `room_id` back then
+    /// (spectators have no car and never change rooms).
+    pub fn baseline(&self, player_id: &str, room_id: usize, spectator: bool) -> Option<u64> {
+        let acked = *self.acks.get(player_id)?;
+        let (_, poses) = self.ticks.iter().find(|(tick, _)| *tick == acked)?;
+        // The latest tick is the snapshot being built: nothing to diff
+        if self.ticks.back().is_some_and(|(latest, _)| *latest == acked) {
+            return None;
+        }
+        (spectator || poses.get(player_id).is_some_and(|own| own.room_id == room_id)).then_some(acked)
+    }
+
+    /// Latest recorded snapshot against `baseline` for the entities in
+    /// `room_id` (None if the baseline isn't in the history).
+    pub fn delta(&self, baseline: u64, room_id: usize) -> Option<Delta> {
+        let start = self.ticks.iter().position(|(tick, _)| *tick == baseline)?;
+        let in_room = |poses: &HashMap<String, PublishedPose>| -> HashMap<String, PublishedPose> {
+            poses.iter().filter(|(_, p)| p.room_id == room_id).map(|(id, p)| (id.clone(), *p)).collect()
+        };
+        let base = in_room(&self.ticks[start].1);
+        let now = in_room(&self.ticks.back()?.1);
+
+        // Unchanged = present at every snapshot since the baseline with the
+        // same published pose; anything that blinked out and back is resent.
+        let mut unchanged: HashSet<&str> = now
+            .iter()
+            .filter(|(id, pose)| base.get(*id) == Some(pose))
+            .map(|(id, _)| id.as_str())
+            .collect();
+        let mut removed: HashSet<String> = base.keys().filter(|id| !now.contains_key(*id)).cloned().collect();
+        for (_, poses) in self.ticks.iter().skip(start + 1) {
+            let poses = in_room(poses);
+            unchanged.retain(|id| poses.get(*id) == now.get(*id));
+            removed.extend(poses.into_keys().filter(|id| !now.contains_key(id)));
+        }
+
+        let mut added: Vec<String> = now.keys().filter(|id| !base.contains_key(*id)).cloned().collect();
+        let mut removed: Vec<String> = removed.into_iter().collect();
+        added.sort();
+        removed.sort();
+        Some(Delta {
+            send: now.keys().filter(|id| !unchanged.contains(id.as_str())).cloned().collect(),
+            added,
+            removed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROT: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+    fn record(history: &mut SnapshotHistory, tick: u64, entities: &[(&str, usize, f32)]) {
+        history.record(tick, entities.iter().map(|&(id, room, x)| (id, room, [x, 0.0, 0.0], ROT)));
+    }
+
+    #[test]
+    fn a_delta_carries_only_what_moved_joined_or_left_since_the_baseline() {
+        let mut history = SnapshotHistory::default();
+        record(&mut history, 1, &[("a", 0, 0.0), ("b", 0, 5.0), ("c", 0, 9.0)]);
+        record(&mut history, 2, &[("a", 0, 1.0), ("b", 0, 5.0), ("c", 0, 9.0)]);
+        record(&mut history, 3, &[("a", 0, 1.0), ("b", 0, 5.0), ("d", 0, 3.0)]);
+
+        let delta = history.delta(1, 0).unwrap();
+        assert_eq!(delta.send, HashSet::from(["a".to_string(), "d".to_string()]));
+        assert_eq!(delta.added, ["d"]);
+        assert_eq!(delta.removed, ["c"]);
+
+        // Against the tick before: a has not moved since
+        let delta = history.delta(2, 0).unwrap();
+        assert_eq!(delta.send, HashSet::from(["d".to_string()]));
+        assert!(history.delta(0, 0).is_none(), "never recorded");
+    }
+
+    #[test]
+    fn an_entity_that_came_and_went_between_acks_is_removed() {
+        let mut history = SnapshotHistory::default();
+        record(&mut history, 1, &[("a", 0, 0.0)]);
+        record(&mut history, 2, &[("a", 0, 0.0), ("blink", 0, 2.0)]);
+        record(&mut history, 3, &[("a", 0, 0.0)]);
+        let delta = history.delta(1, 0).unwrap();
+        assert!(delta.send.is_empty() && delta.added.is_empty());
+        assert_eq!(delta.removed, ["blink"]);
+
+        // Out and back in at the same pose: resent, the client dropped it
+        record(&mut history, 4, &[("a", 0, 0.0), ("blink", 0, 2.0)]);
+        record(&mut history, 5, &[("a", 0, 0.0)]);
+        record(&mut history, 6, &[("a", 0, 0.0), ("blink", 0, 2.0)]);
+        let delta = history.delta(4, 0).unwrap();
+        assert_eq!(delta.send, HashSet::from(["blink".to_string()]));
+    }
+
+    #[test]
+    fn a_slow_creep_is_sent_once_it_passes_the_epsilon() {
+        let mut history = SnapshotHistory::default();
+        let step = MOVE_EPSILON * 0.3;
+        let mut sent_at = Vec::new();
+        for tick in 1..=12u64 {
+            record(&mut history, tick, &[("a", 0, tick as f32 * step)]);
+            if tick > 1 && !history.delta(tick - 1, 0).unwrap().send.is_empty() {
+                sent_at.push(tick);
+            }
+        }
+        // Every tick on its own is below the epsilon, the sum is not
+        assert!(!sent_at.is_empty() && sent_at.len() < 6, "sent at {sent_at:?}");
+        let held = history.ticks.back().unwrap().1["a"].pos[0];
+        assert!((12.0 * step - held).abs() <= MOVE_EPSILON, "published pose trails the body by under an epsilon");
+    }
+
+    #[test]
+    fn baselines_need_an_ack_in_the_history_from_the_same_room() {
+        let mut history = SnapshotHistory::default();
+        assert_eq!(history.ack("a", 5, 4), Err("ack_from_future"));
+        record(&mut history, 1, &[("a", 0, 0.0), ("b", 1, 0.0)]);
+        record(&mut history, 2, &[("a", 0, 0.0), ("b", 0, 0.0)]);
+        assert_eq!(history.baseline("a", 0, false), None, "never acked");
+
+        history.ack("a", 1, 2).unwrap();
+        history.ack("b", 1, 2).unwrap();
+        assert_eq!(history.baseline("a", 0, false), Some(1));
+        assert_eq!(history.baseline("b", 0, false), None, "b's car was in room 1 at the baseline");
+        history.ack("coach", 1, 2).unwrap();
+        assert_eq!(history.baseline("coach", 0, true), Some(1), "a spectator has no car to place");
+        assert_eq!(history.delta(1, 0).unwrap().added, ["b"]);
+
+        history.ack("a", 0, 2).unwrap();
+        assert_eq!(history.baseline("a", 0, false), Some(1), "an older ack arriving late is ignored");
+        history.ack("a", 2, 2).unwrap();
+        assert_eq!(history.baseline("a", 0, false), None, "acked the snapshot being built");
+
+        for tick in 3..3 + HISTORY_TICKS as u64 {
+            record(&mut history, tick, &[("a", 0, 0.0)]);
+        }
+        assert_eq!(history.baseline("a", 0, false), None, "ack left the history");
+
+        history.forget("a");
+        assert!(history.acked_clients().all(|id| id != "a"));
+    }
+
+    #[test]
+    fn a_tick_going_backwards_drops_every_baseline() {
+        let mut history = SnapshotHistory::default();
+        record(&mut history, 10, &[("a", 0, 0.0)]);
+        record(&mut history, 11, &[("a", 0, 0.0)]);
+        history.ack("a", 10, 11).unwrap();
+        record(&mut history, 1, &[("a", 0, 0.0)]);
+        assert_eq!(history.ticks.len(), 1);
+        assert_eq!(history.baseline("a", 0, false), None);
+    }
+}
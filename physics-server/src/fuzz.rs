@@ -0,0 +1,486 @@
+// ==============================================================================
+// fuzz.rs — PROTOCOL FUZZ HARNESS FOR THE NET LAYER (--fuzz)
+// ------------------------------------------------------------------------------
+//   physics-server --fuzz [corpus dir] [--sessions 200] [--seed 1]
+//
+// Runs an in-process server (net::serve_websocket on 127.0.0.1:<free port>,
+// the real main-loop tick) and throws real WebSocket connections at it. Each
+// session opens 1..=MAX_CLIENTS clients that join, send 5..MAX_MESSAGES frames
+// and leave, sometimes with a close frame and sometimes by just dropping the
+// socket (whose car is then held for reclaim, FUZZ_DISCONNECT_GRACE_TICKS
+// here instead of the usual seconds). Some join as spectators (a "join" with
+// "mode":"spectator" first). Now and then a raw TCP client sends garbage
+// instead of a handshake.
+//
+// Frames are built from the seed corpus (one message per file, default
+// fuzz/corpus). Each seed is sent as is, or after JSON mutations (fields set to
+// adversarial values, removed, "type" / "cmd" swapped, nesting past serde's
+// recursion limit), or after byte mutations (flips, inserts, cuts, sent as
+// Text or Binary), or as plain random bytes. "$self" / "$other" in a seed are
+// replaced with this client's / another client's player id, so targeted
+// handlers get past their lookups. Admin seeds use token "fuzz": run with
+// AVENLAB_ADMIN_TOKEN=fuzz to reach the admin handlers.
+//
+// Checks:
+//   - no panics anywhere in the process (panic hook counter)
+//   - after every tick: SharedGameState::debug_validate (which includes
+//     PhysicsWorld::debug_validate) is empty: no half-joined players, no
+//     orphan map entries, no NaN / Inf in bodies or vehicle state
+//   - after every session, once all its connections are gone: the state
+//     footprint (bodies, colliders, per-player maps, spawn slots, join
+//     queue) is back to the pre-fuzz baseline, i.e. memory doesn't grow with
+//     the number of connections served
+//
+// Frame content is reproducible from --seed; scheduling between the tick loop
+// and the connection tasks is not, so rerun a failing seed a few times.
+// ==============================================================================
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use rand::Rng;
+use rand::rngs::StdRng;
+use serde_json::{Value, json};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Notify;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+use crate::game_rng::GameRng;
+use crate::health::HealthState;
+use crate::join_queue::JoinQueue;
+use crate::net::serve_websocket;
+use crate::physics::PhysicsWorld;
+use crate::read_model::ReadModelSlot;
+use crate::stall::TimedMutex;
+use crate::state::SharedGameState;
+
+pub const DEFAULT_CORPUS: &str = "fuzz/corpus";
+pub const DEFAULT_SESSIONS: usize = 200;
+const MAX_CLIENTS: usize = 3;
+const MAX_MESSAGES: usize = 80;
+const RAW_TCP_CHANCE: f64 = 0.15;            // per session
+const SPECTATOR_CHANCE: f64 = 0.2;           // per client
+const WELCOME_TIMEOUT: Duration = Duration::from_secs(2);
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+const FUZZ_DISCONNECT_GRACE_TICKS: u64 = 20; // dropped sockets' cars, held for reclaim
+const MAX_VIOLATIONS: usize = 50;            // stop early once this many are collected
+
+/// Every field ClientMessage reads (client_message.rs) plus a few it doesn't.
+const FIELDS: &[&str] = &[
+    "type", "throttle", "steer", "brake", "ascend", "pitch", "yaw", "roll", "categories", "auto_lod",
+    "cmd", "player_id", "room_id", "token", "caps", "name", "text", "brake_bias_delta", "ability",
+    "target", "set_timescale", "input_hold", "mass", "offset", "path", "entity", "enabled", "want_velocities",
+    "encoding", "tick", "seq", "client_time", "mode", "protocol_version", "extra",
+];
+const TYPES: &[&str] = &[
+    "input", "turret", "adjust", "ability", "hello", "rename", "chat", "stats", "debug_subscribe",
+    "ghost", "respawn", "admin", "switch_room", "ping", "ack", "time_sync", "resume", "join", "fire", "", "INPUT",
+];
+const ADMIN_CMDS: &[&str] = &[
+    "move_player", "set_payload", "attach_trace", "detach_trace", "report_setup", "subscribe_events", "subscribe_stats", "kick",
+    "reset_world", "list_players", "nope",
+];
+
+static PANICS: AtomicUsize = AtomicUsize::new(0);
+
+pub struct FuzzRun<'a> {
+    pub corpus_dir: &'a str,
+    pub sessions: usize,
+    pub seed: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct FuzzReport {
+    pub sessions: usize,
+    pub connections: usize,
+    pub frames: usize,
+    pub ticks: u64,
+    pub panics: usize,
+    pub violations: Vec<String>,
+}
+
+impl FuzzReport {
+    fn violation(&mut self, session: usize, what: String) {
+        if self.violations.len() < MAX_VIOLATIONS {
+            self.violations.push(format!("session {}: {}", session, what));
+        }
+    }
+}
+
+// ==========================================================
+// Frames
+// ==========================================================
+#[derive(Debug, Clone)]
+enum Frame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+struct ClientPlan {
+    frames: Vec<Frame>,
+    close_frame: bool, // false = drop the socket without a close handshake
+    spectator: bool,   // join as a spectator (no vehicle)
+}
+
+fn load_corpus(dir: &str) -> Result<Vec<String>, String> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .map_err(|e| format!("{}: {}", dir, e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .collect();
+    paths.sort(); // reproducible seed order
+    let seeds: Vec<String> = paths
+        .iter()
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .map(|text| text.trim().to_string())
+        .collect();
+    if seeds.is_empty() {
+        return Err(format!("no seed messages in {}", dir));
+    }
+    Ok(seeds)
+}
+
+fn adversarial(rng: &mut StdRng) -> Value {
+    match rng.gen_range(0..20) {
+        0 => Value::Null,
+        1 => json!(true),
+        2 => json!(0),
+        3 => json!(-1),
+        4 => json!(rng.gen_range(-2.0..2.0)),
+        5 => json!(1e30),
+        6 => json!(-1e30),
+        7 => json!(f64::MAX),
+        8 => json!(f64::MIN_POSITIVE),
+        9 => json!(u64::MAX),
+        10 => json!(i64::MIN),
+        11 => json!(""),
+        12 => json!("$self"),
+        13 => json!("$other"),
+        14 => json!("x".repeat(rng.gen_range(1..8192))),
+        15 => json!("\u{0}\u{202e}💥\\\"'<script>"),
+        16 => json!([]),
+        17 => json!({ "a": { "b": [null, 1, "c"] } }),
+        18 => json!([1.0, -1.0, 0.5]),
+        _ => {
+            // Deeper than serde_json's recursion limit (128) at the high end
+            let depth = rng.gen_range(1..200);
+            (0..depth).fold(json!(0), |inner, _| json!([inner]))
+        }
+    }
+}
+
+fn pick<'a>(rng: &mut StdRng, items: &[&'a str]) -> &'a str {
+    items[rng.gen_range(0..items.len())]
+}
+
+fn mutate_json(rng: &mut StdRng, mut msg: Value) -> Value {
+    let Some(obj) = msg.as_object_mut() else { return adversarial(rng) };
+    for _ in 0..rng.gen_range(1..=4) {
+        match rng.gen_range(0..6) {
+            0 => {
+                let field = pick(rng, FIELDS);
+                obj.insert(field.to_string(), adversarial(rng));
+            }
+            1 => {
+                let keys: Vec<String> = obj.keys().cloned().collect();
+                if !keys.is_empty() {
+                    obj.remove(&keys[rng.gen_range(0..keys.len())]);
+                }
+            }
+            2 => {
+                obj.insert("type".to_string(), json!(pick(rng, TYPES)));
+            }
+            3 => {
+                obj.insert("cmd".to_string(), json!(pick(rng, ADMIN_CMDS)));
+            }
+            4 => {
+                let axis = pick(rng, &["throttle", "steer", "brake", "decay_secs", "bogus"]);
+                let mode = pick(rng, &["latch", "decay_to_neutral", "require_full", "?"]);
+                let value = if rng.gen_bool(0.5) { json!(mode) } else { adversarial(rng) };
+                obj.insert("input_hold".to_string(), json!({ axis: value }));
+            }
+            _ => {
+                obj.insert("caps".to_string(), json!({ "compression": adversarial(rng) }));
+            }
+        }
+    }
+    msg
+}
+
+fn mutate_bytes(rng: &mut StdRng, mut bytes: Vec<u8>) -> Vec<u8> {
+    for _ in 0..rng.gen_range(1..=8) {
+        let len = bytes.len();
+        match rng.gen_range(0..5) {
+            0 if len > 0 => bytes[rng.gen_range(0..len)] ^= 1 << rng.gen_range(0..8),
+            1 => bytes.insert(rng.gen_range(0..=len), rng.r#gen()),
+            2 if len > 0 => {
+                let start = rng.gen_range(0..len);
+                let end = rng.gen_range(start..=len);
+                bytes.drain(start..end);
+            }
+            3 => bytes.truncate(rng.gen_range(0..=len)),
+            _ if len > 0 => {
+                let start = rng.gen_range(0..len);
+                let end = rng.gen_range(start..=len);
+                let chunk = bytes[start..end].to_vec();
+                bytes.splice(start..start, chunk);
+            }
+            _ => {}
+        }
+    }
+    bytes
+}
+
+fn random_bytes(rng: &mut StdRng, max: usize) -> Vec<u8> {
+    (0..rng.gen_range(0..max)).map(|_| rng.r#gen()).collect()
+}
+
+fn gen_frame(rng: &mut StdRng, seeds: &[String]) -> Frame {
+    let seed = &seeds[rng.gen_range(0..seeds.len())];
+    match rng.gen_range(0..10) {
+        0..=2 => Frame::Text(seed.clone()),
+        3..=5 => match serde_json::from_str::<Value>(seed) {
+            Ok(msg) => Frame::Text(mutate_json(rng, msg).to_string()),
+            Err(_) => Frame::Text(seed.clone()), // non-JSON seeds ("ping")
+        },
+        6..=7 => {
+            let bytes = mutate_bytes(rng, seed.clone().into_bytes());
+            if rng.gen_bool(0.7) {
+                Frame::Text(String::from_utf8_lossy(&bytes).into_owned())
+            } else {
+                Frame::Binary(bytes)
+            }
+        }
+        8 => Frame::Binary(random_bytes(rng, 512)),
+        _ => Frame::Text(String::from_utf8_lossy(&random_bytes(rng, 512)).into_owned()),
+    }
+}
+
+fn gen_plan(rng: &mut StdRng, seeds: &[String]) -> ClientPlan {
+    let count = rng.gen_range(5..=MAX_MESSAGES);
+    ClientPlan {
+        frames: (0..count).map(|_| gen_frame(rng, seeds)).collect(),
+        close_frame: rng.gen_bool(0.5),
+        spectator: rng.gen_bool(SPECTATOR_CHANCE),
+    }
+}
+
+// ==========================================================
+// Clients
+// ==========================================================
+async fn run_client(url: String, plan: ClientPlan, known_ids: Arc<std::sync::Mutex<Vec<String>>>) {
+    // Spectators state their version in the join, everyone else in the query
+    let url = if plan.spectator { url } else { format!("{}/?protocol={}", url, crate::protocol::PROTOCOL_VERSION) };
+    let Ok((ws, _)) = connect_async(url.as_str()).await else { return };
+    let (mut write, mut read) = ws.split();
+    if plan.spectator {
+        let join = json!({ "type": "join", "protocol_version": crate::protocol::PROTOCOL_VERSION, "mode": "spectator", "room_id": 0 });
+        if write.send(Message::Text(join.to_string())).await.is_err() {
+            return;
+        }
+    }
+
+    // Wait for our player id, then keep draining so the server never blocks on us
+    let welcome = tokio::time::timeout(WELCOME_TIMEOUT, async {
+        while let Some(Ok(msg)) = read.next().await {
+            let Message::Text(text) = msg else { continue };
+            let Ok(v) = serde_json::from_str::<Value>(&text) else { continue };
+            if v["type"] == "welcome" {
+                return v["player_id"].as_str().map(str::to_string);
+            }
+        }
+        None
+    })
+    .await;
+    let self_id = welcome.ok().flatten().unwrap_or_default();
+    if !self_id.is_empty() {
+        known_ids.lock().unwrap_or_else(|e| e.into_inner()).push(self_id.clone());
+    }
+    let drain = tokio::spawn(async move { while let Some(Ok(_)) = read.next().await {} });
+
+    for frame in plan.frames {
+        let other = {
+            let ids = known_ids.lock().unwrap_or_else(|e| e.into_inner());
+            ids.iter().rev().find(|id| **id != self_id).cloned().unwrap_or_default()
+        };
+        let msg = match frame {
+            Frame::Text(text) => Message::Text(text.replace("$self", &self_id).replace("$other", &other)),
+            Frame::Binary(bytes) => Message::Binary(bytes),
+        };
+        if write.send(msg).await.is_err() {
+            break;
+        }
+    }
+
+    if plan.close_frame {
+        let _ = write.close().await;
+        let _ = tokio::time::timeout(WELCOME_TIMEOUT, drain).await;
+    } else {
+        drain.abort(); // dropping both halves closes the socket without a handshake
+    }
+}
+
+async fn run_raw_client(addr: std::net::SocketAddr, bytes: Vec<u8>) {
+    let Ok(mut stream) = TcpStream::connect(addr).await else { return };
+    let _ = stream.write_all(&bytes).await;
+    let _ = stream.shutdown().await;
+}
+
+// ==========================================================
+// State footprint (leak check between sessions)
+// ==========================================================
+#[derive(Debug, PartialEq)]
+struct Footprint {
+    bodies: usize,
+    colliders: usize,
+    joints: usize,
+    vehicles: usize,
+    wheels: usize,
+    body_to_player: usize,
+    entities: usize,
+    clients: usize,
+    debug_subs: usize,
+    admin_subscribers: usize,
+    client_views: usize,
+    spectators: usize,
+    spawn_slots: usize,
+    join_queue_idle: bool,
+}
+
+impl Footprint {
+    fn capture(game: &SharedGameState, phys: &PhysicsWorld) -> Self {
+        Self {
+            bodies: phys.bodies.len(),
+            colliders: phys.colliders.len(),
+            joints: phys.joints.len(),
+            vehicles: phys.vehicles.len(),
+            wheels: phys.wheels.len(),
+            body_to_player: phys.body_to_player.len(),
+            entities: game.entities.len(),
+            clients: game.clients.len(),
+            debug_subs: game.debug_subs.len(),
+            admin_subscribers: game.admin_subscribers.len(),
+            client_views: game.client_views.len(),
+            spectators: game.spectators.len(),
+            spawn_slots: game.spawns.team_counts.values().sum(),
+            join_queue_idle: game.join_queue.is_idle(),
+        }
+    }
+}
+
+// ==========================================================
+// Driver
+// ==========================================================
+pub async fn run_fuzz(run: &FuzzRun<'_>) -> Result<FuzzReport, String> {
+    let seeds = load_corpus(run.corpus_dir)?;
+    if std::env::var("AVENLAB_ADMIN_TOKEN").ok().as_deref() != Some("fuzz") {
+        println!("ℹ️  AVENLAB_ADMIN_TOKEN is not \"fuzz\": admin handlers will only see rejected tokens");
+    }
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        PANICS.fetch_add(1, Ordering::Relaxed);
+        default_hook(info);
+    }));
+
+    let state = Arc::new(TimedMutex::new("state", SharedGameState::new(crate::tick_rate::TickRate::from_env())));
+    let physics = Arc::new(TimedMutex::new("physics", PhysicsWorld::new()));
+    let health = Arc::new(HealthState::from_env());
+    let read_model = ReadModelSlot::default();
+    let shutdown = Arc::new(Notify::new());
+    let dt = {
+        let mut game = state.lock().await;
+        game.join_queue = JoinQueue::new(health.max_players);
+        game.disconnect_grace_ticks = FUZZ_DISCONNECT_GRACE_TICKS;
+        game.tick_rate.dt()
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.map_err(|e| e.to_string())?;
+    let addr = listener.local_addr().map_err(|e| e.to_string())?;
+    let server = tokio::spawn(serve_websocket(
+        listener,
+        Arc::clone(&state),
+        Arc::clone(&physics),
+        Arc::clone(&health),
+        Arc::clone(&shutdown),
+    ));
+
+    let baseline = {
+        let phys = physics.lock().await;
+        let game = state.lock().await;
+        Footprint::capture(&game, &phys)
+    };
+    println!(
+        "🧪 Fuzzing ws://{} with {} seed messages, {} sessions (seed {})",
+        addr, seeds.len(), run.sessions, run.seed
+    );
+
+    let mut rng = GameRng::new(run.seed).child("fuzz");
+    let mut report = FuzzReport::default();
+    let known_ids = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let url = format!("ws://{}", addr);
+
+    for session in 0..run.sessions {
+        let mut tasks = Vec::new();
+        for _ in 0..rng.gen_range(1..=MAX_CLIENTS) {
+            let plan = gen_plan(&mut rng, &seeds);
+            report.frames += plan.frames.len();
+            tasks.push(tokio::spawn(run_client(url.clone(), plan, Arc::clone(&known_ids))));
+        }
+        if rng.gen_bool(RAW_TCP_CHANCE) {
+            tasks.push(tokio::spawn(run_raw_client(addr, random_bytes(&mut rng, 2048))));
+        }
+        report.connections += tasks.len();
+
+        // Tick while the clients talk, checking invariants after every tick
+        let mut settled_at = None;
+        let started = tokio::time::Instant::now();
+        loop {
+            {
+                let mut phys = physics.lock().await;
+                let mut game = state.lock().await;
+                crate::run_tick(&mut phys, &mut game, &health, &read_model, dt);
+                report.ticks += 1;
+                for error in game.debug_validate(&phys) {
+                    report.violation(session, error);
+                }
+                let drained = game.clients.is_empty() && game.join_queue.is_idle() && game.awaiting_reclaim.is_empty();
+                if tasks.iter().all(|t| t.is_finished()) && drained {
+                    settled_at.get_or_insert(report.ticks);
+                }
+            }
+            if settled_at.is_some() {
+                break;
+            }
+            if started.elapsed() > DRAIN_TIMEOUT * 4 {
+                report.violation(session, "connections never drained".to_string());
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        known_ids.lock().unwrap_or_else(|e| e.into_inner()).clear();
+
+        // Everything this session created must be gone again
+        {
+            let phys = physics.lock().await;
+            let game = state.lock().await;
+            let now = Footprint::capture(&game, &phys);
+            if now != baseline {
+                report.violation(session, format!("state did not return to baseline: {:?} vs {:?}", now, baseline));
+            }
+        }
+
+        report.sessions += 1;
+        if report.violations.len() >= MAX_VIOLATIONS {
+            break;
+        }
+    }
+
+    // Not a graceful shutdown: that would save AVENLAB_STATE_FILE over a real server's
+    server.abort();
+    report.panics = PANICS.load(Ordering::Relaxed);
+    Ok(report)
+}
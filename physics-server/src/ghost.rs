@@ -0,0 +1,306 @@
+// ==============================================================================
+// ghost.rs — BEST-LAP GHOSTS (TIME TRIAL)
+// ------------------------------------------------------------------------------
+// While lap timing runs (timing.rs), every player's pose is recorded each tick
+// of the current lap. When a lap finishes as the player's best, its recording
+// becomes their ghost trace, replacing the previous one.
+//
+// Storage (bounded): poses are quantized (position to the millimetre as i32,
+// rotation as i16 quaternion components) and only keyframes are kept: a tick
+// is dropped when interpolating its neighbours reproduces it within
+// POS_TOLERANCE / ROT_TOLERANCE. Laps longer than MAX_GHOST_TICKS are not kept.
+//
+// Playback: {"type":"ghost","enabled":true} turns a player's own ghost on. The
+// ghost starts with each of their laps (ghost tick 0 = the tick they crossed
+// the start/finish line) and waits at the finish once its lap is over. It
+// never touches physics: each tick its pose is sampled from the trace and
+// added to that player's snapshots only:
+//
+//   players[i] = {"id":"ghost:<player id>","ghost":true,"owner":..,
+//                 "x","y","z","rot","ghost_tick":..,"lap_time":..}
+// ==============================================================================
+
+use std::collections::{HashMap, HashSet};
+
+use crate::snapshot::GhostEntry;
+
+const POS_QUANTUM: f32 = 0.001;          // m per step (i32: ±2000 km)
+const ROT_QUANTUM: f32 = 1.0 / 32767.0;  // per quaternion component (i16)
+const POS_TOLERANCE: f32 = 0.02;         // m, interpolation error allowed when dropping ticks
+const ROT_TOLERANCE: f32 = 0.002;        // per quaternion component
+pub const MAX_GHOST_TICKS: usize = 36_000; // 10 min at 60 Hz
+
+pub type Pose = ([f32; 3], [f32; 4]); // position, rotation (i, j, k, w)
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct QuantPose {
+    pos: [i32; 3],
+    rot: [i16; 4],
+}
+
+impl QuantPose {
+    fn encode((pos, rot): Pose) -> Self {
+        Self {
+            pos: pos.map(|p| (p / POS_QUANTUM).round() as i32),
+            rot: rot.map(|r| (r.clamp(-1.0, 1.0) / ROT_QUANTUM).round() as i16),
+        }
+    }
+
+    fn decode(&self) -> Pose {
+        (self.pos.map(|p| p as f32 * POS_QUANTUM), self.rot.map(|r| r as f32 * ROT_QUANTUM))
+    }
+}
+
+/// Pose at fraction `t` between `a` and `b`: lerp + normalized quaternion lerp.
+fn interpolate(a: Pose, b: Pose, t: f32) -> Pose {
+    let (pa, ra) = a;
+    let (pb, mut rb) = b;
+    if ra.iter().zip(&rb).map(|(x, y)| x * y).sum::<f32>() < 0.0 {
+        rb = rb.map(|r| -r); // shortest arc
+    }
+    let pos = [0, 1, 2].map(|i| pa[i] + (pb[i] - pa[i]) * t);
+    let rot = [0, 1, 2, 3].map(|i| ra[i] + (rb[i] - ra[i]) * t);
+    let norm = rot.iter().map(|r| r * r).sum::<f32>().sqrt().max(1e-6);
+    (pos, rot.map(|r| r / norm))
+}
+
+fn within_tolerance(a: Pose, b: Pose) -> bool {
+    let dp = (0..3).map(|i| (a.0[i] - b.0[i]).powi(2)).sum::<f32>().sqrt();
+    let same = (0..4).all(|i| (a.1[i] - b.1[i]).abs() <= ROT_TOLERANCE);
+    let flipped = (0..4).all(|i| (a.1[i] + b.1[i]).abs() <= ROT_TOLERANCE);
+    dp <= POS_TOLERANCE && (same || flipped)
+}
+
+// ==========================================================
+// Stored lap
+// ==========================================================
+#[derive(Debug, Clone)]
+pub struct GhostTrace {
+    keys: Vec<(u32, QuantPose)>, // (tick from lap start, pose), ascending, first at 0, last at ticks - 1
+    ticks: u32,
+    lap_time: f32,
+}
+
+impl GhostTrace {
+    fn from_frames(frames: &[QuantPose], lap_time: f32) -> Option<Self> {
+        let last = frames.len().checked_sub(1)?;
+        let decoded: Vec<Pose> = frames.iter().map(QuantPose::decode).collect();
+
+        // Greedy: stretch each segment while every tick inside it still interpolates
+        let mut keys = vec![(0, frames[0])];
+        let mut anchor = 0;
+        for end in 2..=last {
+            let fits = (anchor + 1..end).all(|i| {
+                let t = (i - anchor) as f32 / (end - anchor) as f32;
+                within_tolerance(interpolate(decoded[anchor], decoded[end], t), decoded[i])
+            });
+            if !fits {
+                anchor = end - 1;
+                keys.push((anchor as u32, frames[anchor]));
+            }
+        }
+        if last > 0 {
+            keys.push((last as u32, frames[last]));
+        }
+        Some(Self { keys, ticks: frames.len() as u32, lap_time })
+    }
+
+    #[cfg(test)]
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    #[cfg(test)]
+    pub fn keyframes(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Pose `tick` ticks into the lap (the last pose once the lap is over).
+    pub fn sample(&self, tick: u32) -> Pose {
+        let i = self.keys.partition_point(|(t, _)| *t <= tick);
+        let (t0, a) = self.keys[i.saturating_sub(1)];
+        match self.keys.get(i) {
+            Some(&(t1, b)) => interpolate(a.decode(), b.decode(), (tick - t0) as f32 / (t1 - t0) as f32),
+            None => a.decode(),
+        }
+    }
+}
+
+// ==========================================================
+// Recording + per-player playback
+// ==========================================================
+#[derive(Debug)]
+struct LapRecording {
+    lap: u32,        // timing lap number (0 = not started)
+    start_tick: u64, // server tick the lap started on
+    frames: Vec<QuantPose>,
+    overflow: bool,  // longer than MAX_GHOST_TICKS: not kept
+}
+
+#[derive(Debug, Default)]
+pub struct Ghosts {
+    recording: HashMap<String, LapRecording>,
+    best: HashMap<String, GhostTrace>,
+    viewers: HashSet<String>, // players with their ghost turned on
+}
+
+impl Ghosts {
+    /// Feed one player's pose for `tick`. `lap` is their timing lap after this
+    /// tick's update; `best_lap_time` is set when this tick finished a lap
+    /// that is now their best.
+    pub fn record(&mut self, id: &str, tick: u64, lap: u32, pose: Pose, best_lap_time: Option<f32>) {
+        let rec = self.recording.entry(id.to_string()).or_insert_with(|| LapRecording {
+            lap,
+            start_tick: tick,
+            frames: Vec::new(),
+            overflow: false,
+        });
+
+        if rec.lap != lap {
+            // This tick's crossing closes the old lap
+            if let Some(lap_time) = best_lap_time.filter(|_| rec.lap > 0 && !rec.overflow) {
+                rec.frames.push(QuantPose::encode(pose));
+                if let Some(trace) = GhostTrace::from_frames(&rec.frames, lap_time) {
+                    println!(
+                        "👻 Ghost stored for {}: {:.3} s, {} ticks in {} keyframes",
+                        id, lap_time, trace.ticks, trace.keys.len()
+                    );
+                    self.best.insert(id.to_string(), trace);
+                }
+            }
+            *rec = LapRecording { lap, start_tick: tick, frames: Vec::new(), overflow: false };
+        }
+
+        if lap == 0 || rec.overflow {
+            return;
+        }
+        if rec.frames.len() >= MAX_GHOST_TICKS {
+            rec.overflow = true;
+            rec.frames = Vec::new();
+            return;
+        }
+        rec.frames.push(QuantPose::encode(pose));
+    }
+
+    /// Turn a player's ghost on / off; returns their best lap time, if any.
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) -> Option<f32> {
+        if enabled {
+            self.viewers.insert(id.to_string());
+        } else {
+            self.viewers.remove(id);
+        }
+        self.best.get(id).map(|t| t.lap_time)
+    }
+
+    #[cfg(test)]
+    pub fn trace(&self, id: &str) -> Option<&GhostTrace> {
+        self.best.get(id)
+    }
+
+    /// Ghost snapshot entry for `id`'s own snapshot on `tick` (None if off,
+    /// no best lap yet or no lap running).
+    pub fn snapshot_entry(&self, id: &str, tick: u64) -> Option<GhostEntry> {
+        if !self.viewers.contains(id) {
+            return None;
+        }
+        let trace = self.best.get(id)?;
+        let rec = self.recording.get(id).filter(|r| r.lap > 0)?;
+        let ghost_tick = u32::try_from(tick.saturating_sub(rec.start_tick)).unwrap_or(u32::MAX);
+        let ([x, y, z], rot) = trace.sample(ghost_tick);
+        Some(GhostEntry {
+            id: format!("ghost:{}", id),
+            ghost: true,
+            owner: id.to_string(),
+            x,
+            y,
+            z,
+            rot,
+            ghost_tick,
+            lap_time: trace.lap_time,
+        })
+    }
+
+    pub fn remove_player(&mut self, id: &str) {
+        self.recording.remove(id);
+        self.best.remove(id);
+        self.viewers.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Driving a 50 m circle, one tick = 1/60 s; yaw follows the heading.
+    fn circle(tick: u64) -> Pose {
+        let angle = tick as f32 / 60.0 * 0.5;
+        let half = angle / 2.0;
+        ([50.0 * angle.cos(), 0.6, 50.0 * angle.sin()], [0.0, half.sin(), 0.0, half.cos()])
+    }
+
+    /// One lap on ticks 10..=610 (lap 1), crossing into lap 2 on tick 610.
+    fn lap_of(ghosts: &mut Ghosts, id: &str, lap_time: Option<f32>) {
+        for tick in 0..10 {
+            ghosts.record(id, tick, 0, circle(tick), None);
+        }
+        for tick in 10..610 {
+            ghosts.record(id, tick, 1, circle(tick), None);
+        }
+        ghosts.record(id, 610, 2, circle(610), lap_time);
+    }
+
+    #[test]
+    fn the_ghost_replays_the_best_lap_tick_for_tick() {
+        let mut ghosts = Ghosts::default();
+        lap_of(&mut ghosts, "a", Some(10.0));
+        let trace = ghosts.trace("a").expect("best lap stored");
+        assert_eq!(trace.ticks(), 601);
+        assert!(trace.keyframes() < 601 / 4, "{} keyframes", trace.keyframes());
+        assert_eq!(ghosts.set_enabled("a", true), Some(10.0));
+
+        // Lap 2 starts on 610: ghost tick n is lap 1's tick 10 + n
+        for tick in 610..1300 {
+            ghosts.record("a", tick, 2, circle(tick), None);
+            let ghost = ghosts.snapshot_entry("a", tick).expect("ghost entry");
+            let n = tick - 610;
+            assert_eq!(ghost.ghost_tick as u64, n);
+            let (pos, rot) = circle(10 + n.min(600)); // waits at the finish
+            let err = ((ghost.x - pos[0]).powi(2) + (ghost.y - pos[1]).powi(2) + (ghost.z - pos[2]).powi(2)).sqrt();
+            assert!(err <= POS_TOLERANCE + POS_QUANTUM, "tick {tick}: {err} m off");
+            assert!(ghost.rot.iter().zip(rot).all(|(g, r)| (g - r).abs() <= ROT_TOLERANCE + ROT_QUANTUM), "tick {tick}");
+        }
+    }
+
+    #[test]
+    fn only_the_owner_sees_a_ghost_they_turned_on() {
+        let mut ghosts = Ghosts::default();
+        lap_of(&mut ghosts, "a", Some(10.0));
+        lap_of(&mut ghosts, "b", None);
+        assert!(ghosts.snapshot_entry("a", 611).is_none(), "off until asked for");
+        assert_eq!(ghosts.set_enabled("b", true), None, "no best lap, no ghost");
+        assert!(ghosts.snapshot_entry("b", 611).is_none());
+
+        ghosts.set_enabled("a", true);
+        let ghost = ghosts.snapshot_entry("a", 611).unwrap();
+        assert_eq!((ghost.id.as_str(), ghost.owner.as_str()), ("ghost:a", "a"));
+        ghosts.set_enabled("a", false);
+        assert!(ghosts.snapshot_entry("a", 611).is_none());
+
+        ghosts.remove_player("a");
+        assert!(ghosts.trace("a").is_none());
+    }
+
+    #[test]
+    fn a_slower_lap_keeps_the_stored_best() {
+        let mut ghosts = Ghosts::default();
+        lap_of(&mut ghosts, "a", Some(10.0));
+        // Lap 2 drives elsewhere and isn't a best
+        for tick in 610..1200 {
+            ghosts.record("a", tick, 2, ([0.0, 0.6, tick as f32], [0.0, 0.0, 0.0, 1.0]), None);
+        }
+        ghosts.record("a", 1200, 3, ([0.0, 0.6, 1200.0], [0.0, 0.0, 0.0, 1.0]), None);
+        let trace = ghosts.trace("a").unwrap();
+        assert_eq!(trace.ticks(), 601);
+        assert_eq!(trace.sample(0).0, QuantPose::encode(circle(10)).decode().0);
+    }
+}
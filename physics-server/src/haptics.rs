@@ -0,0 +1,261 @@
+// ==============================================================================
+// haptics.rs — RUMBLE / CAMERA-SHAKE HINTS FROM SERVER CONTACT DATA
+// ------------------------------------------------------------------------------
+// The client can't see contact impulses, suspension velocities or ABS
+// activity, so the physics step emits small per-player hint events:
+//
+//   collision  : chassis contact impulse rising above COLLISION_MIN_IMPULSE
+//   kerb_hit   : a wheel's suspension velocity (compression rate) spikes
+//   abs_active : ABS is scaling brake impulses (re-sent while active)
+//
+// Each carries a normalized intensity (0..1) and a duration hint (ms).
+// Events go only to the affected player (SharedGameState::send_haptics).
+// ==============================================================================
+
+use serde::Serialize;
+
+// --- kerb detector (rate of change of suspension compression, m/s) ---
+const KERB_ON_RATE: f32 = 1.5;    // spike threshold
+const KERB_OFF_RATE: f32 = 0.5;   // must settle below this before re-arming
+const KERB_FULL_RATE: f32 = 6.0;  // intensity 1.0
+const KERB_DURATION_MS: u32 = 80;
+
+// --- collisions (chassis contact impulse, N*s) ---
+const COLLISION_MIN_IMPULSE: f32 = 300.0;
+const COLLISION_FULL_DV: f32 = 6.0; // m/s of velocity change => intensity 1.0
+const COLLISION_MIN_MS: f32 = 80.0;
+const COLLISION_MAX_MS: f32 = 350.0;
+
+// --- ABS ---
+const ABS_RESEND_TICKS: u32 = 6;    // ~100 ms at 60 Hz
+const ABS_DURATION_MS: u32 = 120;   // slightly longer than the resend interval
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HapticKind {
+    Collision,
+    KerbHit,
+    AbsActive,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HapticEvent {
+    #[serde(skip)]
+    pub player_id: String,
+    #[serde(rename = "type")]
+    pub kind: HapticKind,
+    pub intensity: f32,   // 0..1
+    pub duration_ms: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wheel: Option<&'static str>,
+}
+
+// ==========================================================
+// Per-wheel kerb spike detector (with hysteresis)
+// ==========================================================
+// The chassis barely moves when a wheel rolls onto a kerb; what spikes is
+// the suspension velocity (compression rate), which the springs can't follow.
+// The event fires one tick after the spike peaks so intensity reflects the
+// peak rate rather than the first sample over the threshold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum KerbPhase {
+    Armed,
+    Rising { peak: f32 },
+    Settling, // waiting to drop below KERB_OFF_RATE
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct KerbDetector {
+    phase: KerbPhase,
+    last_compression: Option<f32>, // None while airborne
+}
+
+impl Default for KerbDetector {
+    fn default() -> Self {
+        Self { phase: KerbPhase::Armed, last_compression: None }
+    }
+}
+
+impl KerbDetector {
+    /// Compression fed on the last update (None = wheel was airborne).
+    pub fn compression(&self) -> Option<f32> {
+        self.last_compression
+    }
+
+    /// Feed this tick's compression (None = wheel off the ground);
+    /// returns an intensity when a spike has peaked.
+    pub fn update(&mut self, compression: Option<f32>, dt: f32) -> Option<f32> {
+        let prev = std::mem::replace(&mut self.last_compression, compression);
+        let rate = match (compression, prev) {
+            (Some(now), Some(prev)) => (now - prev).abs() / dt.max(1e-6),
+            _ => 0.0, // left the ground (or just landed): no rate this tick
+        };
+
+        match self.phase {
+            KerbPhase::Armed if rate > KERB_ON_RATE => {
+                self.phase = KerbPhase::Rising { peak: rate };
+                None
+            }
+            KerbPhase::Rising { peak } if rate >= peak => {
+                self.phase = KerbPhase::Rising { peak: rate };
+                None
+            }
+            KerbPhase::Rising { peak } => {
+                self.phase = KerbPhase::Settling;
+                Some((peak / KERB_FULL_RATE).clamp(0.0, 1.0))
+            }
+            KerbPhase::Settling if rate < KERB_OFF_RATE => {
+                self.phase = KerbPhase::Armed;
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+pub fn kerb_event(player_id: &str, wheel: &'static str, intensity: f32) -> HapticEvent {
+    HapticEvent {
+        player_id: player_id.to_string(),
+        kind: HapticKind::KerbHit,
+        intensity,
+        duration_ms: KERB_DURATION_MS,
+        wheel: Some(wheel),
+    }
+}
+
+// ==========================================================
+// Per-vehicle state (collision edge + ABS resend)
+// ==========================================================
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HapticState {
+    in_collision: bool,
+    abs_ticks: u32, // ticks ABS has been active (0 = inactive)
+}
+
+impl HapticState {
+    /// `impulse` = summed chassis normal impulse this tick, `mass` in kg.
+    pub fn collision_update(&mut self, player_id: &str, impulse: f32, mass: f32) -> Option<HapticEvent> {
+        let hit = impulse > COLLISION_MIN_IMPULSE;
+        let rising = hit && !self.in_collision;
+        self.in_collision = hit;
+        if !rising {
+            return None;
+        }
+
+        let intensity = (impulse / (mass.max(1.0) * COLLISION_FULL_DV)).clamp(0.0, 1.0);
+        Some(HapticEvent {
+            player_id: player_id.to_string(),
+            kind: HapticKind::Collision,
+            intensity,
+            duration_ms: (COLLISION_MIN_MS + (COLLISION_MAX_MS - COLLISION_MIN_MS) * intensity) as u32,
+            wheel: None,
+        })
+    }
+
+    /// `abs_intensity` = 0 when ABS is idle, up to 1 at maximum intervention.
+    pub fn abs_update(&mut self, player_id: &str, abs_intensity: f32) -> Option<HapticEvent> {
+        if abs_intensity <= 0.0 {
+            self.abs_ticks = 0;
+            return None;
+        }
+
+        let send = self.abs_ticks.is_multiple_of(ABS_RESEND_TICKS);
+        self.abs_ticks += 1;
+        send.then(|| HapticEvent {
+            player_id: player_id.to_string(),
+            kind: HapticKind::AbsActive,
+            intensity: abs_intensity.clamp(0.0, 1.0),
+            duration_ms: ABS_DURATION_MS,
+            wheel: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    /// Roll one wheel over a 20 cm kerb with 1 m ramps at `speed`; every
+    /// intensity the detector reports.
+    fn kerb_intensities(speed: f32) -> Vec<f32> {
+        let profile = |x: f32| 0.2 * (1.0 - (x - 2.0).abs()).max(0.0); // ramp up over x 1..2, down 2..3
+        let mut detector = KerbDetector::default();
+        (0..(5.0 / speed / DT) as usize)
+            .filter_map(|tick| detector.update(Some(0.05 + profile(tick as f32 * DT * speed)), DT))
+            .collect()
+    }
+
+    #[test]
+    fn kerb_intensity_follows_the_speed() {
+        let slow = kerb_intensities(10.0);
+        let fast = kerb_intensities(20.0);
+        // One strike per kerb: the far ramp arrives while still settling
+        assert_eq!(slow.len(), 1, "{:?}", slow);
+        assert_eq!(fast.len(), 1, "{:?}", fast);
+        // 0.2 m/m slope: 2 and 4 m/s compression rate
+        assert!((slow[0] - 2.0 / KERB_FULL_RATE).abs() < 1e-3, "{:?}", slow);
+        assert!((fast[0] - 2.0 * slow[0]).abs() < 1e-3, "{:?} vs {:?}", fast, slow);
+    }
+
+    #[test]
+    fn kerb_detector_needs_to_settle_before_firing_again() {
+        let mut d = KerbDetector::default();
+        let mut c = 0.1;
+        assert_eq!(d.update(Some(c), DT), None, "first contact: no rate yet");
+        let mut feed = |d: &mut KerbDetector, rate: f32| {
+            c += rate * DT;
+            d.update(Some(c), DT)
+        };
+
+        assert_eq!(feed(&mut d, 3.0), None, "rising");
+        assert_eq!(feed(&mut d, 2.0), Some(0.5), "fires one tick after the peak");
+        assert_eq!(feed(&mut d, 3.0), None, "still settling: no second event");
+        assert_eq!(feed(&mut d, 0.2), None);
+        assert_eq!(feed(&mut d, 3.0), None, "re-armed");
+        assert!(feed(&mut d, 0.0).is_some());
+
+        // Leaving the ground is not a spike
+        let mut d = KerbDetector::default();
+        assert_eq!(d.update(Some(0.1), DT), None);
+        assert_eq!(d.update(None, DT), None);
+        assert_eq!(d.update(Some(0.4), DT), None);
+        assert_eq!(d.compression(), Some(0.4));
+    }
+
+    #[test]
+    fn collision_fires_on_the_rising_edge_only() {
+        let mut state = HapticState::default();
+        assert!(state.collision_update("p", 100.0, 1000.0).is_none(), "below the threshold");
+
+        let ev = state.collision_update("p", 3000.0, 1000.0).unwrap();
+        assert_eq!((ev.kind, ev.player_id.as_str()), (HapticKind::Collision, "p"));
+        assert!((ev.intensity - 0.5).abs() < 1e-6);
+        assert_eq!(ev.duration_ms, 215);
+        assert!(state.collision_update("p", 9000.0, 1000.0).is_none(), "contact held");
+
+        state.collision_update("p", 0.0, 1000.0);
+        assert_eq!(state.collision_update("p", 1e6, 1000.0).unwrap().intensity, 1.0);
+    }
+
+    #[test]
+    fn abs_is_resent_while_active() {
+        let mut state = HapticState::default();
+        let sent: Vec<usize> = (0..14).filter(|_| state.abs_update("p", 0.7).is_some()).collect();
+        assert_eq!(sent, [0, 6, 12]);
+
+        assert!(state.abs_update("p", 0.0).is_none());
+        let ev = state.abs_update("p", 2.0).unwrap();
+        assert_eq!((ev.kind, ev.intensity, ev.duration_ms), (HapticKind::AbsActive, 1.0, ABS_DURATION_MS));
+    }
+
+    #[test]
+    fn events_serialize_without_the_player_id() {
+        let ev = kerb_event("p", "FL", 0.25);
+        assert_eq!(
+            serde_json::to_value(&ev).unwrap(),
+            serde_json::json!({ "type": "kerb_hit", "intensity": 0.25, "duration_ms": KERB_DURATION_MS, "wheel": "FL" })
+        );
+    }
+}
@@ -0,0 +1,507 @@
+// ==============================================================================
+// health.rs — ADMIN HTTP PORT: /healthz + /readyz (LOAD-BALANCER PROBES), /world
+// ------------------------------------------------------------------------------
+// Probes must never take the main physics/game mutexes (a stuck tick loop
+// would hang the probe instead of failing it). The tick loop mirrors what the
+// probes need into lock-free atomics on HealthState.
+//
+// /healthz : 200 while the tick counter keeps advancing between probes,
+//            503 once it has not moved for STALL_THRESHOLD. The body also
+//            carries the count of late ticks (stall.rs).
+// /readyz  : 200 when the WebSocket listener is bound, the physics world is
+//            initialized, the server is not shutting down and (if a max
+//            capacity is configured) there is room for another player.
+// /world   : latest WorldReadModel as JSON (lock-free, see read_model.rs);
+//            /world/<entity_id> for a single entity. Every player's pose, so
+//            it needs "Authorization: Bearer <AVENLAB_ADMIN_TOKEN>" (401
+//            otherwise, and always when no token is configured)
+// /metrics : (Cargo feature "metrics", on by default) Prometheus text:
+//            run_tick duration histogram (physics_tick_seconds),
+//            connected_clients, entities, snapshot_bytes_sent_total and
+//            input_messages_total, plus avenlab_* extras: tick, players,
+//            tick stalls (total and by blamed lock), iteration gap EWMA /
+//            max, frames dropped and connections closed by the message rate
+//            limit (rate_limit.rs), input → apply / apply → send latency histograms
+//            (avenlab_input_apply_seconds, avenlab_apply_send_seconds;
+//            latency.rs).
+//            All fed through atomics by the tick loop and net.rs, so a scrape
+//            never waits on the simulation
+//
+// Env:
+// - AVENLAB_ADMIN_ADDR   (default 0.0.0.0:9002)
+// - AVENLAB_ADMIN_TOKEN  (unset = /world refused; same token as admin commands)
+// - AVENLAB_MAX_PLAYERS  (default 0 = unlimited)
+// ==============================================================================
+
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::latency::LatencyTotals;
+use crate::read_model::ReadModelSlot;
+use crate::stall::StallReport;
+use crate::tick_metrics::TickSample;
+
+const STALL_THRESHOLD: Duration = Duration::from_secs(2);
+/// physics_tick_seconds bucket bounds (s); 0.016 = one 60 Hz tick
+const TICK_SECONDS_BUCKETS: [f64; 9] = [0.001, 0.002, 0.004, 0.008, 0.016, 0.033, 0.066, 0.1, 0.25];
+
+pub struct HealthState {
+    pub tick: AtomicU64,
+    pub players: AtomicUsize,
+    pub max_players: usize,
+    pub listener_bound: AtomicBool,
+    pub world_ready: AtomicBool,
+    pub shutting_down: AtomicBool,
+    /// Bearer token /world requires (None = /world refused)
+    admin_token: Option<String>,
+
+    /// (last tick seen by a probe, when it last changed)
+    last_seen: Mutex<(u64, Instant)>,
+    /// How long the tick may stand still before /healthz fails
+    stall_threshold: Duration,
+
+    /// Late tick-loop iterations (stall.rs): total, by blamed lock, gap stats
+    pub tick_stalls: AtomicU64,
+    stall_blame: Mutex<BTreeMap<&'static str, u64>>,
+    gap_ewma_us: AtomicU64,
+    gap_max_us: AtomicU64,
+
+    /// run_tick durations (per bucket, not cumulative; +Inf = the count)
+    tick_seconds_buckets: [AtomicU64; TICK_SECONDS_BUCKETS.len()],
+    tick_seconds_sum_us: AtomicU64,
+    tick_seconds_count: AtomicU64,
+    clients: AtomicUsize,
+    entities: AtomicUsize,
+    snapshot_bytes: AtomicU64,
+    /// "input" messages received (net.rs read loops)
+    pub input_messages: AtomicU64,
+    /// Frames dropped / connections closed by the rate limit (rate_limit.rs)
+    pub rate_limited_frames: AtomicU64,
+    pub rate_limit_disconnects: AtomicU64,
+    /// Latency histograms over all clients, copied in once per tick
+    latency: Mutex<LatencyTotals>,
+}
+
+impl HealthState {
+    pub fn new(max_players: usize) -> Self {
+        Self {
+            tick: AtomicU64::new(0),
+            players: AtomicUsize::new(0),
+            max_players,
+            listener_bound: AtomicBool::new(false),
+            world_ready: AtomicBool::new(false),
+            shutting_down: AtomicBool::new(false),
+            admin_token: None,
+            last_seen: Mutex::new((0, Instant::now())),
+            stall_threshold: STALL_THRESHOLD,
+            tick_stalls: AtomicU64::new(0),
+            stall_blame: Mutex::new(BTreeMap::new()),
+            gap_ewma_us: AtomicU64::new(0),
+            gap_max_us: AtomicU64::new(0),
+            tick_seconds_buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            tick_seconds_sum_us: AtomicU64::new(0),
+            tick_seconds_count: AtomicU64::new(0),
+            clients: AtomicUsize::new(0),
+            entities: AtomicUsize::new(0),
+            snapshot_bytes: AtomicU64::new(0),
+            input_messages: AtomicU64::new(0),
+            rate_limited_frames: AtomicU64::new(0),
+            rate_limit_disconnects: AtomicU64::new(0),
+            latency: Mutex::new(LatencyTotals::default()),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let max_players = std::env::var("AVENLAB_MAX_PLAYERS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+        Self::new(max_players).with_admin_token(std::env::var("AVENLAB_ADMIN_TOKEN").ok())
+    }
+
+    /// Token /world requires as "Authorization: Bearer <token>"; None or
+    /// empty leaves /world refused.
+    pub fn with_admin_token(mut self, token: Option<String>) -> Self {
+        self.admin_token = token.filter(|t| !t.is_empty());
+        self
+    }
+
+    #[cfg(test)]
+    fn with_stall_threshold(mut self, threshold: Duration) -> Self {
+        self.stall_threshold = threshold;
+        self
+    }
+
+    /// Does the request carry the admin bearer token?
+    fn authorized(&self, request: &str) -> bool {
+        let Some(expected) = self.admin_token.as_deref() else { return false };
+        request
+            .lines()
+            .skip(1)
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| line.split_once(':'))
+            .filter(|(name, _)| name.trim().eq_ignore_ascii_case("authorization"))
+            .any(|(_, value)| value.trim().strip_prefix("Bearer ") == Some(expected))
+    }
+
+    /// Called by the tick loop once per tick.
+    pub fn publish_tick(&self, tick: u64, players: usize) {
+        self.tick.store(tick, Ordering::Relaxed);
+        self.players.store(players, Ordering::Relaxed);
+    }
+
+    /// Called by the tick loop once per tick with its iteration gap stats.
+    pub fn publish_gap(&self, ewma_ms: f32, max_ms: f32) {
+        self.gap_ewma_us.store((ewma_ms * 1000.0) as u64, Ordering::Relaxed);
+        self.gap_max_us.store((max_ms * 1000.0) as u64, Ordering::Relaxed);
+    }
+
+    /// Called by run_tick once per tick with its timings and the snapshot
+    /// bytes it queued.
+    pub fn record_tick(&self, sample: &TickSample, snapshot_bytes: usize) {
+        let secs = sample.total.as_secs_f64();
+        if let Some(i) = TICK_SECONDS_BUCKETS.iter().position(|le| secs <= *le) {
+            self.tick_seconds_buckets[i].fetch_add(1, Ordering::Relaxed);
+        }
+        self.tick_seconds_sum_us.fetch_add(sample.total.as_micros() as u64, Ordering::Relaxed);
+        self.tick_seconds_count.fetch_add(1, Ordering::Relaxed);
+        self.clients.store(sample.clients, Ordering::Relaxed);
+        self.entities.store(self.players.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.snapshot_bytes.fetch_add(snapshot_bytes as u64, Ordering::Relaxed);
+    }
+
+    /// Called by run_tick after the snapshots with the latency histograms.
+    pub fn publish_latency(&self, totals: &LatencyTotals) {
+        self.latency.lock().unwrap_or_else(|e| e.into_inner()).clone_from(totals);
+    }
+
+    pub fn record_stall(&self, report: &StallReport) {
+        self.tick_stalls.fetch_add(1, Ordering::Relaxed);
+        let mut blame = self.stall_blame.lock().unwrap_or_else(|e| e.into_inner());
+        *blame.entry(report.blamed).or_default() += 1;
+    }
+
+    pub fn metrics(&self) -> String {
+        let mut out = String::new();
+        out += &format!("avenlab_tick {}\n", self.tick.load(Ordering::Relaxed));
+        out += &format!("avenlab_players {}\n", self.players.load(Ordering::Relaxed));
+        out += &format!("avenlab_tick_stalls_total {}\n", self.tick_stalls.load(Ordering::Relaxed));
+        let blame = self.stall_blame.lock().unwrap_or_else(|e| e.into_inner());
+        for (cause, count) in blame.iter() {
+            out += &format!("avenlab_tick_stalls_by_cause{{cause=\"{}\"}} {}\n", cause, count);
+        }
+        out += &format!("avenlab_tick_gap_ewma_ms {:.3}\n", self.gap_ewma_us.load(Ordering::Relaxed) as f32 / 1000.0);
+        out += &format!("avenlab_tick_gap_max_ms {:.3}\n", self.gap_max_us.load(Ordering::Relaxed) as f32 / 1000.0);
+        out += &format!("avenlab_rate_limited_frames_total {}\n", self.rate_limited_frames.load(Ordering::Relaxed));
+        out += &format!("avenlab_rate_limit_disconnects_total {}\n", self.rate_limit_disconnects.load(Ordering::Relaxed));
+
+        let count = self.tick_seconds_count.load(Ordering::Relaxed);
+        out += "# TYPE physics_tick_seconds histogram\n";
+        let mut cumulative = 0;
+        for (le, bucket) in TICK_SECONDS_BUCKETS.iter().zip(&self.tick_seconds_buckets) {
+            cumulative += bucket.load(Ordering::Relaxed);
+            out += &format!("physics_tick_seconds_bucket{{le=\"{}\"}} {}\n", le, cumulative.min(count));
+        }
+        out += &format!("physics_tick_seconds_bucket{{le=\"+Inf\"}} {}\n", count);
+        out += &format!("physics_tick_seconds_sum {:.6}\n", self.tick_seconds_sum_us.load(Ordering::Relaxed) as f64 / 1e6);
+        out += &format!("physics_tick_seconds_count {}\n", count);
+        out += "# TYPE connected_clients gauge\n";
+        out += &format!("connected_clients {}\n", self.clients.load(Ordering::Relaxed));
+        out += "# TYPE entities gauge\n";
+        out += &format!("entities {}\n", self.entities.load(Ordering::Relaxed));
+        out += "# TYPE snapshot_bytes_sent_total counter\n";
+        out += &format!("snapshot_bytes_sent_total {}\n", self.snapshot_bytes.load(Ordering::Relaxed));
+        out += "# TYPE input_messages_total counter\n";
+        out += &format!("input_messages_total {}\n", self.input_messages.load(Ordering::Relaxed));
+        let latency = self.latency.lock().unwrap_or_else(|e| e.into_inner());
+        out += &latency.input_to_apply.prometheus("avenlab_input_apply_seconds");
+        out += &latency.apply_to_send.prometheus("avenlab_apply_send_seconds");
+        out
+    }
+
+    pub fn at_capacity(&self) -> bool {
+        self.max_players > 0 && self.players.load(Ordering::Relaxed) >= self.max_players
+    }
+
+    /// Tick loop liveness: compares the counter against the previous probe.
+    pub fn check_health(&self) -> Result<(), String> {
+        let tick = self.tick.load(Ordering::Relaxed);
+        let mut last = self.last_seen.lock().unwrap_or_else(|e| e.into_inner());
+
+        if tick != last.0 {
+            *last = (tick, Instant::now());
+            return Ok(());
+        }
+
+        let stalled_for = last.1.elapsed();
+        if stalled_for > self.stall_threshold {
+            Err(format!("tick loop stalled at tick {} for {:.1}s", tick, stalled_for.as_secs_f32()))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn check_ready(&self) -> Result<(), String> {
+        if !self.listener_bound.load(Ordering::Relaxed) {
+            return Err("websocket listener not bound".into());
+        }
+        if !self.world_ready.load(Ordering::Relaxed) {
+            return Err("physics world not initialized".into());
+        }
+        if self.shutting_down.load(Ordering::Relaxed) {
+            return Err("shutting down".into());
+        }
+        if self.at_capacity() {
+            return Err("at capacity".into());
+        }
+        Ok(())
+    }
+}
+
+fn http_response(status: &str, body: &str) -> String {
+    http_response_typed(status, "text/plain", body)
+}
+
+fn http_response_typed(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\nCache-Control: no-store\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+async fn handle_probe(mut stream: TcpStream, health: Arc<HealthState>, world: Arc<ReadModelSlot>) {
+    let mut buf = [0u8; 1024];
+    let n = match stream.read(&mut buf).await {
+        Ok(n) if n > 0 => n,
+        _ => return,
+    };
+
+    // Only the request line matters: "GET /path HTTP/1.1"
+    let req = String::from_utf8_lossy(&buf[..n]);
+    let path = req.split_whitespace().nth(1).unwrap_or("/");
+    let path = path.split('?').next().unwrap_or(path);
+
+    // World view: whole model, or /world/<entity_id> (never touches the tick's mutexes)
+    if let Some(rest) = path.strip_prefix("/world") {
+        if !health.authorized(&req) {
+            let resp = http_response("401 Unauthorized", "admin token required\n");
+            let _ = stream.write_all(resp.as_bytes()).await;
+            let _ = stream.shutdown().await;
+            return;
+        }
+        let model = world.load();
+        let body = match rest.trim_start_matches('/') {
+            "" => serde_json::to_string(&*model).ok(),
+            id => model
+                .entity(id)
+                .and_then(|e| serde_json::to_string(e).ok())
+                .map(|e| format!("{{\"tick\":{},\"entity\":{}}}", model.tick, e)),
+        };
+        let resp = match body {
+            Some(body) => http_response_typed("200 OK", "application/json", &body),
+            None => http_response("404 Not Found", "unknown entity\n"),
+        };
+        let _ = stream.write_all(resp.as_bytes()).await;
+        let _ = stream.shutdown().await;
+        return;
+    }
+
+    #[cfg(feature = "metrics")]
+    if path == "/metrics" {
+        let resp = http_response("200 OK", &health.metrics());
+        let _ = stream.write_all(resp.as_bytes()).await;
+        let _ = stream.shutdown().await;
+        return;
+    }
+
+    let result = match path {
+        "/healthz" => Some(health.check_health()),
+        "/readyz" => Some(health.check_ready()),
+        _ => None,
+    };
+
+    let resp = match result {
+        Some(Ok(())) if path == "/healthz" => {
+            let stalls = health.tick_stalls.load(Ordering::Relaxed);
+            http_response("200 OK", &format!("ok\ntick_stalls {}\n", stalls))
+        }
+        Some(Ok(())) => http_response("200 OK", "ok\n"),
+        Some(Err(reason)) => http_response("503 Service Unavailable", &format!("{}\n", reason)),
+        None => http_response("404 Not Found", "not found\n"),
+    };
+
+    let _ = stream.write_all(resp.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+pub async fn start_admin_server(health: Arc<HealthState>, world: Arc<ReadModelSlot>) {
+    let addr = std::env::var("AVENLAB_ADMIN_ADDR").unwrap_or_else(|_| "0.0.0.0:9002".to_string());
+
+    let listener = match TcpListener::bind(&addr).await {
+        Ok(l) => l,
+        Err(e) => {
+            eprintln!("⚠️ Admin HTTP port {} unavailable: {}", addr, e);
+            return;
+        }
+    };
+
+    println!("🩺 Health endpoints on http://{}/healthz and /readyz", addr);
+
+    while let Ok((stream, _addr)) = listener.accept().await {
+        tokio::spawn(handle_probe(stream, Arc::clone(&health), Arc::clone(&world)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One request against handle_probe over a real socket; (status line, body).
+    async fn probe(health: &Arc<HealthState>, path: &str) -> (String, String) {
+        probe_with(health, path, "").await
+    }
+
+    /// `probe` with extra header lines ("Name: value\r\n" each).
+    async fn probe_with(health: &Arc<HealthState>, path: &str, headers: &str) -> (String, String) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = {
+            let health = Arc::clone(health);
+            tokio::spawn(async move {
+                let (stream, _) = listener.accept().await.unwrap();
+                handle_probe(stream, health, Arc::new(ReadModelSlot::default())).await;
+            })
+        };
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client.write_all(format!("GET {} HTTP/1.1\r\nHost: lb\r\n{}\r\n", path, headers).as_bytes()).await.unwrap();
+        let mut resp = String::new();
+        client.read_to_string(&mut resp).await.unwrap();
+        server.await.unwrap();
+        let (head, body) = resp.split_once("\r\n\r\n").unwrap();
+        (head.lines().next().unwrap().to_string(), body.to_string())
+    }
+
+    #[test]
+    fn ready_only_once_bound_initialized_and_not_full() {
+        let health = HealthState::new(2);
+        assert_eq!(health.check_ready(), Err("websocket listener not bound".to_string()));
+        health.listener_bound.store(true, Ordering::Relaxed);
+        assert_eq!(health.check_ready(), Err("physics world not initialized".to_string()));
+        health.world_ready.store(true, Ordering::Relaxed);
+        assert_eq!(health.check_ready(), Ok(()));
+
+        health.publish_tick(10, 2);
+        assert_eq!(health.check_ready(), Err("at capacity".to_string()));
+        health.publish_tick(11, 1);
+        assert_eq!(health.check_ready(), Ok(()));
+        health.shutting_down.store(true, Ordering::Relaxed);
+        assert_eq!(health.check_ready(), Err("shutting down".to_string()));
+
+        // 0 = unlimited
+        let unlimited = HealthState::new(0);
+        unlimited.publish_tick(1, 10_000);
+        assert!(!unlimited.at_capacity());
+    }
+
+    #[tokio::test]
+    async fn healthy_while_the_tick_moves_and_stalled_after_the_threshold() {
+        let threshold = Duration::from_millis(200);
+        let health = Arc::new(HealthState::new(0).with_stall_threshold(threshold));
+        health.tick.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(probe(&health, "/healthz").await.0, "HTTP/1.1 200 OK");
+        health.tick.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(probe(&health, "/healthz").await.0, "HTTP/1.1 200 OK");
+
+        // The loop stops: fine until the threshold passes, then 503
+        assert_eq!(probe(&health, "/healthz").await.0, "HTTP/1.1 200 OK");
+        tokio::time::sleep(threshold + Duration::from_millis(50)).await;
+        let (status, body) = probe(&health, "/healthz").await;
+        assert_eq!(status, "HTTP/1.1 503 Service Unavailable");
+        assert!(body.starts_with("tick loop stalled at tick 2"), "{}", body);
+
+        // Moving again recovers on the next probe
+        health.tick.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(probe(&health, "/healthz").await.0, "HTTP/1.1 200 OK");
+    }
+
+    #[tokio::test]
+    async fn probes_answer_over_http() {
+        let health = Arc::new(HealthState::new(0));
+        health.publish_tick(5, 0);
+        assert_eq!(probe(&health, "/healthz").await, ("HTTP/1.1 200 OK".to_string(), "ok\ntick_stalls 0\n".to_string()));
+        // Query strings from load balancers are ignored
+        assert_eq!(probe(&health, "/readyz?from=lb").await, ("HTTP/1.1 503 Service Unavailable".to_string(), "websocket listener not bound\n".to_string()));
+
+        health.listener_bound.store(true, Ordering::Relaxed);
+        health.world_ready.store(true, Ordering::Relaxed);
+        assert_eq!(probe(&health, "/readyz").await.0, "HTTP/1.1 200 OK");
+        assert_eq!(probe(&health, "/nope").await.0, "HTTP/1.1 404 Not Found");
+    }
+
+    #[tokio::test]
+    async fn world_needs_the_admin_bearer_token() {
+        let open = Arc::new(HealthState::new(0).with_admin_token(Some(String::new())));
+        assert_eq!(probe_with(&open, "/world", "Authorization: Bearer \r\n").await.0, "HTTP/1.1 401 Unauthorized", "no token configured");
+
+        let health = Arc::new(HealthState::new(0).with_admin_token(Some("s3cret".to_string())));
+        assert_eq!(probe(&health, "/world").await, ("HTTP/1.1 401 Unauthorized".to_string(), "admin token required\n".to_string()));
+        assert_eq!(probe_with(&health, "/world/p1", "Authorization: Bearer nope\r\n").await.0, "HTTP/1.1 401 Unauthorized");
+        assert_eq!(probe_with(&health, "/world", "authorization: Bearer s3cret\r\n").await.0, "HTTP/1.1 200 OK");
+        assert_eq!(probe_with(&health, "/world/p1", "Authorization: Bearer s3cret\r\n").await.0, "HTTP/1.1 404 Not Found");
+        // Probes stay open
+        assert_eq!(probe(&health, "/healthz").await.0, "HTTP/1.1 200 OK");
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn metrics_scrape_parses_as_prometheus_text() {
+        let health = Arc::new(HealthState::new(0));
+        health.publish_tick(3, 2);
+        for ms in [1, 3, 20] {
+            let sample = TickSample { total: Duration::from_millis(ms), clients: 2, ..Default::default() };
+            health.record_tick(&sample, 1000);
+        }
+        health.input_messages.fetch_add(7, Ordering::Relaxed);
+        health.rate_limited_frames.fetch_add(40, Ordering::Relaxed);
+        health.rate_limit_disconnects.fetch_add(1, Ordering::Relaxed);
+
+        let (status, body) = probe(&health, "/metrics").await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        // "name{labels} value" per sample line, comments skipped
+        let samples: BTreeMap<&str, f64> = body
+            .lines()
+            .filter(|l| !l.starts_with('#'))
+            .map(|l| {
+                let (name, value) = l.rsplit_once(' ').unwrap_or_else(|| panic!("bad line {:?}", l));
+                (name, value.parse().unwrap_or_else(|_| panic!("bad value {:?}", l)))
+            })
+            .collect();
+        assert!(body.contains("# TYPE physics_tick_seconds histogram\n"), "{}", body);
+
+        let buckets: Vec<f64> = TICK_SECONDS_BUCKETS
+            .iter()
+            .map(|le| samples[format!("physics_tick_seconds_bucket{{le=\"{}\"}}", le).as_str()])
+            .collect();
+        assert!(buckets.windows(2).all(|w| w[0] <= w[1]), "cumulative: {:?}", buckets);
+        assert_eq!(samples["physics_tick_seconds_bucket{le=\"0.002\"}"], 1.0);
+        assert_eq!(samples["physics_tick_seconds_bucket{le=\"0.004\"}"], 2.0);
+        assert_eq!(samples["physics_tick_seconds_bucket{le=\"+Inf\"}"], 3.0);
+        assert_eq!(samples["physics_tick_seconds_count"], 3.0);
+        assert!((samples["physics_tick_seconds_sum"] - 0.024).abs() < 1e-6);
+
+        assert_eq!(samples["connected_clients"], 2.0);
+        assert_eq!(samples["entities"], 2.0);
+        assert_eq!(samples["snapshot_bytes_sent_total"], 3000.0);
+        assert_eq!(samples["input_messages_total"], 7.0);
+        assert_eq!(samples["avenlab_rate_limited_frames_total"], 40.0);
+        assert_eq!(samples["avenlab_rate_limit_disconnects_total"], 1.0);
+    }
+}
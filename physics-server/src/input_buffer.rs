@@ -0,0 +1,221 @@
+// ==============================================================================
+// input_buffer.rs — PER-PLAYER INPUT BUFFER KEYED BY CLIENT-ESTIMATED TICK
+// ------------------------------------------------------------------------------
+// Untagged inputs apply at the next tick after they arrive, so a player on a
+// 150 ms connection always steers ~150 ms after what they saw. A client can
+// instead tag each input with the server tick it's meant for (its own
+// estimate, e.g. from snapshot ticks + half its RTT):
+//
+//   {"type":"input","throttle":1.0,"tick":1234}
+//
+// Tagged inputs wait in the player's buffer (BTreeMap tick → input) and are
+// applied at the start of that tick's step (SharedGameState::
+// apply_input_mailbox). A tick with no entry keeps the latest input applied,
+// as with untagged inputs. Several inputs for one tick merge in arrival order
+// (latest axis wins).
+//
+// Clock estimate off:
+//   late        : tick already simulated → applied right away (this tick);
+//                 counted in "late"
+//   too far     : more than MAX_LEAD_SECS ahead → not buffered, applied right
+//                 away like an untagged input; counted in "clock_off". A
+//                 client whose estimate is wildly wrong therefore drives as if
+//                 it never tagged, with no extra delay, instead of queueing
+//                 inputs for seconds or forever
+//   full buffer : more than MAX_BUFFERED ticks queued → the earliest is
+//                 applied right away to make room (nothing is dropped)
+//
+// Every tagged input updates the player's measured lead: how many ticks
+// before its tick it arrived (negative = late), smoothed. Snapshots to that
+// client carry it as "input_lead_ticks" so the client can tune its estimate:
+// aim for a small positive lead (1-2 ticks) to absorb jitter.
+// ==============================================================================
+
+use std::collections::BTreeMap;
+
+use serde_json::json;
+
+pub const MAX_LEAD_SECS: f32 = 0.5; // tagged further ahead than this = clock estimate off
+const MAX_BUFFERED: usize = 32;     // distinct ticks queued per player
+const LEAD_SMOOTHING: f32 = 0.1;    // EMA weight of each new lead sample
+
+/// One "input" message's axes (input_hold::AXIS_NAMES order, None = absent)
+/// and its prediction seq.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BufferedInput {
+    pub axes: [Option<f32>; 7],
+    pub seq: Option<u64>,
+}
+
+impl BufferedInput {
+    /// `later` arrived after self for the same tick.
+    fn merge(&mut self, later: BufferedInput) {
+        for (axis, value) in self.axes.iter_mut().zip(later.axes) {
+            if value.is_some() {
+                *axis = value;
+            }
+        }
+        self.seq = self.seq.max(later.seq);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct InputBuffer {
+    queue: BTreeMap<u64, BufferedInput>,
+    queued_seq: Option<u64>, // highest seq queued, for dropping duplicates on arrival
+    lead_ticks: Option<f32>, // smoothed measured lead
+    late: u64,
+    clock_off: u64,
+}
+
+impl InputBuffer {
+    /// Highest seq waiting in the buffer (None when nothing seq'd was queued).
+    pub fn queued_seq(&self) -> Option<u64> {
+        self.queued_seq
+    }
+
+    /// A tagged input for `target` arrived while tick `current` is about to
+    /// be simulated. Returns what has to be applied now, in order.
+    pub fn push(&mut self, target: u64, current: u64, max_lead: u64, input: BufferedInput) -> Vec<BufferedInput> {
+        let lead = target as f64 - current as f64;
+        let sample = lead.clamp(-(max_lead as f64), max_lead as f64) as f32;
+        self.lead_ticks = Some(match self.lead_ticks {
+            Some(avg) => avg + (sample - avg) * LEAD_SMOOTHING,
+            None => sample,
+        });
+
+        if target <= current {
+            if target < current {
+                self.late += 1;
+            }
+            return vec![input];
+        }
+        if target - current > max_lead {
+            self.clock_off += 1;
+            return vec![input];
+        }
+
+        self.queued_seq = self.queued_seq.max(input.seq);
+        self.queue.entry(target).and_modify(|queued| queued.merge(input)).or_insert(input);
+        let mut now = Vec::new();
+        while self.queue.len() > MAX_BUFFERED {
+            now.extend(self.queue.pop_first().map(|(_, input)| input));
+        }
+        now
+    }
+
+    /// Inputs for ticks up to `current`, in tick order.
+    pub fn due(&mut self, current: u64) -> Vec<BufferedInput> {
+        let later = self.queue.split_off(&(current + 1));
+        std::mem::replace(&mut self.queue, later).into_values().collect()
+    }
+
+    /// Everything queued, in tick order (respawn: the seq restarts).
+    pub fn flush(&mut self) -> Vec<BufferedInput> {
+        self.queued_seq = None;
+        std::mem::take(&mut self.queue).into_values().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Smoothed lead, 0.1 tick resolution (snapshot "input_lead_ticks"; f64
+    /// so it serializes without f32 noise digits).
+    pub fn lead_ticks(&self) -> Option<f64> {
+        self.lead_ticks.map(|lead| (lead as f64 * 10.0).round() / 10.0)
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "queued": self.queue.len(),
+            "lead_ticks": self.lead_ticks(),
+            "late": self.late,
+            "clock_off": self.clock_off,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn throttle(value: f32, seq: u64) -> BufferedInput {
+        BufferedInput { axes: [Some(value), None, None, None, None, None, None], seq: Some(seq) }
+    }
+
+    #[test]
+    fn a_late_input_applies_now_and_counts_as_late() {
+        let mut buffer = InputBuffer::default();
+        assert_eq!(buffer.push(8, 10, 30, throttle(1.0, 1)), vec![throttle(1.0, 1)]);
+        // Tagged for the tick about to run: on time, not late
+        assert_eq!(buffer.push(10, 10, 30, throttle(0.5, 2)), vec![throttle(0.5, 2)]);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.to_json()["late"], 1);
+        assert_eq!(buffer.queued_seq(), None, "nothing was queued");
+    }
+
+    #[test]
+    fn a_target_beyond_max_lead_applies_now_as_clock_off() {
+        let mut buffer = InputBuffer::default();
+        assert_eq!(buffer.push(41, 10, 30, throttle(1.0, 1)), vec![throttle(1.0, 1)]);
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.to_json()["clock_off"], 1);
+        // Right at max_lead still waits
+        assert!(buffer.push(40, 10, 30, throttle(1.0, 2)).is_empty());
+        assert_eq!(buffer.to_json()["queued"], 1);
+    }
+
+    #[test]
+    fn overflow_applies_the_earliest_ticks_first() {
+        let mut buffer = InputBuffer::default();
+        for i in 0..MAX_BUFFERED as u64 {
+            assert!(buffer.push(100 + i, 10, 200, throttle(0.0, i)).is_empty());
+        }
+        // A tick earlier than everything queued: it is the one evicted
+        assert_eq!(buffer.push(50, 10, 200, throttle(0.5, 99)), vec![throttle(0.5, 99)]);
+        // A later one evicts the earliest queued
+        assert_eq!(buffer.push(200, 10, 200, throttle(1.0, 100)), vec![throttle(0.0, 0)]);
+        assert_eq!(buffer.to_json()["queued"], MAX_BUFFERED);
+        assert_eq!(buffer.queued_seq(), Some(100));
+    }
+
+    #[test]
+    fn two_inputs_for_one_tick_merge_latest_axis_wins() {
+        let mut buffer = InputBuffer::default();
+        buffer.push(12, 10, 30, BufferedInput { axes: [Some(1.0), Some(0.5), None, None, None, None, None], seq: Some(3) });
+        buffer.push(12, 10, 30, BufferedInput { axes: [None, Some(-0.5), Some(1.0), None, None, None, None], seq: Some(4) });
+        assert_eq!(buffer.due(11), vec![]);
+        assert_eq!(
+            buffer.due(12),
+            vec![BufferedInput { axes: [Some(1.0), Some(-0.5), Some(1.0), None, None, None, None], seq: Some(4) }]
+        );
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn due_returns_inputs_in_tick_order() {
+        let mut buffer = InputBuffer::default();
+        for (tick, seq) in [(15, 1), (12, 2), (14, 3), (13, 4)] {
+            buffer.push(tick, 10, 30, throttle(tick as f32, seq));
+        }
+        assert_eq!(buffer.due(14), vec![throttle(12.0, 2), throttle(13.0, 4), throttle(14.0, 3)]);
+        assert_eq!(buffer.due(20), vec![throttle(15.0, 1)]);
+        assert!(buffer.due(30).is_empty());
+    }
+
+    #[test]
+    fn lead_ticks_is_a_clamped_moving_average() {
+        let mut buffer = InputBuffer::default();
+        assert_eq!(buffer.lead_ticks(), None);
+        buffer.push(14, 10, 30, throttle(1.0, 1));
+        assert_eq!(buffer.lead_ticks(), Some(4.0), "first sample taken as is");
+        buffer.push(15, 15, 30, throttle(1.0, 2));
+        assert_eq!(buffer.lead_ticks(), Some(3.6)); // 4 + (0 - 4) * 0.1
+        // A wild estimate counts only as far as max_lead
+        buffer.push(10_000, 15, 30, throttle(1.0, 3));
+        assert_eq!(buffer.lead_ticks(), Some(6.2)); // 3.6 + (30 - 3.6) * 0.1
+        buffer.push(0, 40, 30, throttle(1.0, 4));
+        assert_eq!(buffer.lead_ticks(), Some(2.6)); // 6.24 + (-30 - 6.24) * 0.1
+    }
+}
@@ -0,0 +1,264 @@
+// ==============================================================================
+// input_hold.rs — PER-AXIS INPUT HOLD / NEUTRAL RETURN
+// ------------------------------------------------------------------------------
+// Input messages may carry only the axes that changed; absent axes keep their
+// last value. What happens when an axis stops being updated is chosen per
+// axis in the hello handshake:
+//
+//   {"type":"hello","input_hold":{"steer":"decay_to_neutral","decay_secs":0.5}}
+//
+//   latch            : keep the last value until the client sends another
+//   decay_to_neutral : after INPUT_STALE_SECS without an update, return to 0
+//                      exponentially (time constant decay_secs)
+//   require_full     : input messages missing this axis are rejected
+//                      ({"type":"error","reason":"partial_input"})
+//
+// Defaults: throttle + brake decay (a lost "throttle: 0" must not pin the
+// accelerator), everything else latches. Clients that only send changes
+// while an axis is held should latch that axis.
+//
+// Whatever the modes, a client that sends no input message at all for its
+// vehicle's config.input_timeout_s (a frozen game loop, a half-dead socket)
+// stops driving: throttle and steer decay to 0 like decay_to_neutral axes and
+// the brake is held at no less than STALE_BRAKE until input resumes (the
+// first message after that releases it unless it sets the brake itself). The
+// debug overlay's "input_stale" field is the silence so far once past the
+// timeout (null otherwise).
+//
+// Decay runs once per server tick on wall-clock time, before inputs are
+// recorded, so replays see the decayed values and never decay themselves.
+// ==============================================================================
+
+use serde_json::{Map, Value};
+
+pub const AXIS_NAMES: [&str; 7] = ["throttle", "steer", "brake", "ascend", "pitch", "yaw", "roll"];
+
+pub const INPUT_STALE_SECS: f32 = 0.1;   // ~6 missed packets of a 60 Hz stream before decay starts
+const DEFAULT_DECAY_SECS: f32 = 0.25;    // time constant of the return to neutral
+const DECAY_SECS_RANGE: [f32; 2] = [0.02, 5.0];
+const NEUTRAL_SNAP: f32 = 1e-3;          // below this, decayed axes snap to exactly 0
+const STALE_BRAKE: f32 = 0.3;            // gentle brake while a silent client's car rolls out
+const THROTTLE: usize = 0;
+const STEER: usize = 1;
+const BRAKE: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AxisHold {
+    Latch,
+    DecayToNeutral,
+    RequireFull,
+}
+
+impl AxisHold {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AxisHold::Latch => "latch",
+            AxisHold::DecayToNeutral => "decay_to_neutral",
+            AxisHold::RequireFull => "require_full",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "latch" => Some(AxisHold::Latch),
+            "decay_to_neutral" => Some(AxisHold::DecayToNeutral),
+            "require_full" => Some(AxisHold::RequireFull),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct InputHold {
+    pub modes: [AxisHold; 7], // AXIS_NAMES order
+    pub decay_secs: f32,
+    stale_secs: [f32; 7],     // since each axis was last sent
+    silent_secs: f32,         // since the last accepted input message
+}
+
+impl Default for InputHold {
+    fn default() -> Self {
+        let mut modes = [AxisHold::Latch; 7];
+        modes[0] = AxisHold::DecayToNeutral; // throttle
+        modes[2] = AxisHold::DecayToNeutral; // brake
+        Self { modes, decay_secs: DEFAULT_DECAY_SECS, stale_secs: [0.0; 7], silent_secs: 0.0 }
+    }
+}
+
+impl InputHold {
+    /// Apply a hello `input_hold` object on top of the current modes.
+    pub fn with_overrides(mut self, overrides: &Value) -> Result<Self, &'static str> {
+        let obj = overrides.as_object().ok_or("invalid_input_hold")?;
+        for (key, value) in obj {
+            if key == "decay_secs" {
+                let secs = value.as_f64().ok_or("invalid_input_hold")? as f32;
+                if !(DECAY_SECS_RANGE[0]..=DECAY_SECS_RANGE[1]).contains(&secs) {
+                    return Err("invalid_input_hold");
+                }
+                self.decay_secs = secs;
+                continue;
+            }
+            let axis = AXIS_NAMES.iter().position(|a| a == key).ok_or("invalid_input_hold")?;
+            self.modes[axis] = value.as_str().and_then(AxisHold::parse).ok_or("invalid_input_hold")?;
+        }
+        Ok(self)
+    }
+
+    /// Modes as sent back in hello_ack.
+    pub fn to_json(self) -> Value {
+        let mut obj: Map<String, Value> = AXIS_NAMES
+            .iter()
+            .zip(self.modes)
+            .map(|(name, mode)| (name.to_string(), Value::from(mode.as_str())))
+            .collect();
+        obj.insert("decay_secs".to_string(), Value::from(self.decay_secs));
+        Value::Object(obj)
+    }
+
+    /// Check an incoming message (None = axis absent) and mark the present
+    /// axes fresh. Rejected messages change nothing.
+    pub fn accept(&mut self, axes: &[Option<f32>; 7]) -> Result<(), &'static str> {
+        let partial = self.modes.iter().zip(axes).any(|(mode, v)| *mode == AxisHold::RequireFull && v.is_none());
+        if partial {
+            return Err("partial_input");
+        }
+        for (stale, v) in self.stale_secs.iter_mut().zip(axes) {
+            if v.is_some() {
+                *stale = 0.0;
+            }
+        }
+        self.silent_secs = 0.0;
+        Ok(())
+    }
+
+    /// One tick of wall-clock time: age every axis, pull stale decaying ones
+    /// toward 0; past `timeout` seconds without any message, stop the car.
+    pub fn decay(&mut self, values: [&mut f32; 7], dt: f32, timeout: f32) {
+        let k = (-dt / self.decay_secs).exp();
+        self.silent_secs += dt;
+        let silent = self.silent_secs > timeout;
+        for (axis, ((value, stale), mode)) in values.into_iter().zip(self.stale_secs.iter_mut()).zip(self.modes).enumerate() {
+            *stale += dt;
+            if silent && axis == BRAKE {
+                *value = value.max(STALE_BRAKE);
+                continue;
+            }
+            let decays = mode == AxisHold::DecayToNeutral || (silent && (axis == THROTTLE || axis == STEER));
+            if !decays || *stale <= INPUT_STALE_SECS {
+                continue;
+            }
+            *value *= k;
+            if value.abs() < NEUTRAL_SNAP {
+                *value = 0.0;
+            }
+        }
+    }
+
+    /// Seconds without an input message, once past `timeout` (debug overlay).
+    pub fn silent_for(&self, timeout: f32) -> Option<f32> {
+        (self.silent_secs > timeout).then_some(self.silent_secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const DT: f32 = 1.0 / 60.0;
+    const TIMEOUT: f32 = 2.0;
+
+    /// Full input (steer 0.5, throttle 0.8) for a second, then `seconds` of
+    /// messages carrying only the throttle. Returns the axes at the end.
+    fn steer_stops_updating(hold: &mut InputHold, seconds: f32) -> [f32; 7] {
+        let mut values = [0.0; 7];
+        let full = [Some(0.8), Some(0.5), Some(0.0), Some(0.0), Some(0.0), Some(0.0), Some(0.0)];
+        let partial = [Some(0.8), None, None, None, None, None, None];
+        for tick in 0..60 + (seconds / DT).round() as usize {
+            let axes = if tick < 60 { full } else { partial };
+            if hold.accept(&axes).is_ok() {
+                for (value, v) in values.iter_mut().zip(axes) {
+                    if let Some(v) = v {
+                        *value = v;
+                    }
+                }
+            }
+            let [a, b, c, d, e, f, g] = &mut values;
+            hold.decay([a, b, c, d, e, f, g], DT, TIMEOUT);
+        }
+        values
+    }
+
+    fn with_steer(mode: &str) -> InputHold {
+        InputHold::default().with_overrides(&json!({ "steer": mode, "brake": "latch" })).unwrap()
+    }
+
+    #[test]
+    fn a_latched_steer_keeps_its_last_value() {
+        let values = steer_stops_updating(&mut with_steer("latch"), 1.0);
+        assert_eq!(values[STEER], 0.5);
+        assert_eq!(values[THROTTLE], 0.8, "throttle is still being sent");
+    }
+
+    #[test]
+    fn a_decaying_steer_returns_to_neutral_once_stale() {
+        // Still fresh: untouched
+        let values = steer_stops_updating(&mut with_steer("decay_to_neutral"), INPUT_STALE_SECS - DT);
+        assert_eq!(values[STEER], 0.5);
+
+        // One time constant past the stale threshold: down to ~1/e
+        let values = steer_stops_updating(&mut with_steer("decay_to_neutral"), INPUT_STALE_SECS + DEFAULT_DECAY_SECS);
+        let expected = 0.5 * (-1.0f32).exp();
+        assert!((values[STEER] - expected).abs() < 0.03, "steer {} vs {}", values[STEER], expected);
+
+        // And eventually exactly 0
+        let values = steer_stops_updating(&mut with_steer("decay_to_neutral"), 2.5);
+        assert_eq!(values[STEER], 0.0);
+        assert_eq!(values[THROTTLE], 0.8);
+    }
+
+    #[test]
+    fn require_full_rejects_messages_missing_the_steer() {
+        let mut hold = with_steer("require_full");
+        assert_eq!(hold.accept(&[Some(0.8), None, Some(0.0), Some(0.0), Some(0.0), Some(0.0), Some(0.0)]), Err("partial_input"));
+        let values = steer_stops_updating(&mut hold, 1.0);
+        // Dropped whole: the steer holds, the unrefreshed throttle decays
+        assert_eq!(values[STEER], 0.5);
+        assert!(values[THROTTLE] < 0.05);
+    }
+
+    #[test]
+    fn defaults_decay_the_pedals_and_latch_the_rest() {
+        let hold = InputHold::default();
+        assert_eq!(hold.modes[THROTTLE], AxisHold::DecayToNeutral);
+        assert_eq!(hold.modes[BRAKE], AxisHold::DecayToNeutral);
+        assert!(hold.modes.iter().enumerate().all(|(axis, mode)| axis == THROTTLE || axis == BRAKE || *mode == AxisHold::Latch));
+
+        let ack = hold.with_overrides(&json!({ "yaw": "require_full", "decay_secs": 0.5 })).unwrap().to_json();
+        assert_eq!(ack["yaw"], "require_full");
+        assert_eq!(ack["steer"], "latch");
+        assert_eq!(ack["decay_secs"], 0.5);
+
+        for bad in [json!({ "steer": "drift" }), json!({ "wings": "latch" }), json!({ "decay_secs": 10.0 }), json!("latch")] {
+            assert_eq!(hold.with_overrides(&bad).err(), Some("invalid_input_hold"), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn a_silent_client_rolls_to_a_stop_whatever_the_modes() {
+        let mut hold = with_steer("latch");
+        let mut values = [0.8, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0];
+        hold.accept(&values.map(Some)).unwrap();
+        for _ in 0..((TIMEOUT + 2.5) / DT) as usize {
+            let [a, b, c, d, e, f, g] = &mut values;
+            hold.decay([a, b, c, d, e, f, g], DT, TIMEOUT);
+        }
+        assert_eq!(values[THROTTLE], 0.0);
+        assert_eq!(values[STEER], 0.0);
+        assert_eq!(values[BRAKE], STALE_BRAKE);
+        assert!(hold.silent_for(TIMEOUT).unwrap() > TIMEOUT);
+
+        hold.accept(&[Some(0.0), None, None, None, None, None, None]).unwrap();
+        assert_eq!(hold.silent_for(TIMEOUT), None);
+    }
+}
@@ -0,0 +1,345 @@
+// ==============================================================================
+// input_sanity.rs — SERVER-SIDE SANITY CHECKS ON CLIENT INPUT (ANTI-CHEAT)
+// ------------------------------------------------------------------------------
+// One InputSanity per connection, consulted before anything a client sends
+// reaches the vehicle. Violations are counted per category; non-finite input
+// (opt-in) and admin commands without admin rights can get a client kicked,
+// operators review the rest.
+//
+//   range       axis outside its range (throttle/steer/ascend/pitch/yaw/roll
+//               -1..1, brake 0..1): clamped
+//   non_finite  NaN / ±inf axis (a huge literal like 1e300 overflows f32):
+//               dropped, i.e. keeps its last value. net.rs logs the client
+//               once and disconnects it after AVENLAB_NONFINITE_KICK of them
+//   rate        more than max_input_hz input messages in one second: the
+//               excess messages are dropped (inputs latch, so nothing is lost)
+//   steer_osc   full-lock steer flipping sign on OSCILLATION_FLIPS consecutive
+//               messages, each within OSCILLATION_MAX_GAP of the last: flagged
+//               once per run, inputs still applied
+//   tune        "adjust" message with a parameter the room doesn't whitelist
+//               or a step beyond the room's limit (see TuneRules): rejected
+//   admin       admin command from a connection that didn't join as admin
+//               and sent no valid token: rejected; net.rs disconnects it
+//               after AVENLAB_ADMIN_REJECT_KICK of them
+//
+// Admin events: {"type":"admin_event","event":"input_sanity","player_id":..,
+//   "reason":"steer_osc"|"threshold","counters":{..}} to clients subscribed
+//   with {"type":"admin","cmd":"subscribe_events","token":..}. Sent on every
+//   flagged oscillation run, and when the violation total reaches
+//   REPORT_THRESHOLD, then twice that, four times, .. (a flood stays quiet).
+// Stats: the "stats" reply carries the same counters under "sanity".
+//
+// Env:
+// - AVENLAB_MAX_INPUT_HZ        input messages per second (default 240)
+// - AVENLAB_LOCKED_SETUP_ROOMS  rooms where "adjust" is disabled, e.g. "0,3"
+// - AVENLAB_NONFINITE_KICK      non-finite axes before a disconnect (default
+//                               0 = never)
+// - AVENLAB_ADMIN_REJECT_KICK   rejected admin commands before a disconnect
+//                               (default 10, 0 = never)
+// ==============================================================================
+
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+
+const DEFAULT_MAX_INPUT_HZ: u32 = 240;        // 4x a 60 Hz client; 144 Hz displays stay clear
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+const RANGE_TOLERANCE: f32 = 1e-3;            // float noise at the limits is clamped, not counted
+const FULL_LOCK: f32 = 0.9;                   // |steer| counted as full lock
+const OSCILLATION_MAX_GAP: Duration = Duration::from_millis(50);
+pub const OSCILLATION_FLIPS: u32 = 30;        // ~0.5 s of flipping every 60 Hz tick
+pub const REPORT_THRESHOLD: u64 = 100;        // violations before the first admin event
+const DEFAULT_MAX_BRAKE_BIAS_STEP: f32 = 0.05;
+const DEFAULT_ADMIN_REJECT_KICK: u64 = 10;    // an operator fumbling the token stays well clear
+
+/// Axis ranges in input_hold::AXIS_NAMES order.
+pub const AXIS_RANGES: [(f32, f32); 7] = [
+    (-1.0, 1.0), // throttle
+    (-1.0, 1.0), // steer
+    (0.0, 1.0),  // brake
+    (-1.0, 1.0), // ascend
+    (-1.0, 1.0), // pitch
+    (-1.0, 1.0), // yaw
+    (-1.0, 1.0), // roll
+];
+const STEER: usize = 1;
+
+// ==========================================================
+// Tune whitelist (per room, RoomSettings::tune)
+// ==========================================================
+#[derive(Debug, Clone)]
+pub struct TuneRules {
+    pub params: Vec<(String, f32)>, // "adjust" field → largest |step| per message
+}
+
+impl Default for TuneRules {
+    fn default() -> Self {
+        Self { params: vec![("brake_bias_delta".to_string(), DEFAULT_MAX_BRAKE_BIAS_STEP)] }
+    }
+}
+
+impl TuneRules {
+    pub fn locked() -> Self {
+        Self { params: Vec::new() }
+    }
+
+    fn max_step(&self, param: &str) -> Option<f32> {
+        self.params.iter().find(|(p, _)| p == param).map(|(_, max)| *max)
+    }
+}
+
+/// "0,3" → rooms where tuning is off (malformed entries are skipped)
+pub fn parse_locked_rooms(text: &str) -> Vec<usize> {
+    text.split(',').filter_map(|r| r.trim().parse().ok()).collect()
+}
+
+// ==========================================================
+// Per-connection checker
+// ==========================================================
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SanityCounters {
+    pub range: u64,
+    pub non_finite: u64,
+    pub rate: u64,
+    pub steer_osc: u64,
+    pub tune: u64,
+    pub admin: u64,
+}
+
+impl SanityCounters {
+    pub fn total(&self) -> u64 {
+        self.range + self.non_finite + self.rate + self.steer_osc + self.tune + self.admin
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "range": self.range,
+            "non_finite": self.non_finite,
+            "rate": self.rate,
+            "steer_osc": self.steer_osc,
+            "tune": self.tune,
+            "admin": self.admin,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct InputSanity {
+    pub counters: SanityCounters,
+    max_input_hz: u32,
+    max_non_finite: u64,                 // non-finite axes before kick() says so (0 = never)
+    max_admin_rejects: u64,              // rejected admin commands before admin_kick() (0 = never)
+    window: Option<(Instant, u32)>,      // (window start, messages in it)
+    last_steer: Option<(f32, Instant)>,
+    flips: u32,                          // consecutive full-lock sign flips
+    next_report: u64,                    // counters.total() that triggers the next threshold report
+    pending: Option<&'static str>,       // admin event reason waiting for take_report
+}
+
+impl Default for InputSanity {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_INPUT_HZ)
+    }
+}
+
+impl InputSanity {
+    pub fn new(max_input_hz: u32) -> Self {
+        Self {
+            counters: SanityCounters::default(),
+            max_input_hz: max_input_hz.max(1),
+            max_non_finite: 0,
+            max_admin_rejects: DEFAULT_ADMIN_REJECT_KICK,
+            window: None,
+            last_steer: None,
+            flips: 0,
+            next_report: REPORT_THRESHOLD,
+            pending: None,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let hz = std::env::var("AVENLAB_MAX_INPUT_HZ").ok().and_then(|v| v.parse().ok());
+        let kick = std::env::var("AVENLAB_NONFINITE_KICK").ok().and_then(|v| v.parse().ok());
+        let admin_kick = std::env::var("AVENLAB_ADMIN_REJECT_KICK").ok().and_then(|v| v.parse().ok());
+        let mut sanity = Self::new(hz.unwrap_or(DEFAULT_MAX_INPUT_HZ)).with_non_finite_kick(kick.unwrap_or(0));
+        sanity.max_admin_rejects = admin_kick.unwrap_or(DEFAULT_ADMIN_REJECT_KICK);
+        sanity
+    }
+
+    pub fn with_non_finite_kick(mut self, max_non_finite: u64) -> Self {
+        self.max_non_finite = max_non_finite;
+        self
+    }
+
+    /// True once the client has sent max_non_finite non-finite axes.
+    pub fn kick(&self) -> bool {
+        self.max_non_finite > 0 && self.counters.non_finite >= self.max_non_finite
+    }
+
+    /// Sanitized axes to apply, or None if the message is dropped (rate).
+    pub fn check_input(&mut self, axes: [Option<f32>; 7], now: Instant) -> Option<[Option<f32>; 7]> {
+        // Rate: fixed one-second windows
+        let (start, count) = self.window.get_or_insert((now, 0));
+        if now.duration_since(*start) >= RATE_WINDOW {
+            *start = now;
+            *count = 0;
+        }
+        *count += 1;
+        if *count > self.max_input_hz {
+            self.count(|c| &mut c.rate);
+            return None;
+        }
+
+        // Range: clamp, drop non-finite
+        let mut out = axes;
+        for (axis, (min, max)) in out.iter_mut().zip(AXIS_RANGES) {
+            let Some(value) = *axis else { continue };
+            if !value.is_finite() {
+                *axis = None;
+                self.count(|c| &mut c.non_finite);
+            } else if value < min - RANGE_TOLERANCE || value > max + RANGE_TOLERANCE {
+                *axis = Some(value.clamp(min, max));
+                self.count(|c| &mut c.range);
+            } else {
+                *axis = Some(value.clamp(min, max));
+            }
+        }
+
+        // Pattern: machine-gun full-lock steer flips
+        if let Some(steer) = out[STEER] {
+            let flip = self.last_steer.is_some_and(|(last, at)| {
+                steer.abs() >= FULL_LOCK
+                    && last.abs() >= FULL_LOCK
+                    && steer.signum() != last.signum()
+                    && now.duration_since(at) <= OSCILLATION_MAX_GAP
+            });
+            self.flips = if flip { self.flips + 1 } else { 0 };
+            if self.flips == OSCILLATION_FLIPS {
+                self.count(|c| &mut c.steer_osc);
+                self.pending = Some("steer_osc");
+            }
+            self.last_steer = Some((steer, now));
+        }
+
+        Some(out)
+    }
+
+    /// Validate an "adjust" message against the room's whitelist: `params`
+    /// are the message's fields (besides "type"). Returns the brake bias step.
+    pub fn check_adjust(&mut self, rules: &TuneRules, params: &[String], brake_bias_delta: Option<f32>) -> Result<f32, &'static str> {
+        if params.iter().any(|p| rules.max_step(p).is_none()) {
+            self.count(|c| &mut c.tune);
+            return Err("tune_not_allowed");
+        }
+        let Some(delta) = brake_bias_delta else { return Ok(0.0) };
+        let max = rules.max_step("brake_bias_delta").unwrap_or(0.0);
+        if !delta.is_finite() || delta.abs() > max {
+            self.count(|c| &mut c.tune);
+            return Err("tune_out_of_range");
+        }
+        Ok(delta)
+    }
+
+    /// An admin command was refused (no admin rights).
+    pub fn reject_admin(&mut self) {
+        self.count(|c| &mut c.admin);
+    }
+
+    /// True once the client has had max_admin_rejects admin commands refused.
+    pub fn admin_kick(&self) -> bool {
+        self.max_admin_rejects > 0 && self.counters.admin >= self.max_admin_rejects
+    }
+
+    /// Admin event body if a threshold was crossed since the last call.
+    pub fn take_report(&mut self) -> Option<serde_json::Value> {
+        let reason = self.pending.take()?;
+        Some(json!({ "reason": reason, "counters": self.counters.to_json() }))
+    }
+
+    fn count(&mut self, counter: impl FnOnce(&mut SanityCounters) -> &mut u64) {
+        *counter(&mut self.counters) += 1;
+        if self.counters.total() >= self.next_report {
+            self.next_report *= 2;
+            self.pending.get_or_insert("threshold");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steer(value: f32) -> [Option<f32>; 7] {
+        [Some(0.5), Some(value), Some(0.0), None, None, None, None]
+    }
+
+    /// `hz` input messages per second for `secs` from `start`, steer from
+    /// `steer_at(t)`. Returns how many were applied.
+    fn stream(sanity: &mut InputSanity, start: Instant, hz: f32, secs: f32, steer_at: impl Fn(f32) -> f32) -> usize {
+        let mut applied = 0;
+        for i in 0..(hz * secs) as u32 {
+            let t = i as f32 / hz;
+            let now = start + Duration::from_secs_f32(t);
+            applied += sanity.check_input(steer(steer_at(t)), now).is_some() as usize;
+        }
+        applied
+    }
+
+    #[test]
+    fn out_of_range_axes_are_clamped_and_counted() {
+        let mut sanity = InputSanity::default();
+        let now = Instant::now();
+        let out = sanity.check_input([Some(3.0), Some(-1.0005), Some(-0.5), Some(f32::NAN), Some(2.0), None, Some(f32::INFINITY)], now).unwrap();
+        assert_eq!(out, [Some(1.0), Some(-1.0), Some(0.0), None, Some(1.0), None, None]);
+        // -1.0005 is float noise at the limit, brake -0.5 is out of its 0..1
+        assert_eq!(sanity.counters, SanityCounters { range: 3, non_finite: 2, ..Default::default() });
+        assert!(sanity.take_report().is_none(), "below the report threshold");
+    }
+
+    #[test]
+    fn machine_gun_steer_is_flagged_once_with_an_admin_event() {
+        let mut sanity = InputSanity::default();
+        let applied = stream(&mut sanity, Instant::now(), 60.0, 2.0, |t| if ((t * 60.0).round() as u32).is_multiple_of(2) { 1.0 } else { -1.0 });
+        assert_eq!(applied, 120, "flagged, not dropped");
+        assert_eq!(sanity.counters.steer_osc, 1, "one flag per run");
+        let report = sanity.take_report().expect("admin event");
+        assert_eq!(report["reason"], "steer_osc");
+        assert_eq!(report["counters"]["steer_osc"], 1);
+        assert!(sanity.take_report().is_none());
+    }
+
+    #[test]
+    fn a_1000_hz_stream_is_cut_to_the_rate_limit() {
+        let mut sanity = InputSanity::default();
+        let applied = stream(&mut sanity, Instant::now(), 1000.0, 3.0, |t| (t * 2.0).sin());
+        assert_eq!(applied, 3 * DEFAULT_MAX_INPUT_HZ as usize);
+        assert_eq!(sanity.counters.rate, 3 * (1000 - DEFAULT_MAX_INPUT_HZ as u64));
+        assert_eq!(sanity.take_report().unwrap()["reason"], "threshold");
+    }
+
+    #[test]
+    fn legitimate_traffic_is_never_flagged() {
+        let mut sanity = InputSanity::default();
+        // 144 Hz analog steering, keyboard taps, 10 Hz full-lock slaloms
+        let start = Instant::now();
+        let secs = |s: u64| start + Duration::from_secs(s);
+        stream(&mut sanity, start, 144.0, 20.0, |t| (t * 1.3).sin() * 0.8);
+        stream(&mut sanity, secs(21), 60.0, 20.0, |t| if ((t * 3.0) as u32).is_multiple_of(3) { 1.0 } else { 0.0 });
+        stream(&mut sanity, secs(42), 60.0, 20.0, |t| if ((t * 10.0) as u32).is_multiple_of(2) { 1.0 } else { -1.0 });
+        assert_eq!(sanity.counters.total(), 0);
+        assert!(sanity.take_report().is_none());
+    }
+
+    #[test]
+    fn adjust_only_takes_the_rooms_whitelisted_steps() {
+        let mut sanity = InputSanity::default();
+        let bias = ["brake_bias_delta".to_string()];
+        assert_eq!(sanity.check_adjust(&TuneRules::default(), &bias, Some(0.05)), Ok(0.05));
+        assert_eq!(sanity.check_adjust(&TuneRules::default(), &bias, Some(0.2)), Err("tune_out_of_range"));
+        assert_eq!(sanity.check_adjust(&TuneRules::default(), &bias, Some(f32::NAN)), Err("tune_out_of_range"));
+        assert_eq!(sanity.check_adjust(&TuneRules::default(), &["spring_rate".to_string()], None), Err("tune_not_allowed"));
+        assert_eq!(sanity.check_adjust(&TuneRules::locked(), &bias, Some(0.01)), Err("tune_not_allowed"));
+        assert_eq!(sanity.counters.tune, 4);
+        assert_eq!(parse_locked_rooms("0, 3,x"), [0, 3]);
+    }
+}
@@ -0,0 +1,370 @@
+// ==============================================================================
+// input_trace.rs — RECORDED INPUT INJECTION (A/B SETUP COMPARISONS)
+// ------------------------------------------------------------------------------
+// An input trace is a list of per-tick axis keyframes, each held until the
+// next one. Attached to an entity (PhysicsWorld::attach_input_trace) it
+// replaces that entity's live inputs until it runs out: network input for the
+// entity is ignored meanwhile, and once per tick the trace's axes are posted
+// to the entity's input mailbox (SharedGameState::post_trace_inputs), so they
+// go through the same hold, clamp and decay path as network input. Any number
+// of entities can play the same trace at once; attaching also turns on their
+// telemetry ring (telemetry.rs).
+//
+// Sources:
+//   *.csv    tick,throttle,steer,brake[,ascend,pitch,yaw,roll]
+//            optional header naming the columns (any order, missing = 0);
+//            ticks are rebased so the first row is tick 0, the last row ends it
+//   *.jsonl  an AVENLAB_RECORD recording: one entity's input records, from its
+//            first input until its despawn (or the last snapshot)
+//
+//   admin    : {"type":"admin","cmd":"attach_trace","player_id":..,"path":..,
+//               "entity":<recorded id, optional>,"token":..}
+//              {"type":"admin","cmd":"detach_trace","player_id":..,"token":..}
+//   headless : physics-server --ab <trace> [--vehicle gt86] [--set mass=1400,..]
+//                             [--entity <recorded id>] [--out ab_telemetry]
+//              runs the base config and the modified one side by side on the
+//              same trace, writes <out>_a.csv and <out>_b.csv (one row per tick)
+// ==============================================================================
+
+use std::sync::Arc;
+
+use crate::input_hold::AXIS_NAMES;
+use crate::physics::{PhysicsWorld, vehicle_config_by_name};
+use crate::recording::{Record, load_records};
+use crate::snapshot::ClientTx;
+use crate::state::{EntityType, SharedGameState};
+use crate::sweep::set_param;
+use crate::tick_rate::TickRate;
+
+const AB_DT: f32 = 1.0 / 60.0;
+const AB_SETTLE_TICKS: u32 = 60;                 // let both cars land before the trace starts
+const AB_SPAWNS: [[f32; 3]; 2] = [[-10.0, 0.0, 5.0], [10.0, 0.0, 5.0]]; // chassis never meet each other
+const AB_IDS: [&str; 2] = ["a", "b"];
+const DEFAULT_AB_OUTPUT: &str = "ab_telemetry";
+
+#[derive(Debug, Clone)]
+pub struct InputTrace {
+    keyframes: Vec<(u64, [f32; 7])>, // (tick from start, axes in AXIS_NAMES order), ascending
+    ticks: u64,                      // playback length
+}
+
+impl InputTrace {
+    fn new(mut keyframes: Vec<(u64, [f32; 7])>, end: u64) -> Result<Self, String> {
+        keyframes.sort_by_key(|(tick, _)| *tick);
+        let Some(&(first, _)) = keyframes.first() else {
+            return Err("trace has no input rows".to_string());
+        };
+        for (tick, _) in keyframes.iter_mut() {
+            *tick -= first;
+        }
+        if keyframes.iter().any(|(_, axes)| axes.iter().any(|a| !a.is_finite())) {
+            return Err("trace axes must be finite".to_string());
+        }
+        let last = keyframes.last().map_or(0, |(tick, _)| *tick);
+        Ok(Self { keyframes, ticks: end.saturating_sub(first).max(last + 1) })
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    pub fn from_csv(text: &str) -> Result<Self, String> {
+        let mut columns: Vec<Option<usize>> = Vec::new(); // per column: None = tick, Some(axis)
+        let mut keyframes = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            if columns.is_empty() {
+                if fields[0].parse::<f64>().is_err() {
+                    columns = csv_header(&fields)?;
+                    continue;
+                }
+                columns = std::iter::once(None).chain((0..AXIS_NAMES.len()).map(Some)).collect();
+            }
+
+            let mut tick = None;
+            let mut axes = [0.0; 7];
+            for (field, column) in fields.iter().zip(&columns) {
+                match column {
+                    None => tick = field.parse::<u64>().ok(),
+                    Some(axis) => {
+                        axes[*axis] = field.parse::<f32>().map_err(|_| format!("line {}: bad value '{}'", i + 1, field))?;
+                    }
+                }
+            }
+            let tick = tick.ok_or(format!("line {}: missing or bad tick", i + 1))?;
+            keyframes.push((tick, axes));
+        }
+        Self::new(keyframes, 0)
+    }
+
+    /// One entity's inputs from a recording (`entity` None = the first one
+    /// with input records).
+    pub fn from_records(records: &[Record], entity: Option<&str>) -> Result<Self, String> {
+        let entity = match entity {
+            Some(id) => id.to_string(),
+            None => records
+                .iter()
+                .find_map(|r| match r {
+                    Record::Input { entity_id, .. } => Some(entity_id.clone()),
+                    _ => None,
+                })
+                .ok_or("recording has no input records")?,
+        };
+
+        let mut keyframes = Vec::new();
+        let mut end = None;
+        let mut last_snapshot = 0;
+        for record in records {
+            match record {
+                Record::Input { tick, entity_id, axes } if *entity_id == entity && end.is_none() => {
+                    let a = axes;
+                    keyframes.push((*tick, [a.throttle, a.steer, a.brake, a.ascend, a.pitch, a.yaw, a.roll]));
+                }
+                Record::Despawn { tick, entity_id } if *entity_id == entity && !keyframes.is_empty() => {
+                    end.get_or_insert(*tick);
+                }
+                Record::Snapshot { tick, .. } => last_snapshot = *tick,
+                _ => {}
+            }
+        }
+        if keyframes.is_empty() {
+            return Err(format!("no input records for entity '{}'", entity));
+        }
+        Self::new(keyframes, end.unwrap_or(last_snapshot + 1))
+    }
+
+    /// Load by extension: .csv, anything else is read as a recording.
+    pub fn load(path: &str, entity: Option<&str>) -> Result<Self, String> {
+        if path.ends_with(".csv") {
+            let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+            Self::from_csv(&text).map_err(|e| format!("{}: {}", path, e))
+        } else {
+            Self::from_records(&load_records(path)?, entity)
+        }
+    }
+}
+
+fn csv_header(fields: &[&str]) -> Result<Vec<Option<usize>>, String> {
+    let columns: Vec<Option<usize>> = fields
+        .iter()
+        .map(|name| match *name {
+            "tick" => Ok(None),
+            _ => AXIS_NAMES.iter().position(|a| a == name).map(Some).ok_or(format!("unknown column '{}'", name)),
+        })
+        .collect::<Result<_, _>>()?;
+    if columns.iter().filter(|c| c.is_none()).count() != 1 {
+        return Err("header needs exactly one 'tick' column".to_string());
+    }
+    Ok(columns)
+}
+
+// ==========================================================
+// Playback (one per entity, shared trace)
+// ==========================================================
+#[derive(Debug, Clone)]
+pub struct TracePlayback {
+    trace: Arc<InputTrace>,
+    tick: u64,
+    cursor: usize, // keyframe in effect
+}
+
+impl TracePlayback {
+    pub fn new(trace: Arc<InputTrace>) -> Self {
+        Self { trace, tick: 0, cursor: 0 }
+    }
+
+    /// Axes for the next tick; None once the trace has run out.
+    pub fn next_axes(&mut self) -> Option<[f32; 7]> {
+        if self.tick >= self.trace.ticks {
+            return None;
+        }
+        let keyframes = &self.trace.keyframes;
+        while keyframes.get(self.cursor + 1).is_some_and(|(t, _)| *t <= self.tick) {
+            self.cursor += 1;
+        }
+        self.tick += 1;
+        Some(keyframes[self.cursor].1)
+    }
+}
+
+// ==========================================================
+// Headless A/B run (--ab)
+// ==========================================================
+pub struct AbRun<'a> {
+    pub trace_path: &'a str,
+    pub entity: Option<&'a str>,
+    pub vehicle: &'a str,
+    pub set: Option<&'a str>, // "name=value,name=value" VehicleConfig overrides for B
+    pub output: Option<&'a str>,
+}
+
+pub fn run_ab(run: &AbRun) -> Result<(), String> {
+    let trace = Arc::new(InputTrace::load(run.trace_path, run.entity)?);
+    let base = vehicle_config_by_name(run.vehicle).ok_or(format!("unknown vehicle '{}' (gt86, arcade, tank)", run.vehicle))?;
+    let mut modified = base;
+    for pair in run.set.unwrap_or("").split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (name, value) = pair.split_once('=').ok_or(format!("--set expects name=value, got '{}'", pair))?;
+        let value = value.trim().parse::<f32>().map_err(|_| format!("--set {}: '{}' is not a number", name, value))?;
+        set_param(&mut modified, name.trim(), value)?;
+    }
+
+    // Both cars are entities with a (socketless) client, so the trace
+    // reaches them through the input mailbox like in a live server
+    let mut world = PhysicsWorld::new();
+    let mut game = SharedGameState::new(TickRate::default());
+    for ((id, spawn), config) in AB_IDS.iter().zip(AB_SPAWNS).zip([base, modified]) {
+        let body = world
+            .spawn_vehicle_with_config(id.to_string(), spawn, 0.0, config)
+            .map_err(|e| format!("{}: {}", id, e.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; ")))?;
+        game.register_client(id.to_string(), ClientTx::channel().0);
+        game.add_entity(id, EntityType::Vehicle);
+        game.attach_body(id, body);
+    }
+    for _ in 0..AB_SETTLE_TICKS {
+        world.step(AB_DT);
+    }
+    for id in AB_IDS {
+        world.attach_input_trace(id, Arc::clone(&trace)).map_err(|e| format!("{}: {}", id, e))?;
+    }
+    println!(
+        "🆎 A/B run: {} ticks of {}, {} vs {} with {}",
+        trace.ticks(), run.trace_path, run.vehicle, run.vehicle, run.set.unwrap_or("no changes")
+    );
+
+    loop {
+        game.post_trace_inputs(&mut world);
+        if AB_IDS.iter().all(|id| !world.input_traces.contains_key(*id)) {
+            break;
+        }
+        game.apply_input_mailbox(&mut world);
+        world.decay_stale_inputs(AB_DT);
+        world.step(AB_DT);
+        world.clear_debug_overlay();
+    }
+
+    let prefix = run.output.unwrap_or(DEFAULT_AB_OUTPUT);
+    for id in AB_IDS {
+        let ring = world.telemetry.get(id).ok_or(format!("no telemetry for {}", id))?;
+        let path = format!("{}_{}.csv", prefix, id);
+        std::fs::write(&path, ring.to_csv()).map_err(|e| format!("{}: {}", path, e))?;
+        println!("💾 Wrote {} telemetry rows to {}", ring.len(), path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::MailboxInput;
+
+    /// Every axis `trace` plays, tick by tick.
+    fn played(trace: InputTrace) -> Vec<[f32; 7]> {
+        let mut playback = TracePlayback::new(Arc::new(trace));
+        std::iter::from_fn(|| playback.next_axes()).collect()
+    }
+
+    #[test]
+    fn csv_keyframes_are_rebased_and_held_until_the_next_one() {
+        let trace = InputTrace::from_csv("# warm-up lap\n100,1.0,0.0,0.0\n\n103,0.5,-0.25,0.0\n105,0,0,1\n").unwrap();
+        assert_eq!(trace.ticks(), 6);
+        let axes = played(trace);
+        let throttle_steer_brake: Vec<[f32; 3]> = axes.iter().map(|a| [a[0], a[1], a[2]]).collect();
+        assert_eq!(
+            throttle_steer_brake,
+            [[1.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.5, -0.25, 0.0], [0.5, -0.25, 0.0], [0.0, 0.0, 1.0]]
+        );
+        assert!(axes.iter().all(|a| a[3..] == [0.0; 4]), "missing columns are 0");
+    }
+
+    #[test]
+    fn csv_header_names_the_columns_in_any_order() {
+        let trace = InputTrace::from_csv("steer,tick,roll\n0.5,7,-1\n-0.5,9,0\n").unwrap();
+        assert_eq!(trace.ticks(), 3);
+        assert_eq!(played(trace), vec![
+            [0.0, 0.5, 0.0, 0.0, 0.0, 0.0, -1.0],
+            [0.0, 0.5, 0.0, 0.0, 0.0, 0.0, -1.0],
+            [0.0, -0.5, 0.0, 0.0, 0.0, 0.0, 0.0],
+        ]);
+    }
+
+    #[test]
+    fn bad_rows_name_the_line() {
+        let err = |text: &str| InputTrace::from_csv(text).unwrap_err();
+        assert_eq!(err("0,1,0,0\n1,fast,0,0\n"), "line 2: bad value 'fast'");
+        assert_eq!(err("tick,throttle\n,1\n"), "line 2: missing or bad tick");
+        assert_eq!(err("0,1,0,0\n-3,1,0,0\n"), "line 2: missing or bad tick");
+        assert_eq!(err("tick,throttle,boost\n0,1,1\n"), "unknown column 'boost'");
+        assert_eq!(err("throttle,steer\n1,0\n"), "header needs exactly one 'tick' column");
+        assert_eq!(err("tick,throttle\n"), "trace has no input rows");
+        assert_eq!(err("0,inf,0,0\n"), "trace axes must be finite");
+    }
+
+    #[test]
+    fn traced_input_goes_through_the_mailbox_and_overrides_live_input() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        let _rx = game.join_test_player(&mut phys, "p");
+        let trace = InputTrace::from_csv("0,0.5,2.0,0\n1,0.75,0,0\n").unwrap();
+        phys.attach_input_trace("p", Arc::new(trace)).unwrap();
+
+        // Live input is ignored, nothing moves before the mailbox is drained
+        game.input_sender("p").send(MailboxInput::Axes([Some(-1.0); 7], None, None));
+        game.post_trace_inputs(&mut phys);
+        assert_eq!(phys.vehicles["p"].throttle, 0.0);
+        game.apply_input_mailbox(&mut phys);
+        // Clamped like live input, and reported as the entity's input
+        assert_eq!((phys.vehicles["p"].throttle, phys.vehicles["p"].steer), (0.5, 1.0));
+        assert_eq!(game.entities["p"].last_input.as_ref().unwrap().axes.steer, 1.0);
+
+        game.post_trace_inputs(&mut phys);
+        game.apply_input_mailbox(&mut phys);
+        assert_eq!(phys.vehicles["p"].throttle, 0.75);
+
+        // Run out: detached, live input drives again
+        game.post_trace_inputs(&mut phys);
+        assert!(!phys.input_traces.contains_key("p"));
+        game.input_sender("p").send(MailboxInput::Axes([Some(-1.0), None, None, None, None, None, None], None, None));
+        game.apply_input_mailbox(&mut phys);
+        assert_eq!(phys.vehicles["p"].throttle, -1.0);
+    }
+
+    #[test]
+    fn ab_run_writes_two_csvs_of_the_trace_length() {
+        let dir = std::env::temp_dir();
+        let trace_path = dir.join(format!("avenlab-ab-trace-{}.csv", std::process::id()));
+        let out = dir.join(format!("avenlab-ab-{}", std::process::id()));
+        std::fs::write(&trace_path, "tick,throttle,steer\n0,1,0\n60,1,0.4\n119,0,0\n").unwrap();
+        let (trace_path, out) = (trace_path.to_str().unwrap(), out.to_str().unwrap());
+
+        let run = AbRun { trace_path, entity: None, vehicle: "gt86", set: Some("mass=1400"), output: Some(out) };
+        run_ab(&run).unwrap();
+        let csv = |side: &str| {
+            let path = format!("{}_{}.csv", out, side);
+            let text = std::fs::read_to_string(&path).unwrap();
+            let _ = std::fs::remove_file(&path);
+            text
+        };
+        let (a, b) = (csv("a"), csv("b"));
+        let bad_set = run_ab(&AbRun { set: Some("mass"), ..run });
+        let _ = std::fs::remove_file(trace_path);
+        assert_eq!(bad_set.unwrap_err(), "--set expects name=value, got 'mass'");
+
+        assert_eq!(a.lines().count(), 1 + 120, "header + one row per trace tick");
+        assert_eq!(b.lines().count(), a.lines().count());
+        // Row by row on the same ticks, with the same consumed inputs
+        for (row_a, row_b) in a.lines().zip(b.lines()).skip(1) {
+            let fields = |row: &str| row.split(',').map(str::to_string).collect::<Vec<_>>();
+            let (fa, fb) = (fields(row_a), fields(row_b));
+            assert_eq!(fa[0], fb[0], "tick");
+            assert_eq!(fa[9..12], fb[9..12], "throttle, steer, brake");
+        }
+        assert!(a != b, "the heavier car drives differently");
+
+        let bad = AbRun { trace_path: "missing.csv", entity: None, vehicle: "gt86", set: None, output: Some(out) };
+        assert!(run_ab(&bad).unwrap_err().starts_with("missing.csv: "));
+    }
+}
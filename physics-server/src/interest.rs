@@ -0,0 +1,152 @@
+// ==============================================================================
+// interest.rs — PER-CLIENT INTEREST SET + SNAPSHOT KEEPALIVE GUARANTEES
+// ------------------------------------------------------------------------------
+// With AVENLAB_INTEREST_RADIUS set, each client's snapshot only carries the
+// room entities within that distance of its own vehicle, and only those that
+// moved since they were last sent to that client. Two guarantees keep the
+// client's picture from going stale:
+//
+//   keepalive     : every entity the client knows about gets a full update at
+//                   least every AVENLAB_KEEPALIVE_SECS (default 2 s), moving
+//                   or not
+//   out of range  : an entity that leaves the interest set gets an explicit
+//                   `entity_out_of_range` hint (last known position) so the
+//                   client can fade it out instead of freezing it
+//
+// Unset radius = every room entity every tick (snapshots are complete).
+// ==============================================================================
+
+use std::collections::{HashMap, HashSet};
+
+use crate::tick_rate::TickRate;
+
+const DEFAULT_KEEPALIVE_SECS: f32 = 2.0;
+pub const MOVE_EPSILON: f32 = 0.01;  // m, smaller position changes count as stationary
+pub const ROT_EPSILON: f32 = 1e-4;   // quaternion component change
+
+#[derive(Debug, Clone, Copy)]
+pub struct InterestConfig {
+    pub radius: Option<f32>, // m; None = whole room, complete snapshots
+    pub keepalive_ticks: u64,
+}
+
+impl Default for InterestConfig {
+    fn default() -> Self {
+        Self { radius: None, keepalive_ticks: TickRate::default().ticks(DEFAULT_KEEPALIVE_SECS) }
+    }
+}
+
+impl InterestConfig {
+    /// Keepalive seconds are converted to ticks at `rate`.
+    pub fn from_env(rate: TickRate) -> Self {
+        let mut cfg = Self {
+            radius: std::env::var("AVENLAB_INTEREST_RADIUS")
+                .ok()
+                .and_then(|v| v.parse::<f32>().ok())
+                .filter(|r| r.is_finite() && *r > 0.0),
+            keepalive_ticks: rate.ticks(DEFAULT_KEEPALIVE_SECS),
+        };
+        if let Some(secs) = std::env::var("AVENLAB_KEEPALIVE_SECS").ok().and_then(|v| v.parse::<f32>().ok()) {
+            cfg.keepalive_ticks = rate.ticks(secs);
+        }
+        cfg
+    }
+
+    /// Is `pos` within the interest radius of `viewer`?
+    pub fn in_range(&self, viewer: [f32; 3], pos: [f32; 3]) -> bool {
+        let Some(radius) = self.radius else { return true };
+        let d = [pos[0] - viewer[0], pos[1] - viewer[1], pos[2] - viewer[2]];
+        d[0] * d[0] + d[1] * d[1] + d[2] * d[2] <= radius * radius
+    }
+}
+
+/// What one client was last sent about one entity.
+#[derive(Debug, Clone, Copy)]
+struct KnownEntity {
+    last_sent_tick: u64,
+    pos: [f32; 3],
+    rot: [f32; 4],
+}
+
+/// Entities a client currently knows about (sent and still in interest).
+#[derive(Debug, Default)]
+pub struct ClientView {
+    known: HashMap<String, KnownEntity>,
+}
+
+impl ClientView {
+    /// Include this entity in the client's snapshot? True when new to the
+    /// client, moved since last sent, or due for its keepalive refresh.
+    /// Records the send.
+    pub fn should_send(&mut self, id: &str, pos: [f32; 3], rot: [f32; 4], tick: u64, keepalive_ticks: u64) -> bool {
+        let send = match self.known.get(id) {
+            None => true,
+            Some(k) => {
+                let moved = (0..3).any(|i| (pos[i] - k.pos[i]).abs() > MOVE_EPSILON)
+                    || (0..4).any(|i| (rot[i] - k.rot[i]).abs() > ROT_EPSILON);
+                moved || tick.saturating_sub(k.last_sent_tick) >= keepalive_ticks
+            }
+        };
+        if send {
+            self.known.insert(id.to_string(), KnownEntity { last_sent_tick: tick, pos, rot });
+        }
+        send
+    }
+
+    /// Forget entities no longer in the interest set; returns them with
+    /// their last position sent to this client.
+    pub fn drop_missing(&mut self, in_interest: &HashSet<&str>) -> Vec<(String, [f32; 3])> {
+        let gone: Vec<String> = self
+            .known
+            .keys()
+            .filter(|id| !in_interest.contains(id.as_str()))
+            .cloned()
+            .collect();
+        gone.into_iter()
+            .filter_map(|id| self.known.remove(&id).map(|k| (id, k.pos)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROT: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+    #[test]
+    fn moving_entities_go_every_tick_stationary_ones_on_keepalive() {
+        let mut view = ClientView::default();
+        let sent = |view: &mut ClientView, step: f32| -> Vec<u64> {
+            (0..25).filter(|&t| view.should_send("e", [t as f32 * step, 0.0, 0.0], ROT, t, 10)).collect()
+        };
+        assert_eq!(sent(&mut view, 0.0), [0, 10, 20]);
+
+        let mut view = ClientView::default();
+        assert_eq!(sent(&mut view, 0.5).len(), 25);
+
+        // Drift below MOVE_EPSILON never counts as movement
+        let mut view = ClientView::default();
+        assert_eq!(sent(&mut view, MOVE_EPSILON / 100.0), [0, 10, 20]);
+    }
+
+    #[test]
+    fn dropped_entities_report_their_last_sent_position() {
+        let mut view = ClientView::default();
+        view.should_send("near", [1.0, 0.0, 0.0], ROT, 0, 10);
+        view.should_send("far", [30.0, 0.0, 0.0], ROT, 0, 10);
+
+        let still_in: HashSet<&str> = ["near"].into();
+        assert_eq!(view.drop_missing(&still_in), [("far".to_string(), [30.0, 0.0, 0.0])]);
+        assert!(view.drop_missing(&still_in).is_empty());
+        assert!(view.should_send("far", [30.0, 0.0, 0.0], ROT, 1, 10), "new again on re-entry");
+    }
+
+    #[test]
+    fn no_radius_means_everything_is_in_range() {
+        let cfg = InterestConfig { radius: Some(10.0), keepalive_ticks: 120 };
+        assert!(cfg.in_range([0.0; 3], [6.0, 0.0, 8.0]));
+        assert!(!cfg.in_range([0.0; 3], [6.0, 0.1, 8.0]));
+        assert!(InterestConfig::default().in_range([0.0; 3], [1e6, 0.0, 0.0]));
+    }
+}
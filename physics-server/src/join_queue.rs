@@ -0,0 +1,235 @@
+// ==============================================================================
+// join_queue.rs — FIFO JOIN QUEUE WHEN THE SERVER IS FULL
+// ------------------------------------------------------------------------------
+// With AVENLAB_MAX_PLAYERS set, a connection that arrives while every slot is
+// taken is not spawned. It gets a `queued` message instead:
+//
+//   {"type":"queued","position":1,"queue_length":3,"estimated_wait_secs":42.0}
+//
+// and waits (server pings keep it alive, see QUEUE_PING_INTERVAL) until a
+// player leaves. Promotion is strictly FIFO; the promoted connection then
+// runs the normal spawn flow and gets its welcome. Everyone behind a client
+// that left the queue (promoted or disconnected) is sent its new position.
+//
+// Slots are reserved from admission until the entity exists (`spawned`), so
+// a connection racing a promotion can't take the freed slot.
+//
+// Estimated wait = position / recent churn (players leaving per second over
+// CHURN_WINDOW); null until someone has left.
+// ==============================================================================
+
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+use tokio::sync::oneshot;
+
+use crate::protocol;
+use crate::snapshot::ClientTx;
+
+pub const QUEUE_PING_INTERVAL: Duration = Duration::from_secs(10);
+const CHURN_WINDOW: Duration = Duration::from_secs(600);
+const MIN_CHURN_SPAN: Duration = Duration::from_secs(60); // don't extrapolate from a single burst
+
+struct QueuedClient {
+    id: String,
+    tx: ClientTx,
+    promote: oneshot::Sender<()>,
+}
+
+pub enum Admission {
+    Spawn,
+    Queued(oneshot::Receiver<()>), // resolves when promoted
+}
+
+pub struct JoinQueue {
+    max_players: usize,          // 0 = unlimited, queue never used
+    waiting: VecDeque<QueuedClient>,
+    reserved: HashSet<String>,   // admitted / promoted, entity not created yet
+    departures: VecDeque<Instant>,
+}
+
+impl JoinQueue {
+    pub fn new(max_players: usize) -> Self {
+        Self {
+            max_players,
+            waiting: VecDeque::new(),
+            reserved: HashSet::new(),
+            departures: VecDeque::new(),
+        }
+    }
+
+    fn has_slot(&self, players: usize) -> bool {
+        self.max_players == 0 || players + self.reserved.len() < self.max_players
+    }
+
+    /// Take a free slot now, or join the back of the queue (sends `queued`).
+    /// `players` = entities currently in the game.
+    pub fn admit(&mut self, id: &str, tx: ClientTx, players: usize) -> Admission {
+        if self.waiting.is_empty() && self.has_slot(players) {
+            self.reserved.insert(id.to_string());
+            return Admission::Spawn;
+        }
+
+        let (promote, promoted) = oneshot::channel();
+        self.waiting.push_back(QueuedClient { id: id.to_string(), tx, promote });
+        let position = self.waiting.len();
+        let _ = self.waiting[position - 1].tx.send(self.queued_message(position));
+        println!("⏳ {} queued at position {} (server full)", id, position);
+        Admission::Queued(promoted)
+    }
+
+    /// The admitted client's entity now exists (it counts as a player).
+    pub fn spawned(&mut self, id: &str) {
+        self.reserved.remove(id);
+    }
+
+    /// A spawned player left: count it toward churn and promote.
+    pub fn player_left(&mut self, players: usize) {
+        let now = Instant::now();
+        self.departures.push_back(now);
+        while self.departures.front().is_some_and(|t| now.duration_since(*t) > CHURN_WINDOW) {
+            self.departures.pop_front();
+        }
+        self.promote(players);
+    }
+
+    /// A queued or admitted-but-not-spawned connection went away.
+    pub fn leave(&mut self, id: &str, players: usize) {
+        self.reserved.remove(id);
+        if let Some(i) = self.waiting.iter().position(|c| c.id == id) {
+            self.waiting.remove(i);
+            self.send_positions(i);
+        }
+        self.promote(players);
+    }
+
+    /// Nobody queued or holding a reservation.
+    pub fn is_idle(&self) -> bool {
+        self.waiting.is_empty() && self.reserved.is_empty()
+    }
+
+    /// Connections waiting for a slot.
+    pub fn queued(&self) -> usize {
+        self.waiting.len()
+    }
+
+    /// Send `msg` to every queued client (server shutdown).
+    pub fn broadcast(&self, msg: &str) {
+        for client in &self.waiting {
+            let _ = client.tx.send(msg.to_string());
+        }
+    }
+
+    fn promote(&mut self, players: usize) {
+        let mut promoted_any = false;
+        while self.has_slot(players) {
+            let Some(next) = self.waiting.pop_front() else { break };
+            promoted_any = true;
+            // Receiver gone = connection closed between its last read and now
+            if next.promote.send(()).is_ok() {
+                println!("✅ {} promoted from the join queue", next.id);
+                self.reserved.insert(next.id);
+            }
+        }
+        if promoted_any {
+            self.send_positions(0);
+        }
+    }
+
+    /// Re-send positions to everyone from queue index `from` on.
+    fn send_positions(&self, from: usize) {
+        for (i, client) in self.waiting.iter().enumerate().skip(from) {
+            let _ = client.tx.send(self.queued_message(i + 1));
+        }
+    }
+
+    fn queued_message(&self, position: usize) -> String {
+        protocol::event("queued", json!({
+            "position": position,
+            "queue_length": self.waiting.len(),
+            "estimated_wait_secs": self.estimated_wait_secs(position),
+        }))
+    }
+
+    /// Seconds until `position` reaches the front at the recent churn rate.
+    pub fn estimated_wait_secs(&self, position: usize) -> Option<f32> {
+        let oldest = self.departures.front()?;
+        let span = oldest.elapsed().max(MIN_CHURN_SPAN).as_secs_f32();
+        let per_sec = self.departures.len() as f32 / span;
+        Some(position as f32 / per_sec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::OutFrame;
+    use tokio::sync::mpsc::UnboundedReceiver;
+
+    fn positions(rx: &mut UnboundedReceiver<OutFrame>) -> Vec<u64> {
+        std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|frame| match frame {
+                OutFrame::Text(text) => serde_json::from_str::<serde_json::Value>(&text).ok(),
+                OutFrame::Binary(_) => None,
+            })
+            .filter(|m| m["type"] == "queued")
+            .map(|m| m["position"].as_u64().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn a_full_room_queues_in_order_and_promotes_on_leave() {
+        let mut queue = JoinQueue::new(2);
+        for (players, id) in ["a", "b"].iter().enumerate() {
+            let (tx, _rx) = ClientTx::channel();
+            assert!(matches!(queue.admit(id, tx, players), Admission::Spawn));
+            queue.spawned(id);
+        }
+
+        let (tx_c, mut rx_c) = ClientTx::channel();
+        let Admission::Queued(mut promoted_c) = queue.admit("c", tx_c, 2) else { panic!("c should queue") };
+        let (tx_d, mut rx_d) = ClientTx::channel();
+        let Admission::Queued(mut promoted_d) = queue.admit("d", tx_d, 2) else { panic!("d should queue") };
+        assert_eq!(positions(&mut rx_c), vec![1]);
+        assert_eq!(positions(&mut rx_d), vec![2]);
+        assert_eq!(queue.queued(), 2);
+
+        // "a" leaves: the head of the queue gets the slot, "d" moves up
+        queue.player_left(1);
+        assert!(promoted_c.try_recv().is_ok());
+        assert!(promoted_d.try_recv().is_err());
+        assert_eq!(positions(&mut rx_d), vec![1]);
+        assert_eq!(queue.queued(), 1);
+
+        // The slot stays reserved for "c" until it spawns
+        assert!(!queue.has_slot(1));
+        queue.spawned("c");
+        assert!(!queue.has_slot(2));
+        assert!(queue.estimated_wait_secs(1).is_some());
+    }
+
+    #[test]
+    fn leaving_the_queue_moves_everyone_behind_up() {
+        let mut queue = JoinQueue::new(1);
+        let (tx, _rx) = ClientTx::channel();
+        assert!(matches!(queue.admit("a", tx, 0), Admission::Spawn));
+        queue.spawned("a");
+
+        let mut rx: Vec<_> = ["b", "c", "d"]
+            .iter()
+            .map(|id| {
+                let (tx, mut rx) = ClientTx::channel();
+                assert!(matches!(queue.admit(id, tx, 1), Admission::Queued(_)));
+                positions(&mut rx);
+                rx
+            })
+            .collect();
+
+        queue.leave("c", 1);
+        assert!(positions(&mut rx[0]).is_empty());
+        assert_eq!(positions(&mut rx[2]), vec![2]);
+        assert_eq!(queue.queued(), 2);
+        assert!(queue.estimated_wait_secs(1).is_none());
+    }
+}
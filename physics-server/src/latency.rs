@@ -0,0 +1,155 @@
+// ==============================================================================
+// latency.rs — INPUT → APPLY → SNAPSHOT LATENCY PER CLIENT
+// ------------------------------------------------------------------------------
+// How stale the world feels to a player, in two server-side legs:
+//
+//   input → apply   an "input" / "turret" message's receive time (stamped by
+//                   the read loop's InputSender) to the tick that drains it
+//                   from the mailbox (apply_input_mailbox). At most one tick
+//                   period plus lock waits while the loop keeps up.
+//   apply → send    that tick's drain to the snapshot reflecting it being
+//                   queued on the client's channel (end of broadcast_snapshot;
+//                   the wait grows with --snapshot-divisor).
+//
+// Each leg is a histogram per client (the "stats" reply, "latency") and one
+// over all clients (/metrics: avenlab_input_apply_seconds,
+// avenlab_apply_send_seconds). Tick-tagged inputs (input_buffer.rs) count as
+// applied when drained; their intended lead is not latency.
+// ==============================================================================
+
+use std::time::{Duration, Instant};
+
+use serde_json::{Value, json};
+
+/// Bucket bounds (s); 0.016 = one 60 Hz tick
+pub const LATENCY_SECONDS_BUCKETS: [f64; 9] = [0.001, 0.002, 0.004, 0.008, 0.016, 0.033, 0.066, 0.1, 0.25];
+
+#[derive(Debug, Clone, Default)]
+pub struct LatencyHistogram {
+    buckets: [u64; LATENCY_SECONDS_BUCKETS.len()], // per bucket, not cumulative; +Inf = count
+    count: u64,
+    sum_us: u64,
+    max_us: u64,
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, d: Duration) {
+        let secs = d.as_secs_f64();
+        if let Some(i) = LATENCY_SECONDS_BUCKETS.iter().position(|le| secs <= *le) {
+            self.buckets[i] += 1;
+        }
+        let us = d.as_micros() as u64;
+        self.count += 1;
+        self.sum_us += us;
+        self.max_us = self.max_us.max(us);
+    }
+
+    pub fn to_json(&self) -> Value {
+        let buckets: serde_json::Map<String, Value> = LATENCY_SECONDS_BUCKETS
+            .iter()
+            .zip(&self.buckets)
+            .map(|(le, n)| (format!("{}", le), json!(n)))
+            .collect();
+        json!({
+            "count": self.count,
+            "avg_ms": if self.count > 0 { (self.sum_us as f64 / self.count as f64 / 10.0).round() / 100.0 } else { 0.0 },
+            "max_ms": (self.max_us as f64 / 10.0).round() / 100.0,
+            "buckets": buckets,
+        })
+    }
+
+    /// Prometheus histogram lines for `name` (with the # TYPE line).
+    pub fn prometheus(&self, name: &str) -> String {
+        let mut out = format!("# TYPE {} histogram\n", name);
+        let mut cumulative = 0;
+        for (le, bucket) in LATENCY_SECONDS_BUCKETS.iter().zip(&self.buckets) {
+            cumulative += bucket;
+            out += &format!("{}_bucket{{le=\"{}\"}} {}\n", name, le, cumulative);
+        }
+        out += &format!("{}_bucket{{le=\"+Inf\"}} {}\n", name, self.count);
+        out += &format!("{}_sum {:.6}\n", name, self.sum_us as f64 / 1e6);
+        out += &format!("{}_count {}\n", name, self.count);
+        out
+    }
+}
+
+/// Both legs, for one client or all of them.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyTotals {
+    pub input_to_apply: LatencyHistogram,
+    pub apply_to_send: LatencyHistogram,
+}
+
+impl LatencyTotals {
+    pub fn to_json(&self) -> Value {
+        json!({
+            "input_to_apply": self.input_to_apply.to_json(),
+            "apply_to_send": self.apply_to_send.to_json(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ClientLatency {
+    pub totals: LatencyTotals,
+    /// Earliest drain not reflected in a snapshot yet: (tick, when)
+    applied: Option<(u64, Instant)>,
+}
+
+impl ClientLatency {
+    /// An input received at `received` was drained for `tick` at `now`.
+    /// Returns the input → apply time.
+    pub fn consumed(&mut self, received: Instant, tick: u64, now: Instant) -> Duration {
+        let waited = now.saturating_duration_since(received);
+        self.totals.input_to_apply.record(waited);
+        self.applied.get_or_insert((tick, now));
+        waited
+    }
+
+    /// The snapshot of `tick` was queued at `now`. Returns the apply → send
+    /// time when it reflects a drain not yet accounted for.
+    pub fn snapshot_sent(&mut self, tick: u64, now: Instant) -> Option<Duration> {
+        let (applied_tick, applied_at) = self.applied?;
+        if applied_tick > tick {
+            return None;
+        }
+        self.applied = None;
+        let waited = now.saturating_duration_since(applied_at);
+        self.totals.apply_to_send.record(waited);
+        Some(waited)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_buckets_and_prometheus_are_cumulative() {
+        let mut h = LatencyHistogram::default();
+        h.record(Duration::from_micros(500));
+        h.record(Duration::from_millis(10));
+        h.record(Duration::from_secs(1)); // past the last bound: +Inf only
+        let text = h.prometheus("x");
+        assert!(text.contains("x_bucket{le=\"0.001\"} 1\n"));
+        assert!(text.contains("x_bucket{le=\"0.016\"} 2\n"));
+        assert!(text.contains("x_bucket{le=\"0.25\"} 2\n"));
+        assert!(text.contains("x_bucket{le=\"+Inf\"} 3\n"));
+        assert!(text.contains("x_count 3\n"));
+        assert_eq!(h.to_json()["max_ms"], 1000.0);
+    }
+
+    #[test]
+    fn apply_to_send_closes_on_the_first_snapshot_at_or_after_the_drain() {
+        let start = Instant::now();
+        let mut latency = ClientLatency::default();
+        latency.consumed(start, 5, start + Duration::from_millis(4));
+        latency.consumed(start, 5, start + Duration::from_millis(6)); // same tick: first drain counts
+
+        assert_eq!(latency.snapshot_sent(4, start + Duration::from_millis(7)), None); // older snapshot
+        assert_eq!(latency.snapshot_sent(5, start + Duration::from_millis(9)), Some(Duration::from_millis(5)));
+        assert_eq!(latency.snapshot_sent(6, start + Duration::from_millis(20)), None); // already accounted for
+        assert_eq!(latency.totals.input_to_apply.count, 2);
+        assert_eq!(latency.totals.apply_to_send.count, 1);
+    }
+}
@@ -7,132 +7,610 @@ mod spawn;      // spawn logic
 mod suspension_contact;
 mod debug_builders;
 mod vehicle;
+mod recording;  // input recording + replay verification
+mod health;     // admin HTTP health / readiness probes
+mod plugins;    // per-tick gameplay hooks
+mod compression; // optional zstd frames for large payloads
+mod moderation;  // display name policy + chat filter
+mod haptics;    // rumble / camera-shake hints
+mod latency;    // input → apply → snapshot latency histograms per client
+mod read_model; // lock-free per-tick world view for read-only consumers
+mod interest;   // per-client snapshot interest + keepalive guarantees
+mod delta;      // snapshot history + client acks for delta snapshots
+mod setup_report; // corner weights / setup sheet (admin + --report setup)
+mod join_queue;   // FIFO join queue when AVENLAB_MAX_PLAYERS is reached
+mod input_hold;   // per-axis latch / decay-to-neutral / require-full input semantics
+mod input_buffer; // tagged inputs queued until their client-estimated tick
+mod sweep;        // headless parameter sweeps (--sweep sweep.toml)
+mod timing;       // lap / sector timing + delta-to-best (AVENLAB_TRACK_FILE)
+mod input_trace;  // recorded input injection per entity (A/B runs, --ab)
+mod telemetry;    // per-vehicle telemetry ring buffers
+mod game_rng;     // seeded server RNG + per-subsystem child streams (AVENLAB_SEED)
+mod surface;      // collider material tags + per-wheel hit descriptions
+mod tick_rate;    // main loop tick rate + dt (AVENLAB_TICK_HZ)
+mod time_sync;    // server clock (ms since start) + time_sync handshake
+mod input_sanity; // anti-cheat range / rate / pattern / tune checks on client input
+mod ghost;        // best-lap ghost recording + per-client playback
+mod airborne;     // airborne detection, mid-air control torque, landing settle
+mod recovery;     // flipped-car detection, righting in place after a timeout
+mod client_message; // typed client → server messages (serde, strict numbers)
+mod fuzz;         // protocol fuzz harness against an in-process server (--fuzz)
+mod session;      // warm restart: session file + resume tokens (--persist-session)
+mod trails;       // per-wheel contact / tire-force trails for the debug overlay
+mod stall;        // tick-loop stall detection + timed physics / state mutexes
+mod schema;       // JSON Schema bundle of the typed wire payloads (--dump-schema)
+mod vehicle_presets; // VehicleConfig presets from configs/vehicles.toml (AVENLAB_VEHICLES_FILE)
+mod terrain;      // heightfield terrain from .json / .pgm (--terrain) + client export
+mod map;          // static boxes / ramps / cylinders from a JSON map file (--map)
+mod collisions;   // chassis impact records → "collisions" messages
+mod rate_limit;   // per-connection token-bucket message rate limit
+mod snapshot;     // typed snapshot / debug messages, JSON or MessagePack per client
+mod tick_metrics; // per-tick phase timings, slow-tick warnings, 1 s "server_stats" summary
+mod server_config; // bind address / port / tick rate / snapshot divisor / capacity / map (CLI + env)
+mod protocol;     // wire protocol version, join negotiation, versioned outbound messages
 
 
-use rapier3d::prelude::RigidBodyHandle;
-use crate::net::start_websocket_server;
+use crate::net::{bind_websocket, serve_websocket};
+use crate::server_config::{ServerConfig, USAGE};
 use crate::physics::PhysicsWorld;
-use crate::state::{SharedGameState, EntityType}; // shared world state
+use crate::state::SharedGameState; // shared world state
+use crate::health::{HealthState, start_admin_server};
+use crate::join_queue::JoinQueue;
+use crate::plugins::PluginHost;
+use crate::read_model::{ReadModelSlot, WorldReadModel};
 
 use std::sync::Arc; // multiple threads own the same object
-use tokio::sync::Mutex; // only 1 thread at a time can mutate the object
+use std::time::Instant;
+use tokio::sync::Notify;
+use crate::stall::{StallMonitor, TimedMutex}; // only 1 thread at a time can mutate the object
+use crate::tick_rate::TickClock;
+use crate::tick_metrics::TickSample;
 // use tokio::time::{interval, Duration};
 
 #[tokio::main]
 async fn main() {
+    // -------------------------------------------------
+    // 0) Offline replay verification: --verify <recording>
+    // -------------------------------------------------
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(i) = args.iter().position(|a| a == "--verify") {
+        let Some(path) = args.get(i + 1) else {
+            eprintln!("usage: physics-server --verify <recording.jsonl>");
+            std::process::exit(2);
+        };
+        match recording::verify_recording(path) {
+            Ok(report) => match report.first_divergence {
+                None => {
+                    println!("✅ Replay matches: {} ticks verified", report.ticks_checked);
+                    return;
+                }
+                Some(d) => {
+                    eprintln!(
+                        "❌ Divergence at tick {} (expected {:016x}, got {:016x}) after {} matching ticks",
+                        d.tick, d.expected, d.actual, report.ticks_checked - 1
+                    );
+                    std::process::exit(1);
+                }
+            },
+            Err(e) => {
+                eprintln!("❌ Could not replay recording: {}", e);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    // -------------------------------------------------
+    // 0b) Setup sheet: --report setup [gt86|arcade|tank]
+    // -------------------------------------------------
+    if let Some(i) = args.iter().position(|a| a == "--report") {
+        if args.get(i + 1).map(String::as_str) != Some("setup") {
+            eprintln!("usage: physics-server --report setup [gt86|arcade|tank]");
+            std::process::exit(2);
+        }
+        let name = args.get(i + 2).map_or("gt86", String::as_str);
+        let Some(config) = physics::vehicle_config_by_name(name) else {
+            eprintln!("❌ Unknown vehicle '{}' (gt86, arcade, tank)", name);
+            std::process::exit(2);
+        };
+        match setup_report::settle_and_sample("setup", config, config.brake_bias, Default::default()) {
+            Some(report) => {
+                print!("{}", report.format_table());
+                return;
+            }
+            None => {
+                eprintln!("❌ Could not settle the vehicle (invalid config?)");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // -------------------------------------------------
+    // 0c) Parameter sweep: --sweep <sweep.toml>
+    // -------------------------------------------------
+    if let Some(i) = args.iter().position(|a| a == "--sweep") {
+        let Some(path) = args.get(i + 1) else {
+            eprintln!("usage: physics-server --sweep <sweep.toml>");
+            std::process::exit(2);
+        };
+        if let Err(e) = sweep::run_sweep_file(path) {
+            eprintln!("❌ Sweep failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // -------------------------------------------------
+    // 0d) A/B comparison: --ab <trace> [--vehicle ..] [--set ..] [--entity ..] [--out ..]
+    // -------------------------------------------------
+    if let Some(i) = args.iter().position(|a| a == "--ab") {
+        let Some(trace_path) = args.get(i + 1) else {
+            eprintln!("usage: physics-server --ab <trace.csv|recording.jsonl> [--vehicle gt86] [--set mass=1400,..] [--entity id] [--out prefix]");
+            std::process::exit(2);
+        };
+        let flag = |name: &str| args.iter().position(|a| a == name).and_then(|j| args.get(j + 1)).map(String::as_str);
+        let run = input_trace::AbRun {
+            trace_path,
+            entity: flag("--entity"),
+            vehicle: flag("--vehicle").unwrap_or("gt86"),
+            set: flag("--set"),
+            output: flag("--out"),
+        };
+        if let Err(e) = input_trace::run_ab(&run) {
+            eprintln!("❌ A/B run failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // -------------------------------------------------
+    // 0e) Protocol fuzzing: --fuzz [corpus dir] [--sessions N] [--seed S]
+    // -------------------------------------------------
+    if let Some(i) = args.iter().position(|a| a == "--fuzz") {
+        let flag = |name: &str| args.iter().position(|a| a == name).and_then(|j| args.get(j + 1));
+        let corpus_dir = args.get(i + 1).filter(|a| !a.starts_with("--")).map_or(fuzz::DEFAULT_CORPUS, String::as_str);
+        let (Ok(sessions), Ok(seed)) = (
+            flag("--sessions").map_or(Ok(fuzz::DEFAULT_SESSIONS), |v| v.parse()),
+            flag("--seed").map_or(Ok(1), |v| v.parse()),
+        ) else {
+            eprintln!("usage: physics-server --fuzz [corpus dir] [--sessions 200] [--seed 1]");
+            std::process::exit(2);
+        };
+        let run = fuzz::FuzzRun { corpus_dir, sessions, seed };
+        match fuzz::run_fuzz(&run).await {
+            Ok(report) => {
+                println!(
+                    "🧪 {} sessions, {} connections, {} frames, {} ticks",
+                    report.sessions, report.connections, report.frames, report.ticks
+                );
+                if report.panics == 0 && report.violations.is_empty() {
+                    println!("✅ No panics, no invariant violations");
+                    return;
+                }
+                eprintln!("❌ {} panics, {} invariant violations", report.panics, report.violations.len());
+                for violation in &report.violations {
+                    eprintln!("   - {}", violation);
+                }
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("❌ Fuzz run failed: {}", e);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    // -------------------------------------------------
+    // 0f) Protocol schema: --dump-schema [file]
+    // -------------------------------------------------
+    if let Some(i) = args.iter().position(|a| a == "--dump-schema") {
+        let path = args.get(i + 1).filter(|a| !a.starts_with("--")).map(String::as_str);
+        if let Err(e) = schema::dump_schema(path) {
+            eprintln!("❌ Could not write the protocol schema: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     println!("🚀 Starting Rust Physics Server...");
 
+    // Listen address, tick rate, snapshot divisor, capacity, map (flags, then env)
+    let config = match ServerConfig::from_args(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("❌ Invalid configuration: {}", e);
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+    println!("⚙️ Config: {}", config.summary());
+
     // -------------------------------------------------
     // 1) Create global shared game state
     // -------------------------------------------------
-    let state = Arc::new(Mutex::new(SharedGameState::new()));
+    let tick_rate = config.tick_rate;
+    let state = Arc::new(TimedMutex::new("state", SharedGameState::new(tick_rate)));
+
+    // Gameplay plugins (AVENLAB_PLUGINS=low_gravity_zone,...)
+    {
+        let mut game = state.lock().await;
+        game.plugins = PluginHost::from_env(&game.rng);
+        game.snapshot_divisor = config.snapshot_divisor;
+    }
+    let dt = tick_rate.dt();
     // -------------------------------------------------
     // 2) Create global shared physics world
     // -------------------------------------------------
-    let physics = Arc::new(Mutex::new(PhysicsWorld::new()));
+    let physics = Arc::new(TimedMutex::new("physics", PhysicsWorld::new()));
+
+    // Lock-free mirror of tick/readiness for the admin probes
+    let health = Arc::new(
+        HealthState::new(config.max_players).with_admin_token(std::env::var("AVENLAB_ADMIN_TOKEN").ok()),
+    );
+    // server_time_ms zero (snapshots, time_sync replies)
+    time_sync::start_clock();
+    {
+        let mut phys = physics.lock().await;
+        vehicle_presets::load_into(&mut phys);
+        if let Some(i) = args.iter().position(|a| a == "--terrain") {
+            let loaded = args
+                .get(i + 1)
+                .ok_or_else(|| "usage: physics-server --terrain <heights.json|heights.pgm>".to_string())
+                .and_then(|path| {
+                    let t = terrain::load_file(path)?;
+                    phys.load_heightfield(t.nx, t.ny, t.width, t.depth, t.heights)?;
+                    Ok(path)
+                });
+            match loaded {
+                Ok(path) => {
+                    if let Some(rec) = state.lock().await.recorder.as_mut() {
+                        rec.record_terrain(path);
+                    }
+                }
+                Err(e) => eprintln!("❌ Terrain not loaded, keeping the flat ground: {}", e),
+            }
+        }
+        if let Some(path) = config.map_path.as_deref() {
+            let mut game = state.lock().await;
+            let spawn_areas = game.spawns.spawn_areas();
+            match phys.load_map(path, &spawn_areas) {
+                Ok(count) => {
+                    println!("🗺️ Map {} loaded: {} static object(s)", path, count);
+                    if let Some(rec) = game.recorder.as_mut() {
+                        rec.record_map(path);
+                    }
+                }
+                Err(e) => eprintln!("❌ Map not loaded, no static objects: {}", e),
+            }
+        }
+        phys.warm_up(health.max_players);
+    }
+    health.world_ready.store(true, std::sync::atomic::Ordering::Relaxed);
+
+    // Connections beyond AVENLAB_MAX_PLAYERS wait in the join queue
+    state.lock().await.join_queue = JoinQueue::new(health.max_players);
+
+    // Warm restart: --persist-session [file] saves on shutdown, restores here
+    if let Some(i) = args.iter().position(|a| a == "--persist-session") {
+        let path = args
+            .get(i + 1)
+            .filter(|a| !a.starts_with("--"))
+            .map_or(session::DEFAULT_SESSION_FILE, String::as_str)
+            .to_string();
+        let mut phys = physics.lock().await;
+        let mut game = state.lock().await;
+        if std::path::Path::new(&path).exists() {
+            match session::load(&path) {
+                Ok(saved) => {
+                    let restored = session::restore(&saved, &mut game, &mut phys);
+                    println!("♻️ Restored session from {}: {} player(s), resuming at tick {}", path, restored, saved.tick);
+                }
+                Err(e) => eprintln!("⚠️ Could not restore session: {}", e),
+            }
+        }
+        game.session_file = Some(path);
+    }
+
+    // Immutable per-tick world view (readers never take the physics mutex)
+    let read_model = Arc::new(ReadModelSlot::default());
+    tokio::spawn(start_admin_server(Arc::clone(&health), Arc::clone(&read_model)));
 
     // -------------------------------------------------
     // 3) Launch WebSocket server (network thread)
+    //    SIGINT / SIGTERM → shutdown_signal → graceful close
     // -------------------------------------------------
-    tokio::spawn(start_websocket_server(
+    let shutdown_signal = Arc::new(Notify::new());
+    tokio::spawn(wait_for_shutdown_signal(Arc::clone(&shutdown_signal)));
+
+    let listener = match bind_websocket(&config).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
+    let mut server = tokio::spawn(serve_websocket(
+        listener,
         Arc::clone(&state),
         Arc::clone(&physics),
+        Arc::clone(&health),
+        Arc::clone(&shutdown_signal),
     ));
 
     // -------------------------------------------------
-    // 4) Fixed timestep physics loop (~60 Hz)
+    // 4) Fixed timestep physics loop (--tick-hz / AVENLAB_TICK_HZ, default 60 Hz)
+    //    Wakes every period; TickClock turns the real time
+    //    since the last wake-up into whole ticks (catch-up)
     // -------------------------------------------------
     // let mut ticker = interval(Duration::from_millis(16));
     
-    let mut interval = tokio::time::interval(std::time::Duration::from_millis(16));
+    let mut interval = tokio::time::interval(tick_rate.period());
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay); // the clock catches up, not the interval
+    let mut clock = TickClock::from_env(tick_rate);
+    let mut stalls = StallMonitor::new(tick_rate.period());
+    // Periodic one-line summary: players, held cars, spectators, queue (AVENLAB_STATS_LOG_SECS)
+    let stats_every = state::stats_log_interval();
+    let mut next_stats = stats_every.map(|every| Instant::now() + every);
 
     loop {
         // ticker.tick().await;
 
-        interval.tick().await;
+        // Keep ticking while connections drain; stop once the server is done
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = &mut server => break,
+        }
 
-        // Lock physics & game state
+        // Lock physics & game state (lock waits feed the stall monitor)
+        let woke = Instant::now();
+        let phys = physics.lock().await;
+        let physics_wait = woke.elapsed();
+        let mut game = state.lock().await;
+        let acquired = Instant::now();
+        let state_wait = acquired - woke - physics_wait;
+
+        let stall = stalls.observe(game.tick, acquired, (&*physics, physics_wait), (&*state, state_wait));
+        if let Some(report) = stall {
+            health.record_stall(&report);
+            eprintln!("🐢 Tick stall {}", serde_json::json!({ "type": "tick_stall", "stall": report }));
+        }
+        health.publish_gap(stalls.ewma_ms(), stalls.max_gap_ms());
+
+        let frame = clock.advance(acquired);
+        game.drift_ms = clock.drift_s(acquired) * 1000.0;
+        if let (Some(every), Some(due)) = (stats_every, next_stats)
+            && acquired >= due
+        {
+            println!("📊 Stats {}", game.server_stats());
+            next_stats = Some(acquired + every);
+        }
+        drop(game);
+        drop(phys);
+
+        run_catch_up(&physics, &state, &health, &read_model, dt, frame.steps).await;
+        stalls.tick_done(acquired.elapsed());
+    }
+
+    println!("👋 Server stopped");
+}
+
+/// The ticks of one wake-up, each under its own lock acquisition (physics,
+/// then game): between catch-up ticks the read loops, joins and admin
+/// commands get the locks instead of waiting out the whole frame.
+async fn run_catch_up(
+    physics: &TimedMutex<PhysicsWorld>,
+    state: &TimedMutex<SharedGameState>,
+    health: &HealthState,
+    read_model: &ReadModelSlot,
+    dt: f32,
+    steps: u32,
+) {
+    for step in 0..steps {
+        if step > 0 {
+            tokio::task::yield_now().await;
+        }
         let mut phys = physics.lock().await;
         let mut game = state.lock().await;
+        run_tick(&mut phys, &mut game, health, read_model, dt);
+    }
+}
 
-        // -----------------------------------------------------
-        // 5) For each known entity, apply their last input
-        //    NOTE: We assume net.rs already created the entity,
-        //    assigned team/room/spawn position,
-        //    AND attached the correct physics body.
-        // -----------------------------------------------------
-        for entity in game.entities.values_mut() {  
-            // Skip unspawned entities (net.rs will handle this)
-            if entity.body_handle == RigidBodyHandle::invalid() {
-                continue;
-            }
+/// One tick of the world: inputs, plugins, physics step, timing, snapshots,
+/// debug overlay. Callers hold both locks (physics, then game).
+pub fn run_tick(phys: &mut PhysicsWorld, game: &mut SharedGameState, health: &HealthState, read_model: &ReadModelSlot, dt: f32) {
+    // Phase boundaries for tick_metrics.rs (Instant::now only, no allocation)
+    let started = Instant::now();
 
-            // If the player has sent recent input, apply it
-            if let Some(ref input) = entity.last_input {
-                let axes = &input.axes;
-                match entity.kind {
-                    // Vehicle: throttle + steering
-                    EntityType::Vehicle => {
-                        // Vehicle: throttle + steering
-                        phys.apply_player_input(
-                            &entity.id,
-                            axes.throttle,
-                            axes.steer,
-                            axes.brake,
-                            axes.ascend,
-                            axes.pitch,
-                            axes.yaw,
-                            axes.roll,
-                        );
+    // Warm restart: held cars nobody came back for are removed
+    session::expire_reclaims(game, phys);
 
-                    }
-                    // Air/sea vehicles: full 6DOF controls
-                    EntityType::Drone
-                    | EntityType::Helicopter
-                    | EntityType::Jet
-                    | EntityType::Boat
-                    | EntityType::Ship => {
-                        phys.apply_player_input(
-                            &entity.id,
-                            axes.throttle,
-                            axes.steer,
-                            axes.brake,
-                            axes.ascend,
-                            axes.pitch,
-                            axes.yaw,
-                            axes.roll,
-                        );
-                    }
+    // -----------------------------------------------------
+    // 5) Apply the input / turret messages read loops posted
+    //    since the last tick (net.rs never locks physics for
+    //    these; see SharedGameState::apply_input_mailbox)
+    // -----------------------------------------------------
+    // Injected input traces go through the mailbox too, overriding
+    // live input (input_trace.rs)
+    game.post_trace_inputs(phys);
+    game.apply_input_mailbox(phys);
+
+    // Axes nobody has updated lately return to neutral (input_hold.rs)
+    phys.decay_stale_inputs(dt);
+    let inputs_done = Instant::now();
+
+    // Record the inputs consumed by this tick (before stepping)
+    let next_tick = game.tick + 1;
+    if let Some(rec) = game.recorder.as_mut() {
+        rec.record_inputs(next_tick, phys);
+    }
+
+    // Gameplay plugins: on_tick + event dispatch (before stepping, on simulated time)
+    let plugin_dt = phys.scaled_dt(dt);
+    let rooms = |id: &str| game.entities.get(id).map(|e| e.room_id);
+    let events = game.plugins.run_tick(phys, &rooms, next_tick, plugin_dt);
+    game.broadcast_game_events(&events);
+
+    // -----------------------------------------------------
+    // 6) Step the physics world forward by dt
+    // -----------------------------------------------------
+    phys.watched_overlays = game.overlay_vehicles(); // per-vehicle overlays to keep
+    let step_started = Instant::now();
+    phys.step(dt);
+    let step_done = Instant::now();
+
+    // -----------------------------------------------------
+    // 7) Update global tick counter
+    // -----------------------------------------------------
+    game.tick += 1;
+
+    let tick = game.tick;
+    if let Some(rec) = game.recorder.as_mut() {
+        rec.record_snapshot(tick, phys);
+    }
+    game.update_timing(phys);
+    health.publish_tick(tick, game.entities.len());
+    read_model.publish(WorldReadModel::capture(tick, phys));
+
+    // -----------------------------------------------------
+    // 8) Broadcast snapshots to all connected players
+    // -----------------------------------------------------
+    let broadcast_started = Instant::now();
+    // (every snapshot_divisor-th tick; --snapshot-divisor)
+    let snapshot_bytes = if tick.is_multiple_of(game.snapshot_divisor.max(1) as u64) { game.broadcast_snapshot(phys) } else { 0 };
+    health.publish_latency(&game.latency_totals);
+    let haptics = std::mem::take(&mut phys.haptic_events);
+    game.send_haptics(&haptics);
+    let collisions = std::mem::take(&mut phys.collision_events);
+    game.broadcast_events(&collisions);
+
+    // -----------------------------------------------------
+    // 9) Broadcast debug overlay (raycasts, wheels, springs)
+    // -----------------------------------------------------
+    game.broadcast_debug_overlay(phys);
+
+    // -----------------------------------------------------
+    // 10) Clear debug overlay for next frame
+    // -----------------------------------------------------
+    phys.clear_debug_overlay();
+
+    // -----------------------------------------------------
+    // 11) Tick timings (1 s summary to "server_stats" subscribers)
+    // -----------------------------------------------------
+    let done = Instant::now();
+    let sample = TickSample {
+        inputs: inputs_done - started,
+        step: step_done - step_started,
+        suspension: phys.suspension_time,
+        broadcast: done - broadcast_started,
+        total: done - started,
+        bodies: phys.bodies.len(),
+        contacts: phys.narrow_phase.contact_pairs().filter(|pair| pair.has_any_active_contact).count(),
+        clients: game.clients.len(),
+    };
+    health.record_tick(&sample, snapshot_bytes);
+    if let Some(summary) = game.tick_metrics.record(tick, sample, done) {
+        game.send_server_stats(&summary);
+    }
+}
+
+/// Resolves on Ctrl+C (SIGINT) or SIGTERM and wakes everything waiting on `shutdown`.
+async fn wait_for_shutdown_signal(shutdown: Arc<Notify>) {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = sigterm.recv() => {}
                 }
             }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
         }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
 
+    println!("🛑 Shutdown signal received");
+    shutdown.notify_waiters();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::OutFrame;
+    use crate::state::MailboxInput;
+    use crate::tick_rate::TickRate;
+
+    /// Drives run_tick the way the main loop does (input arrives between
+    /// ticks, then the tick runs) and checks the input posted before tick N
+    /// is applied on tick N and acknowledged in tick N's snapshot. Moving the
+    /// mailbox drain after the snapshot broadcast, or the broadcast before
+    /// the drain, breaks it.
+    #[test]
+    fn input_is_applied_and_sent_within_one_tick() {
+        let tick_rate = TickRate::try_new(60).unwrap();
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(tick_rate);
+        let health = HealthState::new(0);
+        let read_model = ReadModelSlot::default();
+        let mut rx = game.join_test_player(&mut phys, "p");
+        let inputs = game.input_sender("p");
 
-        // -----------------------------------------------------
-        // 6) Step the physics world forward by dt
-        // -----------------------------------------------------
-        phys.step(1.0 / 60.0);
+        const TICKS: u64 = 10;
+        for seq in 1..=TICKS {
+            let throttle = seq as f32 / TICKS as f32;
+            inputs.send(MailboxInput::Axes([Some(throttle), Some(0.2), None, None, None, None, None], Some(seq), None));
+            let tick = game.tick + 1;
+            run_tick(&mut phys, &mut game, &health, &read_model, tick_rate.dt());
+            assert_eq!(game.tick, tick);
+            assert_eq!(phys.vehicles["p"].throttle, throttle, "applied on tick {}", tick);
 
-        // -----------------------------------------------------
-        // 7) Update global tick counter
-        // -----------------------------------------------------
-        game.tick += 1;
+            let snapshots: Vec<serde_json::Value> = std::iter::from_fn(|| rx.try_recv().ok())
+                .filter_map(|frame| match frame {
+                    OutFrame::Text(text) => serde_json::from_str(&text).ok(),
+                    OutFrame::Binary(_) => None,
+                })
+                .filter(|m: &serde_json::Value| m["type"] == "snapshot")
+                .collect();
+            assert_eq!(snapshots.len(), 1, "one snapshot per tick");
+            assert_eq!(snapshots[0]["data"]["tick"], tick);
+            assert_eq!(snapshots[0]["data"]["last_processed_seq"], seq, "tick {}'s snapshot reflects its input", tick);
+        }
 
-        // -----------------------------------------------------
-        // 8) Broadcast snapshots to all connected players
-        // -----------------------------------------------------
-        game.broadcast_snapshot(&phys.bodies);
+        // Both latency legs saw every input, each accounted within its tick
+        let latency = game.latency["p"].totals.to_json();
+        assert_eq!(latency["input_to_apply"]["count"], TICKS);
+        assert_eq!(latency["apply_to_send"]["count"], TICKS);
+    }
 
-        // -----------------------------------------------------
-        // 9) Broadcast debug overlay (raycasts, wheels, springs)
-        // -----------------------------------------------------
-        let overlay = phys.debug_snapshot();
-        game.broadcast_debug_overlay(&overlay);
+    /// A connection polling the state lock during a catch-up frame gets it
+    /// between the ticks, not only once the whole frame is done.
+    #[tokio::test]
+    async fn catch_up_ticks_release_the_locks_between_them() {
+        let tick_rate = TickRate::default();
+        let physics = TimedMutex::new("physics", PhysicsWorld::new());
+        let state = Arc::new(TimedMutex::new("state", SharedGameState::new(tick_rate)));
+        let health = HealthState::new(0);
+        let read_model = ReadModelSlot::default();
 
-        // -----------------------------------------------------
-        // 10) Clear debug overlay for next frame
-        // -----------------------------------------------------
-        phys.clear_debug_overlay();
+        let observer = {
+            let state = Arc::clone(&state);
+            tokio::spawn(async move {
+                let mut seen = Vec::new();
+                while seen.last() != Some(&3) {
+                    seen.push(state.lock().await.tick);
+                    tokio::task::yield_now().await;
+                }
+                seen
+            })
+        };
 
+        run_catch_up(&physics, &state, &health, &read_model, tick_rate.dt(), 3).await;
+        let seen = observer.await.unwrap();
+        assert!(seen.contains(&1) && seen.contains(&2), "{:?}", seen);
     }
 }
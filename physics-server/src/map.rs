@@ -0,0 +1,227 @@
+// ==============================================================================
+// map.rs — STATIC MAP OBJECTS (BOXES, RAMPS, CYLINDERS) FROM A JSON FILE
+// ------------------------------------------------------------------------------
+// Something to drive around. `--map <file>` loads a list of primitives at
+// startup (PhysicsWorld::load_map); each becomes a fixed body whose collider
+// blocks chassis and wheel colliders and carries the suspension rays, so cars
+// drive up ramps and onto boxes.
+//
+//   {"objects": [
+//     {"kind": "box",      "position": [10, 1.4, 0], "half_extents": [2, 0.5, 2]},
+//     {"kind": "ramp",     "position": [0, 1.4, 20], "rotation": [-12, 0, 0],
+//      "half_extents": [3, 0.2, 6]},
+//     {"kind": "cylinder", "position": [-10, 2.9, 0], "radius": 1.0, "half_height": 2.0},
+//     {"kind": "box",      "position": [0, 0.91, -20], "half_extents": [6, 0.01, 6],
+//      "friction": 0.1, "material": "ice", "name": "ice_patch"}
+//   ]}
+//
+//   position     : center (world, m); the flat ground's top is y 0.9
+//   rotation     : degrees about X, Y, Z (applied in that order), default 0
+//   friction     : tire grip multiplier on this surface (1.0 = the ground,
+//                  ~0.1 = ice), also scales the collider's friction; default 1.0
+//   material     : surface tag reported per wheel (surface.rs), optional
+//   name         : surface source name instead of "prop", optional
+//   ramp         : a box the client renders as a ramp; same collider
+//   cylinder     : vertical axis before rotation
+//
+// The whole file is rejected (nothing inserted) when an object is malformed
+// or overlaps a spawn area: every team spawn slot of every configured room
+// (SpawnManager::spawn_areas), widened by SPAWN_CLEAR_RADIUS_M, from
+// SPAWN_CLEAR_HALF_HEIGHT_M below to the same distance above spawn height.
+//
+// New clients get the same list as one "map" message after the welcome:
+//   {"type":"map","objects":[ ...as in the file, defaults filled in... ]}
+// ==============================================================================
+
+use rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::protocol::PROTOCOL_VERSION;
+
+pub const SPAWN_CLEAR_RADIUS_M: f32 = 3.0;      // beyond a chassis half-diagonal
+pub const SPAWN_CLEAR_HALF_HEIGHT_M: f32 = 1.5; // around the chassis spawn height
+const MAX_MAP_OBJECTS: usize = 4096;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MapShape {
+    Box { half_extents: [f32; 3] },
+    Ramp { half_extents: [f32; 3] },
+    Cylinder { radius: f32, half_height: f32 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapObject {
+    #[serde(flatten)]
+    pub shape: MapShape,
+    pub position: [f32; 3],
+    #[serde(default)]
+    pub rotation: [f32; 3], // degrees about X, Y, Z
+    #[serde(default = "default_friction")]
+    pub friction: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub material: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+fn default_friction() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MapFile {
+    pub objects: Vec<MapObject>,
+}
+
+impl MapObject {
+    pub fn validate(&self) -> Result<(), String> {
+        let positive = |v: f32| v.is_finite() && v > 0.0;
+        match &self.shape {
+            MapShape::Box { half_extents } | MapShape::Ramp { half_extents } => {
+                if !half_extents.iter().all(|h| positive(*h)) {
+                    return Err(format!("half_extents must be positive (got {:?})", half_extents));
+                }
+            }
+            MapShape::Cylinder { radius, half_height } => {
+                if !(positive(*radius) && positive(*half_height)) {
+                    return Err(format!("radius / half_height must be positive (got {} / {})", radius, half_height));
+                }
+            }
+        }
+        if !self.position.iter().chain(&self.rotation).all(|v| v.is_finite()) {
+            return Err("position / rotation must be finite".to_string());
+        }
+        if !(self.friction.is_finite() && self.friction >= 0.0) {
+            return Err(format!("friction must be >= 0 (got {})", self.friction));
+        }
+        Ok(())
+    }
+
+    pub fn isometry(&self) -> Isometry<Real> {
+        let [rx, ry, rz] = self.rotation.map(f32::to_radians);
+        let rotation = Rotation::from_axis_angle(&Vector::z_axis(), rz)
+            * Rotation::from_axis_angle(&Vector::y_axis(), ry)
+            * Rotation::from_axis_angle(&Vector::x_axis(), rx);
+        Isometry::from_parts(Vector::from(self.position).into(), rotation)
+    }
+
+    pub fn shape(&self) -> SharedShape {
+        match self.shape {
+            MapShape::Box { half_extents: [hx, hy, hz] } | MapShape::Ramp { half_extents: [hx, hy, hz] } => SharedShape::cuboid(hx, hy, hz),
+            MapShape::Cylinder { radius, half_height } => SharedShape::cylinder(half_height, radius),
+        }
+    }
+
+    /// "box #3 'ice_patch'" for error messages.
+    pub fn label(&self, index: usize) -> String {
+        let kind = match self.shape {
+            MapShape::Box { .. } => "box",
+            MapShape::Ramp { .. } => "ramp",
+            MapShape::Cylinder { .. } => "cylinder",
+        };
+        match &self.name {
+            Some(name) => format!("{} #{} '{}'", kind, index, name),
+            None => format!("{} #{}", kind, index),
+        }
+    }
+}
+
+// Typed rather than json!, so values serialize as f32 ("0.2", not "0.20000000298023224")
+#[derive(Serialize)]
+struct MapMessage<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    v: u32, // protocol.rs PROTOCOL_VERSION
+    objects: &'a [MapObject],
+}
+
+impl MapFile {
+    /// The "map" message sent to each client after the welcome.
+    pub fn client_message(&self) -> String {
+        let message = MapMessage { kind: "map", v: PROTOCOL_VERSION, objects: &self.objects };
+        serde_json::to_string(&message).unwrap_or_default()
+    }
+}
+
+/// Parsed and validated map (spawn overlap is checked by load_map).
+pub fn load_file(path: &str) -> Result<MapFile, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let map: MapFile = serde_json::from_str(&text).map_err(|e| format!("{}: {}", path, e))?;
+    if map.objects.len() > MAX_MAP_OBJECTS {
+        return Err(format!("{}: {} objects (max {})", path, map.objects.len(), MAX_MAP_OBJECTS));
+    }
+    for (i, object) in map.objects.iter().enumerate() {
+        object.validate().map_err(|e| format!("{}: {}: {}", path, object.label(i), e))?;
+    }
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_rng::GameRng;
+    use crate::physics::PhysicsWorld;
+    use crate::spawn::SpawnManager;
+
+    /// `json` written to a temp file named after the test; its path.
+    fn map_file(name: &str, json: &str) -> String {
+        let path = std::env::temp_dir().join(format!("avenlab-map-{}-{}.json", name, std::process::id()));
+        std::fs::write(&path, json).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn an_obstacle_on_a_spawn_point_rejects_the_whole_map() {
+        let spawn_areas = SpawnManager::new(4, GameRng::new(1).child("spawn")).spawn_areas();
+        let ([x, _, z], _) = spawn_areas[0];
+        let path = map_file(
+            "on-spawn",
+            &format!(
+                r#"{{"objects": [
+                    {{"kind": "box", "position": [200, 1.4, 200], "half_extents": [1, 0.5, 1]}},
+                    {{"kind": "cylinder", "position": [{}, 1.9, {}], "radius": 0.5, "half_height": 1.0, "name": "pillar"}}
+                ]}}"#,
+                x, z
+            ),
+        );
+        let mut phys = PhysicsWorld::new();
+        let bodies = phys.bodies.len();
+        let err = phys.load_map(&path, &spawn_areas).unwrap_err();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(err, format!("{}: cylinder #1 'pillar' overlaps the spawn area at ({}, {})", path, x, z));
+        assert_eq!(phys.bodies.len(), bodies, "nothing inserted");
+        assert!(phys.map.is_none());
+    }
+
+    #[test]
+    fn malformed_map_files_are_readable_errors() {
+        let cases = [
+            ("not-json", "{objects: [", "key must be a string"),
+            ("unknown-field", r#"{"objects": [], "spawns": []}"#, "unknown field `spawns`"),
+            ("unknown-kind", r#"{"objects": [{"kind": "cone", "position": [0, 1, 0]}]}"#, "unknown variant `cone`"),
+            ("no-position", r#"{"objects": [{"kind": "box", "half_extents": [1, 1, 1]}]}"#, "missing field `position`"),
+            (
+                "flat-box",
+                r#"{"objects": [{"kind": "box", "position": [0, 1, 0], "half_extents": [1, 0, 1], "name": "floor"}]}"#,
+                "box #0 'floor': half_extents must be positive (got [1.0, 0.0, 1.0])",
+            ),
+            (
+                "negative-friction",
+                r#"{"objects": [{"kind": "ramp", "position": [0, 1, 0], "half_extents": [1, 1, 1]}, {"kind": "box", "position": [0, 1, 0], "half_extents": [1, 1, 1], "friction": -1}]}"#,
+                "box #1: friction must be >= 0 (got -1)",
+            ),
+        ];
+        for (name, json, expected) in cases {
+            let path = map_file(name, json);
+            let err = load_file(&path).unwrap_err();
+            let _ = std::fs::remove_file(&path);
+            assert!(err.starts_with(&format!("{}: ", path)) && err.contains(expected), "{}: {}", name, err);
+        }
+
+        let missing = std::env::temp_dir().join("avenlab-map-missing.json");
+        let err = load_file(missing.to_str().unwrap()).unwrap_err();
+        assert!(err.contains("No such file"), "{}", err);
+    }
+}
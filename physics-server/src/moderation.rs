@@ -0,0 +1,299 @@
+// ==============================================================================
+// moderation.rs — DISPLAY NAME POLICY + CHAT FILTER
+// ------------------------------------------------------------------------------
+// Names:
+// - trimmed, 1..=max_name_len characters
+// - allowed characters: ASCII letters/digits, space, '_', '-', '.'
+// - must not contain a blocklisted term (case-folded substring match)
+// - collisions get "#N" appended (see unique_name)
+// - renames are rate limited per player (RenameLimiter, once per minute)
+//
+// Chat:
+// - same blocklist; ChatFilter::Reject drops the message, ChatFilter::Mask
+//   replaces each matched term with '*'
+//
+// Env:
+// - AVENLAB_NAME_MAX_LEN   (default 16)
+// - AVENLAB_BLOCKLIST      path to a file, one term per line ('#' = comment)
+// - AVENLAB_CHAT_FILTER    "reject" | "mask" (default mask)
+// ==============================================================================
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+pub const RENAME_COOLDOWN: Duration = Duration::from_secs(60);
+const CHAT_MAX_LEN: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChatFilter {
+    Reject,
+    Mask,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModerationError {
+    EmptyName,
+    NameTooLong { max: usize },
+    InvalidCharacter(char),
+    Blocked,
+    RateLimited { retry_in: Duration },
+    ChatTooLong { max: usize },
+    UnknownPlayer,
+}
+
+impl fmt::Display for ModerationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModerationError::EmptyName => write!(f, "name_empty"),
+            ModerationError::NameTooLong { max } => write!(f, "name_too_long (max {max})"),
+            ModerationError::InvalidCharacter(c) => write!(f, "name_invalid_character {c:?}"),
+            ModerationError::Blocked => write!(f, "blocked_term"),
+            ModerationError::RateLimited { retry_in } => write!(f, "rate_limited (retry in {}s)", retry_in.as_secs() + 1),
+            ModerationError::ChatTooLong { max } => write!(f, "chat_too_long (max {max})"),
+            ModerationError::UnknownPlayer => write!(f, "unknown_player"),
+        }
+    }
+}
+
+pub struct ModerationPolicy {
+    pub max_name_len: usize,
+    pub blocklist: Vec<String>, // lowercase terms
+    pub chat_filter: ChatFilter,
+}
+
+impl Default for ModerationPolicy {
+    fn default() -> Self {
+        Self { max_name_len: 16, blocklist: Vec::new(), chat_filter: ChatFilter::Mask }
+    }
+}
+
+impl ModerationPolicy {
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+
+        if let Some(n) = std::env::var("AVENLAB_NAME_MAX_LEN").ok().and_then(|v| v.parse().ok()) {
+            policy.max_name_len = n;
+        }
+        if std::env::var("AVENLAB_CHAT_FILTER").is_ok_and(|v| v.eq_ignore_ascii_case("reject")) {
+            policy.chat_filter = ChatFilter::Reject;
+        }
+        if let Ok(path) = std::env::var("AVENLAB_BLOCKLIST") {
+            match std::fs::read_to_string(&path) {
+                Ok(text) => {
+                    policy.blocklist = parse_blocklist(&text);
+                    println!("🛡  Loaded {} blocklist terms from {}", policy.blocklist.len(), path);
+                }
+                Err(e) => eprintln!("⚠️ Could not read blocklist {}: {}", path, e),
+            }
+        }
+        policy
+    }
+
+    fn allowed_char(c: char) -> bool {
+        c.is_ascii_alphanumeric() || matches!(c, ' ' | '_' | '-' | '.')
+    }
+
+    /// Validate a requested display name; returns the trimmed name.
+    pub fn check_name(&self, raw: &str) -> Result<String, ModerationError> {
+        let name = raw.trim();
+        if name.is_empty() {
+            return Err(ModerationError::EmptyName);
+        }
+        if name.chars().count() > self.max_name_len {
+            return Err(ModerationError::NameTooLong { max: self.max_name_len });
+        }
+        if let Some(c) = name.chars().find(|c| !Self::allowed_char(*c)) {
+            return Err(ModerationError::InvalidCharacter(c));
+        }
+        if !self.blocked_spans(name).is_empty() {
+            return Err(ModerationError::Blocked);
+        }
+        Ok(name.to_string())
+    }
+
+    /// Apply the chat filter: Ok(text to broadcast) or Err if rejected.
+    pub fn filter_chat(&self, text: &str) -> Result<String, ModerationError> {
+        let text = text.trim();
+        if text.chars().count() > CHAT_MAX_LEN {
+            return Err(ModerationError::ChatTooLong { max: CHAT_MAX_LEN });
+        }
+
+        let spans = self.blocked_spans(text);
+        if spans.is_empty() {
+            return Ok(text.to_string());
+        }
+
+        match self.chat_filter {
+            ChatFilter::Reject => Err(ModerationError::Blocked),
+            ChatFilter::Mask => Ok(text
+                .chars()
+                .enumerate()
+                .map(|(i, c)| if spans.iter().any(|&(a, b)| i >= a && i < b) { '*' } else { c })
+                .collect()),
+        }
+    }
+
+    /// Char-index spans [start, end) of blocklisted terms (case-folded).
+    fn blocked_spans(&self, text: &str) -> Vec<(usize, usize)> {
+        let folded: Vec<char> = text.chars().map(fold_char).collect();
+        let mut spans = Vec::new();
+
+        for term in &self.blocklist {
+            let term: Vec<char> = term.chars().collect();
+            if term.is_empty() || term.len() > folded.len() {
+                continue;
+            }
+            for start in 0..=folded.len() - term.len() {
+                if folded[start..start + term.len()] == term[..] {
+                    spans.push((start, start + term.len()));
+                }
+            }
+        }
+        spans
+    }
+}
+
+/// One char in, one char out, so char indices line up with the original.
+fn fold_char(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+pub fn parse_blocklist(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(|l| l.chars().map(fold_char).collect())
+        .collect()
+}
+
+/// Resolve collisions by appending "#N" (N = 2, 3, ...), keeping the base
+/// short enough that the suffixed name still fits max_len.
+pub fn unique_name(base: &str, max_len: usize, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(base) {
+        return base.to_string();
+    }
+    for n in 2.. {
+        let suffix = format!("#{}", n);
+        let keep = max_len.saturating_sub(suffix.len()).max(1);
+        let candidate = format!("{}{}", base.chars().take(keep).collect::<String>(), suffix);
+        if !taken(&candidate) {
+            return candidate;
+        }
+    }
+    unreachable!()
+}
+
+// ==========================================================
+// Rename rate limit (per player)
+// ==========================================================
+pub struct RenameLimiter {
+    cooldown: Duration,
+    last: HashMap<String, Instant>,
+}
+
+impl Default for RenameLimiter {
+    fn default() -> Self {
+        Self { cooldown: RENAME_COOLDOWN, last: HashMap::new() }
+    }
+}
+
+impl RenameLimiter {
+    /// First name choice (join) is free; afterwards one change per cooldown.
+    pub fn check(&self, player_id: &str, now: Instant) -> Result<(), ModerationError> {
+        match self.last.get(player_id) {
+            Some(&at) if now.duration_since(at) < self.cooldown => Err(ModerationError::RateLimited {
+                retry_in: self.cooldown - now.duration_since(at),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn record(&mut self, player_id: &str, now: Instant) {
+        self.last.insert(player_id.to_string(), now);
+    }
+
+    pub fn forget(&mut self, player_id: &str) {
+        self.last.remove(player_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(filter: ChatFilter) -> ModerationPolicy {
+        ModerationPolicy { blocklist: parse_blocklist("# comment\n\n  Darn \nheck\n"), chat_filter: filter, ..Default::default() }
+    }
+
+    #[test]
+    fn blocklist_skips_comments_and_case_folds() {
+        assert_eq!(parse_blocklist("# comment\n\n  Darn \nheck\n"), ["darn", "heck"]);
+    }
+
+    #[test]
+    fn names_are_trimmed_and_must_not_be_empty() {
+        let p = policy(ChatFilter::Mask);
+        assert_eq!(p.check_name("  Rally_Fan-2.0 "), Ok("Rally_Fan-2.0".to_string()));
+        assert_eq!(p.check_name("   "), Err(ModerationError::EmptyName));
+    }
+
+    #[test]
+    fn names_are_limited_in_characters_not_bytes() {
+        let p = ModerationPolicy { max_name_len: 4, ..Default::default() };
+        assert_eq!(p.check_name("abcd"), Ok("abcd".to_string()));
+        assert_eq!(p.check_name("abcde"), Err(ModerationError::NameTooLong { max: 4 }));
+        // Four chars, eight bytes: rejected for the character class, not the length
+        assert_eq!(p.check_name("éééé"), Err(ModerationError::InvalidCharacter('é')));
+    }
+
+    #[test]
+    fn names_outside_the_character_class_are_rejected() {
+        let p = ModerationPolicy::default();
+        assert_eq!(p.check_name("a<b>"), Err(ModerationError::InvalidCharacter('<')));
+        assert_eq!(p.check_name("tab\there"), Err(ModerationError::InvalidCharacter('\t')));
+    }
+
+    #[test]
+    fn blocklisted_terms_are_found_in_any_case() {
+        let p = policy(ChatFilter::Mask);
+        assert_eq!(p.check_name("xXDARNXx"), Err(ModerationError::Blocked));
+        assert!(p.check_name("Darwin").is_ok());
+    }
+
+    #[test]
+    fn collisions_append_a_counter_within_the_length_limit() {
+        let taken = ["Ace", "Ace#2", "Maximilian12345", "Maximilian123#2"];
+        let is_taken = |n: &str| taken.contains(&n);
+        assert_eq!(unique_name("Bob", 16, is_taken), "Bob");
+        assert_eq!(unique_name("Ace", 16, is_taken), "Ace#3");
+        assert_eq!(unique_name("Maximilian12345", 15, is_taken), "Maximilian123#3");
+    }
+
+    #[test]
+    fn chat_is_masked_or_rejected_per_policy() {
+        assert_eq!(policy(ChatFilter::Mask).filter_chat(" oh HECK, darn "), Ok("oh ****, ****".to_string()));
+        assert_eq!(policy(ChatFilter::Reject).filter_chat("oh heck"), Err(ModerationError::Blocked));
+        assert_eq!(policy(ChatFilter::Reject).filter_chat("gg"), Ok("gg".to_string()));
+        assert_eq!(
+            ModerationPolicy::default().filter_chat(&"a".repeat(CHAT_MAX_LEN + 1)),
+            Err(ModerationError::ChatTooLong { max: CHAT_MAX_LEN })
+        );
+    }
+
+    #[test]
+    fn renames_are_limited_to_one_per_cooldown() {
+        let mut limiter = RenameLimiter::default();
+        let t0 = Instant::now();
+        assert_eq!(limiter.check("p", t0), Ok(()), "the first name is free");
+        limiter.record("p", t0);
+
+        let later = t0 + Duration::from_secs(20);
+        assert_eq!(limiter.check("p", later), Err(ModerationError::RateLimited { retry_in: Duration::from_secs(40) }));
+        assert_eq!(limiter.check("q", later), Ok(()), "per player");
+        assert_eq!(limiter.check("p", t0 + RENAME_COOLDOWN), Ok(()));
+
+        limiter.forget("p");
+        assert_eq!(limiter.check("p", later), Ok(()));
+    }
+}
@@ -29,6 +29,8 @@
 //      optional aligning `), then apply all impulses to the chassis.
 // 3) pipeline.step(...)
 //    - Rapier integrates the final velocities/poses.
+// Steps longer than SUSPENSION_MAX_DT (low tick rates) run 1-3 as substeps;
+// deep slow motion (timestep scale below 0.5) adds more.
 // ------------------------------------------------------------------------------
 // Key dependencies:
 // - suspension_contact::build_suspension_contact()
@@ -40,20 +42,93 @@
 // src/physics.rs
 use rapier3d::prelude::*;
 use rapier3d::prelude::{InteractionGroups, Group};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use schemars::JsonSchema;
 use serde::Serialize;
-use crate::suspension_contact::{SuspensionContact, build_suspension_contact};
-use crate::aven_tire::anti_roll::{ apply_arb_load_transfer};
-use crate::aven_tire::steering::{ apply_vehicle_controls, SteeringState, SteeringConfig, solve_steering};
-use crate::aven_tire::{ ContactPatch, ControlInput, SolveContext, WheelId, solve_step};
-use crate::aven_tire::state::{TireState};
-use crate::vehicle::{Vehicle, VehicleConfig};
+use crate::suspension_contact::{RayCacheScene, SuspensionContact, WheelRayCache, build_suspension_contact, merge_axle_contacts};
+use crate::aven_tire::anti_roll::{ apply_arb_load_transfer, axle_arb_pairs};
+use crate::aven_tire::steering::{ apply_vehicle_controls, SteeringConfig, solve_steering};
+use crate::aven_tire::{ ContactPatch, ControlInput, Impulse, ImpulseSource, MAX_AXLES, SolveContext, WheelId, Vec3, solve_step};
+use crate::aven_tire::state::{TireState, WheelState};
+use crate::aven_tire::combined_slip::KammCircle;
+use crate::vehicle::{ConfigError, Payload, Drivetrain, Vehicle, VehicleBuilder, VehicleConfig};
+use crate::haptics::{HapticEvent, KerbDetector, kerb_event};
+use crate::debug_builders::build_chassis_box_wireframe;
+use crate::input_trace::{InputTrace, TracePlayback};
+use crate::input_sanity::AXIS_RANGES;
+use crate::telemetry::TelemetryRing;
+use crate::surface::{SurfaceRegistry, WheelSurface};
+use crate::airborne::{AIR_ANGULAR_DAMPING_SCALE, air_control_torque};
+use crate::recovery::{RECOVER_LIFT_M, upright_rotation};
+use crate::trails::{DebugTrail, WheelTrail, trail_len_from_env};
+use crate::terrain::Heightfield;
+use crate::map::{MapFile, SPAWN_CLEAR_HALF_HEIGHT_M, SPAWN_CLEAR_RADIUS_M};
+use crate::collisions::{CollisionRecord, CollisionTracker, WORLD};
 // use crate::aven_tire::v_mag;
 
 const GROUP_GROUND: Group  = Group::from_bits_truncate(0b0001);
 const GROUP_CHASSIS: Group = Group::from_bits_truncate(0b0010);
+const GROUP_WHEEL: Group    = Group::from_bits_truncate(0b0100); // wheel colliders (VehicleConfig::wheel_colliders)
+const GROUP_OBSTACLE: Group = Group::from_bits_truncate(0b1000); // curbs / props, hit by wheel colliders
 
-#[derive(Clone, Serialize)]
+/// What a chassis collides with: the ground, plus other chassis with
+/// vehicle_collisions (a pair collides when each filter has the other's membership)
+fn chassis_filter(config: &VehicleConfig) -> Group {
+    if config.vehicle_collisions { GROUP_GROUND | GROUP_CHASSIS } else { GROUP_GROUND }
+}
+
+/// Suspension ray groups: whatever the chassis collides with, plus obstacles.
+/// Cars that ghost through each other don't ride on each other's roofs either.
+pub fn wheel_ray_groups(config: &VehicleConfig) -> InteractionGroups {
+    InteractionGroups::new(GROUP_CHASSIS | GROUP_WHEEL, chassis_filter(config) | GROUP_OBSTACLE)
+}
+
+const SUSPENSION_SAG_M: f32 = 0.065; // static sag per wheel (m)
+const SUSPENSION_ZETA: f32 = 1.05;   // damping ratio (0.7–1.0)
+const MAX_PAYLOAD_KG: f32 = 2000.0;  // set_payload upper bound
+const PAYLOAD_SIZE_M: f32 = 0.5;     // payload inertia: a solid cube this wide
+
+// Safety teleport cooldown per vehicle (anti "drive off the map" exploit),
+// simulated seconds; converted to ticks with the step's dt
+const MIN_RESPAWN_INTERVAL_S: f32 = 5.0;
+
+// Spawn height over the flat ground box (top at FLAT_GROUND_TOP); with a
+// heightfield the same clearance is kept above the terrain (terrain.rs)
+const SPAWN_HEIGHT: f32 = 1.3;
+const FLAT_GROUND_TOP: f32 = 0.9;
+
+const GROUND_FRICTION: f32 = 1.2; // ground collider; map objects scale it by their grip
+
+// Startup warm-up / spawn-time queries
+#[cfg(test)]
+const GROUND_PROBE_TOP: f32 = 1_000.0;  // ground_height ray start (m), the world's position bound
+const WARM_UP_DEFAULT_PLAYERS: usize = 16; // capacity hint without AVENLAB_MAX_PLAYERS
+const WARM_UP_MAX_PLAYERS: usize = 1024;
+
+// Embedded wheel recovery (see suspension_contact.rs)
+const EMBED_RECOVERY_RATE: f32 = 4.0; // 1/s, penetration depth -> separation speed
+const EMBED_MAX_SPEED: f32 = 1.0;     // m/s, cap on the separation speed / per-tick dv
+const EMBEDDED_RAY_COLOR: [f32; 3] = [1.0, 0.0, 1.0];
+
+// Magnet abilities (attract / repel another vehicle)
+pub const MAGNET_FORCE_N: f32 = 8_000.0;  // N while the ability is held (~0.8 g on a GT86)
+const MAGNET_MAX_IMPULSE: f32 = 400.0;    // N*s per body per call (no instant yanks)
+const MAGNET_RANGE: f32 = 40.0;           // m, farther targets are unaffected
+
+// Bullet time (replay / kill cam): simulated seconds per real second
+const TIMESTEP_SCALE_MIN: f32 = 0.1;
+const TIMESTEP_SCALE_MAX: f32 = 2.0;
+const TIMESTEP_SCALE_FULL_QUALITY: f32 = 0.5; // below this, substeps scale up
+
+// Longest step the explicit spring / damper / tire solve takes in one go;
+// longer ticks (AVENLAB_TICK_HZ below ~55, fast-forward) are split into substeps
+const SUSPENSION_MAX_DT: f32 = 1.0 / 55.0;
+
+// Corner weights (setup report): suspension load low-passed over the damper's
+// tick-to-tick chatter so a parked car reads its static load
+const CORNER_LOAD_TAU: f32 = 0.25; // s
+
+#[derive(Clone, Serialize, JsonSchema)]
 pub struct DebugRay {
     pub origin: [f32; 3],
     pub direction: [f32; 3],
@@ -62,7 +137,7 @@ pub struct DebugRay {
     pub color: [f32; 3],
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, JsonSchema)]
 pub struct DebugSlipRay {
     pub origin: [f32; 3],
     pub direction: [f32; 3],
@@ -71,7 +146,7 @@ pub struct DebugSlipRay {
     pub color: [f32; 3],
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, JsonSchema)]
 pub struct DebugWheel {
     pub id: String,                 // "FL", "FR", "RL", "RR"
 
@@ -83,12 +158,16 @@ pub struct DebugWheel {
     pub steer: f32,
     pub steering: bool,
     pub drive: bool,
+    pub spin_rpm: f32,              // visual wheel RPM (magnitude)
+    pub spin_dir: f32,              // +1 forward, -1 reverse, 0 stopped
+    pub embedded: bool,             // ray started inside the ground (depenetrating)
+    pub surface: Option<WheelSurface>, // what the ray hit (surface.rs)
 
     // pub lateral_force: [f32; 3],                // for debug visualization
     // pub lateral_magnitude: f32,                 // for debug visualization
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, JsonSchema)]
 pub struct DebugOverlay {
     pub chassis: Option<DebugChassis>,
     pub suspension_rays: Vec<DebugRay>,
@@ -97,6 +176,46 @@ pub struct DebugOverlay {
     pub wheels: Vec<DebugWheel>,
     pub chassis_right: [f32; 3],
     pub slip_vectors: Vec<DebugSlipRay>,
+    pub tire_forces: Vec<DebugRay>,    // per grounded wheel: summed tire force at the contact
+    pub drive_split: Option<[f32; 2]>, // [left, right] drive torque share
+    pub brake_force_axle: Option<[f32; 2]>, // [front, rear] applied brake force (N)
+    pub wireframes: Vec<DebugRay>,     // chassis box edges (built in debug_snapshot)
+    pub trails: Vec<DebugTrail>,       // per-wheel contact / force history (trails.rs)
+    pub flipped: Option<f32>,          // seconds counted as flipped (recovery.rs), None = upright
+    pub input_stale: Option<f32>,      // seconds without client input past input_timeout_s (input_hold.rs)
+}
+
+// Debug overlay categories (bitmask requested per subscriber)
+pub const DEBUG_SUSPENSION_RAYS: u32 = 1 << 0;
+pub const DEBUG_LOAD_BARS: u32       = 1 << 1;
+pub const DEBUG_ARB_LINKS: u32       = 1 << 2;
+pub const DEBUG_SLIP_VECTORS: u32    = 1 << 3;
+pub const DEBUG_WHEELS: u32          = 1 << 4;
+pub const DEBUG_TIRE_FORCES: u32     = 1 << 5; // tire force vectors at each contact
+pub const DEBUG_DYNAMICS: u32        = 1 << 6; // drive_split, brake_force_axle
+pub const DEBUG_WIREFRAMES: u32      = 1 << 7; // chassis box edges
+pub const DEBUG_TRAILS: u32          = 1 << 8; // per-wheel contact / force trails
+pub const DEBUG_ALL: u32             = 0x1ff;
+
+// High-frequency primitives dropped by auto LOD at speed
+pub const DEBUG_HIGH_FREQUENCY: u32 =
+    DEBUG_SUSPENSION_RAYS | DEBUG_LOAD_BARS | DEBUG_ARB_LINKS | DEBUG_SLIP_VECTORS | DEBUG_TIRE_FORCES | DEBUG_WIREFRAMES;
+
+const CHASSIS_WIREFRAME_COLOR: [f32; 3] = [0.9, 0.9, 0.2];
+const TIRE_FORCE_COLOR: [f32; 3] = [1.0, 0.1, 0.8];
+/// Tire force drawn at full length (1.25 m, same scale as the load bars)
+const TIRE_FORCE_FULL_SCALE: f32 = 12000.0;
+
+/// Lengths of the overlay lists before a vehicle's primitives are pushed
+#[derive(Clone, Copy)]
+struct OverlayMark {
+    suspension_rays: usize,
+    load_bars: usize,
+    arb_links: usize,
+    wheels: usize,
+    slip_vectors: usize,
+    tire_forces: usize,
+    trails: usize,
 }
 
 impl DebugOverlay {
@@ -106,6 +225,105 @@ impl DebugOverlay {
         self.wheels.clear();
         self.arb_links.clear(); 
         self.slip_vectors.clear(); 
+        self.tire_forces.clear();
+        self.wireframes.clear();
+        self.trails.clear();
+    }
+
+    fn mark(&self) -> OverlayMark {
+        OverlayMark {
+            suspension_rays: self.suspension_rays.len(),
+            load_bars: self.load_bars.len(),
+            arb_links: self.arb_links.len(),
+            wheels: self.wheels.len(),
+            slip_vectors: self.slip_vectors.len(),
+            tire_forces: self.tire_forces.len(),
+            trails: self.trails.len(),
+        }
+    }
+
+    /// Primitives pushed since `mark` (one vehicle's share), with the
+    /// per-vehicle scalars as they stand now.
+    fn since(&self, mark: OverlayMark) -> DebugOverlay {
+        DebugOverlay {
+            chassis: self.chassis.clone(),
+            suspension_rays: self.suspension_rays[mark.suspension_rays..].to_vec(),
+            load_bars: self.load_bars[mark.load_bars..].to_vec(),
+            arb_links: self.arb_links[mark.arb_links..].to_vec(),
+            wheels: self.wheels[mark.wheels..].to_vec(),
+            chassis_right: self.chassis_right,
+            slip_vectors: self.slip_vectors[mark.slip_vectors..].to_vec(),
+            tire_forces: self.tire_forces[mark.tire_forces..].to_vec(),
+            drive_split: self.drive_split,
+            brake_force_axle: self.brake_force_axle,
+            wireframes: Vec::new(),
+            trails: self.trails[mark.trails..].to_vec(),
+            flipped: self.flipped,
+            input_stale: self.input_stale,
+        }
+    }
+
+    fn empty() -> DebugOverlay {
+        DebugOverlay {
+            chassis: None,
+            suspension_rays: Vec::new(),
+            load_bars: Vec::new(),
+            arb_links: Vec::new(),
+            wheels: Vec::new(),
+            chassis_right: [1.0, 0.0, 0.0],
+            slip_vectors: Vec::new(),
+            tire_forces: Vec::new(),
+            drive_split: None,
+            brake_force_axle: None,
+            wireframes: Vec::new(),
+            trails: Vec::new(),
+            flipped: None,
+            input_stale: None,
+        }
+    }
+
+    /// Add another vehicle's primitives after ours; chassis and the
+    /// per-vehicle scalars become that vehicle's (as in the shared overlay,
+    /// where the last vehicle stepped wins).
+    fn append(&mut self, other: DebugOverlay) {
+        self.chassis = other.chassis;
+        self.suspension_rays.extend(other.suspension_rays);
+        self.load_bars.extend(other.load_bars);
+        self.arb_links.extend(other.arb_links);
+        self.wheels.extend(other.wheels);
+        self.chassis_right = other.chassis_right;
+        self.slip_vectors.extend(other.slip_vectors);
+        self.tire_forces.extend(other.tire_forces);
+        self.drive_split = other.drive_split;
+        self.brake_force_axle = other.brake_force_axle;
+        self.trails.extend(other.trails);
+        self.flipped = other.flipped;
+        self.input_stale = other.input_stale;
+    }
+
+    /// Copy of the overlay containing only the requested categories.
+    /// Chassis pose is always kept so the client can anchor the rest.
+    pub fn filtered(&self, mask: u32) -> DebugOverlay {
+        fn pick<T: Clone>(on: bool, v: &[T]) -> Vec<T> {
+            if on { v.to_vec() } else { Vec::new() }
+        }
+
+        DebugOverlay {
+            chassis: self.chassis.clone(),
+            suspension_rays: pick(mask & DEBUG_SUSPENSION_RAYS != 0, &self.suspension_rays),
+            load_bars: pick(mask & DEBUG_LOAD_BARS != 0, &self.load_bars),
+            arb_links: pick(mask & DEBUG_ARB_LINKS != 0, &self.arb_links),
+            wheels: pick(mask & DEBUG_WHEELS != 0, &self.wheels),
+            chassis_right: self.chassis_right,
+            slip_vectors: pick(mask & DEBUG_SLIP_VECTORS != 0, &self.slip_vectors),
+            tire_forces: pick(mask & DEBUG_TIRE_FORCES != 0, &self.tire_forces),
+            drive_split: if mask & DEBUG_DYNAMICS != 0 { self.drive_split } else { None },
+            brake_force_axle: if mask & DEBUG_DYNAMICS != 0 { self.brake_force_axle } else { None },
+            wireframes: pick(mask & DEBUG_WIREFRAMES != 0, &self.wireframes),
+            trails: pick(mask & DEBUG_TRAILS != 0, &self.trails),
+            flipped: self.flipped,
+            input_stale: self.input_stale,
+        }
     }
 }
 
@@ -124,15 +342,84 @@ pub struct Wheel {
     pub steer: bool,             // is this a steering wheel?
 
     pub tire_state: TireState,
+    pub wheel_state: WheelState,
+    pub ray_cache: WheelRayCache,
+    pub kerb: KerbDetector,
+    pub collider: Option<ColliderHandle>, // ball on GROUP_WHEEL, only with wheel_colliders
+    pub trail: WheelTrail,       // debug overlay contact / force history
+    pub axle: usize,             // axle index, 0 = front-most (brake bias, ARB pairing)
+    pub prev_compression: Option<f32>, // last solve's compression (damper velocity), None after airborne
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, JsonSchema)]
 pub struct DebugChassis {
     pub position: [f32; 3],
     pub rotation: [f32; 4], // quaternion
     pub half_extents: [f32; 3],
 }
 
+/// Obstacle sensing result for AI drivers (distances in meters)
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct SensorResult {
+    pub front_clear: bool,
+    pub left_clear: bool,
+    pub right_clear: bool,
+    pub front_dist: f32,
+    pub left_dist: f32,
+    pub right_dist: f32,
+}
+
+/// Solver contact impulses on a vehicle chassis (one per contact manifold)
+#[derive(Clone, Debug, Serialize)]
+pub struct ContactForce {
+    pub other_player_id: Option<String>, // None = static world / non-player body
+    pub point: [f32; 3],                 // impulse-weighted contact point (world)
+    pub normal_impulse: f32,             // N*s, summed over manifold points
+    pub tangent_impulse: f32,            // N*s, friction impulse magnitude
+}
+
+/// Wheel placement for client meshes (chassis-local, +Z forward / +X left)
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct WheelLayout {
+    pub id: String,
+    pub offset: [f32; 3],
+    pub radius: f32,
+    pub rest_length: f32,
+    pub max_length: f32,
+    pub steer: bool,
+    pub drive: bool,
+}
+
+/// Everything a client needs to place wheels + chassis for one vehicle
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub struct VehicleLayout {
+    pub wheels: Vec<WheelLayout>,
+    pub chassis_half_extents: [f32; 3],
+    pub chassis_com_offset: [f32; 3],
+    pub vehicle: String, // config name (client model selection)
+}
+
+/// Independently rotating turret on a combat vehicle's hull.
+/// Yaw is physical (motorized revolute joint about the hull's local Y);
+/// pitch is a clamped barrel elevation carried for the client only.
+pub struct Turret {
+    pub body: RigidBodyHandle,
+    pub joint: ImpulseJointHandle,
+    pub yaw_angle: f32,   // commanded yaw (rad, relative to hull)
+    pub pitch_angle: f32, // barrel elevation (rad)
+    pub offset: [f32; 3], // hull-local mount point
+    pub yaw_limit: f32,   // rad, symmetric
+}
+
+pub type TurretId = String; // turrets are keyed by their vehicle's player id
+
+const TURRET_HALF_EXTENTS: [f32; 3] = [0.6, 0.25, 0.6];
+const TURRET_MASS: f32 = 150.0;
+const TURRET_MOTOR_STIFFNESS: f32 = 60.0; // acceleration-based motor
+const TURRET_MOTOR_DAMPING: f32 = 15.0;
+const TURRET_PITCH_MIN: f32 = -0.17; // ~ -10°
+const TURRET_PITCH_MAX: f32 = 0.52;  // ~ +30°
+
 enum BodyImpulse {
     Linear {
         handle: RigidBodyHandle,
@@ -153,6 +440,7 @@ pub const GT86: VehicleConfig = VehicleConfig {
     max_speed: 55.0,          // m/s
     linear_damping: 0.08,     // coasting comes back
     angular_damping: 0.6,     // drag
+    powertrain_efficiency_at_speed: [(0.0, 0.75), (20.0, 1.0), (40.0, 0.7), (55.0, 0.4)], // peak ~20 m/s
 
     wheelbase: 2.5,           // meters (front axle to rear axle)
     track_width: 1.5,         // meters (left to right)
@@ -162,11 +450,11 @@ pub const GT86: VehicleConfig = VehicleConfig {
     chassis_half_extents: [1.0, 0.35, 2.1], // GT86-ish
     chassis_com_offset: [0.0, -0.15, 0.0], // slightly below visual center
 
-    arb_front: 18_000.0,      // N/m
-    arb_rear: 12_000.0,       // N/m
+    arb_rates: [18_000.0, 12_000.0, 0.0, 0.0], // N/m per axle, front to rear
     
     load_sensitivity: 0.15,   // k spring load sensitivity
     mu_base: 0.85,             // base friction coefficient
+    kamm: KammCircle { cx: 0.95, cy: 1.05 }, // slightly more lateral capacity
 
     // NEW: assists (toggles + thresholds)
     abs_enabled: true,
@@ -176,6 +464,38 @@ pub const GT86: VehicleConfig = VehicleConfig {
     abs_nx_limit: 0.90,
     tcs_nx_limit: 0.85,
 
+    drivetrain: Drivetrain::Rwd,
+
+    // torque vectoring (off: RWD open diff)
+    tv_enabled: false,
+    tv_gain: 0.35,
+    tv_max_bias: 0.3,
+    tv_min_speed: 5.0,
+
+    stability_assist: 0.0,
+
+    air_control_torque: 1500.0, // ~0.7 rad/s² of pitch: trims a jump, can't flip the car
+
+    engine_brake_coefficient: 100.0, // ~0.15 g lifting off at 20 m/s
+
+    brake_bias: 0.6,
+    brake_bias_range: [0.45, 0.75],
+
+    wheel_colliders: false,
+    vehicle_collisions: true,
+
+    auto_flip_recover: true,
+    flip_timeout_s: 3.0,
+
+    input_timeout_s: 0.5,
+};
+
+/// GT86 with arcade yaw stabilization (spins die out without steering),
+/// ghosting through other cars
+pub const ARCADE_GT86: VehicleConfig = VehicleConfig {
+    stability_assist: 0.6,
+    vehicle_collisions: false,
+    ..GT86
 };
 
 pub const TANK: VehicleConfig = VehicleConfig {
@@ -185,6 +505,7 @@ pub const TANK: VehicleConfig = VehicleConfig {
     max_speed: 18.0,
     linear_damping: 2.0,
     angular_damping: 4.0,
+    powertrain_efficiency_at_speed: [(0.0, 0.9), (6.0, 1.0), (12.0, 0.8), (18.0, 0.4)],
 
     wheelbase: 2.5,           // meters (front axle to rear axle)
     track_width: 1.5,         // meters (left to right)
@@ -196,16 +517,60 @@ pub const TANK: VehicleConfig = VehicleConfig {
 
     mu_base: 8.0,
     load_sensitivity: 0.30,
+    kamm: KammCircle { cx: 1.0, cy: 1.0 },
 
-    arb_front: 18_000.0,
-    arb_rear: 12_000.0,
+    arb_rates: [18_000.0, 12_000.0, 0.0, 0.0],
 
     abs_enabled: true,
     tcs_enabled: true,
     abs_nx_limit: 0.90,
     tcs_nx_limit: 0.85,
+
+    drivetrain: Drivetrain::Rwd,
+
+    tv_enabled: false,
+    tv_gain: 0.35,
+    tv_max_bias: 0.3,
+    tv_min_speed: 3.0,
+
+    stability_assist: 0.0,
+
+    air_control_torque: 0.0,
+
+    engine_brake_coefficient: 4000.0, // ~0.2 g at 15 m/s
+
+    brake_bias: 0.5,
+    brake_bias_range: [0.4, 0.6],
+
+    wheel_colliders: false,
+    vehicle_collisions: true,
+
+    auto_flip_recover: true,
+    flip_timeout_s: 3.0,
+
+    input_timeout_s: 0.5,
 };
 
+/// Config a player gets when they don't ask for one (or ask for an unknown one).
+pub const DEFAULT_VEHICLE: &str = "gt86";
+
+/// Built-in configs by name (--report setup, --sweep, preset bases).
+pub fn vehicle_config_by_name(name: &str) -> Option<VehicleConfig> {
+    match name {
+        "gt86" => Some(GT86),
+        "arcade" => Some(ARCADE_GT86),
+        "tank" => Some(TANK),
+        _ => None,
+    }
+}
+
+fn builtin_vehicle_presets() -> BTreeMap<String, VehicleConfig> {
+    ["gt86", "arcade", "tank"]
+        .into_iter()
+        .filter_map(|name| Some((name.to_string(), vehicle_config_by_name(name)?)))
+        .collect()
+}
+
 #[inline] fn v3(v: Vector<Real>) -> [f32; 3] { [v.x, v.y, v.z] }
 #[inline] fn p3(p: Point<Real>)  -> [f32; 3] { [p.x, p.y, p.z] }
 
@@ -245,28 +610,278 @@ fn effective_mass_at_point(
     }
 }
 
+// Restitution used by the analytical collision response (0 = plastic, 1 = elastic)
+const COLLISION_RESTITUTION: f32 = 0.2;
+// Penetration depth → extra separation velocity (1/s), Baumgarte-style bias
+const COLLISION_DEPTH_BIAS: f32 = 10.0;
+
+/// Inverse mass seen by an impulse along `dir_world` applied at `point_world`:
+/// 1/m + ((I^-1 (r × n)) × r) · n
+fn inv_mass_at_point(
+    body: &RigidBody,
+    point_world: Point<Real>,
+    dir_world: Vector<Real>,
+) -> f32 {
+    let mp = body.mass_properties();
+    let inv_m = mp.local_mprops.inv_mass;
+
+    let com_world: Point<Real> = body.position() * mp.local_mprops.local_com;
+    let r = point_world - com_world;
+    let rxn = r.cross(&dir_world);
+
+    // effective_world_inv_inertia_sqrt is sqrt(I^-1); apply it twice
+    let inv_i_sqrt = mp.effective_world_inv_inertia_sqrt;
+    let ang = (inv_i_sqrt * (inv_i_sqrt * rxn)).cross(&r).dot(&dir_world);
+
+    inv_m + ang.max(0.0)
+}
+
 /// Accumulated impulses for one rigid body this frame
+#[derive(Default)]
 struct ImpulseAccumulator {
     linear: Vec<Vector<Real>>,
     at_points: Vec<(Vector<Real>, Point<Real>)>,
+    angular: Vec<Vector<Real>>, // torque impulses (N*m*s, world)
 }
 
 impl ImpulseAccumulator {
-    fn new() -> Self {
-        Self { linear: vec![], at_points: vec![] }
+    fn clear(&mut self) {
+        self.linear.clear();
+        self.at_points.clear();
+        self.angular.clear();
     }
 
-    fn apply(self, body: &mut RigidBody) {
-        for j in self.linear {
+    /// Apply and empty (capacity is kept for the next vehicle).
+    fn apply(&mut self, body: &mut RigidBody) {
+        for j in self.linear.drain(..) {
             body.apply_impulse(j, true);
         }
-        for (j, p) in self.at_points {
+        for (j, p) in self.at_points.drain(..) {
             body.apply_impulse_at_point(j, p, true);
         }
+        for t in self.angular.drain(..) {
+            body.apply_torque_impulse(t, true);
+        }
+    }
+}
+
+/// apply_suspension scratch space, reused by every vehicle every tick so the
+/// per-vehicle contact / impulse lists don't allocate once warmed up.
+#[derive(Default)]
+struct SolveBuffers {
+    contacts: Vec<ContactPatch>,   // tire contact patches (PHASE 1 → 3B)
+    suspension_contacts: Vec<(WheelId, SuspensionContact)>,
+    axle_compression: HashMap<WheelId, f32>,
+    axle_normal_force: HashMap<WheelId, f32>,
+    impulses: Vec<Impulse>,        // solve_step output
+    accumulator: ImpulseAccumulator,
+}
+
+// ==========================================================
+// Impulse audit log (post-hoc debugging)
+// ------------------------------------------------------------------------------
+// With AVENLAB_DEBUG_FORCES=1 every impulse applied to a chassis in
+// apply_suspension is recorded here. Fixed-capacity ring buffer: allocated
+// once, oldest entry dropped when full, so it is cheap in release builds.
+// ==========================================================
+pub const IMPULSE_AUDIT_CAPACITY: usize = 600; // ~10 s at 60 Hz
+
+#[derive(Debug, Clone)]
+pub struct ImpulseAuditEntry {
+    pub tick: u64,
+    pub player_id: String,
+    pub wheel_id: Option<String>, // None for chassis-wide impulses (stability assist)
+    pub source: ImpulseSource,
+    pub impulse: [f32; 3],        // N*s (N*m*s for StabilityAssist / AirControl), world
+    pub point: Option<[f32; 3]>,  // None => applied at COM
+    pub normal_force: f32,        // wheel load when applied (N)
+}
+
+// ==========================================================
+// Lag compensation: per-vehicle pose history
+// ------------------------------------------------------------------------------
+// After every step each vehicle's chassis pose is pushed (tagged with the
+// tick the snapshot for that state carries). Hit detection (fire_projectile)
+// rewinds a target to the tick the shooter saw with get_position_at_tick.
+// ==========================================================
+pub const INPUT_HISTORY_CAPACITY: usize = 180; // 3 s at 60 Hz
+pub const PROJECTILE_RANGE_M: f32 = 300.0;
+
+/// A fire_projectile hit on another vehicle's chassis (rewound pose).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectileHit {
+    pub target: String,
+    pub distance: f32,     // m from the muzzle
+    pub point: [f32; 3],   // world, where the target was at the rewound tick
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct InputHistoryFrame {
+    pub tick: u64,
+    pub position: [f32; 3],
+    pub rotation: [f32; 4], // quaternion [x, y, z, w]
+}
+
+fn push_audit(log: &mut VecDeque<ImpulseAuditEntry>, entry: ImpulseAuditEntry) {
+    if log.len() == IMPULSE_AUDIT_CAPACITY {
+        log.pop_front();
+    }
+    log.push_back(entry);
+}
+
+/// Moment of inertia about world up (kg*m^2): 1 / (up · I⁻¹ · up)
+fn yaw_inertia(body: &RigidBody) -> f32 {
+    let up = vector![0.0, 1.0, 0.0];
+    let inv_i_sqrt = body.mass_properties().effective_world_inv_inertia_sqrt;
+    let inv_i_up = inv_i_sqrt * (inv_i_sqrt * up);
+    1.0 / up.dot(&inv_i_up).max(1e-6)
+}
+
+
+
+// ==========================================================
+// SuspensionContact -> tire solver ContactPatch (one wheel, or a merged axle)
+// ==========================================================
+fn build_contact_patch(
+    id: WheelId,
+    contact: &SuspensionContact,
+    wheel: &Wheel,
+    vehicle: &Vehicle,
+    body_ro: &RigidBody,
+) -> ContactPatch {
+    let forward = if contact.forward.magnitude_squared() < 1e-6 {
+        body_ro.position().rotation * vector![0.0, 0.0, 1.0]
+    } else { contact.forward };
+
+    let v = contact.point_vel;
+
+    // suspension axis (world-space)
+    let n = contact.ground_normal;
+
+    // planar/tangent velocity at contact
+    let v_n = v.dot(&n);
+    let v_t = v - n * v_n;
+
+    // safe normalize
+    let speed_t = v_t.norm();
+    let brake_dir = if speed_t > 1e-4 {
+        -v_t / speed_t   // oppose motion
+    } else {
+        // if nearly stopped, fall back to opposing v_long in wheel frame
+        let s = if contact.v_long >= 0.0 { -1.0 } else { 1.0 };
+        forward * s
+    };
+
+    let yaw_rate = body_ro.angvel().y; // assuming Y-up
+
+    let com_world: Point<Real> = *body_ro.center_of_mass();
+    let relative_com = contact.apply_point - com_world;
+
+    ContactPatch {
+        wheel: id,
+        axle: wheel.axle,
+        grounded: contact.grounded,
+        hit_point: p3(contact.hit_point),
+        apply_point: p3(contact.apply_point),
+        forward: v3(forward),
+        side: v3(contact.side),
+        v_long: contact.v_long,
+        v_lat: contact.v_lat,
+        normal_force:contact.normal_force,
+        mu_lat: contact.mu_lat,
+        mu_long: contact.mu_long,
+        roll_factor: contact.roll_factor,
+        drive: wheel.drive,
+        brake: vehicle.brake,
+        steer_angle: vehicle.steer_angle,
+        compression_ratio: contact.compression_ratio,
+        vel_world: v3(contact.point_vel),
+        brake_dir: v3(brake_dir),
+        speed_planar: speed_t,
+        yaw_rate,
+        relative_com: v3(relative_com),
+        tire_state: wheel.tire_state,
+        drive_scale: 1.0,
+    }
+}
+
+fn with_wireframes(mut overlay: DebugOverlay) -> DebugOverlay {
+    if let Some(chassis) = &overlay.chassis {
+        overlay.wireframes = build_chassis_box_wireframe(chassis, CHASSIS_WIREFRAME_COLOR);
+    }
+    overlay
+}
+
+// ==========================================================
+// Road camber (no heightfield): rotate the flat-up normal about the
+// road-forward axis, taken as the chassis heading projected onto the
+// ground. Rotating +deg about +Z tilts the normal toward chassis -X
+// (right), so the suspension push gains a component toward the low
+// (right) edge and the tire plane tilts with it.
+// NOTE: the brush model ignores lateral slip below its deadzone
+// (~1.5 m/s), so even parked cars creep toward the low edge. Keep the
+// camber small (1–3°); default is 0 (flat).
+// ==========================================================
+fn camber_ground_normal(rot: &Rotation<Real>, camber_deg: f32) -> Vector<Real> {
+    let up = vector![0.0, 1.0, 0.0];
+    if camber_deg == 0.0 {
+        return up;
+    }
+
+    let fwd = rot * vector![0.0, 0.0, 1.0];
+    let fwd_flat = vector![fwd.x, 0.0, fwd.z];
+    let axis = if fwd_flat.norm_squared() > 1e-6 {
+        UnitVector::new_normalize(fwd_flat)
+    } else {
+        Vector::z_axis()
+    };
+
+    Rotation::from_axis_angle(&axis, camber_deg.to_radians()) * up
+}
+
+// ==========================================================
+// Wheel colliders (VehicleConfig::wheel_colliders)
+// Ball centre follows the suspension: the ray model puts the wheel
+// centre (radius - suspension length) above the mount, with
+// suspension length = rest - compression. Airborne = compression 0.
+// ==========================================================
+fn wheel_collider_position(wheel: &Wheel, compression: f32) -> Isometry<Real> {
+    let lift = wheel.radius - (wheel.rest_length - compression);
+    Isometry::translation(wheel.offset.x, wheel.offset.y + lift, wheel.offset.z)
+}
+
+fn place_wheel_collider(colliders: &mut ColliderSet, wheel: &Wheel, compression: f32) {
+    if let Some(collider) = wheel.collider.and_then(|h| colliders.get_mut(h)) {
+        collider.set_position_wrt_parent(wheel_collider_position(wheel, compression));
     }
 }
 
+// ==========================================================
+// Payload static load split (set_payload)
+// Lever rule between the axles (z) and between the sides (x):
+// a point mass over the rear axle puts all of its weight on the
+// rear wheels, one on the centreline splits it left/right evenly.
+// ==========================================================
+fn payload_corner_shares(wheels: &[Wheel], offset: [f32; 3]) -> Vec<f32> {
+    let (x_min, x_max, z_min, z_max) = wheels.iter().fold(
+        (f32::MAX, f32::MIN, f32::MAX, f32::MIN),
+        |(x0, x1, z0, z1), w| (x0.min(w.offset.x), x1.max(w.offset.x), z0.min(w.offset.z), z1.max(w.offset.z)),
+    );
+    let lever = |p: f32, lo: f32, hi: f32| if hi - lo > 1e-3 { ((p - lo) / (hi - lo)).clamp(0.0, 1.0) } else { 0.5 };
+    let front = lever(offset[2], z_min, z_max);
+    let right = lever(offset[0], x_min, x_max);
+    let z_mid = 0.5 * (z_min + z_max);
+    let x_mid = 0.5 * (x_min + x_max);
 
+    wheels
+        .iter()
+        .map(|w| {
+            let axle = if w.offset.z >= z_mid { front } else { 1.0 - front };
+            let side = if w.offset.x >= x_mid { right } else { 1.0 - right };
+            axle * side
+        })
+        .collect()
+}
 
 pub struct PhysicsWorld {
     pub gravity: Vector<Real>, // gravity vector
@@ -280,22 +895,84 @@ pub struct PhysicsWorld {
     pub multibody_joints: MultibodyJointSet,// for articulated bodies
     pub ccd: CCDSolver, // continuous collision detection
     pub query_pipeline: QueryPipeline, // for raycasting
+    query_pipeline_sig: Option<u64>, // collider set the query pipeline was last built from
     // pub suspension: VehicleSuspension,
     pub wheels: HashMap<RigidBodyHandle, Vec<Wheel>>, // body handle → wheels
     pub vehicles: HashMap<String, Vehicle>, // playerId → vehicle   
+    pub vehicle_presets: BTreeMap<String, VehicleConfig>, // spawnable configs by name (built-ins + vehicle_presets.rs)
     pub body_to_player: HashMap<RigidBodyHandle, String>, // body handle → playerId
+    pub turrets: HashMap<String, Turret>, // playerId → turret
+    pub spring_joints: Vec<ImpulseJointHandle>, // tow cables / anchors (create_spring_joint)
+    pub static_epoch: u64, // bumped when static colliders change (wheel ray caches)
+    pub road_camber_deg: f32, // cross-slope, + = road falls away to the vehicle's right
+    pub haptic_events: Vec<HapticEvent>, // rumble hints from the last step (drained by main)
+    pub collision_events: Vec<CollisionRecord>, // chassis impacts from the last step (drained by main)
+    pub suspension_time: std::time::Duration, // apply_suspension's share of the last step (tick_metrics.rs)
+    collisions: CollisionTracker, // per-pair impact threshold + edge state (collisions.rs)
+    pub tick: u64, // steps taken (impulse audit timestamps)
+    pub debug_show_forces: bool, // record impulse_audit_log (AVENLAB_DEBUG_FORCES=1)
+    pub debug_trail_len: usize,  // points per wheel trail (AVENLAB_DEBUG_TRAIL_LEN)
+    pub use_axle_averaged_tire_model: bool, // 2 merged axle contacts instead of 4 (AVENLAB_AXLE_TIRE_MODEL=1)
+    pub payload_sag: bool, // payload compresses the stock springs instead of re-deriving them (AVENLAB_PAYLOAD_SAG=1)
+    pub impulse_audit_log: VecDeque<ImpulseAuditEntry>, // last IMPULSE_AUDIT_CAPACITY impulses
+    pub input_history: HashMap<String, VecDeque<InputHistoryFrame>>, // playerId → last INPUT_HISTORY_CAPACITY poses
+    pub input_traces: HashMap<String, TracePlayback>, // playerId → injected input trace (overrides live input)
+    pub telemetry: HashMap<String, TelemetryRing>, // playerId → per-step telemetry (enable_telemetry)
+    pub surfaces: SurfaceRegistry, // collider material tags + friendly names (surface.rs)
+    pub terrain: Option<Heightfield>, // loaded heightfield replacing the ground box (terrain.rs)
+    pub map: Option<MapFile>, // loaded static map objects (map.rs)
     pub debug_overlay: DebugOverlay,// for debug visualization
+    pub watched_overlays: HashSet<String>, // players whose own overlay is kept (debug_subscribe targets)
+    pub vehicle_overlays: HashMap<String, DebugOverlay>, // last step's overlay per watched player
+    pub timestep_scale: f32, // step(dt) simulates dt * scale (set_timestep_scale, 0.1..=2.0)
+    pub max_angular_velocity: f32, // rad/s clamp applied after each step
+    pub max_linear_velocity: f32,  // m/s clamp applied after each step
+    solve_buffers: SolveBuffers,   // apply_suspension scratch (no per-vehicle allocation)
 }
 
 impl PhysicsWorld {
 
-    pub fn despawn_vehicle_for_player(&mut self, player_id: &str) {
+    /// Heading of a player's chassis: yaw about +Y, 0 = facing +Z.
+    pub fn vehicle_yaw(&self, player_id: &str) -> Option<f32> {
+        let body = self.bodies.get(self.vehicles.get(player_id)?.body)?;
+        let forward = body.rotation() * Vector::z();
+        Some(forward.x.atan2(forward.z))
+    }
+
+    /// Chassis positions of every vehicle but `except` (spawn slot choice).
+    pub fn vehicle_positions(&self, except: Option<&str>) -> Vec<[f32; 3]> {
+        self.vehicles
+            .iter()
+            .filter(|(id, _)| Some(id.as_str()) != except)
+            .filter_map(|(_, v)| self.bodies.get(v.body))
+            .map(|b| (*b.translation()).into())
+            .collect()
+    }
+
+    // ===========================================================================
+    // Remove a player's vehicle: chassis body (attached colliders and joints
+    // with it), turret, and every per-player / per-body map entry.
+    // No-op for a player without a vehicle (already removed, never spawned).
+    // ===========================================================================
+    pub fn remove_vehicle(&mut self, player_id: &str) {
         let Some(vehicle) = self.vehicles.remove(player_id) else {
             return;
         };
 
         let body_handle = vehicle.body;
 
+        // Turret first (removing the hull drops the joint but not the turret body)
+        if let Some(turret) = self.turrets.remove(player_id) {
+            self.bodies.remove(
+                turret.body,
+                &mut self.island_manager,
+                &mut self.colliders,
+                &mut self.joints,
+                &mut self.multibody_joints,
+                true,
+            );
+        }
+
         self.bodies.remove(
             body_handle,
             &mut self.island_manager,
@@ -305,573 +982,3408 @@ impl PhysicsWorld {
             true, // remove attached colliders
         );
 
+        // Removing the body dropped any cables attached to it
+        self.spring_joints.retain(|h| self.joints.get(*h).is_some());
+        self.wheels.remove(&body_handle);
+        self.body_to_player.remove(&body_handle);
+        self.input_history.remove(player_id);
+        self.input_traces.remove(player_id);
+        self.telemetry.remove(player_id);
+        self.collisions.forget(player_id);
+
         println!("🧹 Physics vehicle removed for {}", player_id);
     }
 
-    pub fn debug_snapshot(&self) -> DebugOverlay {
-        self.debug_overlay.clone()
-    }
+    // ===========================================================================
+    // Invariant check (fuzz harness, debugging): per-player maps agree with
+    // each other and with the body set, and nothing simulated is NaN / Inf.
+    // Returns one line per violation; empty = consistent.
+    // ===========================================================================
+    pub fn debug_validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
 
-    pub fn clear_debug_overlay(&mut self) {
-        self.debug_overlay.suspension_rays.clear();
-        self.debug_overlay.load_bars.clear();
-        self.debug_overlay.arb_links.clear(); 
-        self.debug_overlay.wheels.clear();
-        self.debug_overlay.slip_vectors.clear();
+        for (id, vehicle) in &self.vehicles {
+            if !self.bodies.contains(vehicle.body) {
+                errors.push(format!("vehicle {} points at missing body {:?}", id, vehicle.body));
+            }
+            if self.body_to_player.get(&vehicle.body) != Some(id) {
+                errors.push(format!("vehicle {}: body {:?} not mapped back to it", id, vehicle.body));
+            }
+            if !self.wheels.contains_key(&vehicle.body) {
+                errors.push(format!("vehicle {} has no wheels entry", id));
+            }
+            let axes = [vehicle.throttle, vehicle.steer, vehicle.brake, vehicle.ascend, vehicle.pitch, vehicle.yaw, vehicle.roll];
+            if axes.iter().chain(&vehicle.corner_loads).chain([&vehicle.steer_angle, &vehicle.brake_bias]).any(|v| !v.is_finite()) {
+                errors.push(format!("vehicle {} has non-finite control / load state", id));
+            }
+        }
+        for (handle, id) in &self.body_to_player {
+            if self.vehicles.get(id).is_none_or(|v| v.body != *handle) {
+                errors.push(format!("body {:?} mapped to {} which doesn't own it", handle, id));
+            }
+        }
+        for handle in self.wheels.keys() {
+            if !self.body_to_player.contains_key(handle) {
+                errors.push(format!("wheels for unowned body {:?}", handle));
+            }
+        }
+
+        let per_player = [
+            ("turrets", self.turrets.keys().collect::<Vec<_>>()),
+            ("input_history", self.input_history.keys().collect()),
+            ("input_traces", self.input_traces.keys().collect()),
+            ("telemetry", self.telemetry.keys().collect()),
+        ];
+        for (map, ids) in per_player {
+            for id in ids.into_iter().filter(|id| !self.vehicles.contains_key(*id)) {
+                errors.push(format!("{} entry for {} without a vehicle", map, id));
+            }
+        }
+        for (id, turret) in &self.turrets {
+            if !self.bodies.contains(turret.body) {
+                errors.push(format!("turret of {} points at missing body {:?}", id, turret.body));
+            }
+        }
+
+        for (handle, body) in self.bodies.iter() {
+            let rot = body.rotation();
+            let finite = body.translation().iter()
+                .chain(body.linvel().iter())
+                .chain(body.angvel().iter())
+                .chain([rot.i, rot.j, rot.k, rot.w].iter())
+                .all(|v| v.is_finite());
+            if !finite {
+                errors.push(format!("body {:?} has a non-finite pose / velocity", handle));
+            }
+        }
+        errors
     }
 
-    pub fn new() -> Self {
-        let gravity = vector![0.0, -9.81, 0.0];
+    // ===========================================================================
+    // Analytical collision response between two vehicles (READ-ONLY).
+    // - `normal` points from A to B, `depth` is penetration (m, >= 0).
+    // - Contact point is approximated as the midpoint between both COMs.
+    // - j = -(1+e) * v_rel·n / (1/m_a + 1/m_b + cross terms)
+    // Returns (impulse_a, impulse_b) in world space (N*s). Rapier state is not
+    // modified; callers use the magnitude for damage / knockback tuning.
+    // ===========================================================================
+    pub fn compute_collision_response(
+        &self,
+        a_id: &str,
+        b_id: &str,
+        normal: [f32; 3],
+        depth: f32,
+    ) -> (Vec3, Vec3) {
+        let zero = ([0.0; 3], [0.0; 3]);
 
-        let mut bodies = RigidBodySet::new();
-        let mut colliders = ColliderSet::new();
+        let (Some(va), Some(vb)) = (self.vehicles.get(a_id), self.vehicles.get(b_id)) else {
+            return zero;
+        };
+        let (Some(body_a), Some(body_b)) = (self.bodies.get(va.body), self.bodies.get(vb.body)) else {
+            return zero;
+        };
 
-        // === 1. Create a big static ground box at y = 0 ===
-        //
-        // Size: 1000 x 0.2 x 1000 (very large, very thin)
-        // Centered at (0, -0.1, 0), so its top surface is exactly at y = 0.
-        let ground_rb = RigidBodyBuilder::fixed()
-            .translation(vector![0.0, -0.1, 0.0])
-            .build();
+        let n: Vector<Real> = normal.into();
+        let n_len = n.norm();
+        if !n_len.is_finite() || n_len < 1e-6 {
+            return zero;
+        }
+        let n = n / n_len;
 
-        let ground_handle = bodies.insert(ground_rb);
+        let com_a: Point<Real> = *body_a.center_of_mass(); // world space already
+        let com_b: Point<Real> = *body_b.center_of_mass();
+        let contact = Point::from((com_a.coords + com_b.coords) * 0.5);
 
-        let ground_collider = ColliderBuilder::cuboid(500.0, 1.0, 500.0)
-            .collision_groups(InteractionGroups::new(
-                GROUP_GROUND,
-                // Group::empty(),
-                GROUP_CHASSIS,
-            ))
-            .friction(1.2)
-            .restitution(0.0)
-            .build();
+        // Relative velocity of B w.r.t. A at the contact point
+        let vel_a = body_a.velocity_at_point(&contact);
+        let vel_b = body_b.velocity_at_point(&contact);
+        let v_rel_n = (vel_b - vel_a).dot(&n);
 
-        colliders.insert_with_parent(ground_collider, ground_handle, &mut bodies);
+        // Penetration bias pushes bodies apart even when resting in contact
+        let bias = COLLISION_DEPTH_BIAS * depth.max(0.0);
 
-        println!(
-            "🌎 Ground inserted. Bodies = {}, Colliders = {}",
-            bodies.len(),
-            colliders.len()
-        );
+        // Separating and not penetrating → nothing to resolve
+        if v_rel_n - bias >= 0.0 {
+            return zero;
+        }
 
-        Self {
-            gravity,
-            pipeline: PhysicsPipeline::new(),
-            island_manager: IslandManager::new(),
-            broad_phase: DefaultBroadPhase::new(),
-            narrow_phase: NarrowPhase::new(),
-            bodies,
-            colliders,
-            joints: ImpulseJointSet::new(),
-            multibody_joints: MultibodyJointSet::new(),
-            ccd: CCDSolver::new(),
-            query_pipeline: QueryPipeline::new(),
-            wheels:  HashMap::new(),
-            vehicles: HashMap::new(),
-            body_to_player: HashMap::new(),
-            debug_overlay: DebugOverlay {
-                chassis: None,
-                arb_links: Vec::new(),
-                suspension_rays: Vec::new(),
-                load_bars: Vec::new(),
-                wheels: Vec::new(),
-                chassis_right: [1.0, 0.0, 0.0], // default
-                slip_vectors: Vec::new(),
-            },
+        let denom =
+            inv_mass_at_point(body_a, contact, n) +
+            inv_mass_at_point(body_b, contact, n);
+        if denom <= 1e-8 {
+            return zero;
         }
+
+        let j = (-(1.0 + COLLISION_RESTITUTION) * v_rel_n + bias) / denom;
+
+        (v3(-n * j), v3(n * j))
     }
 
     // ===========================================================================
-    // Attach input to a player's vehicle (just stores it; actual forces are
-    // applied in `step`).
+    // AI obstacle sensor: three rays from the chassis front
+    // - forward:      forward_dist range
+    // - left / right: 45° off forward, lateral_dist range
+    // A clear ray reports its full range as distance. An unknown player reports
+    // everything blocked at 0 m so a bot never drives blind.
     // ===========================================================================
-    pub fn apply_player_input(&mut self,player_id: &str,throttle: f32,steer: f32,brake: f32,ascend: f32,pitch: f32,yaw: f32,roll: f32) {
-        if let Some(v) = self.vehicles.get_mut(player_id) {
-            v.throttle = throttle.clamp(-1.0, 1.0);
-            v.steer = steer.clamp(-1.0, 1.0);
-            v.brake = brake.clamp(0.0, 1.0);
-            v.pitch = pitch;
-            v.roll = roll;
-            v.yaw = yaw;
-            v.ascend = ascend;
-            // v.last_input_time = now();
-        }
-    }
+    pub fn raycast_vehicle_sensor(
+        &self,
+        player_id: &str,
+        forward_dist: f32,
+        lateral_dist: f32,
+    ) -> SensorResult {
+        let blocked = SensorResult {
+            front_clear: false,
+            left_clear: false,
+            right_clear: false,
+            front_dist: 0.0,
+            left_dist: 0.0,
+            right_dist: 0.0,
+        };
 
-    // ============================================================================
-    // Spawn a simple "car" for this player:
-    // - Dynamic rigid body with a box collider.
-    // - Positioned slightly above the ground so it can fall and settle.
-    // ============================================================================
-    pub fn spawn_vehicle_for_player(&mut self, id: String, position: [f32; 3]) {
-        let spawn_x = position[0];
-        let spawn_z = position[2];
-        let spawn_y = 1.3;                  // fixed server convention
-        let config = GT86;                  // you can choose different configs per player if desired
-        let volume = 2.0 * 1.0 * 4.0;       // box size
-        let density = config.mass / volume; // ρ = m / V
-        
-        // Rigid body
-        let rb = RigidBodyBuilder::dynamic()
-            .translation(vector![spawn_x, spawn_y, spawn_z])
-            .linear_damping(config.linear_damping)
-            .angular_damping(config.angular_damping)
-            .ccd_enabled(true)
-            .build();
-        
-        // Box collider
-        let [hx, hy, hz] = config.chassis_half_extents;
-        let [cx, cy, cz] = config.chassis_com_offset;
+        let Some(vehicle) = self.vehicles.get(player_id) else { return blocked };
+        let Some(body) = self.bodies.get(vehicle.body) else { return blocked };
 
+        let pos = body.position();
+        let [_, _, hz] = vehicle.config.chassis_half_extents;
+        let [cx, cy, cz] = vehicle.config.chassis_com_offset;
+
+        // Chassis basis: +Z forward, +X left (see kinematics::wheel_basis_world)
+        let forward = pos.rotation * vector![0.0, 0.0, 1.0];
+        let left = pos.rotation * vector![1.0, 0.0, 0.0];
+        let origin = pos * point![cx, cy, cz + hz + 0.05];
+
+        let filter = QueryFilter::default()
+            .groups(InteractionGroups::new(Group::ALL, GROUP_CHASSIS | GROUP_GROUND))
+            .exclude_rigid_body(vehicle.body);
+
+        let cast = |dir: Vector<Real>, range: f32| -> (bool, f32) {
+            let ray = Ray::new(origin, dir.normalize());
+            match self.query_pipeline.cast_ray(&self.bodies, &self.colliders, &ray, range, true, filter) {
+                Some((_, toi)) => (false, toi),
+                None => (true, range),
+            }
+        };
+
+        let (front_clear, front_dist) = cast(forward, forward_dist);
+        let (left_clear, left_dist) = cast(forward + left, lateral_dist);
+        let (right_clear, right_dist) = cast(forward - left, lateral_dist);
+
+        SensorResult { front_clear, left_clear, right_clear, front_dist, left_dist, right_dist }
+    }
+
+    // ===========================================================================
+    // Actual contact impulses from the last Rapier solve on this player's
+    // chassis (vs. the analytical estimate of compute_collision_response).
+    // ===========================================================================
+    pub fn get_contact_forces(&self, player_id: &str) -> Vec<ContactForce> {
+        let mut out = Vec::new();
+
+        let Some(vehicle) = self.vehicles.get(player_id) else { return out };
+        let Some(body) = self.bodies.get(vehicle.body) else { return out };
+
+        for &chassis_collider in body.colliders() {
+            for pair in self.narrow_phase.contact_pairs_with(chassis_collider) {
+                if !pair.has_any_active_contact {
+                    continue;
+                }
+
+                let other = if pair.collider1 == chassis_collider { pair.collider2 } else { pair.collider1 };
+                let other_player_id = self.colliders.get(other)
+                    .and_then(|c| c.parent())
+                    .and_then(|h| self.body_to_player.get(&h))
+                    .cloned();
+
+                let Some(c1) = self.colliders.get(pair.collider1) else { continue };
+                let c1_pos = c1.position();
+
+                for manifold in &pair.manifolds {
+                    let mut normal_impulse = 0.0;
+                    let mut tangent = rapier3d::na::Vector2::<Real>::zeros();
+                    let mut weighted = Vector::zeros();
+
+                    for pt in &manifold.points {
+                        let j = pt.data.impulse;
+                        normal_impulse += j;
+                        tangent += pt.data.tangent_impulse;
+                        weighted += (c1_pos * pt.local_p1).coords * j;
+                    }
+
+                    if normal_impulse <= 0.0 {
+                        continue;
+                    }
+
+                    out.push(ContactForce {
+                        other_player_id: other_player_id.clone(),
+                        point: v3(weighted / normal_impulse),
+                        normal_impulse,
+                        tangent_impulse: tangent.norm(),
+                    });
+                }
+            }
+        }
+
+        out
+    }
+
+    // ===========================================================================
+    // Vehicle layout (straight from the registered wheels + config)
+    // ===========================================================================
+    pub fn vehicle_layout(&self, player_id: &str) -> Option<VehicleLayout> {
+        let vehicle = self.vehicles.get(player_id)?;
+        let wheels = self.wheels.get(&vehicle.body)?;
+
+        Some(VehicleLayout {
+            wheels: wheels
+                .iter()
+                .map(|w| WheelLayout {
+                    id: w.debug_id.clone(),
+                    offset: [w.offset.x, w.offset.y, w.offset.z],
+                    radius: w.radius,
+                    rest_length: w.rest_length,
+                    max_length: w.max_length,
+                    steer: w.steer,
+                    drive: w.drive,
+                })
+                .collect(),
+            chassis_half_extents: vehicle.config.chassis_half_extents,
+            chassis_com_offset: vehicle.config.chassis_com_offset,
+            vehicle: vehicle.config_name.clone(),
+        })
+    }
+
+    // ===========================================================================
+    // Turrets (combat vehicles)
+    // - Small dynamic body on top of the hull, revolute joint about hull Y.
+    // - Yaw is driven by a position motor within ±rotation_limit_deg.
+    // - Collides with ground only (like the chassis); adds its mass to the
+    //   sprung load through the joint.
+    // ===========================================================================
+    pub fn attach_turret(&mut self, vehicle_id: &str, offset: [f32; 3], rotation_limit_deg: f32) -> Option<TurretId> {
+        let hull = self.vehicles.get(vehicle_id)?.body;
+        let hull_pos = *self.bodies.get(hull)?.position();
+
+        if let Some(old) = self.turrets.remove(vehicle_id) {
+            self.bodies.remove(old.body, &mut self.island_manager, &mut self.colliders, &mut self.joints, &mut self.multibody_joints, true);
+        }
+
+        let [ox, oy, oz] = offset;
+        let [hx, hy, hz] = TURRET_HALF_EXTENTS;
+        let yaw_limit = rotation_limit_deg.abs().to_radians().min(std::f32::consts::PI);
+
+        // Sit the turret's base on the mount point, aligned with the hull
+        let mount = hull_pos * point![ox, oy + hy, oz];
+        let rb = RigidBodyBuilder::dynamic()
+            .translation(mount.coords)
+            .rotation(hull_pos.rotation.scaled_axis())
+            .angular_damping(2.0)
+            .build();
         let collider = ColliderBuilder::cuboid(hx, hy, hz)
-            .translation(vector![cx, cy, cz]) // COM offset
-            .collision_groups(InteractionGroups::new(
-                GROUP_CHASSIS,
-                GROUP_GROUND,
-            ))
-            .active_events(ActiveEvents::empty())
-            .density(density)
-            .friction(0.0) // IMPORTANT
+            .collision_groups(InteractionGroups::new(GROUP_CHASSIS, GROUP_GROUND))
+            .mass(TURRET_MASS)
+            .friction(0.0)
             .restitution(0.0)
             .build();
 
-        let handle = self.bodies.insert(rb); // insert rigid body
-        
-        self.colliders.insert_with_parent(collider, handle, &mut self.bodies); // attach to body
-        self.body_to_player.insert(handle, id.clone()); // map body to player ID  
-        self.register_car(handle); // setup wheels
-        
-        self.vehicles.insert(
-            id.clone(),
-            Vehicle {
-                body: handle,
-                config,
-                throttle: 0.0,
-                steer: 0.0,
-                brake: 0.0,
-                pitch: 0.0,
-                yaw: 0.0,
-                roll: 0.0,
-                ascend: 0.0,
-                steer_angle: 0.0,
-                steer_rate: 0.0,
-                steering: SteeringState::default(),
-                rack_torque: 0.0,
-                rack_torque_filtered: 0.0,
-            },
+        let body = self.bodies.insert(rb);
+        self.colliders.insert_with_parent(collider, body, &mut self.bodies);
+
+        let joint = RevoluteJointBuilder::new(Vector::y_axis())
+            .local_anchor1(point![ox, oy, oz])
+            .local_anchor2(point![0.0, -hy, 0.0])
+            .limits([-yaw_limit, yaw_limit])
+            .motor_position(0.0, TURRET_MOTOR_STIFFNESS, TURRET_MOTOR_DAMPING)
+            .contacts_enabled(false);
+        let joint = self.joints.insert(hull, body, joint, true);
+
+        self.turrets.insert(vehicle_id.to_string(), Turret {
+            body,
+            joint,
+            yaw_angle: 0.0,
+            pitch_angle: 0.0,
+            offset,
+            yaw_limit,
+        });
+
+        println!("🎯 Turret attached to {} (±{:.0}°)", vehicle_id, rotation_limit_deg.abs());
+        Some(vehicle_id.to_string())
+    }
+
+    /// Driver brake bias adjustment (front share), clamped to the config's
+    /// range. Returns the new effective bias.
+    pub fn adjust_brake_bias(&mut self, player_id: &str, delta: f32) -> Option<f32> {
+        let vehicle = self.vehicles.get_mut(player_id)?;
+        if delta.is_finite() {
+            let [min, max] = vehicle.config.brake_bias_range;
+            vehicle.brake_bias = (vehicle.brake_bias + delta).clamp(min, max);
+        }
+        Some(vehicle.brake_bias)
+    }
+
+    /// Step `player_id`'s vehicle (and turret) every `divisor`-th tick by
+    /// divisor x dt: its room runs below the server tick rate (tick_rate.rs).
+    /// True if the divisor changed.
+    pub fn set_tick_divisor(&mut self, player_id: &str, divisor: u32) -> bool {
+        let Some(vehicle) = self.vehicles.get_mut(player_id) else { return false };
+        let divisor = divisor.max(1);
+        let changed = vehicle.tick_divisor != divisor;
+        vehicle.tick_divisor = divisor;
+        changed
+    }
+
+    /// Load cargo / fuel: `mass_kg` as a point mass at the body-local
+    /// `local_offset` (replaces any previous payload, 0 kg unloads). Mass,
+    /// COM and inertia of the chassis change at once, so fz_ref follows on the
+    /// next step. Unless `payload_sag` is set, each spring is re-derived for
+    /// its new static load (same sag, same damping ratio) so the car keeps
+    /// its ride height and only the balance changes.
+    pub fn set_payload(&mut self, player_id: &str, mass_kg: f32, local_offset: [f32; 3]) -> Result<Payload, &'static str> {
+        let vehicle = self.vehicles.get_mut(player_id).ok_or("no_vehicle")?;
+        let [hx, hy, hz] = vehicle.config.chassis_half_extents;
+        let [cx, cy, cz] = vehicle.config.chassis_com_offset;
+        let [ox, oy, oz] = local_offset;
+        let valid = mass_kg.is_finite()
+            && (0.0..=MAX_PAYLOAD_KG).contains(&mass_kg)
+            && local_offset.iter().all(|o| o.is_finite())
+            && (ox - cx).abs() <= hx && (oy - cy).abs() <= hy && (oz - cz).abs() <= hz;
+        if !valid {
+            return Err("invalid_payload");
+        }
+
+        let payload = Payload { mass: mass_kg, offset: local_offset };
+        vehicle.payload = payload;
+        let config = vehicle.config;
+        let handle = vehicle.body;
+
+        let body = self.bodies.get_mut(handle).ok_or("no_vehicle")?;
+        body.set_additional_mass_properties(
+            MassProperties::new(point![ox, oy, oz], mass_kg, Vector::repeat(mass_kg * PAYLOAD_SIZE_M * PAYLOAD_SIZE_M / 6.0)),
+            true,
         );
+        body.recompute_mass_properties_from_colliders(&self.colliders);
 
-        println!(
-            "🚗 Spawned vehicle for player {} at {:?} (body = {:?})",
-            id, position, handle
+        let respring_mass = if self.payload_sag { 0.0 } else { mass_kg };
+        let base_mass = config.mass;
+        let (k_base, _) = self.suspension_from_sag(base_mass, 4, SUSPENSION_SAG_M, SUSPENSION_ZETA);
+        if let Some(wheels) = self.wheels.get_mut(&handle) {
+            let shares = payload_corner_shares(wheels, local_offset);
+            for (wheel, share) in wheels.iter_mut().zip(shares) {
+                let extra = respring_mass * share; // kg on this corner
+                let m_corner = base_mass / 4.0 + extra;
+                wheel.stiffness = k_base + extra * 9.81 / SUSPENSION_SAG_M;
+                wheel.damping = 2.0 * SUSPENSION_ZETA * (wheel.stiffness * m_corner).sqrt();
+            }
+        }
+
+        println!("📦 Payload for {}: {:.0} kg at {:?}", player_id, mass_kg, local_offset);
+        Ok(payload)
+    }
+
+    /// Pull `target_id` and `source_id` toward each other: `force_n` for `dt`
+    /// along the line between the chassis, equal and opposite on both bodies.
+    /// False if either vehicle is missing, out of MAGNET_RANGE or coincident.
+    pub fn apply_magnetic_attraction(&mut self, source_id: &str, target_id: &str, force_n: f32, dt: f32) -> bool {
+        self.apply_magnetic_force(source_id, target_id, force_n, dt)
+    }
+
+    /// Same as `apply_magnetic_attraction`, pushing the two vehicles apart.
+    pub fn apply_magnetic_repulsion(&mut self, source_id: &str, target_id: &str, force_n: f32, dt: f32) -> bool {
+        self.apply_magnetic_force(source_id, target_id, -force_n, dt)
+    }
+
+    fn apply_magnetic_force(&mut self, source_id: &str, target_id: &str, force_n: f32, dt: f32) -> bool {
+        if source_id == target_id || !force_n.is_finite() || !dt.is_finite() {
+            return false;
+        }
+        let (Some(source), Some(target)) = (self.vehicles.get(source_id), self.vehicles.get(target_id)) else {
+            return false;
+        };
+        let (source, target) = (source.body, target.body);
+        let (Some(ps), Some(pt)) = (self.bodies.get(source), self.bodies.get(target)) else { return false };
+
+        // target -> source
+        let delta = ps.translation() - pt.translation();
+        let dist = delta.norm();
+        if !(1e-3..=MAGNET_RANGE).contains(&dist) {
+            return false;
+        }
+        let j = delta / dist * (force_n * dt).clamp(-MAGNET_MAX_IMPULSE, MAGNET_MAX_IMPULSE);
+
+        if let Some(body) = self.bodies.get_mut(target) {
+            body.apply_impulse(j, true);
+        }
+        if let Some(body) = self.bodies.get_mut(source) {
+            body.apply_impulse(-j, true);
+        }
+        true
+    }
+
+    /// Nudge the turret's commanded yaw / barrel pitch (radians).
+    pub fn apply_turret_input(&mut self, vehicle_id: &str, yaw_delta: f32, pitch_delta: f32) {
+        let Some(turret) = self.turrets.get_mut(vehicle_id) else { return };
+        if !yaw_delta.is_finite() || !pitch_delta.is_finite() {
+            return;
+        }
+
+        turret.yaw_angle = (turret.yaw_angle + yaw_delta).clamp(-turret.yaw_limit, turret.yaw_limit);
+        turret.pitch_angle = (turret.pitch_angle + pitch_delta).clamp(TURRET_PITCH_MIN, TURRET_PITCH_MAX);
+
+        if let Some(rev) = self.joints.get_mut(turret.joint).and_then(|j| j.data.as_revolute_mut()) {
+            rev.set_motor_position(turret.yaw_angle, TURRET_MOTOR_STIFFNESS, TURRET_MOTOR_DAMPING);
+        }
+        if let Some(body) = self.bodies.get_mut(turret.body) {
+            body.wake_up(true);
+        }
+    }
+
+    /// Measured turret yaw relative to the hull + barrel pitch (radians).
+    pub fn turret_angles(&self, vehicle_id: &str) -> Option<(f32, f32)> {
+        let turret = self.turrets.get(vehicle_id)?;
+        let joint = self.joints.get(turret.joint)?;
+        let hull = self.bodies.get(joint.body1)?;
+        let top = self.bodies.get(joint.body2)?;
+        let yaw = joint.data.as_revolute()?.angle(hull.rotation(), top.rotation());
+        Some((yaw, turret.pitch_angle))
+    }
+
+    // ===========================================================================
+    // Spring joints (tow cables, dock tethers, terrain anchors)
+    // - Generic joint, all axes free; the joint X axis points from anchor_a to
+    //   anchor_b at creation time (fixed in body_a's frame).
+    // - Frame 1 sits rest_length along that axis, so the LinX motor targeting
+    //   0 pulls the anchors back to rest_length apart.
+    // - Force-based motor: k in N/m, c in N·s/m (f = sqrt(k/m) / 2π).
+    // ===========================================================================
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_spring_joint(
+        &mut self,
+        body_a: RigidBodyHandle,
+        body_b: RigidBodyHandle,
+        anchor_a: Point<Real>,
+        anchor_b: Point<Real>,
+        rest_length: f32,
+        stiffness: f32,
+        damping: f32,
+    ) -> ImpulseJointHandle {
+        let pos_a = self.bodies.get(body_a).map(|b| *b.position()).unwrap_or_default();
+        let pos_b = self.bodies.get(body_b).map(|b| *b.position()).unwrap_or_default();
+
+        // Attachment axis in body_a's frame (falls back to local +X when coincident)
+        let axis_world = pos_b * anchor_b - pos_a * anchor_a;
+        let axis_local = pos_a.rotation.inverse() * axis_world;
+        let axis_rot = if axis_local.norm() > 1e-6 {
+            Rotation::rotation_between(&Vector::x(), &axis_local)
+                .unwrap_or_else(|| Rotation::from_axis_angle(&Vector::y_axis(), std::f32::consts::PI))
+        } else {
+            Rotation::identity()
+        };
+
+        let frame1 = Isometry::from_parts(
+            (anchor_a.coords + axis_rot * Vector::x() * rest_length.max(0.0)).into(),
+            axis_rot,
+        );
+        // Same world orientation on body_b so the axes line up at creation
+        let frame2 = Isometry::from_parts(
+            anchor_b.coords.into(),
+            pos_b.rotation.inverse() * pos_a.rotation * axis_rot,
         );
-    }    
-    
-    fn suspension_from_sag(&mut self, vehicle_mass: f32, wheels: usize, sag_m: f32, zeta: f32) -> (f32, f32) {
-        let m = vehicle_mass / wheels as f32;
-        let g = 9.81_f32;
-        let f_static = m * g;              // per wheel
-        let k = f_static / sag_m.max(1e-3); // N/m
 
-        // damping: c = 2*zeta*sqrt(k*m)
-        let c = 2.0 * zeta * (k * m).sqrt();
-        (k, c)
+        let mut joint = GenericJointBuilder::new(JointAxesMask::empty())
+            .local_frame1(frame1)
+            .local_frame2(frame2)
+            .build();
+        joint.set_motor_model(JointAxis::LinX, MotorModel::ForceBased);
+        joint.set_motor(JointAxis::LinX, 0.0, 0.0, stiffness, damping);
+
+        let handle = self.joints.insert(body_a, body_b, joint, true);
+        self.spring_joints.push(handle);
+        handle
+    }
+
+    /// Remove a spring joint; false if it no longer exists.
+    pub fn destroy_joint(&mut self, handle: ImpulseJointHandle) -> bool {
+        self.spring_joints.retain(|h| *h != handle);
+        self.joints.remove(handle, true).is_some()
+    }
+
+    pub fn debug_snapshot(&self) -> DebugOverlay {
+        with_wireframes(self.debug_overlay.clone())
+    }
+
+    /// One watched player's overlay from the last step (None if not watched
+    /// or the vehicle wasn't simulated).
+    pub fn vehicle_debug_snapshot(&self, player_id: &str) -> Option<DebugOverlay> {
+        self.vehicle_overlays.get(player_id).cloned().map(with_wireframes)
+    }
+
+    /// Shared overlay of one room: the kept overlays of `ids` (its
+    /// vehicles, all in `watched_overlays`) in id order. Vehicles of other
+    /// rooms never show up, whatever order they were stepped in.
+    pub fn room_debug_snapshot(&self, ids: &[&str]) -> DebugOverlay {
+        let mut ids = ids.to_vec();
+        ids.sort_unstable();
+        let mut overlay = DebugOverlay::empty();
+        for id in ids {
+            if let Some(own) = self.vehicle_overlays.get(id) {
+                overlay.append(own.clone());
+            }
+        }
+        with_wireframes(overlay)
+    }
+
+    /// Fixed box (curb, rail, prop) on the obstacle layer: suspension rays
+    /// ride over it, wheel colliders hit it, the chassis does not.
+    #[cfg(test)]
+    pub fn add_static_obstacle(&mut self, center: [f32; 3], half_extents: [f32; 3]) -> ColliderHandle {
+        let [hx, hy, hz] = half_extents;
+        let collider = ColliderBuilder::cuboid(hx, hy, hz)
+            .translation(center.into())
+            .collision_groups(InteractionGroups::new(GROUP_OBSTACLE, GROUP_WHEEL))
+            .friction(0.8)
+            .restitution(0.0)
+            .build();
+        let handle = self.colliders.insert(collider);
+        self.invalidate_ray_caches();
+        handle
+    }
+
+    /// Tag a collider's surface material ("gravel", "ice", ..) as reported
+    /// per wheel in the debug overlay and telemetry. False if it doesn't exist.
+    #[cfg(test)]
+    pub fn set_surface_material(&mut self, collider: ColliderHandle, material: &str) -> bool {
+        self.surfaces.set_material(&mut self.colliders, collider, material)
     }
 
-    
     // ===========================================================================
-    //  GTA-style car placeholder with 4 suspension raycasts.
+    // Startup warm-up (main.rs, after the static scene is built). Without it
+    // the first join pays for the first query pipeline / broad-phase build,
+    // and its spawn-time raycasts see a never-updated pipeline.
+    // player_capacity = AVENLAB_MAX_PLAYERS (0 = unlimited).
     // ===========================================================================
-    pub fn register_car(&mut self, body: RigidBodyHandle) {
-        // Find vehicle config & input
+    pub fn warm_up(&mut self, player_capacity: usize) {
+        let started = std::time::Instant::now();
 
-        let vehicle_mass = 1350.0;  // kg
-        let wheels = 4;             // number of wheels
-        let sag_m = 0.065;     // meters
-        let zeta = 1.05;     // damping ratio (0.7–1.0)
-        
-        let (k, c) = self.suspension_from_sag(vehicle_mass, wheels, sag_m, zeta);
-        let w = vec![
-            Wheel { offset: point![-0.8, -0.3,  1.5], rest_length: 0.5, max_length: 0.9, radius: 0.35, stiffness: k, damping: c, drive: false, steer: true, debug_id: "FL".to_string(), tire_state: TireState::Grip},
-            Wheel { offset: point![ 0.8, -0.3,  1.5], rest_length: 0.5, max_length: 0.9, radius: 0.35, stiffness: k, damping: c, drive: false, steer: true, debug_id: "FR".to_string(), tire_state: TireState::Grip},
-            Wheel { offset: point![-0.8, -0.3, -1.5], rest_length: 0.5, max_length: 0.9, radius: 0.35, stiffness: k, damping: c, drive: true,  steer: false, debug_id: "RL".to_string(), tire_state: TireState::Grip},
-            Wheel { offset: point![ 0.8, -0.3, -1.5], rest_length: 0.5, max_length: 0.9, radius: 0.35, stiffness: k, damping: c, drive: true,  steer: false, debug_id: "RR".to_string(), tire_state: TireState::Grip},
-        ];
-        self.wheels.insert(body, w);
+        // Per-player maps sized for a full server (Rapier's body / collider
+        // arenas have no capacity constructor; they grow on first spawns)
+        let player_capacity = match player_capacity {
+            0 => WARM_UP_DEFAULT_PLAYERS, // no limit configured
+            n => n.min(WARM_UP_MAX_PLAYERS),
+        };
+        self.vehicles.reserve(player_capacity);
+        self.wheels.reserve(player_capacity);
+        self.body_to_player.reserve(player_capacity);
+        self.input_history.reserve(player_capacity);
+
+        self.sync_query_pipeline();
+
+        // Zero-dt step: builds broad-phase / narrow-phase structures, moves nothing
+        let params = IntegrationParameters { dt: 0.0, ..IntegrationParameters::default() };
+        self.pipeline.step(
+            &self.gravity,
+            &params,
+            &mut self.island_manager,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.joints,
+            &mut self.multibody_joints,
+            &mut self.ccd,
+            Some(&mut self.query_pipeline),
+            &(),
+            &(),
+        );
+
+        println!(
+            "🔥 Physics warm-up: {} colliders, room for {} players ({:.2} ms)",
+            self.colliders.len(),
+            player_capacity,
+            started.elapsed().as_secs_f32() * 1000.0
+        );
     }
 
-    // ============================================================================
-    //  Apply Suspension
-    // ============================================================================
-    fn apply_suspension(&mut self, dt: Real) {
+    /// Rebuild the query pipeline if colliders were added, removed or
+    /// replaced (or static ones moved) since it was last updated. Spawn-time
+    /// raycasts run between ticks, where the pipeline may predate the change.
+    pub fn refresh_query_pipeline(&mut self) {
+        if self.query_pipeline_sig != Some(self.collider_signature()) {
+            self.sync_query_pipeline();
+        }
+    }
+
+    fn sync_query_pipeline(&mut self) {
         self.query_pipeline.update(&self.colliders);
+        self.query_pipeline_sig = Some(self.collider_signature());
+    }
 
-        
-        for (&handle, wheels) in self.wheels.iter_mut() {
-            let Some(body_ro) = self.bodies.get(handle) else { continue };
-            let Some(player_id) = self.body_to_player.get(&handle) else { continue };
-            let Some(vehicle) = self.vehicles.get_mut(player_id) else { continue };
-            
-            // ======================================================
-            //  Debug: chassis
-            // ======================================================
-            let pos = body_ro.position();
-            self.debug_overlay.chassis = Some(DebugChassis {
-                position: pos.translation.vector.into(),
-                rotation: [ pos.rotation.i, pos.rotation.j, pos.rotation.k, pos.rotation.w, ],
-                half_extents: vehicle.config.chassis_half_extents,
-            });
+    // Every collider handle (index + generation) and the static epoch
+    fn collider_signature(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.static_epoch.hash(&mut hasher);
+        for (handle, _) in self.colliders.iter() {
+            handle.into_raw_parts().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
 
-            // ==================================================
-            //  Impulse Accumulator
-            // ==================================================
-            let mut impulses = ImpulseAccumulator::new();
+    /// Height of the static scene at (x, z): top of the highest fixed
+    /// collider under GROUND_PROBE_TOP, or None over a hole.
+    #[cfg(test)]
+    pub fn ground_height(&mut self, x: f32, z: f32) -> Option<f32> {
+        self.refresh_query_pipeline();
+        let ray = Ray::new(point![x, GROUND_PROBE_TOP, z], vector![0.0, -1.0, 0.0]);
+        self.query_pipeline
+            .cast_ray(&self.bodies, &self.colliders, &ray, 2.0 * GROUND_PROBE_TOP, true, QueryFilter::only_fixed())
+            .map(|(_, toi)| GROUND_PROBE_TOP - toi)
+    }
 
-            // --------------------------------------------------
-            //  VEHICLE CONSTANTS
-            // --------------------------------------------------
-            let body_mass = body_ro.mass() as f32;
-            let fz_ref = body_mass * 9.81 / wheels.len() as f32;
-            
-            
-            // --------------------------------------------------
-            // PHASE 1 — SENSE
-            // --------------------------------------------------
-            let mut contacts: Vec<ContactPatch> = Vec::new();
-            let mut suspension_contacts: Vec<(WheelId, SuspensionContact)> = Vec::new();
-            let mut axle_compression = HashMap::new();
-            let mut axle_normal_force = HashMap::new();
-            
-            let cfg = SteeringConfig {
-                wheelbase: vehicle.config.wheelbase,
-                track_width: vehicle.config.track_width,
-                max_steer_angle: vehicle.config.max_steer_angle,
-                ackermann: vehicle.config.ackermann,
-            };
-            
-            let target = vehicle.steer * cfg.max_steer_angle;
-            
-            let tau = 0.10; // seconds to reach ~63%
-            let k = 1.0 - (-dt as f32 / tau).exp();
-            vehicle.steer_angle += (target - vehicle.steer_angle) * k;
+    /// Replace the ground (box or previous heightfield) with a heightfield:
+    /// nx x ny vertices, row-major (row = z), world-space heights, spanning
+    /// width x depth centered on the origin (terrain.rs for the layout).
+    pub fn load_heightfield(&mut self, nx: usize, ny: usize, width: f32, depth: f32, heights: Vec<f32>) -> Result<ColliderHandle, String> {
+        let field = Heightfield { nx, ny, width, depth, heights };
+        field.validate()?;
+
+        if let Some(old) = self.surfaces.terrain() {
+            self.colliders.remove(old, &mut self.island_manager, &mut self.bodies, false);
+        }
+        let matrix = rapier3d::na::DMatrix::from_row_slice(ny, nx, &field.heights);
+        let collider = ColliderBuilder::heightfield(matrix, vector![width, 1.0, depth])
+            .collision_groups(InteractionGroups::new(GROUP_GROUND, GROUP_CHASSIS))
+            .friction(GROUND_FRICTION)
+            .restitution(0.0)
+            .build();
+        let handle = self.colliders.insert(collider);
+        self.surfaces.set_terrain(handle);
+        self.invalidate_ray_caches();
+
+        println!("⛰️ Heightfield terrain loaded: {}x{} vertices over {} x {} m", nx, ny, width, depth);
+        self.terrain = Some(field);
+        Ok(handle)
+    }
+
+    /// Insert the static objects of a map file (map.rs) as fixed bodies.
+    /// All or nothing: a malformed object or one overlapping a spawn area
+    /// (team spawn point + jitter radius, SpawnManager::spawn_areas) rejects
+    /// the file. Returns the number of objects inserted.
+    pub fn load_map(&mut self, path: &str, spawn_areas: &[([f32; 3], f32)]) -> Result<usize, String> {
+        let map = crate::map::load_file(path)?;
+
+        for &([x, _, z], jitter) in spawn_areas {
+            let clear = SharedShape::cylinder(SPAWN_CLEAR_HALF_HEIGHT_M, SPAWN_CLEAR_RADIUS_M + jitter);
+            let clear_pos = Isometry::translation(x, self.spawn_height(x, z), z);
+            for (i, object) in map.objects.iter().enumerate() {
+                let overlaps = rapier3d::parry::query::intersection_test(&clear_pos, clear.as_ref(), &object.isometry(), object.shape().as_ref())
+                    .unwrap_or(true);
+                if overlaps {
+                    return Err(format!("{}: {} overlaps the spawn area at ({}, {})", path, object.label(i), x, z));
+                }
+            }
+        }
+
+        for object in &map.objects {
+            let body = self.bodies.insert(RigidBodyBuilder::fixed().position(object.isometry()).build());
+            let collider = ColliderBuilder::new(object.shape())
+                .collision_groups(InteractionGroups::new(GROUP_GROUND | GROUP_OBSTACLE, GROUP_CHASSIS | GROUP_WHEEL))
+                .friction(GROUND_FRICTION * object.friction)
+                .restitution(0.0)
+                .build();
+            let collider = self.colliders.insert_with_parent(collider, body, &mut self.bodies);
+            self.surfaces.set_grip(collider, object.friction);
+            if let Some(material) = &object.material {
+                self.surfaces.set_material(&mut self.colliders, collider, material);
+            }
+            if let Some(name) = &object.name {
+                self.surfaces.name_prop(collider, name);
+            }
+        }
+        self.invalidate_ray_caches();
+
+        let count = map.objects.len();
+        self.map = Some(map);
+        Ok(count)
+    }
+
+    /// Chassis spawn height at (x, z): the fixed server convention over the
+    /// flat ground, the same clearance above the local cell with terrain.
+    fn spawn_height(&self, x: f32, z: f32) -> f32 {
+        self.terrain
+            .as_ref()
+            .and_then(|t| t.cell_top(x, z))
+            .map_or(SPAWN_HEIGHT, |top| top + SPAWN_HEIGHT - FLAT_GROUND_TOP)
+    }
+
+    /// Call after adding / removing / moving static colliders (props, track
+    /// pieces) so wheels drop their cached suspension raycasts.
+    pub fn invalidate_ray_caches(&mut self) {
+        self.static_epoch += 1;
+    }
+
+    pub fn clear_debug_overlay(&mut self) {
+        self.debug_overlay.suspension_rays.clear();
+        self.debug_overlay.load_bars.clear();
+        self.debug_overlay.arb_links.clear(); 
+        self.debug_overlay.wheels.clear();
+        self.debug_overlay.slip_vectors.clear();
+        self.debug_overlay.tire_forces.clear();
+        self.debug_overlay.trails.clear();
+        self.vehicle_overlays.clear();
+    }
+
+    pub fn new() -> Self {
+        let gravity = vector![0.0, -9.81, 0.0];
+
+        let mut bodies = RigidBodySet::new();
+        let mut colliders = ColliderSet::new();
+
+        // === 1. Create a big static ground box at y = 0 ===
+        //
+        // Size: 1000 x 0.2 x 1000 (very large, very thin)
+        // Centered at (0, -0.1, 0), so its top surface is exactly at y = 0.
+        let ground_rb = RigidBodyBuilder::fixed()
+            .translation(vector![0.0, -0.1, 0.0])
+            .build();
+
+        let ground_handle = bodies.insert(ground_rb);
+
+        let ground_collider = ColliderBuilder::cuboid(500.0, 1.0, 500.0)
+            .collision_groups(InteractionGroups::new(
+                GROUP_GROUND,
+                // Group::empty(),
+                GROUP_CHASSIS,
+            ))
+            .friction(GROUND_FRICTION)
+            .restitution(0.0)
+            .build();
+
+        let ground_collider = colliders.insert_with_parent(ground_collider, ground_handle, &mut bodies);
+        let mut surfaces = SurfaceRegistry::default();
+        surfaces.set_terrain(ground_collider);
+
+        println!(
+            "🌎 Ground inserted. Bodies = {}, Colliders = {}",
+            bodies.len(),
+            colliders.len()
+        );
+
+        Self {
+            gravity,
+            pipeline: PhysicsPipeline::new(),
+            island_manager: IslandManager::new(),
+            broad_phase: DefaultBroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            bodies,
+            colliders,
+            joints: ImpulseJointSet::new(),
+            multibody_joints: MultibodyJointSet::new(),
+            ccd: CCDSolver::new(),
+            query_pipeline: QueryPipeline::new(),
+            query_pipeline_sig: None,
+            wheels:  HashMap::new(),
+            vehicles: HashMap::new(),
+            vehicle_presets: builtin_vehicle_presets(),
+            body_to_player: HashMap::new(),
+            debug_overlay: DebugOverlay {
+                chassis: None,
+                arb_links: Vec::new(),
+                suspension_rays: Vec::new(),
+                load_bars: Vec::new(),
+                wheels: Vec::new(),
+                chassis_right: [1.0, 0.0, 0.0], // default
+                slip_vectors: Vec::new(),
+                tire_forces: Vec::new(),
+                drive_split: None,
+                brake_force_axle: None,
+                wireframes: Vec::new(),
+                trails: Vec::new(),
+                flipped: None,
+                input_stale: None,
+            },
+            watched_overlays: HashSet::new(),
+            vehicle_overlays: HashMap::new(),
+            timestep_scale: 1.0,
+            max_angular_velocity: 20.0,
+            max_linear_velocity: 120.0,
+            solve_buffers: SolveBuffers::default(),
+            turrets: HashMap::new(),
+            spring_joints: Vec::new(),
+            static_epoch: 0,
+            road_camber_deg: 0.0,
+            haptic_events: Vec::new(),
+            collision_events: Vec::new(),
+            suspension_time: std::time::Duration::ZERO,
+            collisions: CollisionTracker::from_env(),
+            tick: 0,
+            debug_show_forces: std::env::var("AVENLAB_DEBUG_FORCES").is_ok_and(|v| v == "1"),
+            debug_trail_len: trail_len_from_env(),
+            impulse_audit_log: VecDeque::with_capacity(IMPULSE_AUDIT_CAPACITY),
+            input_history: HashMap::new(),
+            input_traces: HashMap::new(),
+            telemetry: HashMap::new(),
+            surfaces,
+            terrain: None,
+            map: None,
+            use_axle_averaged_tire_model: std::env::var("AVENLAB_AXLE_TIRE_MODEL").is_ok_and(|v| v == "1"),
+            payload_sag: std::env::var("AVENLAB_PAYLOAD_SAG").is_ok_and(|v| v == "1"),
+        }
+    }
+
+    // ===========================================================================
+    // Attach input to a player's vehicle (just stores it; actual forces are
+    // applied in `step`).
+    // ===========================================================================
+    pub fn apply_player_input(&mut self,player_id: &str,throttle: f32,steer: f32,brake: f32,ascend: f32,pitch: f32,yaw: f32,roll: f32) {
+        let axes = [throttle, steer, brake, ascend, pitch, yaw, roll].map(Some);
+        let _ = self.apply_partial_input(player_id, axes); // every axis present: never rejected
+    }
+
+    /// Input message that may omit axes (input_hold::AXIS_NAMES order, None =
+    /// keep the last value). Err("partial_input") when a require_full axis is missing.
+    /// Axes are clamped to their ranges and non-finite ones ignored, whether or
+    /// not the caller ran them through InputSanity.
+    pub fn apply_partial_input(&mut self, player_id: &str, axes: [Option<f32>; 7]) -> Result<(), &'static str> {
+        if self.input_traces.contains_key(player_id) {
+            return Ok(()); // an injected trace is driving (input_trace.rs)
+        }
+        self.write_input(player_id, axes)
+    }
+
+    /// An attached trace's axes for this tick, from the input mailbox
+    /// (MailboxInput::Trace): the same hold / clamp path as live input.
+    /// Ignored once the trace has been detached.
+    pub fn apply_trace_input(&mut self, player_id: &str, axes: [f32; 7]) -> Result<(), &'static str> {
+        if !self.input_traces.contains_key(player_id) {
+            return Ok(());
+        }
+        self.write_input(player_id, axes.map(Some))
+    }
+
+    fn write_input(&mut self, player_id: &str, axes: [Option<f32>; 7]) -> Result<(), &'static str> {
+        let Some(v) = self.vehicles.get_mut(player_id) else { return Ok(()) };
+        let resumed = v.input_hold.silent_for(v.config.input_timeout_s).is_some();
+        v.input_hold.accept(&axes)?;
+        if resumed && axes[2].is_none() {
+            v.brake = 0.0; // release the staleness brake (input_hold.rs)
+        }
+        let mut axes = axes;
+        for (axis, (min, max)) in axes.iter_mut().zip(AXIS_RANGES) {
+            *axis = axis.filter(|x| x.is_finite()).map(|x| x.clamp(min, max));
+        }
+        let [throttle, steer, brake, ascend, pitch, yaw, roll] = axes;
+        if let Some(x) = throttle { v.throttle = x; }
+        if let Some(x) = steer { v.steer = x; }
+        if let Some(x) = brake { v.brake = x; }
+        if let Some(x) = ascend { v.ascend = x; }
+        if let Some(x) = pitch { v.pitch = x; }
+        if let Some(x) = yaw { v.yaw = x; }
+        if let Some(x) = roll { v.roll = x; }
+        Ok(())
+    }
+
+    /// Drive `player_id` from `trace` until it runs out, ignoring live input;
+    /// replaces any trace already playing. Turns on its telemetry ring.
+    pub fn attach_input_trace(&mut self, player_id: &str, trace: std::sync::Arc<InputTrace>) -> Result<(), &'static str> {
+        if !self.vehicles.contains_key(player_id) {
+            return Err("no_vehicle");
+        }
+        self.enable_telemetry(player_id).reserve_ticks(trace.ticks() as usize);
+        println!("📼 Input trace attached to {} ({} ticks)", player_id, trace.ticks());
+        self.input_traces.insert(player_id.to_string(), TracePlayback::new(trace));
+        Ok(())
+    }
+
+    /// Hand `player_id` back to live input; false if no trace was playing.
+    pub fn detach_input_trace(&mut self, player_id: &str) -> bool {
+        self.input_traces.remove(player_id).is_some()
+    }
+
+    /// Start (or keep) recording `player_id` into its telemetry ring.
+    pub fn enable_telemetry(&mut self, player_id: &str) -> &mut TelemetryRing {
+        self.telemetry.entry(player_id.to_string()).or_default()
+    }
+
+    /// Every attached trace's axes for the next tick (once per server tick;
+    /// SharedGameState::post_trace_inputs posts them to the input mailbox).
+    /// Traces that ran out are detached.
+    pub fn next_trace_inputs(&mut self) -> Vec<(String, [f32; 7])> {
+        let mut next = Vec::new();
+        let mut finished = Vec::new();
+        for (id, playback) in self.input_traces.iter_mut() {
+            if !self.vehicles.contains_key(id) {
+                continue;
+            }
+            match playback.next_axes() {
+                Some(axes) => next.push((id.clone(), axes)),
+                None => finished.push(id.clone()),
+            }
+        }
+        for id in finished {
+            self.input_traces.remove(&id);
+            println!("📼 Input trace finished for {}", id);
+        }
+        next
+    }
+
+    /// Return stale decay_to_neutral axes toward 0 and stop cars whose client
+    /// went silent (once per server tick, wall-clock `dt`; see input_hold.rs).
+    pub fn decay_stale_inputs(&mut self, dt: f32) {
+        for vehicle in self.vehicles.values_mut() {
+            let mut hold = vehicle.input_hold;
+            let timeout = vehicle.config.input_timeout_s;
+            hold.decay(vehicle.axes_mut(), dt, timeout);
+            vehicle.input_hold = hold;
+        }
+    }
+
+    // ============================================================================
+    // Spawn a simple "car" for this player:
+    // - Dynamic rigid body with a box collider.
+    // - Positioned slightly above the ground so it can fall and settle,
+    //   turned `yaw` radians about +Y (0 = facing +Z).
+    // - Config by preset name (vehicle_presets); unknown names get
+    //   DEFAULT_VEHICLE. The name used is kept on the vehicle.
+    // ============================================================================
+    pub fn spawn_vehicle_for_player(&mut self, id: String, position: [f32; 3], yaw: f32, config_name: &str) -> Result<RigidBodyHandle, Vec<ConfigError>> {
+        let (name, config) = match self.vehicle_presets.get(config_name) {
+            Some(config) => (config_name, *config),
+            None => {
+                println!("⚠️ Unknown vehicle '{}' for {}; spawning {}", config_name, id, DEFAULT_VEHICLE);
+                (DEFAULT_VEHICLE, self.vehicle_presets.get(DEFAULT_VEHICLE).copied().unwrap_or(GT86))
+            }
+        };
+        let handle = self.spawn_vehicle_with_config(id.clone(), position, yaw, config)?;
+        if let Some(v) = self.vehicles.get_mut(&id) {
+            v.config_name = name.to_string();
+        }
+        Ok(handle)
+    }
+
+    // ============================================================================
+    // Add / replace spawnable presets (vehicle_presets.rs). All or nothing:
+    // every config must pass validate + validate_wheels first.
+    // ============================================================================
+    pub fn register_vehicle_presets(&mut self, presets: BTreeMap<String, VehicleConfig>) -> Result<usize, String> {
+        for (name, config) in &presets {
+            let wheels = self.build_car_wheels(config);
+            let checked = config.validate().and_then(|_| config.validate_wheels(&wheels, SUSPENSION_SAG_M));
+            if let Err(errors) = checked {
+                let reasons: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                return Err(format!("preset '{}': {}", name, reasons.join("; ")));
+            }
+        }
+        let count = presets.len();
+        self.vehicle_presets.extend(presets);
+        Ok(count)
+    }
+
+    // ============================================================================
+    // Put a player's car back on its wheels at `position` (x / z; height from
+    // the ground there) facing `yaw`: upright, at rest, controls and steering
+    // cleared.
+    // Same body, so config, payload and turret carry over. Client "respawn"
+    // (flipped / fallen off the map); the caller enforces the cooldown.
+    // ============================================================================
+    pub fn reset_vehicle(&mut self, player_id: &str, position: [f32; 3], yaw: f32) -> Result<[f32; 3], &'static str> {
+        let [x, _, z] = position;
+        let y = self.spawn_height(x, z);
+        self.place_vehicle(player_id, vector![x, y, z], Rotation::from_axis_angle(&Vector::y_axis(), yaw))?;
+
+        let vehicle = self.vehicles.get_mut(player_id).ok_or("no_vehicle")?;
+        for axis in vehicle.axes_mut() {
+            *axis = 0.0;
+        }
+        vehicle.steer_angle = 0.0;
+        vehicle.steer_rate = 0.0;
+        vehicle.steering = Default::default();
+        vehicle.rack_torque = 0.0;
+        vehicle.rack_torque_filtered = 0.0;
+        vehicle.air = Default::default();
+        vehicle.flip = Default::default();
+        let handle = vehicle.body;
+
+        for wheel in self.wheels.get_mut(&handle).into_iter().flatten() {
+            wheel.tire_state = TireState::Grip;
+            wheel.wheel_state = WheelState::default();
+            wheel.trail.reset();
+        }
+        Ok([x, y, z])
+    }
+
+    /// Teleport a player's chassis to `position` / `rotation` at rest (reset,
+    /// flip recovery). The turret body would be dragged there by its joint, so
+    /// it is rebuilt on the hull.
+    fn place_vehicle(&mut self, player_id: &str, position: Vector<Real>, rotation: Rotation<Real>) -> Result<(), &'static str> {
+        let handle = self.vehicles.get(player_id).ok_or("no_vehicle")?.body;
+        let body = self.bodies.get_mut(handle).ok_or("no_vehicle")?;
+        body.set_translation(position, true);
+        body.set_rotation(rotation, true);
+        body.set_linvel(vector![0.0, 0.0, 0.0], true);
+        body.set_angvel(vector![0.0, 0.0, 0.0], true);
+
+        for wheel in self.wheels.get_mut(&handle).into_iter().flatten() {
+            wheel.prev_compression = None; // don't damp the teleport
+        }
+        if let Some((offset, limit_deg)) = self.turrets.get(player_id).map(|t| (t.offset, t.yaw_limit.to_degrees())) {
+            self.attach_turret(player_id, offset, limit_deg);
+        }
+        Ok(())
+    }
+
+    /// Right cars that have rested on their roof / side for their config's
+    /// flip_timeout_s (auto_flip_recover; recovery.rs). Once per tick.
+    fn recover_flipped_vehicles(&mut self, dt: f32) {
+        let mut ids: Vec<String> = self.vehicles.keys().cloned().collect();
+        ids.sort();
+        for id in ids {
+            let Some(vehicle) = self.vehicles.get_mut(&id) else { continue };
+            let Some(body) = self.bodies.get(vehicle.body) else { continue };
+            let timeout = vehicle.config.flip_timeout_s;
+            let expired = vehicle.flip.update(body.rotation(), body.linvel().norm(), body.angvel().norm(), dt, timeout);
+            if !(expired && vehicle.config.auto_flip_recover) {
+                continue;
+            }
+            vehicle.flip = Default::default();
+            let position = body.translation() + vector![0.0, RECOVER_LIFT_M, 0.0];
+            let rotation = upright_rotation(body.rotation());
+            if self.place_vehicle(&id, position, rotation).is_ok() {
+                println!("🙃 Righted flipped vehicle {} after {:.1}s", id, timeout);
+            }
+        }
+    }
+
+    // ============================================================================
+    // Replace a player's body with a fresh one at `position` / `yaw`, keeping
+    // its config (room migration, respawn).
+    // ============================================================================
+    pub fn respawn_vehicle_for_player(&mut self, id: &str, position: [f32; 3], yaw: f32) -> Result<RigidBodyHandle, Vec<ConfigError>> {
+        let config = self.vehicles.get(id).map(|v| v.config).unwrap_or(GT86);
+        let config_name = self.vehicles.get(id).map(|v| v.config_name.clone());
+        let input_hold = self.vehicles.get(id).map(|v| v.input_hold);
+        let tick_divisor = self.vehicles.get(id).map_or(1, |v| v.tick_divisor);
+        let payload = self.vehicles.get(id).map(|v| v.payload).filter(|p| p.mass > 0.0);
+        let turret = self.turrets.get(id).map(|t| (t.offset, t.yaw_limit.to_degrees()));
+        self.remove_vehicle(id);
+        let handle = self.spawn_vehicle_with_config(id.to_string(), position, yaw, config)?;
+        if let (Some(hold), Some(v)) = (input_hold, self.vehicles.get_mut(id)) {
+            v.input_hold = hold;
+        }
+        if let (Some(name), Some(v)) = (config_name, self.vehicles.get_mut(id)) {
+            v.config_name = name;
+        }
+        self.set_tick_divisor(id, tick_divisor);
+        if let Some((offset, limit_deg)) = turret {
+            self.attach_turret(id, offset, limit_deg);
+        }
+        if let Some(p) = payload {
+            let _ = self.set_payload(id, p.mass, p.offset);
+        }
+        Ok(handle)
+    }
+
+    pub fn spawn_vehicle_with_config(&mut self, id: String, position: [f32; 3], yaw: f32, config: VehicleConfig) -> Result<RigidBodyHandle, Vec<ConfigError>> {
+        let spawn_x = position[0];
+        let spawn_z = position[2];
+        let spawn_y = self.spawn_height(spawn_x, spawn_z);
+
+        // Reject bad configs before anything touches Rapier
+        config.validate()?;
+        let mut wheels = self.build_car_wheels(&config);
+        config.validate_wheels(&wheels, SUSPENSION_SAG_M)?;
+
+        let volume = 2.0 * 1.0 * 4.0;       // box size
+        let density = config.mass / volume; // ρ = m / V
+        
+        // Rigid body
+        let rb = RigidBodyBuilder::dynamic()
+            .translation(vector![spawn_x, spawn_y, spawn_z])
+            .rotation(vector![0.0, yaw, 0.0])
+            .linear_damping(config.linear_damping)
+            .angular_damping(config.angular_damping)
+            .ccd_enabled(true)
+            .build();
+        
+        // Box collider
+        let [hx, hy, hz] = config.chassis_half_extents;
+        let [cx, cy, cz] = config.chassis_com_offset;
+
+        let collider = ColliderBuilder::cuboid(hx, hy, hz)
+            .translation(vector![cx, cy, cz]) // COM offset
+            .collision_groups(InteractionGroups::new(
+                GROUP_CHASSIS,
+                chassis_filter(&config),
+            ))
+            .active_events(ActiveEvents::CONTACT_FORCE_EVENTS) // impacts (collisions.rs)
+            .contact_force_event_threshold(0.0)
+            .density(density)
+            .friction(0.0) // IMPORTANT
+            .restitution(0.0)
+            .build();
+
+        let handle = self.bodies.insert(rb); // insert rigid body
+        
+        self.colliders.insert_with_parent(collider, handle, &mut self.bodies); // attach to body
+
+        // Optional hard wheels: massless balls that only meet obstacles
+        // (flat ground stays ray-only), moved to the suspension position each tick
+        if config.wheel_colliders {
+            for wheel in wheels.iter_mut() {
+                let ball = ColliderBuilder::ball(wheel.radius)
+                    .position(wheel_collider_position(wheel, 0.0))
+                    .collision_groups(InteractionGroups::new(GROUP_WHEEL, GROUP_OBSTACLE))
+                    .density(0.0)
+                    .friction(0.0)
+                    .restitution(0.0)
+                    .build();
+                wheel.collider = Some(self.colliders.insert_with_parent(ball, handle, &mut self.bodies));
+            }
+        }
+
+        self.body_to_player.insert(handle, id.clone()); // map body to player ID  
+        self.wheels.insert(handle, wheels); // setup wheels
+        
+        self.vehicles.insert(id.clone(), VehicleBuilder::new(handle, config).build());
+
+        println!(
+            "🚗 Spawned vehicle for player {} at {:?} (body = {:?})",
+            id, position, handle
+        );
+
+        Ok(handle)
+    }    
+    
+    fn suspension_from_sag(&mut self, vehicle_mass: f32, wheels: usize, sag_m: f32, zeta: f32) -> (f32, f32) {
+        let m = vehicle_mass / wheels as f32;
+        let g = 9.81_f32;
+        let f_static = m * g;              // per wheel
+        let k = f_static / sag_m.max(1e-3); // N/m
+
+        // damping: c = 2*zeta*sqrt(k*m)
+        let c = 2.0 * zeta * (k * m).sqrt();
+        (k, c)
+    }
+
+    
+    // ===========================================================================
+    //  GTA-style car placeholder with 4 suspension raycasts.
+    // ===========================================================================
+    pub fn build_car_wheels(&mut self, config: &VehicleConfig) -> Vec<Wheel> {
+        let vehicle_mass = config.mass; // kg
+        let wheels = 4;                 // number of wheels
+        let sag_m = SUSPENSION_SAG_M;   // meters
+        let zeta = SUSPENSION_ZETA;     // damping ratio (0.7–1.0)
+        
+        let (k, c) = self.suspension_from_sag(vehicle_mass, wheels, sag_m, zeta);
+        let front_drive = config.drivetrain == Drivetrain::Awd;
+        vec![
+            Wheel { offset: point![-0.8, -0.3,  1.5], rest_length: 0.5, max_length: 0.9, radius: 0.35, stiffness: k, damping: c, drive: front_drive, steer: true, debug_id: "FL".to_string(), tire_state: TireState::Grip, wheel_state: WheelState::default(), ray_cache: WheelRayCache::default(), kerb: KerbDetector::default(), collider: None, trail: WheelTrail::default(), axle: 0, prev_compression: None},
+            Wheel { offset: point![ 0.8, -0.3,  1.5], rest_length: 0.5, max_length: 0.9, radius: 0.35, stiffness: k, damping: c, drive: front_drive, steer: true, debug_id: "FR".to_string(), tire_state: TireState::Grip, wheel_state: WheelState::default(), ray_cache: WheelRayCache::default(), kerb: KerbDetector::default(), collider: None, trail: WheelTrail::default(), axle: 0, prev_compression: None},
+            Wheel { offset: point![-0.8, -0.3, -1.5], rest_length: 0.5, max_length: 0.9, radius: 0.35, stiffness: k, damping: c, drive: true,  steer: false, debug_id: "RL".to_string(), tire_state: TireState::Grip, wheel_state: WheelState::default(), ray_cache: WheelRayCache::default(), kerb: KerbDetector::default(), collider: None, trail: WheelTrail::default(), axle: 1, prev_compression: None},
+            Wheel { offset: point![ 0.8, -0.3, -1.5], rest_length: 0.5, max_length: 0.9, radius: 0.35, stiffness: k, damping: c, drive: true,  steer: false, debug_id: "RR".to_string(), tire_state: TireState::Grip, wheel_state: WheelState::default(), ray_cache: WheelRayCache::default(), kerb: KerbDetector::default(), collider: None, trail: WheelTrail::default(), axle: 1, prev_compression: None},
+        ]
+    }
+
+    // ============================================================================
+    //  Apply Suspension (vehicles stepping at `tick_divisor` only)
+    // ============================================================================
+    fn apply_suspension(&mut self, dt: Real, tick_divisor: u32) {
+        self.sync_query_pipeline();
+
+        // Colliders that can move between ticks invalidate cached wheel rays
+        // (sleeping bodies cannot move without waking; the cache refill treats them as static)
+        let dynamic_aabbs: Vec<(RigidBodyHandle, Aabb)> = self
+            .island_manager
+            .active_dynamic_bodies()
+            .iter()
+            .chain(self.island_manager.active_kinematic_bodies())
+            .filter_map(|&h| Some((h, self.bodies.get(h)?)))
+            .flat_map(|(h, rb)| rb.colliders().iter().map(move |&c| (h, c)))
+            .filter_map(|(h, c)| Some((h, self.colliders.get(c)?.compute_aabb())))
+            .collect();
+        let ray_scene = RayCacheScene { static_epoch: self.static_epoch, dynamic_aabbs: &dynamic_aabbs };
+
+        let mut buffers = std::mem::take(&mut self.solve_buffers);
+
+        for (&handle, wheels) in self.wheels.iter_mut() {
+            let Some(body_ro) = self.bodies.get(handle) else { continue };
+            let Some(player_id) = self.body_to_player.get(&handle) else { continue };
+            let Some(vehicle) = self.vehicles.get_mut(player_id) else { continue };
+            if vehicle.tick_divisor != tick_divisor {
+                continue;
+            }
+            let overlay_mark = self.debug_overlay.mark();
+            
+            // ======================================================
+            //  Debug: chassis
+            // ======================================================
+            let pos = body_ro.position();
+            self.debug_overlay.chassis = Some(DebugChassis {
+                position: pos.translation.vector.into(),
+                rotation: [ pos.rotation.i, pos.rotation.j, pos.rotation.k, pos.rotation.w, ],
+                half_extents: vehicle.config.chassis_half_extents,
+            });
+            self.debug_overlay.flipped = vehicle.flip.flipped().then_some(vehicle.flip.flipped_for);
+            self.debug_overlay.input_stale = vehicle.input_hold.silent_for(vehicle.config.input_timeout_s);
+
+            // ==================================================
+            //  Impulse Accumulator
+            // ==================================================
+            let impulses = &mut buffers.accumulator;
+            impulses.clear();
+
+            // --------------------------------------------------
+            //  VEHICLE CONSTANTS
+            // --------------------------------------------------
+            let body_mass = body_ro.mass(); // includes payload (set_payload)
+            let fz_ref = body_mass * 9.81 / wheels.len() as f32;
+            let mut axle_wheels = [0_u8; MAX_AXLES];
+            let driven_wheels = wheels.iter().filter(|w| w.drive).count();
+            for wheel in wheels.iter() {
+                if let Some(n) = axle_wheels.get_mut(wheel.axle) {
+                    *n += 1;
+                }
+            }
+            
+            
+            // --------------------------------------------------
+            // PHASE 1 — SENSE
+            // --------------------------------------------------
+            let contacts = &mut buffers.contacts;
+            contacts.clear();
+            let suspension_contacts = &mut buffers.suspension_contacts;
+            let axle_compression = &mut buffers.axle_compression;
+            let axle_normal_force = &mut buffers.axle_normal_force;
+            suspension_contacts.clear();
+            axle_compression.clear();
+            axle_normal_force.clear();
+            
+            let cfg = SteeringConfig {
+                wheelbase: vehicle.config.wheelbase,
+                track_width: vehicle.config.track_width,
+                max_steer_angle: vehicle.config.max_steer_angle,
+                ackermann: vehicle.config.ackermann,
+            };
+            
+            let target = vehicle.steer * cfg.max_steer_angle;
+            
+            let tau = 0.10; // seconds to reach ~63%
+            let k = 1.0 - (-dt as f32 / tau).exp();
+            vehicle.steer_angle += (target - vehicle.steer_angle) * k;
+
+
+            // Flat ground normal, banked by the road camber (the contact
+            // swaps in the hit surface's normal, keeping the camber tilt)
+            let ground_n = camber_ground_normal(&body_ro.position().rotation, self.road_camber_deg);
+
+            let (fl, fr) = solve_steering(&cfg, &body_ro.position().rotation, vehicle.steer_angle);
+            vehicle.steering.fl = fl;
+            vehicle.steering.fr = fr;
+            
+            vehicle.wheel_surfaces = Default::default();
+            for wheel in wheels.iter_mut() {
+                let normal_force = 0.0;
+                if let Some(mut contact) = build_suspension_contact(
+                    wheel,
+                    vehicle,
+                    &vehicle.steering,
+                    body_ro,
+                    &self.query_pipeline,
+                    &self.bodies,
+                    &self.colliders,
+                    handle,
+                    &ray_scene,
+                    ground_n,
+                    fz_ref,
+                    dt as f32,
+                ) {
+                    if let Some(collider) = contact.hit_collider {
+                        let grip = self.surfaces.grip(collider); // map.rs "friction" (ice patches)
+                        contact.mu_lat *= grip;
+                        contact.mu_long *= grip;
+                    }
+                    let id = WheelId::from_debug(&wheel.debug_id);
+                    let surface = contact.hit_collider.and_then(|collider| {
+                        self.surfaces.describe(&self.colliders, &self.bodies, &self.body_to_player, collider, contact.hit_normal)
+                    });
+                    vehicle.wheel_surfaces[id.index()] = surface.clone();
+                    place_wheel_collider(&mut self.colliders, wheel, contact.compression);
+
+                    let kerb_compression = (contact.grounded && !contact.embedded).then_some(contact.compression);
+                    if let Some(intensity) = wheel.kerb.update(kerb_compression, dt) {
+                        self.haptic_events.push(kerb_event(player_id, id.as_str(), intensity));
+                    }
+
+                    axle_compression.insert(id, contact.compression);
+                    axle_normal_force.insert(id, contact.normal_force);
+                    suspension_contacts.push((id, contact.clone()));
+
+                    wheel.wheel_state.update(
+                        contact.v_long,
+                        vehicle.throttle,
+                        wheel.drive,
+                        wheel.tire_state,
+                        wheel.radius,
+                    );
+
+                    contacts.push(build_contact_patch(id, &contact, wheel, vehicle, body_ro));
+
+                    // ===============================================================================
+                    // debug slip rays
+                    // ===============================================================================
+                    if contact.forward.magnitude() > 1e-4 {
+                        let slip_mag = contact.v_lat.abs();
+                        if slip_mag > 0.01 {
+                            let slip_dir = if contact.v_lat >= 0.0 { contact.side } else { -contact.side };
+                            let slip_len = (slip_mag * 0.25).clamp(0.02, 0.6);
+                            let color = match contact.wheel_id.as_str() {
+                                "FL" | "RL" => [0.2, 0.6, 1.0],
+                                "FR" | "RR" => [1.0, 0.4, 0.2],
+                                _ => [1.0, 1.0, 1.0],
+                            };
+                            let slip_origin = contact.hit_point + contact.ground_normal * wheel.radius * 0.25;
+                            let slip_angle = 0.0;
+                            self.debug_overlay.slip_vectors.push(DebugSlipRay {
+                                origin: slip_origin.into(),
+                                direction: slip_dir.into(),
+                                slip_angle: slip_angle,
+                                magnitude: slip_len,
+                                color,
+                            });
+                        }
+                    }
+
+                    // ==================================================================
+                    //  Shared Debug Params
+                    // ==================================================================
+                    let origin = pos * (wheel.offset + vector![0.0, wheel.radius + 0.02, 0.0]);
+                    let dir = vector![0.0, -1.0, 0.0];
+                    let ground_n = contact.ground_normal;
+                    let max_dist = wheel.rest_length + wheel.max_length + wheel.radius;
+                    let wheel_center = contact.hit_point + contact.ground_normal * wheel.radius;
+                    
+                    // ==========================================================
+                    //  DEBUG: suspension ray (ALWAYS push)
+                    // ==========================================================
+                    self.debug_overlay.suspension_rays.push(DebugRay {
+                        origin: origin.into(),
+                        direction: dir.into(),
+                        length: max_dist,
+                        hit: Some(p3(contact.hit_point)),
+                        color: if contact.embedded {
+                            EMBEDDED_RAY_COLOR
+                        } else if contact.grounded {
+                            [0.0, 1.0, 0.0]
+                        } else {
+                            [1.0, 0.0, 0.0]
+                        },
+                    });
+
+                    // ----------------------------------------------------------
+                    // DEBUG: wheel numeric (ALWAYS push)
+                    // ----------------------------------------------------------
+                    self.debug_overlay.wheels.push(DebugWheel {
+                        id: wheel.debug_id.clone(),
+                        center: wheel_center.into(),
+                        radius: wheel.radius as f32,
+                        grounded: contact.grounded,
+                        compression: contact.compression,
+                        normal_force: contact.normal_force,
+                        steer: vehicle.steer,
+                        steering: wheel.steer,
+                        drive: wheel.drive,
+                        spin_rpm: wheel.wheel_state.spin_rpm,
+                        spin_dir: wheel.wheel_state.spin_direction,
+                        embedded: contact.embedded,
+                        surface,
+                    });
+
+                    // ----------------------------------------------------------
+                    // DEBUG: load bar (optional but super helpful)
+                    // ----------------------------------------------------------
+                    let norm = (contact.normal_force / 12000.0).clamp(0.0, 1.0);
+                    let bar_len = norm.sqrt() * 1.25;
+
+                    let bar_origin = wheel_center + ground_n * 0.03;
+                    let color = match wheel.debug_id.as_str() {
+                        "FL" | "RL" => [0.2, 0.6, 1.0],
+                        "FR" | "RR" => [1.0, 0.4, 0.2],
+                        _ => [1.0, 1.0, 1.0],
+                    };
+
+                    self.debug_overlay.load_bars.push(DebugRay {
+                        origin: bar_origin.into(),
+                        direction: ground_n.into(),
+                        length: bar_len,
+                        hit: Some((bar_origin + ground_n * bar_len).into()),
+                        color,
+                    });
+
+                } else {
+                    place_wheel_collider(&mut self.colliders, wheel, 0.0);
+                    wheel.kerb.update(None, dt);
+                } // end contact creation
+                
+            } // end wheel iter()
+
+            // Airborne: no wheel loaded for a few ticks (airborne.rs)
+            let wheels_down = suspension_contacts.iter()
+                .filter(|(_, c)| c.compression > 0.0 || c.embedded)
+                .count();
+            vehicle.air.update(wheels_down, dt);
+
+            // --------------------------------------------------
+            // PHASE 2 — REDISTRIBUTE (ARB)
+            // --------------------------------------------------
+            let arb_pairs = axle_arb_pairs(
+                wheels.iter().map(|w| (w.axle, w.offset.x, WheelId::from_debug(&w.debug_id))),
+            );
+            for (axle, pair) in arb_pairs.into_iter().enumerate() {
+                let Some((left, right)) = pair else { continue };
+                apply_arb_load_transfer(
+                    left, right,
+                    axle_normal_force,
+                    axle_compression,
+                    vehicle.config.arb_rates[axle],
+                    fz_ref,
+                );
+            }
+
+            // --------------------------------------------------
+            // PHASE 3A — SUSPENSION IMPULSES (STORE ONLY)
+            // --------------------------------------------------
+            let mut corner_loads = [0.0_f32; 4];
+            for (wheel_id, contact) in suspension_contacts.iter() {
+
+                let axel_normal = axle_normal_force.get(wheel_id).copied().unwrap_or(contact.normal_force);
+                let max_normal_impulse = fz_ref * 1.5 * dt; // ≈ 1.5g per wheel
+                let normal_impulse_mag = (axel_normal * dt as f32).clamp(0.0, max_normal_impulse as f32);
+                corner_loads[wheel_id.index()] = normal_impulse_mag / dt;
+
+                impulses.at_points.push((
+                    contact.ground_normal * normal_impulse_mag as Real,
+                    contact.apply_point,
+                ));
+
+                if self.debug_show_forces {
+                    push_audit(&mut self.impulse_audit_log, ImpulseAuditEntry {
+                        tick: self.tick,
+                        player_id: player_id.clone(),
+                        wheel_id: Some(wheel_id.as_str().to_string()),
+                        source: ImpulseSource::Suspension,
+                        impulse: (contact.ground_normal * normal_impulse_mag).into(),
+                        point: Some(contact.apply_point.into()),
+                        normal_force: axel_normal,
+                    });
+                }
+
+                // Embedded: push the wheel's share of the mass out of the ground,
+                // separation speed proportional to depth but bounded
+                if contact.embedded {
+                    let mass_share = fz_ref / 9.81;
+                    let v_target = (contact.penetration * EMBED_RECOVERY_RATE).min(EMBED_MAX_SPEED);
+                    let dv = (v_target - contact.suspension_vel).clamp(0.0, EMBED_MAX_SPEED);
+                    let j = contact.ground_normal * (mass_share * dv);
+                    impulses.at_points.push((j, contact.apply_point));
+
+                    if self.debug_show_forces {
+                        push_audit(&mut self.impulse_audit_log, ImpulseAuditEntry {
+                            tick: self.tick,
+                            player_id: player_id.clone(),
+                            wheel_id: Some(wheel_id.as_str().to_string()),
+                            source: ImpulseSource::Depenetration,
+                            impulse: j.into(),
+                            point: Some(contact.apply_point.into()),
+                            normal_force: axel_normal,
+                        });
+                    }
+                }
+            }
+
+            let k = 1.0 - (-dt / CORNER_LOAD_TAU).exp();
+            for (avg, load) in vehicle.corner_loads.iter_mut().zip(corner_loads) {
+                *avg += (load - *avg) * k;
+            }
+
+            // --------------------------------------------------
+            // PHASE 3B — TIRE SOLVER
+            // --------------------------------------------------
+            for contact in contacts.iter_mut() {
+                if let Some(nf) = axle_normal_force.get(&contact.wheel) {
+                    contact.normal_force = *nf;
+                }
+            }
+
+            // Simplified model: one averaged contact per axle (left wheel's id)
+            let axle_averaged = self.use_axle_averaged_tire_model;
+            if axle_averaged {
+                let after_arb = |id: WheelId| {
+                    suspension_contacts.iter().find(|(w, _)| *w == id).map(|(_, c)| SuspensionContact {
+                        normal_force: axle_normal_force.get(&id).copied().unwrap_or(c.normal_force),
+                        ..c.clone()
+                    })
+                };
+
+                contacts.clear();
+                for (left, right) in [(WheelId::FL, WheelId::FR), (WheelId::RL, WheelId::RR)] {
+                    let (id, axle) = match (after_arb(left), after_arb(right)) {
+                        (Some(l), Some(r)) => (left, merge_axle_contacts(&l, &r)),
+                        (Some(l), None) => (left, l),
+                        (None, Some(r)) => (right, r),
+                        (None, None) => continue,
+                    };
+                    let Some(wheel) = wheels.iter().find(|w| w.debug_id == id.as_str()) else { continue };
+                    contacts.push(build_contact_patch(id, &axle, wheel, vehicle, body_ro));
+                }
+            }
+
+            let ctx = SolveContext {
+                dt: dt as f32,
+                mass: body_mass,
+                engine_force: vehicle.config.engine_force,
+                brake_force: vehicle.config.brake_force,
+                abs_enabled: vehicle.config.abs_enabled,
+                tcs_enabled: vehicle.config.tcs_enabled,
+                abs_limit: vehicle.config.abs_nx_limit,
+                tcs_limit: vehicle.config.tcs_nx_limit,
+                driven_wheels: driven_wheels as f32 / if axle_averaged { 2.0 } else { 1.0 },
+                wheels_per_contact: if axle_averaged { 2.0 } else { 1.0 },
+                axle_wheels,
+                base_front_bias: vehicle.brake_bias,
+                wheelbase: vehicle.config.wheelbase,
+                mu_base: vehicle.config.mu_base,
+                powertrain_curve: vehicle.config.powertrain_efficiency_at_speed,
+                engine_brake_coefficient: vehicle.config.engine_brake_coefficient,
+                kamm: vehicle.config.kamm,
+                tv_enabled: vehicle.config.tv_enabled && !axle_averaged, // needs left/right patches
+                tv_gain: vehicle.config.tv_gain,
+                tv_max_bias: vehicle.config.tv_max_bias,
+                tv_min_speed: vehicle.config.tv_min_speed,
+                stability_assist: vehicle.config.stability_assist,
+                yaw_inertia: yaw_inertia(body_ro),
+            };
+
+            let control = ControlInput {
+                throttle: vehicle.throttle,
+                brake: vehicle.brake,
+                steer: vehicle.steer,
+            };
+
+            let tire_impulses = &mut buffers.impulses;
+            tire_impulses.clear();
+            let tire_forces = solve_step(&ctx, &control, contacts, tire_impulses);
+            self.debug_overlay.drive_split = Some(tire_forces.drive_split);
+            self.debug_overlay.brake_force_axle = Some(tire_forces.brake_force_axle);
+            for imp in tire_impulses.iter() {
+                if self.debug_show_forces {
+                    push_audit(&mut self.impulse_audit_log, ImpulseAuditEntry {
+                        tick: self.tick,
+                        player_id: player_id.clone(),
+                        wheel_id: Some(imp.wheel.as_str().to_string()),
+                        source: imp.source,
+                        impulse: imp.impulse,
+                        point: imp.at_point,
+                        normal_force: contacts.iter().find(|c| c.wheel == imp.wheel).map_or(0.0, |c| c.normal_force),
+                    });
+                }
+                let j: Vector<Real> = imp.impulse.into();
+                match imp.at_point {
+                    Some(p) => impulses.at_points.push((j, Point::from(p))),
+                    None => impulses.linear.push(j),
+                }
+            }
+            if let Some(ev) = vehicle.haptics.abs_update(player_id, tire_forces.abs_intensity) {
+                self.haptic_events.push(ev);
+            }
+            if tire_forces.yaw_impulse != 0.0 {
+                impulses.angular.push(vector![0.0, tire_forces.yaw_impulse, 0.0]);
+                if self.debug_show_forces {
+                    push_audit(&mut self.impulse_audit_log, ImpulseAuditEntry {
+                        tick: self.tick,
+                        player_id: player_id.clone(),
+                        wheel_id: None,
+                        source: ImpulseSource::StabilityAssist,
+                        impulse: [0.0, tire_forces.yaw_impulse, 0.0],
+                        point: None,
+                        normal_force: axle_normal_force.values().sum(),
+                    });
+                }
+            }
+
+            // --------------------------------------------------
+            // DEBUG: tire force vectors + contact / tire-force trails (trails.rs)
+            // --------------------------------------------------
+            for wheel in wheels.iter_mut() {
+                let id = WheelId::from_debug(&wheel.debug_id);
+                let contact = suspension_contacts.iter().find(|(w, _)| *w == id).map(|(_, c)| c);
+                match contact {
+                    Some(c) if c.grounded => {
+                        let force = tire_impulses
+                            .iter()
+                            .filter(|imp| imp.wheel == id)
+                            .fold(Vector::zeros(), |sum, imp| sum + Vector::from(imp.impulse))
+                            / dt;
+                        let magnitude = force.magnitude();
+                        if magnitude > 1.0 {
+                            let length = (magnitude / TIRE_FORCE_FULL_SCALE).clamp(0.0, 1.0).sqrt() * 1.25;
+                            let direction = force / magnitude;
+                            self.debug_overlay.tire_forces.push(DebugRay {
+                                origin: c.hit_point.into(),
+                                direction: direction.into(),
+                                length,
+                                hit: Some((c.hit_point + direction * length).into()),
+                                color: TIRE_FORCE_COLOR,
+                            });
+                        }
+                        wheel.trail.push(self.tick, c.hit_point, force, self.debug_trail_len);
+                    }
+                    _ => wheel.trail.reset(),
+                }
+                self.debug_overlay.trails.push(wheel.trail.to_debug(&wheel.debug_id, self.tick));
+            }
+
+            // --------------------------------------------------
+            // PHASE 3C — APPLY ALL IMPULSES (ONCE)
+            // --------------------------------------------------
+
+            // Static Friction lock at low speed
+            let body = self.bodies.get_mut(handle).unwrap();
+            let v = body.linvel();
+            let speed = (v.x * v.x + v.z * v.z).sqrt();
+
+            let hard_brake = control.brake > 0.8;
+            let near_rest  = speed < 0.4;
+
+            if hard_brake && near_rest && !vehicle.air.airborne {
+                // Kill planar velocity
+                body.set_linvel(vector![0.0, v.y, 0.0], true);
+
+                // Kill yaw
+                body.set_angvel(vector![0.0, 0.0, 0.0], true);
+            }
+
+            // Mid-air: light angular damping + steer / pitch attitude control
+            let airborne = vehicle.air.airborne;
+            let angular_damping = vehicle.config.angular_damping
+                * if airborne { AIR_ANGULAR_DAMPING_SCALE } else { 1.0 };
+            if body.angular_damping() != angular_damping {
+                body.set_angular_damping(angular_damping);
+            }
+            if airborne && vehicle.config.air_control_torque > 0.0 {
+                let j = air_control_torque(body.rotation(), vehicle.steer, vehicle.pitch, vehicle.config.air_control_torque) * dt;
+                impulses.angular.push(j);
+                if self.debug_show_forces {
+                    push_audit(&mut self.impulse_audit_log, ImpulseAuditEntry {
+                        tick: self.tick,
+                        player_id: player_id.clone(),
+                        wheel_id: None,
+                        source: ImpulseSource::AirControl,
+                        impulse: j.into(),
+                        point: None,
+                        normal_force: 0.0,
+                    });
+                }
+            }
+
+            impulses.apply(body);
+
+            if self.watched_overlays.contains(player_id) {
+                self.vehicle_overlays.insert(player_id.clone(), self.debug_overlay.since(overlay_mark));
+            }
+
+        } // Players loop
+
+        self.solve_buffers = buffers;
+    } // end
+
+    // ===========================================================================
+    // Recorded impulses for one player, oldest first (needs debug_show_forces)
+    // ===========================================================================
+    pub fn dump_audit_log(&self, player_id: &str) -> Vec<ImpulseAuditEntry> {
+        self.impulse_audit_log
+            .iter()
+            .filter(|e| e.player_id == player_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Slow down / speed up the simulation (bullet time). Clamped to
+    /// 0.1..=2.0; non-finite values are ignored. Returns the scale in effect.
+    pub fn set_timestep_scale(&mut self, scale: f32) -> f32 {
+        if scale.is_finite() {
+            self.timestep_scale = scale.clamp(TIMESTEP_SCALE_MIN, TIMESTEP_SCALE_MAX);
+        }
+        self.timestep_scale
+    }
+
+    /// Simulated time for a tick of `dt` real seconds.
+    pub fn scaled_dt(&self, dt: Real) -> Real {
+        dt * self.timestep_scale
+    }
+
+    /// Substeps for a tick of `dt` simulated seconds: enough to keep each
+    /// under SUSPENSION_MAX_DT, and ceil(0.5 / scale) in deep slow motion,
+    /// where small per-tick motion makes contacts jitter visibly.
+    pub fn substep_count(&self, dt: Real) -> usize {
+        let stability = (dt / SUSPENSION_MAX_DT).ceil().max(1.0) as usize;
+        let slow_motion = (TIMESTEP_SCALE_FULL_QUALITY / self.timestep_scale).ceil().max(1.0) as usize;
+        stability.max(slow_motion)
+    }
+
+    pub fn step(&mut self, dt: Real) {
+        // Everything below (controls, tire forces, Rapier) runs on simulated time
+        let dt = self.scaled_dt(dt);
+
+        // prevent ui clutter
+        self.debug_overlay.clear();
+        self.haptic_events.clear();
+        self.collision_events.clear();
+        self.tick += 1;
+
+        // Rooms below the server rate (tick_divisor n > 1) step every n-th
+        // tick by n x dt, one rate group at a time with the other groups'
+        // bodies disabled. Vehicles sitting this tick out keep their overlay.
+        let groups = self.due_tick_groups();
+        let split = self.vehicles.values().any(|v| v.tick_divisor > 1);
+        let tick = self.tick;
+        self.retain_vehicle_overlays(|v| !tick.is_multiple_of(v.tick_divisor as u64));
+
+        // Chassis contact-force events (collision events are not enabled)
+        let (force_send, force_recv) = rapier3d::crossbeam::channel::unbounded();
+        let (collision_send, _) = rapier3d::crossbeam::channel::unbounded();
+        let events = ChannelEventCollector::new(collision_send, force_send);
+
+        let mut suspension_time = std::time::Duration::ZERO;
+        for &group in &groups {
+            if split {
+                self.enable_tick_group(Some(group));
+            }
+
+            // Stiff springs go unstable on long explicit steps: split the tick
+            let group_dt = dt * group as f32;
+            let substeps = self.substep_count(group_dt);
+            let sub_dt = group_dt / substeps as f32;
+            let params = IntegrationParameters { dt: sub_dt, ..IntegrationParameters::default() };
+
+            for substep in 0..substeps {
+                if substep > 0 {
+                    self.debug_overlay.clear(); // overlay shows the last substep
+                    self.retain_vehicle_overlays(|v| v.tick_divisor != group);
+                }
+
+                // Convert inputs → intent (NO PHYSICS)
+                apply_vehicle_controls(self.vehicles.values_mut().filter(|v| v.tick_divisor == group), sub_dt);
+
+                // Apply suspension + traction + tire forces
+                let suspension_started = std::time::Instant::now();
+                self.apply_suspension(sub_dt, group);
+                suspension_time += suspension_started.elapsed();
+
+                // Step physics
+                let hooks = ();
+                self.pipeline.step(
+                    &self.gravity,
+                    &params,
+                    &mut self.island_manager,
+                    &mut self.broad_phase,
+                    &mut self.narrow_phase,
+                    &mut self.bodies,
+                    &mut self.colliders,
+                    &mut self.joints,
+                    &mut self.multibody_joints,
+                    &mut self.ccd,
+                    Some(&mut self.query_pipeline),
+                    &hooks,
+                    &events,
+                );
+                while let Ok(event) = force_recv.try_recv() {
+                    self.record_contact_force(&event);
+                }
+            }
+        }
+        if split {
+            self.enable_tick_group(None);
+        }
+        self.collision_events = self.collisions.finish();
+        self.suspension_time = suspension_time;
+
+        // Collision rumble hints (rising edge of chassis contact impulse)
+        let mut ids: Vec<String> = self.vehicles.keys().cloned().collect();
+        ids.sort();
+        for id in ids {
+            let impulse: f32 = self.get_contact_forces(&id).iter().map(|c| c.normal_impulse).sum();
+            let Some(vehicle) = self.vehicles.get_mut(&id) else { continue };
+            if !tick.is_multiple_of(vehicle.tick_divisor as u64) {
+                continue; // didn't step
+            }
+            let mass = self.bodies.get(vehicle.body).map_or(vehicle.config.mass, |b| b.mass());
+            if let Some(ev) = vehicle.haptics.collision_update(&id, impulse, mass) {
+                self.haptic_events.push(ev);
+            }
+        }
+
+        // Safety: clamp runaway velocities (edge-on hits can spin bodies at 100+ rad/s)
+        for (_, body) in self.bodies.iter_mut() {
+            if !body.is_dynamic() {
+                continue;
+            }
+
+            let angvel = *body.angvel();
+            let w = angvel.norm();
+            if w > self.max_angular_velocity {
+                body.set_angvel(angvel.normalize() * self.max_angular_velocity, true);
+            }
+
+            let linvel = *body.linvel();
+            let v = linvel.norm();
+            if v > self.max_linear_velocity {
+                body.set_linvel(linvel.normalize() * self.max_linear_velocity, true);
+            }
+        }
+
+        // Safety: prevent bodies from exploding to insane coordinates
+        let cooldown_ticks = (MIN_RESPAWN_INTERVAL_S / dt).ceil() as u64;
+        let reset_y = if self.terrain.is_some() { self.spawn_height(0.0, 0.0) } else { 1.0 };
+        for (handle, body) in self.bodies.iter_mut() {
+            let mut pos = *body.translation();
+
+            let finite = pos.x.is_finite() && pos.y.is_finite() && pos.z.is_finite();
+            let bad = !finite || pos.x.abs() > 1_000.0 || pos.y.abs() > 1_000.0 || pos.z.abs() > 1_000.0;
+            if !bad {
+                continue;
+            }
+
+            // Vehicles only get one teleport per cooldown; in between, pin them
+            // at the boundary instead (NaN positions still have to be reset)
+            let vehicle = self.body_to_player.get(&handle)
+                .and_then(|id| Some((id, self.vehicles.get_mut(id)?)));
+            if let Some((player_id, vehicle)) = vehicle {
+                let since = vehicle.last_respawn_tick.map(|t| self.tick.saturating_sub(t));
+                if finite && since.is_some_and(|since| since <= cooldown_ticks) {
+                    vehicle.blocked_respawns += 1;
+                    if vehicle.blocked_respawns == 1 {
+                        println!(
+                            "🚫 Respawn for {} refused: last teleport {} ticks ago (cooldown {})",
+                            player_id, since.unwrap_or_default(), cooldown_ticks
+                        );
+                    }
+                    body.set_linvel(vector![0.0, 0.0, 0.0], true);
+                    body.set_angvel(vector![0.0, 0.0, 0.0], true);
+                    if pos.y > 0.0 {
+                        body.apply_impulse(vector![0.0, -body.mass() * 9.81 * dt, 0.0], true);
+                    }
+                    continue;
+                }
+                if vehicle.blocked_respawns > 0 {
+                    println!(
+                        "⚠️ {} hit the bounds {} more times during its respawn cooldown",
+                        player_id, vehicle.blocked_respawns
+                    );
+                }
+                vehicle.last_respawn_tick = Some(self.tick);
+                vehicle.blocked_respawns = 0;
+            }
+
+            // Reset this body to a safe position above the heightfield
+            pos = vector![0.0, reset_y, 0.0];
+            body.set_translation(pos, true);
+            body.set_linvel(vector![0.0, 0.0, 0.0], true);
+            body.set_angvel(vector![0.0, 0.0, 0.0], true);
+            for wheel in self.wheels.get_mut(&handle).into_iter().flatten() {
+                wheel.prev_compression = None; // don't damp the teleport
+            }
+
+            println!("⚠️ Reset exploding body back to {:?}", pos);
+        }
+
+        self.recover_flipped_vehicles(dt);
+
+        self.record_input_history();
+        self.record_telemetry(dt);
+    }
+
+    /// Tick divisors stepping this tick, ascending (1, the server rate, always does).
+    fn due_tick_groups(&self) -> Vec<u32> {
+        let mut groups: Vec<u32> = self.vehicles.values()
+            .map(|v| v.tick_divisor)
+            .filter(|&n| self.tick.is_multiple_of(n as u64))
+            .chain([1])
+            .collect();
+        groups.sort_unstable();
+        groups.dedup();
+        groups
+    }
+
+    /// Enable only the bodies stepping at `tick_divisor`: a vehicle's chassis
+    /// and turret go with its divisor, every other body runs at the server
+    /// rate. None enables everything again.
+    fn enable_tick_group(&mut self, tick_divisor: Option<u32>) {
+        let groups: HashMap<RigidBodyHandle, u32> = self.vehicles.iter()
+            .flat_map(|(id, v)| {
+                let turret = self.turrets.get(id).map(|t| t.body);
+                std::iter::once(v.body).chain(turret).map(move |body| (body, v.tick_divisor))
+            })
+            .collect();
+        for (handle, body) in self.bodies.iter_mut() {
+            if body.is_fixed() {
+                continue;
+            }
+            let group = groups.get(&handle).copied().unwrap_or(1);
+            let enabled = tick_divisor.is_none_or(|n| n == group);
+            if enabled && !body.is_enabled() {
+                // Rapier gives a re-enabled body its colliders (and their
+                // mass) back only in the next pipeline step, after the
+                // suspension has read body.mass(): do it now
+                body.set_enabled(true);
+                for &handle in body.colliders() {
+                    if let Some(collider) = self.colliders.get_mut(handle) {
+                        collider.set_enabled(false); // "disabled by parent" → disabled → enabled
+                        collider.set_enabled(true);
+                    }
+                }
+                body.recompute_mass_properties_from_colliders(&self.colliders);
+            } else {
+                body.set_enabled(enabled);
+            }
+        }
+    }
+
+    /// Keep only the per-vehicle overlays of vehicles matching `keep`.
+    fn retain_vehicle_overlays(&mut self, keep: impl Fn(&Vehicle) -> bool) {
+        let vehicles = &self.vehicles;
+        self.vehicle_overlays.retain(|id, _| vehicles.get(id).is_some_and(&keep));
+    }
+
+    /// Feed one substep's chassis contact into the impact tracker: who, where
+    /// (impulse-weighted contact point) and how hard (N*s, the pair's solver
+    /// impulses; the event only says which pair was loaded).
+    fn record_contact_force(&mut self, event: &ContactForceEvent) {
+        let Some(pair) = self.narrow_phase.contact_pair(event.collider1, event.collider2) else { return };
+        let Some(c1) = self.colliders.get(pair.collider1) else { return };
+        let c1_pos = c1.position();
+
+        let mut total = 0.0;
+        let mut weighted = Vector::zeros();
+        for pt in pair.manifolds.iter().flat_map(|m| &m.points) {
+            total += pt.data.impulse;
+            weighted += (c1_pos * pt.local_p1).coords * pt.data.impulse;
+        }
+        if total <= 0.0 {
+            return;
+        }
+
+        // Player id + vehicle mass per side ("world" for anything that isn't a car)
+        let side = |collider: ColliderHandle| {
+            self.colliders
+                .get(collider)
+                .and_then(|c| c.parent())
+                .and_then(|body| self.body_to_player.get(&body))
+                .and_then(|id| Some((id.as_str(), self.vehicles.get(id)?.config.mass)))
+        };
+        let (a, b) = (side(pair.collider1), side(pair.collider2));
+        let mass = a.iter().chain(b.iter()).map(|s| s.1).fold(f32::INFINITY, f32::min);
+        if !mass.is_finite() {
+            return; // no vehicle involved
+        }
+        self.collisions.add(
+            a.map_or(WORLD, |s| s.0),
+            b.map_or(WORLD, |s| s.0),
+            v3(weighted / total),
+            total,
+            mass,
+        );
+    }
+
+    /// Push the post-step state of every vehicle with telemetry on.
+    fn record_telemetry(&mut self, dt: f32) {
+        for (id, ring) in self.telemetry.iter_mut() {
+            let Some(vehicle) = self.vehicles.get(id) else { continue };
+            if !self.tick.is_multiple_of(vehicle.tick_divisor as u64) {
+                continue;
+            }
+            let Some(body) = self.bodies.get(vehicle.body) else { continue };
+            ring.record(self.tick, dt * vehicle.tick_divisor as f32, vehicle, body);
+        }
+    }
+
+    /// Push every vehicle's post-step pose for lag compensation.
+    fn record_input_history(&mut self) {
+        for (id, vehicle) in &self.vehicles {
+            let Some(body) = self.bodies.get(vehicle.body) else { continue };
+            let rot = body.rotation();
+            let history = self.input_history.entry(id.clone()).or_default();
+            if history.len() == INPUT_HISTORY_CAPACITY {
+                history.pop_front();
+            }
+            history.push_back(InputHistoryFrame {
+                tick: self.tick,
+                position: (*body.translation()).into(),
+                rotation: [rot.i, rot.j, rot.k, rot.w],
+            });
+        }
+    }
+
+    /// Chassis pose of `player_id` at `tick` (position, quaternion [x, y, z, w]),
+    /// interpolated across missing ticks. None outside the recorded window.
+    pub fn get_position_at_tick(&self, player_id: &str, tick: u64) -> Option<([f32; 3], [f32; 4])> {
+        let history = self.input_history.get(player_id)?;
+        let after = history.iter().position(|f| f.tick >= tick)?;
+        let b = history[after];
+        if b.tick == tick {
+            return Some((b.position, b.rotation));
+        }
+        let a = history.get(after.checked_sub(1)?)?;
+
+        let t = (tick - a.tick) as f32 / (b.tick - a.tick) as f32;
+        let pa = Vector::from(a.position);
+        let pb = Vector::from(b.position);
+        let to_quat = |[x, y, z, w]: [f32; 4]| Rotation::from_quaternion(rapier3d::na::Quaternion::new(w, x, y, z));
+        let rot = to_quat(a.rotation).slerp(&to_quat(b.rotation), t);
+        Some((pa.lerp(&pb, t).into(), [rot.i, rot.j, rot.k, rot.w]))
+    }
+
+    /// Hitscan shot from `shooter`: along the barrel (turret yaw + pitch) if
+    /// it has a turret, else straight off the nose. Every other vehicle
+    /// `can_hit` accepts is rewound to `seen_tick`, the snapshot the shooter
+    /// was looking at (its present pose outside the history window); static
+    /// geometry in between blocks the shot.
+    pub fn fire_projectile(&self, shooter: &str, seen_tick: u64, can_hit: impl Fn(&str) -> bool) -> Option<ProjectileHit> {
+        use rapier3d::parry::query::RayCast;
+
+        let vehicle = self.vehicles.get(shooter)?;
+        let ray = match self.turrets.get(shooter).and_then(|t| Some((t, self.bodies.get(t.body)?))) {
+            Some((turret, top)) => {
+                let (sin, cos) = turret.pitch_angle.sin_cos();
+                Ray::new((*top.translation()).into(), top.rotation() * vector![0.0, sin, cos])
+            }
+            None => {
+                let pos = self.bodies.get(vehicle.body)?.position();
+                let [_, _, hz] = vehicle.config.chassis_half_extents;
+                let [cx, cy, cz] = vehicle.config.chassis_com_offset;
+                Ray::new(pos * point![cx, cy, cz + hz + 0.05], pos.rotation * vector![0.0, 0.0, 1.0])
+            }
+        };
+        let range = self
+            .query_pipeline
+            .cast_ray(&self.bodies, &self.colliders, &ray, PROJECTILE_RANGE_M, true, QueryFilter::only_fixed())
+            .map_or(PROJECTILE_RANGE_M, |(_, toi)| toi);
+
+        let mut hit: Option<ProjectileHit> = None;
+        for (id, target) in &self.vehicles {
+            if id == shooter || !can_hit(id) {
+                continue;
+            }
+            let pose = match self.get_position_at_tick(id, seen_tick) {
+                Some(([x, y, z], [i, j, k, w])) => Isometry::from_parts(
+                    Translation::new(x, y, z),
+                    Rotation::from_quaternion(rapier3d::na::Quaternion::new(w, i, j, k)),
+                ),
+                None => {
+                    // Mid-despawn / stale handle: skip it, the others can still be hit
+                    let Some(body) = self.bodies.get(target.body) else { continue };
+                    *body.position()
+                }
+            };
+            let [hx, hy, hz] = target.config.chassis_half_extents;
+            let [cx, cy, cz] = target.config.chassis_com_offset;
+            let chassis = pose * Isometry::translation(cx, cy, cz);
+            let max = hit.as_ref().map_or(range, |h| h.distance);
+            if let Some(toi) = Cuboid::new(vector![hx, hy, hz]).cast_ray(&chassis, &ray, max, true) {
+                hit = Some(ProjectileHit { target: id.clone(), distance: toi, point: ray.point_at(toi).into() });
+            }
+        }
+        hit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::haptics::HapticKind;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn remove_vehicle_frees_everything_and_can_be_called_twice() {
+        let mut phys = PhysicsWorld::new();
+        let baseline = (phys.bodies.len(), phys.colliders.len());
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        phys.step(DT);
+
+        phys.remove_vehicle("p");
+        assert!(!phys.vehicles.contains_key("p"));
+        assert!(!phys.wheels.contains_key(&body));
+        assert!(!phys.body_to_player.contains_key(&body));
+        assert!(phys.bodies.get(body).is_none());
+        assert_eq!((phys.bodies.len(), phys.colliders.len()), baseline);
+        assert_eq!(phys.debug_validate(), Vec::<String>::new());
+
+        // Again, and for a player whose spawn never finished
+        phys.remove_vehicle("p");
+        phys.remove_vehicle("never-spawned");
+        assert_eq!((phys.bodies.len(), phys.colliders.len()), baseline);
+        phys.step(DT);
+    }
+
+    #[test]
+    fn tire_forces_are_drawn_under_their_debug_category() {
+        let mut phys = PhysicsWorld::new();
+        phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        phys.apply_player_input("p", 1.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0);
+        for _ in 0..90 {
+            phys.clear_debug_overlay();
+            phys.step(DT);
+        }
+
+        let overlay = phys.debug_snapshot();
+        assert!(!overlay.tire_forces.is_empty(), "driven wheels on the ground push a force ray");
+        for ray in &overlay.tire_forces {
+            assert!(ray.length > 0.0 && ray.length <= 1.25);
+            assert!(ray.direction.iter().all(|c| c.is_finite()));
+        }
+        assert_eq!(overlay.filtered(DEBUG_TIRE_FORCES).tire_forces.len(), overlay.tire_forces.len());
+        assert!(overlay.filtered(DEBUG_ALL & !DEBUG_TIRE_FORCES).tire_forces.is_empty());
+    }
+
+    #[test]
+    fn a_silent_client_coasts_to_a_stop() {
+        let mut phys = PhysicsWorld::new();
+        phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        // Latch every axis, so only the input timeout can take the throttle away
+        let latch = serde_json::json!({ "throttle": "latch", "brake": "latch" });
+        let hold = phys.vehicles["p"].input_hold.with_overrides(&latch).unwrap();
+        phys.vehicles.get_mut("p").unwrap().input_hold = hold;
+        let body = phys.vehicles["p"].body;
+        let tick = |phys: &mut PhysicsWorld| {
+            phys.decay_stale_inputs(DT); // what the server loop does each tick
+            phys.clear_debug_overlay();
+            phys.step(DT);
+        };
+
+        // 2 s of full throttle, one message per tick
+        for _ in 0..120 {
+            phys.apply_player_input("p", 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+            tick(&mut phys);
+        }
+        let speed = phys.bodies[body].linvel().norm();
+        assert!(speed > 5.0, "accelerated to {:.2} m/s", speed);
+        assert_eq!(phys.debug_snapshot().input_stale, None);
+
+        // The client freezes holding full throttle: latched until the timeout...
+        let timeout_ticks = (phys.vehicles["p"].config.input_timeout_s / DT) as usize;
+        for _ in 0..timeout_ticks - 1 {
+            tick(&mut phys);
+        }
+        assert_eq!(phys.vehicles["p"].throttle, 1.0);
+        // ...then the car rolls out under the gentle brake and stops
+        for _ in 0..600 {
+            tick(&mut phys);
+        }
+        let v = &phys.vehicles["p"];
+        assert_eq!((v.throttle, v.steer), (0.0, 0.0));
+        assert!(v.brake > 0.0);
+        let speed = phys.bodies[body].linvel().norm();
+        assert!(speed < 0.2, "still moving at {:.2} m/s after 10 s of silence", speed);
+        assert!(phys.debug_snapshot().input_stale.is_some_and(|s| s > 10.0));
+
+        // The next message without a brake axis releases it
+        phys.apply_partial_input("p", [Some(0.5), None, None, None, None, None, None]).unwrap();
+        tick(&mut phys);
+        assert_eq!(phys.vehicles["p"].brake, 0.0);
+        assert_eq!(phys.debug_snapshot().input_stale, None);
+    }
+
+    #[test]
+    fn collision_response_pushes_through_the_centers_of_mass_away_from_the_origin() {
+        let mut phys = PhysicsWorld::new();
+        let a = phys.spawn_vehicle_for_player("a".into(), [40.0, 5.0, -30.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        let b = phys.spawn_vehicle_for_player("b".into(), [44.0, 5.0, -30.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        phys.bodies[b].set_linvel(vector![-4.0, 0.0, 0.0], true);
+        let mass = phys.bodies[a].mass();
+        // Equal cars along the line between their centers: the contact sits on
+        // that line, so no lever arm and j is the plain 1-D exchange
+        let expected = (1.0 + COLLISION_RESTITUTION) * 4.0 * mass / 2.0;
+
+        let (impulse_a, impulse_b) = phys.compute_collision_response("a", "b", [1.0, 0.0, 0.0], 0.0);
+        assert!((impulse_b[0] - expected).abs() < expected * 1e-3, "j = {} (expected {})", impulse_b[0], expected);
+        assert_eq!(impulse_a, [-impulse_b[0], -impulse_b[1], -impulse_b[2]]);
+        assert!(impulse_b[1].abs() < 1e-3 && impulse_b[2].abs() < 1e-3);
+
+        // A spin about +Y moves the contact point sideways only, not along n
+        phys.bodies[a].set_angvel(vector![0.0, 2.0, 0.0], true);
+        let (_, spun) = phys.compute_collision_response("a", "b", [1.0, 0.0, 0.0], 0.0);
+        assert!((spun[0] - expected).abs() < expected * 1e-3, "j = {} with A spinning", spun[0]);
+
+        // Applied at the midpoint of the two centers, neither car picks up spin
+        let contact = Point::from((phys.bodies[a].center_of_mass().coords + phys.bodies[b].center_of_mass().coords) * 0.5);
+        phys.bodies[a].set_angvel(vector![0.0, 0.0, 0.0], true);
+        for (handle, impulse) in [(a, impulse_a), (b, impulse_b)] {
+            phys.bodies[handle].apply_impulse_at_point(impulse.into(), contact, true);
+            let w = phys.bodies[handle].angvel();
+            assert!(w.norm() < 1e-4, "angular response {:?}", w);
+        }
+    }
+
+    #[test]
+    fn sensor_measures_the_distance_to_a_car_ahead() {
+        let mut phys = PhysicsWorld::new();
+        phys.spawn_vehicle_for_player("bot".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        phys.spawn_vehicle_for_player("ahead".into(), [0.0, 1.0, 10.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        phys.step(DT);
+
+        // Rays start 5 cm past the front face (com z + 2.1 + 0.05); the parked
+        // car's rear face is 2.1 m behind its center
+        let [_, _, hz] = GT86.chassis_half_extents;
+        let expected = 10.0 - hz - (hz + 0.05);
+        let sensor = phys.raycast_vehicle_sensor("bot", 20.0, 3.0);
+        assert!(!sensor.front_clear);
+        assert!((sensor.front_dist - expected).abs() < 0.02, "front {} m, expected {} m", sensor.front_dist, expected);
+        // 45° to either side passes beside it within 3 m
+        assert!(sensor.left_clear && sensor.right_clear);
+        assert_eq!((sensor.left_dist, sensor.right_dist), (3.0, 3.0));
+
+        // Out of range is clear at the full range
+        let short = phys.raycast_vehicle_sensor("bot", expected - 0.5, 3.0);
+        assert!(short.front_clear);
+        assert_eq!(short.front_dist, expected - 0.5);
+
+        let unknown = phys.raycast_vehicle_sensor("nobody", 20.0, 3.0);
+        assert!(!unknown.front_clear && !unknown.left_clear && !unknown.right_clear);
+        assert_eq!(unknown.front_dist, 0.0);
+    }
+
+    #[test]
+    fn turret_yaw_follows_input_within_its_limits() {
+        let mut phys = PhysicsWorld::new();
+        phys.spawn_vehicle_for_player("t".into(), [0.0, 1.5, 0.0], 0.0, "tank").unwrap();
+        assert_eq!(phys.attach_turret("t", [0.0, 0.8, 0.0], 45.0).as_deref(), Some("t"));
+        assert!(phys.attach_turret("nobody", [0.0, 0.8, 0.0], 45.0).is_none());
+
+        // Commands past the limit clamp; the barrel pitch clamps on its own range
+        phys.apply_turret_input("t", 2.0, 1.0);
+        let turret = &phys.turrets["t"];
+        assert_eq!(turret.yaw_angle, 45f32.to_radians());
+        assert_eq!(turret.pitch_angle, TURRET_PITCH_MAX);
+        phys.apply_turret_input("t", f32::NAN, 0.0);
+        assert_eq!(phys.turrets["t"].yaw_angle, 45f32.to_radians());
+
+        for _ in 0..180 {
+            phys.step(DT);
+        }
+        let (yaw, pitch) = phys.turret_angles("t").unwrap();
+        assert!((yaw - 45f32.to_radians()).abs() < 0.05, "motor settles on the command, got {:.3}", yaw);
+        assert_eq!(pitch, TURRET_PITCH_MAX);
+
+        phys.remove_vehicle("t");
+        assert!(phys.turrets.is_empty());
+        assert_eq!(phys.debug_validate(), Vec::<String>::new());
+    }
+
+    /// Drive a car across a field of `columns` x 10 low props; `cached =
+    /// false` bumps the static epoch every tick so every wheel ray takes the
+    /// full query. Returns the trajectory and the summed suspension pass time.
+    fn drive_over_props(cached: bool, columns: usize) -> (Vec<[f32; 7]>, std::time::Duration) {
+        let mut phys = PhysicsWorld::new();
+        for i in 0..columns {
+            for j in 0..10 {
+                let center = [i as f32 * 1.6 - 0.8 * columns as f32, FLAT_GROUND_TOP + 0.04, j as f32 * 2.5 + 4.0];
+                phys.add_static_obstacle(center, [0.5, 0.04, 0.5]);
+            }
+        }
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+
+        let mut trajectory = Vec::new();
+        let mut suspension_time = std::time::Duration::ZERO;
+        for tick in 0..300 {
+            if !cached {
+                phys.invalidate_ray_caches();
+            }
+            let steer = if tick > 150 { 0.3 } else { 0.0 };
+            phys.apply_player_input("p", 1.0, steer, 0.0, 0.0, 0.0, 0.0, 0.0);
+            phys.clear_debug_overlay();
+            phys.step(DT);
+            suspension_time += phys.suspension_time;
+            let rb = &phys.bodies[body];
+            let (t, r) = (rb.translation(), rb.rotation());
+            trajectory.push([t.x, t.y, t.z, r.i, r.j, r.k, r.w]);
+        }
+        (trajectory, suspension_time)
+    }
+
+    #[test]
+    fn ray_cache_leaves_the_trajectory_bit_identical() {
+        let (cached, _) = drive_over_props(true, 10);
+        let (uncached, _) = drive_over_props(false, 10);
+        let end = cached.last().unwrap();
+        assert!(end[2] > 10.0, "the car crossed the prop field, z = {:.1}", end[2]);
+        for (tick, (a, b)) in cached.iter().zip(&uncached).enumerate() {
+            assert_eq!(a, b, "diverged at tick {}", tick);
+        }
+    }
+
+    /// Suspension pass time over a 500-prop field, ray cache on vs off
+    /// (best of 5 runs each). Run in release:
+    ///   cargo test --release -- --ignored --nocapture bench_
+    #[test]
+    #[ignore = "benchmark, run by hand in release"]
+    fn bench_ray_cache_over_500_props() {
+        let best = |cached: bool| {
+            let runs: Vec<_> = (0..5).map(|_| drive_over_props(cached, 50)).collect();
+            assert!(runs.windows(2).all(|w| w[0].0 == w[1].0), "deterministic");
+            (runs[0].0.clone(), runs.iter().map(|(_, t)| *t).min().unwrap())
+        };
+        let (cached, cached_time) = best(true);
+        let (uncached, uncached_time) = best(false);
+        assert_eq!(cached, uncached, "same trajectory with and without the cache");
+        println!(
+            "🧪 500 props, 300 ticks: suspension pass {:.2} ms cached vs {:.2} ms uncached ({:.0}% less)",
+            cached_time.as_secs_f64() * 1000.0,
+            uncached_time.as_secs_f64() * 1000.0,
+            100.0 * (1.0 - cached_time.as_secs_f64() / uncached_time.as_secs_f64())
+        );
+        assert!(cached_time < uncached_time);
+    }
+
+    /// Drive car "p" for 180 ticks, alone or with `crowd` other cars (a mix
+    /// of presets, all at full throttle) parked 100 m+ down the road. Returns
+    /// p's trajectory, the mean step and suspension pass time (ms).
+    fn drive_in_a_crowd(crowd: usize) -> (Vec<[f32; 7]>, f64, f64) {
+        let mut phys = PhysicsWorld::new();
+        let mut ids = Vec::new();
+        for k in 0..crowd {
+            let (id, config) = (format!("c{}", k), ["gt86", "arcade", "tank"][k % 3]);
+            let position = [(k % 7) as f32 * 20.0 - 60.0, 1.5, (k / 7) as f32 * 20.0 + 100.0];
+            phys.spawn_vehicle_for_player(id.clone(), position, 0.0, config).unwrap();
+            ids.push(id);
+            if k == crowd / 2 {
+                phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+            }
+        }
+        if crowd == 0 {
+            phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        }
+        let body = phys.vehicles["p"].body;
+
+        let (mut trajectory, mut step_ms, mut suspension_ms) = (Vec::new(), 0.0, 0.0);
+        for tick in 0..180 {
+            let steer = if tick > 90 { 0.3 } else { 0.0 };
+            phys.apply_player_input("p", 1.0, steer, 0.0, 0.0, 0.0, 0.0, 0.0);
+            for id in &ids {
+                phys.apply_player_input(id, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+            }
+            phys.clear_debug_overlay();
+            let started = std::time::Instant::now();
+            phys.step(DT);
+            step_ms += started.elapsed().as_secs_f64() * 1000.0 / 180.0;
+            suspension_ms += phys.suspension_time.as_secs_f64() * 1000.0 / 180.0;
+            let rb = &phys.bodies[body];
+            let (t, r) = (rb.translation(), rb.rotation());
+            trajectory.push([t.x, t.y, t.z, r.i, r.j, r.k, r.w]);
+        }
+        (trajectory, step_ms, suspension_ms)
+    }
+
+    #[test]
+    fn shared_solve_buffers_leave_the_trajectory_bit_identical() {
+        // p solves right after other cars have filled (and grown) the
+        // scratch buffers with their own contacts and impulses
+        let (alone, _, _) = drive_in_a_crowd(0);
+        let (crowded, _, _) = drive_in_a_crowd(49);
+        assert!(alone.last().unwrap()[2] > 10.0, "p drove off");
+        for (tick, (a, b)) in alone.iter().zip(&crowded).enumerate() {
+            assert_eq!(a, b, "diverged at tick {}", tick);
+        }
+    }
+
+    #[test]
+    fn solve_buffers_stop_growing_once_warmed_up() {
+        let mut phys = PhysicsWorld::new();
+        for k in 0..50 {
+            let config = ["gt86", "arcade", "tank"][k % 3];
+            phys.spawn_vehicle_for_player(format!("c{}", k), [(k % 10) as f32 * 20.0, 1.5, (k / 10) as f32 * 20.0], 0.0, config).unwrap();
+        }
+        for _ in 0..30 {
+            phys.step(DT);
+        }
+        let capacities = |b: &SolveBuffers| {
+            (b.contacts.capacity(), b.suspension_contacts.capacity(), b.impulses.capacity(), b.axle_compression.capacity(), b.axle_normal_force.capacity())
+        };
+        let warmed = capacities(&phys.solve_buffers);
+        assert!(warmed.0 > 0 && warmed.2 > 0);
+        for k in 0..50 {
+            phys.apply_player_input(&format!("c{}", k), 1.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0);
+        }
+        for _ in 0..60 {
+            phys.step(DT);
+            assert_eq!(capacities(&phys.solve_buffers), warmed, "a scratch buffer reallocated");
+        }
+    }
+
+    /// Step and suspension pass time with 50 cars in the world. Run in release:
+    ///   cargo test --release -- --ignored --nocapture bench_
+    #[test]
+    #[ignore = "benchmark, run by hand in release"]
+    fn bench_fifty_car_step() {
+        let (_, step_ms, suspension_ms) = (0..5).map(|_| drive_in_a_crowd(49)).min_by(|a, b| a.1.total_cmp(&b.1)).unwrap();
+        println!("🧪 50 cars, 180 ticks: step {:.3} ms, suspension pass {:.3} ms (mean per tick, best of 5)", step_ms, suspension_ms);
+        assert!(step_ms < 1000.0 / 60.0, "50 cars fit the 60 Hz budget");
+    }
+
+    #[test]
+    fn a_new_prop_under_a_parked_car_is_seen_after_invalidation() {
+        let mut phys = PhysicsWorld::new();
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        for _ in 0..120 {
+            phys.step(DT);
+        }
+        let parked_y = phys.bodies[body].translation().y;
+
+        // A slab slid under the car: add_static_obstacle bumps the epoch itself
+        phys.add_static_obstacle([0.0, FLAT_GROUND_TOP + 0.1, 0.0], [2.0, 0.1, 3.0]);
+        for _ in 0..120 {
+            phys.step(DT);
+        }
+        let lifted = phys.bodies[body].translation().y - parked_y;
+        assert!((lifted - 0.2).abs() < 0.05, "car rides on the slab, lifted {:.3} m", lifted);
+    }
+
+    #[test]
+    fn vehicle_layout_mirrors_the_registered_wheels_and_config() {
+        let mut phys = PhysicsWorld::new();
+        phys.spawn_vehicle_for_player("car".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        phys.spawn_vehicle_for_player("tank".into(), [10.0, 1.0, 0.0], 0.0, "tank").unwrap();
+        let car = phys.vehicle_layout("car").unwrap();
+        let tank = phys.vehicle_layout("tank").unwrap();
+        assert!(phys.vehicle_layout("nobody").is_none());
+
+        assert_eq!((car.vehicle.as_str(), tank.vehicle.as_str()), (DEFAULT_VEHICLE, "tank"));
+        // TANK still shares the GT86 chassis and wheel spec; the layout has to
+        // come from its own config, not from a copy of the default
+        assert_eq!(tank.chassis_half_extents, TANK.chassis_half_extents);
+        assert_eq!(tank.chassis_com_offset, TANK.chassis_com_offset);
+        assert_eq!(car.chassis_half_extents, GT86.chassis_half_extents);
+
+        for (id, layout) in [("car", &car), ("tank", &tank)] {
+            let wheels = &phys.wheels[&phys.vehicles[id].body];
+            assert_eq!(layout.wheels.len(), wheels.len());
+            for (l, w) in layout.wheels.iter().zip(wheels) {
+                assert_eq!(l.id, w.debug_id);
+                assert_eq!(l.offset, [w.offset.x, w.offset.y, w.offset.z]);
+                assert_eq!((l.radius, l.rest_length, l.max_length), (w.radius, w.rest_length, w.max_length));
+                assert_eq!((l.steer, l.drive), (w.steer, w.drive));
+            }
+        }
+    }
+
+    /// Lateral (x) drift after `ticks` of straight full throttle on a road
+    /// with the given cross-slope.
+    fn camber_drift(camber_deg: f32, ticks: usize) -> f32 {
+        let mut phys = PhysicsWorld::new();
+        phys.road_camber_deg = camber_deg;
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        for _ in 0..ticks {
+            phys.apply_player_input("p", 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+            phys.clear_debug_overlay();
+            phys.step(DT);
+        }
+        phys.bodies[body].translation().x
+    }
+
+    #[test]
+    fn road_camber_drifts_the_car_toward_the_low_side() {
+        let flat = camber_drift(0.0, 180);
+        let right = camber_drift(2.0, 180);
+        let left = camber_drift(-2.0, 180);
+        assert!(flat.abs() < 0.1, "no camber, no drift: {:.3} m", flat);
+        // Facing +z the vehicle's right is -x
+        assert!(right < -0.5, "falls away to the right, drifts right: {:.3} m", right);
+        assert!(left > 0.5, "mirror image: {:.3} m", left);
+        assert!((right + left).abs() < 0.1 * left, "symmetric: {:.3} vs {:.3}", right, left);
+
+        // The drift builds as the car picks up speed
+        let early = camber_drift(2.0, 90);
+        assert!(early.abs() * 2.0 < right.abs(), "{:.3} m after 1.5 s, {:.3} m after 3 s", early, right);
+    }
+
+    /// Roll across a row of low bumps at about `speed`: the tick of every
+    /// kerb_hit per wheel, and the mean speed actually held.
+    fn kerb_hits(speed: f32) -> (HashMap<&'static str, Vec<usize>>, f32) {
+        let mut phys = PhysicsWorld::new();
+        for i in 0..6 {
+            phys.add_static_obstacle([0.0, FLAT_GROUND_TOP + 0.02, 40.0 + i as f32 * 8.0], [3.0, 0.02, 0.1]);
+        }
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        for _ in 0..60 {
+            phys.step(DT);
+        }
+        phys.bodies[body].set_linvel(vector![0.0, 0.0, speed], true);
+
+        let mut hits: HashMap<&'static str, Vec<usize>> = HashMap::new();
+        let ticks = (95.0 / speed / DT) as usize;
+        let mut distance = 0.0;
+        for tick in 0..ticks {
+            // Hold the speed so the cadence is set by the bump spacing alone
+            let v = phys.bodies[body].linvel().z;
+            phys.apply_player_input("p", ((speed - v) * 0.5).clamp(0.0, 1.0), 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+            phys.clear_debug_overlay();
+            phys.step(DT);
+            distance += phys.bodies[body].linvel().z * DT;
+            for ev in &phys.haptic_events {
+                assert!(ev.intensity > 0.0 && ev.intensity <= 1.0);
+                if let (HapticKind::KerbHit, Some(wheel)) = (ev.kind, ev.wheel) {
+                    hits.entry(wheel).or_default().push(tick);
+                }
+            }
+        }
+        (hits, distance / (ticks as f32 * DT))
+    }
+
+    #[test]
+    fn bumps_fire_kerb_hits_at_the_spacing_cadence() {
+        for target in [8.0, 12.0] {
+            let (hits, speed) = kerb_hits(target);
+            let spacing = 8.0 / speed / DT; // ticks between bumps
+            let wheelbase = 3.0 / speed / DT; // front → rear axle (offsets at ±1.5 m)
+            // Up and down edges of one bump may both fire: one strike per cluster
+            let strikes = |wheel: &str| -> Vec<usize> {
+                let ticks = &hits[wheel];
+                let mut strikes = vec![ticks[0]];
+                for pair in ticks.windows(2) {
+                    if (pair[1] - pair[0]) as f32 > spacing / 2.0 {
+                        strikes.push(pair[1]);
+                    }
+                }
+                strikes
+            };
+            for wheel in ["FL", "FR", "RL", "RR"] {
+                let strikes = strikes(wheel);
+                assert_eq!(strikes.len(), 6, "{} at {:.1} m/s: one strike per bump, got {:?}", wheel, speed, hits[wheel]);
+                for pair in strikes.windows(2) {
+                    let gap = (pair[1] - pair[0]) as f32;
+                    assert!((gap - spacing).abs() <= 2.0, "{} at {:.1} m/s: {} ticks apart, expected {:.1}", wheel, speed, gap, spacing);
+                }
+            }
+            let lag = (strikes("RL")[0] - strikes("FL")[0]) as f32;
+            assert!((lag - wheelbase).abs() <= 2.0, "rear follows the front by {} ticks, expected {:.1}", lag, wheelbase);
+        }
+    }
+
+    #[test]
+    fn tethered_car_bounces_at_the_spring_frequency() {
+        let mut phys = PhysicsWorld::new();
+        let car = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        for _ in 0..60 {
+            phys.step(DT);
+        }
+        let y = phys.bodies[car].translation().y;
+        let dock = phys.bodies.insert(RigidBodyBuilder::fixed().translation(vector![0.0, y, -6.0]).build());
+
+        // Stretched 1 m past its 5 m rest length, pulling the car backwards
+        let m = phys.bodies[car].mass();
+        let f = 0.5; // Hz
+        let k = m * (2.0 * std::f32::consts::PI * f).powi(2);
+        let joint = phys.create_spring_joint(dock, car, Point::origin(), Point::origin(), 5.0, k, 0.0);
+
+        // Time between successive turning points (rest of the car's z velocity) = half a period
+        let mut turns = Vec::new();
+        let mut last_v = 0.0_f32;
+        for tick in 0..600 {
+            phys.step(DT);
+            let v = phys.bodies[car].linvel().z;
+            if tick > 0 && v.signum() != last_v.signum() {
+                turns.push(tick);
+            }
+            last_v = v;
+        }
+        let half_period = 1.0 / (2.0 * f * DT);
+        assert!(turns.len() >= 8, "still swinging after 10 s: {:?}", turns);
+        for pair in turns.windows(2) {
+            let gap = (pair[1] - pair[0]) as f32;
+            assert!((gap - half_period).abs() <= 3.0, "half period {} ticks, expected {:.0}: {:?}", gap, half_period, turns);
+        }
+
+        assert!(phys.destroy_joint(joint));
+        assert!(!phys.destroy_joint(joint));
+        assert!(phys.spring_joints.is_empty());
+    }
+
+    #[test]
+    fn a_car_sunk_into_the_ground_climbs_back_out() {
+        let mut phys = PhysicsWorld::new();
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        for _ in 0..120 {
+            phys.step(DT);
+        }
+        let rest_y = phys.bodies[body].translation().y;
+
+        // Chassis box 0.3 m into the ground: every hub closer to it than one radius
+        let [_, hy, _] = GT86.chassis_half_extents;
+        let sunk_y = FLAT_GROUND_TOP - 0.3 + hy - GT86.chassis_com_offset[1];
+        phys.bodies[body].set_translation(vector![0.0, sunk_y, 0.0], true);
+        phys.clear_debug_overlay();
+        phys.step(DT);
+        let wheels = phys.debug_snapshot().wheels;
+        assert_eq!(wheels.len(), 4);
+        assert!(wheels.iter().all(|w| w.embedded && w.grounded), "every wheel reports embedded");
+
+        let respawns = phys.vehicles["p"].last_respawn_tick;
+        let mut peak_speed: f32 = 0.0;
+        let mut tick = |phys: &mut PhysicsWorld| {
+            phys.clear_debug_overlay();
+            phys.step(DT);
+            peak_speed = peak_speed.max(phys.bodies[body].linvel().norm());
+        };
+        // Out of the ground well within a second...
+        for _ in 0..60 {
+            tick(&mut phys);
+        }
+        assert!(phys.debug_snapshot().wheels.iter().all(|w| !w.embedded));
+        assert!(phys.bodies[body].translation().y > rest_y - 0.15);
+        // ...and resting on the springs once the rebound has died down
+        for _ in 0..30 {
+            tick(&mut phys);
+        }
+        let rb = &phys.bodies[body];
+        assert!((rb.translation().y - rest_y).abs() < 0.02, "back at ride height: {:.3} vs {:.3}", rb.translation().y, rest_y);
+        assert!(rb.linvel().norm() < 0.2 && rb.angvel().norm() < 0.2, "and at rest");
+        assert!(peak_speed < 3.0, "pushed out, not launched: {:.2} m/s", peak_speed);
+        assert_eq!(phys.vehicles["p"].last_respawn_tick, respawns, "no safety teleport");
+    }
+
+    /// Throw the car out of bounds and step once; true if it was teleported back.
+    fn out_of_bounds_teleports(phys: &mut PhysicsWorld, body: RigidBodyHandle) -> bool {
+        phys.bodies[body].set_translation(vector![2_000.0, 2.0, 0.0], true);
+        phys.step(DT);
+        phys.bodies[body].translation().x.abs() < 1.0
+    }
+
+    #[test]
+    fn safety_teleport_cooldown_is_five_simulated_seconds() {
+        let mut phys = PhysicsWorld::new();
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        assert_eq!(phys.vehicles["p"].last_respawn_tick, None);
+
+        // A brand-new car (tick 1, nothing to cool down from) is teleported
+        assert!(out_of_bounds_teleports(&mut phys, body));
+        assert_eq!(phys.vehicles["p"].last_respawn_tick, Some(1));
+
+        // Again straight away: pinned at the boundary instead
+        assert!(!out_of_bounds_teleports(&mut phys, body));
+        assert_eq!(phys.vehicles["p"].blocked_respawns, 1);
+
+        // At half speed 5 s of simulated time is 600 ticks
+        phys.set_timestep_scale(0.5);
+        while phys.tick < 1 + 300 {
+            phys.step(DT);
+        }
+        assert!(!out_of_bounds_teleports(&mut phys, body), "only 2.5 simulated seconds");
+        while phys.tick < 1 + 600 {
+            phys.step(DT);
+        }
+        assert!(out_of_bounds_teleports(&mut phys, body));
+        assert_eq!(phys.vehicles["p"].blocked_respawns, 0);
+    }
+
+    #[test]
+    fn a_client_reset_does_not_hold_off_the_safety_teleport() {
+        let mut phys = PhysicsWorld::new();
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        for _ in 0..400 {
+            phys.step(DT);
+        }
+
+        phys.reset_vehicle("p", [10.0, 0.0, 10.0], 0.0).unwrap();
+        assert_eq!(phys.vehicles["p"].last_respawn_tick, None);
+        phys.step(DT);
+        assert!(out_of_bounds_teleports(&mut phys, body), "fell off the map right after a respawn");
+    }
+
+    /// Front share of the applied brake force on the first braking tick
+    /// from 15 m/s, after nudging the brake bias by `bias_delta`.
+    fn front_brake_share(bias_delta: f32) -> (f32, f32) {
+        let mut phys = PhysicsWorld::new();
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        for _ in 0..60 {
+            phys.step(DT);
+        }
+        phys.bodies[body].set_linvel(vector![0.0, 0.0, 15.0], true);
+        for _ in 0..10 {
+            phys.step(DT);
+        }
+        let bias = phys.adjust_brake_bias("p", bias_delta).unwrap();
+
+        phys.apply_player_input("p", 0.0, 0.0, 0.4, 0.0, 0.0, 0.0, 0.0);
+        phys.clear_debug_overlay();
+        phys.step(DT);
+        let [front, rear] = phys.debug_snapshot().brake_force_axle.unwrap();
+        assert!(front + rear > 0.0);
+        (bias, front / (front + rear))
+    }
+
+    #[test]
+    fn brake_bias_moves_the_axle_brake_split() {
+        let (bias, share) = front_brake_share(0.0);
+        assert_eq!(bias, GT86.brake_bias);
+        assert!((share - bias).abs() < 0.01, "front share {:.3} at bias {:.2}", share, bias);
+
+        let (shifted_bias, shifted) = front_brake_share(-0.1);
+        assert!((shifted_bias - (GT86.brake_bias - 0.1)).abs() < 1e-6);
+        assert!((shifted - share + 0.1).abs() < 0.01, "front share {:.3} -> {:.3}", share, shifted);
+
+        // Clamped to the config's range; non-finite deltas are ignored
+        let (clamped, _) = front_brake_share(1.0);
+        assert_eq!(clamped, GT86.brake_bias_range[1]);
+        let (unchanged, _) = front_brake_share(f32::NAN);
+        assert_eq!(unchanged, GT86.brake_bias);
+    }
+
+    #[test]
+    fn slow_motion_adds_substeps() {
+        let mut phys = PhysicsWorld::new();
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        for _ in 0..120 {
+            phys.step(DT);
+        }
+
+        let substeps = |phys: &mut PhysicsWorld, scale: f32| {
+            phys.set_timestep_scale(scale);
+            phys.substep_count(phys.scaled_dt(DT))
+        };
+        assert_eq!(substeps(&mut phys, 2.0), 2, "fast-forward splits for spring stability");
+        assert_eq!(substeps(&mut phys, 1.0), 1);
+        assert_eq!(substeps(&mut phys, 0.5), 1);
+        assert_eq!(substeps(&mut phys, 0.25), 2);
+        assert_eq!(substeps(&mut phys, 0.1), 5);
+
+        // A parked car stays parked on the finest substeps
+        let rest_y = phys.bodies[body].translation().y;
+        for _ in 0..120 {
+            phys.step(DT);
+        }
+        assert!((phys.bodies[body].translation().y - rest_y).abs() < 0.01);
+        assert!(phys.bodies[body].linvel().norm() < 0.05);
+    }
+
+    /// Roll at 10 m/s into a 0.3 m curb whose face is at z = 10; returns the chassis
+    /// (min speed, max z) over the next two seconds.
+    fn into_a_curb(wheel_colliders: bool) -> (f32, f32) {
+        let mut phys = PhysicsWorld::new();
+        phys.add_static_obstacle([0.0, FLAT_GROUND_TOP + 0.15, 15.0], [4.0, 0.15, 5.0]);
+        let config = VehicleConfig { wheel_colliders, ..vehicle_config_by_name(DEFAULT_VEHICLE).unwrap() };
+        let body = phys.spawn_vehicle_with_config("p".into(), [0.0, 1.0, 0.0], 0.0, config).unwrap();
+        for _ in 0..60 {
+            phys.step(DT);
+        }
+        phys.bodies[body].set_linvel(vector![0.0, 0.0, 10.0], true);
+        let (mut min_speed, mut max_z) = (f32::MAX, 0.0f32);
+        for _ in 0..120 {
+            phys.step(DT);
+            let z = phys.bodies[body].translation().z;
+            min_speed = min_speed.min(phys.bodies[body].linvel().z);
+            max_z = max_z.max(z);
+        }
+        (min_speed, max_z)
+    }
+
+    #[test]
+    fn wheel_colliders_stop_the_car_at_a_curb() {
+        // Ray-only wheels find the curb top once past the face and climb on
+        let (_, max_z) = into_a_curb(false);
+        assert!(max_z > 15.0, "ray-only car got to z = {max_z}");
+
+        // Hard wheels meet the face: the front tyres (1.5 m ahead) stop at it
+        let (min_speed, max_z) = into_a_curb(true);
+        assert!(min_speed < 1.0, "still doing {min_speed} m/s");
+        assert!(max_z + 1.5 < 10.0, "front axle got to z = {}", max_z + 1.5);
+    }
+
+    #[test]
+    fn wheel_colliders_leave_flat_ground_to_the_rays() {
+        let rest_height = |wheel_colliders: bool| {
+            let mut phys = PhysicsWorld::new();
+            let config = VehicleConfig { wheel_colliders, ..vehicle_config_by_name(DEFAULT_VEHICLE).unwrap() };
+            let body = phys.spawn_vehicle_with_config("p".into(), [0.0, 1.0, 0.0], 0.0, config).unwrap();
+            for _ in 0..120 {
+                phys.step(DT);
+            }
+            assert!(phys.get_contact_forces("p").is_empty());
+            phys.bodies[body].translation().y
+        };
+        assert!((rest_height(true) - rest_height(false)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn shots_hit_where_the_target_was_at_the_seen_tick() {
+        let mut phys = PhysicsWorld::new();
+        phys.spawn_vehicle_for_player("shooter".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        let target = phys.spawn_vehicle_for_player("target".into(), [0.0, 1.0, 20.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        for _ in 0..60 {
+            phys.step(DT);
+        }
+        let seen = phys.tick;
+        let now = phys.fire_projectile("shooter", seen, |_| true).expect("parked target in the line of fire");
+        assert_eq!(now.target, "target");
+
+        // The target drives out of the line of fire
+        for _ in 0..30 {
+            phys.bodies[target].set_linvel(vector![15.0, 0.0, 0.0], true);
+            phys.step(DT);
+        }
+        assert!(phys.bodies[target].translation().x > 5.0);
+        assert_eq!(phys.fire_projectile("shooter", phys.tick, |_| true), None);
+
+        // Rewound to what the shooter saw, it is still hit, at the same spot
+        let rewound = phys.fire_projectile("shooter", seen, |_| true).expect("rewound hit");
+        assert_eq!(rewound.target, "target");
+        assert!((rewound.distance - now.distance).abs() < 1e-3);
+        assert!(phys.fire_projectile("shooter", seen, |id| id != "target").is_none());
+
+        // Older than the history: the present pose, a miss
+        for _ in 0..INPUT_HISTORY_CAPACITY {
+            phys.step(DT);
+        }
+        assert_eq!(phys.get_position_at_tick("target", seen), None);
+        assert_eq!(phys.fire_projectile("shooter", seen, |_| true), None);
+    }
 
+    #[test]
+    fn a_target_without_a_body_does_not_void_the_shot() {
+        let mut phys = PhysicsWorld::new();
+        phys.spawn_vehicle_for_player("shooter".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        phys.spawn_vehicle_for_player("target".into(), [0.0, 1.0, 20.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        phys.spawn_vehicle_for_player("stale".into(), [10.0, 1.0, 20.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        for _ in 0..10 {
+            phys.step(DT);
+        }
+        phys.vehicles.get_mut("stale").unwrap().body = RigidBodyHandle::invalid();
 
-            let (fl, fr) = solve_steering(&cfg, &body_ro.position().rotation, vehicle.steer_angle);
-            vehicle.steering.fl = fl;
-            vehicle.steering.fr = fr;
-            
-            for wheel in wheels.iter_mut() {
-                let normal_force = 0.0;
-                let mut grounded = false;
-                if let Some(contact) = build_suspension_contact(
-                    wheel,
-                    vehicle,
-                    &vehicle.steering,
-                    body_ro,
-                    &self.query_pipeline,
-                    &self.bodies,
-                    &self.colliders,
-                    handle,
-                    fz_ref,
-                    dt as f32,
-                ) {
-                    let id = WheelId::from_debug(&wheel.debug_id);
+        // Tick 0 has no history: every candidate falls back to its body
+        assert_eq!(phys.get_position_at_tick("stale", 0), None);
+        let hit = phys.fire_projectile("shooter", 0, |_| true).expect("the target is still hit");
+        assert_eq!(hit.target, "target");
+    }
 
-                    axle_compression.insert(id, contact.compression);
-                    axle_normal_force.insert(id, contact.normal_force);
-                    suspension_contacts.push((id, contact.clone()));
+    #[test]
+    fn pose_history_interpolates_between_recorded_ticks() {
+        let mut phys = PhysicsWorld::new();
+        phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        phys.step(DT);
+        let history = phys.input_history.get_mut("p").unwrap();
+        history.clear();
+        history.push_back(InputHistoryFrame { tick: 10, position: [0.0, 1.0, 0.0], rotation: [0.0, 0.0, 0.0, 1.0] });
+        history.push_back(InputHistoryFrame { tick: 14, position: [4.0, 1.0, 0.0], rotation: [0.0, 0.0, 0.0, 1.0] });
 
-                    let forward = if contact.forward.magnitude_squared() < 1e-6 {
-                        body_ro.position().rotation * vector![0.0, 0.0, 1.0]
-                    } else { contact.forward };
+        let (position, rotation) = phys.get_position_at_tick("p", 11).unwrap();
+        assert!((position[0] - 1.0).abs() < 1e-5);
+        assert_eq!(rotation, [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(phys.get_position_at_tick("p", 14).unwrap().0, [4.0, 1.0, 0.0]);
+        assert_eq!(phys.get_position_at_tick("p", 9), None);
+        assert_eq!(phys.get_position_at_tick("p", 15), None);
+    }
 
-                    let v = contact.point_vel;
+    /// Metres to stop from 20 m/s on full brake, with `payload_kg` 1 m behind centre.
+    fn braking_distance(payload_kg: f32) -> f32 {
+        let mut phys = PhysicsWorld::new();
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        phys.set_payload("p", payload_kg, [0.0, 0.0, -1.0]).unwrap();
+        for _ in 0..60 {
+            phys.step(DT);
+        }
+        phys.bodies[body].set_linvel(vector![0.0, 0.0, 20.0], true);
+        let start = phys.bodies[body].translation().z;
+        phys.apply_player_input("p", 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0);
+        for _ in 0..600 {
+            phys.step(DT);
+            if phys.bodies[body].linvel().z < 0.1 {
+                break;
+            }
+        }
+        phys.bodies[body].translation().z - start
+    }
 
-                    // suspension axis (world-space)
-                    // ground normal (for now flat; later use contact.ground_normal)
-                    let n = vector![0.0, 1.0, 0.0];
+    #[test]
+    fn a_rear_payload_loads_the_rear_corners_and_lengthens_braking() {
+        use crate::setup_report::settle_and_sample;
+        let stock = settle_and_sample("p", GT86, GT86.brake_bias, Payload::default()).unwrap();
+        let loaded = settle_and_sample("p", GT86, GT86.brake_bias, Payload { mass: 300.0, offset: [0.0, 0.0, -1.0] }).unwrap();
 
-                    // planar/tangent velocity at contact
-                    let v_n = v.dot(&n);
-                    let v_t = v - n * v_n;
+        // Lever rule: 1 m behind centre on a 3 m wheelbase is 5/6 on the rear axle
+        let axle = |loads: [f32; 4]| (loads[0] + loads[1], loads[2] + loads[3]);
+        let (front, rear) = axle(stock.corner_loads);
+        let (loaded_front, loaded_rear) = axle(loaded.corner_loads);
+        let weight = 300.0 * 9.81;
+        assert!((loaded_rear - rear - weight * 5.0 / 6.0).abs() < 0.05 * weight, "rear +{:.0} N", loaded_rear - rear);
+        assert!((loaded_front - front - weight / 6.0).abs() < 0.05 * weight, "front +{:.0} N", loaded_front - front);
+        assert!((loaded.corner_loads[2] - loaded.corner_loads[3]).abs() < 0.01 * loaded_rear, "centred left/right");
 
-                    // safe normalize
-                    let speed_t = v_t.norm();
-                    let brake_dir = if speed_t > 1e-4 {
-                        -v_t / speed_t   // oppose motion
-                    } else {
-                        // if nearly stopped, fall back to opposing v_long in wheel frame
-                        let s = if contact.v_long >= 0.0 { -1.0 } else { 1.0 };
-                        forward * s
-                    };
+        let (empty, heavy) = (braking_distance(0.0), braking_distance(300.0));
+        assert!(heavy > empty * 1.05, "stopping distance {:.1} m loaded vs {:.1} m", heavy, empty);
+    }
 
-                    let yaw_rate = body_ro.angvel().y as f32; // assuming Y-up
-                    
-                    let com_world: Point<Real> = body_ro.position() * body_ro.center_of_mass();
-                    let relative_com = contact.apply_point - com_world;
-
-                    grounded = contact.grounded;
-
-                    contacts.push(ContactPatch {
-                        wheel: id,
-                        grounded,
-                        hit_point: p3(contact.hit_point),
-                        apply_point: p3(contact.apply_point),
-                        forward: v3(forward),
-                        side: v3(contact.side),
-                        v_long: contact.v_long,
-                        v_lat: contact.v_lat,
-                        normal_force:contact.normal_force,
-                        mu_lat: contact.mu_lat,
-                        mu_long: contact.mu_long,
-                        roll_factor: contact.roll_factor,
-                        drive: wheel.drive,
-                        brake: vehicle.brake,
-                        steer_angle: vehicle.steer_angle,
-                        compression_ratio: contact.compression_ratio,
-                        vel_world: v3(contact.point_vel),
-                        brake_dir: v3(brake_dir),
-                        speed_planar: speed_t as f32,
-                        yaw_rate,
-                        relative_com: v3(relative_com),
-                        tire_state: wheel.tire_state,
-                    });
+    #[test]
+    fn the_overlay_reports_each_wheels_surface() {
+        use crate::surface::SurfaceKind;
+        let mut phys = PhysicsWorld::new();
+        // Thin gravel pad under the front-left wheel (x = -0.8, z = 1.5 facing +z)
+        let pad = phys.add_static_obstacle([-0.8, FLAT_GROUND_TOP + 0.01, 1.5], [0.3, 0.01, 0.3]);
+        assert!(phys.set_surface_material(pad, "gravel"));
+        phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        for _ in 0..60 {
+            phys.clear_debug_overlay();
+            phys.step(DT);
+        }
 
-                    // ===============================================================================
-                    // debug slip rays
-                    // ===============================================================================
-                    if contact.forward.magnitude() > 1e-4 {
-                        let slip_mag = contact.v_lat.abs();
-                        if slip_mag > 0.01 {
-                            let slip_dir = if contact.v_lat >= 0.0 { contact.side } else { -contact.side };
-                            let slip_len = (slip_mag * 0.25).clamp(0.02, 0.6);
-                            let color = match contact.wheel_id.as_str() {
-                                "FL" | "RL" => [0.2, 0.6, 1.0],
-                                "FR" | "RR" => [1.0, 0.4, 0.2],
-                                _ => [1.0, 1.0, 1.0],
-                            };
-                            let slip_origin = contact.hit_point + contact.ground_normal * wheel.radius * 0.25;
-                            let slip_angle = 0.0;
-                            self.debug_overlay.slip_vectors.push(DebugSlipRay {
-                                origin: slip_origin.into(),
-                                direction: slip_dir.into(),
-                                slip_angle: slip_angle,
-                                magnitude: slip_len,
-                                color,
-                            });
-                        }
-                    }
+        let overlay = phys.debug_snapshot();
+        let surface = |id: &str| overlay.wheels.iter().find(|w| w.id == id).and_then(|w| w.surface.clone()).expect(id);
+        let gravel = surface("FL");
+        assert_eq!(gravel.material, "gravel");
+        assert_eq!(gravel.source, "prop");
+        assert_eq!(gravel.kind, SurfaceKind::Static);
+        assert!(gravel.normal[1] > 0.99);
+        for id in ["FR", "RL", "RR"] {
+            let ground = surface(id);
+            assert_ne!(ground.material, "gravel", "{}", id);
+            assert_ne!(ground.source, "prop", "{}", id);
+        }
+    }
 
-                    // ==================================================================
-                    //  Shared Debug Params
-                    // ==================================================================
-                    let origin = pos * (wheel.offset + vector![0.0, wheel.radius + 0.02, 0.0]);
-                    let dir = vector![0.0, -1.0, 0.0];
-                    let ground_n = vector![0.0, 1.0, 0.0];
-                    let max_dist = wheel.rest_length + wheel.max_length + wheel.radius;
-                    let wheel_center = contact.hit_point + contact.ground_normal * wheel.radius;
-                    
-                    // ==========================================================
-                    //  DEBUG: suspension ray (ALWAYS push)
-                    // ==========================================================
-                    self.debug_overlay.suspension_rays.push(DebugRay {
-                        origin: origin.into(),
-                        direction: dir.into(),
-                        length: max_dist,
-                        hit: Some(p3(contact.hit_point)),
-                        color: if contact.grounded { [0.0, 1.0, 0.0] } else { [1.0, 0.0, 0.0] },
-                    });
+    #[test]
+    fn a_slow_room_steps_every_nth_tick_and_keeps_pace() {
+        let mut phys = PhysicsWorld::new();
+        let fast = phys.spawn_vehicle_for_player("fast".into(), [-10.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        let slow = phys.spawn_vehicle_for_player("slow".into(), [10.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        assert!(phys.set_tick_divisor("slow", 3));
+        assert!(!phys.set_tick_divisor("slow", 3), "unchanged");
 
-                    // ----------------------------------------------------------
-                    // DEBUG: wheel numeric (ALWAYS push)
-                    // ----------------------------------------------------------
-                    self.debug_overlay.wheels.push(DebugWheel {
-                        id: wheel.debug_id.clone(),
-                        center: wheel_center.into(),
-                        radius: wheel.radius as f32,
-                        grounded: contact.grounded,
-                        compression: contact.compression,
-                        normal_force: contact.normal_force,
-                        steer: vehicle.steer,
-                        steering: wheel.steer,
-                        drive: wheel.drive,
-                    });
+        // Both settle to the same ride height and stay parked
+        for _ in 0..180 {
+            phys.step(DT);
+        }
+        for _ in 0..40 {
+            for _ in 0..3 {
+                phys.step(DT);
+            }
+            let (fast_y, slow_y) = (phys.bodies[fast].translation().y, phys.bodies[slow].translation().y);
+            assert!((fast_y - slow_y).abs() < 0.01, "tick {}: ride height {fast_y} vs {slow_y}", phys.tick);
+            assert!(phys.bodies[slow].linvel().norm() < 0.15);
+        }
 
-                    // ----------------------------------------------------------
-                    // DEBUG: load bar (optional but super helpful)
-                    // ----------------------------------------------------------
-                    let norm = (contact.normal_force / 12000.0).clamp(0.0, 1.0);
-                    let bar_len = norm.sqrt() * 1.25;
+        // The slow car only moves on every 3rd tick, by three ticks' worth
+        for body in [fast, slow] {
+            phys.bodies[body].set_linvel(vector![0.0, 0.0, 10.0], true);
+        }
+        let (fast_z, slow_z) = (phys.bodies[fast].translation().z, phys.bodies[slow].translation().z);
+        assert_eq!(phys.tick % 3, 0);
+        for n in 1..=60 {
+            let before = phys.bodies[slow].translation().z;
+            phys.step(DT);
+            let moved = phys.bodies[slow].translation().z - before;
+            if n % 3 == 0 {
+                assert!(moved > 0.4, "tick {n}: moved {moved}");
+            } else {
+                assert_eq!(moved, 0.0, "tick {n}");
+            }
+        }
+        let fast_dz = phys.bodies[fast].translation().z - fast_z;
+        let slow_dz = phys.bodies[slow].translation().z - slow_z;
+        assert!(fast_dz > 9.0, "{fast_dz}");
+        assert!((fast_dz - slow_dz).abs() < 0.2, "fast {fast_dz} m vs slow {slow_dz} m");
 
-                    let bar_origin = wheel_center + ground_n * 0.03;
-                    let color = match wheel.debug_id.as_str() {
-                        "FL" | "RL" => [0.2, 0.6, 1.0],
-                        "FR" | "RR" => [1.0, 0.4, 0.2],
-                        _ => [1.0, 1.0, 1.0],
-                    };
+        // A respawn keeps the room's rate
+        phys.respawn_vehicle_for_player("slow", [10.0, 1.0, 0.0], 0.0).unwrap();
+        assert_eq!(phys.vehicles["slow"].tick_divisor, 3);
+    }
 
-                    self.debug_overlay.load_bars.push(DebugRay {
-                        origin: bar_origin.into(),
-                        direction: ground_n.into(),
-                        length: bar_len,
-                        hit: Some((bar_origin + ground_n * bar_len).into()),
-                        color,
-                    });
+    #[test]
+    fn a_jump_yaws_within_the_air_control_budget_and_lands_without_bouncing_off() {
+        // Drop from 3 m above ride height, steering right the whole way down
+        let jump = |steer: f32| {
+            let mut phys = PhysicsWorld::new();
+            let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+            for _ in 0..240 {
+                phys.step(DT);
+            }
+            let rest_y = phys.bodies[body].translation().y;
+            phys.bodies[body].set_translation(vector![0.0, rest_y + 3.0, 0.0], true);
+            phys.apply_player_input("p", 0.0, steer, 0.0, 0.0, 0.0, 0.0, 0.0);
 
-                } // end contact creation
-                
-            } // end wheel iter()
+            let mut max_yaw_rate: f32 = 0.0;
+            let mut air_time = 0.0;
+            let mut took_off = false;
+            while !took_off || phys.vehicles["p"].air.airborne {
+                phys.step(DT);
+                air_time += DT;
+                took_off |= phys.vehicles["p"].air.airborne;
+                max_yaw_rate = max_yaw_rate.max(phys.bodies[body].angvel().y);
+                assert!(air_time < 2.0, "never landed");
+            }
+            let inertia_y = 1.0 / phys.bodies[body].mass_properties().effective_world_inv_inertia_sqrt.m22.powi(2);
 
-            // --------------------------------------------------
-            // PHASE 2 — REDISTRIBUTE (ARB)
-            // --------------------------------------------------
-            apply_arb_load_transfer(
-                WheelId::FL, WheelId::FR,
-                &mut axle_normal_force,
-                &axle_compression,
-                vehicle.config.arb_front,
-                fz_ref,
-            );
+            // Landed: the car stays down and settles back to ride height
+            phys.apply_player_input("p", 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+            for _ in 0..120 {
+                phys.step(DT);
+                assert!(!phys.vehicles["p"].air.airborne, "bounced back into the air");
+            }
+            let settled = (phys.bodies[body].translation().y - rest_y).abs();
+            (max_yaw_rate, air_time, inertia_y, settled)
+        };
 
-            apply_arb_load_transfer(
-                WheelId::RL, WheelId::RR,
-                &mut axle_normal_force,
-                &axle_compression,
-                vehicle.config.arb_rear,
-                fz_ref,
-            );
+        let (yaw_rate, air_time, inertia_y, settled) = jump(1.0);
+        let budget = GT86.air_control_torque * air_time / inertia_y; // capped torque, undamped, whole flight
+        assert!(yaw_rate > 0.5 * budget, "yaw rate {yaw_rate} rad/s, budget {budget}");
+        assert!(yaw_rate <= budget * 1.05, "yaw rate {yaw_rate} rad/s over budget {budget}");
+        assert!(settled < 0.03, "{settled} m off ride height");
 
-            // --------------------------------------------------
-            // PHASE 3A — SUSPENSION IMPULSES (STORE ONLY)
-            // --------------------------------------------------
-            for (wheel_id, contact) in suspension_contacts.iter() {
+        let (still, ..) = jump(0.0);
+        assert!(still.abs() < 0.01, "no input, no yaw: {still}");
+    }
 
-                let axel_normal = axle_normal_force.get(wheel_id).copied().unwrap_or(contact.normal_force);
-                let max_normal_impulse = fz_ref * 1.5 * dt; // ≈ 1.5g per wheel
-                let normal_impulse_mag = (axel_normal * dt as f32).clamp(0.0, max_normal_impulse as f32);
+    /// Largest sideways distance (m, ground plane) of a trail's points from
+    /// the chord between its ends.
+    fn trail_bow(trail: &DebugTrail) -> f32 {
+        let (a, b) = (trail.points[0].position, trail.points[trail.points.len() - 1].position);
+        let (cx, cz) = (b[0] - a[0], b[2] - a[2]);
+        let chord = cx.hypot(cz);
+        trail
+            .points
+            .iter()
+            .map(|p| ((p.position[0] - a[0]) * cz - (p.position[2] - a[2]) * cx).abs() / chord)
+            .fold(0.0, f32::max)
+    }
 
-                impulses.at_points.push((
-                    contact.ground_normal * normal_impulse_mag as Real,
-                    contact.apply_point,
-                ));
+    #[test]
+    fn a_drift_bows_the_rear_trails_more_than_the_front() {
+        let mut phys = PhysicsWorld::new();
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        for _ in 0..60 {
+            phys.step(DT);
+        }
+        // Flick the tail out at speed, then hold power and lock
+        phys.bodies[body].set_linvel(vector![0.0, 0.0, 12.0], true);
+        phys.bodies[body].set_angvel(vector![0.0, 2.0, 0.0], true);
+        for _ in 0..phys.debug_trail_len {
+            phys.apply_player_input("p", 1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+            phys.clear_debug_overlay();
+            phys.step(DT);
+        }
+
+        let overlay = phys.debug_snapshot();
+        let trail = |id: &str| overlay.trails.iter().find(|t| t.wheel == id).unwrap();
+        for id in ["FL", "FR", "RL", "RR"] {
+            let ages: Vec<u64> = trail(id).points.iter().map(|p| p.age).collect();
+            assert_eq!(ages, (0..phys.debug_trail_len as u64).rev().collect::<Vec<_>>(), "{}: oldest first, one point per tick", id);
+        }
+        let front = (trail_bow(trail("FL")) + trail_bow(trail("FR"))) / 2.0;
+        let rear = (trail_bow(trail("RL")) + trail_bow(trail("RR"))) / 2.0;
+        assert!(rear > 0.05 && rear > 1.2 * front, "rear trails bow {:.3} m, front {:.3} m", rear, front);
+
+        assert!(overlay.filtered(DEBUG_ALL & !DEBUG_TRAILS).trails.is_empty());
+        assert_eq!(overlay.filtered(DEBUG_TRAILS).trails.len(), 4);
+    }
+
+    /// Damper force (N) of every wheel in the last solve: from the suspension
+    /// extension rate, and from the old input (contact point velocity along
+    /// world up), both without the landing settle window.
+    fn damper_forces(phys: &PhysicsWorld, body: RigidBodyHandle) -> Vec<(f32, f32)> {
+        use crate::suspension_contact::{DAMPER_LIMIT, compute_suspension_force};
+        let wheel = &phys.wheels[&body][0];
+        let (k, c) = (wheel.stiffness, wheel.damping);
+        let damper = |compression: f32, vel: f32| compute_suspension_force(compression, vel, k, c, DAMPER_LIMIT) - k * compression;
+        phys.solve_buffers
+            .suspension_contacts
+            .iter()
+            .map(|(_, s)| (damper(s.compression, s.damper_vel), damper(s.compression, s.suspension_velocity_vertical)))
+            .collect()
+    }
+
+    #[test]
+    fn climbing_a_steady_hill_burns_no_damper_force() {
+        let mut phys = PhysicsWorld::new();
+        // 6° ramp starting at z = 10, its surface flush with the flat ground
+        let (slope, half_length, half_height) = (6.0_f32.to_radians(), 40.0, 0.5);
+        let normal = vector![0.0, slope.cos(), -slope.sin()];
+        let top = vector![0.0, FLAT_GROUND_TOP + half_length * slope.sin(), 10.0 + half_length * slope.cos()];
+        phys.colliders.insert(
+            ColliderBuilder::cuboid(4.0, half_height, half_length)
+                .translation(top - normal * half_height)
+                .rotation(vector![-slope, 0.0, 0.0])
+                .collision_groups(InteractionGroups::new(GROUP_OBSTACLE, GROUP_WHEEL))
+                .build(),
+        );
+        phys.invalidate_ray_caches();
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+
+        let mut climbing = 0;
+        for _ in 0..600 {
+            let v = phys.bodies[body].linvel().norm();
+            phys.apply_player_input("p", ((7.0 - v) * 0.5).clamp(0.0, 1.0), 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+            phys.step(DT);
+            let z = phys.bodies[body].translation().z;
+            if !(20.0..80.0).contains(&z) {
+                continue; // on the flat, or still taking the transition
             }
+            climbing += 1;
+            let climb_rate = phys.bodies[body].linvel().y;
+            assert!(climb_rate > 0.5, "climbing at {:.2} m/s", climb_rate);
+            for (damper, world_up) in damper_forces(&phys, body) {
+                assert_eq!(damper, 0.0, "steady climb, steady suspension");
+                assert!(world_up < -1000.0, "the world-up velocity would have pushed {:.0} N", world_up);
+            }
+        }
+        assert!(climbing > 300, "{} ticks on the slope", climbing);
+    }
 
-            // --------------------------------------------------
-            // PHASE 3B — TIRE SOLVER
-            // --------------------------------------------------
-            for contact in contacts.iter_mut() {
-                if let Some(nf) = axle_normal_force.get(&contact.wheel) {
-                    contact.normal_force = *nf;
+    #[test]
+    fn a_two_metre_drop_on_flat_ground_is_damped_as_before() {
+        let mut phys = PhysicsWorld::new();
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        for _ in 0..120 {
+            phys.step(DT);
+        }
+        let rest = phys.bodies[body].translation().y;
+        phys.bodies[body].set_translation(vector![0.0, rest + 2.0, 0.0], true);
+
+        // Peak bump (+) and rebound (-) damper force through the landing
+        let (mut bump, mut rebound) = ([0.0_f32; 2], [0.0_f32; 2]);
+        for _ in 0..90 {
+            phys.step(DT);
+            for (damper, world_up) in damper_forces(&phys, body) {
+                for (i, f) in [damper, world_up].into_iter().enumerate() {
+                    bump[i] = bump[i].max(f);
+                    rebound[i] = rebound[i].min(f);
                 }
             }
+        }
+        let [bump, bump_before] = bump;
+        let [rebound, rebound_before] = rebound;
+        assert!(bump > 5000.0, "the landing is damped: {:.0} N", bump);
+        assert!((bump - bump_before).abs() < 0.1 * bump_before, "bump {:.0} N, was {:.0} N", bump, bump_before);
+        assert!((rebound - rebound_before).abs() < 0.25 * -rebound_before, "rebound {:.0} N, was {:.0} N", rebound, rebound_before);
+    }
 
-            let ctx = SolveContext {
-                dt: dt as f32,
-                mass: body_mass,
-                engine_force: vehicle.config.engine_force,
-                brake_force: vehicle.config.brake_force,
-                abs_enabled: vehicle.config.abs_enabled,
-                tcs_enabled: vehicle.config.tcs_enabled,
-                abs_limit: vehicle.config.abs_nx_limit,
-                tcs_limit: vehicle.config.tcs_nx_limit,
-                driven_wheels: 2.0,
-                base_front_bias: 0.66,
-                bias_gain: 0.25,
-                wheelbase: vehicle.config.wheelbase,
-                mu_base: vehicle.config.mu_base,
-            };
+    #[test]
+    fn ground_height_is_right_before_the_first_step() {
+        let mut phys = PhysicsWorld::new();
+        assert_eq!(phys.tick, 0);
+        let ground = phys.ground_height(3.0, -7.0).expect("flat ground under the origin");
+        assert!((ground - FLAT_GROUND_TOP).abs() < 1e-4, "{}", ground);
 
-            let control = ControlInput {
-                throttle: vehicle.throttle,
-                brake: vehicle.brake,
-                steer: vehicle.steer,
-            };
+        // A prop added between ticks is seen without waiting for a step
+        phys.add_static_obstacle([3.0, FLAT_GROUND_TOP + 0.5, -7.0], [1.0, 0.5, 1.0]);
+        let top = phys.ground_height(3.0, -7.0).unwrap();
+        assert!((top - (FLAT_GROUND_TOP + 1.0)).abs() < 1e-4, "{}", top);
+        assert!((phys.ground_height(10.0, -7.0).unwrap() - FLAT_GROUND_TOP).abs() < 1e-4);
+    }
 
-            let tire_forces = solve_step(&ctx, &control, &mut contacts);
-            for imp in tire_forces.impulses {
-                let j: Vector<Real> = imp.impulse.into();
-                match imp.at_point {
-                    Some(p) => impulses.at_points.push((j, Point::from(p))),
-                    None => impulses.linear.push(j),
-                }
+    #[test]
+    fn warm_up_builds_the_world_without_moving_it() {
+        let mut phys = PhysicsWorld::new();
+        phys.warm_up(0);
+        assert_eq!(phys.tick, 0);
+        assert!(phys.vehicles.capacity() >= WARM_UP_DEFAULT_PLAYERS);
+        assert!((phys.ground_height(0.0, 0.0).unwrap() - FLAT_GROUND_TOP).abs() < 1e-4);
+
+        // The first car settles just as on a cold world
+        let settle = |phys: &mut PhysicsWorld| {
+            let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+            for _ in 0..60 {
+                phys.step(DT);
             }
+            phys.bodies[body].translation().y
+        };
+        let (warm, cold) = (settle(&mut phys), settle(&mut PhysicsWorld::new()));
+        assert!((warm - cold).abs() < 1e-3, "warm {:.4} m, cold {:.4} m", warm, cold);
+    }
 
-            // --------------------------------------------------
-            // PHASE 3C — APPLY ALL IMPULSES (ONCE)
-            // --------------------------------------------------
+    /// Mean body roll (deg) through a 20 m/s step steer, bars at `arb_scale`
+    /// x the preset, with the last solve's (pre-bar, post-bar) front loads.
+    fn step_steer_roll(arb_scale: f32) -> (f32, [(f32, f32); 2]) {
+        let mut phys = PhysicsWorld::new();
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        for rate in phys.vehicles.get_mut("p").unwrap().config.arb_rates.iter_mut() {
+            *rate *= arb_scale;
+        }
+        for _ in 0..60 {
+            phys.step(DT);
+        }
+        phys.bodies[body].set_linvel(vector![0.0, 0.0, 20.0], true);
+        let mut roll = 0.0;
+        for tick in 0..90 {
+            phys.apply_player_input("p", 0.3, 0.4, 0.0, 0.0, 0.0, 0.0, 0.0);
+            phys.step(DT);
+            if tick >= 30 {
+                let right = phys.bodies[body].rotation() * Vector::x();
+                roll += right.y.asin().to_degrees().abs() / 60.0;
+            }
+        }
+        let buffers = &phys.solve_buffers;
+        let load = |id: WheelId| {
+            let before = buffers.suspension_contacts.iter().find(|(w, _)| *w == id).unwrap().1.normal_force;
+            (before, buffers.axle_normal_force[&id])
+        };
+        (roll, [load(WheelId::FL), load(WheelId::FR)])
+    }
 
-            // Static Friction lock at low speed
-            let body = self.bodies.get_mut(handle).unwrap();
-            let v = body.linvel();
-            let speed = (v.x * v.x + v.z * v.z).sqrt();
+    #[test]
+    fn anti_roll_bars_move_load_to_the_outside_wheel_and_cut_roll() {
+        let (no_bars, _) = step_steer_roll(0.0);
+        let (preset, [(fl, fl_after), (fr, fr_after)]) = step_steer_roll(1.0);
+        let (stiff, _) = step_steer_roll(2.0);
+        assert!(stiff < preset && preset < 0.8 * no_bars, "roll {:.2} / {:.2} / {:.2} deg", no_bars, preset, stiff);
 
-            let hard_brake = control.brake > 0.8;
-            let near_rest  = speed < 0.4;
+        // FR is on the outside of this turn, the more compressed: the bar loads it
+        assert!(fr > fl);
+        assert!(fr_after - fr > 100.0, "FR {:.0} -> {:.0} N", fr, fr_after);
+        assert!(((fl_after + fr_after) - (fl + fr)).abs() < 1.0, "axle total conserved");
+    }
 
-            if hard_brake && near_rest {
-                // Kill planar velocity
-                body.set_linvel(vector![0.0, v.y, 0.0], true);
+    #[test]
+    fn impulse_audit_log_records_suspension_and_tire_impulses_in_a_fixed_ring() {
+        let mut phys = PhysicsWorld::new();
+        phys.debug_show_forces = true;
+        let ring_capacity = phys.impulse_audit_log.capacity();
+        phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        phys.spawn_vehicle_for_player("q".into(), [10.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        for _ in 0..60 {
+            phys.apply_player_input("p", 1.0, 0.3, 0.0, 0.0, 0.0, 0.0, 0.0);
+            phys.step(DT);
+        }
 
-                // Kill yaw
-                body.set_angvel(vector![0.0, 0.0, 0.0], true);
-            }
+        let log = phys.dump_audit_log("p");
+        assert!(!log.is_empty() && log.iter().all(|e| e.player_id == "p"));
+        assert!(log.windows(2).all(|w| w[0].tick <= w[1].tick), "oldest first");
+        assert_eq!(log.last().unwrap().tick, phys.tick);
+        let of = |source: ImpulseSource| log.iter().filter(move |e| e.source == source);
+        for source in [ImpulseSource::Suspension, ImpulseSource::TireLongitudinal, ImpulseSource::TireLateral] {
+            assert!(of(source).count() > 0, "{:?} impulses logged", source);
+        }
+        for e in of(ImpulseSource::Suspension) {
+            assert!(["FL", "FR", "RL", "RR"].contains(&e.wheel_id.as_deref().unwrap()));
+            assert!(e.impulse[1] >= 0.0 && e.point.is_some() && e.normal_force >= 0.0, "{:?}", e);
+        }
+        assert!(of(ImpulseSource::Suspension).any(|e| e.impulse[1] > 0.0 && e.normal_force > 0.0), "loaded wheels");
+        assert!(of(ImpulseSource::TireLongitudinal).any(|e| e.impulse[0] != 0.0 || e.impulse[2] != 0.0));
 
-            impulses.apply(body);
+        // Two cars x four wheels x several impulses a tick overflow the ring:
+        // full, oldest dropped, never grown
+        assert_eq!(phys.impulse_audit_log.len(), IMPULSE_AUDIT_CAPACITY);
+        assert_eq!(phys.impulse_audit_log.capacity(), ring_capacity);
+        assert!(phys.impulse_audit_log.front().unwrap().tick > 1);
+        assert!(!phys.dump_audit_log("q").is_empty());
 
-        } // Players loop
-        
-    } // end
+        // Off: nothing more is recorded
+        phys.debug_show_forces = false;
+        let newest = phys.impulse_audit_log.back().unwrap().tick;
+        phys.step(DT);
+        assert_eq!(phys.impulse_audit_log.back().unwrap().tick, newest);
+    }
 
-    pub fn step(&mut self, dt: Real) {
+    /// A car dropped on its roof with `auto_flip_recover`, 1 s timeout:
+    /// (seconds counted as flipped before it was righted, the tick it was
+    /// righted on, final up · world up and heading).
+    fn drop_on_roof(auto_flip_recover: bool) -> (f32, Option<usize>, f32, f32) {
+        let mut phys = PhysicsWorld::new();
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        let config = &mut phys.vehicles.get_mut("p").unwrap().config;
+        (config.auto_flip_recover, config.flip_timeout_s) = (auto_flip_recover, 1.0);
+        let yaw = 0.7;
+        let roof = Rotation::from_axis_angle(&Vector::y_axis(), yaw) * Rotation::from_axis_angle(&Vector::z_axis(), std::f32::consts::PI);
+        phys.place_vehicle("p", vector![0.0, FLAT_GROUND_TOP + 1.5, 0.0], roof).unwrap();
 
-        // prevent ui clutter
-        self.debug_overlay.clear();
-        
-        // Convert inputs → intent (NO PHYSICS)
-        apply_vehicle_controls(self.vehicles.values_mut(), dt);
-        
-        // Apply suspension + traction + tire forces
-        self.apply_suspension(dt);
-        
-        // Step physics
-        let hooks = ();
-        let mut events = ();
-        self.pipeline.step(
-            &self.gravity,
-            &IntegrationParameters {
-                dt,
-                ..IntegrationParameters::default()
-            },
-            &mut self.island_manager,
-            &mut self.broad_phase,
-            &mut self.narrow_phase,
-            &mut self.bodies,
-            &mut self.colliders,
-            &mut self.joints,
-            &mut self.multibody_joints,
-            &mut self.ccd,
-            Some(&mut self.query_pipeline),
-            &mut events,
-            &hooks,
-        );
+        let (mut counted, mut righted) = (0.0_f32, None);
+        for tick in 0..240 {
+            phys.step(DT);
+            let flipped_for = phys.vehicles["p"].flip.flipped_for;
+            if flipped_for == 0.0 && counted > 0.0 && righted.is_none() && (phys.bodies[body].rotation() * Vector::y()).y > 0.99 {
+                righted = Some(tick);
+            }
+            if righted.is_none() {
+                counted = counted.max(flipped_for);
+            }
+        }
+        let rotation = phys.bodies[body].rotation();
+        let forward = rotation * Vector::z();
+        (counted, righted, (rotation * Vector::y()).y, forward.x.atan2(forward.z))
+    }
 
-        // Safety: prevent bodies from exploding to insane coordinates
-        for (_, body) in self.bodies.iter_mut() {
-            let mut pos = *body.translation();
+    #[test]
+    fn a_car_resting_on_its_roof_is_righted_after_the_timeout() {
+        let (counted, righted, up, heading) = drop_on_roof(true);
+        assert!(righted.is_some(), "righted within 4 s");
+        // Counted a full timeout at rest (the fall itself doesn't count), not more
+        assert!((1.0 - DT..1.0 + DT).contains(&counted), "flipped for {:.3} s", counted);
+        assert!(up > 0.95, "on its wheels, up {:.3}", up);
+        assert!((heading - 0.7).abs() < 0.05, "same heading, got {:.3}", heading);
 
-            let bad =
-                !pos.x.is_finite() || !pos.y.is_finite() || !pos.z.is_finite() ||
-                pos.x.abs() > 1_000.0 || pos.y.abs() > 1_000.0 || pos.z.abs() > 1_000.0;
+        let (counted, righted, up, _) = drop_on_roof(false);
+        assert_eq!(righted, None);
+        assert!(counted > 1.5, "well past the timeout, {:.1} s", counted);
+        assert!(up < -0.9, "left on its roof, up {:.3}", up);
+    }
 
-            if bad {
-                // Reset this body to a safe position above the heightfield
-                pos = vector![0.0, 1.0, 0.0];
-                body.set_translation(pos, true);
-                body.set_linvel(vector![0.0, 0.0, 0.0], true);
-                body.set_angvel(vector![0.0, 0.0, 0.0], true);
+    /// Two `config` cars spawned on one spot, stepped for `ticks`: the deepest
+    /// chassis-chassis penetration (m) after the first half second, and both
+    /// final positions.
+    fn spawn_on_top_of_each_other(config: &str, ticks: usize) -> (f32, [Vector<Real>; 2]) {
+        let mut phys = PhysicsWorld::new();
+        let a = phys.spawn_vehicle_for_player("a".into(), [0.0, 1.0, 0.0], 0.0, config).unwrap();
+        let b = phys.spawn_vehicle_for_player("b".into(), [0.0, 1.0, 0.0], 0.0, config).unwrap();
+        let mut deepest: f32 = 0.0;
+        for tick in 0..ticks {
+            phys.step(DT);
+            let [ca, cb] = [a, b].map(|body| &phys.colliders[phys.bodies[body].colliders()[0]]);
+            let contact = rapier3d::parry::query::contact(ca.position(), ca.shape(), cb.position(), cb.shape(), 0.0).unwrap();
+            if tick >= 30 {
+                deepest = deepest.max(contact.map_or(0.0, |c| -c.dist));
+            }
+        }
+        (deepest, [a, b].map(|body| *phys.bodies[body].translation()))
+    }
+
+    #[test]
+    fn two_cars_spawned_on_one_spot_push_apart() {
+        let (deepest, [a, b]) = spawn_on_top_of_each_other("gt86", 600);
+        assert!(deepest < 0.06, "resting contact, not a 0.7 m overlap: sank {:.3} m into each other", deepest);
+        let apart = (a - b).xz().norm();
+        assert!(apart > 2.0, "side by side in the end: {:.2} m apart", apart);
+        assert!(a.y < 2.0 && b.y < 2.0, "both back on the ground: {:.2} / {:.2} m", a.y, b.y);
+    }
+
+    #[test]
+    fn arcade_cars_ghost_through_each_other_without_launching() {
+        let (deepest, [a, b]) = spawn_on_top_of_each_other("arcade", 180);
+        assert!(deepest > 0.5, "arcade chassis overlap freely");
+        assert!((a - b).norm() < 0.01 && a.y < 2.0, "still on one spot, on the ground: {:?} / {:?}", a, b);
+    }
 
-                println!("⚠️ Reset exploding body back to {:?}", pos);
+    #[test]
+    fn impacts_are_reported_once_and_resting_contact_never() {
+        let mut phys = PhysicsWorld::new();
+        // Wall across the road at z = 20 (fixed, ground layer: chassis hit it)
+        phys.colliders.insert(ColliderBuilder::cuboid(5.0, 1.0, 0.5).translation(vector![-10.0, FLAT_GROUND_TOP + 1.0, 20.0]).build());
+        let wall_car = phys.spawn_vehicle_for_player("w".into(), [-10.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        // Head-on pair
+        let a = phys.spawn_vehicle_for_player("a".into(), [10.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        let b = phys.spawn_vehicle_for_player("b".into(), [10.0, 1.0, 14.0], std::f32::consts::PI, DEFAULT_VEHICLE).unwrap();
+        let mut records = Vec::new();
+        for tick in 0..240 {
+            if tick == 60 {
+                phys.bodies[wall_car].set_linvel(vector![0.0, 0.0, 10.0], true);
+                phys.bodies[a].set_linvel(vector![0.0, 0.0, 6.0], true);
+                phys.bodies[b].set_linvel(vector![0.0, 0.0, -6.0], true);
             }
+            phys.step(DT);
+            records.extend(phys.collision_events.iter().map(|r| (tick, r.clone())));
         }
+
+        // Exactly the two crashes, once each: nothing from cars resting or rolling on the ground
+        let pairs: Vec<(&str, &str)> = records.iter().map(|(_, r)| (r.a.as_str(), r.b.as_str())).collect();
+        assert_eq!(pairs, [("a", "b"), ("w", WORLD)], "{:?}", records);
+        let (_, head_on) = &records[0];
+        assert!((head_on.point[2] - 7.0).abs() < 0.5, "between the two noses: {:?}", head_on.point);
+        let (_, wall) = &records[1];
+        assert!((wall.point[2] - 19.5).abs() < 0.1, "on the wall face: {:?}", wall.point);
+        let mass = phys.bodies[a].mass();
+        assert!(head_on.impulse / mass > 1.0 && wall.impulse / mass > 1.0, "{:?}", records);
     }
 }
@@ -0,0 +1,520 @@
+// ==============================================================================
+// plugins.rs — PER-TICK GAME LOGIC HOOKS
+// ------------------------------------------------------------------------------
+// Small gameplay rules (scoring, zone effects, gravity wells) live in plugins
+// instead of the tick loop. Plugins only see the world through TickCtx:
+// - telemetry queries (entity ids, position, velocity, mass)
+// - event emission (dispatched to every plugin's on_event after on_tick),
+//   tagged with the room of the entity it is about (emit_for) or world-wide
+// - a limited force API (external force on an entity's chassis)
+// - payload changes (cargo pickups; PhysicsWorld::set_payload)
+// - a seeded RNG (GameRng child "plugins"; never thread_rng, so replays match)
+//
+// Hook order per tick (main.rs, after inputs, before the physics step):
+//   1) on_tick for every plugin, in registration order
+//   2) on_event for every plugin, for each event emitted during (1)
+// on_entity_spawned / on_entity_removed fire from net.rs as players come and go.
+//
+// Registration: AVENLAB_PLUGINS="low_gravity_zone,..." (see build_plugin).
+// ==============================================================================
+
+use rand::Rng;
+use rand::rngs::StdRng;
+use rapier3d::prelude::Vector;
+use serde::Serialize;
+
+use crate::game_rng::GameRng;
+use crate::physics::PhysicsWorld;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum GameEvent {
+    /// Free-form plugin event (name + JSON payload)
+    Custom { source: String, name: String, data: serde_json::Value },
+}
+
+pub trait GamePlugin: Send {
+    fn name(&self) -> &str;
+    fn on_tick(&mut self, _ctx: &mut TickCtx) {}
+    fn on_entity_spawned(&mut self, _id: &str) {}
+    fn on_entity_removed(&mut self, _id: &str) {}
+    fn on_event(&mut self, _event: &GameEvent) {}
+}
+
+/// An emitted event and the room its clients are in (None = world-wide,
+/// e.g. a gust that pushes every car).
+#[derive(Debug, Clone)]
+pub struct RoomEvent {
+    pub room_id: Option<usize>,
+    pub event: GameEvent,
+}
+
+/// Entity id → room, from SharedGameState's entities (None = not seated).
+pub type RoomLookup<'a> = &'a dyn Fn(&str) -> Option<usize>;
+
+// ==========================================================
+// TickCtx — the only window plugins get into the world
+// ==========================================================
+pub struct TickCtx<'a> {
+    phys: &'a mut PhysicsWorld,
+    rooms: RoomLookup<'a>,
+    events: Vec<RoomEvent>,
+    pub rng: &'a mut StdRng, // shared by all plugins, drawn in registration order
+    pub tick: u64,
+    pub dt: f32,
+}
+
+impl<'a> TickCtx<'a> {
+    pub fn new(phys: &'a mut PhysicsWorld, rooms: RoomLookup<'a>, rng: &'a mut StdRng, tick: u64, dt: f32) -> Self {
+        Self { phys, rooms, events: Vec::new(), rng, tick, dt }
+    }
+
+    pub fn entity_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.phys.vehicles.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    pub fn position(&self, id: &str) -> Option<[f32; 3]> {
+        let body = self.phys.bodies.get(self.phys.vehicles.get(id)?.body)?;
+        let t = body.translation();
+        Some([t.x, t.y, t.z])
+    }
+
+    pub fn velocity(&self, id: &str) -> Option<[f32; 3]> {
+        let body = self.phys.bodies.get(self.phys.vehicles.get(id)?.body)?;
+        let v = body.linvel();
+        Some([v.x, v.y, v.z])
+    }
+
+    pub fn mass(&self, id: &str) -> Option<f32> {
+        let body = self.phys.bodies.get(self.phys.vehicles.get(id)?.body)?;
+        Some(body.mass())
+    }
+
+    /// Apply an external force (N) at the chassis COM for this tick only.
+    /// Returns false if the entity has no body or the force is not finite.
+    pub fn apply_force(&mut self, id: &str, force: [f32; 3]) -> bool {
+        if force.iter().any(|f| !f.is_finite()) {
+            return false;
+        }
+        let Some(handle) = self.phys.vehicles.get(id).map(|v| v.body) else { return false };
+        let Some(body) = self.phys.bodies.get_mut(handle) else { return false };
+
+        let [fx, fy, fz] = force;
+        body.apply_impulse(Vector::new(fx, fy, fz) * self.dt, true);
+        true
+    }
+
+    /// Load / unload cargo (see PhysicsWorld::set_payload). False if the
+    /// entity is missing or the payload is out of range.
+    pub fn set_payload(&mut self, id: &str, mass_kg: f32, offset: [f32; 3]) -> bool {
+        self.phys.set_payload(id, mass_kg, offset).is_ok()
+    }
+
+    /// World-wide event: every room hears it.
+    pub fn emit(&mut self, event: GameEvent) {
+        self.events.push(RoomEvent { room_id: None, event });
+    }
+
+    /// Event about entity `id`: only its room hears it.
+    pub fn emit_for(&mut self, id: &str, event: GameEvent) {
+        let room_id = (self.rooms)(id);
+        self.events.push(RoomEvent { room_id, event });
+    }
+}
+
+// ==========================================================
+// PluginHost — owns plugins and drives the hook order
+// ==========================================================
+pub struct PluginHost {
+    plugins: Vec<Box<dyn GamePlugin>>,
+    rng: StdRng,
+}
+
+impl Default for PluginHost {
+    fn default() -> Self {
+        Self { plugins: Vec::new(), rng: GameRng::default().child("plugins") }
+    }
+}
+
+impl PluginHost {
+    pub fn register(&mut self, plugin: Box<dyn GamePlugin>) {
+        println!("🧩 Plugin registered: {}", plugin.name());
+        self.plugins.push(plugin);
+    }
+
+    /// Build from AVENLAB_PLUGINS (comma-separated names), drawing from `rng`.
+    pub fn from_env(rng: &GameRng) -> Self {
+        let mut host = Self { plugins: Vec::new(), rng: rng.child("plugins") };
+        let list = std::env::var("AVENLAB_PLUGINS").unwrap_or_default();
+        for name in list.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            match build_plugin(name) {
+                Some(p) => host.register(p),
+                None => eprintln!("⚠️ Unknown plugin '{}'", name),
+            }
+        }
+        host
+    }
+
+    /// Run on_tick for all plugins, then dispatch their events.
+    /// Returns the emitted events so the caller can broadcast them to their rooms.
+    pub fn run_tick(&mut self, phys: &mut PhysicsWorld, rooms: RoomLookup, tick: u64, dt: f32) -> Vec<RoomEvent> {
+        if self.plugins.is_empty() {
+            return Vec::new();
+        }
+
+        let mut ctx = TickCtx::new(phys, rooms, &mut self.rng, tick, dt);
+        for plugin in self.plugins.iter_mut() {
+            plugin.on_tick(&mut ctx);
+        }
+        let events = std::mem::take(&mut ctx.events);
+
+        for event in &events {
+            for plugin in self.plugins.iter_mut() {
+                plugin.on_event(&event.event);
+            }
+        }
+        events
+    }
+
+    pub fn entity_spawned(&mut self, id: &str) {
+        for plugin in self.plugins.iter_mut() {
+            plugin.on_entity_spawned(id);
+        }
+    }
+
+    pub fn entity_removed(&mut self, id: &str) {
+        for plugin in self.plugins.iter_mut() {
+            plugin.on_entity_removed(id);
+        }
+    }
+}
+
+pub fn build_plugin(name: &str) -> Option<Box<dyn GamePlugin>> {
+    match name {
+        "low_gravity_zone" => Some(Box::new(LowGravityZone::default())),
+        "cargo_pickup" => Some(Box::new(CargoPickup::default())),
+        "crosswind_gusts" => Some(Box::new(CrosswindGusts::default())),
+        _ => None,
+    }
+}
+
+// ==========================================================
+// Example: low-gravity zone
+// ----------------------------------------------------------
+// Cylinder (XZ radius, unbounded height) where part of gravity is cancelled
+// by an upward force. Emits enter/exit events.
+// ==========================================================
+pub struct LowGravityZone {
+    pub center: [f32; 2],   // x, z
+    pub radius: f32,        // m
+    pub gravity_scale: f32, // 0.3 => feels like 30% gravity
+    inside: std::collections::HashSet<String>,
+}
+
+impl Default for LowGravityZone {
+    fn default() -> Self {
+        Self {
+            center: [0.0, 30.0],
+            radius: 15.0,
+            gravity_scale: 0.3,
+            inside: std::collections::HashSet::new(),
+        }
+    }
+}
+
+impl GamePlugin for LowGravityZone {
+    fn name(&self) -> &str {
+        "low_gravity_zone"
+    }
+
+    fn on_tick(&mut self, ctx: &mut TickCtx) {
+        for id in ctx.entity_ids() {
+            let (Some(p), Some(m)) = (ctx.position(&id), ctx.mass(&id)) else { continue };
+
+            let dx = p[0] - self.center[0];
+            let dz = p[2] - self.center[1];
+            let in_zone = dx * dx + dz * dz <= self.radius * self.radius;
+
+            if in_zone {
+                let lift = m * 9.81 * (1.0 - self.gravity_scale);
+                ctx.apply_force(&id, [0.0, lift, 0.0]);
+            }
+
+            let was_inside = self.inside.contains(&id);
+            if in_zone != was_inside {
+                if in_zone { self.inside.insert(id.clone()); } else { self.inside.remove(&id); }
+                ctx.emit_for(&id, GameEvent::Custom {
+                    source: self.name().to_string(),
+                    name: if in_zone { "zone_enter" } else { "zone_exit" }.to_string(),
+                    data: serde_json::json!({ "id": id }),
+                });
+            }
+        }
+    }
+
+    fn on_entity_removed(&mut self, id: &str) {
+        self.inside.remove(id);
+    }
+}
+
+// ==========================================================
+// Example: cargo pickup
+// ----------------------------------------------------------
+// Driving into the pickup circle loads a crate over the rear
+// axle (once per spawn). Emits cargo_loaded.
+// ==========================================================
+pub struct CargoPickup {
+    pub center: [f32; 2],   // x, z
+    pub radius: f32,        // m
+    pub mass: f32,          // kg
+    pub offset: [f32; 3],   // body-local crate position
+    loaded: std::collections::HashSet<String>,
+}
+
+impl Default for CargoPickup {
+    fn default() -> Self {
+        Self {
+            center: [0.0, -30.0],
+            radius: 6.0,
+            mass: 300.0,
+            offset: [0.0, 0.2, -1.5],
+            loaded: std::collections::HashSet::new(),
+        }
+    }
+}
+
+impl GamePlugin for CargoPickup {
+    fn name(&self) -> &str {
+        "cargo_pickup"
+    }
+
+    fn on_tick(&mut self, ctx: &mut TickCtx) {
+        for id in ctx.entity_ids() {
+            if self.loaded.contains(&id) {
+                continue;
+            }
+            let Some(p) = ctx.position(&id) else { continue };
+            let dx = p[0] - self.center[0];
+            let dz = p[2] - self.center[1];
+            if dx * dx + dz * dz > self.radius * self.radius {
+                continue;
+            }
+            if ctx.set_payload(&id, self.mass, self.offset) {
+                self.loaded.insert(id.clone());
+                ctx.emit_for(&id, GameEvent::Custom {
+                    source: self.name().to_string(),
+                    name: "cargo_loaded".to_string(),
+                    data: serde_json::json!({ "id": id, "mass": self.mass }),
+                });
+            }
+        }
+    }
+
+    fn on_entity_spawned(&mut self, id: &str) {
+        self.loaded.remove(id);
+    }
+
+    fn on_entity_removed(&mut self, id: &str) {
+        self.loaded.remove(id);
+    }
+}
+
+// ==========================================================
+// Example: crosswind gusts
+// ----------------------------------------------------------
+// Every few seconds (random gap) a gust of random strength,
+// direction and length pushes every vehicle sideways, as a
+// force proportional to its mass. Emits gust_start.
+// ==========================================================
+pub struct CrosswindGusts {
+    pub gap_secs: [f32; 2],      // calm time between gusts (min, max)
+    pub duration_secs: [f32; 2], // gust length (min, max)
+    pub max_accel: f32,          // m/s² at full strength
+    calm_left: f32,
+    gust: Option<Gust>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gust {
+    pub direction: [f32; 2], // unit XZ
+    pub accel: f32,          // m/s²
+    pub secs_left: f32,
+}
+
+impl Default for CrosswindGusts {
+    fn default() -> Self {
+        Self {
+            gap_secs: [2.0, 6.0],
+            duration_secs: [0.5, 1.5],
+            max_accel: 2.5,
+            calm_left: 2.0, // first gust after the shortest gap
+            gust: None,
+        }
+    }
+}
+
+impl GamePlugin for CrosswindGusts {
+    fn name(&self) -> &str {
+        "crosswind_gusts"
+    }
+
+    fn on_tick(&mut self, ctx: &mut TickCtx) {
+        if self.gust.is_none() {
+            self.calm_left -= ctx.dt;
+            if self.calm_left > 0.0 {
+                return;
+            }
+            let angle = ctx.rng.gen_range(0.0..std::f32::consts::TAU);
+            let gust = Gust {
+                direction: [angle.cos(), angle.sin()],
+                accel: self.max_accel * ctx.rng.gen_range(0.3f32..=1.0),
+                secs_left: ctx.rng.gen_range(self.duration_secs[0]..=self.duration_secs[1]),
+            };
+            self.calm_left = ctx.rng.gen_range(self.gap_secs[0]..=self.gap_secs[1]);
+            self.gust = Some(gust);
+            ctx.emit(GameEvent::Custom {
+                source: self.name().to_string(),
+                name: "gust_start".to_string(),
+                data: serde_json::json!({ "direction": gust.direction, "accel": gust.accel, "secs": gust.secs_left }),
+            });
+        }
+
+        let Some(gust) = self.gust.as_mut() else { return };
+        for id in ctx.entity_ids() {
+            let Some(m) = ctx.mass(&id) else { continue };
+            let f = m * gust.accel;
+            ctx.apply_force(&id, [gust.direction[0] * f, 0.0, gust.direction[1] * f]);
+        }
+        gust.secs_left -= ctx.dt;
+        if gust.secs_left <= 0.0 {
+            self.gust = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::DEFAULT_VEHICLE;
+    use std::sync::{Arc, Mutex};
+
+    const DT: f32 = 1.0 / 60.0;
+
+    /// Logs every hook it sees as "<name>:<hook>" and emits one event per tick.
+    struct Probe {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl GamePlugin for Probe {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn on_tick(&mut self, ctx: &mut TickCtx) {
+            self.log.lock().unwrap().push(format!("{}:tick", self.name));
+            ctx.emit(GameEvent::Custom { source: self.name.to_string(), name: "ping".to_string(), data: serde_json::Value::Null });
+        }
+
+        fn on_entity_spawned(&mut self, id: &str) {
+            self.log.lock().unwrap().push(format!("{}:spawned {}", self.name, id));
+        }
+
+        fn on_entity_removed(&mut self, id: &str) {
+            self.log.lock().unwrap().push(format!("{}:removed {}", self.name, id));
+        }
+
+        fn on_event(&mut self, event: &GameEvent) {
+            let GameEvent::Custom { source, .. } = event;
+            self.log.lock().unwrap().push(format!("{}:event from {}", self.name, source));
+        }
+    }
+
+    fn probes(log: &Arc<Mutex<Vec<String>>>) -> PluginHost {
+        let mut host = PluginHost::default();
+        for name in ["a", "b"] {
+            host.register(Box::new(Probe { name, log: Arc::clone(log) }));
+        }
+        host
+    }
+
+    #[test]
+    fn ticks_run_in_registration_order_then_every_plugin_sees_every_event() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut host = probes(&log);
+        let mut phys = PhysicsWorld::new();
+
+        let events = host.run_tick(&mut phys, &|_| None, 1, DT);
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["a:tick", "b:tick", "a:event from a", "b:event from a", "a:event from b", "b:event from b"]
+        );
+
+        log.lock().unwrap().clear();
+        host.entity_spawned("p");
+        host.entity_removed("p");
+        assert_eq!(*log.lock().unwrap(), ["a:spawned p", "b:spawned p", "a:removed p", "b:removed p"]);
+    }
+
+    #[test]
+    fn the_force_api_only_takes_finite_forces_on_known_entities() {
+        let mut phys = PhysicsWorld::new();
+        let body = phys.spawn_vehicle_for_player("p".into(), [0.0, 5.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        let mut rng = GameRng::default().child("plugins");
+        let mut ctx = TickCtx::new(&mut phys, &|_| None, &mut rng, 1, DT);
+
+        assert_eq!(ctx.entity_ids(), ["p"]);
+        assert!(!ctx.apply_force("p", [f32::NAN, 0.0, 0.0]));
+        assert!(!ctx.apply_force("ghost", [1.0, 0.0, 0.0]));
+        let mass = ctx.mass("p").unwrap();
+        assert!(ctx.apply_force("p", [mass, 0.0, 0.0])); // 1 m/s² for one tick
+        assert!(!ctx.set_payload("p", -1.0, [0.0; 3]));
+
+        let vx = phys.bodies[body].linvel().x;
+        assert!((vx - DT).abs() < 1e-4, "vx {}", vx);
+    }
+
+    #[test]
+    fn low_gravity_zone_lifts_and_reports_enter_and_exit() {
+        let mut phys = PhysicsWorld::new();
+        let zone = LowGravityZone::default();
+        let [x, z] = zone.center;
+        phys.spawn_vehicle_for_player("p".into(), [x, 1.0, z], 0.0, DEFAULT_VEHICLE).unwrap();
+        let mut host = PluginHost::default();
+        host.register(Box::new(zone));
+
+        let rooms = |id: &str| (id == "p").then_some(3);
+        let events = host.run_tick(&mut phys, &rooms, 1, DT);
+        assert_eq!(events.len(), 1);
+        let GameEvent::Custom { name, .. } = &events[0].event;
+        assert_eq!((name.as_str(), events[0].room_id), ("zone_enter", Some(3)), "tagged with p's room");
+        assert!(host.run_tick(&mut phys, &rooms, 2, DT).is_empty());
+        phys.remove_vehicle("p");
+        host.entity_removed("p");
+        phys.spawn_vehicle_for_player("p".into(), [x + 100.0, 1.0, z], 0.0, DEFAULT_VEHICLE).unwrap();
+        assert!(host.run_tick(&mut phys, &rooms, 3, DT).is_empty(), "removal forgets the car was inside");
+    }
+
+    #[test]
+    fn gusts_replay_from_the_seed() {
+        let gusts = |seed: u64| -> Vec<serde_json::Value> {
+            let mut phys = PhysicsWorld::new();
+            let mut host = PluginHost { plugins: Vec::new(), rng: GameRng::new(seed).child("plugins") };
+            host.register(build_plugin("crosswind_gusts").unwrap());
+            (0..600)
+                .flat_map(|tick| host.run_tick(&mut phys, &|_| None, tick, DT))
+                .map(|RoomEvent { room_id, event: GameEvent::Custom { data, .. } }| {
+                    assert_eq!(room_id, None, "a gust pushes every room");
+                    data
+                })
+                .collect()
+        };
+        let first = gusts(7);
+        assert!(first.len() >= 2, "10 s has a gust every 2-6 s");
+        assert_eq!(first, gusts(7));
+        assert_ne!(first, gusts(8));
+    }
+}
@@ -0,0 +1,121 @@
+// ==============================================================================
+// protocol.rs — WIRE PROTOCOL VERSION + VERSIONED OUTBOUND MESSAGES
+// ------------------------------------------------------------------------------
+// PROTOCOL_VERSION is bumped whenever a message changes shape in a way an
+// older client would misread (renamed / retyped fields, new meaning). Adding
+// an optional field is not a bump.
+//
+// Negotiation: the client states the version it speaks in its join message
+//
+//   {"type":"join","protocol_version":1, ..}
+//
+// or in the handshake query (ws://host:9001/?protocol=1, for clients that
+// resume or skip the join), and is refused when it is outside
+// MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION or not stated at all:
+//
+//   {"type":"error","reason":"unsupported_version","client_version":..,
+//    "min_protocol_version":1,"protocol_version":1}
+//
+// then closed with reason "unsupported_version". Clients from before
+// versioning (no join, no ?protocol=) are only admitted with
+// AVENLAB_LEGACY_CLIENTS=1.
+//
+// Outbound messages carry the version so a client can check what it reads:
+//   - welcome          "protocol_version" (built by welcome() below)
+//   - everything else  "v": JSON messages are built by event() below, the
+//                      snapshot / debug frames by snapshot.rs, the typed
+//                      map / terrain / haptics messages stamp it themselves
+//
+// Env:
+// - AVENLAB_LEGACY_CLIENTS=1   admit clients that don't state a version
+// ==============================================================================
+
+use serde_json::{Value, json};
+
+/// Version this server speaks (welcome "protocol_version", snapshot / debug "v").
+pub const PROTOCOL_VERSION: u32 = 1;
+/// Oldest client version still accepted.
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// AVENLAB_LEGACY_CLIENTS=1: a client that states no version (join or
+/// ?protocol=) is admitted anyway.
+pub fn legacy_clients_allowed() -> bool {
+    std::env::var("AVENLAB_LEGACY_CLIENTS").is_ok_and(|v| v == "1")
+}
+
+/// "protocol=<n>" out of a handshake query string.
+pub fn version_from_query(query: &str) -> Option<u32> {
+    query.split('&').find_map(|pair| pair.strip_prefix("protocol=")).and_then(|v| v.parse().ok())
+}
+
+/// Whether a client speaking `version` may join.
+pub fn supports(version: u32) -> bool {
+    (MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version)
+}
+
+/// {"type":<kind>,"v":PROTOCOL_VERSION} plus `fields` (a JSON object):
+/// every outbound JSON message but the welcome is built here.
+pub fn event(kind: &str, fields: Value) -> String {
+    stamped(json!({ "type": kind, "v": PROTOCOL_VERSION }), fields)
+}
+
+/// The "unsupported_version" error sent before closing.
+pub fn unsupported_version(client_version: Option<u32>) -> String {
+    event(
+        "error",
+        json!({
+            "reason": "unsupported_version",
+            "client_version": client_version,
+            "min_protocol_version": MIN_PROTOCOL_VERSION,
+            "protocol_version": PROTOCOL_VERSION,
+        }),
+    )
+}
+
+/// {"type":"welcome","protocol_version":..} plus `fields` (a JSON object).
+pub fn welcome(fields: Value) -> String {
+    stamped(json!({ "type": "welcome", "protocol_version": PROTOCOL_VERSION }), fields)
+}
+
+fn stamped(mut msg: Value, fields: Value) -> String {
+    if let (Some(msg), Value::Object(fields)) = (msg.as_object_mut(), fields) {
+        msg.extend(fields);
+    }
+    msg.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_carry_type_version_and_fields() {
+        let msg: Value = serde_json::from_str(&event("world_reset", json!({ "tick": 7, "team": "Red" }))).unwrap();
+        assert_eq!(msg, json!({ "type": "world_reset", "v": PROTOCOL_VERSION, "tick": 7, "team": "Red" }));
+
+        let bare: Value = serde_json::from_str(&event("pong", json!({}))).unwrap();
+        assert_eq!(bare, json!({ "type": "pong", "v": PROTOCOL_VERSION }));
+
+        let welcome: Value = serde_json::from_str(&welcome(json!({ "player_id": "p" }))).unwrap();
+        assert_eq!(welcome["protocol_version"], PROTOCOL_VERSION);
+        assert_eq!(welcome["player_id"], "p");
+    }
+
+    #[test]
+    fn negotiation_refuses_unknown_and_unstated_versions() {
+        assert!(supports(PROTOCOL_VERSION));
+        assert!(!supports(PROTOCOL_VERSION + 1));
+        assert!(!supports(MIN_PROTOCOL_VERSION - 1));
+        assert_eq!(version_from_query("resume=abc&protocol=1"), Some(1));
+        assert_eq!(version_from_query("vehicle=tank"), None);
+        // Unstated is refused unless legacy admission is switched on
+        if std::env::var_os("AVENLAB_LEGACY_CLIENTS").is_none() {
+            assert!(!legacy_clients_allowed());
+        }
+
+        let refusal: Value = serde_json::from_str(&unsupported_version(None)).unwrap();
+        assert_eq!(refusal["type"], "error");
+        assert_eq!(refusal["reason"], "unsupported_version");
+        assert_eq!(refusal["client_version"], Value::Null);
+    }
+}
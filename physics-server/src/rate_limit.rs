@@ -0,0 +1,181 @@
+// ==============================================================================
+// rate_limit.rs — PER-CONNECTION MESSAGE RATE LIMIT (TOKEN BUCKET)
+// ------------------------------------------------------------------------------
+// Most client messages take the physics or game-state mutex in the read loop,
+// so a client spamming thousands of frames a second starves the tick. One
+// RateLimiter per connection, checked before a frame is parsed:
+//
+//   bucket : `burst` tokens, refilled at `rate` per second; every frame takes
+//            one. An empty bucket drops the frame before it touches a lock
+//            (inputs latch, so a dropped input only delays the next one)
+//   abuse  : more than `abuse_drops` frames dropped within one second closes
+//            the connection with reason "rate_limited"
+//
+// The defaults sit above InputSanity's input rate (input_sanity.rs), so a
+// well-behaved client never reaches the bucket's floor; the "stats" reply
+// carries the number of dropped frames under "rate_limit".
+//
+// Env:
+// - AVENLAB_MSG_RATE    frames per second (default 240)
+// - AVENLAB_MSG_BURST   bucket size (default 480)
+// - AVENLAB_MSG_ABUSE   dropped frames per second before a disconnect
+//                       (default 1000)
+// ==============================================================================
+
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+
+const DEFAULT_RATE: f32 = 240.0;
+const DEFAULT_BURST: f32 = 480.0;
+const DEFAULT_ABUSE_DROPS: u32 = 1000;
+const ABUSE_WINDOW: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateVerdict {
+    Allow,
+    Drop,
+    Disconnect,
+}
+
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: f32,                           // tokens per second
+    burst: f32,                          // bucket size
+    abuse_drops: u32,                    // drops per ABUSE_WINDOW before Disconnect
+    tokens: f32,
+    last_refill: Option<Instant>,
+    drop_window: Option<(Instant, u32)>, // (window start, drops in it)
+    pub dropped: u64,                    // frames dropped over the connection's life
+}
+
+impl RateLimiter {
+    pub fn new(rate: f32, burst: f32, abuse_drops: u32) -> Self {
+        let burst = burst.max(1.0);
+        Self {
+            rate: rate.max(0.0),
+            burst,
+            abuse_drops: abuse_drops.max(1),
+            tokens: burst,
+            last_refill: None,
+            drop_window: None,
+            dropped: 0,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let env = |name: &str| std::env::var(name).ok();
+        let rate = env("AVENLAB_MSG_RATE").and_then(|v| v.parse::<f32>().ok()).filter(|v| v.is_finite() && *v > 0.0);
+        let burst = env("AVENLAB_MSG_BURST").and_then(|v| v.parse::<f32>().ok()).filter(|v| v.is_finite() && *v >= 1.0);
+        let abuse = env("AVENLAB_MSG_ABUSE").and_then(|v| v.parse::<u32>().ok());
+        Self::new(
+            rate.unwrap_or(DEFAULT_RATE),
+            burst.unwrap_or(DEFAULT_BURST),
+            abuse.unwrap_or(DEFAULT_ABUSE_DROPS),
+        )
+    }
+
+    /// One frame arrived at `now`.
+    pub fn check(&mut self, now: Instant) -> RateVerdict {
+        let elapsed = self.last_refill.map_or(0.0, |at| now.duration_since(at).as_secs_f32());
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        self.last_refill = Some(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return RateVerdict::Allow;
+        }
+
+        self.dropped += 1;
+        let (start, drops) = self.drop_window.get_or_insert((now, 0));
+        if now.duration_since(*start) >= ABUSE_WINDOW {
+            *start = now;
+            *drops = 0;
+        }
+        *drops += 1;
+        if *drops > self.abuse_drops { RateVerdict::Disconnect } else { RateVerdict::Drop }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({ "dropped": self.dropped })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `frames` frames spread evenly over `secs` from `start`; returns the
+    /// verdict counts (allowed, dropped, disconnect).
+    fn flood(rate: &mut RateLimiter, start: Instant, frames: u32, secs: f32) -> (u32, u32, u32) {
+        let mut counts = (0, 0, 0);
+        for i in 0..frames {
+            let now = start + Duration::from_secs_f32(secs).mul_f64(i as f64 / frames as f64);
+            match rate.check(now) {
+                RateVerdict::Allow => counts.0 += 1,
+                RateVerdict::Drop => counts.1 += 1,
+                RateVerdict::Disconnect => counts.2 += 1,
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn an_empty_bucket_drops_until_it_refills() {
+        let mut rate = RateLimiter::new(10.0, 3.0, 100);
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(rate.check(start), RateVerdict::Allow);
+        }
+        assert_eq!(rate.check(start), RateVerdict::Drop, "burst spent");
+        assert_eq!(rate.check(start + Duration::from_millis(50)), RateVerdict::Drop, "half a token");
+        assert_eq!(rate.check(start + Duration::from_millis(110)), RateVerdict::Allow, "one token back");
+        assert_eq!(rate.check(start + Duration::from_millis(110)), RateVerdict::Drop);
+
+        // A long pause refills to the burst, no further
+        let later = start + Duration::from_secs(10);
+        for _ in 0..3 {
+            assert_eq!(rate.check(later), RateVerdict::Allow);
+        }
+        assert_eq!(rate.check(later), RateVerdict::Drop);
+        assert_eq!(rate.dropped, 4);
+        assert_eq!(rate.to_json()["dropped"], 4);
+    }
+
+    #[test]
+    fn a_steady_client_under_the_rate_is_never_dropped() {
+        let mut rate = RateLimiter::new(DEFAULT_RATE, DEFAULT_BURST, DEFAULT_ABUSE_DROPS);
+        let (allowed, dropped, disconnect) = flood(&mut rate, Instant::now(), 120 * 5, 5.0);
+        assert_eq!((allowed, dropped, disconnect), (600, 0, 0));
+    }
+
+    #[test]
+    fn a_flood_only_lets_the_rate_through_and_is_cut_off() {
+        // 5000 frames/s against 100/s: the first second passes burst + rate
+        let mut rate = RateLimiter::new(100.0, 20.0, 1000);
+        let start = Instant::now();
+        let (allowed, dropped, disconnect) = flood(&mut rate, start, 1000, 0.2);
+        assert_eq!(disconnect, 0, "under the abuse threshold so far");
+        assert!((38..=42).contains(&allowed), "burst 20 + 0.2 s × 100, got {allowed}");
+        assert_eq!(allowed + dropped, 1000);
+
+        // Keep flooding: the 1001st drop inside one second closes the connection
+        let mut verdict = RateVerdict::Allow;
+        let mut frames = 0;
+        while verdict != RateVerdict::Disconnect && frames < 10_000 {
+            frames += 1;
+            verdict = rate.check(start + Duration::from_secs_f32(0.2 + frames as f32 / 5000.0));
+        }
+        assert_eq!(verdict, RateVerdict::Disconnect);
+        assert!(rate.dropped > 1000 && rate.dropped < 1100, "cut off at the threshold, dropped {}", rate.dropped);
+    }
+
+    #[test]
+    fn drops_spread_over_seconds_stay_below_the_abuse_threshold() {
+        // 10 drops a second against a threshold of 20: never a disconnect
+        let mut rate = RateLimiter::new(10.0, 1.0, 20);
+        let (allowed, dropped, disconnect) = flood(&mut rate, Instant::now(), 200, 10.0);
+        assert_eq!(disconnect, 0);
+        assert!((95..=105).contains(&allowed), "the rate still applies, got {allowed}");
+        assert_eq!(allowed + dropped, 200);
+    }
+}
@@ -0,0 +1,229 @@
+// ==============================================================================
+// read_model.rs — LOCK-FREE, READ-ONLY VIEW OF THE PHYSICS WORLD
+// ------------------------------------------------------------------------------
+// Read-only consumers (admin HTTP, metrics, bots' sensing, interest
+// management) should not await Mutex<PhysicsWorld> and compete with the tick.
+// At the end of each tick the loop captures an immutable WorldReadModel and
+// publishes it into a ReadModelSlot (arc-swap):
+//
+//   - publish() swaps a pointer; it never waits for readers
+//   - load()    returns an Arc to the latest model; it never waits for the
+//               writer, and the model it holds stays valid (and unchanged)
+//               however long the reader keeps it
+//
+// Everything in one model is captured from the same tick, under the physics
+// lock, right after step().
+// ==============================================================================
+
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use serde::Serialize;
+
+use crate::aven_tire::state::TireState;
+use crate::physics::PhysicsWorld;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WheelSummary {
+    pub id: String,
+    pub grounded: bool,
+    pub compression: f32, // m (0 while airborne)
+    pub spin_rpm: f32,
+    pub tire_state: &'static str, // "grip" | "slide" | "lock"
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EntityReadModel {
+    pub id: String,
+    pub position: [f32; 3],
+    pub rotation: [f32; 4], // quaternion [x, y, z, w]
+    pub linvel: [f32; 3],
+    pub angvel: [f32; 3],
+    pub speed: f32, // m/s
+    pub wheels: Vec<WheelSummary>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WorldReadModel {
+    pub tick: u64,
+    pub entities: Vec<EntityReadModel>, // sorted by id
+}
+
+fn tire_state_str(state: TireState) -> &'static str {
+    match state {
+        TireState::Grip => "grip",
+        TireState::Slide => "slide",
+        TireState::Lock => "lock",
+    }
+}
+
+impl WorldReadModel {
+    /// Snapshot every vehicle after `tick` has been stepped.
+    pub fn capture(tick: u64, phys: &PhysicsWorld) -> Self {
+        let mut entities: Vec<EntityReadModel> = phys
+            .vehicles
+            .iter()
+            .filter_map(|(id, vehicle)| {
+                let body = phys.bodies.get(vehicle.body)?;
+                let (pos, rot) = (body.translation(), body.rotation());
+                let (lv, av) = (body.linvel(), body.angvel());
+
+                let wheels = phys
+                    .wheels
+                    .get(&vehicle.body)
+                    .map(|wheels| {
+                        wheels
+                            .iter()
+                            .map(|w| WheelSummary {
+                                id: w.debug_id.clone(),
+                                grounded: w.kerb.compression().is_some(),
+                                compression: w.kerb.compression().unwrap_or(0.0),
+                                spin_rpm: w.wheel_state.spin_rpm,
+                                tire_state: tire_state_str(w.tire_state),
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                Some(EntityReadModel {
+                    id: id.clone(),
+                    position: [pos.x, pos.y, pos.z],
+                    rotation: [rot.i, rot.j, rot.k, rot.w],
+                    linvel: [lv.x, lv.y, lv.z],
+                    angvel: [av.x, av.y, av.z],
+                    speed: lv.norm(),
+                    wheels,
+                })
+            })
+            .collect();
+        entities.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Self { tick, entities }
+    }
+
+    pub fn entity(&self, id: &str) -> Option<&EntityReadModel> {
+        self.entities
+            .binary_search_by(|e| e.id.as_str().cmp(id))
+            .ok()
+            .map(|i| &self.entities[i])
+    }
+}
+
+// ==========================================================
+// Single-writer (tick loop), many-reader slot
+// ==========================================================
+pub struct ReadModelSlot {
+    current: ArcSwap<WorldReadModel>,
+}
+
+impl Default for ReadModelSlot {
+    fn default() -> Self {
+        Self { current: ArcSwap::from_pointee(WorldReadModel::default()) }
+    }
+}
+
+impl ReadModelSlot {
+    pub fn publish(&self, model: WorldReadModel) {
+        self.current.store(Arc::new(model));
+    }
+
+    pub fn load(&self) -> Arc<WorldReadModel> {
+        self.current.load_full()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::DEFAULT_VEHICLE;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn capture_matches_the_world_it_was_taken_from() {
+        let mut phys = PhysicsWorld::new();
+        for (id, x) in [("b", 5.0), ("a", -5.0)] {
+            phys.spawn_vehicle_for_player(id.into(), [x, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        }
+        phys.apply_player_input("a", 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+        for _ in 0..30 {
+            phys.step(DT);
+        }
+
+        let model = WorldReadModel::capture(30, &phys);
+        assert_eq!(model.tick, 30);
+        assert_eq!(model.entities.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+        for e in &model.entities {
+            let body = &phys.bodies[phys.vehicles[&e.id].body];
+            let t = body.translation();
+            assert_eq!(e.position, [t.x, t.y, t.z]);
+            assert_eq!(e.speed, body.linvel().norm());
+            assert_eq!(e.wheels.len(), 4);
+            for w in e.wheels.iter().filter(|w| !w.grounded) {
+                assert_eq!(w.compression, 0.0);
+            }
+        }
+        assert!(model.entity("a").unwrap().speed > model.entity("b").unwrap().speed);
+        assert!(model.entity("c").is_none());
+    }
+
+    #[test]
+    fn a_held_model_survives_later_publishes() {
+        let mut phys = PhysicsWorld::new();
+        phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        phys.apply_player_input("p", 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+
+        let slot = ReadModelSlot::default();
+        assert_eq!(slot.load().tick, 0);
+        assert!(slot.load().entities.is_empty());
+
+        phys.step(DT);
+        slot.publish(WorldReadModel::capture(1, &phys));
+        let held = slot.load();
+        let held_position = held.entity("p").unwrap().position;
+
+        // The writer keeps publishing while a reader holds tick 1
+        for tick in 2..=60 {
+            phys.step(DT);
+            slot.publish(WorldReadModel::capture(tick, &phys));
+        }
+        assert_eq!(held.tick, 1);
+        assert_eq!(held.entity("p").unwrap().position, held_position);
+        assert_eq!(slot.load().tick, 60);
+        assert_ne!(slot.load().entity("p").unwrap().position, held_position);
+    }
+
+    #[test]
+    fn readers_on_other_threads_always_see_whole_ticks() {
+        let slot = Arc::new(ReadModelSlot::default());
+        let mut phys = PhysicsWorld::new();
+        phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        phys.step(DT);
+        let template = WorldReadModel::capture(0, &phys);
+
+        let reader = {
+            let slot = slot.clone();
+            std::thread::spawn(move || {
+                let mut last = 0;
+                while last < 500 {
+                    let model = slot.load();
+                    assert!(model.tick >= last, "ticks never go backwards");
+                    // Every field was written from the same tick (see publish below)
+                    if let Some(e) = model.entity("p") {
+                        assert_eq!(e.position[0], model.tick as f32);
+                    }
+                    last = model.tick;
+                }
+            })
+        };
+
+        // The writer never waits on the reader
+        for tick in 1..=500 {
+            let mut model = template.clone();
+            model.tick = tick;
+            model.entities[0].position[0] = tick as f32;
+            slot.publish(model);
+        }
+        reader.join().unwrap();
+    }
+}
@@ -0,0 +1,525 @@
+// ==============================================================================
+// recording.rs — EVENT-SOURCED INPUT RECORDING + REPLAY VERIFICATION
+// ------------------------------------------------------------------------------
+// When AVENLAB_RECORD=<path> is set, the server appends typed JSON-lines records:
+// - spawn    : vehicle created (tick it is first stepped on, spawn position and
+//              yaw, built-in config name; older recordings without one replay
+//              gt86, without a yaw face +Z)
+// - despawn  : vehicle removed
+// - input    : inputs a vehicle CONSUMED on a tick (only written when changed)
+// - seed     : GameRng seed (first record; replays seed plugins with it)
+// - tick_rate: AVENLAB_TICK_HZ the server ran at (replays step at 1/hz)
+// - payload  : admin cargo change (set_payload; plugin pickups replay themselves)
+// - respawn  : client "respawn" (PhysicsWorld::reset_vehicle at the position / yaw)
+// - rebuild  : body rebuilt elsewhere, config / payload kept (reset_world,
+//              room move; PhysicsWorld::respawn_vehicle_for_player)
+// - kick     : admin kick (or a failed reset_world rebuild): vehicle removed
+// - ability  : magnet pull / push applied for one tick (target, repel)
+// - tick_divisor: vehicle moved onto a room rate of every n-th tick
+//              (AVENLAB_ROOM_TICK_HZ; PhysicsWorld::set_tick_divisor)
+// - terrain  : heightfield file the server loaded (path + FNV-1a of its bytes)
+// - map      : map file the server loaded (path + FNV-1a of its bytes)
+// - snapshot : world checksum after the tick was stepped
+//
+// Ticks are the value of SharedGameState::tick AFTER the step, i.e. the same
+// tick number the client sees in the snapshot broadcast.
+//
+// verify_recording(path):
+//   Loads the recorded terrain / map (refusing files whose hash changed),
+//   replays the events through a fresh PhysicsWorld at the recorded tick rate
+//   and compares per-tick checksums against the recorded snapshots, reporting
+//   the first divergent tick. This is the nondeterminism regression catcher.
+//   A recording without a tick_rate record is refused. Plugins from
+//   AVENLAB_PLUGINS run during replay too, so verify with the same plugin
+//   list the recording server used.
+// ==============================================================================
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::physics::{DEFAULT_VEHICLE, MAGNET_FORCE_N, PhysicsWorld};
+use crate::plugins::PluginHost;
+use crate::game_rng::GameRng;
+use crate::tick_rate::TickRate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RecordedAxes {
+    pub throttle: f32,
+    pub steer: f32,
+    pub brake: f32,
+    pub ascend: f32,
+    pub pitch: f32,
+    pub yaw: f32,
+    pub roll: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Record {
+    Spawn {
+        tick: u64,
+        entity_id: String,
+        position: [f32; 3],
+        #[serde(default)]
+        yaw: f32,
+        #[serde(default = "default_vehicle")]
+        vehicle: String,
+    },
+    Despawn { tick: u64, entity_id: String },
+    Input { tick: u64, entity_id: String, axes: RecordedAxes },
+    Snapshot { tick: u64, checksum: u64 },
+    Timescale { tick: u64, scale: f32 },
+    Payload { tick: u64, entity_id: String, mass: f32, offset: [f32; 3] },
+    Respawn {
+        tick: u64,
+        entity_id: String,
+        position: [f32; 3],
+        #[serde(default)]
+        yaw: f32,
+    },
+    Rebuild { tick: u64, entity_id: String, position: [f32; 3], yaw: f32 },
+    Kick { tick: u64, entity_id: String },
+    Ability { tick: u64, entity_id: String, target: String, repel: bool },
+    TickDivisor { tick: u64, entity_id: String, divisor: u32 },
+    Seed { seed: u64 },
+    TickRate { hz: u32 },
+    Terrain { path: String, hash: u64 },
+    Map { path: String, hash: u64 },
+}
+
+fn default_vehicle() -> String {
+    DEFAULT_VEHICLE.to_string()
+}
+
+// ==========================================================
+// World checksum (FNV-1a over vehicle rigid-body state)
+// ==========================================================
+pub(crate) const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+pub(crate) fn fnv_bytes(mut h: u64, bytes: &[u8]) -> u64 {
+    for b in bytes {
+        h ^= *b as u64;
+        h = h.wrapping_mul(FNV_PRIME);
+    }
+    h
+}
+
+/// FNV-1a of a file's bytes (terrain / map records).
+pub fn file_hash(path: &str) -> Result<u64, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+    Ok(fnv_bytes(FNV_OFFSET, &bytes))
+}
+
+/// Order-independent of HashMap iteration: vehicles are hashed sorted by id.
+pub fn world_checksum(phys: &PhysicsWorld) -> u64 {
+    let mut ids: Vec<&String> = phys.vehicles.keys().collect();
+    ids.sort();
+
+    let mut h = FNV_OFFSET;
+    for id in ids {
+        let Some(body) = phys.bodies.get(phys.vehicles[id].body) else { continue };
+        h = fnv_bytes(h, id.as_bytes());
+
+        let t = body.translation();
+        let r = body.rotation();
+        let lv = body.linvel();
+        let av = body.angvel();
+        for v in [t.x, t.y, t.z, r.i, r.j, r.k, r.w, lv.x, lv.y, lv.z, av.x, av.y, av.z] {
+            h = fnv_bytes(h, &v.to_bits().to_le_bytes());
+        }
+    }
+    h
+}
+
+// ==========================================================
+// Recorder (server side)
+// ==========================================================
+pub struct Recorder {
+    out: BufWriter<File>,
+    last_inputs: HashMap<String, RecordedAxes>,
+}
+
+impl Recorder {
+    pub fn create(path: &str) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).write(true).truncate(true).open(path)?;
+        Ok(Self { out: BufWriter::new(file), last_inputs: HashMap::new() })
+    }
+
+    /// Build a recorder from AVENLAB_RECORD, if set.
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var("AVENLAB_RECORD").ok()?;
+        match Self::create(&path) {
+            Ok(r) => {
+                println!("⏺  Recording inputs + checksums to {}", path);
+                Some(r)
+            }
+            Err(e) => {
+                eprintln!("⚠️ Could not open recording file {}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    fn write(&mut self, record: &Record) {
+        if let Ok(line) = serde_json::to_string(record) {
+            let _ = writeln!(self.out, "{}", line);
+        }
+    }
+
+    pub fn record_seed(&mut self, seed: u64) {
+        self.write(&Record::Seed { seed });
+    }
+
+    pub fn record_tick_rate(&mut self, hz: u32) {
+        self.write(&Record::TickRate { hz });
+    }
+
+    /// Terrain / map file the world was built with (main.rs, before the
+    /// first tick).
+    pub fn record_terrain(&mut self, path: &str) {
+        if let Ok(hash) = file_hash(path) {
+            self.write(&Record::Terrain { path: path.to_string(), hash });
+        }
+    }
+
+    pub fn record_map(&mut self, path: &str) {
+        if let Ok(hash) = file_hash(path) {
+            self.write(&Record::Map { path: path.to_string(), hash });
+        }
+    }
+
+    pub fn record_spawn(&mut self, tick: u64, entity_id: &str, position: [f32; 3], yaw: f32, vehicle: &str) {
+        self.write(&Record::Spawn { tick, entity_id: entity_id.to_string(), position, yaw, vehicle: vehicle.to_string() });
+    }
+
+    pub fn record_timescale(&mut self, tick: u64, scale: f32) {
+        self.write(&Record::Timescale { tick, scale });
+    }
+
+    pub fn record_payload(&mut self, tick: u64, entity_id: &str, mass: f32, offset: [f32; 3]) {
+        self.write(&Record::Payload { tick, entity_id: entity_id.to_string(), mass, offset });
+    }
+
+    pub fn record_respawn(&mut self, tick: u64, entity_id: &str, position: [f32; 3], yaw: f32) {
+        self.write(&Record::Respawn { tick, entity_id: entity_id.to_string(), position, yaw });
+    }
+
+    pub fn record_rebuild(&mut self, tick: u64, entity_id: &str, position: [f32; 3], yaw: f32) {
+        self.last_inputs.remove(entity_id); // the new body starts at rest, inputs cleared
+        self.write(&Record::Rebuild { tick, entity_id: entity_id.to_string(), position, yaw });
+    }
+
+    pub fn record_kick(&mut self, tick: u64, entity_id: &str) {
+        self.last_inputs.remove(entity_id);
+        self.write(&Record::Kick { tick, entity_id: entity_id.to_string() });
+    }
+
+    pub fn record_ability(&mut self, tick: u64, entity_id: &str, target: &str, repel: bool) {
+        self.write(&Record::Ability { tick, entity_id: entity_id.to_string(), target: target.to_string(), repel });
+    }
+
+    pub fn record_tick_divisor(&mut self, tick: u64, entity_id: &str, divisor: u32) {
+        self.write(&Record::TickDivisor { tick, entity_id: entity_id.to_string(), divisor });
+    }
+
+    pub fn record_despawn(&mut self, tick: u64, entity_id: &str) {
+        self.last_inputs.remove(entity_id);
+        self.write(&Record::Despawn { tick, entity_id: entity_id.to_string() });
+    }
+
+    /// Record the inputs each vehicle is about to consume on `tick`.
+    pub fn record_inputs(&mut self, tick: u64, phys: &PhysicsWorld) {
+        let mut ids: Vec<&String> = phys.vehicles.keys().collect();
+        ids.sort();
+
+        for id in ids {
+            let v = &phys.vehicles[id];
+            let axes = RecordedAxes {
+                throttle: v.throttle,
+                steer: v.steer,
+                brake: v.brake,
+                ascend: v.ascend,
+                pitch: v.pitch,
+                yaw: v.yaw,
+                roll: v.roll,
+            };
+            if self.last_inputs.get(id) == Some(&axes) {
+                continue;
+            }
+            self.last_inputs.insert(id.clone(), axes);
+            self.write(&Record::Input { tick, entity_id: id.clone(), axes });
+        }
+    }
+
+    pub fn record_snapshot(&mut self, tick: u64, phys: &PhysicsWorld) {
+        let checksum = world_checksum(phys);
+        self.write(&Record::Snapshot { tick, checksum });
+        let _ = self.out.flush();
+    }
+}
+
+// ==========================================================
+// Verification (offline replay)
+// ==========================================================
+#[derive(Debug)]
+pub struct VerifyReport {
+    pub ticks_checked: u64,
+    pub first_divergence: Option<Divergence>,
+}
+
+#[derive(Debug)]
+pub struct Divergence {
+    pub tick: u64,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+pub fn load_records(path: &str) -> Result<Vec<Record>, String> {
+    let file = File::open(path).map_err(|e| format!("open {}: {}", path, e))?;
+    let mut records = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("read {}: {}", path, e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let rec = serde_json::from_str::<Record>(&line)
+            .map_err(|e| format!("{}:{}: {}", path, i + 1, e))?;
+        records.push(rec);
+    }
+    Ok(records)
+}
+
+/// Recorded terrain / map files into `phys`, each checked against its hash.
+fn load_world_files(phys: &mut PhysicsWorld, records: &[Record]) -> Result<(), String> {
+    for rec in records {
+        let (Record::Terrain { path, hash } | Record::Map { path, hash }) = rec else { continue };
+        let actual = file_hash(path)?;
+        if actual != *hash {
+            return Err(format!("{} changed since it was recorded (hash {:016x}, recorded {:016x})", path, actual, hash));
+        }
+        if matches!(rec, Record::Terrain { .. }) {
+            let t = crate::terrain::load_file(path)?;
+            phys.load_heightfield(t.nx, t.ny, t.width, t.depth, t.heights)?;
+        } else {
+            // The recording server already refused maps over its spawn areas
+            phys.load_map(path, &[])?;
+        }
+    }
+    Ok(())
+}
+
+/// Replay records through a fresh world stepped at the recording's tick rate.
+pub fn verify_records(records: &[Record]) -> Result<VerifyReport, String> {
+    // Group per tick, keeping file order within a tick (spawn order = handle order)
+    let mut events: BTreeMap<u64, Vec<&Record>> = BTreeMap::new();
+    let mut expected: BTreeMap<u64, u64> = BTreeMap::new();
+    let mut seed = 0; // recordings from before seeds were recorded never drew randomness
+    let mut dt = None;
+    for rec in records {
+        match rec {
+            Record::Snapshot { tick, checksum } => { expected.insert(*tick, *checksum); }
+            Record::Seed { seed: s } => seed = *s,
+            Record::TickRate { hz } => dt = Some(TickRate::new(*hz).dt()),
+            Record::Terrain { .. } | Record::Map { .. } => {}
+            Record::Spawn { tick, .. }
+            | Record::Despawn { tick, .. }
+            | Record::Input { tick, .. }
+            | Record::Timescale { tick, .. }
+            | Record::Payload { tick, .. }
+            | Record::Respawn { tick, .. }
+            | Record::Rebuild { tick, .. }
+            | Record::Kick { tick, .. }
+            | Record::Ability { tick, .. }
+            | Record::TickDivisor { tick, .. } => events.entry(*tick).or_default().push(rec),
+        }
+    }
+    // Stepping at a guessed rate would report a bogus divergence on tick one
+    let dt = dt.ok_or("recording has no tick_rate record")?;
+
+    let mut phys = PhysicsWorld::new();
+    crate::vehicle_presets::load_into(&mut phys); // recorded spawns may name file presets
+    load_world_files(&mut phys, records)?;
+    let mut plugins = PluginHost::from_env(&GameRng::new(seed));
+    let mut report = VerifyReport { ticks_checked: 0, first_divergence: None };
+
+    let (Some(&first), Some(&last)) = (expected.keys().next(), expected.keys().next_back()) else {
+        return Ok(report);
+    };
+    let start = events.keys().next().copied().unwrap_or(first).min(first);
+
+    for tick in start..=last {
+        for rec in events.get(&tick).into_iter().flatten() {
+            match rec {
+                Record::Spawn { entity_id, position, yaw, vehicle, .. } => {
+                    if phys.spawn_vehicle_for_player(entity_id.clone(), *position, *yaw, vehicle).is_ok() {
+                        plugins.entity_spawned(entity_id);
+                    }
+                }
+                Record::Despawn { entity_id, .. } | Record::Kick { entity_id, .. } => {
+                    phys.remove_vehicle(entity_id);
+                    plugins.entity_removed(entity_id);
+                }
+                Record::Input { entity_id, axes, .. } => phys.apply_player_input(
+                    entity_id,
+                    axes.throttle,
+                    axes.steer,
+                    axes.brake,
+                    axes.ascend,
+                    axes.pitch,
+                    axes.yaw,
+                    axes.roll,
+                ),
+                Record::Timescale { scale, .. } => {
+                    phys.set_timestep_scale(*scale);
+                }
+                Record::Payload { entity_id, mass, offset, .. } => {
+                    let _ = phys.set_payload(entity_id, *mass, *offset);
+                }
+                Record::TickDivisor { entity_id, divisor, .. } => {
+                    phys.set_tick_divisor(entity_id, *divisor);
+                }
+                Record::Respawn { entity_id, position, yaw, .. } => {
+                    let _ = phys.reset_vehicle(entity_id, *position, *yaw);
+                }
+                Record::Rebuild { entity_id, position, yaw, .. } => {
+                    if phys.respawn_vehicle_for_player(entity_id, *position, *yaw).is_ok() {
+                        plugins.entity_removed(entity_id);
+                        plugins.entity_spawned(entity_id);
+                    }
+                }
+                Record::Ability { entity_id, target, repel, .. } => {
+                    if *repel {
+                        phys.apply_magnetic_repulsion(entity_id, target, MAGNET_FORCE_N, dt);
+                    } else {
+                        phys.apply_magnetic_attraction(entity_id, target, MAGNET_FORCE_N, dt);
+                    }
+                }
+                Record::Snapshot { .. }
+                | Record::Seed { .. }
+                | Record::TickRate { .. }
+                | Record::Terrain { .. }
+                | Record::Map { .. } => {}
+            }
+        }
+
+        let plugin_dt = phys.scaled_dt(dt);
+        plugins.run_tick(&mut phys, &|_| None, tick, plugin_dt);
+        phys.step(dt);
+        phys.clear_debug_overlay();
+
+        if let Some(&want) = expected.get(&tick) {
+            report.ticks_checked += 1;
+            let got = world_checksum(&phys);
+            if got != want {
+                report.first_divergence = Some(Divergence { tick, expected: want, actual: got });
+                break;
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+pub fn verify_recording(path: &str) -> Result<VerifyReport, String> {
+    let records = load_records(path)?;
+    verify_records(&records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::health::HealthState;
+    use crate::read_model::ReadModelSlot;
+    use crate::state::{MailboxInput, SharedGameState};
+
+    /// A recording of a real session: two cars driving, a magnet pull, a
+    /// world reset (rebuilt bodies) and a kick, through the server's own
+    /// tick and admin paths.
+    fn record_session(name: &str) -> Vec<Record> {
+        let path = std::env::temp_dir().join(format!("avenlab-{}-{}.jsonl", name, std::process::id()));
+        let path = path.to_str().unwrap().to_string();
+        let tick_rate = TickRate::default();
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(tick_rate);
+        let mut rec = Recorder::create(&path).unwrap();
+        rec.record_tick_rate(tick_rate.hz());
+        game.recorder = Some(rec);
+        let health = HealthState::new(0);
+        let read_model = ReadModelSlot::default();
+
+        let _a = game.join_test_player(&mut phys, "a");
+        let _b = game.join_test_player(&mut phys, "b");
+        let _kicked = game.register_kick("b");
+        let (a, b) = (game.input_sender("a"), game.input_sender("b"));
+        for tick in 0..90 {
+            a.send(MailboxInput::Axes([Some(1.0), Some(0.3), None, None, None, None, None], None, None));
+            b.send(MailboxInput::Axes([Some(0.5), Some(-0.2), None, None, None, None, None], None, None));
+            if tick < 20 {
+                a.send(MailboxInput::Ability { repel: false, target: "b".to_string() });
+            }
+            match tick {
+                40 => { game.reset_world(&mut phys); }
+                60 => game.kick_player(&mut phys, "b").unwrap(),
+                _ => {}
+            }
+            crate::run_tick(&mut phys, &mut game, &health, &read_model, tick_rate.dt());
+        }
+        game.recorder = None; // flush
+        let records = load_records(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        records
+    }
+
+    #[test]
+    fn replay_matches_a_session_with_abilities_resets_and_kicks() {
+        let records = record_session("replay");
+        for kind in ["ability", "rebuild", "kick"] {
+            let seen = records.iter().any(|r| serde_json::to_value(r).unwrap()["type"] == kind);
+            assert!(seen, "no {} record", kind);
+        }
+
+        let report = verify_records(&records).unwrap();
+        assert!(report.first_divergence.is_none(), "{:?}", report.first_divergence);
+        assert_eq!(report.ticks_checked, 90);
+    }
+
+    #[test]
+    fn replay_reports_the_first_divergent_tick() {
+        let mut records = record_session("diverge");
+        for rec in &mut records {
+            if let Record::Snapshot { tick: 50, checksum } = rec {
+                *checksum ^= 1;
+            }
+        }
+
+        let report = verify_records(&records).unwrap();
+        let divergence = report.first_divergence.expect("tampered tick diverges");
+        assert_eq!(divergence.tick, 50);
+        assert_eq!(report.ticks_checked, 50);
+    }
+
+    #[test]
+    fn replay_refuses_a_map_that_changed_since_recording() {
+        let path = std::env::temp_dir().join(format!("avenlab-map-{}.json", std::process::id()));
+        let path = path.to_str().unwrap().to_string();
+        std::fs::write(&path, r#"{"objects":[]}"#).unwrap();
+        let hash = file_hash(&path).unwrap();
+        std::fs::write(&path, r#"{"objects":[{"kind":"box"}]}"#).unwrap();
+
+        let records = vec![Record::TickRate { hz: 60 }, Record::Map { path: path.clone(), hash }];
+        let err = verify_records(&records).unwrap_err();
+        let _ = std::fs::remove_file(&path);
+        assert!(err.contains("changed since it was recorded"), "{}", err);
+    }
+
+    #[test]
+    fn replay_refuses_a_recording_without_a_tick_rate() {
+        let mut records = record_session("no-rate");
+        records.retain(|r| !matches!(r, Record::TickRate { .. }));
+        assert!(verify_records(&records).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+// ==============================================================================
+// recovery.rs — AUTOMATIC FLIP DETECTION + RECOVERY
+// ------------------------------------------------------------------------------
+// A car on its roof or side has its suspension rays pointing at the sky and no
+// way to move. Per vehicle (Vehicle::flip), once per tick after the Rapier step
+// (PhysicsWorld::recover_flipped_vehicles):
+//
+//   flipped  : chassis up · world up < FLIPPED_UP_DOT while nearly stationary
+//              (speed < FLIPPED_MAX_SPEED, spin < FLIPPED_MAX_ANGVEL); the
+//              timer resets as soon as either stops holding
+//   recover  : flipped for config.flip_timeout_s with config.auto_flip_recover
+//              on → righted in place: lifted RECOVER_LIFT_M, upright with the
+//              same heading, velocities zeroed
+//
+// Rolling over mid-crash doesn't count (not stationary), so only cars that
+// have come to rest upside down get picked up. The debug overlay's "flipped"
+// field is the time counted so far (null while upright).
+// ==============================================================================
+
+use rapier3d::prelude::*;
+
+const FLIPPED_UP_DOT: f32 = 0.2;       // ~78° of roll / pitch
+const FLIPPED_MAX_SPEED: f32 = 1.0;    // m/s
+const FLIPPED_MAX_ANGVEL: f32 = 1.0;   // rad/s
+pub const RECOVER_LIFT_M: f32 = 0.5;   // clears the wheels once upright
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FlipState {
+    pub flipped_for: f32, // seconds the car has been counted as flipped (0 = upright / moving)
+}
+
+impl FlipState {
+    /// Feed one tick; true once the car has been flipped for `timeout` seconds.
+    pub fn update(&mut self, rotation: &Rotation<Real>, linvel: f32, angvel: f32, dt: f32, timeout: f32) -> bool {
+        let up_dot = (rotation * Vector::y()).y;
+        if up_dot < FLIPPED_UP_DOT && linvel < FLIPPED_MAX_SPEED && angvel < FLIPPED_MAX_ANGVEL {
+            self.flipped_for += dt;
+        } else {
+            self.flipped_for = 0.0;
+        }
+        self.flipped_for >= timeout
+    }
+
+    pub fn flipped(&self) -> bool {
+        self.flipped_for > 0.0
+    }
+}
+
+/// Upright orientation with the chassis's current heading (+Z forward). A car
+/// standing on its nose or tail has no horizontal forward; it gets the heading
+/// it would tip over to (roof side when on its nose, floor side on its tail).
+pub fn upright_rotation(rotation: &Rotation<Real>) -> Rotation<Real> {
+    let forward = rotation * Vector::z();
+    let heading = if forward.x.hypot(forward.z) > 1e-3 { forward } else { rotation * Vector::y() * -forward.y.signum() };
+    let yaw = heading.x.atan2(heading.z);
+    Rotation::from_axis_angle(&Vector::y_axis(), yaw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    fn heading(rotation: &Rotation<Real>) -> f32 {
+        let forward = rotation * Vector::z();
+        forward.x.atan2(forward.z)
+    }
+
+    #[test]
+    fn the_timer_runs_only_while_upside_down_and_at_rest() {
+        let roof = Rotation::from_axis_angle(&Vector::z_axis(), std::f32::consts::PI);
+        let upright = Rotation::identity();
+        let mut flip = FlipState::default();
+        for _ in 0..3 {
+            assert!(!flip.update(&roof, 0.2, 0.1, 0.25, 1.0));
+        }
+        assert!(flip.flipped());
+        assert!(flip.update(&roof, 0.2, 0.1, 0.25, 1.0), "one second on the roof");
+
+        // Moving, spinning or upright again each start the count over
+        for (rotation, linvel, angvel) in [(&roof, 2.0, 0.0), (&roof, 0.0, 3.0), (&upright, 0.0, 0.0)] {
+            let mut flip = FlipState { flipped_for: 0.9 };
+            assert!(!flip.update(rotation, linvel, angvel, DT, 1.0));
+            assert_eq!(flip, FlipState::default());
+        }
+        // On its side counts too
+        let side = Rotation::from_axis_angle(&Vector::z_axis(), std::f32::consts::FRAC_PI_2);
+        let mut flip = FlipState::default();
+        flip.update(&side, 0.0, 0.0, DT, 1.0);
+        assert!(flip.flipped());
+    }
+
+    #[test]
+    fn upright_rotation_keeps_the_heading() {
+        let yaw = 0.7;
+        let heading_rot = Rotation::from_axis_angle(&Vector::y_axis(), yaw);
+        let on_roof = heading_rot * Rotation::from_axis_angle(&Vector::z_axis(), std::f32::consts::PI);
+        let on_side = heading_rot * Rotation::from_axis_angle(&Vector::z_axis(), 1.4);
+        for rotation in [on_roof, on_side] {
+            let righted = upright_rotation(&rotation);
+            assert!(((righted * Vector::y()).y - 1.0).abs() < 1e-5, "upright");
+            assert!((heading(&righted) - yaw).abs() < 1e-5, "heading {}", heading(&righted));
+        }
+
+        // Resting on its nose (+Z down): the roof faces the way it tips over
+        let nose_down = heading_rot * Rotation::from_axis_angle(&Vector::x_axis(), std::f32::consts::FRAC_PI_2);
+        assert!((nose_down * Vector::z()).y < -0.99);
+        assert!((heading(&upright_rotation(&nose_down)) - yaw).abs() < 1e-5);
+        // On its tail: the floor side, the same heading again
+        let tail_down = heading_rot * Rotation::from_axis_angle(&Vector::x_axis(), -std::f32::consts::FRAC_PI_2);
+        assert!((tail_down * Vector::z()).y > 0.99);
+        assert!((heading(&upright_rotation(&tail_down)) - yaw).abs() < 1e-5);
+    }
+}
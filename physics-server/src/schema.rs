@@ -0,0 +1,253 @@
+// ==============================================================================
+// schema.rs — JSON SCHEMA OF THE WIRE PROTOCOL (--dump-schema)
+// ------------------------------------------------------------------------------
+// One JSON Schema (draft 2020-12) bundle of the typed wire payloads, for client
+// type codegen and for CI to diff against the last release:
+//
+//   physics-server --dump-schema [file]      (stdout without a file)
+//
+//   $defs.ClientMessage   every client → server message, tagged by "type"
+//                         (client_message.rs)
+//   $defs.ServerMessage   oneOf the typed server messages (snapshot, debug;
+//                         snapshot.rs), told apart by their "type" const
+//   $defs.<Type>          every type reachable from the roots below
+//                         (DebugOverlay, DebugRay, VehicleLayout, ..), named
+//                         after the Rust type
+//
+// Nothing is written by hand: each type derives schemars::JsonSchema next to
+// its serde derive, and schemars reads the same serde attributes (rename,
+// skip_serializing_if, tagging) the serializer uses, so the schema can't
+// drift from what goes on the wire. Messages still assembled with json!
+// (welcome, layout, events) join the bundle once they are typed.
+//
+// Server types are described as serialized and closed
+// (additionalProperties: false): a client generated from the bundle sees
+// exactly the fields the server writes. Client types are described as
+// deserialized and stay open, since serde ignores unknown fields; fields read
+// through client_message's non_null carry #[schemars(with = inner type)], so
+// the schema refuses null where the parser does.
+//
+// Keys come out sorted (serde_json maps), so the dump diffs cleanly.
+// ==============================================================================
+
+use schemars::Schema;
+use schemars::generate::{SchemaGenerator, SchemaSettings};
+use serde_json::{Map, Value, json};
+
+use crate::client_message::ClientMessage;
+use crate::physics::VehicleLayout;
+use crate::snapshot::{DebugMessage, SnapshotMessage};
+
+const TITLE: &str = "AvenLab wire protocol";
+
+/// Server → client messages and payloads, described as serialized.
+fn server_definitions() -> Map<String, Value> {
+    let mut generator = SchemaSettings::draft2020_12().for_serialize().into_generator();
+    let messages = server_messages(&mut generator);
+    generator.subschema_for::<VehicleLayout>(); // "vehicle_layout" layout, welcome
+    let mut defs = generator.take_definitions(true);
+    defs.insert("ServerMessage".to_string(), json!({ "oneOf": messages }));
+    for schema in defs.values_mut() {
+        close_objects(schema);
+    }
+    defs
+}
+
+/// `$ref`s of the typed server messages.
+fn server_messages(generator: &mut SchemaGenerator) -> Vec<Schema> {
+    vec![
+        generator.subschema_for::<SnapshotMessage>(),
+        generator.subschema_for::<DebugMessage>(),
+    ]
+}
+
+/// Client → server messages, described as deserialized.
+fn client_definitions() -> Map<String, Value> {
+    let mut generator = SchemaSettings::draft2020_12().for_deserialize().into_generator();
+    generator.subschema_for::<ClientMessage>();
+    generator.take_definitions(true)
+}
+
+/// additionalProperties: false on every object schema with properties.
+fn close_objects(schema: &mut Value) {
+    match schema {
+        Value::Object(map) => {
+            if map.contains_key("properties") && !map.contains_key("additionalProperties") {
+                map.insert("additionalProperties".to_string(), json!(false));
+            }
+            map.values_mut().for_each(close_objects);
+        }
+        Value::Array(items) => items.iter_mut().for_each(close_objects),
+        _ => {}
+    }
+}
+
+// ==========================================================
+// Bundle
+// ==========================================================
+pub fn protocol_schema() -> Value {
+    let settings = SchemaSettings::draft2020_12();
+    json!({
+        "$schema": settings.meta_schema,
+        "title": TITLE,
+        "$defs": client_definitions().into_iter().chain(server_definitions()).collect::<Map<String, Value>>(),
+    })
+}
+
+/// --dump-schema [file]: the bundle, pretty-printed.
+pub fn dump_schema(path: Option<&str>) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(&protocol_schema()).map_err(|e| e.to_string())? + "\n";
+    match path {
+        None => print!("{}", text),
+        Some(path) => {
+            std::fs::write(path, text).map_err(|e| format!("{}: {}", path, e))?;
+            println!("📜 Protocol schema written to {}", path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client_message::MESSAGE_TYPES;
+    use crate::physics::{DEFAULT_VEHICLE, PhysicsWorld};
+    use crate::snapshot::{ClientTx, OutFrame};
+    use crate::state::{EntityType, SharedGameState};
+    use crate::tick_rate::TickRate;
+    use tokio::sync::mpsc::UnboundedReceiver;
+
+    /// Validator for `#/$defs/<def>` of the bundle.
+    fn validator(root: &Value, def: &str) -> jsonschema::Validator {
+        let mut schema = root.clone();
+        schema["$ref"] = json!(format!("#/$defs/{}", def));
+        jsonschema::validator_for(&schema).unwrap_or_else(|e| panic!("{}: {}", def, e))
+    }
+
+    /// Join `id` the way net.rs does, minus the socket: client, entity, body.
+    fn join(game: &mut SharedGameState, phys: &mut PhysicsWorld, id: &str, x: f32) -> UnboundedReceiver<OutFrame> {
+        let (tx, rx) = ClientTx::channel();
+        game.register_client(id.to_string(), tx);
+        game.add_entity(id, EntityType::Vehicle);
+        let body = phys.spawn_vehicle_for_player(id.to_string(), [x, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        game.attach_body(id, body);
+        rx
+    }
+
+    fn texts(rx: &mut UnboundedReceiver<OutFrame>) -> Vec<Value> {
+        std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|frame| match frame {
+                OutFrame::Text(text) => Some(serde_json::from_str(&text).unwrap()),
+                OutFrame::Binary(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn captured_server_messages_match_the_schema() {
+        let root = protocol_schema();
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        let mut a = join(&mut game, &mut phys, "a", 0.0);
+        let mut b = join(&mut game, &mut phys, "b", 8.0);
+        game.velocity_clients.insert("b".to_string()); // b asks for the optional velocities
+        phys.apply_player_input("a", 1.0, 0.3, 0.0, 0.0, 0.0, 0.0, 0.0);
+        phys.watched_overlays = game.overlay_vehicles();
+        for _ in 0..60 {
+            phys.clear_debug_overlay();
+            phys.step(1.0 / 60.0);
+        }
+        game.broadcast_snapshot(&phys);
+        game.broadcast_debug_overlay(&phys);
+        game.broadcast_vehicle_layout(&phys, "a");
+
+        let (server, layout) = (validator(&root, "ServerMessage"), validator(&root, "VehicleLayout"));
+        let messages: Vec<Value> = texts(&mut a).into_iter().chain(texts(&mut b)).collect();
+        let mut seen = Vec::new();
+        for msg in &messages {
+            let checked = match msg["type"].as_str() {
+                Some("snapshot" | "debug") => server.validate(msg),
+                Some("vehicle_layout") => layout.validate(&msg["layout"]),
+                _ => continue,
+            };
+            checked.unwrap_or_else(|e| panic!("{}\n{}", e, msg));
+            seen.push(msg["type"].as_str().unwrap());
+        }
+        for kind in ["snapshot", "debug", "vehicle_layout"] {
+            assert!(seen.contains(&kind), "no {} captured", kind);
+        }
+        let velocities = messages.iter().any(|m| m["type"] == "snapshot" && m["data"]["players"][0].get("wy").is_some());
+        assert!(velocities, "b's snapshot carries velocities");
+    }
+
+    #[test]
+    fn the_schema_refuses_changed_server_messages() {
+        let root = protocol_schema();
+        let server = validator(&root, "ServerMessage");
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        let mut a = join(&mut game, &mut phys, "a", 0.0);
+        phys.step(1.0 / 60.0);
+        game.broadcast_snapshot(&phys);
+        let snapshot = texts(&mut a).pop().unwrap();
+        server.validate(&snapshot).unwrap();
+
+        // A field the schema doesn't know, a retyped one, a dropped one, another tag
+        for edit in [
+            |m: &mut Value| m["data"]["players"][0]["health"] = json!(100),
+            |m: &mut Value| m["data"]["players"][0]["x"] = json!("1.5"),
+            |m: &mut Value| m["data"]["players"][0]["rot"] = json!([0.0, 0.0, 1.0]),
+            |m: &mut Value| drop(m["data"].as_object_mut().unwrap().remove("tick")),
+            |m: &mut Value| m["type"] = json!("debug"),
+        ] {
+            let mut changed = snapshot.clone();
+            edit(&mut changed);
+            assert!(!server.is_valid(&changed), "accepted {}", changed);
+        }
+    }
+
+    #[test]
+    fn client_frames_match_the_schema_and_the_parser() {
+        let root = protocol_schema();
+        let schema = validator(&root, "ClientMessage");
+        // One variant per "type" the parser accepts
+        let variants = root["$defs"]["ClientMessage"]["oneOf"].as_array().unwrap();
+        let tags: Vec<&str> = variants.iter().filter_map(|v| v["properties"]["type"]["const"].as_str()).collect();
+        assert_eq!(tags, MESSAGE_TYPES);
+
+        let frames = [
+            (json!({ "type": "input", "throttle": 0.8, "steer": -0.2 }), true),
+            (json!({ "type": "input", "extra": "ignored" }), true),
+            (json!({ "type": "hello", "name": "ann", "caps": { "compression": "zstd" } }), true),
+            (json!({ "type": "admin", "token": "t", "set_timescale": 0.25 }), true),
+            (json!({ "type": "admin", "cmd": "set_payload", "mass": 80.0, "offset": [0.0, 0.5, -1.0] }), true),
+            (json!({ "type": "adjust", "brake_bias_delta": 0.02, "arb_front": 1.5 }), true),
+            (json!({ "type": "debug_subscribe", "categories": 3, "auto_lod": true }), true),
+            (json!({ "type": "respawn" }), true),
+            (json!({ "type": "input", "throttle": "1" }), false),
+            (json!({ "type": "input", "steer": null }), false),
+            (json!({ "type": "admin", "offset": [0.0, 0.5] }), false),
+            (json!({ "type": "switch_room", "room_id": -1 }), false),
+            (json!({ "type": "turret", "yaw": true }), false),
+            (json!({ "type": "warp" }), false),
+        ];
+        for (frame, valid) in frames {
+            assert_eq!(ClientMessage::parse(&frame.to_string()).is_ok(), valid, "parser on {}", frame);
+            assert_eq!(schema.is_valid(&frame), valid, "schema on {}", frame);
+        }
+    }
+
+    #[test]
+    fn the_dump_is_stable_and_self_contained() {
+        let root = protocol_schema();
+        assert_eq!(serde_json::to_string(&root).unwrap(), serde_json::to_string(&protocol_schema()).unwrap());
+        let defs = root["$defs"].as_object().unwrap();
+        for name in ["ClientMessage", "Caps", "AdminMessage", "ServerMessage", "SnapshotMessage", "SnapshotPlayer", "GhostEntry", "DebugMessage", "DebugOverlay", "DebugRay", "DebugSlipRay", "DebugWheel", "DebugChassis", "WheelSurface", "DebugTrail", "VehicleLayout", "WheelLayout", "Payload"] {
+            assert!(defs.contains_key(name), "no {} in the bundle", name);
+        }
+        // Every def compiles, which also resolves every $ref inside it
+        for name in defs.keys() {
+            validator(&root, name);
+        }
+    }
+}
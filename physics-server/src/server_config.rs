@@ -0,0 +1,152 @@
+// ==============================================================================
+// server_config.rs — LISTEN ADDRESS, TICK RATE, SNAPSHOT RATE, CAPACITY, MAP
+// ------------------------------------------------------------------------------
+// Built once in main.rs, before anything is bound or spawned; every value
+// comes from its command-line flag, else its env var, else the default:
+//
+//   --bind <ip>               AVENLAB_BIND_ADDR          0.0.0.0
+//   --port <port>             AVENLAB_PORT               9001
+//   --tick-hz <hz>            AVENLAB_TICK_HZ            60 (10..=120), alias --tick-rate
+//   --snapshot-divisor <n>    AVENLAB_SNAPSHOT_DIVISOR   1 (snapshot every n ticks)
+//   --max-clients <n>         AVENLAB_MAX_PLAYERS        0 = unlimited (join queue)
+//   --map <map.json>          AVENLAB_MAP_FILE           none
+//
+// Anything invalid (unparsable, tick rate 0 or out of range, port 0, fewer
+// than one snapshot a second, map file missing) is a readable error and exit
+// code 2 instead of a silent clamp or a panic later; a port that is already
+// taken fails the bind in main.rs with exit code 1. The effective config is
+// logged at startup ("⚙️").
+// ==============================================================================
+
+use std::net::{IpAddr, SocketAddr};
+
+use crate::tick_rate::{DEFAULT_TICK_HZ, TickRate};
+
+pub const DEFAULT_BIND_ADDR: &str = "0.0.0.0";
+pub const DEFAULT_PORT: u16 = 9001;
+
+pub const USAGE: &str = "usage: physics-server [--bind 0.0.0.0] [--port 9001] [--tick-hz 60] \
+[--snapshot-divisor 1] [--max-clients 0] [--map map.json] [--terrain heights.json] [--persist-session [file]]";
+
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub bind_addr: IpAddr,
+    pub port: u16,
+    pub tick_rate: TickRate,
+    pub snapshot_divisor: u32,  // snapshots go out every n-th tick
+    pub max_players: usize,     // 0 = unlimited
+    pub map_path: Option<String>,
+}
+
+impl ServerConfig {
+    /// Flags from `args` (unknown ones are left to main.rs), env otherwise.
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        Self::parse(args, |name| std::env::var(name).ok())
+    }
+
+    fn parse(args: &[String], env: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let setting = |flag: &str, var: &str| -> Result<Option<(String, String)>, String> {
+            match args.iter().position(|a| a == flag) {
+                Some(i) => match args.get(i + 1).filter(|v| !v.starts_with("--")) {
+                    Some(value) => Ok(Some((flag.to_string(), value.clone()))),
+                    None => Err(format!("{} needs a value", flag)),
+                },
+                None => Ok(env(var).filter(|v| !v.trim().is_empty()).map(|v| (var.to_string(), v))),
+            }
+        };
+        fn parsed<T: std::str::FromStr>(setting: Option<(String, String)>, what: &str) -> Result<Option<T>, String> {
+            setting
+                .map(|(source, value)| value.trim().parse().map_err(|_| format!("{}={:?} is not a valid {}", source, value, what)))
+                .transpose()
+        }
+
+        let bind_addr = parsed(setting("--bind", "AVENLAB_BIND_ADDR")?, "IP address")?
+            .unwrap_or_else(|| DEFAULT_BIND_ADDR.parse().expect("default bind address"));
+        let port: u16 = parsed(setting("--port", "AVENLAB_PORT")?, "port")?.unwrap_or(DEFAULT_PORT);
+        if port == 0 {
+            return Err("port must be 1..=65535".to_string());
+        }
+        let hz_flag = if args.iter().any(|a| a == "--tick-rate") { "--tick-rate" } else { "--tick-hz" };
+        let hz: u32 = parsed(setting(hz_flag, "AVENLAB_TICK_HZ")?, "tick rate")?.unwrap_or(DEFAULT_TICK_HZ);
+        let tick_rate = TickRate::try_new(hz)?;
+        let snapshot_divisor: u32 = parsed(setting("--snapshot-divisor", "AVENLAB_SNAPSHOT_DIVISOR")?, "divisor")?.unwrap_or(1);
+        if snapshot_divisor == 0 || snapshot_divisor > tick_rate.hz() {
+            return Err(format!(
+                "snapshot divisor {} at {} Hz: must be 1..={} (at least one snapshot a second)",
+                snapshot_divisor,
+                tick_rate.hz(),
+                tick_rate.hz()
+            ));
+        }
+        let max_players = parsed(setting("--max-clients", "AVENLAB_MAX_PLAYERS")?, "client count")?.unwrap_or(0);
+        let map_path = setting("--map", "AVENLAB_MAP_FILE")?.map(|(_, path)| path);
+        if let Some(path) = map_path.as_deref()
+            && !std::path::Path::new(path).is_file()
+        {
+            return Err(format!("map file {} not found", path));
+        }
+
+        Ok(Self { bind_addr, port, tick_rate, snapshot_divisor, max_players, map_path })
+    }
+
+    pub fn listen_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_addr, self.port)
+    }
+
+    /// Startup log line.
+    pub fn summary(&self) -> String {
+        format!(
+            "ws://{}, {} Hz (dt {:.4} s), snapshots every {} tick(s) ({:.1} Hz), max clients {}, map {}",
+            self.listen_addr(),
+            self.tick_rate.hz(),
+            self.tick_rate.dt(),
+            self.snapshot_divisor,
+            self.tick_rate.hz() as f32 / self.snapshot_divisor as f32,
+            if self.max_players == 0 { "unlimited".to_string() } else { self.max_players.to_string() },
+            self.map_path.as_deref().unwrap_or("none"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn tick_rate_zero_is_rejected() {
+        for flag in ["--tick-rate", "--tick-hz"] {
+            let err = ServerConfig::from_args(&args(&format!("physics-server {} 0", flag))).unwrap_err();
+            assert_eq!(err, "tick rate 0 Hz out of range (10..=120)");
+        }
+        let err = ServerConfig::parse(&[], |var| (var == "AVENLAB_TICK_HZ").then(|| "0".to_string())).unwrap_err();
+        assert_eq!(err, "tick rate 0 Hz out of range (10..=120)");
+    }
+
+    #[test]
+    fn flags_beat_env_and_bad_values_are_readable() {
+        let env = |var: &str| (var == "AVENLAB_PORT").then(|| "7000".to_string());
+        assert_eq!(ServerConfig::parse(&[], env).unwrap().port, 7000);
+        let config = ServerConfig::parse(&args("--port 7001 --tick-rate 30 --snapshot-divisor 2"), env).unwrap();
+        assert_eq!((config.port, config.tick_rate.hz(), config.snapshot_divisor), (7001, 30, 2));
+
+        let no_env = |_: &str| None;
+        assert_eq!(ServerConfig::parse(&args("--port nine"), no_env).unwrap_err(), "--port=\"nine\" is not a valid port");
+        assert_eq!(ServerConfig::parse(&args("--port 0"), no_env).unwrap_err(), "port must be 1..=65535");
+        assert_eq!(ServerConfig::parse(&args("--port"), no_env).unwrap_err(), "--port needs a value");
+        assert_eq!(ServerConfig::parse(&args("--map /no/such/map.json"), no_env).unwrap_err(), "map file /no/such/map.json not found");
+    }
+
+    #[tokio::test]
+    async fn a_port_already_bound_is_a_readable_error() {
+        let taken = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = taken.local_addr().unwrap().port();
+        let config = ServerConfig::from_args(&args(&format!("physics-server --bind 127.0.0.1 --port {}", port))).unwrap();
+        let err = crate::net::bind_websocket(&config).await.unwrap_err();
+        assert!(err.starts_with(&format!("cannot listen on 127.0.0.1:{}: ", port)), "{}", err);
+        assert!(err.to_lowercase().contains("in use"), "{}", err);
+    }
+}
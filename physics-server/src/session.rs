@@ -0,0 +1,388 @@
+// ==============================================================================
+// session.rs — WARM RESTART (--persist-session)
+// ------------------------------------------------------------------------------
+//   physics-server --persist-session [avenlab_session.json]
+//
+// Deploy a new build without wiping the running session:
+//
+//   shutdown : graceful_shutdown (net.rs) writes the session file before any
+//              connection is cleaned up: tick, every player (id, name, kind,
+//              room, team, resume token, vehicle config name, pose +
+//              velocities, brake bias, payload) and their lap timing bests
+//   startup  : with the same flag and an existing file, every player is put
+//              back: body at the saved pose / velocities, entity, spawn slot
+//              (SpawnManager team counts are rebuilt from the roster), timing
+//              bests. The tick loop resumes at the saved tick. Restored
+//              players have no connection yet: their cars stay in the world,
+//              held for AVENLAB_RECLAIM_GRACE_SECS (default 60), then removed
+//
+// Dropped connections (Wi-Fi hiccup, socket error, no close frame) are held
+// the same way for AVENLAB_DISCONNECT_GRACE_SECS (default 30; 0 = remove at
+// once): the connection's per-client state goes, the car keeps its entity,
+// body and spawn slot and rolls to a stop on input staleness. A client that
+// sends a close frame has left and is removed at once, as are kicked
+// connections and everyone at shutdown.
+//
+// Reclaim: every welcome carries "resume_token" (sent to that client only).
+// Reconnecting with ws://host:9001/?resume=<token>&protocol=1, or sending
+// {"type":"resume","token":<token>} as the first message (within
+// RESUME_WAIT of the handshake, after ?protocol=1&resume or with no query
+// at all; a client that states ?protocol= without the flag joins at once),
+// takes the held car back: same player_id,
+// room, team, name and pose; the welcome says "resumed":true. A wrong or
+// expired token gets {"type":"error","reason":"invalid_resume_token"} and a
+// normal fresh join. A car whose old socket hasn't dropped yet can't be
+// reclaimed. Expiry runs in the main loop (expire_reclaims, every tick) and
+// does the full despawn.
+//
+// Not kept: inputs, ghosts, the lap in progress, the spawn jitter RNG stream,
+// recorder output, physics solver state (contacts re-settle in a tick or two).
+// ==============================================================================
+
+use rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::physics::{DEFAULT_VEHICLE, PhysicsWorld};
+use crate::spawn::Team;
+use crate::state::{EntityState, EntityType, SharedGameState};
+use crate::timing::TimingBests;
+
+pub const DEFAULT_SESSION_FILE: &str = "avenlab_session.json";
+const SESSION_VERSION: u32 = 1;
+const DEFAULT_RECLAIM_GRACE_SECS: f32 = 60.0;
+const DEFAULT_DISCONNECT_GRACE_SECS: f32 = 30.0;
+/// How long a new connection's first frame is awaited for a "resume" or
+/// "join" message before joining fresh (only when one is expected: no
+/// ?protocol=, or the ?resume flag)
+pub const RESUME_WAIT: std::time::Duration = std::time::Duration::from_millis(250);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedPlayer {
+    pub id: String,
+    pub name: String,
+    pub kind: String,
+    pub room_id: usize,
+    pub team: u8,
+    pub resume_token: String,
+    #[serde(default = "default_vehicle")]
+    pub vehicle: String, // built-in config name (vehicle_config_by_name)
+    pub position: [f32; 3],
+    pub rotation: [f32; 4], // quaternion [x, y, z, w]
+    pub linvel: [f32; 3],
+    pub angvel: [f32; 3],
+    pub brake_bias: f32,
+    pub payload_mass: f32,
+    pub payload_offset: [f32; 3],
+    pub timing: Option<TimingBests>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionFile {
+    pub version: u32,
+    pub tick: u64,
+    pub physics_tick: u64,
+    pub players: Vec<SavedPlayer>,
+}
+
+/// "resume=<token>" out of a handshake query string.
+pub fn resume_token_from_query(query: &str) -> Option<String> {
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("resume="))
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+}
+
+/// Bare "resume" flag (ws://host:9001/?protocol=1&resume): the token
+/// follows in a "resume" message, so the first frame is worth waiting for.
+pub fn resume_flag_in_query(query: &str) -> bool {
+    query.split('&').any(|pair| pair == "resume" || pair == "resume=")
+}
+
+fn default_vehicle() -> String {
+    DEFAULT_VEHICLE.to_string()
+}
+
+fn reclaim_grace_secs() -> f32 {
+    std::env::var("AVENLAB_RECLAIM_GRACE_SECS")
+        .ok()
+        .and_then(|v| v.parse::<f32>().ok())
+        .filter(|s| s.is_finite() && *s >= 0.0)
+        .unwrap_or(DEFAULT_RECLAIM_GRACE_SECS)
+}
+
+/// Grace period for dropped connections' cars (0 = removed at once).
+pub fn disconnect_grace_secs() -> f32 {
+    std::env::var("AVENLAB_DISCONNECT_GRACE_SECS")
+        .ok()
+        .and_then(|v| v.parse::<f32>().ok())
+        .filter(|s| s.is_finite() && *s >= 0.0)
+        .unwrap_or(DEFAULT_DISCONNECT_GRACE_SECS)
+}
+
+// ==========================================================
+// Save
+// ==========================================================
+pub fn capture(game: &SharedGameState, phys: &PhysicsWorld) -> SessionFile {
+    let mut ids: Vec<&String> = game.entities.keys().collect();
+    ids.sort();
+
+    let players = ids
+        .into_iter()
+        .filter_map(|id| {
+            let ent = &game.entities[id];
+            let vehicle = phys.vehicles.get(id)?;
+            let rb = phys.bodies.get(vehicle.body)?;
+            let (t, r) = (rb.translation(), rb.rotation());
+            let (v, w) = (rb.linvel(), rb.angvel());
+            Some(SavedPlayer {
+                id: id.clone(),
+                name: ent.name.clone(),
+                kind: ent.kind.as_str().to_string(),
+                room_id: ent.room_id,
+                team: ent.team.index(),
+                resume_token: ent.resume_token.clone(),
+                vehicle: vehicle.config_name.clone(),
+                position: [t.x, t.y, t.z],
+                rotation: [r.i, r.j, r.k, r.w],
+                linvel: [v.x, v.y, v.z],
+                angvel: [w.x, w.y, w.z],
+                brake_bias: vehicle.brake_bias,
+                payload_mass: vehicle.payload.mass,
+                payload_offset: vehicle.payload.offset,
+                timing: game.timing.as_ref().and_then(|t| t.bests(id)),
+            })
+        })
+        .collect();
+
+    SessionFile { version: SESSION_VERSION, tick: game.tick, physics_tick: phys.tick, players }
+}
+
+/// Write the session file; returns the number of players saved.
+/// Callers hold both locks (physics, then game).
+pub fn save(path: &str, game: &SharedGameState, phys: &PhysicsWorld) -> Result<usize, String> {
+    let session = capture(game, phys);
+    let text = serde_json::to_string_pretty(&session).map_err(|e| e.to_string())?;
+    std::fs::write(path, text).map_err(|e| e.to_string())?;
+    Ok(session.players.len())
+}
+
+// ==========================================================
+// Restore
+// ==========================================================
+pub fn load(path: &str) -> Result<SessionFile, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let session: SessionFile = serde_json::from_str(&text).map_err(|e| format!("{}: {}", path, e))?;
+    if session.version != SESSION_VERSION {
+        return Err(format!("{}: session version {} (expected {})", path, session.version, SESSION_VERSION));
+    }
+    Ok(session)
+}
+
+/// Put a saved session back into fresh state + physics; every player is held
+/// for reclaim. Returns the number of players restored (unusable entries are
+/// skipped with a warning). Callers hold both locks (physics, then game).
+pub fn restore(session: &SessionFile, game: &mut SharedGameState, phys: &mut PhysicsWorld) -> usize {
+    game.tick = session.tick;
+    phys.tick = session.physics_tick;
+    let grace_ticks = (reclaim_grace_secs() * game.tick_rate.hz() as f32).ceil() as u64;
+    let deadline = session.tick + grace_ticks;
+
+    let mut restored = 0;
+    for saved in &session.players {
+        if game.entities.contains_key(&saved.id) {
+            eprintln!("⚠️ Session player {} listed twice; skipped", saved.id);
+            continue;
+        }
+        let Some(kind) = EntityType::from_name(&saved.kind) else {
+            eprintln!("⚠️ Session player {} has unknown kind '{}'; skipped", saved.id, saved.kind);
+            continue;
+        };
+        let handle = match phys.spawn_vehicle_for_player(saved.id.clone(), saved.position, 0.0, &saved.vehicle) {
+            Ok(handle) => handle,
+            Err(errors) => {
+                eprintln!("⚠️ Could not respawn session player {}: {:?}", saved.id, errors);
+                continue;
+            }
+        };
+
+        if let Some(rb) = phys.bodies.get_mut(handle) {
+            let [x, y, z, w] = saved.rotation;
+            let rotation = Rotation::from_quaternion(rapier3d::na::Quaternion::new(w, x, y, z));
+            rb.set_position(Isometry::from_parts(Vector::from(saved.position).into(), rotation), true);
+            rb.set_linvel(Vector::from(saved.linvel), true);
+            rb.set_angvel(Vector::from(saved.angvel), true);
+        }
+        if let Some(vehicle) = phys.vehicles.get_mut(&saved.id) {
+            let [min, max] = vehicle.config.brake_bias_range;
+            vehicle.brake_bias = saved.brake_bias.clamp(min, max);
+        }
+        if saved.payload_mass > 0.0
+            && let Err(e) = phys.set_payload(&saved.id, saved.payload_mass, saved.payload_offset)
+        {
+            eprintln!("⚠️ Session player {}: payload not restored ({})", saved.id, e);
+        }
+
+        let team = Team(saved.team);
+        game.spawns.restore_spawn(&saved.id, saved.room_id, team);
+        game.entities.insert(saved.id.clone(), EntityState {
+            id: saved.id.clone(),
+            name: saved.name.clone(),
+            kind,
+            room_id: saved.room_id,
+            team,
+            body_handle: handle,
+            last_input: None,
+            last_snapshot: None,
+            last_respawn_tick: None,
+            input_seq: None,
+            resume_token: saved.resume_token.clone(),
+        });
+        if let (Some(timing), Some(bests)) = (game.timing.as_mut(), saved.timing.clone()) {
+            timing.restore_bests(&saved.id, bests);
+        }
+        game.apply_room_tick_rate(phys, &saved.id);
+        game.plugins.entity_spawned(&saved.id);
+        game.awaiting_reclaim.insert(saved.id.clone(), deadline);
+        restored += 1;
+    }
+    restored
+}
+
+/// Remove held cars whose grace period ran out (called every tick).
+pub fn expire_reclaims(game: &mut SharedGameState, phys: &mut PhysicsWorld) {
+    if game.awaiting_reclaim.is_empty() {
+        return;
+    }
+    let tick = game.tick;
+    let mut expired: Vec<String> = game
+        .awaiting_reclaim
+        .iter()
+        .filter(|(_, deadline)| **deadline <= tick)
+        .map(|(id, _)| id.clone())
+        .collect();
+    expired.sort(); // deterministic removal order
+    for id in expired {
+        println!("⌛ Player {} did not reclaim their car in time; removed", id);
+        game.despawn_player(phys, &id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::ClientTx;
+    use crate::tick_rate::TickRate;
+
+    const GRACE_TICKS: u64 = 30;
+
+    /// A joined player whose connection just dropped, with its resume token.
+    fn held_player() -> (PhysicsWorld, SharedGameState, String) {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        game.disconnect_grace_ticks = GRACE_TICKS;
+        let _rx = game.join_test_player(&mut phys, "p");
+        let token = game.entities["p"].resume_token.clone();
+        game.hold_for_reclaim("p");
+        (phys, game, token)
+    }
+
+    #[test]
+    fn reclaim_before_expiry_keeps_the_car() {
+        let (mut phys, mut game, token) = held_player();
+        let body = game.entities["p"].body_handle;
+        game.tick += GRACE_TICKS - 1;
+        expire_reclaims(&mut game, &mut phys);
+
+        let (tx, _rx) = ClientTx::channel();
+        assert_eq!(game.reclaim(&token, tx).as_deref(), Some("p"));
+        assert!(game.awaiting_reclaim.is_empty());
+        assert!(game.clients.contains_key("p"));
+        assert_eq!(game.entities["p"].body_handle, body);
+        assert!(game.debug_validate(&phys).is_empty());
+    }
+
+    #[test]
+    fn reclaim_after_expiry_finds_nothing() {
+        let (mut phys, mut game, token) = held_player();
+        game.tick += GRACE_TICKS;
+        expire_reclaims(&mut game, &mut phys);
+
+        assert!(!game.entities.contains_key("p"));
+        assert!(!phys.vehicles.contains_key("p"));
+        let (tx, _rx) = ClientTx::channel();
+        assert_eq!(game.reclaim(&token, tx), None);
+        assert!(game.clients.is_empty());
+    }
+
+    #[test]
+    fn wrong_token_leaves_the_car_held() {
+        let (_phys, mut game, token) = held_player();
+        let (tx, _rx) = ClientTx::channel();
+        assert_eq!(game.reclaim(&format!("{}x", token), tx), None);
+        assert!(game.awaiting_reclaim.contains_key("p"));
+        assert!(!game.clients.contains_key("p"));
+    }
+
+    #[test]
+    fn only_the_resume_flag_makes_a_versioned_client_wait() {
+        assert!(resume_flag_in_query("protocol=1&resume"));
+        assert!(resume_flag_in_query("resume=&protocol=1"));
+        assert!(!resume_flag_in_query("protocol=1"));
+        assert!(!resume_flag_in_query("resume=abc&protocol=1")); // token given: nothing to wait for
+        assert_eq!(resume_token_from_query("resume=abc&protocol=1").as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn a_warm_restart_hands_every_car_back_by_token() {
+        use crate::timing::{Checkpoint, Track, TrackTiming};
+        let track = || {
+            let gate = |x: f32| Checkpoint { position: [x, 1.0, 0.0], radius: 1.0, sector: false };
+            TrackTiming::new(Track { checkpoints: vec![gate(0.0), gate(100.0)], continuous_delta: false })
+        };
+
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        game.spawns.max_players = 1; // "b" lands in room 1
+        game.timing = Some(track());
+        let _rx_a = game.join_test_player(&mut phys, "a");
+        let _rx_b = game.join_test_player(&mut phys, "b");
+        let bests = TimingBests { best_lap: Some(42.5), best_splits: vec![21.0], best_sectors: vec![Some(42.5)] };
+        game.timing.as_mut().unwrap().restore_bests("a", bests.clone());
+        for _ in 0..30 {
+            phys.step(1.0 / 60.0);
+        }
+        game.tick = 30;
+
+        let path = std::env::temp_dir().join(format!("avenlab-session-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        assert_eq!(save(path, &game, &phys), Ok(2));
+        let session = load(path).unwrap();
+        let _ = std::fs::remove_file(path);
+
+        let mut phys2 = PhysicsWorld::new();
+        let mut game2 = SharedGameState::new(TickRate::default());
+        game2.timing = Some(track());
+        assert_eq!(restore(&session, &mut game2, &mut phys2), 2);
+        assert_eq!(game2.tick, 30);
+
+        let (tx, _rx) = ClientTx::channel();
+        assert_eq!(game2.reclaim("not-a-token", tx), None);
+
+        for id in ["a", "b"] {
+            let (tx, _rx) = ClientTx::channel();
+            let token = game.entities[id].resume_token.clone();
+            assert_eq!(game2.reclaim(&token, tx).as_deref(), Some(id));
+
+            let (before, after) = (&game.entities[id], &game2.entities[id]);
+            assert_eq!((after.room_id, after.team), (before.room_id, before.team));
+            let old = phys.bodies[before.body_handle].translation();
+            let new = phys2.bodies[after.body_handle].translation();
+            assert!((old - new).norm() < 1e-4, "{} moved from {:?} to {:?}", id, old, new);
+        }
+        assert_ne!(game2.entities["a"].room_id, game2.entities["b"].room_id);
+        assert_eq!(game2.timing.as_ref().unwrap().bests("a"), Some(bests));
+        assert!(game2.awaiting_reclaim.is_empty());
+        assert!(game2.debug_validate(&phys2).is_empty());
+    }
+}
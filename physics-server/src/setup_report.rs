@@ -0,0 +1,227 @@
+// ==============================================================================
+// setup_report.rs — CORNER WEIGHTS / SETUP SHEET
+// ------------------------------------------------------------------------------
+// "What is this car sitting on?" before a race:
+//
+//   admin     : {"type":"admin","cmd":"report_setup","player_id":..,"token":..}
+//               -> {"type":"setup_report","report":{..},"table":".."}
+//   headless  : physics-server --report setup [gt86|arcade|tank]  (stdout)
+//
+// A car that is already at rest is sampled as-is. Anything else (moving,
+// airborne, just spawned) is copied into a scratch world with the same
+// config + brake bias + payload and settled on flat ground first (at least one second,
+// longer while the spawn bounce is still moving load around), so the live
+// world is never stepped by a report.
+//
+// Corner loads are the applied suspension loads (after the anti-roll bars),
+// low-passed by the vehicle step; everything else is derived from them:
+//
+//   cross weight  : (FL + RR) / total      (50% = square car)
+//   frequency     : sqrt(k / m_corner) / 2π per axle, m_corner = Fz / g
+//   ride height   : chassis floor clearance above the ground at each wheel
+// ==============================================================================
+
+use rapier3d::prelude::*;
+use serde::Serialize;
+
+use crate::aven_tire::types::WheelId;
+use crate::physics::PhysicsWorld;
+use crate::vehicle::{Payload, VehicleConfig};
+
+const SETTLE_TICKS: u32 = 60;       // 1 s at 60 Hz, minimum settle
+const SETTLE_MAX_TICKS: u32 = 240;  // give up waiting for a steady load after 4 s
+const SETTLE_WINDOW: u32 = 10;      // ticks the summed load must hold steady over
+const SETTLE_TOLERANCE: f32 = 1e-3; // relative change allowed over the window
+const SETTLE_DT: f32 = 1.0 / 60.0;
+const SETTLE_SPAWN: [f32; 3] = [5.0, 0.0, 5.0]; // off the origin, where parked corner loads come out skewed
+const REST_LINVEL: f32 = 0.1;       // m/s; a parked car still reads ~g*dt/3 between impulse + step
+const REST_ANGVEL: f32 = 0.05;      // rad/s
+const RIDE_HEIGHT_PROBE: f32 = 5.0; // m, deepest ground looked for under the floor
+const GRAVITY: f32 = 9.81;
+
+const CORNERS: [WheelId; 4] = [WheelId::FL, WheelId::FR, WheelId::RL, WheelId::RR];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SetupReport {
+    pub player_id: String,
+    pub settled: bool,                  // true = sampled from a settled copy, false = live car at rest
+    pub mass: f32,                      // kg
+    pub corner_loads: [f32; 4],         // N, FL/FR/RL/RR
+    pub total_load: f32,                // N
+    pub cross_weight: f32,              // 0..1, (FL + RR) / total
+    pub front_weight: f32,              // 0..1, front axle share
+    pub left_weight: f32,               // 0..1, FL + RL share
+    pub spring_rates: [f32; 2],         // N/m per wheel, front / rear
+    pub arb_rates: [f32; 2],            // N/m, front / rear
+    pub natural_freq_hz: [f32; 2],      // front / rear
+    pub ride_heights: [Option<f32>; 4], // m, FL/FR/RL/RR (None = no ground below)
+    pub brake_bias: f32,                // front share of brake demand
+    pub brake_bias_range: [f32; 2],
+}
+
+/// Report for a live car, only if it is already at rest on its wheels.
+pub fn sample_at_rest(world: &PhysicsWorld, player_id: &str) -> Option<SetupReport> {
+    let vehicle = world.vehicles.get(player_id)?;
+    let body = world.bodies.get(vehicle.body)?;
+    let at_rest = body.linvel().norm() < REST_LINVEL
+        && body.angvel().norm() < REST_ANGVEL
+        && vehicle.corner_loads.iter().all(|&fz| fz > 0.0);
+    if !at_rest {
+        return None;
+    }
+    sample(world, player_id, false)
+}
+
+/// Settle a copy of the car on flat ground (>= one second, until the summed
+/// corner load holds steady), then report.
+pub fn settle_and_sample(player_id: &str, config: VehicleConfig, brake_bias: f32, payload: Payload) -> Option<SetupReport> {
+    let mut scratch = PhysicsWorld::new();
+    scratch.spawn_vehicle_with_config(player_id.to_string(), SETTLE_SPAWN, 0.0, config).ok()?;
+    scratch.vehicles.get_mut(player_id)?.brake_bias = brake_bias;
+    if payload.mass > 0.0 {
+        scratch.set_payload(player_id, payload.mass, payload.offset).ok()?;
+    }
+
+    let mut history = std::collections::VecDeque::new();
+    for tick in 0..SETTLE_MAX_TICKS {
+        scratch.step(SETTLE_DT);
+
+        let total = scratch.vehicles.get(player_id)?.corner_loads.iter().sum::<f32>();
+        history.push_back(total);
+        if history.len() > SETTLE_WINDOW as usize {
+            history.pop_front();
+        }
+        let (lo, hi) = history.iter().fold((f32::MAX, f32::MIN), |(lo, hi), &t| (lo.min(t), hi.max(t)));
+        let steady = history.len() == SETTLE_WINDOW as usize && total > 0.0 && hi - lo <= total * SETTLE_TOLERANCE;
+        if tick + 1 >= SETTLE_TICKS && steady {
+            break;
+        }
+    }
+    sample(&scratch, player_id, true)
+}
+
+fn sample(world: &PhysicsWorld, player_id: &str, settled: bool) -> Option<SetupReport> {
+    let vehicle = world.vehicles.get(player_id)?;
+    let body = world.bodies.get(vehicle.body)?;
+    let wheels = world.wheels.get(&vehicle.body)?;
+    let cfg = &vehicle.config;
+
+    let loads = vehicle.corner_loads;
+    let total = loads.iter().sum::<f32>();
+    let share = |fz: f32| if total > 0.0 { fz / total } else { 0.0 };
+    let [fl, fr, rl, rr] = loads;
+
+    let stiffness = |id: WheelId| {
+        wheels.iter().find(|w| w.debug_id == id.as_str()).map_or(0.0, |w| w.stiffness)
+    };
+    let spring_rates = [
+        0.5 * (stiffness(WheelId::FL) + stiffness(WheelId::FR)),
+        0.5 * (stiffness(WheelId::RL) + stiffness(WheelId::RR)),
+    ];
+    let frequency = |k: f32, fz_axle: f32| {
+        let m_corner = 0.5 * fz_axle / GRAVITY;
+        if m_corner > 0.0 { (k / m_corner).sqrt() / std::f32::consts::TAU } else { 0.0 }
+    };
+
+    // Chassis floor under each wheel, straight down to the ground
+    let [_, hy, _] = cfg.chassis_half_extents;
+    let [_, cy, _] = cfg.chassis_com_offset;
+    let ride_heights = CORNERS.map(|id| {
+        let wheel = wheels.iter().find(|w| w.debug_id == id.as_str())?;
+        let floor = body.position() * point![wheel.offset.x, cy - hy, wheel.offset.z];
+        let ray = Ray::new(floor, vector![0.0, -1.0, 0.0]);
+        world
+            .query_pipeline
+            .cast_ray(&world.bodies, &world.colliders, &ray, RIDE_HEIGHT_PROBE, true, QueryFilter::only_fixed())
+            .map(|(_, toi)| toi)
+    });
+
+    Some(SetupReport {
+        player_id: player_id.to_string(),
+        settled,
+        mass: body.mass(),
+        corner_loads: loads,
+        total_load: total,
+        cross_weight: share(fl + rr),
+        front_weight: share(fl + fr),
+        left_weight: share(fl + rl),
+        spring_rates,
+        arb_rates: [cfg.arb_rates[0], cfg.arb_rates[1]],
+        natural_freq_hz: [frequency(spring_rates[0], fl + fr), frequency(spring_rates[1], rl + rr)],
+        ride_heights,
+        brake_bias: vehicle.brake_bias,
+        brake_bias_range: cfg.brake_bias_range,
+    })
+}
+
+impl SetupReport {
+    /// Fixed-width setup sheet (admin reply / stdout).
+    pub fn format_table(&self) -> String {
+        let pct = |x: f32| format!("{:.1}%", x * 100.0);
+        let height = |h: Option<f32>| h.map_or("-".to_string(), |h| format!("{:.3} m", h));
+        let [fl, fr, rl, rr] = self.corner_loads;
+        let [hfl, hfr, hrl, hrr] = self.ride_heights;
+
+        let mut out = String::new();
+        out += &format!(
+            "Setup sheet: {} ({})\n",
+            self.player_id,
+            if self.settled { "settled on flat ground" } else { "live, at rest" }
+        );
+        out += &format!("  mass            {:.0} kg   (m·g = {:.0} N, wheels carry {:.0} N)\n", self.mass, self.mass * GRAVITY, self.total_load);
+        out += "                  left          right\n";
+        out += &format!("  load   front    {:>9.0} N  {:>9.0} N\n", fl, fr);
+        out += &format!("         rear     {:>9.0} N  {:>9.0} N\n", rl, rr);
+        out += &format!("  ride   front    {:>11}  {:>11}\n", height(hfl), height(hfr));
+        out += &format!("         rear     {:>11}  {:>11}\n", height(hrl), height(hrr));
+        out += &format!(
+            "  distribution    front {}  left {}  cross {}\n",
+            pct(self.front_weight), pct(self.left_weight), pct(self.cross_weight)
+        );
+        out += "                  front         rear\n";
+        out += &format!("  spring          {:>9.0} N/m {:>9.0} N/m\n", self.spring_rates[0], self.spring_rates[1]);
+        out += &format!("  anti-roll bar   {:>9.0} N/m {:>9.0} N/m\n", self.arb_rates[0], self.arb_rates[1]);
+        out += &format!("  frequency       {:>9.2} Hz  {:>9.2} Hz\n", self.natural_freq_hz[0], self.natural_freq_hz[1]);
+        out += &format!(
+            "  brake bias      {} front (range {}–{})\n",
+            pct(self.brake_bias), pct(self.brake_bias_range[0]), pct(self.brake_bias_range[1])
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{DEFAULT_VEHICLE, GT86};
+
+    #[test]
+    fn a_square_gt86_reports_half_cross_weight_and_carries_its_mass() {
+        let report = settle_and_sample("p", GT86, GT86.brake_bias, Payload::default()).unwrap();
+        assert!(report.settled);
+        assert!((report.cross_weight - 0.5).abs() < 0.01, "cross weight {:.3}", report.cross_weight);
+        assert!((report.left_weight - 0.5).abs() < 0.01, "left weight {:.3}", report.left_weight);
+
+        let mg = report.mass * GRAVITY;
+        assert!((report.total_load - mg).abs() < 0.01 * mg, "wheels carry {:.0} N of {:.0} N", report.total_load, mg);
+        assert!(report.ride_heights.iter().all(|h| h.is_some_and(|h| h > 0.0)));
+        assert!(report.natural_freq_hz.iter().all(|f| (0.5..5.0).contains(f)));
+        assert!(report.format_table().contains("cross 50."));
+    }
+
+    #[test]
+    fn only_a_car_at_rest_is_sampled_live() {
+        let mut world = PhysicsWorld::new();
+        let body = world.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        for _ in 0..SETTLE_MAX_TICKS {
+            world.step(SETTLE_DT);
+        }
+        let live = sample_at_rest(&world, "p").expect("parked");
+        assert!(!live.settled);
+        assert_eq!(live.brake_bias, GT86.brake_bias);
+        assert!(sample_at_rest(&world, "nobody").is_none());
+
+        world.bodies[body].set_linvel(vector![0.0, 0.0, 5.0], true);
+        assert!(sample_at_rest(&world, "p").is_none(), "rolling");
+    }
+}
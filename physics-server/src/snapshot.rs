@@ -0,0 +1,357 @@
+// ==============================================================================
+// snapshot.rs — TYPED SNAPSHOT / DEBUG MESSAGES + PER-CLIENT WIRE ENCODING
+// ------------------------------------------------------------------------------
+// Snapshots and debug overlays are the big per-tick messages. They are built
+// as the structs below (SharedGameState::broadcast_snapshot /
+// broadcast_debug_overlay) and serialized once per distinct payload in each
+// client's encoding, picked in the hello:
+//
+//   {"type":"hello","encoding":"msgpack"}   → hello_ack "encoding":"msgpack"
+//
+//   json    : text frames (default); zstd applies above its threshold if
+//             negotiated (compression.rs)
+//   msgpack : binary frames, MessagePack maps with the same field names and
+//             shape as the JSON (rmp_serde::to_vec_named). A map never starts
+//             with the zstd frame magic "AZ", so both can be on at once.
+//
+// Both carry the protocol version as "v" (protocol.rs).
+//
+// Only "snapshot" and "debug" switch encoding; every other message stays JSON
+// text. Writers get an OutFrame per message through ClientTx, whose send()
+// takes a String like the plain channel it replaced.
+// ==============================================================================
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, error::SendError, unbounded_channel};
+
+use crate::physics::DebugOverlay;
+use crate::protocol::PROTOCOL_VERSION;
+use crate::vehicle::Payload;
+
+// ==========================================================
+// Outgoing frames
+// ==========================================================
+#[derive(Debug, Clone)]
+pub enum OutFrame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl OutFrame {
+    /// Payload size before compression (metrics).
+    pub fn byte_len(&self) -> usize {
+        match self {
+            OutFrame::Text(text) => text.len(),
+            OutFrame::Binary(bytes) => bytes.len(),
+        }
+    }
+}
+
+/// A connection's outgoing queue (drained by its writer task in net.rs).
+#[derive(Debug, Clone)]
+pub struct ClientTx(UnboundedSender<OutFrame>);
+
+impl ClientTx {
+    pub fn channel() -> (Self, UnboundedReceiver<OutFrame>) {
+        let (tx, rx) = unbounded_channel();
+        (Self(tx), rx)
+    }
+
+    /// Queue a JSON text message.
+    pub fn send(&self, text: String) -> Result<(), SendError<OutFrame>> {
+        self.0.send(OutFrame::Text(text))
+    }
+
+    pub fn send_frame(&self, frame: OutFrame) -> Result<(), SendError<OutFrame>> {
+        self.0.send(frame)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    #[default]
+    Json,
+    Msgpack,
+}
+
+impl Encoding {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "json" => Some(Encoding::Json),
+            "msgpack" => Some(Encoding::Msgpack),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Json => "json",
+            Encoding::Msgpack => "msgpack",
+        }
+    }
+
+    /// `message` as a frame in this encoding (None if it fails to serialize).
+    pub fn encode<T: Serialize>(&self, message: &T) -> Option<OutFrame> {
+        match self {
+            Encoding::Json => serde_json::to_string(message).ok().map(OutFrame::Text),
+            Encoding::Msgpack => rmp_serde::to_vec_named(message).ok().map(OutFrame::Binary),
+        }
+    }
+}
+
+// ==========================================================
+// Snapshot
+// ==========================================================
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SnapshotMessage {
+    #[serde(rename = "type")]
+    #[schemars(extend("const" = "snapshot"))]
+    pub kind: String, // "snapshot"
+    #[serde(default = "protocol_version")]
+    pub v: u32, // protocol.rs PROTOCOL_VERSION
+    pub data: SnapshotData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SnapshotData {
+    pub tick: u64,
+    pub room_id: usize,
+    pub timescale: f32, // playback rate for client animation
+    pub tick_hz: u32,   // tick → time (simulated s = ticks / tick_hz * timescale)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub room_tick_hz: Option<u32>, // room steps (and snapshots) slower than tick_hz (AVENLAB_ROOM_TICK_HZ)
+    pub drift_ms: f64,  // simulation behind wall time (catch-up cap, tick_rate.rs)
+    pub server_time_ms: f64, // server clock when built (time_sync.rs)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,  // interest-managed: unchanged in-range entities are omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<u64>, // delta against this acked tick (delta.rs); absent = full
+    pub players: Vec<SnapshotEntry>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<String>,   // delta: ids new to the room since the baseline
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<String>, // delta: ids gone from the room since the baseline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_processed_seq: Option<u64>, // prediction: last input seq applied for this client
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub own: Option<OwnVehicle>, // prediction: this client's vehicle, full dynamic state
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_lead_ticks: Option<f64>, // tick-tagged inputs: measured lead (input_buffer.rs)
+}
+
+impl SnapshotMessage {
+    pub fn new(data: SnapshotData) -> Self {
+        Self { kind: "snapshot".to_string(), v: PROTOCOL_VERSION, data }
+    }
+}
+
+fn protocol_version() -> u32 {
+    PROTOCOL_VERSION
+}
+
+/// One element of "players": a car, or the client's own best-lap ghost.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum SnapshotEntry {
+    Ghost(GhostEntry),
+    Player(SnapshotPlayer),
+}
+
+impl SnapshotEntry {
+    pub fn id(&self) -> &str {
+        match self {
+            SnapshotEntry::Ghost(g) => &g.id,
+            SnapshotEntry::Player(p) => &p.id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SnapshotPlayer {
+    pub id: String,
+    pub name: String,
+    pub kind: String,
+    pub room_id: usize,
+    pub team: String,
+    pub team_index: u8,
+    pub team_color: String,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub rot: [f32; 4], // FULL authoritative orientation (i, j, k, w)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub suspect: bool, // physically implausible movement (still sent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brake_bias: Option<f32>, // driver-adjusted front brake share (HUD)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub airborne: Option<bool>, // all wheels off the ground (audio / FX)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<Payload>, // cargo / fuel on board (absent when empty)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timing: Option<Value>, // lap / sector timing (timing.rs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turret: Option<TurretAngles>,
+    #[serde(flatten)]
+    pub velocity: Option<SnapshotVelocity>, // "want_velocities" clients only
+}
+
+/// Turret yaw (relative to hull) + barrel pitch, radians
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct TurretAngles {
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+/// Body velocities for dead reckoning: vx/vy/vz linear (m/s), wx/wy/wz
+/// angular (rad/s), world frame, at the body's center of mass
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SnapshotVelocity {
+    pub vx: f32,
+    pub vy: f32,
+    pub vz: f32,
+    pub wx: f32,
+    pub wy: f32,
+    pub wz: f32,
+}
+
+/// The receiving client's own vehicle for prediction reconciliation (clients
+/// that send input "seq"): pose, world-frame velocities at the center of
+/// mass, front wheel steer angle (rad)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema)]
+pub struct OwnVehicle {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub rot: [f32; 4],
+    pub linvel: [f32; 3],
+    pub angvel: [f32; 3],
+    pub steer_angle: f32,
+}
+
+/// Own best-lap ghost (ghost.rs)
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct GhostEntry {
+    pub id: String, // "ghost:<owner>"
+    pub ghost: bool,
+    pub owner: String,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub rot: [f32; 4],
+    pub ghost_tick: u32,
+    pub lap_time: f32,
+}
+
+// ==========================================================
+// Debug overlay
+// ==========================================================
+#[derive(Serialize, JsonSchema)]
+pub struct DebugMessage<'a> {
+    #[serde(rename = "type")]
+    #[schemars(extend("const" = "debug"))]
+    pub kind: &'static str, // "debug"
+    pub v: u32,             // protocol.rs PROTOCOL_VERSION
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<&'a str>, // watched vehicle, absent for the shared overlay
+    pub data: DebugOverlay,
+}
+
+impl<'a> DebugMessage<'a> {
+    pub fn new(target: Option<&'a str>, data: DebugOverlay) -> Self {
+        Self { kind: "debug", v: PROTOCOL_VERSION, target, data }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(id: &str, velocity: Option<SnapshotVelocity>) -> SnapshotEntry {
+        SnapshotEntry::Player(SnapshotPlayer {
+            id: id.to_string(),
+            name: id.to_uppercase(),
+            kind: "vehicle".to_string(),
+            room_id: 1,
+            team: "blue".to_string(),
+            team_index: 1,
+            team_color: "#1e88e5".to_string(),
+            x: 5.0,
+            y: 0.61,
+            z: -2.25,
+            rot: [0.0, 0.6, 0.0, 0.8],
+            suspect: true,
+            brake_bias: Some(0.58),
+            airborne: Some(false),
+            payload: Some(Payload { mass: 80.0, offset: [0.0, 0.2, -1.0] }),
+            timing: Some(serde_json::json!({ "lap": 2, "sector": 1, "delta_ms": -140 })),
+            turret: Some(TurretAngles { yaw: 0.3, pitch: -0.1 }),
+            velocity,
+        })
+    }
+
+    fn message() -> SnapshotMessage {
+        let velocity = SnapshotVelocity { vx: 12.5, vy: 0.0, vz: -3.0, wx: 0.0, wy: 0.4, wz: 0.0 };
+        SnapshotMessage::new(SnapshotData {
+            tick: 1234,
+            room_id: 1,
+            timescale: 0.5,
+            tick_hz: 60,
+            room_tick_hz: Some(20),
+            drift_ms: 1.5,
+            server_time_ms: 1_700_000_000_123.0,
+            partial: true,
+            baseline: Some(1200),
+            players: vec![
+                player("a", Some(velocity)),
+                player("b", None),
+                SnapshotEntry::Ghost(GhostEntry {
+                    id: "ghost:a".to_string(),
+                    ghost: true,
+                    owner: "a".to_string(),
+                    x: 1.0,
+                    y: 0.6,
+                    z: 2.0,
+                    rot: [0.0, 0.0, 0.0, 1.0],
+                    ghost_tick: 300,
+                    lap_time: 61.25,
+                }),
+            ],
+            added: vec!["b".to_string()],
+            removed: vec!["c".to_string()],
+            last_processed_seq: Some(88),
+            own: Some(OwnVehicle {
+                x: 5.0,
+                y: 0.61,
+                z: -2.25,
+                rot: [0.0, 0.6, 0.0, 0.8],
+                linvel: [12.5, 0.0, -3.0],
+                angvel: [0.0, 0.4, 0.0],
+                steer_angle: 0.12,
+            }),
+            input_lead_ticks: Some(2.5),
+        })
+    }
+
+    #[test]
+    fn msgpack_snapshot_decodes_back_into_the_struct() {
+        let sent = message();
+        let Some(OutFrame::Binary(bytes)) = Encoding::Msgpack.encode(&sent) else { panic!("msgpack is a binary frame") };
+        assert!(!bytes.starts_with(b"AZ"), "must not look like a zstd frame");
+        let decoded: SnapshotMessage = rmp_serde::from_slice(&bytes).unwrap();
+
+        assert_eq!(decoded.kind, "snapshot");
+        assert_eq!(decoded.v, PROTOCOL_VERSION);
+        let ids: Vec<&str> = decoded.data.players.iter().map(SnapshotEntry::id).collect();
+        assert_eq!(ids, ["a", "b", "ghost:a"]);
+        let SnapshotEntry::Player(a) = &decoded.data.players[0] else { panic!("a is a player") };
+        assert_eq!(a.velocity, Some(SnapshotVelocity { vx: 12.5, vy: 0.0, vz: -3.0, wx: 0.0, wy: 0.4, wz: 0.0 }));
+        assert!(matches!(&decoded.data.players[1], SnapshotEntry::Player(b) if b.velocity.is_none()));
+        assert!(matches!(&decoded.data.players[2], SnapshotEntry::Ghost(g) if g.ghost_tick == 300));
+        // Field for field what went out: the JSON frame of the decoded
+        // message is the one a JSON client gets
+        let Some(OutFrame::Text(text)) = Encoding::Json.encode(&sent) else { panic!("json is a text frame") };
+        assert_eq!(serde_json::to_string(&decoded).unwrap(), text);
+    }
+}
@@ -1,23 +1,132 @@
 // use uuid::Uuid;
 use serde::{Serialize};
 use std::collections::HashMap;  
+use rand::Rng;
+use rand::rngs::StdRng;
+
+use crate::input_sanity::{TuneRules, parse_locked_rooms};
 
 // ---------------------------------------------
 // TEAM TYPE
 // ---------------------------------------------
+// Team index within a room (0..team_count). Names / colors come from the
+// room's RoomSettings; 2-team rooms keep the "red" / "blue" protocol names.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
-pub enum Team {
-    Red,
-    Blue,
-}
+pub struct Team(pub u8);
 
 impl Team {
-    pub fn as_str(&self) -> &str {
-        match self {
-            Team::Red => "red",
-            Team::Blue => "blue",
+    pub fn index(&self) -> u8 {
+        self.0
+    }
+}
+
+/// Default team names + colors, in team-index order
+const TEAM_PALETTE: [(&str, &str); 8] = [
+    ("red", "#e53935"),
+    ("blue", "#1e88e5"),
+    ("green", "#43a047"),
+    ("yellow", "#fdd835"),
+    ("purple", "#8e24aa"),
+    ("orange", "#fb8c00"),
+    ("cyan", "#00acc1"),
+    ("pink", "#d81b60"),
+];
+pub const MAX_TEAMS: usize = TEAM_PALETTE.len();
+
+const SPAWN_RING_RADIUS: f32 = 5.0; // team spawn points sit on a ring around the origin
+const SPAWN_HEIGHT: f32 = 4.0;
+const MAX_SPAWN_JITTER: f32 = 4.0;  // m; keeps jittered team spawns on their side of the ring
+const SLOT_SPACING_M: f32 = 5.0;    // between a team's spawn slots (+ twice the jitter)
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TeamInfo {
+    pub name: String,
+    pub color: String, // "#rrggbb"
+}
+
+// ---------------------------------------------
+// ROOM SETTINGS (team count + metadata)
+// ---------------------------------------------
+// Env:
+// - AVENLAB_ROOM_CAPACITY players per room before joins open the next room
+//                        (default: SharedGameState's max_players, 10)
+// - AVENLAB_TEAM_COUNT   default teams per room (1..=8, default 2; 1 = co-op)
+// - AVENLAB_ROOM_TEAMS   per-room overrides, e.g. "1:4,2:1"
+// - AVENLAB_SPAWN_JITTER random offset radius around team spawns (m, default 0)
+// - AVENLAB_LOCKED_SETUP_ROOMS rooms where clients may not "adjust" their setup
+// - AVENLAB_TEAM_DEBUG_ROOMS   rooms where teammates may watch each other's
+//                              debug overlay (debug_subscribe "target")
+// - AVENLAB_ROOM_TICK_HZ per-room tick rates below the server rate, e.g.
+//                        "1:20,2:30" (tick_rate.rs TickRate::room_divisor)
+#[derive(Debug, Clone)]
+pub struct RoomSettings {
+    pub teams: Vec<TeamInfo>,
+    pub tune: TuneRules, // what the "adjust" message may change (input_sanity.rs)
+    pub team_debug: bool, // teammates may subscribe to each other's debug overlay
+    pub tick_hz: Option<u32>, // room tick rate (None = the server rate)
+}
+
+impl RoomSettings {
+    pub fn with_team_count(count: usize) -> Self {
+        let count = count.clamp(1, MAX_TEAMS);
+        Self {
+            teams: TEAM_PALETTE[..count]
+                .iter()
+                .map(|(name, color)| TeamInfo { name: name.to_string(), color: color.to_string() })
+                .collect(),
+            tune: TuneRules::default(),
+            team_debug: false,
+            tick_hz: None,
         }
     }
+
+    pub fn team_count(&self) -> usize {
+        self.teams.len()
+    }
+}
+
+impl Default for RoomSettings {
+    fn default() -> Self {
+        Self::with_team_count(2)
+    }
+}
+
+/// Spawn slot `slot` of a team in a room with `team_count` teams. Slot 0 is
+/// the team's point on the ring; further slots continue outward along the
+/// same ray, `spacing` apart, so teams never share ground.
+fn slot_spawn_point(team_count: usize, team: Team, slot: usize, spacing: f32) -> [f32; 3] {
+    let count = team_count.max(1) as f32;
+    let angle = std::f32::consts::PI + team.index() as f32 * std::f32::consts::TAU / count;
+    let radius = SPAWN_RING_RADIUS + slot as f32 * spacing;
+    let mm = |v: f32| (v * 1000.0).round() / 1000.0; // keep exact axis points exact
+    [
+        mm(radius * angle.cos()),
+        SPAWN_HEIGHT,
+        mm(radius * angle.sin()),
+    ]
+}
+
+/// Heading (yaw about +Y, 0 = +Z forward) from `point` toward the ring
+/// center, where the other teams spawn
+fn facing_center(point: [f32; 3]) -> f32 {
+    (-point[0]).atan2(-point[2])
+}
+
+/// Horizontal distance to the nearest of `live` (infinite when empty)
+fn clearance(point: [f32; 3], live: &[[f32; 3]]) -> f32 {
+    live.iter()
+        .map(|p| (p[0] - point[0]).hypot(p[2] - point[2]))
+        .fold(f32::INFINITY, f32::min)
+}
+
+/// "1:4,2:1" → {1: 4, 2: 1} (malformed entries are skipped)
+fn parse_room_teams(text: &str) -> HashMap<usize, usize> {
+    text.split(',')
+        .filter_map(|entry| {
+            let (room, count) = entry.trim().split_once(':')?;
+            Some((room.trim().parse().ok()?, count.trim().parse().ok()?))
+        })
+        .collect()
 }
 
 // ---------------------------------------------
@@ -29,39 +138,108 @@ pub struct PlayerSpawnInfo {
     pub room_id: usize,
     pub team: Team,
     pub position: [f32; 3],
+    pub yaw: f32, // radians about +Y (0 = facing +Z), facing the ring center
 }
 
-// #[derive(Debug)]
-// pub struct Room {
-//     pub id: usize,
-//     pub red_count: usize,
-//     pub blue_count: usize,
-// }
-
 // ---------------------------------------------
 // SPAWN MANAGER FOR ALL ROOMS
 // ---------------------------------------------
 #[derive(Debug)]
 pub struct SpawnManager {
-    /// How many players are in each room
-    // pub room_counts: HashMap<usize, usize>,
-
-    /// How many players of each team are in each room
+    /// How many players of each team are in each room (room population is
+    /// the sum over its teams)
     pub team_counts: HashMap<(usize, Team), usize>,
 
-    // Maximum players per game room
-    // pub max_players: usize,
+    /// Spawn slot held by each player: (room, team, slot index)
+    pub slots: HashMap<String, (usize, Team, usize)>,
+
+    /// Per-room team setup (rooms without an entry use default_room)
+    pub room_settings: HashMap<usize, RoomSettings>,
+    pub default_room: RoomSettings,
+
+    /// Random XZ offset radius around the team spawn point (AVENLAB_SPAWN_JITTER, m)
+    pub jitter: f32,
+    rng: StdRng, // GameRng child "spawn"
+
+    /// Maximum players per game room (AVENLAB_ROOM_CAPACITY)
+    pub max_players: usize,
 }
 
 impl SpawnManager {
-    pub fn new(_max_players:usize) -> Self {
+    pub fn new(max_players: usize, rng: StdRng) -> Self {
+        let default_count = std::env::var("AVENLAB_TEAM_COUNT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(2);
+        let mut room_settings: HashMap<usize, RoomSettings> = std::env::var("AVENLAB_ROOM_TEAMS")
+            .map(|v| parse_room_teams(&v))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(room, count)| (room, RoomSettings::with_team_count(count)))
+            .collect();
+        let locked = std::env::var("AVENLAB_LOCKED_SETUP_ROOMS").map(|v| parse_locked_rooms(&v)).unwrap_or_default();
+        for room in locked {
+            room_settings
+                .entry(room)
+                .or_insert_with(|| RoomSettings::with_team_count(default_count))
+                .tune = TuneRules::locked();
+        }
+        // Same "1,3" room list format as the locked setup rooms
+        let team_debug = std::env::var("AVENLAB_TEAM_DEBUG_ROOMS").map(|v| parse_locked_rooms(&v)).unwrap_or_default();
+        for room in team_debug {
+            room_settings
+                .entry(room)
+                .or_insert_with(|| RoomSettings::with_team_count(default_count))
+                .team_debug = true;
+        }
+        // Same "room:value" format as the team counts
+        let tick_hz = std::env::var("AVENLAB_ROOM_TICK_HZ").map(|v| parse_room_teams(&v)).unwrap_or_default();
+        for (room, hz) in tick_hz.into_iter().filter(|(_, hz)| *hz > 0) {
+            room_settings
+                .entry(room)
+                .or_insert_with(|| RoomSettings::with_team_count(default_count))
+                .tick_hz = Some(hz as u32);
+        }
+
         Self {
-            // room_counts: HashMap::new(),
             team_counts: HashMap::new(),
-            // max_players: max_players,
+            slots: HashMap::new(),
+            room_settings,
+            default_room: RoomSettings::with_team_count(default_count),
+            jitter: std::env::var("AVENLAB_SPAWN_JITTER")
+                .ok()
+                .and_then(|v| v.parse::<f32>().ok())
+                .filter(|j| j.is_finite())
+                .map_or(0.0, |j| j.clamp(0.0, MAX_SPAWN_JITTER)),
+            rng,
+            max_players: std::env::var("AVENLAB_ROOM_CAPACITY")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|n| *n > 0)
+                .unwrap_or(max_players)
+                .max(1),
         }
     }
 
+    pub fn settings(&self, room_id: usize) -> &RoomSettings {
+        self.room_settings.get(&room_id).unwrap_or(&self.default_room)
+    }
+
+    #[allow(dead_code)]
+    pub fn set_room_settings(&mut self, room_id: usize, settings: RoomSettings) {
+        self.room_settings.insert(room_id, settings);
+    }
+
+    /// Name + color for a team in a room (falls back to the palette if the
+    /// room was reconfigured with fewer teams than the index).
+    pub fn team_info(&self, room_id: usize, team: Team) -> TeamInfo {
+        let i = team.index() as usize;
+        self.settings(room_id).teams.get(i).cloned().unwrap_or_else(|| {
+            let (name, color) = TEAM_PALETTE[i % MAX_TEAMS];
+            TeamInfo { name: name.to_string(), color: color.to_string() }
+        })
+    }
+
 
     // ---------------------------------------------------------
     // Generate a new player ID
@@ -73,80 +251,187 @@ impl SpawnManager {
 
 
     // ---------------------------------------------------------
-    // Find a room that has space OR create a new one
+    // Find a room that has space OR create a new one: the lowest room id
+    // below capacity, so freed slots in early rooms are refilled first
+    // and a new id only opens when every room is full
     // ---------------------------------------------------------
-    // fn get_or_create_room(&mut self) -> usize {
-    //     // Find room with space
-    //     for (&room_id, &count) in self.room_counts.iter() {
-    //         if count < self.max_players {
-    //             return room_id;
-    //         }
-    //     }
+    fn get_or_create_room(&self) -> usize {
+        (0..).find(|room_id| self.has_space(*room_id)).unwrap_or(0)
+    }
 
-    //     // No room found → create new
-    //     let new_room = self.room_counts.len();
-    //     self.room_counts.insert(new_room, 0);
-    //     new_room
-    // }
+    pub fn has_space(&self, room_id: usize) -> bool {
+        self.room_population(room_id) < self.max_players
+    }
 
     // ---------------------------------------------------------
-    // Decide team based on balance
+    // Decide team based on balance (fewest players, lowest index on ties)
     // ---------------------------------------------------------
     fn choose_team(&mut self, room_id: usize) -> Team {
-        let red = *self.team_counts.get(&(room_id, Team::Red)).unwrap_or(&0);
-        let blue = *self.team_counts.get(&(room_id, Team::Blue)).unwrap_or(&0);
+        let count = self.settings(room_id).team_count();
+        (0..count as u8)
+            .map(Team)
+            .min_by_key(|t| *self.team_counts.get(&(room_id, *t)).unwrap_or(&0))
+            .unwrap_or(Team(0))
+    }
+
+    // ---------------------------------------------------------
+    // Get spawn location depending on room + team + slot
+    // ---------------------------------------------------------
+    // Teams are spread evenly on a ring; with 2 teams slot 0 is the classic
+    // red (-5, 4, 0) / blue (5, 4, 0) layout. A team gets as many slots as
+    // the room holds players; jittered spawns stay SLOT_SPACING_M apart.
+    fn slot_spacing(&self) -> f32 {
+        SLOT_SPACING_M + 2.0 * self.jitter
+    }
+
+    fn spawn_for_slot(&self, room_id: usize, team: Team, slot: usize) -> [f32; 3] {
+        slot_spawn_point(self.settings(room_id).team_count(), team, slot, self.slot_spacing())
+    }
 
-        if red <= blue {
-            Team::Red
-        } else {
-            Team::Blue
+    // ---------------------------------------------------------
+    // Pick a team slot no other player holds (`own` = the asking player's
+    // current slot counts as free). The first free slot with no live car
+    // within the slot spacing wins; if every free slot is blocked, the one
+    // farthest from any live car.
+    // ---------------------------------------------------------
+    fn choose_slot(&self, room_id: usize, team: Team, live: &[[f32; 3]], own: Option<&str>) -> usize {
+        let taken: Vec<usize> = self
+            .slots
+            .iter()
+            .filter(|(id, (r, t, _))| *r == room_id && *t == team && Some(id.as_str()) != own)
+            .map(|(_, (_, _, slot))| *slot)
+            .collect();
+        let slot_count = self.max_players.max(taken.len() + 1);
+        let mut best: Option<(usize, f32)> = None;
+        for slot in (0..slot_count).filter(|s| !taken.contains(s)) {
+            let clear = clearance(self.spawn_for_slot(room_id, team, slot), live);
+            if clear >= self.slot_spacing() {
+                return slot;
+            }
+            if best.is_none_or(|(_, c)| clear > c) {
+                best = Some((slot, clear));
+            }
         }
+        best.map_or(0, |(slot, _)| slot)
     }
 
     // ---------------------------------------------------------
-    // Get spawn location depending on room + team
+    // Where players can appear: every team slot of the default layout
+    // and of every room with its own settings, with the jitter radius
+    // around it (map.rs keeps obstacles out of these)
     // ---------------------------------------------------------
-    // fn spawn_for_team(team: Team) -> [f32; 3] {
-    //     match team {
-    //         Team::Red => [-10.0, 2.0, 0.0],   // left base
-    //         Team::Blue => [10.0, 2.0, 0.0],   // right base
-    //     }
-    // }
+    pub fn spawn_areas(&self) -> Vec<([f32; 3], f32)> {
+        let mut counts: Vec<usize> = std::iter::once(&self.default_room)
+            .chain(self.room_settings.values())
+            .map(RoomSettings::team_count)
+            .collect();
+        counts.sort_unstable();
+        counts.dedup();
+        let mut areas: Vec<([f32; 3], f32)> = Vec::new();
+        for count in counts {
+            let slots = (0..count as u8).flat_map(|t| (0..self.max_players).map(move |s| (Team(t), s)));
+            for position in slots.map(|(team, slot)| slot_spawn_point(count, team, slot, self.slot_spacing())) {
+                if !areas.iter().any(|(p, _)| *p == position) {
+                    areas.push((position, self.jitter));
+                }
+            }
+        }
+        areas
+    }
 
     // ---------------------------------------------------------
     // Full allocation pipeline called from net.rs
     // ---------------------------------------------------------
-    pub fn allocate_spawn(&mut self, player_id:String) -> PlayerSpawnInfo {
-        // let room_id = self.get_or_create_room();
-        let room_id = 0; // TEMP FIX: all players in room 0
+    // `live` = positions of the cars already in the world (all rooms share
+    // it), so a slot with someone parked on it is skipped.
+    pub fn allocate_spawn(&mut self, player_id:String, live: &[[f32; 3]]) -> PlayerSpawnInfo {
+        let room_id = self.get_or_create_room();
+        self.allocate_spawn_in_room(player_id, room_id, live)
+    }
 
-        // increment room count
-        // *self.room_counts.entry(room_id).or_insert(0) += 1;
-        
-        // Count how many players of each team in this room
-        let _red_count = *self.team_counts.get(&(room_id, Team::Red)).unwrap_or(&0);
-        let _blue_count = *self.team_counts.get(&(room_id, Team::Blue)).unwrap_or(&0);
+    // ---------------------------------------------------------
+    // Give back a team slot (disconnect / room migration)
+    // ---------------------------------------------------------
+    pub fn release_spawn(&mut self, player_id: &str, room_id: usize, team: Team) {
+        if let Some(count) = self.team_counts.get_mut(&(room_id, team)) {
+            *count = count.saturating_sub(1);
+        }
+        if self.slots.get(player_id).is_some_and(|(r, t, _)| *r == room_id && *t == team) {
+            self.slots.remove(player_id);
+        }
+    }
+
+    // ---------------------------------------------------------
+    // Re-take a team slot for a player restored from a saved session
+    // (their car keeps its saved position)
+    // ---------------------------------------------------------
+    pub fn restore_spawn(&mut self, player_id: &str, room_id: usize, team: Team) {
+        *self.team_counts.entry((room_id, team)).or_default() += 1;
+        let slot = self.choose_slot(room_id, team, &[], None);
+        self.slots.insert(player_id.to_string(), (room_id, team, slot));
+    }
 
-        // Choose the next team based on imbalance
-        // let team = if red_count <= blue_count {
-        //     Team::Red
-        // } else {
-        //     Team::Blue
-        // };
+    pub fn room_population(&self, room_id: usize) -> usize {
+        self.team_counts
+            .iter()
+            .filter(|((r, _), _)| *r == room_id)
+            .map(|(_, c)| *c)
+            .sum()
+    }
 
+    /// "room 0: 10/10, room 1: 3/10" (occupied rooms, by id) for logs.
+    pub fn room_summary(&self) -> String {
+        let mut rooms: Vec<usize> = self.team_counts.iter().filter(|(_, c)| **c > 0).map(|((r, _), _)| *r).collect();
+        rooms.sort_unstable();
+        rooms.dedup();
+        if rooms.is_empty() {
+            return "no players".to_string();
+        }
+        rooms
+            .iter()
+            .map(|r| format!("room {}: {}/{}", r, self.room_population(*r), self.max_players))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
 
+    // ---------------------------------------------------------
+    // Spawn point for a player that already holds a team slot (client
+    // "respawn"): slot chosen again against the live cars (`live`
+    // without the player's own), jittered like a fresh allocation.
+    // Returns (position, yaw).
+    // ---------------------------------------------------------
+    pub fn respawn_position(&mut self, player_id: &str, room_id: usize, team: Team, live: &[[f32; 3]]) -> ([f32; 3], f32) {
+        let slot = self.choose_slot(room_id, team, live, Some(player_id));
+        self.slots.insert(player_id.to_string(), (room_id, team, slot));
+        let point = self.spawn_for_slot(room_id, team, slot);
+        (self.jittered(point), facing_center(point))
+    }
+
+    fn jittered(&mut self, mut position: [f32; 3]) -> [f32; 3] {
+        if self.jitter > 0.0 {
+            let r = self.jitter * self.rng.gen_range(0.0f32..1.0).sqrt(); // uniform over the disc
+            let angle = self.rng.gen_range(0.0..std::f32::consts::TAU);
+            let mm = |v: f32| (v * 1000.0).round() / 1000.0;
+            position[0] = mm(position[0] + r * angle.cos());
+            position[2] = mm(position[2] + r * angle.sin());
+        }
+        position
+    }
+
+    // ---------------------------------------------------------
+    // Allocation in an explicit room (migration / matchmaking)
+    // ---------------------------------------------------------
+    pub fn allocate_spawn_in_room(&mut self, player_id:String, room_id: usize, live: &[[f32; 3]]) -> PlayerSpawnInfo {
         let team = self.choose_team(room_id);
 
         // increment team count
         *self.team_counts.entry((room_id, team)).or_insert(0) += 1;
 
-        // let position = Self::spawn_for_team(team);
-
-        // SPAWN POSITION
-        let position = match team {
-            Team::Red => [-5.0, 4.0, 0.0],
-            Team::Blue => [5.0, 4.0, 0.0],
-        };
+        // SPAWN POSITION: a free team slot clear of live cars (+ jitter)
+        let slot = self.choose_slot(room_id, team, live, None);
+        self.slots.insert(player_id.clone(), (room_id, team, slot));
+        let point = self.spawn_for_slot(room_id, team, slot);
+        let position = self.jittered(point);
 
         // Return full spawn info
         PlayerSpawnInfo {
@@ -154,6 +439,95 @@ impl SpawnManager {
             team,
             room_id,
             position,
+            yaw: facing_center(point),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_rng::GameRng;
+
+    /// Two-team rooms of `capacity`, no jitter (whatever AVENLAB_* says).
+    fn manager(capacity: usize) -> SpawnManager {
+        let mut spawns = SpawnManager::new(capacity, GameRng::new(1).child("spawn"));
+        spawns.max_players = capacity;
+        spawns.jitter = 0.0;
+        spawns.room_settings.clear();
+        spawns.default_room = RoomSettings::default();
+        spawns
+    }
+
+    #[test]
+    fn rooms_fill_overflow_and_refill_freed_slots() {
+        let mut spawns = manager(3);
+        let rooms: Vec<usize> = (0..7).map(|i| spawns.allocate_spawn(format!("p{}", i), &[]).room_id).collect();
+        assert_eq!(rooms, [0, 0, 0, 1, 1, 1, 2]);
+        assert_eq!(spawns.room_summary(), "room 0: 3/3, room 1: 3/3, room 2: 1/3");
+
+        // A leaver's seat in room 0 goes to the next joiner, before room 2
+        let (room, team) = (spawns.slots["p1"].0, spawns.slots["p1"].1);
+        spawns.release_spawn("p1", room, team);
+        assert!(!spawns.slots.contains_key("p1"));
+        assert_eq!(spawns.room_population(0), 2);
+        assert_eq!(spawns.allocate_spawn("p7".to_string(), &[]).room_id, 0);
+        assert_eq!(spawns.allocate_spawn("p8".to_string(), &[]).room_id, 2);
+
+        // An emptied room drops out of the summary
+        for id in ["p6", "p8"] {
+            let (room, team, _) = spawns.slots[id];
+            spawns.release_spawn(id, room, team);
+        }
+        assert_eq!(spawns.room_summary(), "room 0: 3/3, room 1: 3/3");
+        assert_eq!(manager(3).room_summary(), "no players");
+    }
+
+    #[test]
+    fn one_team_spawns_stay_apart_and_respawns_skip_parked_cars() {
+        let horizontal = |a: [f32; 3], b: [f32; 3]| (a[0] - b[0]).hypot(a[2] - b[2]);
+        for jitter in [0.0, MAX_SPAWN_JITTER] {
+            let mut spawns = manager(10);
+            spawns.default_room = RoomSettings::with_team_count(1);
+            spawns.jitter = jitter;
+            let mut live: Vec<[f32; 3]> = Vec::new();
+            for i in 0..6 {
+                let spawn = spawns.allocate_spawn(format!("p{}", i), &live);
+                assert_eq!(spawn.team, Team(0));
+                live.push(spawn.position);
+            }
+            for (i, a) in live.iter().enumerate() {
+                for b in &live[i + 1..] {
+                    assert!(horizontal(*a, *b) >= 4.0, "jitter {}: {:?} and {:?} too close", jitter, a, b);
+                }
+            }
+
+            // p0 respawns with a wreck left on its old spot: it lands clear
+            // of that and of the other five
+            let mut others: Vec<[f32; 3]> = live[1..].to_vec();
+            others.push(live[0]);
+            let (position, _) = spawns.respawn_position("p0", 0, Team(0), &others);
+            assert!(clearance(position, &others) >= 4.0, "jitter {}: respawn at {:?}", jitter, position);
         }
     }
+
+    #[test]
+    fn jittered_spawns_replay_from_the_seed() {
+        let spawns = |seed: u64| -> Vec<(usize, [f32; 3])> {
+            let mut spawns = manager(4);
+            spawns.rng = GameRng::new(seed).child("spawn");
+            spawns.jitter = MAX_SPAWN_JITTER;
+            let mut live: Vec<[f32; 3]> = Vec::new();
+            (0..8)
+                .map(|i| {
+                    let spawn = spawns.allocate_spawn(format!("p{}", i), &live);
+                    live.push(spawn.position);
+                    (spawns.slots[&spawn.player_id].2, spawn.position)
+                })
+                .collect()
+        };
+        let first = spawns(42);
+        assert_eq!(first, spawns(42));
+        assert_ne!(first, spawns(43));
+    }
 }
@@ -0,0 +1,283 @@
+// ==============================================================================
+// stall.rs — TICK-LOOP STALL DETECTION + LOCK HOLD TIMING
+// ------------------------------------------------------------------------------
+// On an overloaded host, or while a connection task sits on a mutex, the
+// interval fires late and the simulation silently lags. The main loop measures
+// the gap between consecutive iterations (taken once both locks are held) and
+// keeps an EWMA and a max of it. A gap above AVENLAB_STALL_FACTOR x the tick
+// period (default 2.0) is a stall:
+//
+//   log    : one structured line (JSON) with the gap, the EWMA / max, how long
+//            this iteration waited for each lock, the longest hold of each lock
+//            since the previous iteration (any holder), the previous tick's own
+//            run time, and which of those is to blame
+//   health : HealthState counts stalls (total and per blamed lock) and keeps
+//            the gap EWMA / max for /healthz and /metrics (health.rs)
+//
+// TimedMutex wraps the physics / game-state mutexes; its guard records how long
+// it was held, so a slow net.rs handler shows up next to the stall it caused.
+// ==============================================================================
+
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::{Mutex, MutexGuard};
+
+const DEFAULT_STALL_FACTOR: f32 = 2.0;
+const GAP_EWMA_ALPHA: f32 = 0.1;
+
+// ==========================================================
+// Timed mutex
+// ==========================================================
+pub struct TimedMutex<T> {
+    name: &'static str,
+    inner: Mutex<T>,
+    max_hold_us: AtomicU64, // longest hold since the last take_max_hold()
+}
+
+impl<T> TimedMutex<T> {
+    pub fn new(name: &'static str, value: T) -> Self {
+        Self { name, inner: Mutex::new(value), max_hold_us: AtomicU64::new(0) }
+    }
+
+    pub async fn lock(&self) -> TimedGuard<'_, T> {
+        let guard = self.inner.lock().await;
+        TimedGuard { guard, acquired: Instant::now(), max_hold_us: &self.max_hold_us }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Longest hold since the previous call (resets the window).
+    pub fn take_max_hold(&self) -> Duration {
+        Duration::from_micros(self.max_hold_us.swap(0, Ordering::Relaxed))
+    }
+}
+
+pub struct TimedGuard<'a, T> {
+    guard: MutexGuard<'a, T>,
+    acquired: Instant,
+    max_hold_us: &'a AtomicU64,
+}
+
+impl<T> Deref for TimedGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for TimedGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for TimedGuard<'_, T> {
+    fn drop(&mut self) {
+        let held = self.acquired.elapsed().as_micros() as u64;
+        self.max_hold_us.fetch_max(held, Ordering::Relaxed);
+    }
+}
+
+// ==========================================================
+// Stall monitor (owned by the main loop)
+// ==========================================================
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct LockTiming {
+    pub lock: &'static str,
+    pub wait_ms: f32,     // this iteration's wait to acquire it
+    pub max_hold_ms: f32, // longest hold by anyone since the previous iteration
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StallReport {
+    pub tick: u64,
+    pub gap_ms: f32,
+    pub expected_ms: f32,
+    pub ewma_ms: f32,
+    pub max_gap_ms: f32,
+    pub prev_tick_ms: f32, // run_tick time of the previous iteration
+    pub locks: Vec<LockTiming>,
+    pub blamed: &'static str, // a lock name, or "tick"
+}
+
+pub struct StallMonitor {
+    period: Duration,
+    threshold: Duration,
+    last: Option<Instant>,
+    prev_tick_time: Duration,
+    ewma_ms: f32,
+    max_gap_ms: f32,
+}
+
+impl StallMonitor {
+    pub fn new(period: Duration) -> Self {
+        Self {
+            period,
+            threshold: period.mul_f32(stall_factor_from_env()),
+            last: None,
+            prev_tick_time: Duration::ZERO,
+            ewma_ms: period.as_secs_f32() * 1000.0,
+            max_gap_ms: 0.0,
+        }
+    }
+
+    /// Called once both locks are held, with each mutex and this iteration's
+    /// wait for it; the hold window of each is read and reset.
+    pub fn observe<A, B>(
+        &mut self,
+        tick: u64,
+        now: Instant,
+        first: (&TimedMutex<A>, Duration),
+        second: (&TimedMutex<B>, Duration),
+    ) -> Option<StallReport> {
+        let timing = |name: &'static str, wait: Duration, hold: Duration| LockTiming {
+            lock: name,
+            wait_ms: wait.as_secs_f32() * 1000.0,
+            max_hold_ms: hold.as_secs_f32() * 1000.0,
+        };
+        let locks = vec![
+            timing(first.0.name(), first.1, first.0.take_max_hold()),
+            timing(second.0.name(), second.1, second.0.take_max_hold()),
+        ];
+
+        let gap = now.duration_since(self.last.replace(now)?);
+        let gap_ms = gap.as_secs_f32() * 1000.0;
+        self.ewma_ms += (gap_ms - self.ewma_ms) * GAP_EWMA_ALPHA;
+        self.max_gap_ms = self.max_gap_ms.max(gap_ms);
+        if gap <= self.threshold {
+            return None;
+        }
+
+        // Whatever ate the most of the gap: waiting on a lock, or the tick itself
+        let prev_tick_ms = self.prev_tick_time.as_secs_f32() * 1000.0;
+        let blamed = locks
+            .iter()
+            .map(|l| (l.lock, l.wait_ms))
+            .chain(std::iter::once(("tick", prev_tick_ms)))
+            .fold(("tick", f32::MIN), |best, c| if c.1 > best.1 { c } else { best })
+            .0;
+
+        Some(StallReport {
+            tick,
+            gap_ms,
+            expected_ms: self.period.as_secs_f32() * 1000.0,
+            ewma_ms: self.ewma_ms,
+            max_gap_ms: self.max_gap_ms,
+            prev_tick_ms,
+            locks,
+            blamed,
+        })
+    }
+
+    pub fn tick_done(&mut self, tick_time: Duration) {
+        self.prev_tick_time = tick_time;
+    }
+
+    pub fn ewma_ms(&self) -> f32 {
+        self.ewma_ms
+    }
+
+    pub fn max_gap_ms(&self) -> f32 {
+        self.max_gap_ms
+    }
+}
+
+fn stall_factor_from_env() -> f32 {
+    std::env::var("AVENLAB_STALL_FACTOR")
+        .ok()
+        .and_then(|v| v.parse::<f32>().ok())
+        .filter(|f| f.is_finite() && *f > 1.0)
+        .unwrap_or(DEFAULT_STALL_FACTOR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::health::HealthState;
+
+    const PERIOD: Duration = Duration::from_millis(16);
+    const SLEEP: Duration = Duration::from_millis(60);
+
+    /// One main-loop iteration: take physics, then state, and observe.
+    async fn iteration(monitor: &mut StallMonitor, tick: u64, physics: &TimedMutex<u8>, state: &TimedMutex<u8>) -> Option<StallReport> {
+        let woke = Instant::now();
+        let _phys = physics.lock().await;
+        let physics_wait = woke.elapsed();
+        let _game = state.lock().await;
+        let acquired = Instant::now();
+        let report = monitor.observe(tick, acquired, (physics, physics_wait), (state, acquired - woke - physics_wait));
+        monitor.tick_done(acquired.elapsed());
+        report
+    }
+
+    /// Hold `lock` for SLEEP in another task, starting before the next iteration.
+    async fn hold(lock: &Arc<TimedMutex<u8>>) {
+        let (held_tx, held_rx) = tokio::sync::oneshot::channel();
+        let lock = lock.clone();
+        tokio::spawn(async move {
+            let _guard = lock.lock().await;
+            let _ = held_tx.send(());
+            tokio::time::sleep(SLEEP).await;
+        });
+        held_rx.await.unwrap();
+    }
+
+    async fn stall_while_holding(blame: &str) -> StallReport {
+        let physics = Arc::new(TimedMutex::new("physics", 0));
+        let state = Arc::new(TimedMutex::new("state", 0));
+        let mut monitor = StallMonitor::new(PERIOD);
+        assert!(iteration(&mut monitor, 1, &physics, &state).await.is_none(), "first iteration has no gap");
+
+        hold(if blame == "physics" { &physics } else { &state }).await;
+        iteration(&mut monitor, 2, &physics, &state).await.expect("a 60 ms gap is a stall")
+    }
+
+    #[tokio::test]
+    async fn a_long_physics_hold_is_a_stall_blamed_on_physics() {
+        let report = stall_while_holding("physics").await;
+        assert_eq!(report.blamed, "physics");
+        assert!(report.gap_ms >= 55.0 && report.max_gap_ms >= report.gap_ms, "{:?}", report);
+        let lock = |name: &str| *report.locks.iter().find(|l| l.lock == name).unwrap();
+        assert!(lock("physics").wait_ms >= 55.0 && lock("physics").max_hold_ms >= 55.0, "{:?}", report);
+        assert!(lock("state").max_hold_ms < 20.0, "{:?}", report);
+
+        let health = HealthState::new(0);
+        health.record_stall(&report);
+        let metrics = health.metrics();
+        assert!(metrics.contains("avenlab_tick_stalls_total 1\n"), "{}", metrics);
+        assert!(metrics.contains("avenlab_tick_stalls_by_cause{cause=\"physics\"} 1\n"), "{}", metrics);
+    }
+
+    #[tokio::test]
+    async fn a_long_state_hold_is_blamed_on_state() {
+        let report = stall_while_holding("state").await;
+        assert_eq!(report.blamed, "state");
+        let state = report.locks.iter().find(|l| l.lock == "state").unwrap();
+        assert!(state.wait_ms >= 55.0 && state.max_hold_ms >= 55.0, "{:?}", report);
+    }
+
+    #[tokio::test]
+    async fn on_time_iterations_are_not_stalls_and_a_slow_tick_blames_the_tick() {
+        let physics = TimedMutex::new("physics", 0);
+        let state = TimedMutex::new("state", 0);
+        let mut monitor = StallMonitor::new(PERIOD);
+        for tick in 0..5 {
+            assert!(iteration(&mut monitor, tick, &physics, &state).await.is_none());
+            tokio::time::sleep(Duration::from_millis(2)).await;
+        }
+
+        // The previous tick ran long without anyone else holding a lock
+        monitor.tick_done(SLEEP);
+        tokio::time::sleep(SLEEP).await;
+        let report = iteration(&mut monitor, 5, &physics, &state).await.expect("stall");
+        assert_eq!(report.blamed, "tick");
+        assert!(report.ewma_ms > PERIOD.as_secs_f32() * 1000.0 * 0.5 && report.ewma_ms < report.gap_ms);
+    }
+}
@@ -1,16 +1,37 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::time::Instant;
 
 use rapier3d::prelude::*;
 // use serde::Serialize;
 use serde_json::json;
-use crate::physics::DebugOverlay;
+use crate::physics::{DebugOverlay, PhysicsWorld, DEBUG_ALL, DEBUG_HIGH_FREQUENCY, MAGNET_FORCE_N};
 use crate::spawn::{PlayerSpawnInfo, SpawnManager, Team};
-use tokio::sync::mpsc::UnboundedSender;
+use crate::recording::Recorder;
+use crate::plugins::{PluginHost, RoomEvent};
+use crate::haptics::HapticEvent;
+use crate::collisions::CollisionRecord;
+use crate::interest::{ClientView, InterestConfig};
+use crate::delta::{Delta, SnapshotHistory};
+use crate::input_buffer::{BufferedInput, InputBuffer, MAX_LEAD_SECS};
+use crate::input_sanity::AXIS_RANGES;
+use crate::join_queue::JoinQueue;
+use crate::timing::TrackTiming;
+use crate::ghost::Ghosts;
+use crate::game_rng::GameRng;
+use crate::tick_rate::TickRate;
+use crate::time_sync::server_time_ms;
+use crate::tick_metrics::{TickMetrics, TickSummary};
+use crate::latency::{ClientLatency, LatencyTotals};
+use crate::moderation::{ModerationError, ModerationPolicy, RenameLimiter, unique_name};
+use crate::protocol;
+use crate::snapshot::{ClientTx, DebugMessage, Encoding, OutFrame, OwnVehicle, SnapshotData, SnapshotEntry, SnapshotMessage, SnapshotPlayer, SnapshotVelocity, TurretAngles};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use tokio::sync::oneshot;
 
 /// =======================
 /// Player Input (from net)
 /// =======================
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Axes {
     pub throttle: f32,
     pub steer: f32,
@@ -21,11 +42,68 @@ pub struct Axes {
     pub roll: f32,
 }
 
+impl Axes {
+    /// Overwrite the axes an input message carried (input_hold::AXIS_NAMES
+    /// order, None = absent).
+    pub fn merge(&mut self, axes: &[Option<f32>; 7]) {
+        let [throttle, steer, brake, ascend, pitch, yaw, roll] = *axes;
+        let fields = [
+            (&mut self.throttle, throttle),
+            (&mut self.steer, steer),
+            (&mut self.brake, brake),
+            (&mut self.ascend, ascend),
+            (&mut self.pitch, pitch),
+            (&mut self.yaw, yaw),
+            (&mut self.roll, roll),
+        ];
+        for (field, value) in fields {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct EntityInput {
     pub axes: Axes,
 }
 
+/// One message in the input mailbox: posted by a connection's read loop
+/// without taking a lock, applied by the main loop at the start of the next
+/// tick (SharedGameState::apply_input_mailbox). Posted with its receive time
+/// (latency.rs). Everything that touches physics goes this way; replies
+/// (acks, errors) are sent by the tick.
+#[derive(Debug, Clone)]
+pub enum MailboxInput {
+    Axes([Option<f32>; 7], Option<u64>, Option<u64>), // "input", input_hold::AXIS_NAMES order, None = absent; seq; tick
+    Turret { yaw: f32, pitch: f32 },  // "turret" deltas (rad)
+    Fire(Option<u64>),                // "fire": snapshot tick the shooter saw, None = now
+    Ack(u64),                         // "ack": snapshot tick received (delta.rs)
+    SeqReset,                         // respawned: the client restarts its input seq
+    Adjust(f32),                      // "adjust": brake bias step, already checked against the room's tune
+    Ability { repel: bool, target: String }, // "ability": one tick of magnet force
+    Hello { input_hold: Option<serde_json::Value>, ack: serde_json::Value }, // per-axis hold overrides, hello_ack's other fields
+    Respawn,                          // "respawn"
+    SwitchRoom(usize),                // "switch_room"
+    SetTimescale(f32),                // admin "set_timescale"
+    SetPayload { target: String, mass: f32, offset: [f32; 3] }, // admin "set_payload"
+    Trace([f32; 7]),                  // attached input trace's axes for the tick (input_trace.rs)
+}
+
+/// A connection's handle on the input mailbox.
+#[derive(Debug, Clone)]
+pub struct InputSender {
+    player_id: String,
+    tx: UnboundedSender<(String, MailboxInput, Instant)>,
+}
+
+impl InputSender {
+    pub fn send(&self, input: MailboxInput) {
+        let _ = self.tx.send((self.player_id.clone(), input, Instant::now()));
+    }
+}
+
 /// =========================
 /// Entity Type (server-side)
 /// =========================
@@ -51,6 +129,18 @@ impl EntityType {
             EntityType::Ship => "ship",
         }
     }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "vehicle" => Some(EntityType::Vehicle),
+            "drone" => Some(EntityType::Drone),
+            "helicopter" => Some(EntityType::Helicopter),
+            "jet" => Some(EntityType::Jet),
+            "boat" => Some(EntityType::Boat),
+            "ship" => Some(EntityType::Ship),
+            _ => None,
+        }
+    }
 }
 
 /// =========================
@@ -59,15 +149,96 @@ impl EntityType {
 #[derive(Debug, Clone)]
 pub struct EntityState {
     pub id: String,
+    pub name: String,
     pub kind: EntityType,
     pub room_id: usize,
     pub team: Team,
     pub body_handle: RigidBodyHandle,
+    /// What the client last asked for (every axis it has sent, merged); the
+    /// vehicle's axes are this after input_hold decay / staleness
     pub last_input: Option<EntityInput>,
+    /// (tick, position) from the last snapshot; None after spawn / teleport
+    pub last_snapshot: Option<(u64, [f32; 3])>,
+    /// Game tick of the last client "respawn" (cooldown)
+    pub last_respawn_tick: Option<u64>,
+    /// Highest input "seq" applied (client prediction); None after respawn
+    pub input_seq: Option<u64>,
+    /// Secret sent in this player's welcome only; reclaims the car after a
+    /// warm restart (session.rs)
+    pub resume_token: String,
+}
+
+/// A queued "entity_added" / "entity_removed" for one room's clients,
+/// flushed ahead of the next snapshot (broadcast_snapshot)
+#[derive(Debug, Clone)]
+pub struct EntityEvent {
+    pub room_id: usize,
+    pub except: Option<String>, // the player it's about, when it already knows
+    pub msg: String,
+}
+
+
+
+
+/// =========================
+/// Debug overlay subscription (per client)
+/// =========================
+#[derive(Debug, Clone)]
+pub struct DebugSubscription {
+    /// Requested categories (DEBUG_* bitmask)
+    pub categories: u32,
+    /// Drop high-frequency primitives when the watched vehicle is fast
+    pub auto_lod: bool,
+    /// Another player's vehicle to watch (coach / spectator); None = the
+    /// shared overlay. Cancelled with "debug_target_lost" when it despawns.
+    pub target: Option<String>,
 }
 
+impl Default for DebugSubscription {
+    fn default() -> Self {
+        Self { categories: DEBUG_ALL, auto_lod: false, target: None }
+    }
+}
 
+/// Speed (m/s) above which auto LOD keeps only the numeric wheel block
+pub const DEBUG_AUTO_LOD_SPEED: f32 = 15.0;
 
+/// Minimum time between a player's client "respawn" requests
+pub const RESPAWN_COOLDOWN_S: f32 = 3.0;
+
+/// Period of the "📊" server stats log line (AVENLAB_STATS_LOG_SECS, 0 = off)
+pub const DEFAULT_STATS_LOG_SECS: u64 = 60;
+
+pub fn stats_log_interval() -> Option<std::time::Duration> {
+    let secs = std::env::var("AVENLAB_STATS_LOG_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_STATS_LOG_SECS);
+    (secs > 0).then(|| std::time::Duration::from_secs(secs))
+}
+
+/// ================================
+/// Snapshot plausibility (exploit / physics-bug telemetry)
+/// ================================
+/// Implausible = speed from the position delta, or the reported linear
+/// velocity, exceeds twice the vehicle's max_speed (or values are NaN/Inf).
+pub fn validate_snapshot_entity(
+    pos: [f32; 3],
+    vel: [f32; 3],
+    ticks_elapsed: u64,
+    tick_dt: f32,
+    prev_pos: [f32; 3],
+    max_speed: f32,
+) -> bool {
+    let limit = max_speed * 2.0;
+    let norm = |v: [f32; 3]| (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+
+    let speed = norm(vel);
+    let delta = [pos[0] - prev_pos[0], pos[1] - prev_pos[1], pos[2] - prev_pos[2]];
+    let delta_speed = norm(delta) / (ticks_elapsed.max(1) as f32 * tick_dt);
+
+    speed.is_finite() && delta_speed.is_finite() && speed <= limit && delta_speed <= limit
+}
 
 /// ================================
 /// Shared Game State
@@ -75,6 +246,14 @@ pub struct EntityState {
 pub struct SharedGameState {
     pub tick: u64,
 
+    /// Ticks per second of the main loop; dt for everything per-tick (AVENLAB_TICK_HZ)
+    pub tick_rate: TickRate,
+    /// Snapshots go out on every n-th tick (--snapshot-divisor, server_config.rs)
+    pub snapshot_divisor: u32,
+    /// How far the simulation is behind wall time (ms), set by the main loop
+    /// before each tick (tick_rate.rs TickClock); sent in every snapshot
+    pub drift_ms: f32,
+
     /// All active entities keyed by player_id
     pub entities: HashMap<String, EntityState>,
 
@@ -82,40 +261,270 @@ pub struct SharedGameState {
     pub spawns: crate::spawn::SpawnManager,
 
     /// All connected WebSocket clients for this process
-    pub clients: HashMap<String, UnboundedSender<String>>,
-    
+    pub clients: HashMap<String, ClientTx>,
+    /// Snapshot / debug encoding per client (hello "encoding"; missing = JSON)
+    pub encodings: HashMap<String, Encoding>,
+
+    /// Input / turret messages from every read loop, drained once per tick
+    input_tx: UnboundedSender<(String, MailboxInput, Instant)>,
+    input_rx: UnboundedReceiver<(String, MailboxInput, Instant)>,
+
+    /// Debug overlay subscriptions keyed by player_id (missing = everything)
+    pub debug_subs: HashMap<String, DebugSubscription>,
+
+    /// Authorized admin connections receiving admin_event messages
+    pub admin_subscribers: HashSet<String>,
+    /// Admin connections receiving the 1 s "server_stats" tick summary
+    pub stats_subscribers: HashSet<String>,
+    /// Phase timings of the last ticks (tick_metrics.rs)
+    pub tick_metrics: TickMetrics,
+    /// Read loops' kick signal (admin "kick" closes that connection)
+    pub kick_handles: HashMap<String, oneshot::Sender<()>>,
+
+    /// Clients whose snapshots carry body velocities (hello "want_velocities")
+    pub velocity_clients: HashSet<String>,
+    /// Tick-tagged inputs waiting for their tick + measured lead, per player
+    pub input_buffers: HashMap<String, InputBuffer>,
+    /// Input → apply → snapshot latency per client, and over all clients
+    /// (latency.rs; "stats" reply, /metrics)
+    pub latency: HashMap<String, ClientLatency>,
+    pub latency_totals: LatencyTotals,
+    /// Clients that sent an input "seq": their snapshots carry last_processed_seq
+    /// + their own vehicle's full dynamic state (client prediction)
+    pub prediction_clients: HashSet<String>,
+
+    /// Input/checksum recorder (enabled via AVENLAB_RECORD)
+    pub recorder: Option<Recorder>,
+
+    /// Server seed; subsystems draw from child RNGs (AVENLAB_SEED)
+    pub rng: GameRng,
+
+    /// Gameplay plugins (registered in main.rs)
+    pub plugins: PluginHost,
+
+    /// Players may request `switch_room` themselves (AVENLAB_ALLOW_ROOM_SWITCH=1)
+    pub allow_room_switch: bool,
+
+    /// Display name / chat policy + rename rate limit
+    pub moderation: ModerationPolicy,
+    pub rename_limiter: RenameLimiter,
+
+    /// Snapshot interest radius / keepalive, and what each client knows
+    pub interest: InterestConfig,
+    pub client_views: HashMap<String, ClientView>,
+    /// Recent snapshot poses + acked ticks, for delta snapshots
+    pub snapshot_history: SnapshotHistory,
+
+    /// Connections waiting for a free slot (AVENLAB_MAX_PLAYERS)
+    pub join_queue: JoinQueue,
+
+    /// Lap / sector timing on the checkpoint chain (AVENLAB_TRACK_FILE)
+    pub timing: Option<TrackTiming>,
+    /// Best-lap ghost traces + who has theirs turned on
+    pub ghosts: Ghosts,
+
+    /// Warm restart file (--persist-session); None = sessions aren't kept
+    pub session_file: Option<String>,
+    /// Connections watching a room without a vehicle: player_id → room_id.
+    /// Registered in `clients` (snapshots, debug overlays) but no entity
+    pub spectators: HashMap<String, usize>,
+
+    /// Restored / dropped players not reconnected yet: player_id → tick their car is removed
+    pub awaiting_reclaim: HashMap<String, u64>,
+    /// Dropped connections' cars are held this long (AVENLAB_DISCONNECT_GRACE_SECS; 0 = not held)
+    pub disconnect_grace_ticks: u64,
+    /// entity_added / entity_removed since the last snapshot; sent right
+    /// before it, so the first snapshot with (last without) a car follows
+    /// its event
+    pub entity_events: Vec<EntityEvent>,
 }
 
 impl SharedGameState {
-    pub fn new() -> Self {
+    pub fn new(tick_rate: TickRate) -> Self {
+        let rng = GameRng::from_env();
+        let mut recorder = Recorder::from_env();
+        let (input_tx, input_rx) = unbounded_channel();
+        let grace_secs = crate::session::disconnect_grace_secs();
+        let disconnect_grace_ticks = if grace_secs > 0.0 { tick_rate.ticks(grace_secs) } else { 0 };
+        if let Some(rec) = recorder.as_mut() {
+            rec.record_seed(rng.seed());
+            rec.record_tick_rate(tick_rate.hz());
+        }
         Self {
             tick: 0,
+            tick_rate,
+            snapshot_divisor: 1,
+            drift_ms: 0.0,
             entities: HashMap::new(),
-            spawns: SpawnManager::new(10),
+            spawns: SpawnManager::new(10, rng.child("spawn")),
             clients: HashMap::new(),
+            encodings: HashMap::new(),
+            input_tx,
+            input_rx,
+            debug_subs: HashMap::new(),
+            admin_subscribers: HashSet::new(),
+            velocity_clients: HashSet::new(),
+            prediction_clients: HashSet::new(),
+            kick_handles: HashMap::new(),
+            stats_subscribers: HashSet::new(),
+            tick_metrics: TickMetrics::new(tick_rate.period()),
+            input_buffers: HashMap::new(),
+            latency: HashMap::new(),
+            latency_totals: LatencyTotals::default(),
+            recorder,
+            rng,
+            plugins: PluginHost::default(),
+            allow_room_switch: std::env::var("AVENLAB_ALLOW_ROOM_SWITCH").is_ok_and(|v| v == "1"),
+            moderation: ModerationPolicy::from_env(),
+            rename_limiter: RenameLimiter::default(),
+            interest: InterestConfig::from_env(tick_rate),
+            client_views: HashMap::new(),
+            snapshot_history: SnapshotHistory::default(),
+            join_queue: JoinQueue::new(0),
+            timing: TrackTiming::from_env(),
+            ghosts: Ghosts::default(),
+            session_file: None,
+            spectators: HashMap::new(),
+            awaiting_reclaim: HashMap::new(),
+            disconnect_grace_ticks,
+            entity_events: Vec::new(),
         }
     }
 
     /// Register a new client sender so we can push snapshots to it.
-    pub fn register_client(&mut self, player_id: String, tx: UnboundedSender<String>) {
+    pub fn register_client(&mut self, player_id: String, tx: ClientTx) {
         self.clients.insert(player_id, tx);
         // self.clients.push(tx);
     }
 
     pub fn unregister_client(&mut self, player_id: &str) {
         self.clients.remove(player_id);
+        self.debug_subs.remove(player_id);
+        self.admin_subscribers.remove(player_id);
+        self.kick_handles.remove(player_id);
+        self.stats_subscribers.remove(player_id);
+        self.velocity_clients.remove(player_id);
+        self.prediction_clients.remove(player_id);
+        self.input_buffers.remove(player_id);
+        self.latency.remove(player_id);
+        self.encodings.remove(player_id);
+        self.rename_limiter.forget(player_id);
+        self.client_views.remove(player_id);
+        self.snapshot_history.forget(player_id);
+        self.spectators.remove(player_id);
+    }
+
+    /// Register a spectator: snapshots and debug overlays of `room_id`, no
+    /// entity, vehicle, spawn slot or join queue place.
+    pub fn add_spectator(&mut self, player_id: &str, room_id: usize, tx: ClientTx) {
+        self.register_client(player_id.to_string(), tx);
+        self.spectators.insert(player_id.to_string(), room_id);
+    }
+
+    /// Room a connection sees: its vehicle's, or the one it spectates.
+    pub fn client_room(&self, player_id: &str) -> Option<usize> {
+        self.entities.get(player_id).map(|e| e.room_id).or_else(|| self.spectators.get(player_id).copied())
+    }
+
+    /// One-line server summary (periodic log in main.rs).
+    pub fn server_stats(&self) -> serde_json::Value {
+        json!({
+            "tick": self.tick,
+            "players": self.entities.len().saturating_sub(self.awaiting_reclaim.len()),
+            "held": self.awaiting_reclaim.len(),
+            "spectators": self.spectators.len(),
+            "queued": self.join_queue.queued(),
+            "drift_ms": self.drift_ms.round(),
+            "tick_ms": self.tick_metrics.latest.map(|s| s.total),
+            "slow_ticks": self.tick_metrics.latest.map(|s| s.slow_ticks),
+        })
+    }
+
+    /// The closed 1 s tick summary to "subscribe_stats" admins.
+    pub fn send_server_stats(&self, summary: &TickSummary) {
+        if self.stats_subscribers.is_empty() {
+            return;
+        }
+        let msg = protocol::event("server_stats", json!({ "tick": self.tick, "stats": summary }));
+        for id in &self.stats_subscribers {
+            if let Some(tx) = self.clients.get(id) {
+                let _ = tx.send(msg.clone());
+            }
+        }
+    }
+
+    /// Log an operator-facing event and push it to subscribed admin clients:
+    /// {"type":"admin_event","event":<event>,"player_id":..,"tick":..,<body fields>}
+    pub fn emit_admin_event(&self, event: &str, player_id: &str, body: serde_json::Value) {
+        println!("🚩 Admin event {} for {}: {}", event, player_id, body);
+        let mut fields = json!({ "event": event, "player_id": player_id, "tick": self.tick });
+        if let (Some(fields), serde_json::Value::Object(body)) = (fields.as_object_mut(), body) {
+            fields.extend(body);
+        }
+        let msg = protocol::event("admin_event", fields);
+        for id in &self.admin_subscribers {
+            if let Some(tx) = self.clients.get(id) {
+                let _ = tx.send(msg.clone());
+            }
+        }
+    }
+
+    /// Update what a client wants in its debug overlay stream.
+    pub fn set_debug_subscription(&mut self, player_id: &str, sub: DebugSubscription) {
+        self.debug_subs.insert(player_id.to_string(), sub);
+    }
+
+    /// May `viewer` watch `target`'s overlay? Admins (spectators / coaches)
+    /// may watch anyone; players only a teammate, in rooms whose settings
+    /// allow it (AVENLAB_TEAM_DEBUG_ROOMS).
+    pub fn check_debug_target(&self, viewer: &str, target: &str, admin: bool) -> Result<(), &'static str> {
+        let Some(target_ent) = self.entities.get(target) else { return Err("unknown_debug_target") };
+        if admin {
+            return Ok(());
+        }
+        let teammate = self.entities.get(viewer).is_some_and(|v| v.room_id == target_ent.room_id && v.team == target_ent.team);
+        if teammate && self.spawns.settings(target_ent.room_id).team_debug {
+            Ok(())
+        } else {
+            Err("debug_target_forbidden")
+        }
+    }
+
+    /// Players someone is watching (PhysicsWorld::watched_overlays).
+    pub fn debug_targets(&self) -> HashSet<String> {
+        self.debug_subs.values().filter_map(|sub| sub.target.clone()).collect()
+    }
+
+    /// Vehicles whose own overlay the next step must keep: the watched
+    /// targets, plus every car in a room with a shared-overlay subscriber
+    /// (the room's overlay is built from them).
+    pub fn overlay_vehicles(&self) -> HashSet<String> {
+        let shared_rooms: HashSet<usize> = self.clients.keys()
+            .filter(|id| self.debug_subs.get(id.as_str()).is_none_or(|sub| sub.target.is_none()))
+            .filter_map(|id| self.client_room(id))
+            .collect();
+        let mut ids = self.debug_targets();
+        ids.extend(self.entities.values().filter(|e| shared_rooms.contains(&e.room_id)).map(|e| e.id.clone()));
+        ids
     }
 
     /// Create an entity entry. net.rs calls this right after it decides
     /// which EntityType this connection will be (Vehicle / Drone / etc).
     pub fn add_entity(&mut self, id: &str, kind: EntityType) {
+        let base = format!("Player-{}", id.chars().take(4).collect::<String>());
+        let name = unique_name(&base, self.moderation.max_name_len, |n| self.name_taken(n, id));
         let ent = EntityState {
             id: id.to_string(),
+            name,
             kind,
             room_id: 0, // overwritten later
-            team: Team::Red, // overwritten later
+            team: Team(0), // overwritten later
             body_handle: RigidBodyHandle::invalid(),
             last_input: None,
+            last_snapshot: None,
+            last_respawn_tick: None,
+            input_seq: None,
+            resume_token: uuid::Uuid::new_v4().to_string(),
         };
         self.entities.insert(id.to_string(), ent);
     }
@@ -148,47 +557,1026 @@ impl SharedGameState {
         }
     }
 
+    /// Server ticks per step in `room_id` (1 unless AVENLAB_ROOM_TICK_HZ slows it).
+    pub fn room_tick_divisor(&self, room_id: usize) -> u32 {
+        self.spawns.settings(room_id).tick_hz.map_or(1, |hz| self.tick_rate.room_divisor(hz))
+    }
+
+    /// Put `id`'s vehicle on its room's tick rate (after every spawn and
+    /// room move; changes are recorded for replay).
+    pub fn apply_room_tick_rate(&mut self, phys: &mut PhysicsWorld, id: &str) {
+        let Some(room_id) = self.entities.get(id).map(|e| e.room_id) else { return };
+        let divisor = self.room_tick_divisor(room_id);
+        if phys.set_tick_divisor(id, divisor)
+            && let Some(rec) = self.recorder.as_mut()
+        {
+            rec.record_tick_divisor(self.tick + 1, id, divisor);
+        }
+    }
+
+
+    /// Kick signal for `player_id`'s read loop (net.rs): resolves when an
+    /// admin kicks it.
+    pub fn register_kick(&mut self, player_id: &str) -> oneshot::Receiver<()> {
+        let (kick, kicked) = oneshot::channel();
+        self.kick_handles.insert(player_id.to_string(), kick);
+        kicked
+    }
+
+    /// Admin "kick": the target's room hears {"type":"player_kicked"}, the
+    /// car is despawned right here (recorded as a kick) and the connection,
+    /// if any, closes with reason "kicked"; its disconnect cleanup finds
+    /// nothing left but the client maps.
+    /// Callers hold both locks (physics, then game).
+    pub fn kick_player(&mut self, phys: &mut PhysicsWorld, player_id: &str) -> Result<(), &'static str> {
+        let room_id = self.client_room(player_id).ok_or("unknown_player")?;
+        let kick = self.kick_handles.remove(player_id);
+        if kick.is_none() && !self.awaiting_reclaim.contains_key(player_id) {
+            return Err("player_not_connected"); // still joining
+        }
+        let msg = protocol::event("player_kicked", json!({ "id": player_id }));
+        self.send_to_room(room_id, &msg, Some(player_id));
+        if let Some(kick) = kick {
+            let _ = kick.send(());
+        }
+        self.take_out_player(phys, player_id, true);
+        println!("👢 Kicked {} (admin)", player_id);
+        Ok(())
+    }
+
+    /// Admin "reset_world": held cars are despawned, every other car gets a
+    /// fresh spawn allocation (room, team, slot) as if everyone had just
+    /// joined, in player id order, config / tune / payload kept. Every client
+    /// gets {"type":"world_reset"}, drivers with their new room and team.
+    /// Returns the number of cars respawned.
+    /// Callers hold both locks (physics, then game).
+    pub fn reset_world(&mut self, phys: &mut PhysicsWorld) -> usize {
+        let held: Vec<String> = self.awaiting_reclaim.keys().cloned().collect();
+        for id in &held {
+            self.despawn_player(phys, id);
+        }
+
+        let mut ids: Vec<String> = self.entities.keys().cloned().collect();
+        ids.sort();
+        for id in &ids {
+            if let Some(ent) = self.entities.get(id) {
+                self.spawns.release_spawn(id, ent.room_id, ent.team);
+            }
+        }
+
+        let tick = self.tick + 1;
+        let mut placed = Vec::new();
+        for id in &ids {
+            let spawn = self.spawns.allocate_spawn(id.clone(), &placed);
+            let handle = match phys.respawn_vehicle_for_player(id, spawn.position, spawn.yaw) {
+                Ok(handle) => handle,
+                Err(errors) => {
+                    // Config was valid when it spawned; drop the player rather than leave it
+                    // bodiless. Its old slot is already released: point the entity at the
+                    // new one so the despawn gives that back. Despawned in this tick, the
+                    // connection just closes as "kicked".
+                    eprintln!("❌ Reset respawn failed for {}: {:?}", id, errors);
+                    self.apply_spawn_info(&spawn);
+                    if let Some(kick) = self.kick_handles.remove(id) {
+                        let _ = kick.send(());
+                    }
+                    self.take_out_player(phys, id, true);
+                    continue;
+                }
+            };
+            placed.push(spawn.position);
+            self.apply_spawn_info(&spawn);
+            if let Some(ent) = self.entities.get_mut(id) {
+                ent.body_handle = handle;
+                ent.last_snapshot = None; // teleport, not a speed anomaly
+                ent.last_respawn_tick = None;
+            }
+            if let Some(rec) = self.recorder.as_mut() {
+                rec.record_rebuild(tick, id, spawn.position, spawn.yaw);
+            }
+            self.apply_room_tick_rate(phys, id);
+            self.plugins.entity_removed(id);
+            self.plugins.entity_spawned(id);
+            self.client_views.remove(id);
+            let _ = self.input_tx.send((id.clone(), MailboxInput::SeqReset, Instant::now()));
+        }
+
+        for (player_id, tx) in &self.clients {
+            let mut fields = json!({ "tick": tick });
+            if let Some(ent) = self.entities.get(player_id) {
+                let team = self.spawns.team_info(ent.room_id, ent.team);
+                fields["room_id"] = ent.room_id.into();
+                fields["team"] = team.name.into();
+                fields["team_index"] = ent.team.index().into();
+                fields["team_color"] = team.color.into();
+                fields["team_count"] = self.spawns.settings(ent.room_id).team_count().into();
+                fields["spawn_yaw"] = phys.vehicle_yaw(player_id).into();
+            }
+            let _ = tx.send(protocol::event("world_reset", fields));
+        }
+        for id in self.clients.keys() {
+            self.send_room_layouts(phys, id);
+        }
+
+        println!("🧹 World reset: {} car(s) respawned, {} held car(s) removed", placed.len(), held.len());
+        println!("🏠 Rooms: {}", self.spawns.room_summary());
+        placed.len()
+    }
+
+    /// Admin "list_players": every car (connected or held for reclaim) with
+    /// room, team and position, plus the spectators (the "players" message's
+    /// fields).
+    pub fn list_players(&self, phys: &PhysicsWorld) -> serde_json::Value {
+        let mut players: Vec<serde_json::Value> = self
+            .entities
+            .values()
+            .map(|ent| {
+                let position: Option<[f32; 3]> = phys.bodies.get(ent.body_handle).map(|b| (*b.translation()).into());
+                json!({
+                    "id": ent.id,
+                    "name": ent.name,
+                    "room_id": ent.room_id,
+                    "team": self.spawns.team_info(ent.room_id, ent.team).name,
+                    "team_index": ent.team.index(),
+                    "position": position,
+                    "held": self.awaiting_reclaim.contains_key(&ent.id),
+                })
+            })
+            .collect();
+        players.sort_by(|a, b| a["id"].as_str().cmp(&b["id"].as_str()));
+        let mut spectators: Vec<serde_json::Value> = self
+            .spectators
+            .iter()
+            .map(|(id, room_id)| json!({ "id": id, "room_id": room_id }))
+            .collect();
+        spectators.sort_by(|a, b| a["id"].as_str().cmp(&b["id"].as_str()));
+        json!({ "tick": self.tick, "players": players, "spectators": spectators })
+    }
+
+    /// Post every attached input trace's next tick to the mailbox, the way
+    /// its client would (input_trace.rs); before apply_input_mailbox.
+    pub fn post_trace_inputs(&self, phys: &mut PhysicsWorld) {
+        for (player_id, axes) in phys.next_trace_inputs() {
+            self.input_sender(&player_id).send(MailboxInput::Trace(axes));
+        }
+    }
+
+    /// Mailbox handle for `player_id`'s read loop (net.rs): every message
+    /// that touches physics goes through it instead of locking physics per
+    /// message.
+    pub fn input_sender(&self, player_id: &str) -> InputSender {
+        InputSender { player_id: player_id.to_string(), tx: self.input_tx.clone() }
+    }
+
+    /// Apply everything posted to the input mailbox since the last tick, in
+    /// arrival order, and merge accepted axes into last_input. Once per tick,
+    /// before input traces and decay (main.rs run_tick). Messages for players
+    /// that have left since (or dropped: a held car takes no input) are
+    /// dropped, and so are inputs whose seq isn't above the last one applied
+    /// or queued (duplicates, reordered frames).
+    /// Tick-tagged inputs go through the player's InputBuffer and apply when
+    /// their tick comes up (input_buffer.rs). The other messages (fire, adjust,
+    /// ability, hello, respawn, switch_room, admin timescale / payload) run
+    /// here in the same order and reply to their client.
+    pub fn apply_input_mailbox(&mut self, phys: &mut PhysicsWorld) {
+        let current = self.tick + 1; // the tick about to be simulated
+        let max_lead = self.tick_rate.ticks(MAX_LEAD_SECS);
+        let drained = Instant::now();
+        while let Ok((player_id, input, received)) = self.input_rx.try_recv() {
+            // Spectators only post acks and admin commands; everything else needs a live vehicle
+            let driving = self.entities.contains_key(&player_id);
+            let vehicle_free = matches!(input, MailboxInput::Ack(_) | MailboxInput::SetTimescale(_) | MailboxInput::SetPayload { .. });
+            if !self.clients.contains_key(&player_id) || (!driving && !vehicle_free) {
+                continue;
+            }
+            if matches!(input, MailboxInput::Axes(..) | MailboxInput::Turret { .. }) {
+                let waited = self.latency.entry(player_id.clone()).or_default().consumed(received, current, drained);
+                self.latency_totals.input_to_apply.record(waited);
+            }
+            match input {
+                MailboxInput::Axes(axes, seq, target) => {
+                    let last_seq = self.entities.get(&player_id).and_then(|e| e.input_seq);
+                    let queued_seq = self.input_buffers.get(&player_id).and_then(|b| b.queued_seq());
+                    if seq.is_some_and(|seq| last_seq.max(queued_seq).is_some_and(|last| seq <= last)) {
+                        continue;
+                    }
+                    let input = BufferedInput { axes, seq };
+                    let now = match target {
+                        Some(target) => self.input_buffers.entry(player_id.clone()).or_default().push(target, current, max_lead, input),
+                        None => vec![input],
+                    };
+                    for input in now {
+                        self.apply_axes(phys, &player_id, input);
+                    }
+                }
+                MailboxInput::Trace(axes) => {
+                    // Ranges as a client's axes get them in net.rs (traces are finite by construction)
+                    let axes: [f32; 7] = std::array::from_fn(|i| axes[i].clamp(AXIS_RANGES[i].0, AXIS_RANGES[i].1));
+                    let applied = phys.apply_trace_input(&player_id, axes);
+                    self.record_axes(&player_id, BufferedInput { axes: axes.map(Some), seq: None }, applied);
+                }
+                MailboxInput::Turret { yaw, pitch } => phys.apply_turret_input(&player_id, yaw, pitch),
+                MailboxInput::Fire(seen) => {
+                    // Lag-compensated: targets as of the snapshot the shooter saw
+                    let seen = seen.unwrap_or(self.tick).min(self.tick);
+                    let room = self.entities.get(&player_id).map(|e| e.room_id);
+                    let hit = phys.fire_projectile(&player_id, seen, |id| self.entities.get(id).map(|e| e.room_id) == room);
+                    let hit = hit.map(|h| json!({ "target": h.target, "distance": h.distance, "point": h.point }));
+                    self.reply(&player_id, protocol::event("fire_ack", json!({ "tick": seen, "hit": hit })));
+                }
+                MailboxInput::SeqReset => self.reset_input_seq(phys, &player_id),
+                MailboxInput::Ack(tick) => {
+                    if let Err(reason) = self.snapshot_history.ack(&player_id, tick, self.tick) {
+                        self.reply(&player_id, protocol::event("error", json!({ "reason": reason, "tick": tick })));
+                    }
+                }
+                MailboxInput::Adjust(delta) => match phys.adjust_brake_bias(&player_id, delta) {
+                    Some(bias) => self.reply(&player_id, protocol::event("adjust_ack", json!({ "brake_bias": bias }))),
+                    None => self.reply_error(&player_id, "no_vehicle"),
+                },
+                MailboxInput::Ability { repel, target } => {
+                    let room = |id: &str| self.entities.get(id).map(|e| e.room_id);
+                    if room(&target).is_none() || room(&target) != room(&player_id) {
+                        self.reply_error(&player_id, "invalid_target");
+                        continue;
+                    }
+                    let dt = self.tick_rate.dt(); // one tick of force
+                    let applied = if repel {
+                        phys.apply_magnetic_repulsion(&player_id, &target, MAGNET_FORCE_N, dt)
+                    } else {
+                        phys.apply_magnetic_attraction(&player_id, &target, MAGNET_FORCE_N, dt)
+                    };
+                    if !applied {
+                        self.reply_error(&player_id, "target_out_of_range");
+                    } else if let Some(rec) = self.recorder.as_mut() {
+                        rec.record_ability(current, &player_id, &target, repel);
+                    }
+                }
+                MailboxInput::Hello { input_hold, mut ack } => {
+                    // Per-axis input hold: {"input_hold":{"steer":"decay_to_neutral",..}}
+                    if let Some(vehicle) = phys.vehicles.get_mut(&player_id) {
+                        if let Some(overrides) = input_hold.as_ref() {
+                            match vehicle.input_hold.with_overrides(overrides) {
+                                Ok(hold) => vehicle.input_hold = hold,
+                                Err(reason) => self.reply_error(&player_id, reason),
+                            }
+                        }
+                        ack["input_hold"] = vehicle.input_hold.to_json();
+                    }
+                    self.reply(&player_id, protocol::event("hello_ack", ack));
+                }
+                MailboxInput::Respawn => {
+                    if let Err(reason) = self.respawn_player(phys, &player_id) {
+                        self.reply_error(&player_id, reason);
+                    }
+                }
+                MailboxInput::SwitchRoom(room_id) => {
+                    if !self.allow_room_switch {
+                        self.reply_error(&player_id, "room_switch_disabled");
+                    } else if let Err(e) = self.move_player(phys, &player_id, room_id) {
+                        self.reply_error(&player_id, &e);
+                    }
+                }
+                MailboxInput::SetTimescale(scale) => {
+                    let scale = phys.set_timestep_scale(scale);
+                    if let Some(rec) = self.recorder.as_mut() {
+                        rec.record_timescale(current, scale);
+                    }
+                    println!("⏱️ Timescale set to {:.2}", scale);
+                    self.reply(&player_id, protocol::event("timescale_ack", json!({ "timescale": scale })));
+                }
+                MailboxInput::SetPayload { target, mass, offset } => match phys.set_payload(&target, mass, offset) {
+                    Ok(payload) => {
+                        if let Some(rec) = self.recorder.as_mut() {
+                            rec.record_payload(current, &target, payload.mass, payload.offset);
+                        }
+                        self.reply(&player_id, protocol::event("payload_ack", json!({
+                            "player_id": target,
+                            "payload": payload,
+                        })));
+                    }
+                    Err(reason) => self.reply_error(&player_id, reason),
+                },
+            }
+        }
+
+        // Buffered inputs whose tick has come
+        let due: Vec<(String, Vec<BufferedInput>)> = self
+            .input_buffers
+            .iter_mut()
+            .filter(|(_, buffer)| !buffer.is_empty())
+            .map(|(id, buffer)| (id.clone(), buffer.due(current)))
+            .collect();
+        for (player_id, inputs) in due {
+            if !self.entities.contains_key(&player_id) {
+                continue;
+            }
+            for input in inputs {
+                self.apply_axes(phys, &player_id, input);
+            }
+        }
+    }
+
+    /// A reply from the tick to the client that posted the message.
+    fn reply(&self, player_id: &str, msg: String) {
+        if let Some(tx) = self.clients.get(player_id) {
+            let _ = tx.send(msg);
+        }
+    }
+
+    fn reply_error(&self, player_id: &str, reason: &str) {
+        self.reply(player_id, protocol::event("error", json!({ "reason": reason })));
+    }
+
+    /// The client restarts its input seq (respawn, world reset): inputs
+    /// queued under the old seq apply first.
+    fn reset_input_seq(&mut self, phys: &mut PhysicsWorld, player_id: &str) {
+        let queued = self.input_buffers.get_mut(player_id).map(|b| b.flush()).unwrap_or_default();
+        for input in queued {
+            self.apply_axes(phys, player_id, input);
+        }
+        if let Some(ent) = self.entities.get_mut(player_id) {
+            ent.input_seq = None;
+        }
+    }
 
-    /// Store the latest input from a player. Physics loop will read this
-    /// every tick in main.rs and apply forces.
-    // pub fn update_input(&mut self, id: &str, axes: Axes) {
-    //     if let Some(ent) = self.entities.get_mut(id) {
-    //         ent.last_input = Some(EntityInput { axes });
-    //     }
-    // }
+    /// One input message's axes onto the player's vehicle (absent axes keep
+    /// their value); rejections go back to the client as errors.
+    fn apply_axes(&mut self, phys: &mut PhysicsWorld, player_id: &str, input: BufferedInput) {
+        let applied = phys.apply_partial_input(player_id, input.axes);
+        self.record_axes(player_id, input, applied);
+    }
+
+    /// Merge accepted axes into the entity's last_input, or tell the client
+    /// why they were rejected.
+    fn record_axes(&mut self, player_id: &str, input: BufferedInput, applied: Result<(), &'static str>) {
+        match applied {
+            Ok(()) => {
+                if let Some(ent) = self.entities.get_mut(player_id) {
+                    ent.last_input.get_or_insert_with(|| EntityInput { axes: Axes::default() }).axes.merge(&input.axes);
+                    if input.seq.is_some() {
+                        ent.input_seq = ent.input_seq.max(input.seq);
+                        self.prediction_clients.insert(player_id.to_string());
+                    }
+                }
+            }
+            Err(reason) => {
+                if let Some(tx) = self.clients.get(player_id) {
+                    let _ = tx.send(protocol::event("error", json!({ "reason": reason })));
+                }
+            }
+        }
+    }
 
     /// Remove an entity when the player disconnects.
+    /// Also gives its team slot back to the SpawnManager.
     pub fn remove_entity(&mut self, id: &str) {
-        self.entities.remove(id);
+        if let Some(ent) = self.entities.remove(id) {
+            self.spawns.release_spawn(id, ent.room_id, ent.team);
+        }
+
+        // Whoever watched this car loses the subscription
+        let watchers: Vec<String> = self
+            .debug_subs
+            .iter()
+            .filter(|(_, sub)| sub.target.as_deref() == Some(id))
+            .map(|(watcher, _)| watcher.clone())
+            .collect();
+        for watcher in watchers {
+            self.debug_subs.remove(&watcher);
+            if let Some(tx) = self.clients.get(&watcher) {
+                let _ = tx.send(protocol::event("debug_target_lost", json!({ "target": id })));
+            }
+        }
+        if let Some(timing) = self.timing.as_mut() {
+            timing.remove_player(id);
+        }
+        self.ghosts.remove_player(id);
+    }
+
+    /// Hand a held car (warm restart, dropped connection) to the connection
+    /// presenting its resume token: the client is registered in the same
+    /// step. Returns the player id.
+    pub fn reclaim(&mut self, token: &str, tx: ClientTx) -> Option<String> {
+        let id = self
+            .awaiting_reclaim
+            .keys()
+            .find(|id| self.entities.get(*id).is_some_and(|e| e.resume_token == token))?
+            .clone();
+        self.awaiting_reclaim.remove(&id);
+        self.register_client(id.clone(), tx);
+        Some(id)
     }
 
+    /// A connection dropped without leaving: keep its car (entity, body, spawn
+    /// slot) for disconnect_grace_ticks, for reclaim by resume token;
+    /// session::expire_reclaims removes it after that. Per-client state goes.
+    pub fn hold_for_reclaim(&mut self, player_id: &str) {
+        self.unregister_client(player_id);
+        self.awaiting_reclaim.insert(player_id.to_string(), self.tick + self.disconnect_grace_ticks);
+    }
 
-    pub fn broadcast_debug_overlay(&mut self, overlay: &DebugOverlay) {
-        if self.clients.is_empty() {
+    /// Take a player out of the game entirely (disconnect, expired reclaim):
+    /// physics vehicle, recorder, plugins, client maps, entity + spawn slot;
+    /// the freed slot goes to the head of the join queue.
+    /// Callers hold both locks (physics, then game).
+    pub fn despawn_player(&mut self, phys: &mut PhysicsWorld, player_id: &str) {
+        self.take_out_player(phys, player_id, false);
+    }
+
+    /// despawn_player, recorded as a kick when `kicked` (recording.rs).
+    fn take_out_player(&mut self, phys: &mut PhysicsWorld, player_id: &str, kicked: bool) {
+        if !self.entities.contains_key(player_id) && !phys.vehicles.contains_key(player_id) {
+            // Already taken out (reset_world): only the connection is left to close
+            self.unregister_client(player_id);
             return;
         }
+        phys.remove_vehicle(player_id);
 
-        let payload = json!({
-            "type": "debug",
-            "data": overlay
-        });
+        let tick = self.tick + 1;
+        if let Some(rec) = self.recorder.as_mut() {
+            if kicked {
+                rec.record_kick(tick, player_id);
+            } else {
+                rec.record_despawn(tick, player_id);
+            }
+        }
+        self.plugins.entity_removed(player_id);
+        if let Some(room_id) = self.entities.get(player_id).map(|e| e.room_id) {
+            self.queue_entity_removed(player_id, room_id, None);
+        }
 
-        let msg = payload.to_string();
+        self.unregister_client(player_id);
+        self.remove_entity(player_id);
+        self.awaiting_reclaim.remove(player_id);
 
-        // for tx in self.clients.iter() {
-        //     let _ = tx.send(msg.clone());
-        // }
-        
-        for (_player_id, tx) in &self.clients {
+        let players = self.entities.len();
+        self.join_queue.player_left(players);
+    }
+
+    /// Client "respawn" (flipped, stuck, fell off the map): same car, reset
+    /// upright and at rest at its team's spawn point; everyone in the room
+    /// gets {"type":"respawned"}. At most once per RESPAWN_COOLDOWN_S. The
+    /// client's input seq restarts: the next seq'd input is accepted whatever
+    /// its number.
+    /// Called from the input mailbox (apply_input_mailbox).
+    pub fn respawn_player(&mut self, phys: &mut PhysicsWorld, player_id: &str) -> Result<[f32; 3], &'static str> {
+        let cooldown_ticks = (RESPAWN_COOLDOWN_S * self.tick_rate.hz() as f32).ceil() as u64;
+        let ent = self.entities.get(player_id).ok_or("no_vehicle")?;
+        if ent.last_respawn_tick.is_some_and(|t| self.tick < t + cooldown_ticks) {
+            return Err("respawn_cooldown");
+        }
+        let (room_id, team) = (ent.room_id, ent.team);
+
+        let live = phys.vehicle_positions(Some(player_id));
+        let (position, yaw) = self.spawns.respawn_position(player_id, room_id, team, &live);
+        let position = phys.reset_vehicle(player_id, position, yaw)?;
+
+        let tick = self.tick + 1;
+        if let Some(rec) = self.recorder.as_mut() {
+            rec.record_respawn(tick, player_id, position, yaw);
+        }
+        if let Some(ent) = self.entities.get_mut(player_id) {
+            ent.last_respawn_tick = Some(self.tick);
+            ent.last_snapshot = None; // teleport, not a speed anomaly
+        }
+        // Runs from the mailbox, so the inputs this client sent before the
+        // respawn are applied already: later ones count against the new seq
+        self.reset_input_seq(phys, player_id);
+
+        println!("🔄 Player {} respawned at {:?}", player_id, position);
+        self.send_to_room(room_id, &protocol::event("respawned", json!({
+            "id": player_id,
+            "position": position,
+            "yaw": yaw,
+            "tick": tick,
+        })), None);
+        Ok(position)
+    }
+
+    /// Invariant check (fuzz harness, debugging) across game state and
+    /// physics: every connection is a whole player (client, entity, spawn
+    /// slot, body, vehicle), every entity has a client unless it awaits
+    /// reclaim, and per-client maps only hold connected clients.
+    /// Callers hold both locks (physics, then game). Empty = consistent.
+    pub fn debug_validate(&self, phys: &PhysicsWorld) -> Vec<String> {
+        let mut errors = phys.debug_validate();
+
+        for id in self.clients.keys().filter(|id| !self.entities.contains_key(*id) && !self.spectators.contains_key(*id)) {
+            errors.push(format!("client {} registered without an entity", id));
+        }
+        for id in self.spectators.keys().filter(|id| self.entities.contains_key(*id)) {
+            errors.push(format!("spectator {} has an entity", id));
+        }
+        let mut population: HashMap<(usize, Team), usize> = HashMap::new();
+        for (id, ent) in &self.entities {
+            let held = self.awaiting_reclaim.contains_key(id);
+            if !self.clients.contains_key(id) && !held {
+                errors.push(format!("entity {} without a client", id));
+            }
+            if self.clients.contains_key(id) && held {
+                errors.push(format!("entity {} connected but still awaiting reclaim", id));
+            }
+            if ent.body_handle == RigidBodyHandle::invalid() {
+                errors.push(format!("entity {} without a body", id));
+            } else if phys.body_to_player.get(&ent.body_handle) != Some(id) {
+                errors.push(format!("entity {}: body {:?} isn't its physics body", id, ent.body_handle));
+            }
+            if !phys.vehicles.contains_key(id) {
+                errors.push(format!("entity {} without a physics vehicle", id));
+            }
+            *population.entry((ent.room_id, ent.team)).or_default() += 1;
+        }
+        for id in self.awaiting_reclaim.keys().filter(|id| !self.entities.contains_key(*id)) {
+            errors.push(format!("awaiting_reclaim entry {} without an entity", id));
+        }
+        for id in phys.vehicles.keys().filter(|id| !self.entities.contains_key(*id)) {
+            errors.push(format!("physics vehicle {} without an entity", id));
+        }
+
+        for (&(room, team), &count) in &self.spawns.team_counts {
+            let actual = population.get(&(room, team)).copied().unwrap_or(0);
+            if count != actual {
+                errors.push(format!("room {} team {}: {} spawn slots taken, {} entities", room, team.index(), count, actual));
+            }
+        }
+        for (&(room, team), _) in population.iter().filter(|(key, _)| !self.spawns.team_counts.contains_key(*key)) {
+            errors.push(format!("room {} team {}: entities without a spawn slot", room, team.index()));
+        }
+        for (id, ent) in &self.entities {
+            if self.spawns.slots.get(id).is_none_or(|(r, t, _)| (*r, *t) != (ent.room_id, ent.team)) {
+                errors.push(format!("entity {}: no spawn slot in its room / team", id));
+            }
+        }
+        for id in self.spawns.slots.keys().filter(|id| !self.entities.contains_key(*id)) {
+            errors.push(format!("spawn slot held by {} without an entity", id));
+        }
+
+        let per_client = [
+            ("debug_subs", self.debug_subs.keys().collect::<Vec<_>>()),
+            ("admin_subscribers", self.admin_subscribers.iter().collect()),
+            ("velocity_clients", self.velocity_clients.iter().collect()),
+            ("prediction_clients", self.prediction_clients.iter().collect()),
+            ("input_buffers", self.input_buffers.keys().collect()),
+            ("latency", self.latency.keys().collect()),
+            ("kick_handles", self.kick_handles.keys().collect()),
+            ("stats_subscribers", self.stats_subscribers.iter().collect()),
+            ("spectators", self.spectators.keys().collect()),
+            ("client_views", self.client_views.keys().collect()),
+            ("snapshot_acks", self.snapshot_history.acked_clients().collect()),
+        ];
+        for (map, ids) in per_client {
+            for id in ids.into_iter().filter(|id| !self.clients.contains_key(*id)) {
+                errors.push(format!("{} entry for disconnected client {}", map, id));
+            }
+        }
+        for (id, sub) in &self.debug_subs {
+            if let Some(target) = sub.target.as_ref().filter(|t| !self.entities.contains_key(*t)) {
+                errors.push(format!("debug_subs entry {} watches missing player {}", id, target));
+            }
+        }
+        errors
+    }
+
+    /// Tell every connected client the server is going away.
+    pub fn broadcast_shutdown(&self) {
+        let msg = protocol::event("server_shutdown", json!({ "tick": self.tick }));
+        for tx in self.clients.values() {
             let _ = tx.send(msg.clone());
         }
+        self.join_queue.broadcast(&msg);
     }
 
-    pub fn broadcast_snapshot(&mut self, bodies: &RigidBodySet) {
-        // If no clients, do nothing (saves work when menu/server idle)
+    /// Persist the session roster (names, rooms, teams) as JSON on shutdown.
+    pub fn save_to_file(&self, path: &str) -> std::io::Result<()> {
+        let mut ids: Vec<&String> = self.entities.keys().collect();
+        ids.sort();
+
+        let players: Vec<serde_json::Value> = ids
+            .into_iter()
+            .map(|id| {
+                let ent = &self.entities[id];
+                json!({
+                    "id": ent.id,
+                    "name": ent.name,
+                    "kind": ent.kind.as_str(),
+                    "room_id": ent.room_id,
+                    "team": self.spawns.team_info(ent.room_id, ent.team).name,
+                    "team_index": ent.team.index(),
+                })
+            })
+            .collect();
+
+        let doc = json!({ "tick": self.tick, "players": players });
+        std::fs::write(path, serde_json::to_string_pretty(&doc)?)
+    }
+
+    /// Send a message to every client whose entity is in `room_id`.
+    pub fn send_to_room(&self, room_id: usize, msg: &str, except: Option<&str>) {
+        for (player_id, tx) in &self.clients {
+            if Some(player_id.as_str()) == except {
+                continue;
+            }
+            if self.client_room(player_id) == Some(room_id) {
+                let _ = tx.send(msg.to_string());
+            }
+        }
+    }
+
+    /// What "entity_added" and the welcome's "entities" list say about a car.
+    pub fn entity_info(&self, phys: &PhysicsWorld, id: &str) -> Option<serde_json::Value> {
+        let ent = self.entities.get(id)?;
+        let team = self.spawns.team_info(ent.room_id, ent.team);
+        Some(json!({
+            "id": ent.id,
+            "kind": ent.kind.as_str(),
+            "name": ent.name,
+            "room_id": ent.room_id,
+            "team": team.name,
+            "team_index": ent.team.index(),
+            "team_color": team.color,
+            "vehicle": phys.vehicles.get(id).map(|v| v.config_name.as_str()),
+        }))
+    }
+
+    /// Every car in `room_id` (held ones included), by id: the welcome's
+    /// "entities", so a late joiner starts from a full list.
+    pub fn room_entities(&self, phys: &PhysicsWorld, room_id: usize) -> Vec<serde_json::Value> {
+        let mut ids: Vec<&String> = self.entities.values().filter(|e| e.room_id == room_id).map(|e| &e.id).collect();
+        ids.sort();
+        ids.into_iter().filter_map(|id| self.entity_info(phys, id)).collect()
+    }
+
+    /// The welcome for a player that holds a car in `room_id` on `team`
+    /// (fresh join or resume).
+    pub fn player_welcome(&self, phys: &PhysicsWorld, player_id: &str, room_id: usize, team: Team, resumed: bool, is_admin: bool) -> String {
+        let ent = self.entities.get(player_id);
+        let team_info = self.spawns.team_info(room_id, team);
+        protocol::welcome(json!({
+            "player_id": player_id,
+            "room_id": u32::try_from(room_id).unwrap_or(u32::MAX),
+            "team": team_info.name,
+            "team_index": team.index(),
+            "team_color": team_info.color,
+            "team_count": self.spawns.settings(room_id).team_count(),
+            "name": ent.map(|e| e.name.clone()),
+            "tick_hz": self.tick_rate.hz(),
+            "room_tick_hz": self.tick_rate.hz() / self.room_tick_divisor(room_id), // snapshots arrive at this rate
+            "resume_token": ent.map(|e| e.resume_token.clone()),
+            "resumed": resumed,
+            "is_admin": is_admin,
+            "vehicle": phys.vehicles.get(player_id).map(|v| v.config_name.clone()), // config actually spawned (unknown ?vehicle= falls back to gt86)
+            "spawn_yaw": phys.vehicle_yaw(player_id), // radians about +Y, 0 = +Z; client camera starts behind the car
+            // Chassis extents / COM + wheel list of the spawned car (same schema as "vehicle_layout")
+            "vehicle_layout": phys.vehicle_layout(player_id),
+            "entities": self.room_entities(phys, room_id), // every car in the room, ours included (entity_added / entity_removed after this)
+        }))
+    }
+
+    /// Queue {"type":"entity_added",..entity_info,"vehicle_layout"} for the
+    /// car's room (spawn, room switch), skipping `except`.
+    pub fn queue_entity_added(&mut self, phys: &PhysicsWorld, id: &str, except: Option<&str>) {
+        let (Some(mut fields), Some(room_id)) = (self.entity_info(phys, id), self.entities.get(id).map(|e| e.room_id)) else {
+            return;
+        };
+        fields["vehicle_layout"] = json!(phys.vehicle_layout(id));
+        let msg = protocol::event("entity_added", fields);
+        self.entity_events.push(EntityEvent { room_id, except: except.map(str::to_string), msg });
+    }
+
+    /// Queue {"type":"entity_removed"} for the room the car left (despawn,
+    /// room switch), skipping `except`.
+    pub fn queue_entity_removed(&mut self, id: &str, room_id: usize, except: Option<&str>) {
+        let msg = protocol::event("entity_removed", json!({ "id": id, "room_id": room_id }));
+        self.entity_events.push(EntityEvent { room_id, except: except.map(str::to_string), msg });
+    }
+
+    /// Move a player to another room without reconnecting:
+    /// release the old slot, allocate a spawn in the target room, rebuild the
+    /// physics body there (config preserved) and notify both rooms. Fails
+    /// when the target room is at capacity (SpawnManager::max_players).
+    pub fn move_player(
+        &mut self,
+        phys: &mut PhysicsWorld,
+        player_id: &str,
+        room_id: usize,
+    ) -> Result<PlayerSpawnInfo, String> {
+        let Some(ent) = self.entities.get(player_id) else {
+            return Err(format!("unknown player {}", player_id));
+        };
+        let (from_room, from_team) = (ent.room_id, ent.team);
+        if from_room == room_id {
+            return Err(format!("player {} is already in room {}", player_id, room_id));
+        }
+
+        if !self.spawns.has_space(room_id) {
+            return Err(format!("room {} is full ({} players)", room_id, self.spawns.max_players));
+        }
+
+        let live = phys.vehicle_positions(Some(player_id));
+        let old_slot = self.spawns.slots.get(player_id).copied();
+        let spawn = self.spawns.allocate_spawn_in_room(player_id.to_string(), room_id, &live);
+
+        let handle = match phys.respawn_vehicle_for_player(player_id, spawn.position, spawn.yaw) {
+            Ok(h) => h,
+            Err(errors) => {
+                self.spawns.release_spawn(player_id, room_id, spawn.team);
+                if let Some(slot) = old_slot {
+                    self.spawns.slots.insert(player_id.to_string(), slot);
+                }
+                let reasons: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                return Err(format!("respawn failed: {:?}", reasons));
+            }
+        };
+        self.spawns.release_spawn(player_id, from_room, from_team);
+
+        let tick = self.tick + 1;
+        if let Some(rec) = self.recorder.as_mut() {
+            rec.record_rebuild(tick, player_id, spawn.position, spawn.yaw);
+        }
+        self.plugins.entity_removed(player_id); // new body, as in reset_world
+        self.plugins.entity_spawned(player_id);
+
+        self.queue_entity_removed(player_id, from_room, Some(player_id));
+
+        if let Some(ent) = self.entities.get_mut(player_id) {
+            ent.room_id = room_id;
+            ent.team = spawn.team;
+            ent.body_handle = handle;
+            ent.last_snapshot = None; // teleport, not a speed anomaly
+        }
+        self.apply_room_tick_rate(phys, player_id);
+        self.client_views.remove(player_id); // new room: client knows nothing yet
+
+        self.queue_entity_added(phys, player_id, Some(player_id));
+
+        let team = self.spawns.team_info(room_id, spawn.team);
+        if let Some(tx) = self.clients.get(player_id) {
+            let _ = tx.send(protocol::event("room_changed", json!({
+                "room_id": room_id,
+                "team": team.name,
+                "team_index": spawn.team.index(),
+                "team_color": team.color,
+                "team_count": self.spawns.settings(room_id).team_count(),
+                "spawn_yaw": spawn.yaw,
+                "entities": self.room_entities(phys, room_id), // same as the welcome's
+            })));
+        }
+
+        self.send_room_layouts(phys, player_id);
+
+        println!("🔀 Moved {} from room {} to room {}", player_id, from_room, room_id);
+        Ok(spawn)
+    }
+
+
+    /// Tell everyone in the player's room (including the player) how its
+    /// vehicle's wheels are laid out. Call again whenever the layout changes
+    /// at runtime (ride height / wheel tuning).
+    pub fn broadcast_vehicle_layout(&self, phys: &PhysicsWorld, player_id: &str) {
+        let (Some(ent), Some(layout)) = (self.entities.get(player_id), phys.vehicle_layout(player_id)) else {
+            return;
+        };
+        let msg = protocol::event("vehicle_layout", json!({
+            "id": player_id,
+            "layout": layout,
+        }));
+        self.send_to_room(ent.room_id, &msg, None);
+    }
+
+    /// Send a (newly arrived) player or spectator the layouts of every other
+    /// vehicle in its room.
+    pub fn send_room_layouts(&self, phys: &PhysicsWorld, player_id: &str) {
+        let (Some(tx), Some(room_id)) = (self.clients.get(player_id), self.client_room(player_id)) else {
+            return;
+        };
+        for ent in self.entities.values() {
+            if ent.id == player_id || ent.room_id != room_id {
+                continue;
+            }
+            if let Some(layout) = phys.vehicle_layout(&ent.id) {
+                let _ = tx.send(protocol::event("vehicle_layout", json!({
+                    "id": ent.id,
+                    "layout": layout,
+                })));
+            }
+        }
+    }
+
+    fn name_taken(&self, name: &str, except_id: &str) -> bool {
+        self.entities
+            .values()
+            .any(|e| e.id != except_id && e.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Join-time name choice and `rename`: policy check, collision suffix,
+    /// rate limit, then a name_changed event to the player's room.
+    pub fn rename_player(&mut self, player_id: &str, requested: &str) -> Result<String, ModerationError> {
+        let now = std::time::Instant::now();
+        self.rename_limiter.check(player_id, now)?;
+
+        let wanted = self.moderation.check_name(requested)?;
+        let name = unique_name(&wanted, self.moderation.max_name_len, |n| self.name_taken(n, player_id));
+
+        let Some(ent) = self.entities.get_mut(player_id) else {
+            return Err(ModerationError::UnknownPlayer);
+        };
+        let old = std::mem::replace(&mut ent.name, name.clone());
+        let room_id = ent.room_id;
+        self.rename_limiter.record(player_id, now);
+
+        self.send_to_room(room_id, &protocol::event("name_changed", json!({
+            "id": player_id,
+            "old_name": old,
+            "name": name,
+        })), None);
+
+        println!("🏷  {} renamed '{}' → '{}'", player_id, old, name);
+        Ok(name)
+    }
+
+    /// Filter and relay a chat line to the sender's room.
+    pub fn send_chat(&self, player_id: &str, text: &str) -> Result<(), ModerationError> {
+        let Some(ent) = self.entities.get(player_id) else { return Ok(()) };
+        let text = self.moderation.filter_chat(text)?;
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        self.send_to_room(ent.room_id, &protocol::event("chat", json!({
+            "id": player_id,
+            "name": ent.name,
+            "text": text,
+        })), None);
+        Ok(())
+    }
+
+    /// Rumble / camera-shake hints go only to the affected player.
+    pub fn send_haptics(&self, events: &[HapticEvent]) {
+        for ev in events {
+            let Some(tx) = self.clients.get(&ev.player_id) else { continue };
+            if let Ok(serde_json::Value::Object(mut fields)) = serde_json::to_value(ev)
+                && let Some(serde_json::Value::String(kind)) = fields.remove("type")
+            {
+                fields.insert("tick".to_string(), json!(self.tick));
+                let _ = tx.send(protocol::event(&kind, fields.into()));
+            }
+        }
+    }
+
+    /// Chassis impacts from the last step (collisions.rs), one "collisions"
+    /// message per room that has a player in any of them.
+    pub fn broadcast_events(&self, collisions: &[CollisionRecord]) {
+        if collisions.is_empty() {
+            return;
+        }
+        let mut by_room: BTreeMap<usize, Vec<&CollisionRecord>> = BTreeMap::new();
+        for record in collisions {
+            let rooms: BTreeSet<usize> = [&record.a, &record.b]
+                .into_iter()
+                .filter_map(|id| self.entities.get(id.as_str()).map(|e| e.room_id))
+                .collect();
+            for room_id in rooms {
+                by_room.entry(room_id).or_default().push(record);
+            }
+        }
+        for (room_id, records) in by_room {
+            let msg = protocol::event("collisions", json!({
+                "tick": self.tick,
+                "collisions": records,
+            }));
+            self.send_to_room(room_id, &msg, None);
+        }
+    }
+
+    /// Advance lap / sector timing one tick; `sector_completed` goes to the
+    /// player's room.
+    pub fn update_timing(&mut self, phys: &PhysicsWorld) {
+        let Some(timing) = self.timing.as_mut() else { return };
+        timing.advance(phys.scaled_dt(self.tick_rate.dt()));
+
+        // Sorted: two players finishing a sector on the same tick rank the same every run
+        let mut ids: Vec<&String> = self.entities.keys().collect();
+        ids.sort();
+        for id in ids {
+            let ent = &self.entities[id];
+            let Some(body) = phys.bodies.get(ent.body_handle) else { continue };
+            let (pos, rot) = (body.translation(), body.rotation());
+            let event = timing.update_player(id, [pos.x, pos.y, pos.z]);
+
+            // Ghost recording: a lap that just became the best replaces the ghost
+            let finished_best = event.as_ref()
+                .and_then(|e| e.lap_time)
+                .filter(|t| timing.best_lap(id) == Some(*t));
+            let pose = ([pos.x, pos.y, pos.z], [rot.i, rot.j, rot.k, rot.w]);
+            self.ghosts.record(id, self.tick, timing.lap(id), pose, finished_best);
+
+            let Some(event) = event else { continue };
+
+            let msg = event.to_message();
+            for (other, tx) in &self.clients {
+                if self.entities.get(other).is_some_and(|e| e.room_id == ent.room_id) {
+                    let _ = tx.send(msg.clone());
+                }
+            }
+        }
+    }
+
+    /// Plugin events go to the room they are about; world-wide ones to everyone.
+    pub fn broadcast_game_events(&self, events: &[RoomEvent]) {
+        if events.is_empty() || self.clients.is_empty() {
+            return;
+        }
+        for RoomEvent { room_id, event } in events {
+            let msg = protocol::event("game_event", json!({ "tick": self.tick + 1, "data": event }));
+            match room_id {
+                Some(room_id) => self.send_to_room(*room_id, &msg, None),
+                None => {
+                    for tx in self.clients.values() {
+                        let _ = tx.send(msg.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Debug overlay per client: the watched car's, or the shared overlay of
+    /// the client's own room (never another room's cars).
+    pub fn broadcast_debug_overlay(&mut self, phys: &PhysicsWorld) {
         if self.clients.is_empty() {
             return;
         }
+
+        // Serialize once per distinct (target or room, effective mask, encoding), not once per client
+        type PayloadKey<'a> = (Option<&'a str>, Option<usize>, u32, Encoding);
+        let mut payloads: HashMap<PayloadKey, Option<OutFrame>> = HashMap::new();
+        let mut room_overlays: HashMap<usize, DebugOverlay> = HashMap::new();
+
+        for (player_id, tx) in &self.clients {
+            let sub = self.debug_subs.get(player_id).cloned().unwrap_or_default();
+            let target = sub.target.as_deref().and_then(|t| self.entities.get_key_value(t)).map(|(id, _)| id.as_str());
+            let room_id = match target {
+                Some(_) => None,
+                None => match self.client_room(player_id) {
+                    Some(room_id) => Some(room_id),
+                    None => continue, // not seated anywhere yet
+                },
+            };
+            let mut mask = sub.categories;
+
+            if sub.auto_lod {
+                // LOD follows the car being watched
+                let speed = self.entities.get(target.unwrap_or(player_id))
+                    .and_then(|ent| phys.bodies.get(ent.body_handle))
+                    .map(|body| body.linvel().norm())
+                    .unwrap_or(0.0);
+                if speed > DEBUG_AUTO_LOD_SPEED {
+                    mask &= !DEBUG_HIGH_FREQUENCY;
+                }
+            }
+
+            let encoding = self.encodings.get(player_id).copied().unwrap_or_default();
+            let msg = payloads.entry((target, room_id, mask, encoding)).or_insert_with(|| match (target, room_id) {
+                // Only that vehicle's primitives (recorded because it is watched)
+                (Some(id), _) => phys.vehicle_debug_snapshot(id).and_then(|own| {
+                    encoding.encode(&DebugMessage::new(Some(id), own.filtered(mask)))
+                }),
+                (None, Some(room_id)) => {
+                    let overlay = room_overlays.entry(room_id).or_insert_with(|| {
+                        let ids: Vec<&str> = self.entities.values()
+                            .filter(|e| e.room_id == room_id)
+                            .map(|e| e.id.as_str())
+                            .collect();
+                        phys.room_debug_snapshot(&ids)
+                    });
+                    encoding.encode(&DebugMessage::new(None, overlay.filtered(mask)))
+                }
+                (None, None) => None,
+            });
+
+            if let Some(msg) = msg {
+                let _ = tx.send_frame(msg.clone());
+            }
+        }
+    }
+
+    /// Send this tick's snapshot to every client. Returns the bytes queued
+    /// (before compression), for the snapshot_bytes_sent_total metric.
+    pub fn broadcast_snapshot(&mut self, phys: &PhysicsWorld) -> usize {
+        // entity_added / entity_removed since the last snapshot go out first
+        for event in std::mem::take(&mut self.entity_events) {
+            self.send_to_room(event.room_id, &event.msg, event.except.as_deref());
+        }
+
+        // If no clients, do nothing (saves work when menu/server idle)
+        if self.clients.is_empty() {
+            return 0;
+        }
+        let mut bytes_sent = 0;
+        let timescale = phys.timestep_scale;
+        let (tick_hz, tick_dt) = (self.tick_rate.hz(), self.tick_rate.dt());
+        let drift_ms = (self.drift_ms as f64).round(); // whole ms (f32 would serialize with noise digits)
+        let server_time_ms = server_time_ms();
         // println!("📤 Broadcasting snapshot for tick {}", self.tick);
         // println!(
         //     "   clients: {}, entities: {}",
@@ -196,10 +1584,14 @@ impl SharedGameState {
         //     self.entities.len()
         // );
         
-                // Build the players array for this snapshot
-        let mut players_json = Vec::new();
+        // Build the players array per room (clients only see their own room)
+        let mut players_by_room: HashMap<usize, Vec<SnapshotEntry>> = HashMap::new();
+        let mut poses: HashMap<String, ([f32; 3], [f32; 4])> = HashMap::new();
+        let mut own_vehicles: HashMap<String, OwnVehicle> = HashMap::new();
+        let mut velocities: HashMap<String, SnapshotVelocity> = HashMap::new();
 
-        for ent in self.entities.values() {
+        let tick = self.tick;
+        for ent in self.entities.values_mut() {
             // Skip entities that don’t yet have a physics body
             if ent.body_handle == RigidBodyHandle::invalid() {
                 println!(
@@ -210,7 +1602,7 @@ impl SharedGameState {
             }
 
             // Look up the Rapier body
-            if let Some(body) = bodies.get(ent.body_handle) {
+            if let Some(body) = phys.bodies.get(ent.body_handle) {
                 let pos = body.translation();
                 // println!(
                 //     "   ↪ entity {} @ ({:.2}, {:.2}, {:.2})",
@@ -218,17 +1610,67 @@ impl SharedGameState {
                 // );
                 let rot = body.rotation();
 
-                players_json.push(json!({
-                    "id": ent.id,
-                    "kind": ent.kind.as_str(),
-                    "room_id": ent.room_id,
-                    "team": ent.team.as_str(),
-                    "x": pos.x,
-                    "y": pos.y,
-                    "z": pos.z,
+                let team = self.spawns.team_info(ent.room_id, ent.team);
+                let vehicle = phys.vehicles.get(&ent.id);
+                let mut player = SnapshotPlayer {
+                    id: ent.id.clone(),
+                    name: ent.name.clone(),
+                    kind: ent.kind.as_str().to_string(),
+                    room_id: ent.room_id,
+                    team: team.name.clone(),
+                    team_index: ent.team.index(),
+                    team_color: team.color.clone(),
+                    x: pos.x,
+                    y: pos.y,
+                    z: pos.z,
                     // FULL authoritative orientation
-                    "rot": [rot.i, rot.j, rot.k, rot.w],
-                }));
+                    rot: [rot.i, rot.j, rot.k, rot.w],
+                    suspect: false,
+                    // Driver-adjusted front brake share (HUD)
+                    brake_bias: vehicle.map(|veh| veh.brake_bias),
+                    // All wheels off the ground (client audio / FX)
+                    airborne: vehicle.map(|veh| veh.air.airborne),
+                    // Cargo / fuel on board (telemetry, absent when empty)
+                    payload: vehicle.map(|veh| veh.payload).filter(|p| p.mass > 0.0),
+                    // Lap / sector timing (HUD: current delta, last sector)
+                    timing: self.timing.as_ref().and_then(|t| t.snapshot(&ent.id)),
+                    // Turret yaw (relative to hull) + barrel pitch, radians
+                    turret: phys.turret_angles(&ent.id).map(|(yaw, pitch)| TurretAngles { yaw, pitch }),
+                    velocity: None,
+                };
+
+                // Flag (but still send) physically implausible movement
+                let (p, v) = ([pos.x, pos.y, pos.z], body.linvel());
+                let max_speed = vehicle.map(|veh| veh.config.max_speed);
+                if let (Some((prev_tick, prev_pos)), Some(max_speed)) = (ent.last_snapshot, max_speed) {
+                    let vel = [v.x, v.y, v.z];
+                    if !validate_snapshot_entity(p, vel, tick.saturating_sub(prev_tick), tick_dt, prev_pos, max_speed) {
+                        println!(
+                            "🚨 Implausible state for {} at tick {}: prev_pos={:?} (tick {}) pos={:?} vel={:?}",
+                            ent.id, tick, prev_pos, prev_tick, p, vel
+                        );
+                        player.suspect = true;
+                    }
+                }
+                ent.last_snapshot = Some((tick, p));
+                poses.insert(ent.id.clone(), (p, [rot.i, rot.j, rot.k, rot.w]));
+                if !self.velocity_clients.is_empty() {
+                    let w = body.angvel();
+                    velocities.insert(ent.id.clone(), SnapshotVelocity { vx: v.x, vy: v.y, vz: v.z, wx: w.x, wy: w.y, wz: w.z });
+                }
+                if self.prediction_clients.contains(&ent.id) {
+                    let w = body.angvel();
+                    own_vehicles.insert(ent.id.clone(), OwnVehicle {
+                        x: pos.x,
+                        y: pos.y,
+                        z: pos.z,
+                        rot: [rot.i, rot.j, rot.k, rot.w],
+                        linvel: [v.x, v.y, v.z],
+                        angvel: [w.x, w.y, w.z],
+                        steer_angle: vehicle.map_or(0.0, |veh| veh.steer_angle),
+                    });
+                }
+                players_by_room.entry(ent.room_id).or_default().push(SnapshotEntry::Player(player));
             } else {
                 println!(
                     "   ⚠ body not found in RigidBodySet for entity {} handle {:?}",
@@ -237,21 +1679,163 @@ impl SharedGameState {
             }
         }
 
-        // Build final payload with a top-level "type"
-        let payload = json!({
-            "type": "snapshot",
-            "data": {
-                "tick": self.tick,
-                "players": players_json,
-            }
-        });
+        // Ticks per step of each client's room (AVENLAB_ROOM_TICK_HZ)
+        let room_divisors: HashMap<usize, u32> = self.clients.keys()
+            .map(|id| self.client_room(id).unwrap_or(0))
+            .map(|room_id| (room_id, self.room_tick_divisor(room_id)))
+            .collect();
+        let data = |room_id: usize, partial: bool, players: Vec<SnapshotEntry>| SnapshotData {
+            tick,
+            room_id,
+            timescale,
+            tick_hz,
+            room_tick_hz: room_divisors.get(&room_id).filter(|&&n| n > 1).map(|n| tick_hz / n),
+            drift_ms,
+            server_time_ms,
+            partial,
+            baseline: None,
+            players,
+            added: Vec::new(),
+            removed: Vec::new(),
+            last_processed_seq: None,
+            own: None,
+            input_lead_ticks: None,
+        };
+
+        // Baselines for delta snapshots (delta.rs); interest-managed snapshots diff on their own
+        if self.interest.radius.is_none() || !self.spectators.is_empty() {
+            let entities = &self.entities;
+            self.snapshot_history.record(
+                tick,
+                poses.iter().filter_map(|(id, (pos, rot))| entities.get(id).map(|e| (id.as_str(), e.room_id, *pos, *rot))),
+            );
+        }
+        let mut deltas: HashMap<(usize, u64), Option<Delta>> = HashMap::new();
 
-        let json = payload.to_string();
-        // println!("   Snapshot payload: {}", json);
+        // Build final payload per room, baseline, velocity opt-in and encoding
+        let mut payloads: HashMap<(usize, Option<u64>, bool, Encoding), Option<OutFrame>> = HashMap::new();
 
-        // Send to all registered clients
+        // Send to all registered clients (slow rooms only on the ticks they step)
         for (player_id, tx) in self.clients.iter() {
-            match tx.send(json.clone()) {
+            let room_id = self.client_room(player_id).unwrap_or(0);
+            if !tick.is_multiple_of(room_divisors[&room_id] as u64) {
+                continue;
+            }
+            let spectator = self.spectators.contains_key(player_id);
+            let want_velocities = self.velocity_clients.contains(player_id);
+            let encoding = self.encodings.get(player_id).copied().unwrap_or_default();
+            // Client prediction: own vehicle + last input seq applied, input lead
+            let own = own_vehicles.get(player_id).copied();
+            let input_lead_ticks = self.input_buffers.get(player_id).and_then(|b| b.lead_ticks());
+            let predicted = |mut snapshot: SnapshotData| {
+                if own.is_some() {
+                    snapshot.own = own;
+                    snapshot.last_processed_seq = self.entities.get(player_id).and_then(|e| e.input_seq);
+                }
+                snapshot.input_lead_ticks = input_lead_ticks;
+                snapshot
+            };
+            let with_velocities = |mut players: Vec<SnapshotEntry>| {
+                if want_velocities {
+                    for entry in players.iter_mut() {
+                        if let SnapshotEntry::Player(p) = entry {
+                            p.velocity = velocities.get(&p.id).copied();
+                        }
+                    }
+                }
+                players
+            };
+
+            // Interest-managed: per-client subset + keepalive / out-of-range guarantees
+            // (spectators see the whole room)
+            if self.interest.radius.is_some() && !spectator {
+                let view = self.client_views.entry(player_id.clone()).or_default();
+                let me = poses.get(player_id).map(|(p, _)| *p);
+                let mut in_interest: HashSet<&str> = HashSet::new();
+                let mut players = Vec::new();
+
+                for player in players_by_room.get(&room_id).into_iter().flatten() {
+                    let id = player.id();
+                    let Some(&(pos, rot)) = poses.get(id) else { continue };
+                    if id != player_id && me.is_some_and(|me| !self.interest.in_range(me, pos)) {
+                        continue;
+                    }
+                    in_interest.insert(id);
+                    if view.should_send(id, pos, rot, tick, self.interest.keepalive_ticks) {
+                        players.push(player.clone());
+                    }
+                }
+
+                // Own best-lap ghost: always sent, it moves every tick
+                if let Some(ghost) = self.ghosts.snapshot_entry(player_id, tick) {
+                    players.push(SnapshotEntry::Ghost(ghost));
+                }
+                let players = with_velocities(players);
+
+                for (id, last) in view.drop_missing(&in_interest) {
+                    // Left the room / disconnected: entity_removed already covers it
+                    if self.entities.get(&id).is_none_or(|e| e.room_id != room_id) {
+                        continue;
+                    }
+                    let _ = tx.send(protocol::event("entity_out_of_range", json!({
+                        "id": id,
+                        "tick": tick,
+                        "x": last[0],
+                        "y": last[1],
+                        "z": last[2],
+                    })));
+                }
+
+                if let Some(frame) = encoding.encode(&SnapshotMessage::new(predicted(data(room_id, true, players)))) {
+                    bytes_sent += frame.byte_len();
+                    let _ = tx.send_frame(frame);
+                }
+                continue;
+            }
+
+            // Delta against the client's acked snapshot, or full without one
+            let baseline = self.snapshot_history.baseline(player_id, room_id, spectator);
+            let delta = baseline.and_then(|b| {
+                deltas.entry((room_id, b)).or_insert_with(|| self.snapshot_history.delta(b, room_id)).as_ref()
+            });
+            let baseline = delta.and(baseline);
+            let room_snapshot = || {
+                let room = players_by_room.get(&room_id).into_iter().flatten();
+                let mut snapshot = match delta {
+                    Some(d) => data(room_id, false, room.filter(|p| d.send.contains(p.id())).cloned().collect()),
+                    None => data(room_id, false, room.cloned().collect()),
+                };
+                if let Some(d) = delta {
+                    snapshot.baseline = baseline;
+                    snapshot.added = d.added.clone();
+                    snapshot.removed = d.removed.clone();
+                }
+                snapshot
+            };
+
+            // Own best-lap ghost / own vehicle / input lead: this client's payload differs from the room's
+            let ghost = self.ghosts.snapshot_entry(player_id, tick);
+            if ghost.is_some() || own.is_some() || input_lead_ticks.is_some() {
+                let mut snapshot = predicted(room_snapshot());
+                snapshot.players = with_velocities(snapshot.players);
+                snapshot.players.extend(ghost.map(SnapshotEntry::Ghost));
+                if let Some(frame) = encoding.encode(&SnapshotMessage::new(snapshot)) {
+                    bytes_sent += frame.byte_len();
+                    let _ = tx.send_frame(frame);
+                }
+                continue;
+            }
+
+            let frame = payloads.entry((room_id, baseline, want_velocities, encoding)).or_insert_with(|| {
+                let mut snapshot = room_snapshot();
+                snapshot.players = with_velocities(snapshot.players);
+                encoding.encode(&SnapshotMessage::new(snapshot))
+            });
+            let Some(frame) = frame else { continue };
+            // println!("   Snapshot payload: {:?}", frame);
+            bytes_sent += frame.byte_len();
+
+            match tx.send_frame(frame.clone()) {
                 Ok(_) => {
                     // println!(
                     //     "   ✅ sent snapshot for tick {} to client #{}",
@@ -266,6 +1850,697 @@ impl SharedGameState {
                 }
             }
         }
+
+        // Snapshot queued: closes the apply → send leg of inputs drained up to this tick
+        let sent = Instant::now();
+        for latency in self.latency.values_mut() {
+            if let Some(waited) = latency.snapshot_sent(tick, sent) {
+                self.latency_totals.apply_to_send.record(waited);
+            }
+        }
+        bytes_sent
+    }
+}
+
+#[cfg(test)]
+impl SharedGameState {
+    /// What net.rs does for a join (client, slot, entity, body), without a socket.
+    /// Returns the client's outbound channel.
+    pub(crate) fn join_test_player(&mut self, phys: &mut PhysicsWorld, id: &str) -> UnboundedReceiver<OutFrame> {
+        let (tx, rx) = ClientTx::channel();
+        self.register_client(id.to_string(), tx);
+        let live = phys.vehicle_positions(None);
+        let spawn = self.spawns.allocate_spawn(id.to_string(), &live);
+        self.add_entity(id, EntityType::Vehicle);
+        self.apply_spawn_info(&spawn);
+        let handle = phys
+            .spawn_vehicle_for_player(id.to_string(), spawn.position, spawn.yaw, crate::physics::DEFAULT_VEHICLE)
+            .expect("default vehicle spawns");
+        self.attach_body(id, handle);
+        let tick = self.tick + 1;
+        if let Some(rec) = self.recorder.as_mut() {
+            rec.record_spawn(tick, id, spawn.position, spawn.yaw, crate::physics::DEFAULT_VEHICLE);
+        }
+        self.apply_room_tick_rate(phys, id);
+        self.queue_entity_added(phys, id, Some(id));
+        rx
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::DEBUG_WHEELS;
+
+    #[test]
+    fn reset_world_drops_a_car_that_cannot_respawn() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        let _a = game.join_test_player(&mut phys, "a");
+        let _b = game.join_test_player(&mut phys, "b");
+        let mut kicked = game.register_kick("b");
+        phys.vehicles.get_mut("b").unwrap().config.mass = -1.0; // respawn rejects the config
+
+        assert_eq!(game.reset_world(&mut phys), 1);
+
+        // Gone in the same call, not when its connection gets round to closing
+        assert!(!game.entities.contains_key("b"));
+        assert!(!phys.vehicles.contains_key("b"));
+        assert!(!game.clients.contains_key("b"));
+        assert_eq!(kicked.try_recv(), Ok(()));
+        // Only a's new slot is taken
+        assert!(!game.spawns.slots.contains_key("b"));
+        assert_eq!(game.spawns.team_counts.values().sum::<usize>(), 1);
+
+        // The connection's own cleanup afterwards changes nothing
+        game.despawn_player(&mut phys, "b");
+        assert_eq!(game.spawns.team_counts.values().sum::<usize>(), 1);
+        assert!(game.entities.contains_key("a") && phys.vehicles.contains_key("a"));
+    }
+
+    #[test]
+    fn welcome_pins_the_vehicle_layout_schema() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        let _a = game.join_test_player(&mut phys, "a");
+        let ent = &game.entities["a"];
+        let welcome: serde_json::Value = serde_json::from_str(&game.player_welcome(&phys, "a", ent.room_id, ent.team, false, false)).unwrap();
+
+        let keys = |v: &serde_json::Value| -> Vec<String> { v.as_object().unwrap().keys().cloned().collect::<BTreeSet<_>>().into_iter().collect() };
+        assert_eq!(
+            keys(&welcome),
+            [
+                "entities", "is_admin", "name", "player_id", "protocol_version", "resume_token", "resumed", "room_id", "room_tick_hz",
+                "spawn_yaw", "team", "team_color", "team_count", "team_index", "tick_hz", "type", "vehicle", "vehicle_layout",
+            ]
+        );
+        assert_eq!(welcome["type"], "welcome");
+        assert_eq!(welcome["vehicle"], crate::physics::DEFAULT_VEHICLE);
+
+        let layout = &welcome["vehicle_layout"];
+        assert_eq!(keys(layout), ["chassis_com_offset", "chassis_half_extents", "vehicle", "wheels"]);
+        let config = phys.vehicles["a"].config;
+        assert_eq!(layout["vehicle"], crate::physics::DEFAULT_VEHICLE);
+        assert_eq!(layout["chassis_half_extents"], json!(config.chassis_half_extents));
+        assert_eq!(layout["chassis_com_offset"], json!(config.chassis_com_offset));
+
+        let wheels = layout["wheels"].as_array().unwrap();
+        let ids: Vec<&str> = wheels.iter().map(|w| w["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["FL", "FR", "RL", "RR"]);
+        for wheel in wheels {
+            assert_eq!(keys(wheel), ["drive", "id", "max_length", "offset", "radius", "rest_length", "steer"]);
+            assert_eq!(wheel["offset"].as_array().unwrap().len(), 3);
+            assert!(wheel["radius"].as_f64().unwrap() > 0.0);
+        }
+        // From the wheels the car was built with, not a client-side copy
+        let built = &phys.wheels[&phys.vehicles["a"].body];
+        for (wheel, built) in wheels.iter().zip(built.iter()) {
+            assert_eq!(wheel["offset"], json!([built.offset.x, built.offset.y, built.offset.z]));
+            assert_eq!(wheel["rest_length"], json!(built.rest_length));
+            assert_eq!((wheel["steer"].as_bool(), wheel["drive"].as_bool()), (Some(built.steer), Some(built.drive)));
+        }
+        assert_eq!((wheels[0]["steer"].as_bool(), wheels[0]["drive"].as_bool()), (Some(true), Some(false)));
+        assert_eq!((wheels[2]["steer"].as_bool(), wheels[2]["drive"].as_bool()), (Some(false), Some(true)));
+
+        // "entities" lists the joiner itself
+        assert_eq!(welcome["entities"][0]["id"], "a");
+    }
+
+    #[test]
+    fn mailbox_runs_physics_messages_in_order_and_replies() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        let mut rx = game.join_test_player(&mut phys, "p");
+        let inputs = game.input_sender("p");
+        let bias = phys.vehicles["p"].brake_bias;
+
+        let throttle = |t: f32, seq: u64| MailboxInput::Axes([Some(t), None, None, None, None, None, None], Some(seq), None);
+        inputs.send(throttle(0.5, 10));
+        inputs.send(MailboxInput::Respawn);
+        inputs.send(throttle(0.25, 1)); // seq restarts after the respawn
+        inputs.send(MailboxInput::Adjust(0.02));
+        inputs.send(MailboxInput::SwitchRoom(0)); // already there
+        // Nothing is touched until the tick drains the mailbox
+        assert_eq!(phys.vehicles["p"].brake_bias, bias);
+        game.apply_input_mailbox(&mut phys);
+
+        assert_eq!(game.entities["p"].input_seq, Some(1));
+        assert_eq!(game.entities["p"].last_input.as_ref().unwrap().axes.throttle, 0.25);
+        assert_eq!(game.entities["p"].last_respawn_tick, Some(game.tick));
+        let replies = received(&mut rx);
+        let ack = replies.iter().find(|m| m["type"] == "adjust_ack").expect("adjust_ack");
+        assert_eq!(ack["brake_bias"].as_f64().unwrap() as f32, phys.vehicles["p"].brake_bias);
+        assert!((phys.vehicles["p"].brake_bias - bias - 0.02).abs() < 1e-6);
+        assert!(replies.iter().any(|m| m["type"] == "error" && m["reason"] == "room_switch_disabled"));
+    }
+
+    #[test]
+    fn an_input_tagged_for_tick_n_applies_on_tick_n() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        let _rx = game.join_test_player(&mut phys, "p");
+        let inputs = game.input_sender("p");
+        let target = game.tick + 4;
+        inputs.send(MailboxInput::Axes([Some(0.75), None, None, None, None, None, None], Some(1), Some(target)));
+
+        let throttle = |game: &SharedGameState| game.entities["p"].last_input.as_ref().map(|i| i.axes.throttle);
+        for _ in 0..3 {
+            game.apply_input_mailbox(&mut phys); // for tick game.tick + 1
+            assert_eq!(throttle(&game), None, "tick {} is before the tag", game.tick + 1);
+            assert_eq!(game.input_buffers["p"].queued_seq(), Some(1));
+            game.tick += 1;
+        }
+        game.apply_input_mailbox(&mut phys);
+        assert_eq!(game.tick + 1, target);
+        assert_eq!(throttle(&game), Some(0.75));
+        assert_eq!(game.entities["p"].input_seq, Some(1));
+        assert!(game.input_buffers["p"].is_empty());
+    }
+
+    #[test]
+    fn fire_acks_only_hit_cars_in_the_shooters_room() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        game.spawns.max_players = 2;
+        let mut rx = game.join_test_player(&mut phys, "a");
+        game.join_test_player(&mut phys, "b");
+        game.join_test_player(&mut phys, "c");
+        assert_eq!(game.entities["c"].room_id, 1);
+
+        // c (other room) right in front of a, b behind it in the same line
+        phys.reset_vehicle("a", [0.0, 0.0, 0.0], 0.0).unwrap();
+        phys.reset_vehicle("c", [0.0, 0.0, 15.0], 0.0).unwrap();
+        phys.reset_vehicle("b", [0.0, 0.0, 30.0], 0.0).unwrap();
+        for _ in 0..30 {
+            phys.step(1.0 / 60.0);
+        }
+        game.tick = phys.tick;
+        received(&mut rx);
+
+        game.input_sender("a").send(MailboxInput::Fire(None));
+        game.apply_input_mailbox(&mut phys);
+        let ack = received(&mut rx).into_iter().find(|m| m["type"] == "fire_ack").expect("fire_ack");
+        assert_eq!(ack["tick"], game.tick);
+        assert_eq!(ack["hit"]["target"], "b");
+    }
+
+    /// Text frames queued for a client so far, parsed.
+    fn received(rx: &mut UnboundedReceiver<OutFrame>) -> Vec<serde_json::Value> {
+        std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|frame| match frame {
+                OutFrame::Text(text) => serde_json::from_str(&text).ok(),
+                OutFrame::Binary(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn snapshots_only_carry_the_receivers_room() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        game.spawns.max_players = 2;
+        let mut rx: Vec<_> = ["a", "b", "c"].iter().map(|id| game.join_test_player(&mut phys, id)).collect();
+        assert_eq!(game.entities["c"].room_id, 1);
+
+        game.broadcast_snapshot(&phys);
+        let ids = |rx: &mut UnboundedReceiver<OutFrame>| -> Vec<String> {
+            let snapshot = received(rx).into_iter().rfind(|m| m["type"] == "snapshot").expect("snapshot");
+            let mut ids: Vec<String> = snapshot["data"]["players"].as_array().unwrap().iter().map(|p| p["id"].as_str().unwrap().to_string()).collect();
+            ids.sort();
+            ids
+        };
+        assert_eq!(ids(&mut rx[0]), ["a", "b"]);
+        assert_eq!(ids(&mut rx[1]), ["a", "b"]);
+        assert_eq!(ids(&mut rx[2]), ["c"]);
+
+        // b's seat frees on disconnect and the next joiner takes it
+        game.despawn_player(&mut phys, "b");
+        let _d = game.join_test_player(&mut phys, "d");
+        assert_eq!(game.entities["d"].room_id, 0);
+    }
+
+    #[test]
+    fn each_room_steps_and_snapshots_at_its_own_rate() {
+        use crate::spawn::RoomSettings;
+
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        game.spawns.max_players = 2;
+        game.spawns.set_room_settings(0, RoomSettings { tick_hz: Some(30), ..RoomSettings::default() });
+        game.spawns.set_room_settings(1, RoomSettings { tick_hz: Some(20), ..RoomSettings::default() });
+        let mut rx: Vec<_> = ["a", "b", "c"].iter().map(|id| game.join_test_player(&mut phys, id)).collect();
+        assert_eq!(game.entities["c"].room_id, 1);
+        assert_eq!((phys.vehicles["a"].tick_divisor, phys.vehicles["c"].tick_divisor), (2, 3));
+        let ent = &game.entities["c"];
+        let welcome: serde_json::Value = serde_json::from_str(&game.player_welcome(&phys, "c", ent.room_id, ent.team, false, false)).unwrap();
+        assert_eq!((welcome["tick_hz"].as_u64(), welcome["room_tick_hz"].as_u64()), (Some(60), Some(20)));
+
+        let snapshots = |rx: &mut UnboundedReceiver<OutFrame>| -> Vec<(u64, u64)> {
+            received(rx)
+                .into_iter()
+                .filter(|m| m["type"] == "snapshot")
+                .map(|m| (m["data"]["tick"].as_u64().unwrap(), m["data"]["room_tick_hz"].as_u64().unwrap()))
+                .collect()
+        };
+        let run = |phys: &mut PhysicsWorld, game: &mut SharedGameState| {
+            for _ in 0..12 {
+                phys.step(1.0 / 60.0);
+                game.tick = phys.tick;
+                game.broadcast_snapshot(phys);
+            }
+        };
+        rx.iter_mut().for_each(|rx| { received(rx); });
+        run(&mut phys, &mut game);
+        assert_eq!(snapshots(&mut rx[0]), [(2, 30), (4, 30), (6, 30), (8, 30), (10, 30), (12, 30)]);
+        assert_eq!(snapshots(&mut rx[2]), [(3, 20), (6, 20), (9, 20), (12, 20)]);
+
+        // A room move takes the car onto the new room's rate
+        game.move_player(&mut phys, "a", 1).unwrap();
+        assert_eq!(phys.vehicles["a"].tick_divisor, 3);
+        received(&mut rx[0]);
+        run(&mut phys, &mut game);
+        assert_eq!(snapshots(&mut rx[0]), [(15, 20), (18, 20), (21, 20), (24, 20)]);
+    }
+
+    #[test]
+    fn a_four_team_room_seats_two_per_team_and_snapshots_carry_the_colors() {
+        use crate::spawn::RoomSettings;
+
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        game.spawns.max_players = 8;
+        game.spawns.set_room_settings(0, RoomSettings::with_team_count(4));
+        let mut rx: Vec<_> = (0..8).map(|i| game.join_test_player(&mut phys, &format!("p{}", i))).collect();
+
+        let mut per_team = [0; 4];
+        for ent in game.entities.values() {
+            assert_eq!(ent.room_id, 0);
+            per_team[ent.team.index() as usize] += 1;
+        }
+        assert_eq!(per_team, [2, 2, 2, 2]);
+
+        game.broadcast_snapshot(&phys);
+        let snapshot = received(&mut rx[0]).into_iter().rfind(|m| m["type"] == "snapshot").expect("snapshot");
+        let players = snapshot["data"]["players"].as_array().unwrap();
+        assert_eq!(players.len(), 8);
+        let expected = [("red", "#e53935"), ("blue", "#1e88e5"), ("green", "#43a047"), ("yellow", "#fdd835")];
+        for p in players {
+            let index = game.entities[p["id"].as_str().unwrap()].team.index();
+            assert_eq!(p["team_index"], index);
+            assert_eq!((p["team"].as_str().unwrap(), p["team_color"].as_str().unwrap()), expected[index as usize]);
+        }
+    }
+
+    #[test]
+    fn far_stationary_cars_get_keepalives_and_an_out_of_range_hint() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        game.interest = InterestConfig { radius: Some(40.0), keepalive_ticks: 10 };
+        let mut a = game.join_test_player(&mut phys, "a");
+        let _b = game.join_test_player(&mut phys, "b");
+        let a_pos = *phys.bodies[phys.vehicles["a"].body].translation();
+        let b_body = phys.vehicles["b"].body;
+        phys.bodies[b_body].set_translation(a_pos + vector![30.0, 0.0, 0.0], false);
+
+        // Nothing moves (no physics steps): b only rides along on keepalives
+        let mut sent_b = Vec::new();
+        for tick in 1..=30 {
+            game.tick = tick;
+            game.broadcast_snapshot(&phys);
+            let snapshot = received(&mut a).into_iter().rfind(|m| m["type"] == "snapshot").expect("snapshot");
+            if snapshot["data"]["players"].as_array().unwrap().iter().any(|p| p["id"] == "b") {
+                sent_b.push(tick);
+            }
+        }
+        assert_eq!(sent_b, [1, 11, 21]);
+
+        // b drives off past the radius: one hint with the last position a saw
+        phys.bodies[b_body].set_translation(a_pos + vector![100.0, 0.0, 0.0], false);
+        game.tick = 31;
+        game.broadcast_snapshot(&phys);
+        let hints: Vec<_> = received(&mut a).into_iter().filter(|m| m["type"] == "entity_out_of_range").collect();
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0]["id"], "b");
+        assert_eq!(hints[0]["x"], a_pos.x + 30.0);
+
+        game.tick = 32;
+        game.broadcast_snapshot(&phys);
+        assert!(received(&mut a).iter().all(|m| m["type"] != "entity_out_of_range"), "sent once");
+    }
+
+    #[test]
+    fn msgpack_clients_get_the_json_snapshot_as_a_binary_frame() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        let mut a = game.join_test_player(&mut phys, "a");
+        let mut b = game.join_test_player(&mut phys, "b");
+        game.encodings.insert("b".to_string(), Encoding::Msgpack);
+
+        game.broadcast_snapshot(&phys);
+        let Some(OutFrame::Text(text)) = std::iter::from_fn(|| a.try_recv().ok()).last() else { panic!("json snapshot") };
+        let Some(OutFrame::Binary(bytes)) = std::iter::from_fn(|| b.try_recv().ok()).last() else { panic!("binary snapshot") };
+        let decoded: SnapshotMessage = rmp_serde::from_slice(&bytes).expect("decodes into SnapshotMessage");
+        assert_eq!(decoded.data.tick, game.tick);
+        assert_eq!(decoded.data.players.len(), 2);
+        assert_eq!(serde_json::to_string(&decoded).unwrap(), text);
+    }
+
+    #[test]
+    fn entity_events_go_out_versioned_ahead_of_the_snapshot() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        let mut a = game.join_test_player(&mut phys, "a");
+        game.broadcast_snapshot(&phys);
+        received(&mut a);
+        let mut b = game.join_test_player(&mut phys, "b");
+
+        game.broadcast_snapshot(&phys);
+        let to_a = received(&mut a);
+        let types: Vec<&str> = to_a.iter().filter_map(|m| m["type"].as_str()).collect();
+        assert_eq!(types, ["entity_added", "snapshot"]);
+        assert_eq!(to_a[0]["id"], "b");
+        assert_eq!(to_a[0]["v"], protocol::PROTOCOL_VERSION);
+        assert!(to_a[0]["vehicle_layout"].is_object());
+        // The joiner's own add is covered by its welcome
+        let to_b = received(&mut b);
+        assert_eq!(to_b.len(), 1);
+        assert_eq!(to_b[0]["type"], "snapshot");
+
+        game.despawn_player(&mut phys, "b");
+        game.broadcast_snapshot(&phys);
+        let to_a = received(&mut a);
+        assert_eq!(to_a[0]["type"], "entity_removed");
+        assert_eq!(to_a[0]["id"], "b");
+        assert_eq!(to_a[0]["v"], protocol::PROTOCOL_VERSION);
+        assert_eq!(to_a[1]["type"], "snapshot");
+    }
+
+    #[test]
+    fn move_player_rebuilds_the_car_in_the_target_room_and_tells_both_rooms() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        game.spawns.max_players = 2;
+        let mut rx: Vec<_> = ["a", "b", "c"].iter().map(|id| game.join_test_player(&mut phys, id)).collect();
+        game.broadcast_snapshot(&phys);
+        rx.iter_mut().for_each(|rx| drop(received(rx)));
+        let old_body = game.entities["a"].body_handle;
+
+        let spawn = game.move_player(&mut phys, "a", 1).unwrap();
+        assert_eq!(spawn.room_id, 1);
+        assert_eq!(game.entities["a"].room_id, 1);
+        assert_eq!(game.spawns.slots["a"].0, 1);
+        assert_eq!((game.spawns.room_population(0), game.spawns.room_population(1)), (1, 2));
+        // A new body at the new spawn, the old one gone
+        let body = game.entities["a"].body_handle;
+        assert_ne!(body, old_body);
+        assert!(phys.bodies.get(old_body).is_none());
+        assert_eq!(phys.vehicles["a"].body, body);
+        assert!(game.debug_validate(&phys).is_empty());
+
+        // The mover gets its new room, with c in it
+        let to_a = received(&mut rx[0]);
+        let changed = to_a.iter().find(|m| m["type"] == "room_changed").expect("room_changed");
+        assert_eq!(changed["room_id"], 1);
+        let ids: Vec<&str> = changed["entities"].as_array().unwrap().iter().map(|e| e["id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["a", "c"]);
+        // The rooms hear about it ahead of the next snapshot
+        game.broadcast_snapshot(&phys);
+        let to_b = received(&mut rx[1]);
+        assert_eq!((to_b[0]["type"].as_str(), to_b[0]["id"].as_str()), (Some("entity_removed"), Some("a")));
+        let to_c = received(&mut rx[2]);
+        assert!(to_c.iter().any(|m| m["type"] == "entity_added" && m["id"] == "a"));
+
+        // Same room, and a full room, change nothing
+        assert!(game.move_player(&mut phys, "a", 1).is_err());
+        let _d = game.join_test_player(&mut phys, "d");
+        assert_eq!(game.entities["d"].room_id, 0);
+        assert!(game.move_player(&mut phys, "c", 0).unwrap_err().contains("full"));
+        assert_eq!((game.entities["c"].room_id, game.spawns.slots["c"].0), (1, 1));
+        assert!(game.move_player(&mut phys, "nobody", 0).is_err());
+    }
+
+    /// One tick of the debug path of run_tick: step with the watched cars'
+    /// overlays kept, then broadcast.
+    fn debug_tick(game: &mut SharedGameState, phys: &mut PhysicsWorld) {
+        phys.watched_overlays = game.overlay_vehicles();
+        phys.clear_debug_overlay();
+        phys.step(1.0 / 60.0);
+        game.broadcast_debug_overlay(phys);
+    }
+
+    fn debug_frames(messages: &[serde_json::Value]) -> impl Iterator<Item = &serde_json::Value> {
+        messages.iter().filter(|m| m["type"] == "debug")
+    }
+
+    #[test]
+    fn a_coach_watches_one_car_until_it_leaves() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        let _rx_a = game.join_test_player(&mut phys, "a");
+        let _rx_b = game.join_test_player(&mut phys, "b");
+        let mut coaches = Vec::new();
+        for coach in ["coach1", "coach2"] {
+            let (tx, rx) = ClientTx::channel();
+            game.add_spectator(coach, 0, tx);
+            assert_eq!(game.check_debug_target(coach, "a", true), Ok(()));
+            game.set_debug_subscription(coach, DebugSubscription { target: Some("a".to_string()), ..Default::default() });
+            coaches.push(rx);
+        }
+        assert_eq!(game.check_debug_target("b", "a", false), Err("debug_target_forbidden"));
+        assert_eq!(game.check_debug_target("coach1", "nobody", true), Err("unknown_debug_target"));
+
+        debug_tick(&mut game, &mut phys);
+        let [first, second] = [received(&mut coaches[0]), received(&mut coaches[1])];
+        let frames: Vec<_> = debug_frames(&first).collect();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0]["target"], "a");
+        assert_eq!(frames[0]["data"]["wheels"].as_array().unwrap().len(), 4, "only a's wheels, not b's");
+        assert_eq!(debug_frames(&second).collect::<Vec<_>>(), frames, "both coaches get the same frame");
+
+        game.despawn_player(&mut phys, "a");
+        for rx in &mut coaches {
+            let messages = received(rx);
+            assert!(messages.iter().any(|m| m["type"] == "debug_target_lost" && m["target"] == "a"), "{:?}", messages);
+        }
+        assert!(game.debug_targets().is_empty());
+        assert!(game.debug_validate(&phys).is_empty());
+
+        debug_tick(&mut game, &mut phys);
+        for rx in &mut coaches {
+            let messages = received(rx);
+            assert!(debug_frames(&messages).all(|m| m.get("target").is_none()), "no more of a's frames: {:?}", messages);
+        }
+    }
+
+    /// The one debug frame in `rx`, raw (its size is what the client pays).
+    fn debug_frame_text(rx: &mut UnboundedReceiver<OutFrame>) -> String {
+        let frames: Vec<String> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|frame| match frame {
+                OutFrame::Text(text) if text.contains("\"type\":\"debug\"") => Some(text.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(frames.len(), 1, "{:?}", frames);
+        frames.into_iter().next().unwrap()
+    }
+
+    const DEBUG_RAY_LISTS: [&str; 7] = ["suspension_rays", "load_bars", "arb_links", "slip_vectors", "tire_forces", "wireframes", "trails"];
+
+    #[test]
+    fn a_wheels_only_subscriber_gets_no_rays_and_a_smaller_frame() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        let mut wheels_only = game.join_test_player(&mut phys, "a");
+        let mut everything = game.join_test_player(&mut phys, "b");
+        game.set_debug_subscription("a", DebugSubscription { categories: DEBUG_WHEELS, ..Default::default() });
+        game.set_debug_subscription("b", DebugSubscription { categories: DEBUG_ALL, ..Default::default() });
+        debug_tick(&mut game, &mut phys);
+
+        let (small, full) = (debug_frame_text(&mut wheels_only), debug_frame_text(&mut everything));
+        let (small_json, full_json): (serde_json::Value, serde_json::Value) = (serde_json::from_str(&small).unwrap(), serde_json::from_str(&full).unwrap());
+        for list in DEBUG_RAY_LISTS {
+            assert_eq!(small_json["data"][list], json!([]), "{} dropped for the wheels-only subscriber", list);
+        }
+        assert_eq!(small_json["data"]["wheels"], full_json["data"]["wheels"]);
+        assert_eq!(small_json["data"]["wheels"].as_array().unwrap().len(), 8);
+        assert!(full_json["data"]["suspension_rays"].as_array().is_some_and(|r| !r.is_empty()));
+        assert!(small.len() * 2 < full.len(), "wheels only {} B vs everything {} B", small.len(), full.len());
+    }
+
+    #[test]
+    fn auto_lod_drops_the_rays_of_a_fast_car_only() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        let mut rx = game.join_test_player(&mut phys, "a");
+        game.set_debug_subscription("a", DebugSubscription { auto_lod: true, ..Default::default() });
+
+        debug_tick(&mut game, &mut phys);
+        let parked: serde_json::Value = serde_json::from_str(&debug_frame_text(&mut rx)).unwrap();
+        assert!(parked["data"]["suspension_rays"].as_array().is_some_and(|r| !r.is_empty()), "slow: full detail");
+
+        let body = game.entities["a"].body_handle;
+        phys.bodies[body].set_linvel(vector![0.0, 0.0, DEBUG_AUTO_LOD_SPEED + 10.0], true);
+        debug_tick(&mut game, &mut phys);
+        assert!(phys.bodies[body].linvel().norm() > DEBUG_AUTO_LOD_SPEED);
+        let fast: serde_json::Value = serde_json::from_str(&debug_frame_text(&mut rx)).unwrap();
+        for list in DEBUG_RAY_LISTS.into_iter().filter(|l| *l != "trails") {
+            assert_eq!(fast["data"][list], json!([]), "{} dropped at speed", list);
+        }
+        assert_eq!(fast["data"]["wheels"].as_array().unwrap().len(), 4, "the numeric wheel block stays");
+    }
+
+    #[test]
+    fn collisions_go_to_the_rooms_of_the_cars_involved() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        game.spawns.max_players = 2;
+        let mut rx: Vec<_> = ["a", "b", "c"].into_iter().map(|id| game.join_test_player(&mut phys, id)).collect();
+        assert_eq!(game.entities["c"].room_id, 1);
+        for rx in &mut rx {
+            received(rx);
+        }
+
+        let wall = CollisionRecord { a: "a".into(), b: crate::collisions::WORLD.into(), point: [0.0, 1.0, 5.0], impulse: 5000.0 };
+        game.broadcast_events(&[wall]);
+        game.broadcast_events(&[]);
+        let got: Vec<Vec<serde_json::Value>> = rx.iter_mut().map(received).collect();
+        for messages in &got[..2] {
+            assert_eq!(messages.len(), 1);
+            assert_eq!(messages[0]["type"], "collisions");
+            assert_eq!(messages[0]["collisions"][0]["a"], "a");
+            assert_eq!(messages[0]["collisions"][0]["b"], "world");
+        }
+        assert!(got[2].is_empty(), "room 1 saw nothing: {:?}", got[2]);
+    }
+
+    #[test]
+    fn debug_overlays_and_plugin_events_stay_in_their_room() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        game.spawns.max_players = 2;
+        let mut rx: Vec<_> = ["a", "b", "c"].into_iter().map(|id| game.join_test_player(&mut phys, id)).collect();
+        assert_eq!(game.entities["c"].room_id, 1);
+        let c_pos = *phys.bodies[game.entities["c"].body_handle].translation();
+
+        // A low-gravity zone around c only
+        let mut zone = crate::plugins::LowGravityZone::default();
+        zone.center = [c_pos.x, c_pos.z];
+        zone.radius = 1.0;
+        game.plugins.register(Box::new(zone));
+        let rooms = |id: &str| game.entities.get(id).map(|e| e.room_id);
+        let events = game.plugins.run_tick(&mut phys, &rooms, game.tick + 1, 1.0 / 60.0);
+        game.broadcast_game_events(&events);
+        debug_tick(&mut game, &mut phys);
+
+        let got: Vec<Vec<serde_json::Value>> = rx.iter_mut().map(received).collect();
+        for (id, messages) in ["a", "b"].into_iter().zip(&got[..2]) {
+            assert!(messages.iter().all(|m| m["type"] != "game_event"), "{} heard room 1's zone: {:?}", id, messages);
+            let frames: Vec<_> = debug_frames(messages).collect();
+            assert_eq!(frames.len(), 1);
+            assert_eq!(frames[0]["data"]["wheels"].as_array().unwrap().len(), 8, "a's and b's wheels, not c's");
+        }
+        let events: Vec<_> = got[2].iter().filter(|m| m["type"] == "game_event").collect();
+        assert_eq!(events.len(), 1);
+        assert_eq!((&events[0]["data"]["name"], &events[0]["data"]["data"]["id"]), (&json!("zone_enter"), &json!("c")));
+        let frames: Vec<_> = debug_frames(&got[2]).collect();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0]["data"]["wheels"].as_array().unwrap().len(), 4, "only c's wheels");
+        let chassis_x = frames[0]["data"]["chassis"]["position"][0].as_f64().unwrap();
+        assert!((chassis_x - c_pos.x as f64).abs() < 0.5, "room 1's chassis is c's, at x {}", chassis_x);
+    }
+
+    /// Latest snapshot in `messages`, applied to what a client holds: a full
+    /// snapshot replaces it, a delta updates the sent entities and drops the
+    /// removed ones. Returns the snapshot's tick and baseline.
+    fn apply_snapshot(held: &mut HashMap<String, [f64; 3]>, messages: &[serde_json::Value]) -> (u64, Option<u64>) {
+        let data = &messages.iter().rfind(|m| m["type"] == "snapshot").expect("snapshot")["data"];
+        let baseline = data["baseline"].as_u64();
+        if baseline.is_none() {
+            held.clear();
+        }
+        for id in data["removed"].as_array().into_iter().flatten() {
+            held.remove(id.as_str().unwrap());
+        }
+        for p in data["players"].as_array().unwrap() {
+            let pos = ["x", "y", "z"].map(|k| p[k].as_f64().unwrap());
+            held.insert(p["id"].as_str().unwrap().to_string(), pos);
+        }
+        (data["tick"].as_u64().unwrap(), baseline)
+    }
+
+    #[test]
+    fn deltas_against_acked_baselines_rebuild_the_full_snapshot() {
+        let mut phys = PhysicsWorld::new();
+        let mut game = SharedGameState::new(TickRate::default());
+        game.spawns.max_players = 8;
+        let mut a = game.join_test_player(&mut phys, "a"); // acks, with loss and a blackout
+        let mut b = game.join_test_player(&mut phys, "b"); // never acks: full snapshots
+        let _c = game.join_test_player(&mut phys, "c"); // drives, leaves the room and comes back
+        let _e = game.join_test_player(&mut phys, "e"); // leaves at tick 120
+        let mut d = None; // joins late, creeps
+        let acks = game.input_sender("a");
+        let nudge = |game: &SharedGameState, phys: &mut PhysicsWorld, id: &str, dx: f32| {
+            let body = &mut phys.bodies[game.entities[id].body_handle];
+            let pos = *body.translation();
+            body.set_translation(pos + vector![dx, 0.0, 0.0], false);
+        };
+
+        let (mut a_held, mut b_held) = (HashMap::new(), HashMap::new());
+        let (mut deltas, mut full_after_blackout, mut delta_entries) = (0, 0, 0);
+        for tick in 1..=400u64 {
+            game.tick = tick;
+            match tick {
+                80 => d = Some(game.join_test_player(&mut phys, "d")),
+                120 => game.despawn_player(&mut phys, "e"),
+                150 => drop(game.move_player(&mut phys, "c", 1).unwrap()),
+                250 => drop(game.move_player(&mut phys, "c", 0).unwrap()),
+                _ => {}
+            }
+            nudge(&game, &mut phys, "c", 0.3);
+            if d.is_some() {
+                nudge(&game, &mut phys, "d", 0.003); // under the epsilon every tick
+            }
+            game.apply_input_mailbox(&mut phys);
+            game.broadcast_snapshot(&phys);
+
+            let to_a = received(&mut a);
+            let (sent, baseline) = apply_snapshot(&mut a_held, &to_a);
+            apply_snapshot(&mut b_held, &received(&mut b));
+            assert_eq!(sent, tick);
+            if baseline.is_some() {
+                deltas += 1;
+                delta_entries += to_a.iter().rfind(|m| m["type"] == "snapshot").unwrap()["data"]["players"].as_array().unwrap().len();
+            } else if (200..=210).contains(&tick) {
+                full_after_blackout += 1;
+            }
+
+            // Same entities as the full snapshot, every pose within two epsilons
+            let mut a_ids: Vec<_> = a_held.keys().collect();
+            let mut b_ids: Vec<_> = b_held.keys().collect();
+            a_ids.sort();
+            b_ids.sort();
+            assert_eq!(a_ids, b_ids, "entity sets diverged at tick {tick}");
+            for (id, pos) in &b_held {
+                let off = (0..3).map(|i| (a_held[id][i] - pos[i]).abs()).fold(0.0, f64::max);
+                assert!(off <= 2.0 * crate::interest::MOVE_EPSILON as f64 + 1e-5, "{id} {off} m off at tick {tick}");
+            }
+
+            if tick == 200 {
+                assert!(!a_held.contains_key("c"), "c is in room 1");
+            }
+
+            // 30% of acks lost, none at all for 100 ticks (longer than the history)
+            if tick % 10 >= 3 && !(100..200).contains(&tick) {
+                acks.send(MailboxInput::Ack(tick));
+            }
+        }
+        assert!(deltas > 200, "{deltas} delta snapshots");
+        assert!(full_after_blackout > 0, "the blackout outlived the history: full snapshots until the next ack");
+        assert!((delta_entries as f64) < 2.0 * deltas as f64, "deltas carry the movers, not the room");
+        assert!(!b_held.contains_key("e") && b_held.contains_key("c") && b_held.contains_key("d"));
+
+        acks.send(MailboxInput::Ack(game.tick + 100));
+        game.apply_input_mailbox(&mut phys);
+        assert!(received(&mut a).iter().any(|m| m.to_string().contains("ack_from_future")));
+    }
+}
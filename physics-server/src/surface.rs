@@ -0,0 +1,135 @@
+// ==============================================================================
+// surface.rs — SURFACE TAGS + WHAT EACH WHEEL IS STANDING ON
+// ------------------------------------------------------------------------------
+// The suspension ray reports the collider it hit and that collider's normal
+// (suspension_contact.rs). This module turns those into something readable:
+//
+//   material : the collider's surface tag, stored as an index into the
+//              registry's material table in Collider::user_data
+//              (0 = untagged = "default")
+//   source   : "terrain" (the world ground box or heightfield), "vehicle:<player id>" (another
+//              car's chassis), a registered prop name, otherwise "prop" for
+//              fixed / parentless colliders and "body" for anything else
+//   kind     : static (parentless or fixed body) / dynamic (can move)
+//   grip     : tire μ multiplier for a collider (default 1.0; map.rs
+//              "friction", e.g. 0.1 for an ice patch)
+//
+// Debug overlay : DebugWheel.surface = {material, source, kind, normal}
+// Telemetry     : surface_fl..surface_rr columns (material)
+//
+// Only grip changes the physics: physics.rs scales each contact's μ by it.
+// Moving-platform work reads the same data.
+// ==============================================================================
+
+use std::collections::HashMap;
+
+use rapier3d::prelude::*;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+pub const DEFAULT_MATERIAL: &str = "default";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SurfaceKind {
+    Static,
+    Dynamic,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct WheelSurface {
+    pub material: String,
+    pub source: String,
+    pub kind: SurfaceKind,
+    pub normal: [f32; 3], // world space, from the ray hit
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SurfaceRegistry {
+    materials: Vec<String>,                    // user_data - 1 → material name
+    props: HashMap<ColliderHandle, String>,    // friendly names for props
+    terrain: Option<ColliderHandle>,
+    grip: HashMap<ColliderHandle, f32>,        // tire μ multipliers (absent = 1.0)
+}
+
+impl SurfaceRegistry {
+    pub fn set_terrain(&mut self, collider: ColliderHandle) {
+        self.terrain = Some(collider);
+    }
+
+    pub fn terrain(&self) -> Option<ColliderHandle> {
+        self.terrain
+    }
+
+    /// Tag a collider's surface material; false if the collider is gone.
+    pub fn set_material(&mut self, colliders: &mut ColliderSet, collider: ColliderHandle, material: &str) -> bool {
+        let Some(co) = colliders.get_mut(collider) else { return false };
+        co.user_data = if material == DEFAULT_MATERIAL {
+            0
+        } else {
+            let index = match self.materials.iter().position(|m| m == material) {
+                Some(i) => i,
+                None => {
+                    self.materials.push(material.to_string());
+                    self.materials.len() - 1
+                }
+            };
+            index as u128 + 1
+        };
+        true
+    }
+
+    pub fn set_grip(&mut self, collider: ColliderHandle, grip: f32) {
+        self.grip.insert(collider, grip);
+    }
+
+    pub fn grip(&self, collider: ColliderHandle) -> f32 {
+        self.grip.get(&collider).copied().unwrap_or(1.0)
+    }
+
+    pub fn name_prop(&mut self, collider: ColliderHandle, name: &str) {
+        self.props.insert(collider, name.to_string());
+    }
+
+    pub fn material(&self, co: &Collider) -> &str {
+        match co.user_data {
+            0 => DEFAULT_MATERIAL,
+            i => self.materials.get(i as usize - 1).map_or(DEFAULT_MATERIAL, String::as_str),
+        }
+    }
+
+    /// Describe the collider a wheel ray hit (None if it no longer exists).
+    pub fn describe(
+        &self,
+        colliders: &ColliderSet,
+        bodies: &RigidBodySet,
+        body_to_player: &HashMap<RigidBodyHandle, String>,
+        collider: ColliderHandle,
+        normal: Vector<Real>,
+    ) -> Option<WheelSurface> {
+        let co = colliders.get(collider)?;
+        let parent = co.parent();
+        let kind = match parent.and_then(|b| bodies.get(b)) {
+            Some(rb) if !rb.is_fixed() => SurfaceKind::Dynamic,
+            _ => SurfaceKind::Static,
+        };
+        let source = if self.terrain == Some(collider) {
+            "terrain".to_string()
+        } else if let Some(id) = parent.and_then(|b| body_to_player.get(&b)) {
+            format!("vehicle:{}", id)
+        } else if let Some(name) = self.props.get(&collider) {
+            name.clone()
+        } else if kind == SurfaceKind::Static {
+            "prop".to_string()
+        } else {
+            "body".to_string()
+        };
+
+        Some(WheelSurface {
+            material: self.material(co).to_string(),
+            source,
+            kind,
+            normal: normal.into(),
+        })
+    }
+}
@@ -17,21 +17,169 @@
 //     basis via steering::solve_steering() and kinematics::wheel_basis_world(),
 //     and finally computes slip components via kinematics::slip_components().
 //
+// Ray cache (per wheel, see WheelRayCache):
+//   The ground under a wheel rarely changes between ticks. After a full
+//   pipeline query we remember the hit collider and a "clean" world-space
+//   region around the ray that contains no other static collider. Next tick,
+//   if the ray still lies inside that region, the static scene epoch is
+//   unchanged and no dynamic collider overlaps the region, the ray is tested
+//   against the cached collider alone. Anything else falls back to the full
+//   query, so results are identical to always querying.
+//
+// Embedded wheels:
+//   After a hard landing or a bad spawn the ray origin can end up inside the
+//   ground (solid cast => toi = 0) or the hub closer than one radius to it.
+//   Both used to read as "airborne", so nothing pushed the car back out.
+//   Such wheels are reported as embedded: compression clamped to max and a
+//   penetration depth the caller turns into a bounded depenetration impulse.
+//
+// Damper velocity:
+//   The damper reads how fast the suspension itself is extending: the change in
+//   measured compression since the previous solve of that wheel (Wheel::
+//   prev_compression), not the contact point's world velocity. A car climbing
+//   a steady hill moves up in the world but its suspension doesn't, so it no
+//   longer burns damper force. On the first contact after being airborne there
+//   is no previous length; that solve falls back to the point velocity along
+//   the ground normal (a landing still gets damped).
+//
 // Notes:
 // - This file does NOT apply impulses. It only measures/constructs contact data.
-// - Ground normal is currently assumed flat-up; for slopes, read normal from
-//   Ray intersection data and propagate it through basis + forces.
+// - Ground normal: the surface normal of the ray hit (ramps, banked track
+//   pieces), tilted by the global road camber the caller passes as `ground_n`
+//   (flat-up when there is none). It drives the suspension axis, the spring
+//   impulse direction and the planar tire basis. Near-vertical faces
+//   (n.y < MIN_GROUND_NORMAL_Y) and embedded contacts fall back to `ground_n`.
+// - The hit collider and its raw normal are reported alongside (hit_collider,
+//   hit_normal) for surface.rs.
 // ==============================================================================
 
 use rapier3d::prelude::*;
 use rapier3d::prelude::vector;
 
-use crate::physics::Wheel;
+use crate::physics::{Wheel, wheel_ray_groups};
 use crate::vehicle::Vehicle;
 use crate::aven_tire::steering::SteeringState;
 use crate::aven_tire::kinematics::{wheel_basis_world, slip_components};
 use crate::aven_tire::WheelId;
 
+const RAY_CACHE_MARGIN: f32 = 0.5;    // m, slack around the ray before refreshing
+const RAY_CACHE_TOI_TOLERANCE: f32 = 0.05; // m, larger hit jumps re-query
+const MIN_GROUND_NORMAL_Y: f32 = 0.3;  // steeper hit faces (walls) are not driven on
+
+#[derive(Clone, Default)]
+pub struct WheelRayCache {
+    region: Option<Aabb>,                // clean region (None = nothing cached)
+    hit: Option<(ColliderHandle, f32)>,  // cached hit (None = clean miss)
+    epoch: u64,                          // static scene epoch at fill time
+}
+
+/// Per-tick inputs for the ray cache (shared by all wheels).
+pub struct RayCacheScene<'a> {
+    pub static_epoch: u64,
+    pub dynamic_aabbs: &'a [(RigidBodyHandle, Aabb)], // awake non-fixed bodies' colliders
+}
+
+fn ray_segment_aabb(ray: &Ray, max_dist: f32) -> Aabb {
+    let end = ray.point_at(max_dist);
+    Aabb::new(
+        point![ray.origin.x.min(end.x), ray.origin.y.min(end.y), ray.origin.z.min(end.z)],
+        point![ray.origin.x.max(end.x), ray.origin.y.max(end.y), ray.origin.z.max(end.z)],
+    )
+}
+
+fn aabb_contains(outer: &Aabb, inner: &Aabb) -> bool {
+    (0..3).all(|i| outer.mins[i] <= inner.mins[i] && inner.maxs[i] <= outer.maxs[i])
+}
+
+/// Collider under a wheel and the ray's hit on it.
+#[derive(Clone, Copy, Debug)]
+struct WheelHit {
+    collider: ColliderHandle,
+    toi: f32,
+    normal: Vector<Real>,
+}
+
+/// Cast the suspension ray, using the wheel's cache when provably equivalent.
+#[allow(clippy::too_many_arguments)]
+fn cast_wheel_ray(
+    cache: &mut WheelRayCache,
+    scene: &RayCacheScene,
+    query: &QueryPipeline,
+    bodies: &RigidBodySet,
+    colliders: &ColliderSet,
+    handle: RigidBodyHandle,
+    groups: InteractionGroups,
+    ray: &Ray,
+    max_dist: f32,
+) -> Option<WheelHit> {
+    let segment = ray_segment_aabb(ray, max_dist);
+
+    // ---------- Cached path ----------
+    if let Some(region) = cache.region {
+        let dynamic_in_region = scene
+            .dynamic_aabbs
+            .iter()
+            .any(|(body, aabb)| *body != handle && aabb.intersects(&region));
+
+        if cache.epoch == scene.static_epoch && aabb_contains(&region, &segment) && !dynamic_in_region {
+            match cache.hit {
+                None => return None, // nothing static in the region
+                Some((collider, last_toi)) => {
+                    let hit = colliders
+                        .get(collider)
+                        .and_then(|co| co.shape().cast_ray_and_get_normal(co.position(), ray, max_dist, true));
+                    if let Some(hit) = hit.filter(|h| (h.time_of_impact - last_toi).abs() <= RAY_CACHE_TOI_TOLERANCE) {
+                        cache.hit = Some((collider, hit.time_of_impact));
+                        return Some(WheelHit { collider, toi: hit.time_of_impact, normal: hit.normal });
+                    }
+                }
+            }
+        }
+    }
+
+    // ---------- Full query + refill ----------
+    let filter = QueryFilter::default().groups(groups).exclude_rigid_body(handle);
+    let hit = query
+        .cast_ray_and_get_normal(bodies, colliders, ray, max_dist, true, filter)
+        .map(|(collider, hit)| WheelHit { collider, toi: hit.time_of_impact, normal: hit.normal });
+
+    // "Static" for caching: parentless (world-attached), fixed, or dynamic but
+    // asleep (must wake to move, at which point it shows up in scene.dynamic_aabbs)
+    let is_static = |h: ColliderHandle| {
+        colliders.get(h).is_some_and(|co| {
+            co.parent()
+                .and_then(|b| bodies.get(b))
+                .is_none_or(|rb| rb.is_fixed() || rb.is_sleeping())
+        })
+    };
+
+    cache.region = None;
+    cache.hit = hit.map(|h| (h.collider, h.toi));
+    cache.epoch = scene.static_epoch;
+
+    // Only cache static hits (or clean misses)
+    if hit.is_some_and(|h| !is_static(h.collider)) {
+        return hit;
+    }
+
+    let region = segment.loosened(RAY_CACHE_MARGIN);
+    let mut clean = true;
+    query.colliders_with_aabb_intersecting_aabb(&region, |&h| {
+        let Some(co) = colliders.get(h) else { return true };
+        let own = co.parent() == Some(handle);
+        // Awake dynamic colliders are re-checked every tick; statics are not
+        if !own && is_static(h) && hit.map(|hit| hit.collider) != Some(h) {
+            clean = false;
+        }
+        clean
+    });
+    if clean {
+        cache.region = Some(region);
+    }
+
+    hit
+}
+
 
 // struct SuspensionState {
 //     compression: f32,
@@ -92,7 +240,9 @@ pub struct SuspensionContact {
     // suspension state
     pub compression: f32,
     pub compression_ratio: f32,
-    pub suspension_vel: f32,
+    pub suspension_vel: f32,                // point velocity along the ground normal
+    pub suspension_velocity_vertical: f32,  // point velocity along world up (overlay / diagnostics)
+    pub damper_vel: f32,                    // suspension extension rate, + = extending (spring-damper)
     pub normal_force: f32,
 
     // kinematics
@@ -113,6 +263,12 @@ pub struct SuspensionContact {
     // misc
     pub grounded: bool,
     pub roll_factor: f32,
+    pub embedded: bool,   // ray started inside / too close to the ground
+    pub penetration: f32, // m the mount must rise for the wheel to fit (0 unless embedded)
+
+    // ground truth (surface.rs)
+    pub hit_collider: Option<ColliderHandle>, // None when embedded with no ray hit
+    pub hit_normal: Vector<Real>,             // the hit collider's normal (world)
 }
 
 // ==========================================================
@@ -148,29 +304,94 @@ fn planar_wheel_basis(
     (forward, side)
 }
 
+pub(crate) const DAMPER_LIMIT: f32 = 0.6; // damper force cap, fraction of spring force
+
 pub(crate) fn compute_suspension_force(
     compression: f32,
-    suspension_vel: f32,
+    damper_vel: f32,
     k: f32,
     c: f32,
+    damper_limit: f32,
 ) -> f32 {
     // Deadzone
-    let v = if suspension_vel.abs() < 0.05 { 0.0 } else { suspension_vel };
+    let v = if damper_vel.abs() < 0.05 { 0.0 } else { damper_vel };
 
-    // One-way damper (kills rebound)
+    // Asymmetric damper: firmer in rebound (extending) than in bump
     // let v = if v > 0.0 { v * 0.4 } else { v };
     let v = if v > 0.0 { v * 1.5 } else { v * 0.8 };
 
 
     let spring = k * compression;
-    let damper = (-c * v).clamp(-spring * 0.6, spring * 0.6);
+    let damper = (-c * v).clamp(-spring * damper_limit, spring * damper_limit);
 
     (spring + damper).max(0.0)
 }
 
 
+// ==========================================================
+// Axle-averaged contact (simplified tire model, high player counts):
+// hit points / compression / slip averaged, loads summed, basis from
+// the left wheel so the solver sees one patch per axle.
+// ==========================================================
+pub fn merge_axle_contacts(left: &SuspensionContact, right: &SuspensionContact) -> SuspensionContact {
+    let avg = |a: f32, b: f32| 0.5 * (a + b);
+    let normal = left.ground_normal + right.ground_normal;
+
+    SuspensionContact {
+        wheel_id: left.wheel_id.clone(),
+        hit_point: rapier3d::na::center(&left.hit_point, &right.hit_point),
+        apply_point: rapier3d::na::center(&left.apply_point, &right.apply_point),
+        ground_normal: if normal.norm_squared() > 1e-6 { normal.normalize() } else { left.ground_normal },
+        compression: avg(left.compression, right.compression),
+        compression_ratio: avg(left.compression_ratio, right.compression_ratio),
+        suspension_vel: avg(left.suspension_vel, right.suspension_vel),
+        suspension_velocity_vertical: avg(left.suspension_velocity_vertical, right.suspension_velocity_vertical),
+        damper_vel: avg(left.damper_vel, right.damper_vel),
+        normal_force: left.normal_force + right.normal_force,
+        point_vel: (left.point_vel + right.point_vel) * 0.5,
+        mu_lat: avg(left.mu_lat, right.mu_lat),
+        mu_long: avg(left.mu_long, right.mu_long),
+        forward: left.forward,
+        side: left.side,
+        v_long: avg(left.v_long, right.v_long),
+        v_lat: avg(left.v_lat, right.v_lat),
+        grounded: left.grounded || right.grounded,
+        roll_factor: avg(left.roll_factor, right.roll_factor),
+        embedded: left.embedded || right.embedded,
+        penetration: left.penetration.max(right.penetration),
+        hit_collider: left.hit_collider,
+        hit_normal: left.hit_normal,
+    }
+}
+
+/// How far the wheel mount must rise before the wheel fits above the ground,
+/// when the ray can't give a usable hit (`toi` None or within one radius),
+/// and the collider it is stuck in. None => the wheel really is in the air.
+fn embedded_penetration(
+    query: &QueryPipeline,
+    bodies: &RigidBodySet,
+    colliders: &ColliderSet,
+    handle: RigidBodyHandle,
+    ray: &Ray,
+    hit: Option<WheelHit>,
+    radius: f32,
+) -> Option<(f32, Option<ColliderHandle>)> {
+    let filter = QueryFilter::only_fixed().exclude_rigid_body(handle);
+    let inside = query
+        .project_point(bodies, colliders, &ray.origin, false, filter)
+        .filter(|(_, proj)| proj.is_inside);
+
+    match (inside, hit) {
+        // Origin inside the ground: depth to the surface above it, plus the wheel
+        (Some((collider, proj)), _) => Some(((proj.point - ray.origin).dot(&-ray.dir).max(0.0) + radius, Some(collider))),
+        (None, Some(hit)) => Some((radius - hit.toi, Some(hit.collider))),
+        (None, None) => None,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn build_suspension_contact(
-    wheel: &Wheel,
+    wheel: &mut Wheel,
     vehicle: &Vehicle,
     steering: &SteeringState,
     body_ro: &RigidBody,
@@ -178,54 +399,86 @@ pub fn build_suspension_contact(
     bodies: &RigidBodySet,
     colliders: &ColliderSet,
     handle: RigidBodyHandle,
+    ray_scene: &RayCacheScene,
+    ground_n: Vector<Real>,
     fz_ref: f32,
-    _dt: f32,
+    dt: f32,
 ) -> Option<SuspensionContact> {
 
     let pos = body_ro.position();
     let rot = pos.rotation;
     let linvel = *body_ro.linvel();
     let angvel = *body_ro.angvel();
-    let com = pos * body_ro.center_of_mass();
+    let com = *body_ro.center_of_mass();
 
     let origin = pos * (wheel.offset + vector![0.0, wheel.radius + 0.02, 0.0]);
     let dir = vector![0.0, -1.0, 0.0];
-    let ground_n = vector![0.0, 1.0, 0.0];
 
     let ray = Ray::new(origin, dir);
     let max_dist = wheel.rest_length + wheel.max_length + wheel.radius;
 
-    let filter = QueryFilter::default().exclude_rigid_body(handle);
-
-    let (_hit, toi) = query.cast_ray(
+    let hit = cast_wheel_ray(
+        &mut wheel.ray_cache,
+        ray_scene,
+        query,
         bodies,
         colliders,
+        handle,
+        wheel_ray_groups(&vehicle.config),
         &ray,
         max_dist,
-        true,
-        filter,
-    )?;
-
-    if toi <= wheel.radius { return None; }
-
-    let hit_point = origin + dir * toi;
-    let suspension_length = (toi - 0.02) - wheel.radius;
-    let suspension_length = suspension_length.clamp(0.0, (wheel.rest_length + wheel.max_length) as f32);
+    );
 
-    let compression = (wheel.rest_length as f32 - suspension_length)
-        .clamp(0.0, wheel.max_length as f32);
+    let (hit_point, compression, penetration, hit_collider) = match hit {
+        Some(WheelHit { collider, toi, .. }) if toi > wheel.radius => {
+            let suspension_length = (toi - 0.02) - wheel.radius;
+            let suspension_length = suspension_length.clamp(0.0, wheel.rest_length + wheel.max_length);
+
+            let compression = (wheel.rest_length - suspension_length)
+                .clamp(0.0, wheel.max_length);
+
+            (origin + dir * toi, compression, 0.0, Some(collider))
+        }
+        _ => {
+            // Embedded: contact on the surface above the mount, fully compressed
+            let Some((penetration, collider)) = embedded_penetration(query, bodies, colliders, handle, &ray, hit, wheel.radius) else {
+                wheel.prev_compression = None; // airborne: no suspension length to difference against
+                return None;
+            };
+            (origin - dir * (penetration - wheel.radius), wheel.max_length, penetration, collider)
+        }
+    };
+    // A ray starting inside a solid reports a meaningless normal; use "up the ray"
+    let hit_normal = hit.filter(|h| h.toi > 0.0).map_or(-dir, |h| h.normal);
+    let embedded = penetration > 0.0;
+
+    // Surface normal, carrying the caller's camber tilt (identity when flat)
+    let ground_n = if embedded || hit_normal.y < MIN_GROUND_NORMAL_Y {
+        ground_n
+    } else {
+        let camber = Rotation::rotation_between(&-dir, &ground_n).unwrap_or_else(Rotation::identity);
+        camber * hit_normal
+    };
 
     let compression_ratio = compression / wheel.max_length;
 
     let r = hit_point.coords - com.coords;
     let point_vel = linvel + angvel.cross(&r);
     let suspension_vel = point_vel.dot(&ground_n) as f32;
+    let suspension_velocity_vertical = point_vel.y;
+
+    let damper_vel = match wheel.prev_compression {
+        Some(prev) if dt > 0.0 => (prev - compression) / dt,
+        _ => suspension_vel,
+    };
+    wheel.prev_compression = Some(compression);
 
     let normal_force = compute_suspension_force(
         compression,
-        suspension_vel,
+        damper_vel,
         wheel.stiffness as f32,
-        wheel.damping as f32,
+        wheel.damping * vehicle.air.damping_scale(), // firmer right after a landing
+        vehicle.air.damper_limit(DAMPER_LIMIT),
     );
 
     let max_nf = fz_ref * 2.2; // allow some load transfer, but not insanity
@@ -255,6 +508,8 @@ pub fn build_suspension_contact(
         compression,
         compression_ratio,
         suspension_vel,
+        suspension_velocity_vertical,
+        damper_vel,
         normal_force,
         mu_lat,
         mu_long: mu0,
@@ -265,5 +520,9 @@ pub fn build_suspension_contact(
         grounded: true,
         roll_factor: roll_factor as f32,
         point_vel: point_vel,
+        embedded,
+        penetration,
+        hit_collider,
+        hit_normal,
     })
 }
@@ -0,0 +1,641 @@
+// ==============================================================================
+// sweep.rs — HEADLESS PARAMETER SWEEP (AUTOMATED TUNING)
+// ------------------------------------------------------------------------------
+//   physics-server --sweep sweep.toml
+//
+// Runs one scenario per parameter combination, each in its own PhysicsWorld
+// (spread over worker threads), scores it by one metric (lower = better),
+// writes a ranked CSV and prints the top 3.
+//
+//   scenario  = "step_steer"           # settle | acceleration | step_steer | lift_off | skidpad
+//   objective = "peak_slip_overshoot"  # a metric of that scenario (below)
+//   vehicle   = "gt86"                 # gt86 | arcade | tank, or a configs/vehicles.toml preset
+//   mode      = "grid"                 # grid (every combination) | random
+//   samples   = 20                     # random only
+//   seed      = 1                      # random only
+//   workers   = 0                      # 0 = one per core
+//   output    = "sweep_results.csv"
+//
+//   [params.arb_front]
+//   min = 10000.0
+//   max = 30000.0
+//   steps = 3                          # grid points (min..=max)
+//
+//   [params.arb_rear]
+//   values = [8000.0, 16000.0]         # explicit values instead of a range
+//
+// Scenario metrics:
+//   settle       : settle_time (s until the dropped car stops moving)
+//   acceleration : time_to_speed (s from rest to 25 m/s, full throttle), top_speed
+//   step_steer   : peak_slip_overshoot (deg of body slip past steady state),
+//                  settle_time (s until the 0.25 s mean yaw rate stays within
+//                  5% / 0.02 rad/s of steady state), yaw_rate
+//   lift_off     : lift_yaw_gain (rad/s: mean yaw rate in the LIFT_WINDOW after
+//                  lifting mid-corner at the limit, minus the same window with
+//                  the throttle held; > 0 = the car tucks in / oversteers),
+//                  held_yaw_rate
+//   skidpad      : max_lateral_g (g, best steady circle over SKIDPAD_STEERS
+//                  at part throttle; higher = less understeer, so it ranks
+//                  worst-first), skidpad_yaw_rate (on that circle)
+//
+// Params are VehicleConfig fields (see set_param); arb_front / arb_rear are
+// axles 0 / 1 of arb_rates, arb_axle_<n> any axle. Combinations that fail
+// VehicleConfig::validate are listed last with the error instead of a score.
+// Runs are deterministic: same file, same CSV.
+// ==============================================================================
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rand::Rng;
+use rand::rngs::StdRng;
+use rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::aven_tire::MAX_AXLES;
+use crate::game_rng::GameRng;
+use crate::physics::{PhysicsWorld, vehicle_config_by_name};
+use crate::vehicle::VehicleConfig;
+
+const DT: f32 = 1.0 / 60.0;
+const SPAWN: [f32; 3] = [5.0, 0.0, 5.0];
+const SETTLE_TICKS: u32 = 60;            // pre-roll before acceleration / step_steer
+const SETTLE_MAX_TICKS: u32 = 240;       // settle scenario horizon (4 s)
+const SETTLE_LINVEL: f32 = 0.1;          // m/s, "stopped" (parked cars read ~0.06)
+const SETTLE_ANGVEL: f32 = 0.05;         // rad/s
+const ACCEL_TARGET_SPEED: f32 = 25.0;    // m/s
+const ACCEL_MAX_TICKS: u32 = 1200;       // 20 s
+const STEP_STEER_SPEED: f32 = 20.0;      // m/s entry speed
+const STEP_STEER_INPUT: f32 = 0.5;
+const STEP_STEER_THROTTLE: f32 = 0.3;    // roughly holds the entry speed
+const STEP_STEER_TICKS: u32 = 240;       // 4 s after the step
+const STEADY_TAIL_TICKS: usize = 30;     // last 0.5 s = steady state
+const YAW_SETTLE_BAND: f32 = 0.05;       // ±5% of the steady yaw rate...
+const YAW_SETTLE_FLOOR: f32 = 0.02;      // ...but at least ±0.02 rad/s
+const YAW_SMOOTH_TICKS: usize = 15;      // tick-to-tick yaw rate chatters; judge a 0.25 s average
+const LIFT_OFF_SPEED: f32 = 25.0;        // m/s entry speed
+const LIFT_OFF_STEER: f32 = 0.3;         // peak lateral accel for GT86 (~0.53 g); more steer plows
+const LIFT_OFF_THROTTLE: f32 = 0.5;
+const LIFT_OFF_CORNER_TICKS: u32 = 120;  // 2 s to settle into the corner
+const LIFT_OFF_WINDOW: u32 = 30;         // 0.5 s compared after the lift
+const SKIDPAD_SPEED: f32 = 12.0;         // m/s entry speed
+const SKIDPAD_STEERS: [f32; 7] = [0.2, 0.3, 0.4, 0.5, 0.6, 0.8, 1.0]; // one circle each
+const SKIDPAD_THROTTLE: f32 = 0.35;      // part throttle: steady circles, drive left to vector
+const SKIDPAD_TICKS: u32 = 360;          // 6 s per circle
+const SKIDPAD_STEADY_TICKS: usize = 120; // last 2 s = steady state
+const TOP_N: usize = 3;
+const DEFAULT_OUTPUT: &str = "sweep_results.csv";
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scenario {
+    Settle,
+    Acceleration,
+    StepSteer,
+    LiftOff,
+    Skidpad,
+}
+
+impl Scenario {
+    fn metrics(self) -> &'static [&'static str] {
+        match self {
+            Scenario::Settle => &["settle_time"],
+            Scenario::Acceleration => &["time_to_speed", "top_speed"],
+            Scenario::StepSteer => &["peak_slip_overshoot", "settle_time", "yaw_rate"],
+            Scenario::LiftOff => &["lift_yaw_gain", "held_yaw_rate"],
+            Scenario::Skidpad => &["max_lateral_g", "skidpad_yaw_rate"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SweepMode {
+    #[default]
+    Grid,
+    Random,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParamRange {
+    pub values: Option<Vec<f32>>,
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+    pub steps: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SweepDef {
+    pub scenario: Scenario,
+    pub objective: String,
+    #[serde(default = "default_vehicle")]
+    pub vehicle: String,
+    #[serde(default)]
+    pub mode: SweepMode,
+    #[serde(default = "default_samples")]
+    pub samples: usize,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    pub workers: usize,
+    pub output: Option<String>,
+    pub params: BTreeMap<String, ParamRange>,
+}
+
+fn default_vehicle() -> String { "gt86".to_string() }
+fn default_samples() -> usize { 20 }
+
+#[derive(Debug, Clone)]
+pub struct SweepResult {
+    pub params: Vec<(String, f32)>,
+    pub metrics: BTreeMap<&'static str, f32>,
+    pub score: f32,              // objective metric; inf when the run failed
+    pub error: Option<String>,   // invalid config
+}
+
+impl SweepDef {
+    pub fn from_toml(text: &str) -> Result<Self, String> {
+        let def: SweepDef = toml::from_str(text).map_err(|e| e.to_string())?;
+        if !def.scenario.metrics().contains(&def.objective.as_str()) {
+            return Err(format!(
+                "objective '{}' is not a metric of {:?} ({})",
+                def.objective, def.scenario, def.scenario.metrics().join(", ")
+            ));
+        }
+        base_config(&def.vehicle)?;
+        if def.params.is_empty() {
+            return Err("no [params.*] to sweep".to_string());
+        }
+        let mut probe = crate::physics::GT86;
+        for (name, range) in &def.params {
+            set_param(&mut probe, name, 0.0)?;
+            range.grid_values().map_err(|e| format!("params.{}: {}", name, e))?;
+        }
+        Ok(def)
+    }
+
+    /// Every parameter combination to run, in a fixed order.
+    pub fn combinations(&self) -> Vec<Vec<(String, f32)>> {
+        match self.mode {
+            SweepMode::Grid => {
+                let mut combos: Vec<Vec<(String, f32)>> = vec![Vec::new()];
+                for (name, range) in &self.params {
+                    let values = range.grid_values().unwrap_or_default();
+                    combos = combos
+                        .into_iter()
+                        .flat_map(|c| values.iter().map(move |&v| {
+                            let mut c = c.clone();
+                            c.push((name.clone(), v));
+                            c
+                        }))
+                        .collect();
+                }
+                combos
+            }
+            SweepMode::Random => {
+                let mut rng = GameRng::new(self.seed).child("sweep");
+                (0..self.samples)
+                    .map(|_| self.params.iter().map(|(name, range)| (name.clone(), range.sample(&mut rng))).collect())
+                    .collect()
+            }
+        }
+    }
+}
+
+impl ParamRange {
+    fn grid_values(&self) -> Result<Vec<f32>, String> {
+        if let Some(values) = &self.values {
+            return if values.is_empty() { Err("values is empty".to_string()) } else { Ok(values.clone()) };
+        }
+        let (Some(min), Some(max)) = (self.min, self.max) else {
+            return Err("need values = [..] or min + max".to_string());
+        };
+        let steps = self.steps.unwrap_or(2);
+        if steps == 0 || min > max {
+            return Err("need steps >= 1 and min <= max".to_string());
+        }
+        if steps == 1 {
+            return Ok(vec![min]);
+        }
+        Ok((0..steps).map(|i| min + (max - min) * i as f32 / (steps - 1) as f32).collect())
+    }
+
+    fn sample(&self, rng: &mut StdRng) -> f32 {
+        match (&self.values, self.min, self.max) {
+            (Some(values), _, _) => values[rng.gen_range(0..values.len())],
+            (None, Some(min), Some(max)) if max > min => rng.gen_range(min..=max),
+            (None, Some(min), _) => min,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Built-in config, else a preset from AVENLAB_VEHICLES_FILE (vehicle_presets.rs).
+fn base_config(name: &str) -> Result<VehicleConfig, String> {
+    if let Some(config) = vehicle_config_by_name(name) {
+        return Ok(config);
+    }
+    let path = crate::vehicle_presets::presets_path();
+    crate::vehicle_presets::load_file(&path)?
+        .and_then(|mut presets| presets.remove(name))
+        .ok_or_else(|| format!("unknown vehicle '{}' (gt86, arcade, tank or a preset in {})", name, path))
+}
+
+/// Write one sweepable VehicleConfig field by name.
+pub fn set_param(config: &mut VehicleConfig, name: &str, value: f32) -> Result<(), String> {
+    if let Some(axle) = name.strip_prefix("arb_axle_").and_then(|i| i.parse::<usize>().ok()) {
+        let rate = config
+            .arb_rates
+            .get_mut(axle)
+            .ok_or_else(|| format!("'{}': no such axle (max {})", name, MAX_AXLES - 1))?;
+        *rate = value;
+        return Ok(());
+    }
+    let field = match name {
+        "mass" => &mut config.mass,
+        "engine_force" => &mut config.engine_force,
+        "brake_force" => &mut config.brake_force,
+        "max_speed" => &mut config.max_speed,
+        "linear_damping" => &mut config.linear_damping,
+        "angular_damping" => &mut config.angular_damping,
+        "mu_base" => &mut config.mu_base,
+        "load_sensitivity" => &mut config.load_sensitivity,
+        "max_steer_angle" => &mut config.max_steer_angle,
+        "ackermann" => &mut config.ackermann,
+        "arb_front" => &mut config.arb_rates[0],
+        "arb_rear" => &mut config.arb_rates[1],
+        "abs_nx_limit" => &mut config.abs_nx_limit,
+        "tcs_nx_limit" => &mut config.tcs_nx_limit,
+        "tv_gain" => &mut config.tv_gain,
+        "tv_max_bias" => &mut config.tv_max_bias,
+        "stability_assist" => &mut config.stability_assist,
+        "brake_bias" => &mut config.brake_bias,
+        "air_control_torque" => &mut config.air_control_torque,
+        "engine_brake_coefficient" => &mut config.engine_brake_coefficient,
+        "flip_timeout_s" => &mut config.flip_timeout_s,
+        _ => return Err(format!("'{}' is not a sweepable VehicleConfig field", name)),
+    };
+    *field = value;
+    Ok(())
+}
+
+/// Run every combination on `workers` threads and rank by the objective.
+pub fn run_sweep(def: &SweepDef) -> Vec<SweepResult> {
+    let base = base_config(&def.vehicle).unwrap_or(crate::physics::GT86);
+    let combos = def.combinations();
+    let workers = match def.workers {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    }
+    .min(combos.len().max(1));
+
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<SweepResult>>> = Mutex::new(vec![None; combos.len()]);
+    std::thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(params) = combos.get(i) else { break };
+                let result = run_one(def, base, params);
+                results.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+
+    let mut results: Vec<SweepResult> = results.into_inner().unwrap().into_iter().flatten().collect();
+    // Stable sort: ties keep combination order
+    results.sort_by(|a, b| a.score.total_cmp(&b.score));
+    results
+}
+
+fn run_one(def: &SweepDef, base: VehicleConfig, params: &[(String, f32)]) -> SweepResult {
+    let mut config = base;
+    for (name, value) in params {
+        let _ = set_param(&mut config, name, *value); // names checked in from_toml
+    }
+    let metrics = match config.validate() {
+        Ok(()) => run_scenario(def.scenario, config),
+        Err(errors) => Err(errors.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; ")),
+    };
+    match metrics {
+        Ok(metrics) => SweepResult {
+            params: params.to_vec(),
+            score: metrics.get(def.objective.as_str()).copied().unwrap_or(f32::INFINITY),
+            metrics,
+            error: None,
+        },
+        Err(error) => SweepResult { params: params.to_vec(), metrics: BTreeMap::new(), score: f32::INFINITY, error: Some(error) },
+    }
+}
+
+// ==========================================================
+// Scenarios (fresh world each, flat ground, car facing +Z)
+// ==========================================================
+fn run_scenario(scenario: Scenario, config: VehicleConfig) -> Result<BTreeMap<&'static str, f32>, String> {
+    if scenario == Scenario::Skidpad {
+        let (lateral_g, yaw_rate) = skidpad(config)?;
+        let mut metrics = BTreeMap::new();
+        metrics.insert("max_lateral_g", lateral_g);
+        metrics.insert("skidpad_yaw_rate", yaw_rate);
+        return Ok(metrics);
+    }
+    if scenario == Scenario::LiftOff {
+        // Same corner twice (runs are deterministic): throttle held vs lifted
+        let held = lift_off_yaw(config, false)?;
+        let lifted = lift_off_yaw(config, true)?;
+        let mut metrics = BTreeMap::new();
+        metrics.insert("lift_yaw_gain", lifted - held);
+        metrics.insert("held_yaw_rate", held);
+        return Ok(metrics);
+    }
+
+    const ID: &str = "sweep";
+    let mut world = PhysicsWorld::new();
+    world
+        .spawn_vehicle_with_config(ID.to_string(), SPAWN, 0.0, config)
+        .map_err(|e| e.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; "))?;
+    let body = world.vehicles[ID].body;
+    let mut metrics = BTreeMap::new();
+
+    let moving = |w: &PhysicsWorld| {
+        let rb = &w.bodies[body];
+        rb.linvel().norm() > SETTLE_LINVEL || rb.angvel().norm() > SETTLE_ANGVEL
+    };
+
+    match scenario {
+        Scenario::Settle => {
+            let mut last_moving = 0;
+            for tick in 1..=SETTLE_MAX_TICKS {
+                world.step(DT);
+                if moving(&world) {
+                    last_moving = tick;
+                }
+            }
+            metrics.insert("settle_time", last_moving as f32 * DT);
+        }
+        Scenario::Acceleration => {
+            for _ in 0..SETTLE_TICKS {
+                world.step(DT);
+            }
+            let mut time_to_speed = f32::INFINITY;
+            let mut top_speed: f32 = 0.0;
+            for tick in 1..=ACCEL_MAX_TICKS {
+                world.apply_player_input(ID, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0);
+                world.step(DT);
+                let v = world.bodies[body].linvel();
+                let speed = (v.x * v.x + v.z * v.z).sqrt();
+                top_speed = top_speed.max(speed);
+                if speed >= ACCEL_TARGET_SPEED {
+                    time_to_speed = tick as f32 * DT;
+                    break;
+                }
+            }
+            metrics.insert("time_to_speed", time_to_speed);
+            metrics.insert("top_speed", top_speed);
+        }
+        Scenario::StepSteer => {
+            for _ in 0..SETTLE_TICKS {
+                world.step(DT);
+            }
+            let rb = world.bodies.get_mut(body).ok_or("vehicle body missing")?;
+            let fwd = rb.position().rotation * vector![0.0, 0.0, 1.0];
+            rb.set_linvel(fwd * STEP_STEER_SPEED, true);
+
+            let mut slip_deg = Vec::new();
+            let mut yaw = Vec::new();
+            for _ in 0..STEP_STEER_TICKS {
+                world.apply_player_input(ID, STEP_STEER_THROTTLE, STEP_STEER_INPUT, 0.0, 0.0, 0.0, 0.0, 0.0);
+                world.step(DT);
+                let rb = &world.bodies[body];
+                let rot = rb.position().rotation;
+                let v = rb.linvel();
+                let v_long = v.dot(&(rot * vector![0.0, 0.0, 1.0]));
+                let v_lat = v.dot(&(rot * vector![1.0, 0.0, 0.0]));
+                slip_deg.push(v_lat.atan2(v_long.abs().max(0.1)).to_degrees().abs());
+                yaw.push(rb.angvel().y.abs());
+            }
+
+            let tail = |xs: &[f32]| xs[xs.len() - STEADY_TAIL_TICKS..].iter().sum::<f32>() / STEADY_TAIL_TICKS as f32;
+            let slip_steady = tail(&slip_deg);
+            let yaw_steady = tail(&yaw);
+            let peak_slip = slip_deg.iter().cloned().fold(0.0, f32::max);
+            let band = (yaw_steady * YAW_SETTLE_BAND).max(YAW_SETTLE_FLOOR);
+            let smoothed: Vec<f32> = yaw
+                .windows(YAW_SMOOTH_TICKS)
+                .map(|w| w.iter().sum::<f32>() / YAW_SMOOTH_TICKS as f32)
+                .collect();
+            let last_outside = smoothed
+                .iter()
+                .rposition(|w| (w - yaw_steady).abs() > band)
+                .map_or(0, |i| i + YAW_SMOOTH_TICKS);
+
+            metrics.insert("peak_slip_overshoot", (peak_slip - slip_steady).max(0.0));
+            metrics.insert("settle_time", last_outside as f32 * DT);
+            metrics.insert("yaw_rate", yaw_steady);
+        }
+        Scenario::LiftOff | Scenario::Skidpad => unreachable!("handled above"),
+    }
+    Ok(metrics)
+}
+
+/// Mean |yaw rate| over LIFT_OFF_WINDOW ticks after the corner phase, with the
+/// throttle kept at LIFT_OFF_THROTTLE or lifted to 0.
+fn lift_off_yaw(config: VehicleConfig, lift: bool) -> Result<f32, String> {
+    const ID: &str = "sweep";
+    let mut world = PhysicsWorld::new();
+    world
+        .spawn_vehicle_with_config(ID.to_string(), SPAWN, 0.0, config)
+        .map_err(|e| e.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; "))?;
+    let body = world.vehicles[ID].body;
+
+    for _ in 0..SETTLE_TICKS {
+        world.step(DT);
+    }
+    let rb = world.bodies.get_mut(body).ok_or("vehicle body missing")?;
+    let fwd = rb.position().rotation * vector![0.0, 0.0, 1.0];
+    rb.set_linvel(fwd * LIFT_OFF_SPEED, true);
+
+    for _ in 0..LIFT_OFF_CORNER_TICKS {
+        world.apply_player_input(ID, LIFT_OFF_THROTTLE, LIFT_OFF_STEER, 0.0, 0.0, 0.0, 0.0, 0.0);
+        world.step(DT);
+    }
+    let throttle = if lift { 0.0 } else { LIFT_OFF_THROTTLE };
+    let mut yaw_sum = 0.0;
+    for _ in 0..LIFT_OFF_WINDOW {
+        world.apply_player_input(ID, throttle, LIFT_OFF_STEER, 0.0, 0.0, 0.0, 0.0, 0.0);
+        world.step(DT);
+        yaw_sum += world.bodies[body].angvel().y.abs();
+    }
+    Ok(yaw_sum / LIFT_OFF_WINDOW as f32)
+}
+
+/// Skidpad: one steady circle per SKIDPAD_STEERS value (constant steer and
+/// SKIDPAD_THROTTLE from SKIDPAD_SPEED, fresh world each), lateral g
+/// averaged over the last SKIDPAD_STEADY_TICKS. Returns the best circle's
+/// lateral g and |yaw rate|.
+fn skidpad(config: VehicleConfig) -> Result<(f32, f32), String> {
+    let mut best = (0.0, 0.0);
+    for steer in SKIDPAD_STEERS {
+        let circle = skidpad_circle(config, steer)?;
+        if circle.0 > best.0 {
+            best = circle;
+        }
+    }
+    Ok(best)
+}
+
+/// Centripetal acceleration: the component of dv/dt across the velocity
+/// (body slip and spin don't count, unlike speed x yaw rate).
+fn skidpad_circle(config: VehicleConfig, steer: f32) -> Result<(f32, f32), String> {
+    const ID: &str = "sweep";
+    let mut world = PhysicsWorld::new();
+    world
+        .spawn_vehicle_with_config(ID.to_string(), SPAWN, 0.0, config)
+        .map_err(|e| e.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; "))?;
+    let body = world.vehicles[ID].body;
+
+    for _ in 0..SETTLE_TICKS {
+        world.step(DT);
+    }
+    let rb = world.bodies.get_mut(body).ok_or("vehicle body missing")?;
+    let fwd = rb.position().rotation * vector![0.0, 0.0, 1.0];
+    rb.set_linvel(fwd * SKIDPAD_SPEED, true);
+
+    let mut lateral_g = Vec::new();
+    let mut yaw = Vec::new();
+    let mut last_v = world.bodies[body].linvel().xz();
+    for _ in 0..SKIDPAD_TICKS {
+        world.apply_player_input(ID, SKIDPAD_THROTTLE, steer, 0.0, 0.0, 0.0, 0.0, 0.0);
+        world.step(DT);
+        let rb = &world.bodies[body];
+        let v = rb.linvel().xz();
+        let a = (v - last_v) / DT;
+        let dir = v / v.norm().max(0.1);
+        lateral_g.push((a.x * dir.y - a.y * dir.x).abs() / 9.81);
+        yaw.push(rb.angvel().y.abs());
+        last_v = v;
+    }
+
+    let tail = |xs: &[f32]| xs[xs.len() - SKIDPAD_STEADY_TICKS..].iter().sum::<f32>() / SKIDPAD_STEADY_TICKS as f32;
+    Ok((tail(&lateral_g), tail(&yaw)))
+}
+
+// ==========================================================
+// Output
+// ==========================================================
+pub fn results_csv(def: &SweepDef, results: &[SweepResult]) -> String {
+    let metric_names = def.scenario.metrics();
+    let mut out = String::from("rank");
+    for name in def.params.keys() {
+        out += &format!(",{}", name);
+    }
+    out += &format!(",score_{}", def.objective);
+    for name in metric_names {
+        out += &format!(",{}", name);
+    }
+    out += ",error\n";
+
+    for (rank, r) in results.iter().enumerate() {
+        out += &format!("{}", rank + 1);
+        for (_, value) in &r.params {
+            out += &format!(",{}", value);
+        }
+        out += &format!(",{}", r.score);
+        for name in metric_names {
+            out += &r.metrics.get(name).map_or(",".to_string(), |m| format!(",{}", m));
+        }
+        out += &format!(",{}\n", r.error.as_deref().unwrap_or("").replace(',', ";"));
+    }
+    out
+}
+
+/// `--sweep <file>`: run, write the CSV, print the best TOP_N.
+pub fn run_sweep_file(path: &str) -> Result<(), String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let def = SweepDef::from_toml(&text)?;
+    let combos = def.combinations().len();
+    println!("🔬 Sweep: {} run(s) of {:?}, objective {}", combos, def.scenario, def.objective);
+
+    let results = run_sweep(&def);
+    let output = def.output.clone().unwrap_or_else(|| DEFAULT_OUTPUT.to_string());
+    std::fs::write(&output, results_csv(&def, &results)).map_err(|e| format!("{}: {}", output, e))?;
+    println!("💾 Wrote {} result(s) to {}", results.len(), output);
+
+    for (rank, r) in results.iter().take(TOP_N).enumerate() {
+        let params = r.params.iter().map(|(n, v)| format!("{}={}", n, v)).collect::<Vec<_>>().join(" ");
+        match &r.error {
+            None => println!("  #{}  {} = {:.4}   {}", rank + 1, def.objective, r.score, params),
+            Some(e) => println!("  #{}  invalid: {}   {}", rank + 1, e, params),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vehicle::Drivetrain;
+
+    /// The awd_tv preset in configs/vehicles.toml, vectoring on and off.
+    fn awd_tv(tv_enabled: bool) -> VehicleConfig {
+        let presets = crate::vehicle_presets::parse_presets(include_str!("../configs/vehicles.toml")).unwrap();
+        let config = presets["awd_tv"];
+        assert_eq!(config.drivetrain, Drivetrain::Awd);
+        VehicleConfig { tv_enabled, ..config }
+    }
+
+    #[test]
+    fn torque_vectoring_raises_skidpad_lateral_g() {
+        let (off, _) = skidpad(awd_tv(false)).unwrap();
+        let (on, _) = skidpad(awd_tv(true)).unwrap();
+        assert!(on > off + 0.005, "lateral g with vectoring {:.3}, without {:.3}", on, off);
+    }
+
+    #[test]
+    fn a_two_by_two_grid_sweep_is_deterministic() {
+        let def = SweepDef::from_toml(
+            r#"
+            scenario = "settle"
+            objective = "settle_time"
+            workers = 2
+
+            [params.mass]
+            values = [1100.0, 1400.0]
+
+            [params.arb_front]
+            min = 10000.0
+            max = 30000.0
+            steps = 2
+            "#,
+        )
+        .unwrap();
+        assert_eq!(def.combinations().len(), 4);
+
+        let first = run_sweep(&def);
+        assert_eq!(first.len(), 4);
+        assert!(first.iter().all(|r| r.error.is_none() && r.score.is_finite()));
+        assert!(first.windows(2).all(|w| w[0].score <= w[1].score), "ranked best first");
+        assert_eq!(results_csv(&def, &first), results_csv(&def, &run_sweep(&def)));
+    }
+
+    #[test]
+    fn lifting_mid_corner_at_the_limit_tightens_the_line() {
+        let lift_off = |engine_brake_coefficient: f32| {
+            let config = VehicleConfig { engine_brake_coefficient, ..crate::physics::GT86 };
+            let metrics = run_scenario(Scenario::LiftOff, config).unwrap();
+            (metrics["lift_yaw_gain"], metrics["held_yaw_rate"])
+        };
+        let (coast, held) = lift_off(0.0);
+        let (gt86, gt86_held) = lift_off(crate::physics::GT86.engine_brake_coefficient);
+        let (heavy, _) = lift_off(200.0);
+
+        // Golden values: 0.208 rad/s held; +0.0023 / +0.0061 / +0.0080 rad/s lifted
+        assert!((held - 0.208).abs() < 0.005, "held yaw rate {held}");
+        assert!((held - gt86_held).abs() < 0.002, "engine braking barely touches the held line: {held} vs {gt86_held}");
+        assert!(gt86 > 0.004, "lift-off yaw gain {gt86} rad/s");
+        assert!(coast < gt86 && gt86 < heavy, "gain by coefficient: {coast} < {gt86} < {heavy}");
+    }
+}
@@ -0,0 +1,163 @@
+// ==============================================================================
+// telemetry.rs — PER-VEHICLE TELEMETRY RING BUFFERS
+// ------------------------------------------------------------------------------
+// Opt-in per vehicle (PhysicsWorld::enable_telemetry; attaching an input trace
+// turns it on). After every step the vehicle's state is pushed to its ring;
+// the oldest frame is dropped once the ring is full.
+//
+// to_csv() writes one row per tick, oldest first:
+//   tick,time,x,y,z,speed,v_long,v_lat,yaw_rate,throttle,steer,brake,
+//   steer_angle,load_fl,load_fr,load_rl,load_rr,
+//   surface_fl,surface_fr,surface_rl,surface_rr   (material, empty = airborne)
+//
+// Two vehicles fed the same input trace produce rows for the same ticks, so
+// their CSVs line up row by row for A/B comparison.
+// ==============================================================================
+
+use std::collections::VecDeque;
+
+use rapier3d::prelude::*;
+
+use crate::surface::WheelSurface;
+use crate::vehicle::Vehicle;
+
+pub const TELEMETRY_CAPACITY: usize = 3600; // 60 s at 60 Hz
+
+const CSV_HEADER: &str = "tick,time,x,y,z,speed,v_long,v_lat,yaw_rate,throttle,steer,brake,steer_angle,load_fl,load_fr,load_rl,load_rr,surface_fl,surface_fr,surface_rl,surface_rr";
+
+#[derive(Debug, Clone)]
+pub struct TelemetryFrame {
+    pub tick: u64,
+    pub time: f32,               // simulated seconds since the first step
+    pub position: [f32; 3],
+    pub speed: f32,              // m/s
+    pub v_long: f32,             // m/s along the chassis +Z
+    pub v_lat: f32,              // m/s along the chassis +X
+    pub yaw_rate: f32,           // rad/s
+    pub inputs: [f32; 3],        // throttle, steer, brake as consumed
+    pub steer_angle: f32,        // rad
+    pub corner_loads: [f32; 4],  // N, FL/FR/RL/RR
+    pub surfaces: [Option<WheelSurface>; 4], // FL/FR/RL/RR, None = airborne
+}
+
+impl TelemetryFrame {
+    pub fn capture(tick: u64, time: f32, vehicle: &Vehicle, body: &RigidBody) -> Self {
+        let t = body.translation();
+        let rot = body.rotation();
+        let v = body.linvel();
+        Self {
+            tick,
+            time,
+            position: [t.x, t.y, t.z],
+            speed: v.norm(),
+            v_long: v.dot(&(rot * vector![0.0, 0.0, 1.0])),
+            v_lat: v.dot(&(rot * vector![1.0, 0.0, 0.0])),
+            yaw_rate: body.angvel().y,
+            inputs: [vehicle.throttle, vehicle.steer, vehicle.brake],
+            steer_angle: vehicle.steer_angle,
+            corner_loads: vehicle.corner_loads,
+            surfaces: vehicle.wheel_surfaces.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TelemetryRing {
+    frames: VecDeque<TelemetryFrame>,
+    capacity: usize,
+    elapsed: f32, // simulated seconds recorded so far
+}
+
+impl Default for TelemetryRing {
+    fn default() -> Self {
+        Self::with_capacity(TELEMETRY_CAPACITY)
+    }
+}
+
+impl TelemetryRing {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { frames: VecDeque::new(), capacity: capacity.max(1), elapsed: 0.0 }
+    }
+
+    /// Grow (never shrink) so a run of `ticks` fits without dropping frames.
+    pub fn reserve_ticks(&mut self, ticks: usize) {
+        self.capacity = self.capacity.max(ticks);
+    }
+
+    /// Append the state after a step of `dt` simulated seconds.
+    pub fn record(&mut self, tick: u64, dt: f32, vehicle: &Vehicle, body: &RigidBody) {
+        self.elapsed += dt;
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(TelemetryFrame::capture(tick, self.elapsed, vehicle, body));
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut out = format!("{}\n", CSV_HEADER);
+        for f in &self.frames {
+            let [x, y, z] = f.position;
+            let [throttle, steer, brake] = f.inputs;
+            let [fl, fr, rl, rr] = f.corner_loads;
+            let [sfl, sfr, srl, srr] = f.surfaces.each_ref().map(|s| s.as_ref().map_or("", |s| s.material.as_str()));
+            out += &format!(
+                "{},{:.4},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+                f.tick, f.time, x, y, z, f.speed, f.v_long, f.v_lat, f.yaw_rate,
+                throttle, steer, brake, f.steer_angle, fl, fr, rl, rr, sfl, sfr, srl, srr
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{DEFAULT_VEHICLE, PhysicsWorld};
+
+    const DT: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn a_full_ring_drops_the_oldest_frame() {
+        let mut phys = PhysicsWorld::new();
+        phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        phys.telemetry.insert("p".into(), TelemetryRing::with_capacity(3));
+        for _ in 0..5 {
+            phys.step(DT);
+        }
+        let ring = &phys.telemetry["p"];
+        assert_eq!(ring.len(), 3);
+        let ticks: Vec<u64> = ring.frames.iter().map(|f| f.tick).collect();
+        assert_eq!(ticks, [3, 4, 5]);
+        assert!((ring.frames[2].time - 5.0 * DT).abs() < 1e-6, "time keeps counting past dropped frames");
+
+        // Growing for a long trace keeps what is there
+        let mut ring = ring.clone();
+        ring.reserve_ticks(10);
+        ring.reserve_ticks(2); // never shrinks
+        assert_eq!((ring.capacity, ring.len()), (10, 3));
+    }
+
+    #[test]
+    fn csv_has_the_header_and_one_row_per_tick() {
+        let mut phys = PhysicsWorld::new();
+        phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, DEFAULT_VEHICLE).unwrap();
+        phys.enable_telemetry("p");
+        for _ in 0..30 {
+            phys.apply_player_input("p", 0.5, -0.2, 0.0, 0.0, 0.0, 0.0, 0.0);
+            phys.step(DT);
+        }
+        let csv = phys.telemetry["p"].to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 31);
+        assert_eq!(lines[0], CSV_HEADER);
+        let columns = CSV_HEADER.split(',').count();
+        assert!(lines[1..].iter().all(|row| row.split(',').count() == columns));
+        let last: Vec<&str> = lines[30].split(',').collect();
+        assert_eq!((last[0], last[1], last[9], last[10]), ("30", "0.5000", "0.5", "-0.2"));
+    }
+}
@@ -0,0 +1,215 @@
+// ==============================================================================
+// terrain.rs — HEIGHTFIELD TERRAIN (LOAD + CLIENT EXPORT)
+// ------------------------------------------------------------------------------
+// Replaces the flat ground box with a Rapier heightfield on the ground layer
+// (PhysicsWorld::load_heightfield). Suspension rays hit it like the box, with
+// the real surface normal on slopes.
+//
+//   --terrain <file>   startup, before warm-up; on error the flat ground stays
+//
+//   .json : {"nx": 129, "ny": 129, "width": 256.0, "depth": 256.0,
+//            "heights": [ ... nx * ny world-space y values ... ]}
+//   .pgm  : binary grayscale (P5, 8 or 16 bit); one vertex per pixel,
+//           AVENLAB_TERRAIN_CELL_M apart (default 1.0), black = y 0,
+//           white = AVENLAB_TERRAIN_HEIGHT_M (default 20.0)
+//
+// PNG is not decoded (no image crate in the build); convert it first, e.g.
+// `magick terrain.png terrain.pgm`.
+//
+// Grid layout (shared with the client): heights are row-major, row = z.
+// Vertex (ix, iz) sits at
+//
+//   x = -width / 2 + ix * width / (nx - 1)
+//   z = -depth / 2 + iz * depth / (ny - 1)
+//
+// Clients get it right after the welcome as one or more "terrain" messages,
+// TERRAIN_CHUNK_HEIGHTS values at most each, whole rows:
+//
+//   {"type":"terrain","nx":..,"ny":..,"width":..,"depth":..,
+//    "chunk":0,"chunks":3,"row":0,"rows":64,"heights":[..]}
+//
+// No terrain loaded → no terrain messages (flat ground, top at y 0.9).
+// ==============================================================================
+
+use serde::{Deserialize, Serialize};
+
+use crate::protocol::PROTOCOL_VERSION;
+
+const TERRAIN_CHUNK_HEIGHTS: usize = 16384;
+const MAX_TERRAIN_VERTICES: usize = 4096 * 4096;
+const DEFAULT_CELL_M: f32 = 1.0;
+const DEFAULT_HEIGHT_M: f32 = 20.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Heightfield {
+    pub nx: usize,        // vertices along x
+    pub ny: usize,        // vertices along z (rows)
+    pub width: f32,       // extent along x (m), centered on the origin
+    pub depth: f32,       // extent along z (m), centered on the origin
+    pub heights: Vec<f32>, // nx * ny, row-major (row = z)
+}
+
+// Typed rather than json!, so heights serialize as f32 ("0.1", not "0.10000000149011612")
+#[derive(Serialize)]
+struct TerrainChunk<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    v: u32, // protocol.rs PROTOCOL_VERSION
+    nx: usize,
+    ny: usize,
+    width: f32,
+    depth: f32,
+    chunk: usize,
+    chunks: usize,
+    row: usize,
+    rows: usize,
+    heights: &'a [f32],
+}
+
+impl Heightfield {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.nx < 2 || self.ny < 2 {
+            return Err(format!("grid must be at least 2x2 (got {}x{})", self.nx, self.ny));
+        }
+        if self.nx.saturating_mul(self.ny) > MAX_TERRAIN_VERTICES {
+            return Err(format!("grid {}x{} exceeds {} vertices", self.nx, self.ny, MAX_TERRAIN_VERTICES));
+        }
+        if self.heights.len() != self.nx * self.ny {
+            return Err(format!("expected {} heights ({}x{}), got {}", self.nx * self.ny, self.nx, self.ny, self.heights.len()));
+        }
+        if !(self.width.is_finite() && self.width > 0.0 && self.depth.is_finite() && self.depth > 0.0) {
+            return Err(format!("width / depth must be positive (got {} x {})", self.width, self.depth));
+        }
+        if let Some(i) = self.heights.iter().position(|h| !h.is_finite()) {
+            return Err(format!("height {} is not finite", i));
+        }
+        Ok(())
+    }
+
+    /// Highest vertex of the grid cell under (x, z); None outside the terrain.
+    /// Spawns sit above this, so they never start inside the surface.
+    pub fn cell_top(&self, x: f32, z: f32) -> Option<f32> {
+        let fx = (x / self.width + 0.5) * (self.nx - 1) as f32;
+        let fz = (z / self.depth + 0.5) * (self.ny - 1) as f32;
+        if !(fx >= 0.0 && fz >= 0.0 && fx <= (self.nx - 1) as f32 && fz <= (self.ny - 1) as f32) {
+            return None;
+        }
+        let ix = (fx as usize).min(self.nx - 2);
+        let iz = (fz as usize).min(self.ny - 2);
+        let h = |ix: usize, iz: usize| self.heights[iz * self.nx + ix];
+        Some(h(ix, iz).max(h(ix + 1, iz)).max(h(ix, iz + 1)).max(h(ix + 1, iz + 1)))
+    }
+
+    /// The "terrain" messages sent to each client after the welcome.
+    pub fn client_messages(&self) -> Vec<String> {
+        let rows_per_chunk = (TERRAIN_CHUNK_HEIGHTS / self.nx).max(1);
+        let chunks = self.ny.div_ceil(rows_per_chunk);
+        (0..chunks)
+            .map(|chunk| {
+                let row = chunk * rows_per_chunk;
+                let rows = rows_per_chunk.min(self.ny - row);
+                let message = TerrainChunk {
+                    kind: "terrain",
+                    v: PROTOCOL_VERSION,
+                    nx: self.nx,
+                    ny: self.ny,
+                    width: self.width,
+                    depth: self.depth,
+                    chunk,
+                    chunks,
+                    row,
+                    rows,
+                    heights: &self.heights[row * self.nx..(row + rows) * self.nx],
+                };
+                serde_json::to_string(&message).unwrap_or_default()
+            })
+            .collect()
+    }
+}
+
+/// Heightfield from a .json or .pgm file (by extension).
+pub fn load_file(path: &str) -> Result<Heightfield, String> {
+    let lower = path.to_ascii_lowercase();
+    let field = if lower.ends_with(".json") {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        serde_json::from_str::<Heightfield>(&text).map_err(|e| format!("{}: {}", path, e))?
+    } else if lower.ends_with(".pgm") {
+        let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+        parse_pgm(&bytes, env_f32("AVENLAB_TERRAIN_CELL_M", DEFAULT_CELL_M), env_f32("AVENLAB_TERRAIN_HEIGHT_M", DEFAULT_HEIGHT_M))
+            .map_err(|e| format!("{}: {}", path, e))?
+    } else if lower.ends_with(".png") {
+        return Err(format!("{}: PNG is not supported, convert it to a binary PGM (P5)", path));
+    } else {
+        return Err(format!("{}: expected a .json or .pgm terrain file", path));
+    };
+    field.validate().map_err(|e| format!("{}: {}", path, e))?;
+    Ok(field)
+}
+
+/// Binary PGM (P5): header "P5 <w> <h> <maxval>" (whitespace / # comments),
+/// then w*h samples, 1 byte each (maxval < 256) or 2 bytes big-endian.
+fn parse_pgm(bytes: &[u8], cell_m: f32, height_m: f32) -> Result<Heightfield, String> {
+    let mut pos = 0;
+    let mut fields = [0usize; 4];
+    for (n, field) in fields.iter_mut().enumerate() {
+        // Skip whitespace and comments
+        loop {
+            match bytes.get(pos) {
+                Some(b) if b.is_ascii_whitespace() => pos += 1,
+                Some(b'#') => {
+                    while bytes.get(pos).is_some_and(|b| *b != b'\n') {
+                        pos += 1;
+                    }
+                }
+                _ => break,
+            }
+        }
+        let start = pos;
+        while bytes.get(pos).is_some_and(|b| !b.is_ascii_whitespace()) {
+            pos += 1;
+        }
+        let token = std::str::from_utf8(&bytes[start..pos]).map_err(|_| "bad PGM header".to_string())?;
+        *field = match n {
+            0 if token == "P5" => 0,
+            0 => return Err(format!("not a binary PGM (magic '{}', expected P5)", token)),
+            _ => token.parse().map_err(|_| format!("bad PGM header value '{}'", token))?,
+        };
+    }
+    let [_, nx, ny, maxval] = fields;
+    if maxval == 0 || maxval > u16::MAX as usize {
+        return Err(format!("bad PGM maxval {}", maxval));
+    }
+    pos += 1; // single whitespace byte before the samples
+
+    let sample_len = if maxval < 256 { 1 } else { 2 };
+    let count = nx.saturating_mul(ny);
+    let data = bytes.get(pos..).unwrap_or_default();
+    if count > MAX_TERRAIN_VERTICES || data.len() < count * sample_len {
+        return Err(format!("PGM data too short or too large for {}x{}", nx, ny));
+    }
+    let heights = data
+        .chunks_exact(sample_len)
+        .take(count)
+        .map(|s| {
+            let v = if sample_len == 1 { s[0] as f32 } else { u16::from_be_bytes([s[0], s[1]]) as f32 };
+            v / maxval as f32 * height_m
+        })
+        .collect();
+
+    Ok(Heightfield {
+        nx,
+        ny,
+        width: nx.saturating_sub(1) as f32 * cell_m,
+        depth: ny.saturating_sub(1) as f32 * cell_m,
+        heights,
+    })
+}
+
+fn env_f32(name: &str, default: f32) -> f32 {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse::<f32>().ok())
+        .filter(|v| v.is_finite() && *v > 0.0)
+        .unwrap_or(default)
+}
@@ -0,0 +1,172 @@
+// ==============================================================================
+// tick_metrics.rs — PER-TICK PHASE TIMINGS + ROLLING 1 s SUMMARY
+// ------------------------------------------------------------------------------
+// run_tick (main.rs) takes an Instant at each phase boundary and hands the
+// durations to TickMetrics::record, with the world's size at that tick:
+//
+//   inputs     expire held cars, input mailbox, traces, input decay
+//   step       PhysicsWorld::step (suspension = its apply_suspension share)
+//   broadcast  snapshots, haptics, collision events, debug overlay
+//   total      the whole run_tick
+//   bodies / contacts / clients   counts after the step
+//
+// Everything is plain fields: no allocation per tick. A tick whose total goes
+// over the budget (one tick period, 16.7 ms at 60 Hz) is counted as slow; the
+// first slow tick of each window is logged ("🐌"), the rest only counted, so
+// a host that can't keep up logs once a second instead of every tick.
+//
+// Every SUMMARY_WINDOW the window closes into a TickSummary (avg / max per
+// phase, slow tick count, latest counts), which is:
+//   - sent as {"type":"server_stats",..} to admins subscribed with
+//     {"type":"admin","cmd":"subscribe_stats","token":..}
+//   - kept as the latest summary for the periodic "📊 Stats" log line
+//
+// Env:
+// - AVENLAB_TICK_BUDGET_MS   slow tick threshold (default: the tick period)
+// ==============================================================================
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+const SUMMARY_WINDOW: Duration = Duration::from_secs(1);
+
+/// One tick's measurements.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TickSample {
+    pub inputs: Duration,
+    pub step: Duration,
+    pub suspension: Duration,
+    pub broadcast: Duration,
+    pub total: Duration,
+    pub bodies: usize,
+    pub contacts: usize,
+    pub clients: usize,
+}
+
+/// avg / max of one phase over a window, in ms.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct PhaseStats {
+    pub avg_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct TickSummary {
+    pub ticks: u32,
+    pub slow_ticks: u32,
+    pub budget_ms: f64,
+    pub inputs: PhaseStats,
+    pub step: PhaseStats,
+    pub suspension: PhaseStats,
+    pub broadcast: PhaseStats,
+    pub total: PhaseStats,
+    pub bodies: usize,
+    pub contacts: usize,
+    pub clients: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct PhaseAccum {
+    sum: Duration,
+    max: Duration,
+}
+
+impl PhaseAccum {
+    fn add(&mut self, d: Duration) {
+        self.sum += d;
+        self.max = self.max.max(d);
+    }
+
+    fn stats(&self, ticks: u32) -> PhaseStats {
+        let ms = |d: Duration| (d.as_secs_f64() * 1e5).round() / 100.0; // 0.01 ms
+        PhaseStats { avg_ms: ms(self.sum / ticks.max(1)), max_ms: ms(self.max) }
+    }
+}
+
+/// Accumulators of the open window
+#[derive(Debug, Default)]
+struct Window {
+    start: Option<Instant>,
+    ticks: u32,
+    slow_ticks: u32,
+    inputs: PhaseAccum,
+    step: PhaseAccum,
+    suspension: PhaseAccum,
+    broadcast: PhaseAccum,
+    total: PhaseAccum,
+    last: TickSample,
+}
+
+#[derive(Debug)]
+pub struct TickMetrics {
+    budget: Duration,
+    window: Window,
+    pub latest: Option<TickSummary>, // last closed window
+}
+
+impl TickMetrics {
+    pub fn new(period: Duration) -> Self {
+        let budget = std::env::var("AVENLAB_TICK_BUDGET_MS")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|ms| ms.is_finite() && *ms > 0.0)
+            .map_or(period, |ms| Duration::from_secs_f64(ms / 1000.0));
+        Self { budget, window: Window::default(), latest: None }
+    }
+
+    /// Add tick `tick`'s sample (finished at `now`). Returns the summary when
+    /// this tick closes a window.
+    pub fn record(&mut self, tick: u64, sample: TickSample, now: Instant) -> Option<TickSummary> {
+        let w = &mut self.window;
+        let start = *w.start.get_or_insert(now);
+        w.ticks += 1;
+        w.inputs.add(sample.inputs);
+        w.step.add(sample.step);
+        w.suspension.add(sample.suspension);
+        w.broadcast.add(sample.broadcast);
+        w.total.add(sample.total);
+        w.last = sample;
+
+        if sample.total > self.budget {
+            w.slow_ticks += 1;
+            if w.slow_ticks == 1 {
+                eprintln!(
+                    "🐌 Slow tick {}: {:.2} ms (budget {:.2}; inputs {:.2}, step {:.2}, broadcast {:.2}; {} bodies, {} contacts, {} clients)",
+                    tick,
+                    sample.total.as_secs_f64() * 1000.0,
+                    self.budget.as_secs_f64() * 1000.0,
+                    sample.inputs.as_secs_f64() * 1000.0,
+                    sample.step.as_secs_f64() * 1000.0,
+                    sample.broadcast.as_secs_f64() * 1000.0,
+                    sample.bodies,
+                    sample.contacts,
+                    sample.clients,
+                );
+            }
+        }
+
+        if now.duration_since(start) < SUMMARY_WINDOW {
+            return None;
+        }
+        let summary = TickSummary {
+            ticks: w.ticks,
+            slow_ticks: w.slow_ticks,
+            budget_ms: (self.budget.as_secs_f64() * 1e5).round() / 100.0,
+            inputs: w.inputs.stats(w.ticks),
+            step: w.step.stats(w.ticks),
+            suspension: w.suspension.stats(w.ticks),
+            broadcast: w.broadcast.stats(w.ticks),
+            total: w.total.stats(w.ticks),
+            bodies: w.last.bodies,
+            contacts: w.last.contacts,
+            clients: w.last.clients,
+        };
+        if w.slow_ticks > 1 {
+            eprintln!("🐌 {} slow ticks of {} in the last window (max {:.2} ms)", w.slow_ticks, w.ticks, summary.total.max_ms);
+        }
+        self.window = Window { start: Some(now), ..Window::default() };
+        self.latest = Some(summary);
+        Some(summary)
+    }
+}
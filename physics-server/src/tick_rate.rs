@@ -0,0 +1,285 @@
+// ==============================================================================
+// tick_rate.rs — SIMULATION TICK RATE (AVENLAB_TICK_HZ)
+// ------------------------------------------------------------------------------
+// The main loop runs one tick every 1/hz real seconds and steps the world by
+// dt = 1/hz simulated seconds, so simulated time keeps pace with real time at
+// any rate. A quiet lobby at 20 Hz costs a third of the CPU of 60 Hz.
+//
+//   AVENLAB_TICK_HZ=<10..=120>   default 60 (--tick-hz, server_config.rs)
+//
+// Everything downstream takes dt from here: PhysicsWorld::step (which
+// substeps the suspension / tire solve above SUSPENSION_MAX_DT, see
+// physics.rs), input decay, plugins, lap timing, snapshot plausibility and
+// interest keepalives. Clients get `tick_hz` in the welcome message and in
+// every snapshot to map ticks to time; recordings store it so --verify
+// replays at the recorded rate.
+//
+// Rooms can run slower than the server rate (AVENLAB_ROOM_TICK_HZ="1:20,2:30",
+// spawn.rs RoomSettings). Rooms still share one PhysicsWorld, so a room's
+// rate is a whole divisor of the server rate: its vehicles step every n-th
+// tick by n x dt (PhysicsWorld::set_tick_divisor) and its clients get a
+// snapshot on those ticks only, with `room_tick_hz` next to `tick_hz`.
+// Ticks keep counting at the server rate everywhere.
+//
+// TickClock keeps the main loop on real time. Each wake-up adds the real time
+// since the last one to an accumulator and runs one tick per whole dt in it,
+// carrying the remainder, so a slow tick (snapshot serialization, a lock held
+// too long) is made up with catch-up ticks instead of slowing the simulation:
+//
+//   cap    : at most AVENLAB_MAX_STEPS_PER_FRAME ticks per wake-up (default
+//            5); time beyond that is dropped and logged (🐌, at most once per
+//            BEHIND_LOG_INTERVAL), so an overloaded host degrades to slow
+//            motion instead of a spiral of ever longer catch-up frames
+//   locks  : each catch-up tick takes the physics / game locks anew
+//            (main.rs run_catch_up), so connections aren't locked out for
+//            the whole frame
+//   drift  : wall time since the loop started minus ticks run x dt, i.e. how
+//            far the simulation is behind real time (dropped time adds up
+//            here). Every snapshot carries it as "drift_ms".
+// ==============================================================================
+
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_TICK_HZ: u32 = 60;
+const MIN_TICK_HZ: u32 = 10;
+const MAX_TICK_HZ: u32 = 120;
+const DEFAULT_MAX_STEPS_PER_FRAME: u32 = 5;
+const BEHIND_LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TickRate {
+    hz: u32,
+}
+
+impl Default for TickRate {
+    fn default() -> Self {
+        Self { hz: DEFAULT_TICK_HZ }
+    }
+}
+
+impl TickRate {
+    /// Clamped to MIN_TICK_HZ..=MAX_TICK_HZ.
+    pub fn new(hz: u32) -> Self {
+        Self { hz: hz.clamp(MIN_TICK_HZ, MAX_TICK_HZ) }
+    }
+
+    /// Like `new`, but out of range is an error instead of a clamp (--tick-hz).
+    pub fn try_new(hz: u32) -> Result<Self, String> {
+        if !(MIN_TICK_HZ..=MAX_TICK_HZ).contains(&hz) {
+            return Err(format!("tick rate {} Hz out of range ({}..={})", hz, MIN_TICK_HZ, MAX_TICK_HZ));
+        }
+        Ok(Self { hz })
+    }
+
+    /// AVENLAB_TICK_HZ if set (and a valid integer), DEFAULT_TICK_HZ otherwise.
+    pub fn from_env() -> Self {
+        let Some(hz) = std::env::var("AVENLAB_TICK_HZ").ok().and_then(|v| v.trim().parse::<u32>().ok()) else {
+            return Self::default();
+        };
+        let rate = Self::new(hz);
+        if rate.hz != hz {
+            eprintln!("⚠️ AVENLAB_TICK_HZ={} out of range, using {} Hz", hz, rate.hz);
+        }
+        println!("⏱  Tick rate {} Hz (dt = {:.4} s)", rate.hz, rate.dt());
+        rate
+    }
+
+    pub fn hz(&self) -> u32 {
+        self.hz
+    }
+
+    /// Simulated seconds per tick (before bullet-time scaling).
+    pub fn dt(&self) -> f32 {
+        1.0 / self.hz as f32
+    }
+
+    /// Real time between ticks.
+    pub fn period(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.hz as f64)
+    }
+
+    /// Ticks per step of a room asking for `room_hz`: the nearest whole
+    /// divisor of this rate (at least 1, so a room never runs faster).
+    pub fn room_divisor(&self, room_hz: u32) -> u32 {
+        (self.hz as f32 / room_hz.max(1) as f32).round().max(1.0) as u32
+    }
+
+    /// Whole ticks covering `secs` (at least one).
+    pub fn ticks(&self, secs: f32) -> u64 {
+        (secs.max(0.0) * self.hz as f32).round().max(1.0) as u64
+    }
+}
+
+// ==========================================================
+// Accumulator (main loop)
+// ==========================================================
+/// What one wake-up of the main loop should do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame {
+    pub steps: u32,        // ticks to run now
+    pub dropped: Duration, // real time beyond the cap, not simulated
+}
+
+#[derive(Debug)]
+pub struct TickClock {
+    period: Duration,
+    max_steps_per_frame: u32,
+    accumulator: Duration,
+    started: Option<Instant>,
+    last: Option<Instant>,
+    ticks: u64,                        // ticks handed out since started
+    dropped: Duration,                 // total real time not simulated
+    behind_logged: Option<Instant>,    // last 🐌 log
+    behind_since: Duration,            // dropped since then
+}
+
+impl TickClock {
+    pub fn new(rate: TickRate, max_steps_per_frame: u32) -> Self {
+        Self {
+            period: rate.period(),
+            max_steps_per_frame: max_steps_per_frame.max(1),
+            accumulator: Duration::ZERO,
+            started: None,
+            last: None,
+            ticks: 0,
+            dropped: Duration::ZERO,
+            behind_logged: None,
+            behind_since: Duration::ZERO,
+        }
+    }
+
+    /// AVENLAB_MAX_STEPS_PER_FRAME if set (and a positive integer).
+    pub fn from_env(rate: TickRate) -> Self {
+        let max = std::env::var("AVENLAB_MAX_STEPS_PER_FRAME").ok().and_then(|v| v.trim().parse::<u32>().ok()).filter(|n| *n > 0);
+        Self::new(rate, max.unwrap_or(DEFAULT_MAX_STEPS_PER_FRAME))
+    }
+
+    /// Real time since the last call → ticks to run now. The first call
+    /// starts the clock and runs one tick.
+    pub fn advance(&mut self, now: Instant) -> Frame {
+        let elapsed = match self.last {
+            Some(last) => now.saturating_duration_since(last),
+            None => {
+                self.started = Some(now.checked_sub(self.period).unwrap_or(now)); // the first tick covers the period before it
+                self.period
+            }
+        };
+        self.last = Some(now);
+        self.accumulator += elapsed;
+
+        let due = (self.accumulator.as_nanos() / self.period.as_nanos().max(1)) as u32;
+        let steps = due.min(self.max_steps_per_frame);
+        self.accumulator -= self.period * steps;
+        let mut dropped = Duration::ZERO;
+        if due > steps {
+            // Keep the sub-tick remainder, drop the whole ticks over the cap
+            dropped = self.period * (due - steps);
+            self.accumulator -= dropped;
+            self.dropped += dropped;
+            self.log_behind(now, dropped);
+        }
+        self.ticks += steps as u64;
+        Frame { steps, dropped }
+    }
+
+    /// Seconds the simulation is behind real time at `now`, counting the
+    /// ticks handed out so far as run: the carried remainder plus all
+    /// dropped time.
+    pub fn drift_s(&self, now: Instant) -> f32 {
+        let Some(started) = self.started else { return 0.0 };
+        let wall = now.saturating_duration_since(started).as_secs_f64();
+        let simulated = self.ticks as f64 * self.period.as_secs_f64();
+        (wall - simulated) as f32
+    }
+
+    fn log_behind(&mut self, now: Instant, dropped: Duration) {
+        self.behind_since += dropped;
+        if self.behind_logged.is_some_and(|at| now.saturating_duration_since(at) < BEHIND_LOG_INTERVAL) {
+            return;
+        }
+        eprintln!(
+            "🐌 Falling behind real time: dropped {:.0} ms of simulation (cap {} ticks / frame, {:.0} ms total)",
+            self.behind_since.as_secs_f64() * 1000.0,
+            self.max_steps_per_frame,
+            self.dropped.as_secs_f64() * 1000.0,
+        );
+        self.behind_logged = Some(now);
+        self.behind_since = Duration::ZERO;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    /// 50 Hz (20 ms ticks, exact in nanoseconds), at most `cap` ticks per frame.
+    fn clock_50hz(cap: u32) -> (TickClock, Instant) {
+        (TickClock::new(TickRate::try_new(50).unwrap(), cap), Instant::now())
+    }
+
+    fn frame(steps: u32, dropped_ms: u64) -> Frame {
+        Frame { steps, dropped: MS * dropped_ms as u32 }
+    }
+
+    #[test]
+    fn the_first_call_runs_one_tick_and_seeds_the_clock() {
+        let (mut clock, t0) = clock_50hz(5);
+        assert_eq!(clock.drift_s(t0), 0.0, "not started");
+        assert_eq!(clock.advance(t0), frame(1, 0));
+        // That tick covered the period before t0: on time, nothing carried
+        assert_eq!(clock.drift_s(t0), 0.0);
+        assert_eq!(clock.advance(t0 + 19 * MS), frame(0, 0));
+        assert_eq!(clock.advance(t0 + 20 * MS), frame(1, 0));
+    }
+
+    #[test]
+    fn the_sub_tick_remainder_carries_over() {
+        let (mut clock, t0) = clock_50hz(5);
+        clock.advance(t0);
+        assert_eq!(clock.advance(t0 + 30 * MS), frame(1, 0)); // 10 ms carried
+        assert_eq!(clock.advance(t0 + 41 * MS), frame(1, 0)); // 10 + 11 = 21
+        assert_eq!(clock.advance(t0 + 99 * MS), frame(2, 0)); // 1 + 58 = 59
+        assert!((clock.drift_s(t0 + 99 * MS) - 0.019).abs() < 1e-6, "{}", clock.drift_s(t0 + 99 * MS));
+        // A clock going backwards adds nothing
+        assert_eq!(clock.advance(t0 + 50 * MS), frame(0, 0));
+    }
+
+    #[test]
+    fn steps_per_frame_are_capped() {
+        let (mut clock, t0) = clock_50hz(3);
+        clock.advance(t0);
+        assert_eq!(clock.advance(t0 + 60 * MS), frame(3, 0), "exactly the cap");
+        let (mut clock, t0) = clock_50hz(1);
+        clock.advance(t0);
+        assert_eq!(clock.advance(t0 + 40 * MS).steps, 1);
+    }
+
+    #[test]
+    fn whole_ticks_over_the_cap_are_dropped_keeping_the_remainder() {
+        let (mut clock, t0) = clock_50hz(3);
+        clock.advance(t0);
+        // 115 ms = 5 ticks + 15 ms: 3 run, 2 dropped, 15 ms carried
+        assert_eq!(clock.advance(t0 + 115 * MS), frame(3, 40));
+        assert_eq!(clock.advance(t0 + 120 * MS), frame(1, 0), "15 + 5 ms");
+        assert_eq!(clock.accumulator, Duration::ZERO);
+        assert_eq!(clock.dropped, 40 * MS);
+    }
+
+    #[test]
+    fn drift_counts_the_dropped_time() {
+        let (mut clock, t0) = clock_50hz(2);
+        clock.advance(t0);
+        assert_eq!(clock.advance(t0 + 200 * MS), frame(2, 160));
+        // 220 ms of wall time since the seeded start, 3 ticks run (60 ms)
+        assert!((clock.drift_s(t0 + 200 * MS) - 0.160).abs() < 1e-6);
+        // Keeping up afterwards doesn't win the dropped time back
+        for i in 1..=10 {
+            assert_eq!(clock.advance(t0 + (200 + 20 * i) * MS).steps, 1);
+        }
+        assert!((clock.drift_s(t0 + 400 * MS) - 0.160).abs() < 1e-6);
+        // Between wake-ups drift grows with the time not yet run
+        assert!((clock.drift_s(t0 + 410 * MS) - 0.170).abs() < 1e-6);
+    }
+}
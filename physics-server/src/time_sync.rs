@@ -0,0 +1,123 @@
+// ==============================================================================
+// time_sync.rs — SERVER CLOCK + TIME SYNC HANDSHAKE
+// ------------------------------------------------------------------------------
+// Clients interpolate between snapshots on their own clock, so they need the
+// offset between it and the server's. The server clock is monotonic
+// milliseconds since the process started (server_time_ms); every snapshot is
+// stamped with it when built ("server_time_ms"), and a client can ask for it
+// directly:
+//
+//   → {"type":"time_sync","client_time":81234.5}
+//   ← {"type":"time_sync","client_time":81234.5,"server_time_ms":4521.250,"tick":271}
+//
+// NTP-style estimate on the client, with t1 = its clock when the reply lands:
+//
+//   rtt    = t1 - client_time
+//   offset = server_time_ms + rtt / 2 - t1      (server ≈ local + offset)
+//
+// Take the sample with the lowest rtt out of a few (5-10 at connect, then one
+// every few seconds).
+//
+// The reply is answered in the read loop without taking a lock (the tick
+// comes from HealthState's atomic) or building a JSON tree: one formatted
+// string of numbers, never more than a couple of times the size of the
+// request. Each connection gets its own small bucket of replies
+// (TIME_SYNC_RATE per second, TIME_SYNC_BURST at once) on top of the frame
+// rate limit; requests past it get no reply at all, so the handshake can't be
+// used to amplify traffic.
+// ==============================================================================
+
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use serde_json::json;
+
+use crate::protocol;
+use crate::rate_limit::RateLimiter;
+
+const TIME_SYNC_RATE: f32 = 4.0;   // replies per second
+const TIME_SYNC_BURST: f32 = 10.0; // connect-time burst of samples
+
+static SERVER_EPOCH: OnceLock<Instant> = OnceLock::new();
+
+/// Pin the server clock's zero (main.rs, at startup); server_time_ms starts
+/// it on first use otherwise.
+pub fn start_clock() {
+    SERVER_EPOCH.get_or_init(Instant::now);
+}
+
+/// Monotonic milliseconds since the server started, µs resolution.
+pub fn server_time_ms() -> f64 {
+    let ms = SERVER_EPOCH.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0;
+    (ms * 1000.0).round() / 1000.0
+}
+
+/// Per-connection reply budget (never disconnects: excess is just ignored).
+pub fn reply_limiter() -> RateLimiter {
+    RateLimiter::new(TIME_SYNC_RATE, TIME_SYNC_BURST, u32::MAX)
+}
+
+/// The "time_sync" reply for `client_time` at game tick `tick`. The echo is
+/// written in shortest form (serde_json's float formatting), so a request
+/// like 1e300 doesn't come back as 300 digits.
+pub fn reply(client_time: f64, tick: u64) -> Option<String> {
+    let client_time = serde_json::Number::from_f64(client_time)?;
+    let server_time_ms = (server_time_ms() * 1000.0).round() / 1000.0;
+    Some(protocol::event("time_sync", json!({
+        "client_time": client_time,
+        "server_time_ms": server_time_ms,
+        "tick": tick,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::rate_limit::RateVerdict;
+
+    fn parsed(reply: &str) -> serde_json::Value {
+        serde_json::from_str(reply).unwrap()
+    }
+
+    #[test]
+    fn the_reply_echoes_client_time_with_the_server_clock_and_tick() {
+        let before = server_time_ms();
+        let reply = parsed(&reply(81234.5, 271).unwrap());
+        assert_eq!(reply["type"], "time_sync");
+        assert_eq!(reply["client_time"], 81234.5);
+        assert_eq!(reply["tick"], 271);
+        let server = reply["server_time_ms"].as_f64().unwrap();
+        assert!(server >= before && server <= server_time_ms());
+    }
+
+    #[test]
+    fn huge_times_come_back_in_shortest_form() {
+        let reply = reply(1e300, 0).unwrap();
+        assert!(reply.contains(r#""client_time":1e300"#), "{}", reply);
+        assert!(reply.len() < 120, "no 300-digit echo: {} bytes", reply.len());
+        assert_eq!(parsed(&reply)["client_time"], 1e300);
+    }
+
+    #[test]
+    fn non_finite_times_get_no_reply() {
+        for t in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert_eq!(reply(t, 1), None);
+        }
+    }
+
+    #[test]
+    fn the_limiter_stops_replying_after_the_burst() {
+        let mut limiter = reply_limiter();
+        let t0 = Instant::now();
+        let allowed = (0..100).filter(|_| limiter.check(t0) == RateVerdict::Allow).count();
+        assert_eq!(allowed, TIME_SYNC_BURST as usize);
+        // Never a disconnect, just no reply
+        assert_eq!(limiter.check(t0), RateVerdict::Drop);
+        // Refills at TIME_SYNC_RATE
+        let one_reply_later = t0 + Duration::from_secs_f32(1.0 / TIME_SYNC_RATE);
+        assert_eq!(limiter.check(one_reply_later), RateVerdict::Allow);
+        assert_eq!(limiter.check(one_reply_later), RateVerdict::Drop);
+    }
+}
@@ -0,0 +1,478 @@
+// ==============================================================================
+// timing.rs — LAP / SECTOR TIMING AND LIVE DELTA-TO-BEST
+// ------------------------------------------------------------------------------
+// Track file (AVENLAB_TRACK_FILE, JSON): an ordered checkpoint chain. Index 0
+// is the start/finish line; checkpoints flagged "sector" end a sector (the
+// finish always does).
+//
+//   {
+//     "continuous_delta": true,
+//     "checkpoints": [
+//       { "position": [0, 1, 0],     "radius": 8 },
+//       { "position": [120, 1, 0],   "radius": 8 },
+//       { "position": [120, 1, 90],  "radius": 8, "sector": true },
+//       { "position": [0, 1, 90],    "radius": 8 }
+//     ]
+//   }
+//
+// A checkpoint counts when the car is within `radius` of it on the ground
+// plane, and only the next one in the chain does (no shortcuts). The first
+// start/finish crossing starts lap 1.
+//
+// Per player: sector splits of the current lap, personal best per sector and
+// best lap (with its checkpoint splits). Session bests are across players.
+//
+//   sector_completed : {"type":"sector_completed","player_id":..,"lap":2,
+//                       "sector":1,"time":23.41,"class":"green","delta":-0.31}
+//                      purple = session best, green = personal best, yellow = slower
+//   snapshot         : players[i].timing = {lap, sector, delta, last_sector}
+//
+// delta = lap time so far minus the best lap's time at the same point: set at
+// each checkpoint, or every tick with continuous_delta (the best lap's splits
+// interpolated by progress along the segment to the next checkpoint).
+// Times are simulated seconds (bullet time slows the clock too), rounded to
+// the millisecond so equal runs compare equal.
+//
+// Warm restart (session.rs) keeps each player's bests (TimingBests); a lap in
+// progress is dropped and starts over at the next start/finish crossing.
+// ==============================================================================
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::protocol;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Checkpoint {
+    pub position: [f32; 3],
+    pub radius: f32,
+    #[serde(default)]
+    pub sector: bool, // ends a sector (checkpoint 0, the finish, always does)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Track {
+    pub checkpoints: Vec<Checkpoint>,
+    #[serde(default)]
+    pub continuous_delta: bool,
+}
+
+impl Track {
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let track: Track = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        if track.checkpoints.len() < 2 {
+            return Err("a track needs at least 2 checkpoints".to_string());
+        }
+        if track.checkpoints.iter().any(|c| !c.radius.is_finite() || c.radius <= 0.0 || c.position.iter().any(|p| !p.is_finite())) {
+            return Err("checkpoint radius must be > 0 and positions finite".to_string());
+        }
+        Ok(track)
+    }
+
+    fn is_sector_end(&self, index: usize) -> bool {
+        index == 0 || self.checkpoints[index].sector
+    }
+
+    pub fn sector_count(&self) -> usize {
+        1 + self.checkpoints[1..].iter().filter(|c| c.sector).count()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SectorClass {
+    Purple, // session best
+    Green,  // personal best
+    Yellow, // slower
+}
+
+impl SectorClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SectorClass::Purple => "purple",
+            SectorClass::Green => "green",
+            SectorClass::Yellow => "yellow",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SectorCompleted {
+    pub player_id: String,
+    pub lap: u32,
+    pub sector: usize,
+    pub time: f32,
+    pub class: SectorClass,
+    pub delta: Option<f32>,
+    pub lap_time: Option<f32>, // set on the sector that finishes the lap
+}
+
+impl SectorCompleted {
+    pub fn to_message(&self) -> String {
+        protocol::event("sector_completed", json!({
+            "player_id": self.player_id,
+            "lap": self.lap,
+            "sector": self.sector,
+            "time": self.time,
+            "class": self.class.as_str(),
+            "delta": self.delta,
+            "lap_time": self.lap_time,
+        }))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct PlayerTiming {
+    lap: u32,                       // current lap (0 = not started)
+    next: usize,                    // next checkpoint expected
+    lap_start: f64,
+    sector: usize,                  // current sector of this lap
+    sector_start: f64,
+    splits: Vec<f32>,               // this lap: time at checkpoints 1.., relative to lap start
+    best_lap: Option<f32>,
+    best_splits: Vec<f32>,          // best lap: [0, cp1, cp2, .., lap time]
+    best_sectors: Vec<Option<f32>>,
+    last_sector: Option<(usize, f32)>,
+    delta: Option<f32>,
+}
+
+/// What survives a warm restart per player: bests, not the lap in progress.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimingBests {
+    pub best_lap: Option<f32>,
+    pub best_splits: Vec<f32>,
+    pub best_sectors: Vec<Option<f32>>,
+}
+
+pub struct TrackTiming {
+    pub track: Track,
+    clock: f64,                          // simulated seconds since the server started timing
+    players: HashMap<String, PlayerTiming>,
+    session_best_sectors: Vec<Option<f32>>,
+    session_best_lap: Option<f32>,
+}
+
+impl TrackTiming {
+    pub fn new(track: Track) -> Self {
+        let sectors = track.sector_count();
+        Self {
+            track,
+            clock: 0.0,
+            players: HashMap::new(),
+            session_best_sectors: vec![None; sectors],
+            session_best_lap: None,
+        }
+    }
+
+    /// Load AVENLAB_TRACK_FILE (None = no timing).
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var("AVENLAB_TRACK_FILE").ok()?;
+        let track = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| Track::from_json(&text));
+        match track {
+            Ok(track) => {
+                println!(
+                    "🏁 Track timing: {} checkpoints, {} sectors ({})",
+                    track.checkpoints.len(), track.sector_count(), path
+                );
+                Some(Self::new(track))
+            }
+            Err(e) => {
+                eprintln!("⚠️ Could not load track file {}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Advance the clock by one tick of simulated time.
+    pub fn advance(&mut self, dt: f32) {
+        self.clock += dt as f64;
+    }
+
+    pub fn remove_player(&mut self, id: &str) {
+        self.players.remove(id);
+    }
+
+    /// Feed one player's position for the current clock; returns the sector
+    /// it completed, if any.
+    pub fn update_player(&mut self, id: &str, pos: [f32; 3]) -> Option<SectorCompleted> {
+        let now = self.clock;
+        let sectors = self.track.sector_count();
+        let n = self.track.checkpoints.len();
+        let timing = self.players.entry(id.to_string()).or_insert_with(|| PlayerTiming {
+            best_sectors: vec![None; sectors],
+            ..Default::default()
+        });
+
+        let target = &self.track.checkpoints[timing.next];
+        let reached = dist_xz(pos, target.position) <= target.radius;
+        if !reached {
+            if self.track.continuous_delta && timing.lap > 0 {
+                timing.delta = live_delta(&self.track, timing, pos, now);
+            }
+            return None;
+        }
+
+        let index = timing.next;
+        timing.next = (index + 1) % n;
+
+        // First start/finish crossing: lap 1 begins
+        if timing.lap == 0 {
+            start_lap(timing, now, 1);
+            return None;
+        }
+
+        let t = millis(now - timing.lap_start);
+        let slot = if index == 0 { n } else { index }; // best_splits position of this checkpoint
+        timing.splits.push(t);
+        timing.delta = timing.best_splits.get(slot).map(|best| t - best);
+        if !self.track.is_sector_end(index) {
+            return None;
+        }
+
+        // Sector complete: classify against the bests before updating them
+        let sector = timing.sector;
+        let time = millis(now - timing.sector_start);
+        let session_best = self.session_best_sectors[sector];
+        let personal_best = timing.best_sectors[sector];
+        let class = if session_best.is_none_or(|b| time < b) {
+            SectorClass::Purple
+        } else if personal_best.is_none_or(|b| time < b) {
+            SectorClass::Green
+        } else {
+            SectorClass::Yellow
+        };
+        if personal_best.is_none_or(|b| time < b) {
+            timing.best_sectors[sector] = Some(time);
+        }
+        if class == SectorClass::Purple {
+            self.session_best_sectors[sector] = Some(time);
+        }
+        timing.last_sector = Some((sector, time));
+        timing.sector += 1;
+        timing.sector_start = now;
+
+        let mut event = SectorCompleted {
+            player_id: id.to_string(),
+            lap: timing.lap,
+            sector,
+            time,
+            class,
+            delta: timing.delta,
+            lap_time: None,
+        };
+
+        // Finish line: close the lap, keep it if it's the best, start the next
+        if index == 0 {
+            event.lap_time = Some(t);
+            if timing.best_lap.is_none_or(|b| t < b) {
+                timing.best_lap = Some(t);
+                timing.best_splits = std::iter::once(0.0).chain(timing.splits.iter().copied()).collect();
+            }
+            if self.session_best_lap.is_none_or(|b| t < b) {
+                self.session_best_lap = Some(t);
+            }
+            let lap = timing.lap + 1;
+            start_lap(timing, now, lap);
+        }
+        Some(event)
+    }
+
+    /// Current lap of a player (0 = not started or unknown).
+    pub fn lap(&self, id: &str) -> u32 {
+        self.players.get(id).map_or(0, |t| t.lap)
+    }
+
+    pub fn best_lap(&self, id: &str) -> Option<f32> {
+        self.players.get(id).and_then(|t| t.best_lap)
+    }
+
+    pub fn bests(&self, id: &str) -> Option<TimingBests> {
+        let timing = self.players.get(id)?;
+        Some(TimingBests {
+            best_lap: timing.best_lap,
+            best_splits: timing.best_splits.clone(),
+            best_sectors: timing.best_sectors.clone(),
+        })
+    }
+
+    /// Restore a player's bests (warm restart); they also count toward the
+    /// session bests. Saved sectors that don't fit this track are dropped.
+    pub fn restore_bests(&mut self, id: &str, bests: TimingBests) {
+        let sectors = self.track.sector_count();
+        let mut best_sectors = bests.best_sectors;
+        best_sectors.resize(sectors, None);
+        for (session, personal) in self.session_best_sectors.iter_mut().zip(&best_sectors) {
+            if let Some(t) = personal.filter(|t| session.is_none_or(|s| *t < s)) {
+                *session = Some(t);
+            }
+        }
+        if let Some(t) = bests.best_lap.filter(|t| self.session_best_lap.is_none_or(|s| *t < s)) {
+            self.session_best_lap = Some(t);
+        }
+        self.players.insert(id.to_string(), PlayerTiming {
+            best_lap: bests.best_lap,
+            best_splits: bests.best_splits,
+            best_sectors,
+            ..Default::default()
+        });
+    }
+
+    /// Snapshot block for one player (None before their first crossing).
+    pub fn snapshot(&self, id: &str) -> Option<serde_json::Value> {
+        let timing = self.players.get(id).filter(|t| t.lap > 0)?;
+        Some(json!({
+            "lap": timing.lap,
+            "sector": timing.sector,
+            "current": millis(self.clock - timing.lap_start),
+            "delta": timing.delta,
+            "last_sector": timing.last_sector.map(|(sector, time)| json!({ "sector": sector, "time": time })),
+            "best_lap": timing.best_lap,
+        }))
+    }
+}
+
+fn start_lap(timing: &mut PlayerTiming, now: f64, lap: u32) {
+    timing.lap = lap;
+    timing.lap_start = now;
+    timing.sector = 0;
+    timing.sector_start = now;
+    timing.splits.clear();
+}
+
+fn millis(t: f64) -> f32 {
+    ((t * 1000.0).round() / 1000.0) as f32
+}
+
+fn dist_xz(a: [f32; 3], b: [f32; 3]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+/// Lap time so far minus the best lap's time at the same progress along the
+/// segment from the last checkpoint to the next one.
+fn live_delta(track: &Track, timing: &PlayerTiming, pos: [f32; 3], now: f64) -> Option<f32> {
+    let n = track.checkpoints.len();
+    let to = timing.next;
+    let from = (to + n - 1) % n;
+    let slot_from = from; // best_splits index (0 = lap start)
+    let slot_to = if to == 0 { n } else { to };
+    let (&t_from, &t_to) = (timing.best_splits.get(slot_from)?, timing.best_splits.get(slot_to)?);
+
+    let a = track.checkpoints[from].position;
+    let b = track.checkpoints[to].position;
+    let (dx, dz) = (b[0] - a[0], b[2] - a[2]);
+    let len_sq = (dx * dx + dz * dz).max(1e-6);
+    let f = (((pos[0] - a[0]) * dx + (pos[2] - a[2]) * dz) / len_sq).clamp(0.0, 1.0);
+
+    Some((now - timing.lap_start) as f32 - (t_from + (t_to - t_from) * f))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    const DT: f32 = 0.1;
+
+    /// 100 m square, sectors end at the far corner and the finish.
+    fn square(continuous_delta: bool) -> TrackTiming {
+        let corner = |x: f32, z: f32, sector: bool| Checkpoint { position: [x, 1.0, z], radius: 0.25, sector };
+        TrackTiming::new(Track {
+            checkpoints: vec![corner(0.0, 0.0, false), corner(100.0, 0.0, false), corner(100.0, 100.0, true), corner(0.0, 100.0, false)],
+            continuous_delta,
+        })
+    }
+
+    /// Position `s` metres along the square from the start/finish.
+    fn along(s: f32) -> [f32; 3] {
+        let s = s.rem_euclid(400.0);
+        match s {
+            s if s < 100.0 => [s, 1.0, 0.0],
+            s if s < 200.0 => [100.0, 1.0, s - 100.0],
+            s if s < 300.0 => [300.0 - s, 1.0, 100.0],
+            s => [0.0, 1.0, 400.0 - s],
+        }
+    }
+
+    /// Cross the start/finish, then drive `legs` of (speed m/s, metres).
+    fn drive(timing: &mut TrackTiming, id: &str, legs: &[(f32, f32)]) -> Vec<SectorCompleted> {
+        let mut s = 0.0;
+        timing.advance(DT);
+        assert!(timing.update_player(id, along(s)).is_none());
+        let mut events = Vec::new();
+        for &(speed, metres) in legs {
+            for _ in 0..(metres / (speed * DT)).round() as usize {
+                s += speed * DT;
+                timing.advance(DT);
+                events.extend(timing.update_player(id, along(s)));
+            }
+        }
+        events
+    }
+
+    type Split = (u32, usize, f32, SectorClass, Option<f32>, Option<f32>); // lap, sector, time, class, delta, lap_time
+
+    fn summary(events: &[SectorCompleted]) -> Vec<Split> {
+        events.iter().map(|e| (e.lap, e.sector, e.time, e.class, e.delta, e.lap_time)).collect()
+    }
+
+    #[test]
+    fn scripted_laps_give_known_splits_deltas_and_classes() {
+        let mut timing = square(false);
+        assert_eq!(timing.track.sector_count(), 2);
+
+        // Lap 1 at 10 m/s, lap 2: sector 0 at 20 m/s, sector 1 at 5 m/s
+        let events = drive(&mut timing, "a", &[(10.0, 400.0), (20.0, 200.0), (5.0, 200.0)]);
+        assert_eq!(summary(&events), vec![
+            (1, 0, 20.0, SectorClass::Purple, None, None),
+            (1, 1, 20.0, SectorClass::Purple, None, Some(40.0)),
+            (2, 0, 10.0, SectorClass::Purple, Some(-10.0), None),
+            (2, 1, 40.0, SectorClass::Yellow, Some(10.0), Some(50.0)),
+        ]);
+        assert_eq!(timing.best_lap("a"), Some(40.0));
+        let hud = timing.snapshot("a").unwrap();
+        assert_eq!(hud["lap"], 3);
+        assert_eq!(hud["sector"], 0);
+        assert_eq!(hud["last_sector"], json!({ "sector": 1, "time": 40.0 }));
+        assert_eq!(hud["best_lap"], 40.0);
+
+        // Another player matching a's first lap: personal bests, not session ones
+        let events = drive(&mut timing, "b", &[(10.0, 400.0)]);
+        let classes: Vec<SectorClass> = events.iter().map(|e| e.class).collect();
+        assert_eq!(classes, [SectorClass::Green, SectorClass::Green]);
+        assert_eq!(timing.snapshot("c"), None);
+    }
+
+    #[test]
+    fn continuous_delta_interpolates_the_best_lap_between_checkpoints() {
+        let mut timing = square(true);
+        drive(&mut timing, "a", &[(10.0, 400.0)]); // best lap: 10 s per side
+        assert_eq!(timing.snapshot("a").unwrap()["delta"], Value::Null);
+
+        // Halfway down the first side of lap 2 at 20 m/s: 2.5 s vs 5 s
+        let mut timing2 = square(true);
+        drive(&mut timing2, "a", &[(10.0, 400.0), (20.0, 50.0)]);
+        let delta = timing2.snapshot("a").unwrap()["delta"].as_f64().unwrap();
+        assert!((delta + 2.5).abs() < 1e-3, "delta {}", delta);
+
+        // Without it the delta only moves at checkpoints
+        let mut stepped = square(false);
+        drive(&mut stepped, "a", &[(10.0, 400.0), (20.0, 50.0)]);
+        assert_eq!(stepped.snapshot("a").unwrap()["delta"], Value::Null);
+    }
+
+    #[test]
+    fn checkpoints_only_count_in_order() {
+        let mut timing = square(false);
+        timing.advance(DT);
+        timing.update_player("a", along(0.0));
+        // Cut straight to the sector checkpoint: not the next one, ignored
+        for _ in 0..10 {
+            timing.advance(DT);
+            assert!(timing.update_player("a", along(200.0)).is_none());
+        }
+        assert_eq!(timing.snapshot("a").unwrap()["sector"], 0);
+    }
+}
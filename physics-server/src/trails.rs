@@ -0,0 +1,129 @@
+// ==============================================================================
+// trails.rs — CONTACT / TIRE-FORCE TRAILS FOR THE DEBUG OVERLAY
+// ------------------------------------------------------------------------------
+// A single-frame force arrow is hard to read at 60 Hz. Every wheel keeps its
+// last N ticks of (contact point, applied tire force) in a ring buffer; the
+// overlay carries one polyline per wheel (DEBUG_TRAILS category), oldest point
+// first, each point tagged with its age in physics ticks so the client can fade
+// it. A sliding rear axle draws an arc while the fronts stay short and straight,
+// which makes slip onset obvious.
+//
+//   length : AVENLAB_DEBUG_TRAIL_LEN points per wheel (default 20, max 240)
+//   reset  : a wheel that leaves the ground drops its trail
+//   force  : sum of that wheel's tire impulses over the substep / dt (N);
+//            with substeps, the last substep of a tick wins
+// ==============================================================================
+
+use std::collections::VecDeque;
+
+use rapier3d::prelude::*;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+pub const DEFAULT_TRAIL_LEN: usize = 20;
+const MAX_TRAIL_LEN: usize = 240;
+
+pub fn trail_len_from_env() -> usize {
+    std::env::var("AVENLAB_DEBUG_TRAIL_LEN")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .map(|n| n.min(MAX_TRAIL_LEN))
+        .unwrap_or(DEFAULT_TRAIL_LEN)
+}
+
+#[derive(Clone, Copy, Serialize, JsonSchema)]
+pub struct DebugTrailPoint {
+    pub position: [f32; 3], // contact point (world)
+    pub force: [f32; 3],    // applied tire force (N, world)
+    pub age: u64,           // physics ticks since recorded
+}
+
+#[derive(Clone, Serialize, JsonSchema)]
+pub struct DebugTrail {
+    pub wheel: String,
+    pub points: Vec<DebugTrailPoint>, // oldest first
+}
+
+#[derive(Clone, Copy)]
+struct TrailPoint {
+    tick: u64,
+    position: [f32; 3],
+    force: [f32; 3],
+}
+
+#[derive(Clone, Default)]
+pub struct WheelTrail {
+    points: VecDeque<TrailPoint>,
+}
+
+impl WheelTrail {
+    pub fn push(&mut self, tick: u64, position: Point<Real>, force: Vector<Real>, capacity: usize) {
+        if capacity == 0 {
+            self.points.clear();
+            return;
+        }
+        let point = TrailPoint { tick, position: position.into(), force: force.into() };
+        match self.points.back_mut() {
+            Some(last) if last.tick == tick => *last = point,
+            _ => self.points.push_back(point),
+        }
+        while self.points.len() > capacity {
+            self.points.pop_front();
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.points.clear();
+    }
+
+    pub fn to_debug(&self, wheel: &str, now_tick: u64) -> DebugTrail {
+        DebugTrail {
+            wheel: wheel.to_string(),
+            points: self
+                .points
+                .iter()
+                .map(|p| DebugTrailPoint {
+                    position: p.position,
+                    force: p.force,
+                    age: now_tick.saturating_sub(p.tick),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(x: f32) -> Point<Real> {
+        point![x, 0.0, 0.0]
+    }
+
+    #[test]
+    fn the_ring_keeps_the_newest_points_with_their_age() {
+        let mut trail = WheelTrail::default();
+        for tick in 0..30 {
+            trail.push(tick, at(tick as f32), Vector::zeros(), 20);
+        }
+        let debug = trail.to_debug("RL", 31);
+        assert_eq!(debug.points.len(), 20);
+        assert_eq!(debug.points[0].position[0], 10.0);
+        assert_eq!((debug.points[0].age, debug.points[19].age), (21, 2));
+    }
+
+    #[test]
+    fn a_substep_overwrites_its_tick_and_airborne_clears() {
+        let mut trail = WheelTrail::default();
+        trail.push(5, at(1.0), Vector::zeros(), 20);
+        trail.push(5, at(2.0), vector![0.0, 0.0, 100.0], 20);
+        let debug = trail.to_debug("FL", 5);
+        assert_eq!(debug.points.len(), 1);
+        assert_eq!((debug.points[0].position[0], debug.points[0].force[2]), (2.0, 100.0));
+
+        trail.reset();
+        assert!(trail.to_debug("FL", 6).points.is_empty());
+        trail.push(7, at(3.0), Vector::zeros(), 0);
+        assert!(trail.to_debug("FL", 7).points.is_empty(), "length 0 turns trails off");
+    }
+}
@@ -1,6 +1,20 @@
 use rapier3d::prelude::*;
+use std::fmt;
 use crate::aven_tire::steering::SteeringState;
+use crate::aven_tire::combined_slip::KammCircle;
+use crate::aven_tire::MAX_AXLES;
+use crate::physics::Wheel;
+use crate::haptics::HapticState;
+use crate::input_hold::InputHold;
+use crate::surface::WheelSurface;
+use crate::airborne::AirState;
+use crate::recovery::FlipState;
 
+/// Vehicle::config_name of a config passed in directly (sweeps, reports).
+pub const CUSTOM_CONFIG_NAME: &str = "custom";
+
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct VehicleConfig {
     pub mass: f32,              // kg
     pub engine_force: f32,      // N
@@ -8,8 +22,10 @@ pub struct VehicleConfig {
     pub max_speed: f32,         // m/s
     pub linear_damping: f32,    // drag
     pub angular_damping: f32,   // rotational drag
+    pub powertrain_efficiency_at_speed: [(f32, f32); 4], // (m/s, 0..1) torque-speed curve
     pub mu_base: f32,          // base friction coefficient
     pub load_sensitivity: f32, // how much friction decreases with load
+    pub kamm: KammCircle,      // friction ellipse (cx long, cy lat)
 
     // --- Geometry ---
     pub wheelbase: f32,      // meters (front axle to rear axle)
@@ -18,8 +34,7 @@ pub struct VehicleConfig {
     pub ackermann: f32,      // 0..1 blend (0 = parallel, 1 = full ackermann)
 
     // --- Anti-roll bars ---
-    pub arb_front: f32,         // N/m
-    pub arb_rear: f32,          // N/m
+    pub arb_rates: [f32; MAX_AXLES], // N/m per axle, front to rear (Wheel::axle)
 
     // NEW: assists (toggles + thresholds)
     pub abs_enabled: bool,
@@ -29,14 +44,60 @@ pub struct VehicleConfig {
     pub abs_nx_limit: f32,  // typical 0.85–1.0
     pub tcs_nx_limit: f32,  // typical 0.85–1.0
 
+    // --- Drivetrain ---
+    #[serde(default)]
+    pub drivetrain: Drivetrain, // rwd | awd (front axle driven too)
+
+    // --- Torque vectoring (outside-wheel drive bias) ---
+    pub tv_enabled: bool,
+    pub tv_gain: f32,       // bias per (steer + normalized yaw error)
+    pub tv_max_bias: f32,   // 0..1 fraction moved to the outside wheel
+    pub tv_min_speed: f32,  // m/s
+
+    // --- Arcade yaw stabilization ---
+    pub stability_assist: f32, // 0 = off, 1 = strong (fraction of unwanted yaw removed per tick)
+
+    // --- Mid-air attitude control (airborne.rs) ---
+    pub air_control_torque: f32, // N·m, steer / pitch authority while airborne (0 = none)
+
+    // engine braking (throttle lifted)
+    pub engine_brake_coefficient: f32, // N per m/s of wheel speed, whole car, driven wheels only
+
+    // --- Brake bias (front share of brake demand) ---
+    pub brake_bias: f32,            // default front share, 0..1
+    pub brake_bias_range: [f32; 2], // [min, max] the driver may adjust to in play
+
     // --- Chassis geometry ---
     pub chassis_half_extents: [f32; 3], // [hx, hy, hz] meters
     pub chassis_com_offset: [f32; 3],   // local offset from collider center
+
+    // --- Physical wheels ---
+    pub wheel_colliders: bool, // ball colliders that hit curbs / props (not flat ground)
+
+    // --- Car-to-car contact ---
+    pub vehicle_collisions: bool, // chassis hits other chassis (both must have it on)
+
+    // --- Flip recovery (recovery.rs) ---
+    pub auto_flip_recover: bool, // right the car in place after resting upside down
+    pub flip_timeout_s: f32,     // seconds flipped and at rest before it is righted
+
+    // --- Input staleness (input_hold.rs) ---
+    pub input_timeout_s: f32, // seconds without any input message before the car is brought to rest
+}
+
+/// Axles the engine drives (Wheel::drive, PhysicsWorld::build_car_wheels).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Drivetrain {
+    #[default]
+    Rwd,
+    Awd,
 }
 
 pub struct Vehicle {
     pub body: RigidBodyHandle,  // the chassis body
     pub config: VehicleConfig,  // vehicle parameters
+    pub config_name: String,    // built-in config it came from (vehicle_config_by_name), "custom" otherwise
     pub throttle: f32,          // -1.0 (full reverse) .. 1.0 (full forward)
     pub steer: f32,             // -1.0 (full left) .. 1.0 (full right)
     pub brake: f32,             // 0.0 (no brake) .. 1.0 (full brake)
@@ -49,4 +110,466 @@ pub struct Vehicle {
     pub steering: SteeringState,// state
     pub rack_torque: f32,       // from tires
     pub rack_torque_filtered: f32, // from tires
-}
\ No newline at end of file
+    pub haptics: HapticState,   // rumble hint edge / resend state
+    pub brake_bias: f32,        // driver-adjusted front brake share (within config.brake_bias_range)
+    pub last_respawn_tick: Option<u64>, // PhysicsWorld::tick of the last safety teleport (None = never)
+    pub blocked_respawns: u32,  // safety teleports refused since then (cooldown)
+    pub corner_loads: [f32; 4], // applied suspension load, low-passed, FL/FR/RL/RR (N)
+    pub input_hold: InputHold,  // per-axis latch / decay / require_full (hello handshake)
+    pub payload: Payload,       // cargo / fuel carried on top of config.mass (PhysicsWorld::set_payload)
+    pub wheel_surfaces: [Option<WheelSurface>; 4], // what each wheel stood on last step, FL/FR/RL/RR (None = airborne)
+    pub air: AirState,          // airborne flag, air time, landing settle (airborne.rs)
+    pub flip: FlipState,        // time counted as flipped (recovery.rs)
+    pub tick_divisor: u32,      // steps every n-th tick by n x dt (room tick rate, PhysicsWorld::set_tick_divisor)
+}
+
+/// Extra mass carried by the chassis: a point mass at a body-local offset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+pub struct Payload {
+    pub mass: f32,        // kg (0 = empty)
+    pub offset: [f32; 3], // body-local position (m), +Z forward
+}
+
+impl Vehicle {
+    /// Control axes in input_hold::AXIS_NAMES order.
+    pub fn axes_mut(&mut self) -> [&mut f32; 7] {
+        [&mut self.throttle, &mut self.steer, &mut self.brake, &mut self.ascend, &mut self.pitch, &mut self.yaw, &mut self.roll]
+    }
+}
+
+// ==========================================================
+// VehicleBuilder
+// ----------------------------------------------------------
+// Body + config are required; all control / steering state
+// starts at rest. New Vehicle fields get their rest value
+// here instead of in struct literals elsewhere; add a with_*
+// method once a caller needs to start one differently.
+// ==========================================================
+pub struct VehicleBuilder {
+    vehicle: Vehicle,
+}
+
+impl VehicleBuilder {
+    pub fn new(body: RigidBodyHandle, config: VehicleConfig) -> Self {
+        Self {
+            vehicle: Vehicle {
+                body,
+                config,
+                config_name: CUSTOM_CONFIG_NAME.to_string(),
+                throttle: 0.0,
+                steer: 0.0,
+                brake: 0.0,
+                pitch: 0.0,
+                yaw: 0.0,
+                roll: 0.0,
+                ascend: 0.0,
+                steer_angle: 0.0,
+                steer_rate: 0.0,
+                steering: SteeringState::default(),
+                rack_torque: 0.0,
+                rack_torque_filtered: 0.0,
+                haptics: HapticState::default(),
+                brake_bias: config.brake_bias,
+                last_respawn_tick: None,
+                blocked_respawns: 0,
+                corner_loads: [0.0; 4],
+                input_hold: InputHold::default(),
+                payload: Payload::default(),
+                wheel_surfaces: Default::default(),
+                air: AirState::default(),
+                flip: FlipState::default(),
+                tick_divisor: 1,
+            },
+        }
+    }
+
+    pub fn build(self) -> Vehicle {
+        self.vehicle
+    }
+}
+
+// ==========================================================
+// Config validation
+// ----------------------------------------------------------
+// Catches physically nonsensical configs before they reach
+// Rapier (where they turn into NaNs / explosions).
+// ==========================================================
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    NonFinite(&'static str),
+    NonPositiveMass(f32),
+    NegativeFriction(f32),
+    AckermannOutOfRange(f32),
+    AbsLimitOutOfRange(f32),
+    TcsLimitOutOfRange(f32),
+    BadChassisExtents([f32; 3]),
+    ComOutsideChassis([f32; 3]),
+    BadPowertrainCurve([(f32, f32); 4]),
+    TorqueVectoringOutOfRange(f32),
+    StabilityAssistOutOfRange(f32),
+    BadKammCircle(f32, f32),
+    SagOutOfRange { wheel: String, sag: f32, max_length: f32 },
+    WheelOutsideChassis { wheel: String, offset: [f32; 3] },
+    SpringFrequencyOutOfRange { wheel: String, hz: f32 },
+    BrakeBiasOutOfRange { bias: f32, range: [f32; 2] },
+    NegativeAirControl(f32),
+    NegativeEngineBrake(f32),
+    AxleOutOfRange { wheel: String, axle: usize },
+    NonPositiveFlipTimeout(f32),
+    NonPositiveInputTimeout(f32),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::NonFinite(field) =>
+                write!(f, "{field} is NaN/Inf; use a finite value"),
+            ConfigError::NonPositiveMass(m) =>
+                write!(f, "mass = {m} kg; must be > 0"),
+            ConfigError::NegativeFriction(mu) =>
+                write!(f, "mu_base = {mu}; friction must be >= 0"),
+            ConfigError::AckermannOutOfRange(a) =>
+                write!(f, "ackermann = {a}; must be within 0..=1"),
+            ConfigError::AbsLimitOutOfRange(l) =>
+                write!(f, "abs_nx_limit = {l}; must be within (0, 1.2]"),
+            ConfigError::TcsLimitOutOfRange(l) =>
+                write!(f, "tcs_nx_limit = {l}; must be within (0, 1.2]"),
+            ConfigError::BadChassisExtents(e) =>
+                write!(f, "chassis_half_extents = {e:?}; all extents must be > 0"),
+            ConfigError::ComOutsideChassis(c) =>
+                write!(f, "chassis_com_offset = {c:?} lies outside the chassis half extents"),
+            ConfigError::BadPowertrainCurve(c) =>
+                write!(f, "powertrain_efficiency_at_speed = {c:?}; speeds must ascend and efficiencies lie within 0..=1"),
+            ConfigError::TorqueVectoringOutOfRange(b) =>
+                write!(f, "tv_max_bias = {b}; must be within 0..1"),
+            ConfigError::StabilityAssistOutOfRange(a) =>
+                write!(f, "stability_assist = {a}; must be within 0..=1"),
+            ConfigError::BadKammCircle(cx, cy) =>
+                write!(f, "kamm = (cx {cx}, cy {cy}); both coefficients must be > 0"),
+            ConfigError::SagOutOfRange { wheel, sag, max_length } =>
+                write!(f, "wheel {wheel}: static sag {sag} m must be within (0, max_length = {max_length} m)"),
+            ConfigError::WheelOutsideChassis { wheel, offset } =>
+                write!(f, "wheel {wheel}: offset {offset:?} is outside the chassis footprint"),
+            ConfigError::SpringFrequencyOutOfRange { wheel, hz } =>
+                write!(f, "wheel {wheel}: spring frequency {hz:.2} Hz outside 0.5–6 Hz; adjust sag or mass"),
+            ConfigError::BrakeBiasOutOfRange { bias, range } =>
+                write!(f, "brake_bias = {bias} with brake_bias_range = {range:?}; need 0 <= min <= bias <= max <= 1"),
+            ConfigError::NegativeAirControl(t) =>
+                write!(f, "air_control_torque = {t} N·m; must be >= 0"),
+            ConfigError::NegativeEngineBrake(c) =>
+                write!(f, "engine_brake_coefficient = {c} N/(m/s); must be >= 0"),
+            ConfigError::AxleOutOfRange { wheel, axle } =>
+                write!(f, "wheel {wheel}: axle {axle}; must be below {MAX_AXLES}"),
+            ConfigError::NonPositiveFlipTimeout(t) =>
+                write!(f, "flip_timeout_s = {t} s; must be > 0"),
+            ConfigError::NonPositiveInputTimeout(t) =>
+                write!(f, "input_timeout_s = {t} s; must be > 0"),
+        }
+    }
+}
+
+const ASSIST_LIMIT_MAX: f32 = 1.2;
+const SPRING_HZ_MIN: f32 = 0.5;
+const SPRING_HZ_MAX: f32 = 6.0;
+
+impl VehicleConfig {
+    /// Check physical sanity of the config itself (no wheel layout).
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+
+        let scalars = [
+            ("mass", self.mass),
+            ("engine_force", self.engine_force),
+            ("brake_force", self.brake_force),
+            ("max_speed", self.max_speed),
+            ("linear_damping", self.linear_damping),
+            ("angular_damping", self.angular_damping),
+            ("mu_base", self.mu_base),
+            ("load_sensitivity", self.load_sensitivity),
+            ("wheelbase", self.wheelbase),
+            ("track_width", self.track_width),
+            ("max_steer_angle", self.max_steer_angle),
+            ("ackermann", self.ackermann),
+            ("abs_nx_limit", self.abs_nx_limit),
+            ("tcs_nx_limit", self.tcs_nx_limit),
+            ("tv_gain", self.tv_gain),
+            ("tv_max_bias", self.tv_max_bias),
+            ("tv_min_speed", self.tv_min_speed),
+            ("stability_assist", self.stability_assist),
+            ("brake_bias", self.brake_bias),
+            ("air_control_torque", self.air_control_torque),
+            ("engine_brake_coefficient", self.engine_brake_coefficient),
+            ("flip_timeout_s", self.flip_timeout_s),
+            ("input_timeout_s", self.input_timeout_s),
+        ];
+        for (name, value) in scalars {
+            if !value.is_finite() {
+                errors.push(ConfigError::NonFinite(name));
+            }
+        }
+        if self.chassis_half_extents.iter().any(|v| !v.is_finite()) {
+            errors.push(ConfigError::NonFinite("chassis_half_extents"));
+        }
+        if self.chassis_com_offset.iter().any(|v| !v.is_finite()) {
+            errors.push(ConfigError::NonFinite("chassis_com_offset"));
+        }
+        if self.arb_rates.iter().any(|v| !v.is_finite()) {
+            errors.push(ConfigError::NonFinite("arb_rates"));
+        }
+        if self.brake_bias_range.iter().any(|v| !v.is_finite()) {
+            errors.push(ConfigError::NonFinite("brake_bias_range"));
+        }
+
+        if self.mass <= 0.0 {
+            errors.push(ConfigError::NonPositiveMass(self.mass));
+        }
+        if self.mu_base < 0.0 {
+            errors.push(ConfigError::NegativeFriction(self.mu_base));
+        }
+        if !(0.0..=1.0).contains(&self.ackermann) {
+            errors.push(ConfigError::AckermannOutOfRange(self.ackermann));
+        }
+        if !(self.abs_nx_limit > 0.0 && self.abs_nx_limit <= ASSIST_LIMIT_MAX) {
+            errors.push(ConfigError::AbsLimitOutOfRange(self.abs_nx_limit));
+        }
+        if !(self.tcs_nx_limit > 0.0 && self.tcs_nx_limit <= ASSIST_LIMIT_MAX) {
+            errors.push(ConfigError::TcsLimitOutOfRange(self.tcs_nx_limit));
+        }
+
+        let he = self.chassis_half_extents;
+        if he.iter().any(|&v| v <= 0.0) {
+            errors.push(ConfigError::BadChassisExtents(he));
+        } else {
+            let com = self.chassis_com_offset;
+            if (0..3).any(|i| com[i].abs() > he[i]) {
+                errors.push(ConfigError::ComOutsideChassis(com));
+            }
+        }
+
+        if !(self.kamm.cx > 0.0 && self.kamm.cy > 0.0) {
+            errors.push(ConfigError::BadKammCircle(self.kamm.cx, self.kamm.cy));
+        }
+        if !(0.0..1.0).contains(&self.tv_max_bias) {
+            errors.push(ConfigError::TorqueVectoringOutOfRange(self.tv_max_bias));
+        }
+        if !(0.0..=1.0).contains(&self.stability_assist) {
+            errors.push(ConfigError::StabilityAssistOutOfRange(self.stability_assist));
+        }
+        if self.air_control_torque < 0.0 {
+            errors.push(ConfigError::NegativeAirControl(self.air_control_torque));
+        }
+        if self.engine_brake_coefficient < 0.0 {
+            errors.push(ConfigError::NegativeEngineBrake(self.engine_brake_coefficient));
+        }
+        if self.flip_timeout_s <= 0.0 {
+            errors.push(ConfigError::NonPositiveFlipTimeout(self.flip_timeout_s));
+        }
+        if self.input_timeout_s <= 0.0 {
+            errors.push(ConfigError::NonPositiveInputTimeout(self.input_timeout_s));
+        }
+        let [bias_min, bias_max] = self.brake_bias_range;
+        if !(0.0 <= bias_min && bias_min <= self.brake_bias && self.brake_bias <= bias_max && bias_max <= 1.0) {
+            errors.push(ConfigError::BrakeBiasOutOfRange { bias: self.brake_bias, range: self.brake_bias_range });
+        }
+
+        let curve = self.powertrain_efficiency_at_speed;
+        let curve_ok = curve.iter().all(|(v, e)| v.is_finite() && (0.0..=1.0).contains(e))
+            && curve.windows(2).all(|w| w[0].0 < w[1].0);
+        if !curve_ok {
+            errors.push(ConfigError::BadPowertrainCurve(curve));
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    /// Check a wheel layout against this config: axle index in range, offsets
+    /// inside the chassis footprint, static sag within travel, and sprung
+    /// spring frequency.
+    pub fn validate_wheels(&self, wheels: &[Wheel], sag_m: f32) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+
+        let [hx, _, hz] = self.chassis_half_extents;
+        let [cx, _, cz] = self.chassis_com_offset;
+        let sprung_mass = self.mass / wheels.len().max(1) as f32;
+
+        for wheel in wheels {
+            if wheel.axle >= MAX_AXLES {
+                errors.push(ConfigError::AxleOutOfRange { wheel: wheel.debug_id.clone(), axle: wheel.axle });
+            }
+
+            let o = wheel.offset;
+            if (o.x - cx).abs() > hx || (o.z - cz).abs() > hz {
+                errors.push(ConfigError::WheelOutsideChassis {
+                    wheel: wheel.debug_id.clone(),
+                    offset: [o.x, o.y, o.z],
+                });
+            }
+
+            if !(sag_m > 0.0 && sag_m < wheel.max_length) {
+                errors.push(ConfigError::SagOutOfRange {
+                    wheel: wheel.debug_id.clone(),
+                    sag: sag_m,
+                    max_length: wheel.max_length,
+                });
+            }
+
+            // f = sqrt(k / m) / 2π
+            let hz_spring = (wheel.stiffness / sprung_mass.max(1e-3)).sqrt() / std::f32::consts::TAU;
+            if !(SPRING_HZ_MIN..=SPRING_HZ_MAX).contains(&hz_spring) {
+                errors.push(ConfigError::SpringFrequencyOutOfRange {
+                    wheel: wheel.debug_id.clone(),
+                    hz: hz_spring,
+                });
+            }
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::{ARCADE_GT86, GT86, PhysicsWorld, TANK};
+
+    const SAG_M: f32 = 0.065;
+
+    /// validate() errors of GT86 after `edit`.
+    fn config_errors(edit: impl FnOnce(&mut VehicleConfig)) -> Vec<ConfigError> {
+        let mut config = GT86;
+        edit(&mut config);
+        config.validate().err().unwrap_or_default()
+    }
+
+    /// validate_wheels() errors of GT86's own wheels after `edit`.
+    fn wheel_errors(sag_m: f32, edit: impl FnOnce(&mut [Wheel])) -> Vec<ConfigError> {
+        let mut wheels = PhysicsWorld::new().build_car_wheels(&GT86);
+        edit(&mut wheels);
+        GT86.validate_wheels(&wheels, sag_m).err().unwrap_or_default()
+    }
+
+    #[test]
+    fn builtin_configs_pass() {
+        let mut phys = PhysicsWorld::new();
+        for config in [GT86, TANK, ARCADE_GT86] {
+            assert_eq!(config.validate(), Ok(()));
+            let wheels = phys.build_car_wheels(&config);
+            assert_eq!(config.validate_wheels(&wheels, SAG_M), Ok(()));
+        }
+    }
+
+    #[test]
+    fn non_finite_fields_are_named() {
+        assert!(config_errors(|c| c.engine_force = f32::NAN).contains(&ConfigError::NonFinite("engine_force")));
+        assert!(config_errors(|c| c.chassis_com_offset[1] = f32::INFINITY).contains(&ConfigError::NonFinite("chassis_com_offset")));
+        assert!(config_errors(|c| c.arb_rates[0] = f32::NAN).contains(&ConfigError::NonFinite("arb_rates")));
+    }
+
+    #[test]
+    fn mass_must_be_positive() {
+        assert_eq!(config_errors(|c| c.mass = 0.0), [ConfigError::NonPositiveMass(0.0)]);
+    }
+
+    #[test]
+    fn friction_must_not_be_negative() {
+        assert_eq!(config_errors(|c| c.mu_base = -0.1), [ConfigError::NegativeFriction(-0.1)]);
+    }
+
+    #[test]
+    fn ackermann_stays_within_0_1() {
+        assert_eq!(config_errors(|c| c.ackermann = 1.5), [ConfigError::AckermannOutOfRange(1.5)]);
+    }
+
+    #[test]
+    fn assist_limits_stay_within_range() {
+        assert_eq!(config_errors(|c| c.abs_nx_limit = 0.0), [ConfigError::AbsLimitOutOfRange(0.0)]);
+        assert_eq!(config_errors(|c| c.tcs_nx_limit = 2.0), [ConfigError::TcsLimitOutOfRange(2.0)]);
+    }
+
+    #[test]
+    fn chassis_extents_must_be_positive_and_hold_the_com() {
+        assert_eq!(config_errors(|c| c.chassis_half_extents[2] = 0.0), [ConfigError::BadChassisExtents([GT86.chassis_half_extents[0], GT86.chassis_half_extents[1], 0.0])]);
+        let com = [0.0, 0.0, GT86.chassis_half_extents[2] + 0.1];
+        assert_eq!(config_errors(|c| c.chassis_com_offset = com), [ConfigError::ComOutsideChassis(com)]);
+    }
+
+    #[test]
+    fn powertrain_curve_ascends_within_0_1() {
+        let errors = config_errors(|c| c.powertrain_efficiency_at_speed[1].0 = 0.0);
+        assert!(matches!(errors[..], [ConfigError::BadPowertrainCurve(_)]));
+        let errors = config_errors(|c| c.powertrain_efficiency_at_speed[0].1 = 1.2);
+        assert!(matches!(errors[..], [ConfigError::BadPowertrainCurve(_)]));
+    }
+
+    #[test]
+    fn torque_vectoring_bias_stays_below_1() {
+        assert_eq!(config_errors(|c| c.tv_max_bias = 1.0), [ConfigError::TorqueVectoringOutOfRange(1.0)]);
+    }
+
+    #[test]
+    fn stability_assist_stays_within_0_1() {
+        assert_eq!(config_errors(|c| c.stability_assist = -0.5), [ConfigError::StabilityAssistOutOfRange(-0.5)]);
+    }
+
+    #[test]
+    fn kamm_coefficients_must_be_positive() {
+        assert_eq!(config_errors(|c| c.kamm.cy = 0.0), [ConfigError::BadKammCircle(GT86.kamm.cx, 0.0)]);
+    }
+
+    #[test]
+    fn brake_bias_sits_inside_its_range() {
+        let range = GT86.brake_bias_range;
+        assert_eq!(config_errors(|c| c.brake_bias = range[1] + 0.05), [ConfigError::BrakeBiasOutOfRange { bias: range[1] + 0.05, range }]);
+        assert_eq!(config_errors(|c| c.brake_bias_range = [0.2, 1.2]), [ConfigError::BrakeBiasOutOfRange { bias: GT86.brake_bias, range: [0.2, 1.2] }]);
+    }
+
+    #[test]
+    fn air_control_and_engine_brake_must_not_be_negative() {
+        assert_eq!(config_errors(|c| c.air_control_torque = -1.0), [ConfigError::NegativeAirControl(-1.0)]);
+        assert_eq!(config_errors(|c| c.engine_brake_coefficient = -1.0), [ConfigError::NegativeEngineBrake(-1.0)]);
+    }
+
+    #[test]
+    fn timeouts_must_be_positive() {
+        assert_eq!(config_errors(|c| c.flip_timeout_s = 0.0), [ConfigError::NonPositiveFlipTimeout(0.0)]);
+        assert_eq!(config_errors(|c| c.input_timeout_s = -1.0), [ConfigError::NonPositiveInputTimeout(-1.0)]);
+    }
+
+    #[test]
+    fn every_violation_is_reported_at_once() {
+        let errors = config_errors(|c| {
+            c.mass = -1.0;
+            c.mu_base = -1.0;
+            c.ackermann = 2.0;
+        });
+        assert_eq!(errors.len(), 3);
+        assert!(errors.iter().all(|e| !e.to_string().is_empty()));
+    }
+
+    #[test]
+    fn wheel_axle_must_be_in_range() {
+        let errors = wheel_errors(SAG_M, |w| w[0].axle = MAX_AXLES);
+        assert_eq!(errors, [ConfigError::AxleOutOfRange { wheel: "FL".to_string(), axle: MAX_AXLES }]);
+    }
+
+    #[test]
+    fn wheels_sit_inside_the_chassis_footprint() {
+        let errors = wheel_errors(SAG_M, |w| w[1].offset.x += 5.0);
+        assert!(matches!(&errors[..], [ConfigError::WheelOutsideChassis { wheel, .. }] if wheel == "FR"));
+    }
+
+    #[test]
+    fn sag_stays_within_travel() {
+        let errors = wheel_errors(0.0, |_| {});
+        assert_eq!(errors.len(), 4);
+        assert!(errors.iter().all(|e| matches!(e, ConfigError::SagOutOfRange { sag, .. } if *sag == 0.0)));
+        let errors = wheel_errors(SAG_M, |w| w[3].max_length = SAG_M);
+        assert!(matches!(&errors[..], [ConfigError::SagOutOfRange { wheel, .. }] if wheel == "RR"));
+    }
+
+    #[test]
+    fn spring_frequency_stays_within_band() {
+        let errors = wheel_errors(SAG_M, |w| w[2].stiffness *= 100.0);
+        assert!(matches!(&errors[..], [ConfigError::SpringFrequencyOutOfRange { wheel, hz }] if wheel == "RL" && *hz > SPRING_HZ_MAX));
+    }
+}
@@ -0,0 +1,158 @@
+// ==============================================================================
+// vehicle_presets.rs — VEHICLE CONFIG PRESETS FROM A FILE
+// ------------------------------------------------------------------------------
+// Tuning without a rebuild. At startup the server reads AVENLAB_VEHICLES_FILE
+// (default configs/vehicles.toml): one table per preset, each a built-in base
+// plus the VehicleConfig fields it changes.
+//
+//   [drift]
+//   base = "gt86"                       # gt86 | arcade | tank (default gt86)
+//   mass = 1250.0
+//   arb_rates = [24000.0, 6000.0, 0.0, 0.0]
+//   kamm = { cx = 1.0, cy = 0.85 }
+//
+// A preset named like a built-in ("gt86") replaces it. Presets are registered
+// in PhysicsWorld::vehicle_presets; joins pick one with ?vehicle=<name>.
+//
+//   missing file   : built-ins only
+//   malformed file : error naming the preset and field (unknown field, wrong
+//                    type, VehicleConfig::validate / validate_wheels failure),
+//                    then built-ins only; nothing from the file is used
+//
+// Recording replays (--verify) load the same file, so recorded spawns of a
+// file preset replay with its config.
+// ==============================================================================
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::physics::{PhysicsWorld, vehicle_config_by_name};
+use crate::vehicle::VehicleConfig;
+
+pub const DEFAULT_PRESETS_FILE: &str = "configs/vehicles.toml";
+const BASE_KEY: &str = "base";
+const DEFAULT_BASE: &str = "gt86";
+
+pub fn presets_path() -> String {
+    std::env::var("AVENLAB_VEHICLES_FILE").unwrap_or_else(|_| DEFAULT_PRESETS_FILE.to_string())
+}
+
+/// Load AVENLAB_VEHICLES_FILE into `phys` (startup, replays); logs the
+/// outcome, keeps the built-ins on any error.
+pub fn load_into(phys: &mut PhysicsWorld) {
+    let path = presets_path();
+    let loaded = load_file(&path).and_then(|presets| match presets {
+        Some(presets) => phys.register_vehicle_presets(presets).map(Some).map_err(|e| format!("{}: {}", path, e)),
+        None => Ok(None),
+    });
+    match loaded {
+        Ok(Some(count)) => {
+            let names: Vec<&str> = phys.vehicle_presets.keys().map(String::as_str).collect();
+            println!("🚙 {} vehicle preset(s) from {} (spawnable: {})", count, path, names.join(", "));
+        }
+        Ok(None) => println!("🚙 No vehicle presets file ({}); built-in configs only", path),
+        Err(e) => eprintln!("❌ Vehicle presets not loaded, using built-in configs: {}", e),
+    }
+}
+
+/// Presets in `path`; Ok(None) when the file doesn't exist.
+pub fn load_file(path: &str) -> Result<Option<BTreeMap<String, VehicleConfig>>, String> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("{}: {}", path, e)),
+    };
+    parse_presets(&text).map(Some).map_err(|e| format!("{}: {}", path, e))
+}
+
+/// name → config (not yet checked against VehicleConfig::validate).
+pub fn parse_presets(text: &str) -> Result<BTreeMap<String, VehicleConfig>, String> {
+    let table: toml::Table = toml::from_str(text).map_err(|e| e.to_string())?;
+    let mut presets = BTreeMap::new();
+    for (name, entry) in table {
+        let toml::Value::Table(fields) = entry else {
+            return Err(format!("'{}' must be a table ([{}])", name, name));
+        };
+        let config = parse_preset(&fields).map_err(|e| format!("preset '{}': {}", name, e))?;
+        presets.insert(name, config);
+    }
+    Ok(presets)
+}
+
+fn parse_preset(fields: &toml::Table) -> Result<VehicleConfig, String> {
+    let base_name = match fields.get(BASE_KEY) {
+        None => DEFAULT_BASE,
+        Some(toml::Value::String(name)) => name.as_str(),
+        Some(_) => return Err(format!("'{}' must be a string", BASE_KEY)),
+    };
+    let base = vehicle_config_by_name(base_name)
+        .ok_or_else(|| format!("unknown base '{}' (gt86, arcade, tank)", base_name))?;
+    let toml::Value::Table(base_fields) = toml::Value::try_from(base).map_err(|e| e.to_string())? else {
+        return Err("base config did not serialize to a table".to_string());
+    };
+
+    // One field at a time, so an error names the field that caused it
+    let mut merged = base_fields.clone();
+    for (key, value) in fields.iter().filter(|(k, _)| *k != BASE_KEY) {
+        if !base_fields.contains_key(key) {
+            return Err(format!("unknown field '{}'", key));
+        }
+        let mut single = base_fields.clone();
+        single.insert(key.clone(), value.clone());
+        VehicleConfig::deserialize(toml::Value::Table(single)).map_err(|e| format!("field '{}': {}", key, e.message()))?;
+        merged.insert(key.clone(), value.clone());
+    }
+    VehicleConfig::deserialize(toml::Value::Table(merged)).map_err(|e| e.message().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics::DEFAULT_VEHICLE;
+
+    #[test]
+    fn a_partial_override_merges_onto_its_base() {
+        let presets = parse_presets("[heavy]\nbase = \"arcade\"\nmass = 2400.0\narb_rates = [1.0, 2.0, 0.0, 0.0]\n\n[light]\nmass = 900.0\n").unwrap();
+        let arcade = vehicle_config_by_name("arcade").unwrap();
+        let gt86 = vehicle_config_by_name("gt86").unwrap();
+
+        let heavy = presets["heavy"];
+        assert_eq!((heavy.mass, heavy.arb_rates), (2400.0, [1.0, 2.0, 0.0, 0.0]));
+        assert_eq!(heavy.kamm.cx, arcade.kamm.cx);
+        assert_eq!(heavy.tcs_enabled, arcade.tcs_enabled);
+        assert_eq!(heavy.drivetrain, arcade.drivetrain);
+        // No base = gt86
+        let light = presets["light"];
+        assert_eq!(light.mass, 900.0);
+        assert_eq!(light.arb_rates, gt86.arb_rates);
+        assert_eq!(light.flip_timeout_s, gt86.flip_timeout_s);
+    }
+
+    #[test]
+    fn errors_name_the_preset_and_field() {
+        let err = |text: &str| parse_presets(text).unwrap_err();
+        assert_eq!(err("[x]\nwingspan = 3.0\n"), "preset 'x': unknown field 'wingspan'");
+        assert_eq!(err("[x]\nbase = \"truck\"\n"), "preset 'x': unknown base 'truck' (gt86, arcade, tank)");
+        assert_eq!(err("[x]\nbase = 3\n"), "preset 'x': 'base' must be a string");
+        assert_eq!(err("x = 1\n"), "'x' must be a table ([x])");
+
+        let wrong_type = err("[x]\nmass = 1000.0\narb_rates = \"stiff\"\n");
+        assert!(wrong_type.starts_with("preset 'x': field 'arb_rates': invalid type"), "{}", wrong_type);
+        let wrong_type = err("[x]\ntcs_enabled = 1\n");
+        assert!(wrong_type.starts_with("preset 'x': field 'tcs_enabled': invalid type"), "{}", wrong_type);
+        assert!(parse_presets("[x\n").is_err(), "not TOML");
+    }
+
+    #[test]
+    fn a_missing_file_leaves_the_builtins_and_the_default_car() {
+        assert!(matches!(load_file("configs/no-such-vehicles.toml"), Ok(None)));
+
+        let mut phys = PhysicsWorld::new();
+        assert_eq!(phys.vehicle_presets.keys().collect::<Vec<_>>(), ["arcade", "gt86", "tank"]);
+        // A preset the file would have added spawns the default car
+        phys.spawn_vehicle_for_player("p".into(), [0.0, 1.0, 0.0], 0.0, "drift").unwrap();
+        assert_eq!(phys.vehicles["p"].config_name, DEFAULT_VEHICLE);
+        assert_eq!(phys.vehicles["p"].config.mass, vehicle_config_by_name(DEFAULT_VEHICLE).unwrap().mass);
+    }
+}